@@ -0,0 +1,306 @@
+use crate::backtest::slippage::{
+    CalibratedSlippage, LinearSlippage, MarketImpactSlippage, PercentageSlippage, SlippageModel,
+};
+use crate::errors::{PolymarketError, Result};
+use crate::models::OrderSide;
+use crate::storage::trade_log::TradeLogEntry;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+/// One live execution: the price we copied at versus the price we were filled at
+#[derive(Clone, Debug)]
+pub struct ExecutionSample {
+    pub market_id: String,
+    pub side: OrderSide,
+    pub size: Decimal,
+    pub quote_price: Decimal,
+    pub fill_price: Decimal,
+}
+
+impl ExecutionSample {
+    /// Build a sample from a trade log entry that recorded a real fill
+    pub fn from_log_entry(entry: &TradeLogEntry) -> Option<Self> {
+        let executed = entry.executed.as_ref()?;
+        if !entry.success
+            || executed.actual_price <= Decimal::ZERO
+            || executed.position.size <= Decimal::ZERO
+        {
+            return None;
+        }
+
+        Some(Self {
+            market_id: entry.trade.market_id.clone(),
+            side: entry.trade.side.clone(),
+            size: executed.position.size,
+            quote_price: entry.trade.price,
+            fill_price: executed.actual_price,
+        })
+    }
+
+    /// Price impact in the adverse direction (positive = worse than quoted)
+    fn impact(&self) -> f64 {
+        let impact = match self.side {
+            OrderSide::Buy => self.fill_price - self.quote_price,
+            OrderSide::Sell => self.quote_price - self.fill_price,
+        };
+        impact.to_f64().unwrap_or(0.0)
+    }
+}
+
+/// A fitted slippage model and how well it reproduces the recorded fills
+#[derive(Clone, Debug)]
+pub struct ModelFit {
+    pub model: Arc<dyn SlippageModel>,
+    /// Root-mean-square error between modelled and actual fill prices
+    pub rmse: Decimal,
+}
+
+/// Fitted slippage models, best fit first
+#[derive(Clone, Debug)]
+pub struct Calibration {
+    pub samples: usize,
+    pub fits: Vec<ModelFit>,
+}
+
+impl Calibration {
+    pub fn best(&self) -> Option<&ModelFit> {
+        self.fits.first()
+    }
+
+    /// Render the best fit the config can express as a section for
+    /// `slippage_model = "calibrated"`
+    pub fn to_toml(&self) -> Option<String> {
+        let (best, calibrated) = self
+            .fits
+            .iter()
+            .find_map(|fit| fit.model.calibrated().map(|calibrated| (fit, calibrated)))?;
+        let mut toml = format!("# Slippage calibrated from {} executions\n", self.samples);
+        for fit in &self.fits {
+            toml.push_str(&format!(
+                "# {} RMSE: {}\n",
+                fit.model.name(),
+                fit.rmse.round_dp(6)
+            ));
+        }
+        if let Some(first) = self.best().filter(|first| first.rmse < best.rmse) {
+            toml.push_str(&format!(
+                "# {} fits better but can't be written to the config\n",
+                first.model.name()
+            ));
+        }
+        toml.push_str("# Use with slippage_model = \"calibrated\" in [backtest]\n");
+        toml.push_str("[backtest.calibrated_slippage]\n");
+        toml.push_str(&match calibrated {
+            CalibratedSlippage::Linear(model) => format!(
+                "type = \"Linear\"\ndepth_coefficient = {}\n",
+                model.depth_coefficient.round_dp(2)
+            ),
+            CalibratedSlippage::Percentage(model) => {
+                format!("type = \"Percentage\"\nrate = {}\n", model.rate.round_dp(8))
+            }
+            CalibratedSlippage::MarketImpact(model) => format!(
+                "type = \"MarketImpact\"\nimpact_param = {}\n",
+                model.impact_param.round_dp(8)
+            ),
+        });
+        Some(toml)
+    }
+}
+
+/// Fit the Linear, Percentage and MarketImpact models by least squares
+pub fn calibrate(samples: &[ExecutionSample]) -> Result<Calibration> {
+    calibrate_with(samples, Vec::new())
+}
+
+/// Fit the built-in models and rank them together with `candidates`, models
+/// supplied ready-made such as square-root impact or per-market curves
+pub fn calibrate_with(
+    samples: &[ExecutionSample],
+    candidates: Vec<Arc<dyn SlippageModel>>,
+) -> Result<Calibration> {
+    if samples.is_empty() {
+        return Err(PolymarketError::SimulationError(
+            "No executed trades with fill prices to calibrate from".to_string(),
+        ));
+    }
+
+    let fitted: [Option<Arc<dyn SlippageModel>>; 3] = [
+        fit_linear(samples).map(|model| Arc::new(model) as _),
+        fit_percentage(samples).map(|model| Arc::new(model) as _),
+        fit_market_impact(samples).map(|model| Arc::new(model) as _),
+    ];
+    let mut fits: Vec<ModelFit> = fitted
+        .into_iter()
+        .flatten()
+        .chain(candidates)
+        .map(|model| ModelFit {
+            rmse: rmse(model.as_ref(), samples),
+            model,
+        })
+        .collect();
+    fits.sort_by_key(|fit| fit.rmse);
+
+    Ok(Calibration {
+        samples: samples.len(),
+        fits,
+    })
+}
+
+/// impact = size / depth_coefficient
+fn fit_linear(samples: &[ExecutionSample]) -> Option<LinearSlippage> {
+    let slope = fit_through_origin(samples, |s| s.size.to_f64().unwrap_or(0.0))?;
+    Some(LinearSlippage {
+        depth_coefficient: Decimal::from_f64_retain(1.0 / slope)?,
+    })
+}
+
+/// impact = quote_price * rate
+fn fit_percentage(samples: &[ExecutionSample]) -> Option<PercentageSlippage> {
+    let rate = fit_through_origin(samples, |s| s.quote_price.to_f64().unwrap_or(0.0))?;
+    Some(PercentageSlippage {
+        rate: Decimal::from_f64_retain(rate)?,
+    })
+}
+
+/// impact = impact_param * ln(size)
+fn fit_market_impact(samples: &[ExecutionSample]) -> Option<MarketImpactSlippage> {
+    let param = fit_through_origin(samples, |s| s.size.to_f64().unwrap_or(1.0).ln())?;
+    Some(MarketImpactSlippage {
+        impact_param: Decimal::from_f64_retain(param)?,
+    })
+}
+
+/// Least-squares slope of impact against `x` with no intercept; None unless positive
+fn fit_through_origin(
+    samples: &[ExecutionSample],
+    x: impl Fn(&ExecutionSample) -> f64,
+) -> Option<f64> {
+    let (xy, xx) = samples.iter().fold((0.0, 0.0), |(xy, xx), sample| {
+        let x = x(sample);
+        (xy + x * sample.impact(), xx + x * x)
+    });
+    let slope = xy / xx;
+    (slope.is_finite() && slope > 0.0).then_some(slope)
+}
+
+fn rmse(model: &dyn SlippageModel, samples: &[ExecutionSample]) -> Decimal {
+    let squared: f64 = samples
+        .iter()
+        .map(|sample| {
+            let modelled =
+                model.calculate_execution_price(sample.quote_price, sample.size, &sample.side);
+            (modelled - sample.fill_price)
+                .to_f64()
+                .unwrap_or(0.0)
+                .powi(2)
+        })
+        .sum();
+    Decimal::from_f64_retain((squared / samples.len() as f64).sqrt()).unwrap_or(Decimal::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn sample(side: OrderSide, size: Decimal, quote: Decimal, fill: Decimal) -> ExecutionSample {
+        ExecutionSample {
+            market_id: "m1".to_string(),
+            side,
+            size,
+            quote_price: quote,
+            fill_price: fill,
+        }
+    }
+
+    #[test]
+    fn test_recovers_linear_depth() {
+        // Fills generated by a linear model with depth 50_000
+        let samples = vec![
+            sample(OrderSide::Buy, dec!(500), dec!(0.5), dec!(0.51)),
+            sample(OrderSide::Buy, dec!(1000), dec!(0.4), dec!(0.42)),
+            sample(OrderSide::Sell, dec!(2000), dec!(0.6), dec!(0.56)),
+            sample(OrderSide::Sell, dec!(250), dec!(0.3), dec!(0.295)),
+        ];
+
+        let calibration = calibrate(&samples).unwrap();
+        let best = calibration.best().unwrap();
+        match best.model.calibrated() {
+            Some(CalibratedSlippage::Linear(model)) => {
+                assert_eq!(model.depth_coefficient.round(), dec!(50000));
+            }
+            other => panic!("expected linear fit, got {:?}", other),
+        }
+        assert!(best.rmse < dec!(0.000001));
+
+        let toml = calibration.to_toml().unwrap();
+        assert!(toml.contains("[backtest.calibrated_slippage]"));
+        assert!(toml.contains("depth_coefficient = 50000"));
+        let section: toml::Value = toml::from_str(&toml).unwrap();
+        let calibrated: CalibratedSlippage = section["backtest"]["calibrated_slippage"]
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(calibrated.model().name(), "Linear");
+    }
+
+    /// impact = coefficient * sqrt(size)
+    #[derive(Debug)]
+    struct SquareRootImpact {
+        coefficient: f64,
+    }
+
+    impl SlippageModel for SquareRootImpact {
+        fn name(&self) -> &str {
+            "SquareRoot"
+        }
+
+        fn calculate_execution_price(
+            &self,
+            quote_price: Decimal,
+            size: Decimal,
+            side: &OrderSide,
+        ) -> Decimal {
+            let impact =
+                Decimal::from_f64_retain(self.coefficient * size.to_f64().unwrap_or(0.0).sqrt())
+                    .unwrap_or_default();
+            match side {
+                OrderSide::Buy => quote_price + impact,
+                OrderSide::Sell => quote_price - impact,
+            }
+        }
+
+        fn scaled(&self, factor: Decimal) -> Arc<dyn SlippageModel> {
+            Arc::new(Self {
+                coefficient: self.coefficient * factor.to_f64().unwrap_or(1.0),
+            })
+        }
+    }
+
+    #[test]
+    fn test_custom_models_are_ranked_with_the_fits() {
+        // Fills with impact 0.001 * sqrt(size)
+        let samples = vec![
+            sample(OrderSide::Buy, dec!(100), dec!(0.5), dec!(0.51)),
+            sample(OrderSide::Buy, dec!(400), dec!(0.4), dec!(0.42)),
+            sample(OrderSide::Sell, dec!(2500), dec!(0.6), dec!(0.55)),
+            sample(OrderSide::Sell, dec!(900), dec!(0.3), dec!(0.27)),
+        ];
+        let custom = Arc::new(SquareRootImpact { coefficient: 0.001 });
+
+        let calibration = calibrate_with(&samples, vec![custom]).unwrap();
+        assert_eq!(calibration.fits.len(), 4);
+        assert_eq!(calibration.best().unwrap().model.name(), "SquareRoot");
+
+        // The config can only hold a built-in model, so the next best is written
+        let toml = calibration.to_toml().unwrap();
+        assert!(toml.contains("# SquareRoot fits better but can't be written to the config"));
+        assert!(toml.contains("type = \""));
+    }
+
+    #[test]
+    fn test_no_samples_is_an_error() {
+        assert!(calibrate(&[]).is_err());
+    }
+}
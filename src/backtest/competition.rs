@@ -0,0 +1,117 @@
+use crate::backtest::slippage::SlippageModel;
+use crate::config::{CompetitionConfig, TraderProfile};
+use crate::errors::Result;
+use crate::models::OrderSide;
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Other copy bots following the same traders
+///
+/// A popular trader's fill is followed by a burst of copies. The ones that
+/// fill before ours walk the book first, so we see a worse quote and less
+/// depth than the trader left behind. Their combined size is
+/// `copiers * copier_size_fraction * ahead_fraction` times the trader's.
+#[derive(Clone, Debug, Default)]
+pub struct Competition {
+    config: CompetitionConfig,
+    /// Per-trader overrides of `config.copiers`
+    copiers: HashMap<Address, u32>,
+}
+
+impl Competition {
+    pub fn new(config: CompetitionConfig, profiles: &[TraderProfile]) -> Result<Self> {
+        let mut copiers = HashMap::new();
+        for profile in profiles {
+            if let Some(count) = profile.competing_copiers {
+                copiers.insert(profile.get_address()?, count);
+            }
+        }
+        Ok(Self { config, copiers })
+    }
+
+    /// Size the competing copiers fill ahead of us after `trader` trades `size`
+    pub fn volume_ahead(&self, trader: Address, size: Decimal) -> Decimal {
+        let copiers = self
+            .copiers
+            .get(&trader)
+            .copied()
+            .unwrap_or(self.config.copiers);
+        Decimal::from(copiers)
+            * self.config.copier_size_fraction
+            * self.config.ahead_fraction
+            * size
+    }
+
+    /// How far the competitors ahead of us move the price against `side`
+    pub fn price_impact(
+        &self,
+        trader: Address,
+        size: Decimal,
+        price: Decimal,
+        side: &OrderSide,
+        slippage_model: &dyn SlippageModel,
+    ) -> Decimal {
+        let ahead = self.volume_ahead(trader, size);
+        if ahead <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        slippage_model.calculate_slippage(price, ahead, side)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::slippage::LinearSlippage;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_popular_traders_cost_more() {
+        let quiet = Address::from_low_u64_be(1);
+        let popular = Address::from_low_u64_be(2);
+        let profile = TraderProfile {
+            address: format!("{:?}", popular),
+            copy_ratio: dec!(1),
+            min_trade_size_usdc: None,
+            max_trade_size_usdc: None,
+            allowed_markets: None,
+            data_file: None,
+            competing_copiers: Some(20),
+            onboarding: None,
+            onboarding_days: None,
+            labels: Vec::new(),
+        };
+        let config = CompetitionConfig {
+            copiers: 2,
+            ..CompetitionConfig::default()
+        };
+        let competition = Competition::new(config, &[profile]).unwrap();
+
+        // 2 copiers at 10% of the trade, half of them ahead of us
+        assert_eq!(competition.volume_ahead(quiet, dec!(1000)), dec!(100));
+        assert_eq!(competition.volume_ahead(popular, dec!(1000)), dec!(1000));
+
+        let model = LinearSlippage {
+            depth_coefficient: dec!(10000),
+        };
+        assert_eq!(
+            competition.price_impact(quiet, dec!(1000), dec!(0.5), &OrderSide::Buy, &model),
+            dec!(0.01)
+        );
+        assert_eq!(
+            competition.price_impact(popular, dec!(1000), dec!(0.5), &OrderSide::Sell, &model),
+            dec!(0.1)
+        );
+        assert_eq!(
+            Competition::default().price_impact(
+                popular,
+                dec!(1000),
+                dec!(0.5),
+                &OrderSide::Buy,
+                &model
+            ),
+            Decimal::ZERO
+        );
+    }
+}
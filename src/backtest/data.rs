@@ -0,0 +1,539 @@
+use crate::errors::{PolymarketError, Result};
+use crate::models::{ExecutedTrade, HistoricalTrade, LiveComparison, OrderSide};
+use crate::storage::TradeLogEntry;
+use chrono::{DateTime, TimeZone, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A chronological stream of historical trades consumed by the backtest engine
+pub trait TradeSource {
+    /// Next trade in timestamp order, or None when the source is exhausted
+    fn next_trade(&mut self) -> Result<Option<HistoricalTrade>>;
+
+    /// Up to `max_len` next trades; an empty chunk means the source is exhausted
+    fn next_chunk(&mut self, max_len: usize) -> Result<Vec<HistoricalTrade>> {
+        let mut chunk = Vec::with_capacity(max_len);
+        while chunk.len() < max_len {
+            match self.next_trade()? {
+                Some(trade) => chunk.push(trade),
+                None => break,
+            }
+        }
+        Ok(chunk)
+    }
+}
+
+/// Trades already held in memory (e.g. generated mock data)
+pub struct MemorySource {
+    trades: std::vec::IntoIter<HistoricalTrade>,
+}
+
+impl MemorySource {
+    pub fn new(mut trades: Vec<HistoricalTrade>) -> Self {
+        trades.sort_by_key(|trade| trade.timestamp);
+        Self {
+            trades: trades.into_iter(),
+        }
+    }
+}
+
+impl TradeSource for MemorySource {
+    fn next_trade(&mut self) -> Result<Option<HistoricalTrade>> {
+        Ok(self.trades.next())
+    }
+}
+
+/// Cursor over a dataset shared between several engines (parameter sweeps)
+pub struct SharedSource {
+    trades: Arc<Vec<HistoricalTrade>>,
+    position: usize,
+}
+
+impl SharedSource {
+    /// `trades` must already be in chronological order
+    pub fn new(trades: Arc<Vec<HistoricalTrade>>) -> Self {
+        Self {
+            trades,
+            position: 0,
+        }
+    }
+}
+
+impl TradeSource for SharedSource {
+    fn next_trade(&mut self) -> Result<Option<HistoricalTrade>> {
+        let trade = self.trades.get(self.position).cloned();
+        self.position += 1;
+        Ok(trade)
+    }
+}
+
+/// Column positions resolved from a CSV header row
+struct CsvColumns {
+    timestamp: usize,
+    market: usize,
+    side: usize,
+    price: usize,
+    size: usize,
+    trader: Option<usize>,
+    liquidity: Option<usize>,
+}
+
+/// Streaming CSV reader that yields one trade per row.
+///
+/// The header row names the columns; `timestamp`, `market`, `side`, `price` and
+/// `size` are required, `liquidity` is optional. `size` and `liquidity` are in
+/// shares. `trader` may be omitted when a `default_trader` is given (per-trader
+/// datasets). Rows must be in chronological order so the file never has to be
+/// loaded whole.
+pub struct CsvTradeReader<R: BufRead> {
+    lines: Lines<R>,
+    columns: Option<CsvColumns>,
+    default_trader: Option<Address>,
+    line_no: usize,
+    last_timestamp: Option<DateTime<Utc>>,
+}
+
+impl CsvTradeReader<BufReader<File>> {
+    /// Open a CSV file for streaming
+    pub fn open(path: &str, default_trader: Option<Address>) -> Result<Self> {
+        let file = File::open(path).map_err(|e| {
+            PolymarketError::SimulationError(format!("Failed to read data file {}: {}", path, e))
+        })?;
+        Ok(Self::new(BufReader::new(file), default_trader))
+    }
+}
+
+impl<R: BufRead> CsvTradeReader<R> {
+    pub fn new(reader: R, default_trader: Option<Address>) -> Self {
+        Self {
+            lines: reader.lines(),
+            columns: None,
+            default_trader,
+            line_no: 0,
+            last_timestamp: None,
+        }
+    }
+
+    /// Next non-blank line, tracking the 1-based line number
+    fn next_line(&mut self) -> Result<Option<String>> {
+        for line in self.lines.by_ref() {
+            self.line_no += 1;
+            let line = line?;
+            if !line.trim().is_empty() {
+                return Ok(Some(line));
+            }
+        }
+        Ok(None)
+    }
+
+    fn parse_header(&self, line: &str) -> Result<CsvColumns> {
+        let header: Vec<String> = line.split(',').map(|h| h.trim().to_lowercase()).collect();
+        let column = |name: &str| header.iter().position(|h| h == name);
+        let required = |name: &str| {
+            column(name).ok_or_else(|| {
+                PolymarketError::ParseError(format!("CSV is missing required column '{}'", name))
+            })
+        };
+
+        let columns = CsvColumns {
+            timestamp: required("timestamp")?,
+            market: required("market")?,
+            side: required("side")?,
+            price: required("price")?,
+            size: required("size")?,
+            trader: column("trader"),
+            liquidity: column("liquidity"),
+        };
+        if columns.trader.is_none() && self.default_trader.is_none() {
+            return Err(PolymarketError::ParseError(
+                "CSV is missing required column 'trader'".to_string(),
+            ));
+        }
+
+        Ok(columns)
+    }
+
+    fn parse_row(&self, columns: &CsvColumns, line: &str) -> Result<HistoricalTrade> {
+        let line_no = self.line_no;
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let field = |col: usize| {
+            fields.get(col).copied().ok_or_else(|| {
+                PolymarketError::ParseError(format!("Line {}: missing column {}", line_no, col + 1))
+            })
+        };
+        let decimal = |col: usize| -> Result<Decimal> {
+            let value = field(col)?;
+            Decimal::from_str(value).map_err(|e| {
+                PolymarketError::ParseError(format!(
+                    "Line {}: invalid number '{}': {}",
+                    line_no, value, e
+                ))
+            })
+        };
+
+        let side = match field(columns.side)?.to_uppercase().as_str() {
+            "BUY" => OrderSide::Buy,
+            "SELL" => OrderSide::Sell,
+            other => {
+                return Err(PolymarketError::ParseError(format!(
+                    "Line {}: invalid side '{}'",
+                    line_no, other
+                )))
+            }
+        };
+
+        let trader = match columns.trader {
+            Some(col) if !field(col)?.is_empty() => {
+                field(col)?.parse::<Address>().map_err(|e| {
+                    PolymarketError::ParseError(format!(
+                        "Line {}: invalid trader address: {}",
+                        line_no, e
+                    ))
+                })?
+            }
+            _ => self.default_trader.ok_or_else(|| {
+                PolymarketError::ParseError(format!("Line {}: missing trader address", line_no))
+            })?,
+        };
+
+        let liquidity = match columns.liquidity {
+            Some(col) if fields.get(col).is_some_and(|v| !v.is_empty()) => Some(decimal(col)?),
+            _ => None,
+        };
+
+        Ok(HistoricalTrade {
+            market: field(columns.market)?.to_string(),
+            side,
+            price: decimal(columns.price)?,
+            size: decimal(columns.size)?,
+            timestamp: parse_timestamp(field(columns.timestamp)?).ok_or_else(|| {
+                PolymarketError::ParseError(format!("Line {}: invalid timestamp", line_no))
+            })?,
+            trader,
+            liquidity,
+        })
+    }
+}
+
+impl<R: BufRead> TradeSource for CsvTradeReader<R> {
+    fn next_trade(&mut self) -> Result<Option<HistoricalTrade>> {
+        if self.columns.is_none() {
+            let header = match self.next_line()? {
+                Some(line) => line,
+                None => return Ok(None),
+            };
+            self.columns = Some(self.parse_header(&header)?);
+        }
+
+        let line = match self.next_line()? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        let columns = self.columns.as_ref().expect("header parsed above");
+        let trade = self.parse_row(columns, &line)?;
+
+        if self
+            .last_timestamp
+            .is_some_and(|last| trade.timestamp < last)
+        {
+            return Err(PolymarketError::ParseError(format!(
+                "Line {}: trades must be in chronological order",
+                self.line_no
+            )));
+        }
+        self.last_timestamp = Some(trade.timestamp);
+
+        Ok(Some(trade))
+    }
+}
+
+/// Merges several chronological sources into one chronological stream,
+/// holding at most one pending trade per source in memory
+pub struct MergedSource {
+    sources: Vec<Box<dyn TradeSource + Send>>,
+    heads: Vec<Option<HistoricalTrade>>,
+    primed: bool,
+}
+
+impl MergedSource {
+    pub fn new(sources: Vec<Box<dyn TradeSource + Send>>) -> Self {
+        let heads = sources.iter().map(|_| None).collect();
+        Self {
+            sources,
+            heads,
+            primed: false,
+        }
+    }
+}
+
+impl TradeSource for MergedSource {
+    fn next_trade(&mut self) -> Result<Option<HistoricalTrade>> {
+        if !self.primed {
+            for (source, head) in self.sources.iter_mut().zip(self.heads.iter_mut()) {
+                *head = source.next_trade()?;
+            }
+            self.primed = true;
+        }
+
+        let next_idx = self
+            .heads
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, head)| head.as_ref().map(|trade| (idx, trade.timestamp)))
+            .min_by_key(|(_, timestamp)| *timestamp)
+            .map(|(idx, _)| idx);
+
+        match next_idx {
+            Some(idx) => {
+                let trade = self.heads[idx].take();
+                self.heads[idx] = self.sources[idx].next_trade()?;
+                Ok(trade)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// A live session read back from the trade log or database
+///
+/// The trades the bot detected become the backtest's input, and what it
+/// actually filled is kept as ground truth to compare the simulation with.
+#[derive(Clone, Debug, Default)]
+pub struct RecordedSession {
+    /// Detected trades, once each, oldest first
+    pub trades: Vec<HistoricalTrade>,
+    /// Live fills, with the detected trade's timestamp
+    fills: Vec<(DateTime<Utc>, ExecutedTrade)>,
+    /// Trades that were skipped or failed, by detected trade timestamp
+    not_copied: Vec<DateTime<Utc>>,
+}
+
+impl RecordedSession {
+    pub fn from_entries(entries: &[TradeLogEntry]) -> Self {
+        let mut session = Self::default();
+        let mut seen = HashSet::new();
+        for entry in entries {
+            let trade = &entry.trade;
+            if seen.insert(trade.id.clone()) {
+                session.trades.push(HistoricalTrade {
+                    market: trade.market_id.clone(),
+                    side: trade.side.clone(),
+                    price: trade.price,
+                    size: trade.size,
+                    timestamp: trade.timestamp,
+                    trader: trade.trader,
+                    liquidity: None,
+                });
+            }
+            match (&entry.executed, &entry.error) {
+                (Some(executed), _) => session.fills.push((trade.timestamp, executed.clone())),
+                (None, Some(_)) => session.not_copied.push(trade.timestamp),
+                (None, None) => {}
+            }
+        }
+        session.trades.sort_by_key(|trade| trade.timestamp);
+        session
+    }
+
+    /// Live results inside `[start, end]` next to the simulated executions
+    pub fn compare(
+        &self,
+        simulated: &[ExecutedTrade],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> LiveComparison {
+        let in_range = |at: &DateTime<Utc>| *at >= start && *at <= end;
+        let live: Vec<&ExecutedTrade> = self
+            .fills
+            .iter()
+            .filter(|(at, _)| in_range(at))
+            .map(|(_, fill)| fill)
+            .collect();
+        let (live_avg_slippage, live_avg_fill_ratio, live_volume_usdc) = summarize(&live);
+        let simulated: Vec<&ExecutedTrade> = simulated.iter().collect();
+        let (simulated_avg_slippage, simulated_avg_fill_ratio, simulated_volume_usdc) =
+            summarize(&simulated);
+
+        LiveComparison {
+            detected_trades: self
+                .trades
+                .iter()
+                .filter(|t| in_range(&t.timestamp))
+                .count(),
+            live_copies: live.len(),
+            live_not_copied: self.not_copied.iter().filter(|at| in_range(at)).count(),
+            live_avg_slippage,
+            live_avg_fill_ratio,
+            live_volume_usdc,
+            simulated_copies: simulated.len(),
+            simulated_avg_slippage,
+            simulated_avg_fill_ratio,
+            simulated_volume_usdc,
+        }
+    }
+}
+
+/// Average slippage, average fill ratio and total filled value of `fills`
+fn summarize(fills: &[&ExecutedTrade]) -> (Decimal, Decimal, Decimal) {
+    if fills.is_empty() {
+        return (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+    }
+    let count = Decimal::from(fills.len());
+    let slippage: Decimal = fills.iter().map(|fill| fill.slippage).sum();
+    let fill_ratio: Decimal = fills.iter().map(|fill| fill.fill_ratio).sum();
+    let volume: Decimal = fills
+        .iter()
+        .map(|fill| fill.actual_price * fill.position.size)
+        .sum();
+    (slippage / count, fill_ratio / count, volume)
+}
+
+/// Parse an RFC 3339 timestamp or unix seconds
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return Utc.timestamp_opt(secs, 0).single();
+    }
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn parse_csv_trades(
+        content: &str,
+        default_trader: Option<Address>,
+    ) -> Result<Vec<HistoricalTrade>> {
+        let mut reader = CsvTradeReader::new(content.as_bytes(), default_trader);
+        let mut trades = Vec::new();
+        while let Some(trade) = reader.next_trade()? {
+            trades.push(trade);
+        }
+        Ok(trades)
+    }
+
+    #[test]
+    fn test_parse_csv_trades() {
+        let csv = "timestamp,market,side,price,size,trader,liquidity\n\
+                   2024-06-01T12:00:00Z,market1,BUY,0.55,100,0x1111111111111111111111111111111111111111,500\n\
+                   1717250400,market2,sell,0.40,50,0x1111111111111111111111111111111111111111,\n";
+
+        let trades = parse_csv_trades(csv, None).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].market, "market1");
+        assert_eq!(trades[0].price, dec!(0.55));
+        assert_eq!(trades[0].liquidity, Some(dec!(500)));
+        assert_eq!(trades[1].side, OrderSide::Sell);
+        assert_eq!(trades[1].liquidity, None);
+        assert_eq!(trades[1].timestamp.timestamp(), 1717250400);
+    }
+
+    #[test]
+    fn test_parse_csv_default_trader() {
+        let trader: Address = "0x2222222222222222222222222222222222222222"
+            .parse()
+            .unwrap();
+        let csv = "timestamp,market,side,price,size\n1717250400,market1,BUY,0.5,10\n";
+
+        let trades = parse_csv_trades(csv, Some(trader)).unwrap();
+        assert_eq!(trades[0].trader, trader);
+
+        assert!(parse_csv_trades(csv, None).is_err());
+    }
+
+    #[test]
+    fn test_csv_rejects_out_of_order_rows() {
+        let trader: Address = "0x2222222222222222222222222222222222222222"
+            .parse()
+            .unwrap();
+        let csv = "timestamp,market,side,price,size\n200,m1,BUY,0.5,10\n100,m1,BUY,0.5,10\n";
+
+        assert!(parse_csv_trades(csv, Some(trader)).is_err());
+    }
+
+    #[test]
+    fn test_recorded_session_from_trade_log() {
+        use crate::models::{Position, Trade};
+
+        let at = |secs: i64| Utc.timestamp_opt(1_717_250_400 + secs, 0).unwrap();
+        let trade = |id: &str, secs: i64| {
+            Trade::sample()
+                .with_id(id)
+                .with_fill(dec!(0.5), dec!(20))
+                .with_timestamp(at(secs))
+        };
+        let fill = ExecutedTrade {
+            position: Position {
+                market_id: "m1".to_string(),
+                entry_price: dec!(0.51),
+                size: dec!(10),
+                side: OrderSide::Buy,
+                timestamp: at(5),
+                pnl: Decimal::ZERO,
+                trader: None,
+            },
+            actual_price: dec!(0.51),
+            slippage: dec!(0.01),
+            fee: Decimal::ZERO,
+            fill_ratio: dec!(0.5),
+        };
+        let entries = vec![
+            TradeLogEntry::detected(&trade("b", 60)),
+            TradeLogEntry::detected(&trade("a", 0)),
+            TradeLogEntry::executed(&trade("a", 0), &fill),
+            TradeLogEntry::failed(&trade("b", 60), "Skipped: market is closed"),
+        ];
+
+        let session = RecordedSession::from_entries(&entries);
+        assert_eq!(session.trades.len(), 2);
+        assert_eq!(session.trades[0].timestamp, at(0));
+
+        let simulated = ExecutedTrade {
+            slippage: dec!(0.004),
+            fill_ratio: Decimal::ONE,
+            ..fill.clone()
+        };
+        let comparison = session.compare(&[simulated], at(0), at(3600));
+        assert_eq!(comparison.detected_trades, 2);
+        assert_eq!(comparison.live_copies, 1);
+        assert_eq!(comparison.live_not_copied, 1);
+        assert_eq!(comparison.live_avg_slippage, dec!(0.01));
+        assert_eq!(comparison.live_volume_usdc, dec!(5.1));
+        assert_eq!(comparison.simulated_avg_slippage, dec!(0.004));
+        assert_eq!(comparison.simulated_avg_fill_ratio, Decimal::ONE);
+    }
+
+    #[test]
+    fn test_merged_source_is_chronological() {
+        let a: Address = "0x1111111111111111111111111111111111111111"
+            .parse()
+            .unwrap();
+        let b: Address = "0x2222222222222222222222222222222222222222"
+            .parse()
+            .unwrap();
+        let csv_a = "timestamp,market,side,price,size\n100,m1,BUY,0.5,1\n300,m1,BUY,0.5,1\n";
+        let csv_b = "timestamp,market,side,price,size\n200,m2,BUY,0.5,1\n400,m2,BUY,0.5,1\n";
+
+        let mut merged = MergedSource::new(vec![
+            Box::new(CsvTradeReader::new(csv_a.as_bytes(), Some(a))),
+            Box::new(CsvTradeReader::new(csv_b.as_bytes(), Some(b))),
+        ]);
+
+        let first = merged.next_chunk(3).unwrap();
+        let timestamps: Vec<i64> = first.iter().map(|t| t.timestamp.timestamp()).collect();
+        assert_eq!(timestamps, vec![100, 200, 300]);
+        assert_eq!(first[1].trader, b);
+
+        let rest = merged.next_chunk(3).unwrap();
+        assert_eq!(rest.len(), 1);
+        assert!(merged.next_chunk(3).unwrap().is_empty());
+    }
+}
@@ -1,23 +1,77 @@
+use crate::backtest::competition::Competition;
+use crate::backtest::data::{
+    CsvTradeReader, MemorySource, MergedSource, RecordedSession, SharedSource, TradeSource,
+};
+use crate::backtest::market_slippage::MarketSlippage;
 use crate::backtest::metrics::PerformanceMetrics;
+use crate::backtest::scenario::ScenarioSet;
 use crate::backtest::simulator::TradeSimulator;
-use crate::backtest::slippage::SlippageModel;
-use crate::config::{BacktestConfig, PositionSizingConfig};
+use crate::backtest::slippage::{LinearSlippage, PercentageSlippage, SlippageModel};
+use crate::config::{BacktestConfig, PositionSizingConfig, TraderProfile};
 use crate::errors::{PolymarketError, Result};
 use crate::execution::PositionSizer;
-use crate::models::{BacktestResults, HistoricalTrade};
+use crate::models::{BacktestResults, HistoricalTrade, OrderSide};
+use crate::monitoring::detector::TradeFilter;
+use crate::monitoring::volatility::PriceHistory;
+use crate::monitoring::{MarketDirectory, Resolutions};
 use chrono::{DateTime, NaiveDate, Utc};
+use ethers::types::Address;
 use rust_decimal::Decimal;
 use std::collections::HashMap;
-use tracing::info;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Copy weight and filter for one profiled trader
+struct TraderWeighting {
+    copy_ratio: Decimal,
+    filter: TradeFilter,
+}
+
+/// Unfilled remainder of a partial fill waiting for fresh liquidity
+struct RolledOrder {
+    side: OrderSide,
+    size: Decimal,
+}
 
 pub struct BacktestEngine {
     config: BacktestConfig,
     position_sizing_config: PositionSizingConfig,
-    market_data: Vec<HistoricalTrade>,
     simulator: TradeSimulator,
     position_sizer: PositionSizer,
     metrics: PerformanceMetrics,
-    slippage_model: SlippageModel,
+    slippage_model: Arc<dyn SlippageModel>,
+    /// Unfilled remainders by source trader and market
+    rolled_orders: HashMap<(Option<Address>, String), RolledOrder>,
+    trader_profiles: Vec<TraderProfile>,
+    trader_weightings: HashMap<Address, TraderWeighting>,
+    /// Each source trader's net holdings per market, rebuilt from the trade stream
+    trader_holdings: HashMap<(Address, String), Decimal>,
+    /// Preloaded dataset used instead of the configured data source
+    shared_data: Option<Arc<Vec<HistoricalTrade>>>,
+    /// Stress-test shocks and what happened inside their windows
+    scenarios: ScenarioSet,
+    /// Models replacing `slippage_model` for particular markets
+    market_slippage: MarketSlippage,
+    /// Latest recorded liquidity per market, for picking a liquidity bucket
+    market_liquidity: HashMap<String, Decimal>,
+    /// How much a slippage spike scales slippage while it is active
+    slippage_factor: Decimal,
+    /// Latest observed price per market, for marking open positions to market
+    mark_prices: HashMap<String, Decimal>,
+    /// Recorded market outcomes, for settling positions open at the end
+    resolutions: Option<Arc<Resolutions>>,
+    /// Gamma lookups for outcomes that aren't recorded
+    markets: Option<Arc<MarketDirectory>>,
+    /// Copies skipped because sizing or balance limits rejected them
+    rejected_copies: usize,
+    /// Live session replayed when data_source = "trade_log"
+    recorded: Option<RecordedSession>,
+    /// Other copy bots following the same traders
+    competition: Competition,
+    /// How far competing copiers moved the price of the trade being copied
+    contention: Decimal,
+    /// Trailing trade prices per market, for volatility sizing
+    price_histories: HashMap<String, PriceHistory>,
 }
 
 impl BacktestEngine {
@@ -28,108 +82,538 @@ impl BacktestEngine {
             0
         };
 
-        let slippage_model = match config.slippage_model.as_str() {
-            "linear" => SlippageModel::Linear {
+        let gas_per_trade = if config.apply_gas_costs {
+            config.estimated_gas_per_trade_usd
+        } else {
+            Decimal::ZERO
+        };
+
+        let slippage_model: Arc<dyn SlippageModel> = match config.slippage_model.as_str() {
+            "linear" => Arc::new(LinearSlippage {
                 depth_coefficient: config.depth_coefficient,
-            },
-            "percentage" => SlippageModel::Percentage {
+            }),
+            "percentage" => Arc::new(PercentageSlippage {
                 rate: config.slippage_percentage,
+            }),
+            "calibrated" => match config.calibrated_slippage {
+                Some(ref calibrated) => calibrated.model(),
+                None => Arc::new(LinearSlippage::default()),
             },
-            _ => SlippageModel::default(),
+            _ => Arc::new(LinearSlippage::default()),
         };
 
         Self {
-            simulator: TradeSimulator::new(config.initial_balance_usdc, fee_rate_bps),
+            simulator: TradeSimulator::new(config.initial_balance_usdc, fee_rate_bps)
+                .with_gas_cost(gas_per_trade),
             position_sizer: PositionSizer::new(position_sizing_config.clone()),
             metrics: PerformanceMetrics::new(config.initial_balance_usdc),
-            market_data: Vec::new(),
             slippage_model,
+            rolled_orders: HashMap::new(),
+            trader_profiles: Vec::new(),
+            trader_weightings: HashMap::new(),
+            trader_holdings: HashMap::new(),
+            shared_data: None,
+            scenarios: ScenarioSet::default(),
+            market_slippage: MarketSlippage::default(),
+            market_liquidity: HashMap::new(),
+            slippage_factor: Decimal::ONE,
+            mark_prices: HashMap::new(),
+            resolutions: None,
+            markets: None,
+            rejected_copies: 0,
+            recorded: None,
+            competition: Competition::default(),
+            contention: Decimal::ZERO,
+            price_histories: HashMap::new(),
             config,
             position_sizing_config,
         }
     }
 
+    /// Copy only the given traders, each with its own weight, filters and dataset
+    pub fn with_trader_profiles(mut self, profiles: Vec<TraderProfile>) -> Self {
+        self.trader_profiles = profiles;
+        self
+    }
+
+    /// Replay a preloaded chronological dataset instead of reading the data source
+    pub fn with_shared_data(mut self, data: Arc<Vec<HistoricalTrade>>) -> Self {
+        self.shared_data = Some(data);
+        self
+    }
+
+    /// Live session read from the trade log, replayed by data_source = "trade_log"
+    pub fn with_recorded_session(mut self, session: RecordedSession) -> Self {
+        self.recorded = Some(session);
+        self
+    }
+
+    /// Outcomes recorded by the live bot, for `end_of_backtest = "resolution"`
+    pub fn with_resolutions(mut self, resolutions: Arc<Resolutions>) -> Self {
+        self.resolutions = Some(resolutions);
+        self
+    }
+
+    /// Look up outcomes that aren't recorded on Gamma, for
+    /// `end_of_backtest = "resolution"`
+    pub fn with_markets(mut self, markets: Arc<MarketDirectory>) -> Self {
+        self.markets = Some(markets);
+        self
+    }
+
+    /// Read the configured data source and per-trader datasets into memory,
+    /// restricted to the backtest date range, for sharing across engines
+    pub fn load_dataset(&mut self) -> Result<Vec<HistoricalTrade>> {
+        let mut source = self.open_data_source()?;
+        let (start_datetime, end_datetime) = self.date_range()?;
+        let mut trades = Vec::new();
+
+        while let Some(trade) = source.next_trade()? {
+            if trade.timestamp > end_datetime {
+                break;
+            }
+            if trade.timestamp >= start_datetime {
+                trades.push(trade);
+            }
+        }
+
+        Ok(trades)
+    }
+
     /// Run the backtest simulation
     pub async fn run(&mut self) -> Result<BacktestResults> {
         info!("Starting backtest simulation...");
 
-        // Load historical data
-        self.load_historical_data().await?;
-
-        info!("Loaded {} historical trades", self.market_data.len());
+        let mut source = self.open_data_source()?;
+        let (start_datetime, end_datetime) = self.date_range()?;
+        self.scenarios = ScenarioSet::from_config(&self.config.scenarios)?;
+        if let Some(ref path) = self.config.market_slippage_file {
+            self.market_slippage = MarketSlippage::load(path)?;
+        }
+        let mut processed = 0usize;
+        let mut last_timestamp = None;
 
-        // Process each historical trade
-        for (idx, historical_trade) in self.market_data.clone().iter().enumerate() {
-            if (idx + 1) % 100 == 0 {
-                info!("Processed {}/{} trades", idx + 1, self.market_data.len());
+        // Stream chronological chunks so memory stays bounded on large datasets
+        'chunks: loop {
+            let chunk = source.next_chunk(self.config.chunk_size)?;
+            if chunk.is_empty() {
+                break;
             }
 
-            // Calculate position size for this trade
-            let current_balance = self.simulator.balance();
-            let my_size = match self
-                .position_sizer
-                .calculate_position_size(historical_trade.size, current_balance)
-            {
-                Ok(size) => size,
-                Err(_) => continue, // Skip if position sizing fails
-            };
+            for historical_trade in &chunk {
+                if historical_trade.timestamp < start_datetime {
+                    continue;
+                }
+                if historical_trade.timestamp > end_datetime {
+                    break 'chunks;
+                }
 
-            // Simulate execution
-            match self.simulator.simulate_execution(
-                &historical_trade.market,
-                historical_trade.side.clone(),
-                my_size,
-                historical_trade.price,
-                &self.slippage_model,
-            ) {
-                Ok(executed_trade) => {
-                    self.metrics.record_trade(executed_trade);
+                if self.scenarios.is_empty() {
+                    self.process_trade(historical_trade)?;
+                } else {
+                    self.process_scenario_trade(historical_trade)?;
                 }
-                Err(PolymarketError::InsufficientBalance) => {
-                    // Skip trades we can't afford
-                    continue;
+                last_timestamp = Some(historical_trade.timestamp);
+
+                processed += 1;
+                if processed.is_multiple_of(100) {
+                    info!("Processed {} trades", processed);
                 }
-                Err(e) => return Err(e),
             }
         }
 
-        // Close all positions at end of backtest
-        info!("Closing all positions...");
-        let market_prices = self.get_final_market_prices();
-        let closed_positions = self.simulator.close_all_positions(&market_prices)?;
+        info!("Processed {} historical trades", processed);
 
-        for closed in closed_positions {
-            self.metrics.record_closed_position(closed);
+        if let Some(last_timestamp) = last_timestamp {
+            self.simulator.set_time(last_timestamp);
         }
+        self.settle_open_positions().await?;
+        let (fees, gas) = self.simulator.costs_paid();
+        self.metrics.record_costs(fees, gas);
 
         // Generate and return results
-        let results = self.metrics.generate_report();
+        let mut results = self.metrics.generate_report();
+        results.scenarios = self.scenarios.reports();
+        results.unrolled_remainders = self.rolled_orders.len();
+        if let Some(ref session) = self.recorded {
+            results.live_comparison =
+                Some(session.compare(self.metrics.executions(), start_datetime, end_datetime));
+        }
         info!("Backtest complete!");
 
         Ok(results)
     }
 
-    /// Load historical trade data
-    async fn load_historical_data(&mut self) -> Result<()> {
-        match self.config.data_source.as_str() {
-            "polymarket_api" => {
-                // Mock implementation - in production, fetch from API
-                info!("Loading data from Polymarket API (mock)...");
-                self.market_data = self.generate_mock_data()?;
+    /// Apply active stress-test shocks around processing one historical trade
+    fn process_scenario_trade(&mut self, historical_trade: &HistoricalTrade) -> Result<()> {
+        let timestamp = historical_trade.timestamp;
+        let equity = self.simulator.total_value(&self.mark_prices);
+        self.scenarios
+            .observe(timestamp, equity, self.rejected_copies);
+
+        let (shocked, slippage_factor) = self.scenarios.apply(historical_trade);
+        self.slippage_factor = slippage_factor;
+
+        if let Some(shocked) = shocked {
+            self.process_trade(&shocked)?;
+        }
+
+        let equity = self.simulator.total_value(&self.mark_prices);
+        self.scenarios
+            .observe(timestamp, equity, self.rejected_copies);
+        Ok(())
+    }
+
+    /// Slippage model for `market_id`: its own or its liquidity bucket's from
+    /// the market slippage file, else the configured one, stressed by any
+    /// active slippage spike
+    fn slippage_model(&self, market_id: &str) -> Arc<dyn SlippageModel> {
+        let liquidity = self.market_liquidity.get(market_id).copied();
+        let model = self
+            .market_slippage
+            .model(market_id, liquidity)
+            .unwrap_or(&self.slippage_model);
+        if self.slippage_factor == Decimal::ONE {
+            model.clone()
+        } else {
+            model.scaled(self.slippage_factor)
+        }
+    }
+
+    /// Copy (or mirror the exit of) a single historical trade
+    fn process_trade(&mut self, historical_trade: &HistoricalTrade) -> Result<()> {
+        self.simulator.set_time(historical_trade.timestamp);
+        self.mark_prices
+            .insert(historical_trade.market.clone(), historical_trade.price);
+        let volatility = &self.position_sizing_config.volatility;
+        if volatility.enabled {
+            let window = std::time::Duration::from_secs(volatility.window_minutes * 60);
+            self.price_histories
+                .entry(historical_trade.market.clone())
+                .or_insert_with(|| PriceHistory::new(window))
+                .record(historical_trade.timestamp, historical_trade.price);
+        }
+        if let Some(liquidity) = historical_trade.liquidity.or(self.config.default_liquidity) {
+            self.simulator
+                .set_liquidity(&historical_trade.market, liquidity);
+            self.market_liquidity
+                .insert(historical_trade.market.clone(), liquidity);
+        }
+
+        // Competing copiers fill first, leaving a worse price and less depth
+        let slippage_model = self.slippage_model(&historical_trade.market);
+        self.contention = self.competition.price_impact(
+            historical_trade.trader,
+            historical_trade.size,
+            historical_trade.price,
+            &historical_trade.side,
+            slippage_model.as_ref(),
+        );
+        self.simulator.consume_liquidity(
+            &historical_trade.market,
+            self.competition
+                .volume_ahead(historical_trade.trader, historical_trade.size),
+        );
+
+        // Mirror the source trader's exits instead of opening new positions
+        if self.config.mirror_exits {
+            if historical_trade.side == OrderSide::Sell {
+                self.mirror_exit(historical_trade);
+                return Ok(());
             }
-            "csv_file" => {
-                info!("Loading data from CSV file: {}", self.config.data_file);
-                self.market_data = self.load_from_csv(&self.config.data_file)?;
+            let holding_key = (historical_trade.trader, historical_trade.market.clone());
+            *self.trader_holdings.entry(holding_key).or_default() += historical_trade.size;
+        }
+
+        // Apply per-trader filters and weights when copying a trader portfolio
+        let trade_usdc = historical_trade.size * historical_trade.price;
+        let target_size = if self.trader_weightings.is_empty() {
+            trade_usdc
+        } else {
+            match self.trader_weightings.get(&historical_trade.trader) {
+                Some(weighting) if weighting.filter.should_copy_historical(historical_trade) => {
+                    trade_usdc * weighting.copy_ratio
+                }
+                _ => return Ok(()),
             }
+        };
+
+        // Shrink copies into markets whose price has been swinging
+        let target_size =
+            match self
+                .price_histories
+                .get(&historical_trade.market)
+                .and_then(|history| {
+                    history.volatility(self.position_sizing_config.volatility.min_samples)
+                }) {
+                Some(volatility) => {
+                    target_size
+                        * self
+                            .position_sizer
+                            .volatility_multiplier(historical_trade.price, volatility)
+                }
+                None => target_size,
+            };
+
+        // Retry any remainders rolled over from earlier partial fills in this market
+        let mut rolled_keys: Vec<_> = self
+            .rolled_orders
+            .keys()
+            .filter(|(_, market)| *market == historical_trade.market)
+            .cloned()
+            .collect();
+        rolled_keys.sort();
+        for key in rolled_keys {
+            if let Some(rolled) = self.rolled_orders.remove(&key) {
+                self.execute_copy(
+                    &historical_trade.market,
+                    key.0,
+                    rolled.side,
+                    rolled.size,
+                    historical_trade.price,
+                )?;
+            }
+        }
+
+        // Calculate position size for this trade
+        let current_balance = self.simulator.balance();
+        let my_size = match self
+            .position_sizer
+            .calculate_position_size(target_size, current_balance)
+        {
+            Ok(size) if historical_trade.price > Decimal::ZERO => size,
             _ => {
-                return Err(PolymarketError::ConfigError(format!(
-                    "Unknown data source: {}",
-                    self.config.data_source
-                )));
+                // Skip if position sizing fails
+                self.rejected_copies += 1;
+                return Ok(());
+            }
+        };
+        // Sizing is in USDC; the simulator fills shares
+        let my_size = my_size / historical_trade.price;
+
+        // Simulate execution
+        self.execute_copy(
+            &historical_trade.market,
+            Some(historical_trade.trader),
+            historical_trade.side.clone(),
+            my_size,
+            historical_trade.price,
+        )
+    }
+
+    /// Reduce our copies of a trader's position by the fraction of it they sold
+    fn mirror_exit(&mut self, historical_trade: &HistoricalTrade) {
+        let holding_key = (historical_trade.trader, historical_trade.market.clone());
+        let holding = self
+            .trader_holdings
+            .get(&holding_key)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+        if holding <= Decimal::ZERO {
+            // Nothing we know of to exit
+            return;
+        }
+
+        let fraction = std::cmp::min(historical_trade.size / holding, Decimal::ONE);
+        let remaining = holding - std::cmp::min(historical_trade.size, holding);
+        if remaining > Decimal::ZERO {
+            self.trader_holdings.insert(holding_key, remaining);
+        } else {
+            self.trader_holdings.remove(&holding_key);
+        }
+
+        // A full exit also cancels any unfilled entry still waiting to roll
+        if fraction == Decimal::ONE {
+            self.rolled_orders.remove(&(
+                Some(historical_trade.trader),
+                historical_trade.market.clone(),
+            ));
+        }
+
+        let exit_size: Decimal = self
+            .simulator
+            .positions()
+            .iter()
+            .filter(|p| {
+                p.market_id == historical_trade.market && p.trader == Some(historical_trade.trader)
+            })
+            .map(|p| p.size * fraction)
+            .sum();
+        if exit_size <= Decimal::ZERO {
+            return;
+        }
+
+        let exit_price = self
+            .slippage_model(&historical_trade.market)
+            .calculate_execution_price(
+                historical_trade.price - self.contention,
+                exit_size,
+                &OrderSide::Sell,
+            );
+        let exit_slippage = historical_trade.price - exit_price;
+        let closed = self.simulator.reduce_positions(
+            &historical_trade.market,
+            Some(historical_trade.trader),
+            fraction,
+            exit_price,
+        );
+        for position in closed {
+            // Measured from the trader's price, like entries
+            self.metrics
+                .record_exit_slippage(exit_slippage * position.position.size);
+            self.metrics.record_closed_position(position);
+        }
+    }
+
+    /// Simulate one copy order, applying the unfilled-remainder policy to partial fills
+    fn execute_copy(
+        &mut self,
+        market_id: &str,
+        trader: Option<Address>,
+        side: OrderSide,
+        size: Decimal,
+        quote_price: Decimal,
+    ) -> Result<()> {
+        let contended_price = match side {
+            OrderSide::Buy => quote_price + self.contention,
+            OrderSide::Sell => quote_price - self.contention,
+        };
+        let filled_size = match self.simulator.simulate_execution(
+            market_id,
+            trader,
+            side.clone(),
+            size,
+            contended_price,
+            self.slippage_model(market_id).as_ref(),
+        ) {
+            Ok(mut executed_trade) => {
+                // Measured from the trader's price, competition included
+                executed_trade.slippage += self.contention;
+                let filled_size = executed_trade.position.size;
+                self.metrics.record_trade(executed_trade);
+                filled_size
+            }
+            Err(PolymarketError::InsufficientBalance) => {
+                // Skip trades we can't afford
+                self.rejected_copies += 1;
+                return Ok(());
+            }
+            Err(PolymarketError::InsufficientLiquidity(_)) => Decimal::ZERO,
+            Err(e) => return Err(e),
+        };
+
+        let remainder = size - filled_size;
+        if remainder > Decimal::ZERO {
+            if self.config.unfilled_policy == "roll" {
+                debug!(
+                    "Rolling {} unfilled in {} to next trade",
+                    remainder, market_id
+                );
+                // Opposite remainders for the same trader net against each other
+                let rolled = self
+                    .rolled_orders
+                    .entry((trader, market_id.to_string()))
+                    .or_insert(RolledOrder {
+                        side: side.clone(),
+                        size: Decimal::ZERO,
+                    });
+                if rolled.side == side {
+                    rolled.size += remainder;
+                } else if rolled.size > remainder {
+                    rolled.size -= remainder;
+                } else {
+                    rolled.side = side;
+                    rolled.size = remainder - rolled.size;
+                }
+                if rolled.size <= Decimal::ZERO {
+                    self.rolled_orders.remove(&(trader, market_id.to_string()));
+                }
+            } else {
+                debug!("Dropping {} unfilled in {}", remainder, market_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Open the configured data source plus per-trader datasets as one
+    /// chronological stream, and prepare per-trader weights and filters
+    fn open_data_source(&mut self) -> Result<Box<dyn TradeSource + Send>> {
+        let mut sources: Vec<Box<dyn TradeSource + Send>> = Vec::new();
+
+        if let Some(ref data) = self.shared_data {
+            // Preloaded data already includes the per-trader datasets
+            sources.push(Box::new(SharedSource::new(Arc::clone(data))));
+        } else {
+            match self.config.data_source.as_str() {
+                "polymarket_api" => {
+                    // Mock implementation - in production, fetch from API
+                    info!("Loading data from Polymarket API (mock)...");
+                    sources.push(Box::new(MemorySource::new(self.generate_mock_data()?)));
+                }
+                "trade_log" => {
+                    let session = self.recorded.as_ref().ok_or_else(|| {
+                        PolymarketError::ConfigError(
+                            "data_source = \"trade_log\" needs the recorded session loaded"
+                                .to_string(),
+                        )
+                    })?;
+                    info!(
+                        "Replaying {} trades detected during live operation",
+                        session.trades.len()
+                    );
+                    sources.push(Box::new(MemorySource::new(session.trades.clone())));
+                }
+                "csv_file" => {
+                    info!("Streaming data from CSV file: {}", self.config.data_file);
+                    sources.push(Box::new(CsvTradeReader::open(
+                        &self.config.data_file,
+                        None,
+                    )?));
+                }
+                _ => {
+                    return Err(PolymarketError::ConfigError(format!(
+                        "Unknown data source: {}",
+                        self.config.data_source
+                    )));
+                }
             }
         }
 
-        // Filter by date range
+        // Per-trader weights, filters and datasets
+        self.competition =
+            Competition::new(self.config.competition.clone(), &self.trader_profiles)?;
+        self.trader_weightings.clear();
+        for profile in self.trader_profiles.clone() {
+            let address = profile.get_address()?;
+
+            if let (Some(data_file), None) = (&profile.data_file, &self.shared_data) {
+                info!("Streaming data for trader {:?} from {}", address, data_file);
+                sources.push(Box::new(CsvTradeReader::open(data_file, Some(address))?));
+            }
+
+            let mut filter = TradeFilter::new(
+                profile.min_trade_size_usdc.unwrap_or(Decimal::ZERO),
+                profile.max_trade_size_usdc.unwrap_or(Decimal::MAX),
+            );
+            if let Some(markets) = profile.allowed_markets {
+                filter = filter.with_allowed_markets(markets.into_iter().collect());
+            }
+
+            self.trader_weightings.insert(
+                address,
+                TraderWeighting {
+                    copy_ratio: profile.copy_ratio,
+                    filter,
+                },
+            );
+        }
+
+        Ok(Box::new(MergedSource::new(sources)))
+    }
+
+    /// Parse the configured start/end dates into an inclusive UTC range
+    fn date_range(&self) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
         let start_date = NaiveDate::parse_from_str(&self.config.start_date, "%Y-%m-%d")
             .map_err(|e| PolymarketError::ParseError(format!("Invalid start date: {}", e)))?;
         let end_date = NaiveDate::parse_from_str(&self.config.end_date, "%Y-%m-%d")
@@ -146,28 +630,18 @@ impl BacktestEngine {
             .and_local_timezone(Utc)
             .unwrap();
 
-        self.market_data.retain(|trade| {
-            trade.timestamp >= start_datetime && trade.timestamp <= end_datetime
-        });
-
-        Ok(())
-    }
-
-    /// Load data from CSV file
-    fn load_from_csv(&self, _path: &str) -> Result<Vec<HistoricalTrade>> {
-        // Mock implementation - would use csv crate in production
-        Ok(Vec::new())
+        Ok((start_datetime, end_datetime))
     }
 
     /// Generate mock historical data for testing
     fn generate_mock_data(&self) -> Result<Vec<HistoricalTrade>> {
-        use crate::models::OrderSide;
         use rust_decimal_macros::dec;
 
         let mut trades = Vec::new();
         let trader = "0x0000000000000000000000000000000000000000"
             .parse()
             .unwrap();
+        let (start_datetime, _) = self.date_range()?;
 
         // Generate 50 sample trades
         for i in 0..50 {
@@ -184,38 +658,84 @@ impl BacktestEngine {
                 side,
                 price,
                 size,
-                timestamp: Utc::now(),
+                timestamp: start_datetime + chrono::Duration::hours(i),
                 trader,
+                liquidity: None,
             });
         }
 
         Ok(trades)
     }
 
-    /// Get final market prices for position closing
-    fn get_final_market_prices(&self) -> HashMap<String, Decimal> {
-        let mut prices = HashMap::new();
+    /// Deal with the positions still open at the end as `end_of_backtest` says
+    ///
+    /// Each is closed at the last price traded in its market, or at its
+    /// resolution payout where known; or left open and marked at that last
+    /// price, counted in the results (`mark`) or reported apart (`unrealized`).
+    async fn settle_open_positions(&mut self) -> Result<()> {
+        let policy = self.config.end_of_backtest.clone();
+        let mut prices = self.mark_prices.clone();
+        if policy == "resolution" {
+            let markets: Vec<String> = self
+                .simulator
+                .positions()
+                .iter()
+                .map(|p| p.market_id.clone())
+                .collect();
+            for market in markets {
+                if let Some(payout) = self.payout(&market).await {
+                    prices.insert(market, payout);
+                }
+            }
+        }
 
-        // In production, would fetch current market prices
-        // For now, use average entry prices
-        for trade in &self.market_data {
-            prices
-                .entry(trade.market.clone())
-                .or_insert(trade.price);
+        match policy.as_str() {
+            "mark" | "unrealized" => {
+                info!(
+                    "Leaving {} positions open, marked at their last prices",
+                    self.simulator.positions().len()
+                );
+                for position in self.simulator.positions() {
+                    let price = prices
+                        .get(&position.market_id)
+                        .copied()
+                        .unwrap_or(position.entry_price);
+                    self.metrics.record_open_position(position.clone(), price);
+                }
+                self.metrics.count_unrealized(policy == "mark");
+            }
+            _ => {
+                info!("Closing all positions...");
+                for closed in self.simulator.close_all_positions(&prices)? {
+                    self.metrics.record_closed_position(closed);
+                }
+            }
         }
+        Ok(())
+    }
 
-        prices
+    /// What a share of `market` paid out, if it has resolved
+    async fn payout(&self, market: &str) -> Option<Decimal> {
+        if let Some(payout) = self.resolutions.as_ref().and_then(|r| r.payout(market)) {
+            return Some(payout);
+        }
+        let info = self.markets.as_ref()?.market(market).await.ok()?;
+        let payouts = info.payouts()?;
+        payouts
+            .into_iter()
+            .find(|(token, _)| token.token_id == market)
+            .map(|(_, payout)| payout)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{CompetitionConfig, ConvictionConfig, VolatilityConfig};
     use rust_decimal_macros::dec;
 
-    #[tokio::test]
-    async fn test_backtest_engine() {
-        let backtest_config = BacktestConfig {
+    fn test_backtest_config() -> BacktestConfig {
+        BacktestConfig {
             mode: "simulation".to_string(),
             start_date: "2024-01-01".to_string(),
             end_date: "2024-12-31".to_string(),
@@ -229,19 +749,276 @@ mod tests {
             fee_rate_bps: 0,
             apply_gas_costs: false,
             estimated_gas_per_trade_usd: dec!(0.1),
-        };
+            default_liquidity: None,
+            unfilled_policy: "drop".to_string(),
+            mirror_exits: true,
+            chunk_size: 10_000,
+            scenarios: Vec::new(),
+            calibrated_slippage: None,
+            market_slippage_file: None,
+            competition: CompetitionConfig::default(),
+            end_of_backtest: "last_price".to_string(),
+        }
+    }
 
-        let position_sizing_config = PositionSizingConfig {
+    fn test_position_sizing_config() -> PositionSizingConfig {
+        PositionSizingConfig {
             max_position_size_absolute: dec!(1000),
             max_position_size_relative: dec!(0.1),
             strategy: "hybrid".to_string(),
             priority: "absolute".to_string(),
-        };
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
+        }
+    }
 
-        let mut engine = BacktestEngine::new(backtest_config, position_sizing_config);
+    #[tokio::test]
+    async fn test_backtest_engine() {
+        let mut engine = BacktestEngine::new(test_backtest_config(), test_position_sizing_config());
         let results = engine.run().await.unwrap();
 
         assert!(results.total_trades > 0);
         assert_eq!(results.initial_balance, dec!(10000));
+        assert_eq!(results.avg_fill_ratio, dec!(1));
+    }
+
+    #[tokio::test]
+    async fn test_backtest_partial_fills() {
+        let backtest_config = BacktestConfig {
+            default_liquidity: Some(dec!(50)),
+            ..test_backtest_config()
+        };
+
+        let mut engine = BacktestEngine::new(backtest_config, test_position_sizing_config());
+        let results = engine.run().await.unwrap();
+
+        assert!(results.total_trades > 0);
+        assert!(results.avg_fill_ratio > Decimal::ZERO);
+        assert!(results.avg_fill_ratio < Decimal::ONE);
+    }
+
+    #[tokio::test]
+    async fn test_backtest_trader_profiles() {
+        let mock_trader = "0x0000000000000000000000000000000000000000".to_string();
+        let profile = TraderProfile {
+            address: mock_trader,
+            copy_ratio: dec!(0.5),
+            min_trade_size_usdc: None,
+            max_trade_size_usdc: None,
+            allowed_markets: Some(vec!["market_1".to_string()]),
+            data_file: None,
+            competing_copiers: None,
+            onboarding: None,
+            onboarding_days: None,
+            labels: Vec::new(),
+        };
+
+        let mut engine = BacktestEngine::new(test_backtest_config(), test_position_sizing_config())
+            .with_trader_profiles(vec![profile.clone()]);
+        let results = engine.run().await.unwrap();
+
+        assert_eq!(results.per_market.len(), 1);
+        assert_eq!(results.per_market[0].key, "market_1");
+
+        // Traders without a profile are not part of the copied portfolio
+        let other = TraderProfile {
+            address: "0x1111111111111111111111111111111111111111".to_string(),
+            allowed_markets: None,
+            ..profile
+        };
+        let mut engine = BacktestEngine::new(test_backtest_config(), test_position_sizing_config())
+            .with_trader_profiles(vec![other]);
+        let results = engine.run().await.unwrap();
+
+        assert_eq!(results.total_trades, 0);
+    }
+
+    #[tokio::test]
+    async fn test_backtest_mirrors_exits() {
+        let mut mirrored =
+            BacktestEngine::new(test_backtest_config(), test_position_sizing_config());
+        let mirrored = mirrored.run().await.unwrap();
+
+        let backtest_config = BacktestConfig {
+            mirror_exits: false,
+            ..test_backtest_config()
+        };
+        let mut held = BacktestEngine::new(backtest_config, test_position_sizing_config());
+        let held = held.run().await.unwrap();
+
+        // Exits close copies during the run rather than all at the end
+        assert!(mirrored.total_trades > 0);
+        assert!(mirrored.avg_holding_period_hours < held.avg_holding_period_hours);
+    }
+
+    #[tokio::test]
+    async fn test_backtest_end_of_backtest_policies() {
+        let run = |policy: &str| {
+            let backtest_config = BacktestConfig {
+                mirror_exits: false,
+                end_of_backtest: policy.to_string(),
+                ..test_backtest_config()
+            };
+            async move {
+                let mut engine =
+                    BacktestEngine::new(backtest_config, test_position_sizing_config());
+                engine.run().await.unwrap()
+            }
+        };
+        let closed = run("last_price").await;
+        let marked = run("mark").await;
+        let unrealized = run("unrealized").await;
+
+        assert!(closed.total_trades > 0);
+        assert!(closed.open_positions.is_empty());
+        // Left open, nothing closes, but the marks count towards the total
+        assert_eq!(marked.total_trades, 0);
+        assert!(!marked.open_positions.is_empty());
+        assert_eq!(marked.total_pnl, marked.unrealized_pnl);
+        assert_eq!(unrealized.open_positions, marked.open_positions);
+        assert_eq!(unrealized.total_pnl, Decimal::ZERO);
+        assert_eq!(unrealized.unrealized_pnl, marked.unrealized_pnl);
+    }
+
+    #[tokio::test]
+    async fn test_backtest_chunking_is_transparent() {
+        let mut whole = BacktestEngine::new(test_backtest_config(), test_position_sizing_config());
+        let whole = whole.run().await.unwrap();
+
+        let backtest_config = BacktestConfig {
+            chunk_size: 7,
+            ..test_backtest_config()
+        };
+        let mut chunked = BacktestEngine::new(backtest_config, test_position_sizing_config());
+        let chunked = chunked.run().await.unwrap();
+
+        assert_eq!(whole.total_trades, chunked.total_trades);
+        assert_eq!(whole.total_pnl, chunked.total_pnl);
+    }
+
+    #[tokio::test]
+    async fn test_backtest_stress_scenarios() {
+        let backtest_config = BacktestConfig {
+            scenarios: vec![
+                crate::config::ScenarioConfig {
+                    name: "outage".to_string(),
+                    kind: "api_gap".to_string(),
+                    start: "2024-01-01T00:00:00Z".to_string(),
+                    end: "2024-01-01T09:30:00Z".to_string(),
+                    market: None,
+                    trader: None,
+                    magnitude: Decimal::ZERO,
+                },
+                crate::config::ScenarioConfig {
+                    name: "crash".to_string(),
+                    kind: "price_shock".to_string(),
+                    start: "2024-01-01T10:00:00Z".to_string(),
+                    end: "2024-01-03T12:00:00Z".to_string(),
+                    market: None,
+                    trader: None,
+                    magnitude: dec!(-0.5),
+                },
+            ],
+            ..test_backtest_config()
+        };
+
+        let mut engine = BacktestEngine::new(backtest_config, test_position_sizing_config());
+        let results = engine.run().await.unwrap();
+
+        assert_eq!(results.scenarios.len(), 2);
+        assert_eq!(results.scenarios[0].trades_affected, 10);
+        assert_eq!(results.scenarios[0].pnl, Decimal::ZERO);
+        assert_eq!(results.scenarios[1].kind, "price_shock");
+        assert_eq!(results.scenarios[1].trades_affected, 40);
+        assert!(results.format_report().contains("STRESS SCENARIOS"));
+    }
+
+    #[tokio::test]
+    async fn test_backtest_sizes_trades_in_shares() {
+        let trade = |size: Decimal| HistoricalTrade {
+            market: "market_1".to_string(),
+            side: OrderSide::Buy,
+            price: dec!(0.5),
+            size,
+            timestamp: "2024-06-01T12:00:00Z".parse().unwrap(),
+            trader: Address::zero(),
+            liquidity: None,
+        };
+        let profile = TraderProfile {
+            address: format!("{:?}", Address::zero()),
+            copy_ratio: Decimal::ONE,
+            min_trade_size_usdc: Some(dec!(150)),
+            max_trade_size_usdc: None,
+            allowed_markets: None,
+            data_file: None,
+            competing_copiers: None,
+            onboarding: None,
+            onboarding_days: None,
+            labels: Vec::new(),
+        };
+        let backtest_config = BacktestConfig {
+            mirror_exits: false,
+            end_of_backtest: "mark".to_string(),
+            ..test_backtest_config()
+        };
+
+        let position_sizing_config = PositionSizingConfig {
+            max_position_size_absolute: dec!(50),
+            ..test_position_sizing_config()
+        };
+
+        // 200 shares at 0.50 is 100 USDC, under the filter; 400 shares is 200 USDC,
+        // capped to a 50 USDC copy of 100 shares
+        let mut engine = BacktestEngine::new(backtest_config, position_sizing_config)
+            .with_trader_profiles(vec![profile])
+            .with_shared_data(Arc::new(vec![trade(dec!(200)), trade(dec!(400))]));
+        let results = engine.run().await.unwrap();
+
+        assert_eq!(results.open_positions.len(), 1);
+        assert_eq!(results.open_positions[0].size, dec!(100));
+    }
+
+    #[tokio::test]
+    async fn test_backtest_rolls_remainders_per_trader() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let trade =
+            |minute: u32, trader: Address, side: OrderSide, liquidity: Decimal| HistoricalTrade {
+                market: "market_1".to_string(),
+                side,
+                price: dec!(0.5),
+                size: dec!(100),
+                timestamp: format!("2024-06-01T12:{:02}:00Z", minute).parse().unwrap(),
+                trader,
+                liquidity: Some(liquidity),
+            };
+        let backtest_config = BacktestConfig {
+            unfilled_policy: "roll".to_string(),
+            mirror_exits: false,
+            end_of_backtest: "mark".to_string(),
+            ..test_backtest_config()
+        };
+
+        let mut engine = BacktestEngine::new(backtest_config, test_position_sizing_config())
+            .with_shared_data(Arc::new(vec![
+                // Alice fills 40 of 100 shares, then the book is empty
+                trade(0, alice, OrderSide::Buy, dec!(40)),
+                trade(1, bob, OrderSide::Buy, Decimal::ZERO),
+                trade(2, alice, OrderSide::Sell, Decimal::ZERO),
+            ]));
+        let results = engine.run().await.unwrap();
+
+        // Bob's remainder is kept apart from Alice's, whose unfilled sell nets
+        // against her unfilled buy
+        assert_eq!(results.unrolled_remainders, 2);
+        let rolled = |trader| &engine.rolled_orders[&(Some(trader), "market_1".to_string())];
+        assert_eq!(rolled(bob).side, OrderSide::Buy);
+        assert_eq!(rolled(bob).size, dec!(100));
+        assert_eq!(rolled(alice).side, OrderSide::Sell);
+        assert_eq!(rolled(alice).size, dec!(40));
+        assert!(results.format_report().contains("2 rolled remainder(s)"));
     }
 }
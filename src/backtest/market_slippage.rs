@@ -0,0 +1,220 @@
+use crate::backtest::slippage::{CalibratedSlippage, LinearSlippage, SlippageModel};
+use crate::errors::{PolymarketError, Result};
+use crate::storage::book_recorder::{BookLevel, BookSnapshot};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// A slippage file as written: models per market and per liquidity bucket
+///
+/// ```toml
+/// [markets."71321045679252212594626385532706912750332728571942532289631379312455583992563"]
+/// type = "Linear"
+/// depth_coefficient = 25000
+///
+/// [[buckets]]
+/// min_liquidity = 1000000
+/// type = "Linear"
+/// depth_coefficient = 2000000
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SlippageTable {
+    #[serde(default)]
+    pub markets: BTreeMap<String, CalibratedSlippage>,
+    #[serde(default)]
+    pub buckets: Vec<LiquidityBucket>,
+}
+
+/// The model for markets whose book holds at least `min_liquidity`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiquidityBucket {
+    pub min_liquidity: Decimal,
+    #[serde(flatten)]
+    pub model: CalibratedSlippage,
+}
+
+/// Slippage models that replace the global one for particular markets
+///
+/// A model listed for the market wins; otherwise the bucket with the highest
+/// `min_liquidity` the market's recorded liquidity reaches applies. Markets
+/// matching neither use the configured `slippage_model`.
+#[derive(Debug, Default)]
+pub struct MarketSlippage {
+    markets: HashMap<String, Arc<dyn SlippageModel>>,
+    /// Deepest first
+    buckets: Vec<(Decimal, Arc<dyn SlippageModel>)>,
+}
+
+impl MarketSlippage {
+    pub fn new(table: &SlippageTable) -> Self {
+        let mut buckets: Vec<(Decimal, Arc<dyn SlippageModel>)> = table
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.min_liquidity, bucket.model.model()))
+            .collect();
+        buckets.sort_by_key(|(min_liquidity, _)| std::cmp::Reverse(*min_liquidity));
+        Self {
+            markets: table
+                .markets
+                .iter()
+                .map(|(market, model)| (market.clone(), model.model()))
+                .collect(),
+            buckets,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            PolymarketError::ConfigError(format!("Failed to read slippage file {}: {}", path, e))
+        })?;
+        let table: SlippageTable = toml::from_str(&content).map_err(|e| {
+            PolymarketError::ConfigError(format!("Invalid slippage file {}: {}", path, e))
+        })?;
+        Ok(Self::new(&table))
+    }
+
+    /// The model for `market_id`, whose book held `liquidity` when known; None
+    /// where the global model applies
+    pub fn model(
+        &self,
+        market_id: &str,
+        liquidity: Option<Decimal>,
+    ) -> Option<&Arc<dyn SlippageModel>> {
+        if let Some(model) = self.markets.get(market_id) {
+            return Some(model);
+        }
+        let liquidity = liquidity?;
+        self.buckets
+            .iter()
+            .find(|(min_liquidity, _)| liquidity >= *min_liquidity)
+            .map(|(_, model)| model)
+    }
+}
+
+/// A linear model per token, fitted to recorded order books
+///
+/// Each snapshot is walked level by level on both sides: after taking `c`
+/// USDC the average fill is `impact` away from the best price, and
+/// `impact = c / depth_coefficient` is fitted through the origin per token.
+pub fn fit_books(snapshots: &[BookSnapshot]) -> SlippageTable {
+    let mut sums: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for snapshot in snapshots {
+        let (xy, xx) = sums.entry(&snapshot.token_id).or_default();
+        for levels in [&snapshot.asks, &snapshot.bids] {
+            for (notional, impact) in walk(levels) {
+                *xy += notional * impact;
+                *xx += notional * notional;
+            }
+        }
+    }
+
+    let markets = sums
+        .into_iter()
+        .filter(|(_, (xy, _))| *xy > 0.0)
+        .filter_map(|(token_id, (xy, xx))| {
+            let depth_coefficient = Decimal::from_f64_retain(xx / xy)?.round_dp(2);
+            let model = CalibratedSlippage::Linear(LinearSlippage { depth_coefficient });
+            Some((token_id.to_string(), model))
+        })
+        .collect();
+    SlippageTable {
+        markets,
+        buckets: Vec::new(),
+    }
+}
+
+/// (USDC taken, average price's distance from the best) after each level
+fn walk(levels: &[BookLevel]) -> Vec<(f64, f64)> {
+    let Some(best) = levels.first().map(|level| level.price) else {
+        return Vec::new();
+    };
+    let (mut shares, mut notional) = (Decimal::ZERO, Decimal::ZERO);
+    let mut points = Vec::new();
+    for level in levels {
+        shares += level.size;
+        notional += level.size * level.price;
+        if shares <= Decimal::ZERO {
+            continue;
+        }
+        let impact = (notional / shares - best).abs();
+        points.push((
+            notional.to_f64().unwrap_or(0.0),
+            impact.to_f64().unwrap_or(0.0),
+        ));
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderSide;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_markets_and_buckets_override_the_global_model() {
+        let table: SlippageTable = toml::from_str(
+            r#"
+            [markets.thin]
+            type = "Linear"
+            depth_coefficient = 1000
+
+            [[buckets]]
+            min_liquidity = 10000
+            type = "Percentage"
+            rate = 0.01
+
+            [[buckets]]
+            min_liquidity = 1000000
+            type = "Percentage"
+            rate = 0.001
+            "#,
+        )
+        .unwrap();
+        let slippage = MarketSlippage::new(&table);
+
+        let price = |model: &Arc<dyn SlippageModel>| {
+            model.calculate_execution_price(dec!(0.5), dec!(100), &OrderSide::Buy)
+        };
+        assert_eq!(price(slippage.model("thin", None).unwrap()), dec!(0.6));
+        let deep = slippage.model("other", Some(dec!(5000000))).unwrap();
+        assert_eq!(price(deep), dec!(0.5005));
+        let mid = slippage.model("other", Some(dec!(50000))).unwrap();
+        assert_eq!(price(mid), dec!(0.505));
+        assert!(slippage.model("other", Some(dec!(500))).is_none());
+        assert!(slippage.model("other", None).is_none());
+
+        // A book whose depth doesn't move the price fits nothing
+        let level = |price: Decimal, size: Decimal| BookLevel { price, size };
+        let snapshot = BookSnapshot {
+            timestamp: Utc::now(),
+            token_id: "fitted".to_string(),
+            bids: Vec::new(),
+            asks: vec![level(dec!(0.5), dec!(200)), level(dec!(0.54), dec!(0))],
+        };
+        let fitted = fit_books(&[snapshot]);
+        assert!(fitted.markets.is_empty());
+
+        let snapshot = BookSnapshot {
+            timestamp: Utc::now(),
+            token_id: "fitted".to_string(),
+            bids: Vec::new(),
+            asks: vec![level(dec!(0.5), dec!(200)), level(dec!(0.52), dec!(200))],
+        };
+        // 204 USDC moves the average fill 0.01
+        let fitted = fit_books(&[snapshot]);
+        match &fitted.markets["fitted"] {
+            CalibratedSlippage::Linear(model) => {
+                assert!(model.depth_coefficient > dec!(10000));
+                assert!(model.depth_coefficient < dec!(30000));
+            }
+            other => panic!("expected a linear model, got {:?}", other),
+        }
+        let written = toml::to_string(&fitted).unwrap();
+        let read: SlippageTable = toml::from_str(&written).unwrap();
+        assert_eq!(read.markets.len(), 1);
+    }
+}
@@ -1,10 +1,26 @@
-use crate::models::{BacktestResults, ClosedPosition, ExecutedTrade};
+use crate::models::{
+    BacktestResults, BreakdownRow, ClosedPosition, CostRow, ExecutedTrade, OpenPositionRow,
+    PeriodRow, Position, RollingSharpe,
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use rust_decimal::MathematicalOps;
+use std::collections::{BTreeMap, HashMap};
+
+/// Days of daily returns behind each rolling Sharpe ratio
+const ROLLING_SHARPE_DAYS: usize = 30;
 
 pub struct PerformanceMetrics {
     trades: Vec<ExecutedTrade>,
     closed_positions: Vec<ClosedPosition>,
+    /// Positions left open at the end, with the price they are marked at
+    open_positions: Vec<(Position, Decimal)>,
+    /// Whether open positions' P&L counts towards the total and final balance
+    count_unrealized: bool,
+    /// Slippage of exits, which aren't recorded as executions
+    exit_slippage: Decimal,
+    fees: Decimal,
+    gas: Decimal,
     initial_balance: Decimal,
 }
 
@@ -13,6 +29,11 @@ impl PerformanceMetrics {
         Self {
             trades: Vec::new(),
             closed_positions: Vec::new(),
+            open_positions: Vec::new(),
+            count_unrealized: false,
+            exit_slippage: Decimal::ZERO,
+            fees: Decimal::ZERO,
+            gas: Decimal::ZERO,
             initial_balance,
         }
     }
@@ -22,14 +43,51 @@ impl PerformanceMetrics {
         self.trades.push(trade);
     }
 
+    /// Every simulated execution so far
+    pub fn executions(&self) -> &[ExecutedTrade] {
+        &self.trades
+    }
+
     /// Record a closed position
     pub fn record_closed_position(&mut self, position: ClosedPosition) {
         self.closed_positions.push(position);
     }
 
+    /// Record a position left open at the end, marked at `mark_price`
+    pub fn record_open_position(&mut self, position: Position, mark_price: Decimal) {
+        self.open_positions.push((position, mark_price));
+    }
+
+    /// Count open positions' P&L in the total P&L, ROI and final balance,
+    /// rather than only reporting it
+    pub fn count_unrealized(&mut self, count: bool) {
+        self.count_unrealized = count;
+    }
+
+    /// Record what exiting cost in slippage, in USDC
+    pub fn record_exit_slippage(&mut self, cost: Decimal) {
+        self.exit_slippage += cost;
+    }
+
+    /// Record the fees and gas paid over the whole backtest
+    pub fn record_costs(&mut self, fees: Decimal, gas: Decimal) {
+        self.fees = fees;
+        self.gas = gas;
+    }
+
     /// Generate comprehensive backtest results
     pub fn generate_report(&self) -> BacktestResults {
-        let total_pnl: Decimal = self.closed_positions.iter().map(|p| p.pnl).sum();
+        let open_positions: Vec<OpenPositionRow> = self
+            .open_positions
+            .iter()
+            .map(|(position, mark_price)| OpenPositionRow::marked(position, *mark_price))
+            .collect();
+        let unrealized_pnl: Decimal = open_positions.iter().map(|p| p.unrealized_pnl).sum();
+        let realized_pnl: Decimal = self.closed_positions.iter().map(|p| p.pnl).sum();
+        let mut total_pnl = realized_pnl;
+        if self.count_unrealized {
+            total_pnl += unrealized_pnl;
+        }
         let winning_trades = self
             .closed_positions
             .iter()
@@ -85,6 +143,27 @@ impl PerformanceMetrics {
 
         let max_drawdown = self.calculate_max_drawdown();
         let sharpe = self.calculate_sharpe_ratio();
+        let sortino = self.calculate_sortino_ratio();
+        let calmar = self.calculate_calmar_ratio(roi, max_drawdown);
+        let (max_consecutive_wins, max_consecutive_losses) = self.calculate_streaks();
+        let rolling_sharpe = self.calculate_rolling_sharpe(ROLLING_SHARPE_DAYS);
+        let (gross_pnl, costs) = self.execution_costs(realized_pnl + unrealized_pnl);
+
+        let largest_win = self
+            .closed_positions
+            .iter()
+            .map(|p| p.pnl)
+            .filter(|pnl| *pnl > Decimal::ZERO)
+            .max()
+            .unwrap_or(Decimal::ZERO);
+        let largest_loss = self
+            .closed_positions
+            .iter()
+            .map(|p| p.pnl)
+            .filter(|pnl| *pnl < Decimal::ZERO)
+            .min()
+            .map(|pnl| pnl.abs())
+            .unwrap_or(Decimal::ZERO);
 
         BacktestResults {
             total_trades: self.closed_positions.len(),
@@ -98,11 +177,182 @@ impl PerformanceMetrics {
             profit_factor,
             max_drawdown,
             sharpe_ratio: sharpe,
+            sortino_ratio: sortino,
+            calmar_ratio: calmar,
+            avg_holding_period_hours: self.calculate_avg_holding_period_hours(),
+            time_in_market: self.calculate_time_in_market(),
+            max_consecutive_wins,
+            max_consecutive_losses,
+            largest_win,
+            largest_loss,
+            avg_fill_ratio: self.average_fill_ratio(),
             initial_balance: self.initial_balance,
             final_balance: self.initial_balance + total_pnl,
+            per_trader: self.breakdown_by(|p| match p.position.trader {
+                Some(trader) => format!("{:?}", trader),
+                None => "unknown".to_string(),
+            }),
+            per_market: self.breakdown_by(|p| p.position.market_id.clone()),
+            monthly: self.breakdown_by_period(&rolling_sharpe, |date| {
+                format!("{}-{:02}", date.year(), date.month())
+            }),
+            weekly: self.breakdown_by_period(&rolling_sharpe, |date| {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }),
+            rolling_sharpe,
+            unrealized_pnl,
+            open_positions,
+            gross_pnl,
+            costs,
+            scenarios: Vec::new(),
+            unrolled_remainders: 0,
+            live_comparison: None,
         }
     }
 
+    /// Group closed positions by key and summarize each group, best P&L first
+    fn breakdown_by<F>(&self, key_fn: F) -> Vec<BreakdownRow>
+    where
+        F: Fn(&ClosedPosition) -> String,
+    {
+        let mut groups: HashMap<String, Vec<&ClosedPosition>> = HashMap::new();
+        for position in &self.closed_positions {
+            groups.entry(key_fn(position)).or_default().push(position);
+        }
+
+        let mut rows: Vec<BreakdownRow> = groups
+            .into_iter()
+            .map(|(key, positions)| {
+                let winning_trades = positions.iter().filter(|p| p.pnl > Decimal::ZERO).count();
+                BreakdownRow {
+                    key,
+                    trades: positions.len(),
+                    winning_trades,
+                    win_rate: Decimal::from(winning_trades) / Decimal::from(positions.len())
+                        * Decimal::from(100),
+                    total_pnl: positions.iter().map(|p| p.pnl).sum(),
+                }
+            })
+            .collect();
+
+        rows.sort_by(|a, b| {
+            b.total_pnl
+                .cmp(&a.total_pnl)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        rows
+    }
+
+    /// Closed positions in exit order
+    fn closed_in_order(&self) -> Vec<&ClosedPosition> {
+        let mut closed: Vec<&ClosedPosition> = self.closed_positions.iter().collect();
+        closed.sort_by_key(|p| p.exit_timestamp);
+        closed
+    }
+
+    /// Group closed positions by the period their exit date falls in, oldest
+    /// first; `period_of` must name periods so they sort chronologically
+    fn breakdown_by_period<F>(
+        &self,
+        rolling_sharpe: &[RollingSharpe],
+        period_of: F,
+    ) -> Vec<PeriodRow>
+    where
+        F: Fn(NaiveDate) -> String,
+    {
+        let mut balance = self.initial_balance;
+        // Each period's row and the balance it started with
+        let mut rows: BTreeMap<String, (PeriodRow, Decimal)> = BTreeMap::new();
+        for position in self.closed_in_order() {
+            let period = period_of(position.exit_timestamp.date_naive());
+            let (row, _) = rows.entry(period.clone()).or_insert_with(|| {
+                let row = PeriodRow {
+                    period,
+                    trades: 0,
+                    winning_trades: 0,
+                    win_rate: Decimal::ZERO,
+                    total_pnl: Decimal::ZERO,
+                    return_pct: Decimal::ZERO,
+                    rolling_sharpe: None,
+                };
+                (row, balance)
+            });
+            row.trades += 1;
+            if position.pnl > Decimal::ZERO {
+                row.winning_trades += 1;
+            }
+            row.total_pnl += position.pnl;
+            balance += position.pnl;
+        }
+
+        rows.into_values()
+            .map(|(mut row, starting_balance)| {
+                row.win_rate = Decimal::from(row.winning_trades) / Decimal::from(row.trades)
+                    * Decimal::from(100);
+                row.return_pct = if starting_balance > Decimal::ZERO {
+                    row.total_pnl / starting_balance * Decimal::from(100)
+                } else {
+                    Decimal::ZERO
+                };
+                row.rolling_sharpe = rolling_sharpe
+                    .iter()
+                    .rev()
+                    .find(|day| period_of(day.date) == row.period)
+                    .map(|day| day.sharpe);
+                row
+            })
+            .collect()
+    }
+
+    /// Sharpe ratio of daily returns over each trailing window of `days`
+    ///
+    /// Every calendar day from the first exit to the last counts, days without
+    /// exits as a zero return, and each day's P&L is taken against the balance
+    /// it started with.
+    fn calculate_rolling_sharpe(&self, days: usize) -> Vec<RollingSharpe> {
+        let closed = self.closed_in_order();
+        let (Some(first), Some(last)) = (closed.first(), closed.last()) else {
+            return Vec::new();
+        };
+        let mut daily_pnl: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+        for position in &closed {
+            *daily_pnl
+                .entry(position.exit_timestamp.date_naive())
+                .or_default() += position.pnl;
+        }
+
+        let (first, last) = (
+            first.exit_timestamp.date_naive(),
+            last.exit_timestamp.date_naive(),
+        );
+        let mut balance = self.initial_balance;
+        let mut returns = Vec::new();
+        let mut date = first;
+        while date <= last {
+            let pnl = daily_pnl.get(&date).copied().unwrap_or_default();
+            let daily_return = if balance > Decimal::ZERO {
+                pnl / balance
+            } else {
+                Decimal::ZERO
+            };
+            returns.push((date, daily_return));
+            balance += pnl;
+            date += Duration::days(1);
+        }
+
+        returns
+            .windows(days)
+            .map(|window| {
+                let values: Vec<Decimal> = window.iter().map(|(_, r)| *r).collect();
+                RollingSharpe {
+                    date: window[window.len() - 1].0,
+                    sharpe: sharpe(&values),
+                }
+            })
+            .collect()
+    }
+
     /// Calculate maximum drawdown as percentage
     fn calculate_max_drawdown(&self) -> Decimal {
         let mut peak = self.initial_balance;
@@ -130,45 +380,227 @@ impl PerformanceMetrics {
         }
 
         let returns: Vec<Decimal> = self.closed_positions.iter().map(|p| p.pnl).collect();
+        sharpe(&returns)
+    }
+
+    /// Calculate Sortino ratio (mean return over downside deviation)
+    fn calculate_sortino_ratio(&self) -> Decimal {
+        if self.closed_positions.is_empty() {
+            return Decimal::ZERO;
+        }
 
-        let mean_return: Decimal =
-            returns.iter().sum::<Decimal>() / Decimal::from(returns.len());
+        let returns: Vec<Decimal> = self.closed_positions.iter().map(|p| p.pnl).collect();
+        let count = Decimal::from(returns.len());
+        let mean_return: Decimal = returns.iter().sum::<Decimal>() / count;
 
-        // Calculate standard deviation
-        let variance: Decimal = returns
+        // Only losses contribute to downside variance
+        let downside_variance: Decimal = returns
             .iter()
-            .map(|r| {
-                let diff = *r - mean_return;
-                diff * diff
-            })
+            .filter(|r| **r < Decimal::ZERO)
+            .map(|r| *r * *r)
             .sum::<Decimal>()
-            / Decimal::from(returns.len());
+            / count;
+
+        let downside_dev = downside_variance.sqrt().unwrap_or(Decimal::ZERO);
+
+        if downside_dev == Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        mean_return / downside_dev
+    }
 
-        let std_dev = variance.sqrt().unwrap_or(Decimal::ONE);
+    /// Calculate Calmar ratio (annualized return % over max drawdown %)
+    fn calculate_calmar_ratio(&self, roi: Decimal, max_drawdown: Decimal) -> Decimal {
+        if max_drawdown == Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let span_seconds = self
+            .backtest_span()
+            .map(|(start, end)| (end - start).num_seconds())
+            .unwrap_or(0);
+
+        let annualized_return = if span_seconds > 0 {
+            let days = Decimal::from(span_seconds) / Decimal::from(86_400);
+            roi * Decimal::from(365) / days
+        } else {
+            roi
+        };
+
+        annualized_return / max_drawdown
+    }
+
+    /// Longest runs of consecutive winning and losing closed positions
+    fn calculate_streaks(&self) -> (usize, usize) {
+        let mut max_wins = 0;
+        let mut max_losses = 0;
+        let mut wins = 0;
+        let mut losses = 0;
 
-        if std_dev == Decimal::ZERO {
+        for position in &self.closed_positions {
+            if position.pnl > Decimal::ZERO {
+                wins += 1;
+                losses = 0;
+            } else if position.pnl < Decimal::ZERO {
+                losses += 1;
+                wins = 0;
+            } else {
+                wins = 0;
+                losses = 0;
+            }
+            max_wins = max_wins.max(wins);
+            max_losses = max_losses.max(losses);
+        }
+
+        (max_wins, max_losses)
+    }
+
+    /// Average time between entry and exit, in hours
+    fn calculate_avg_holding_period_hours(&self) -> Decimal {
+        if self.closed_positions.is_empty() {
             return Decimal::ZERO;
         }
 
-        // Sharpe ratio = mean / std_dev (assuming risk-free rate = 0)
-        mean_return / std_dev
+        let total_seconds: i64 = self
+            .closed_positions
+            .iter()
+            .map(|p| {
+                (p.exit_timestamp - p.position.timestamp)
+                    .num_seconds()
+                    .max(0)
+            })
+            .sum();
+
+        Decimal::from(total_seconds)
+            / Decimal::from(3600)
+            / Decimal::from(self.closed_positions.len())
+    }
+
+    /// Percentage of the backtest span during which at least one position was open
+    fn calculate_time_in_market(&self) -> Decimal {
+        let span_seconds = match self.backtest_span() {
+            Some((start, end)) if (end - start).num_seconds() > 0 => (end - start).num_seconds(),
+            _ => return Decimal::ZERO,
+        };
+
+        let mut intervals: Vec<_> = self
+            .closed_positions
+            .iter()
+            .map(|p| (p.position.timestamp, p.exit_timestamp))
+            .collect();
+        intervals.sort();
+
+        // Merge overlapping holding intervals and sum their length
+        let mut covered_seconds = 0i64;
+        let mut current: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+        for (open, close) in intervals {
+            match current {
+                Some((cur_open, cur_close)) if open <= cur_close => {
+                    current = Some((cur_open, std::cmp::max(cur_close, close)));
+                }
+                Some((cur_open, cur_close)) => {
+                    covered_seconds += (cur_close - cur_open).num_seconds();
+                    current = Some((open, close));
+                }
+                None => current = Some((open, close)),
+            }
+        }
+        if let Some((cur_open, cur_close)) = current {
+            covered_seconds += (cur_close - cur_open).num_seconds();
+        }
+
+        Decimal::from(covered_seconds) / Decimal::from(span_seconds) * Decimal::from(100)
+    }
+
+    /// Earliest entry and latest exit across closed positions
+    fn backtest_span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let start = self
+            .closed_positions
+            .iter()
+            .map(|p| p.position.timestamp)
+            .min()?;
+        let end = self
+            .closed_positions
+            .iter()
+            .map(|p| p.exit_timestamp)
+            .max()?;
+        Some((start, end))
+    }
+
+    /// Average filled/requested ratio across recorded executions
+    fn average_fill_ratio(&self) -> Decimal {
+        if self.trades.is_empty() {
+            return Decimal::ZERO;
+        }
+        self.trades.iter().map(|t| t.fill_ratio).sum::<Decimal>() / Decimal::from(self.trades.len())
     }
 
     /// Get total fees paid
+    #[allow(dead_code)]
     pub fn total_fees(&self) -> Decimal {
-        self.trades.iter().map(|t| t.fee).sum()
+        self.fees
     }
 
-    /// Get total slippage incurred
+    /// Get total slippage incurred, entries and exits
     pub fn total_slippage(&self) -> Decimal {
-        self.trades.iter().map(|t| t.slippage * t.position.size).sum()
+        self.trades
+            .iter()
+            .map(|t| t.slippage * t.position.size)
+            .sum::<Decimal>()
+            + self.exit_slippage
+    }
+
+    /// The P&L before costs, and each cost in total, per execution and as a
+    /// share of it, from `net_pnl` after costs
+    fn execution_costs(&self, net_pnl: Decimal) -> (Decimal, Vec<CostRow>) {
+        let slippage = self.total_slippage();
+        let total = slippage + self.fees + self.gas;
+        let gross_pnl = net_pnl + total;
+        let row = |kind: &str, cost: Decimal| CostRow {
+            kind: kind.to_string(),
+            total: cost,
+            per_trade: if self.trades.is_empty() {
+                Decimal::ZERO
+            } else {
+                cost / Decimal::from(self.trades.len())
+            },
+            pct_of_gross: (gross_pnl > Decimal::ZERO)
+                .then(|| cost / gross_pnl * Decimal::ONE_HUNDRED),
+        };
+        let costs = vec![
+            row("slippage", slippage),
+            row("fees", self.fees),
+            row("gas", self.gas),
+            row("total", total),
+        ];
+        (gross_pnl, costs)
+    }
+}
+
+/// Mean over standard deviation, with a risk-free rate of zero; zero when
+/// the values don't vary
+fn sharpe(values: &[Decimal]) -> Decimal {
+    if values.is_empty() {
+        return Decimal::ZERO;
+    }
+    let count = Decimal::from(values.len());
+    let mean = values.iter().sum::<Decimal>() / count;
+    let variance = values
+        .iter()
+        .map(|v| (*v - mean) * (*v - mean))
+        .sum::<Decimal>()
+        / count;
+    match variance.sqrt() {
+        Some(std_dev) if !std_dev.is_zero() => mean / std_dev,
+        _ => Decimal::ZERO,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{OrderSide, Position};
+    use crate::models::OrderSide;
     use chrono::Utc;
     use rust_decimal_macros::dec;
 
@@ -185,6 +617,7 @@ mod tests {
                 side: OrderSide::Buy,
                 timestamp: Utc::now(),
                 pnl: dec!(0),
+                trader: None,
             },
             exit_price: dec!(0.6),
             pnl: dec!(10), // Win
@@ -199,6 +632,7 @@ mod tests {
                 side: OrderSide::Buy,
                 timestamp: Utc::now(),
                 pnl: dec!(0),
+                trader: None,
             },
             exit_price: dec!(0.4),
             pnl: dec!(-10), // Loss
@@ -213,6 +647,7 @@ mod tests {
                 side: OrderSide::Buy,
                 timestamp: Utc::now(),
                 pnl: dec!(0),
+                trader: None,
             },
             exit_price: dec!(0.7),
             pnl: dec!(20), // Win
@@ -228,4 +663,202 @@ mod tests {
         assert_eq!(results.avg_win, dec!(15)); // (10 + 20) / 2
         assert_eq!(results.avg_loss, dec!(10));
     }
+
+    #[test]
+    fn test_per_trader_and_market_breakdown() {
+        let mut metrics = PerformanceMetrics::new(dec!(10000));
+        let alice: ethers::types::Address = "0x1111111111111111111111111111111111111111"
+            .parse()
+            .unwrap();
+        let bob: ethers::types::Address = "0x2222222222222222222222222222222222222222"
+            .parse()
+            .unwrap();
+
+        for (trader, market, pnl) in [
+            (alice, "m1", dec!(30)),
+            (alice, "m2", dec!(-10)),
+            (bob, "m1", dec!(-5)),
+        ] {
+            metrics.record_closed_position(ClosedPosition {
+                position: Position {
+                    market_id: market.to_string(),
+                    entry_price: dec!(0.5),
+                    size: dec!(100),
+                    side: OrderSide::Buy,
+                    timestamp: Utc::now(),
+                    pnl: dec!(0),
+                    trader: Some(trader),
+                },
+                exit_price: dec!(0.5),
+                pnl,
+                exit_timestamp: Utc::now(),
+            });
+        }
+
+        let results = metrics.generate_report();
+
+        assert_eq!(results.per_trader.len(), 2);
+        assert_eq!(results.per_trader[0].key, format!("{:?}", alice));
+        assert_eq!(results.per_trader[0].trades, 2);
+        assert_eq!(results.per_trader[0].total_pnl, dec!(20));
+        assert_eq!(results.per_trader[0].win_rate, dec!(50));
+        assert_eq!(results.per_trader[1].total_pnl, dec!(-5));
+
+        assert_eq!(results.per_market[0].key, "m1");
+        assert_eq!(results.per_market[0].total_pnl, dec!(25));
+        assert_eq!(results.per_market[1].key, "m2");
+
+        let report = results.format_report();
+        assert!(report.contains("PER-TRADER BREAKDOWN"));
+        assert!(report.contains("PER-MARKET BREAKDOWN"));
+    }
+
+    #[test]
+    fn test_risk_and_streak_metrics() {
+        let mut metrics = PerformanceMetrics::new(dec!(10000));
+        let start = Utc::now();
+
+        // W, W, L, L, L, W with each position held for 2 hours back-to-back
+        for (i, pnl) in [dec!(10), dec!(30), dec!(-5), dec!(-20), dec!(-5), dec!(15)]
+            .into_iter()
+            .enumerate()
+        {
+            let opened = start + chrono::Duration::hours(2 * i as i64);
+            metrics.record_closed_position(ClosedPosition {
+                position: Position {
+                    market_id: "m1".to_string(),
+                    entry_price: dec!(0.5),
+                    size: dec!(100),
+                    side: OrderSide::Buy,
+                    timestamp: opened,
+                    pnl: dec!(0),
+                    trader: None,
+                },
+                exit_price: dec!(0.5),
+                pnl,
+                exit_timestamp: opened + chrono::Duration::hours(2),
+            });
+        }
+
+        let results = metrics.generate_report();
+
+        assert_eq!(results.max_consecutive_wins, 2);
+        assert_eq!(results.max_consecutive_losses, 3);
+        assert_eq!(results.largest_win, dec!(30));
+        assert_eq!(results.largest_loss, dec!(20));
+        assert_eq!(results.avg_holding_period_hours, dec!(2));
+        assert_eq!(results.time_in_market, dec!(100));
+        assert!(results.sortino_ratio > Decimal::ZERO);
+        assert!(results.calmar_ratio > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_monthly_and_weekly_breakdown_with_rolling_sharpe() {
+        let mut metrics = PerformanceMetrics::new(dec!(1000));
+        let day = |date: &str| {
+            format!("{}T12:00:00Z", date)
+                .parse::<DateTime<Utc>>()
+                .unwrap()
+        };
+        for (date, pnl) in [
+            ("2024-01-02", dec!(50)),
+            ("2024-01-20", dec!(-25)),
+            ("2024-02-05", dec!(10.25)),
+            ("2024-03-01", dec!(-5)),
+        ] {
+            metrics.record_closed_position(ClosedPosition {
+                position: Position {
+                    market_id: "m1".to_string(),
+                    entry_price: dec!(0.5),
+                    size: dec!(100),
+                    side: OrderSide::Buy,
+                    timestamp: day(date) - chrono::Duration::hours(1),
+                    pnl: dec!(0),
+                    trader: None,
+                },
+                exit_price: dec!(0.5),
+                pnl,
+                exit_timestamp: day(date),
+            });
+        }
+
+        let results = metrics.generate_report();
+
+        let months: Vec<&str> = results.monthly.iter().map(|r| r.period.as_str()).collect();
+        assert_eq!(months, vec!["2024-01", "2024-02", "2024-03"]);
+        assert_eq!(results.monthly[0].total_pnl, dec!(25));
+        assert_eq!(results.monthly[0].win_rate, dec!(50));
+        // February starts from 1025
+        assert_eq!(results.monthly[1].return_pct, dec!(1));
+        assert_eq!(results.weekly.len(), 4);
+        assert_eq!(results.weekly[0].period, "2024-W01");
+
+        // One value per day from 31 Jan, the 30th day counting the first exit
+        assert_eq!(results.rolling_sharpe.len(), 31);
+        assert_eq!(
+            results.rolling_sharpe[0].date,
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()
+        );
+        assert!(results.monthly[0].rolling_sharpe.is_some());
+        assert_eq!(
+            results.monthly[2].rolling_sharpe,
+            results.rolling_sharpe.last().map(|day| day.sharpe)
+        );
+
+        let report = results.format_report();
+        assert!(report.contains("MONTHLY PERFORMANCE"));
+        assert!(report.contains("Best: 2024-01 with 82.6% of the total P&L"));
+    }
+
+    #[test]
+    fn test_execution_costs_against_gross_pnl() {
+        use crate::backtest::simulator::TradeSimulator;
+        use crate::backtest::slippage::LinearSlippage;
+
+        let mut simulator = TradeSimulator::new(dec!(10000), 100).with_gas_cost(dec!(0.5));
+        let slippage_model = LinearSlippage {
+            depth_coefficient: dec!(100000),
+        };
+        let mut metrics = PerformanceMetrics::new(dec!(10000));
+
+        // Bought at 0.51 after 0.01 slippage: 5.1 fee and 0.5 gas to open
+        let executed = simulator
+            .simulate_execution(
+                "m1",
+                None,
+                OrderSide::Buy,
+                dec!(1000),
+                dec!(0.5),
+                &slippage_model,
+            )
+            .unwrap();
+        assert_eq!(simulator.balance(), dec!(9484.4));
+        metrics.record_trade(executed);
+        // 6 fee and 0.5 gas to close
+        let closed = simulator.close_position("m1", dec!(0.6)).unwrap();
+        assert_eq!(closed.pnl, dec!(77.9));
+        metrics.record_closed_position(closed);
+        let (fees, gas) = simulator.costs_paid();
+        metrics.record_costs(fees, gas);
+
+        let results = metrics.generate_report();
+        assert_eq!(results.total_pnl, dec!(77.9));
+        assert_eq!(results.final_balance, simulator.balance());
+        assert_eq!(results.gross_pnl, dec!(100));
+        let costs: Vec<(&str, Decimal, Option<Decimal>)> = results
+            .costs
+            .iter()
+            .map(|c| (c.kind.as_str(), c.per_trade, c.pct_of_gross))
+            .collect();
+        assert_eq!(
+            costs,
+            vec![
+                ("slippage", dec!(10), Some(dec!(10))),
+                ("fees", dec!(11.1), Some(dec!(11.1))),
+                ("gas", dec!(1), Some(dec!(1))),
+                ("total", dec!(22.1), Some(dec!(22.1))),
+            ]
+        );
+        assert!(results.format_report().contains("EXECUTION COSTS"));
+    }
 }
@@ -1,6 +1,12 @@
+pub mod calibration;
+pub mod competition;
+pub mod data;
 pub mod engine;
+pub mod market_slippage;
 pub mod metrics;
+pub mod scenario;
 pub mod simulator;
 pub mod slippage;
+pub mod sweep;
 
 pub use engine::BacktestEngine;
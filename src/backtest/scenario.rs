@@ -0,0 +1,340 @@
+use crate::config::ScenarioConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::models::{HistoricalTrade, OrderSide, ScenarioReport};
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Keep shocked prices inside the valid outcome-token range
+const MIN_PRICE: Decimal = dec!(0.001);
+const MAX_PRICE: Decimal = dec!(0.999);
+
+/// The kind of shock a scenario injects
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shock {
+    /// Move every price (optionally in one market) by a relative amount
+    PriceJump {
+        market: Option<String>,
+        change: Decimal,
+    },
+    /// Make a trader's entries pricier and exits cheaper
+    LosingStreak {
+        trader: Address,
+        adverse_move: Decimal,
+    },
+    /// Multiply the configured slippage model's impact
+    SlippageSpike { factor: Decimal },
+    /// Hide trades entirely, as if the data API were down
+    ApiGap,
+}
+
+/// A shock and the window it is active in
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    pub name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub shock: Shock,
+}
+
+impl Scenario {
+    pub fn from_config(config: &ScenarioConfig) -> Result<Self> {
+        let parse_time = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| {
+                    PolymarketError::ConfigError(format!(
+                        "Invalid time '{}' in scenario {}: {}",
+                        value, config.name, e
+                    ))
+                })
+        };
+        let start = parse_time(&config.start)?;
+        let end = parse_time(&config.end)?;
+        if end <= start {
+            return Err(PolymarketError::ConfigError(format!(
+                "Scenario {} must end after it starts",
+                config.name
+            )));
+        }
+
+        let shock = match config.kind.as_str() {
+            "price_shock" => Shock::PriceJump {
+                market: config.market.clone(),
+                change: config.magnitude,
+            },
+            "losing_streak" => {
+                let trader = config.trader.as_deref().ok_or_else(|| {
+                    PolymarketError::ConfigError(format!("Scenario {} needs a trader", config.name))
+                })?;
+                Shock::LosingStreak {
+                    trader: trader.parse::<Address>().map_err(|e| {
+                        PolymarketError::ParseError(format!("Invalid address {}: {}", trader, e))
+                    })?,
+                    adverse_move: config.magnitude,
+                }
+            }
+            "slippage_spike" => {
+                if config.magnitude <= Decimal::ZERO {
+                    return Err(PolymarketError::ConfigError(format!(
+                        "Scenario {} needs a positive slippage multiplier",
+                        config.name
+                    )));
+                }
+                Shock::SlippageSpike {
+                    factor: config.magnitude,
+                }
+            }
+            "api_gap" => Shock::ApiGap,
+            other => {
+                return Err(PolymarketError::ConfigError(format!(
+                    "Unknown scenario kind: {}",
+                    other
+                )));
+            }
+        };
+
+        Ok(Self {
+            name: config.name.clone(),
+            start,
+            end,
+            shock,
+        })
+    }
+
+    fn kind(&self) -> &'static str {
+        match self.shock {
+            Shock::PriceJump { .. } => "price_shock",
+            Shock::LosingStreak { .. } => "losing_streak",
+            Shock::SlippageSpike { .. } => "slippage_spike",
+            Shock::ApiGap => "api_gap",
+        }
+    }
+
+    fn is_active(&self, time: DateTime<Utc>) -> bool {
+        time >= self.start && time <= self.end
+    }
+}
+
+/// Portfolio observations collected while a scenario window is open
+#[derive(Default)]
+struct WindowStats {
+    trades_affected: usize,
+    start_equity: Option<Decimal>,
+    end_equity: Decimal,
+    peak_equity: Decimal,
+    max_drawdown: Decimal,
+    start_rejected: usize,
+    end_rejected: usize,
+}
+
+/// Scenarios configured for one backtest run plus what happened inside them
+#[derive(Default)]
+pub struct ScenarioSet {
+    scenarios: Vec<(Scenario, WindowStats)>,
+}
+
+impl ScenarioSet {
+    pub fn from_config(configs: &[ScenarioConfig]) -> Result<Self> {
+        let scenarios = configs
+            .iter()
+            .map(|config| Ok((Scenario::from_config(config)?, WindowStats::default())))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { scenarios })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenarios.is_empty()
+    }
+
+    /// Apply the shocks active at the trade's time
+    ///
+    /// Returns the (possibly altered) trade, or None when an API gap hides it,
+    /// along with the slippage multiplier to use while executing it.
+    pub fn apply(&mut self, trade: &HistoricalTrade) -> (Option<HistoricalTrade>, Decimal) {
+        let mut shocked = trade.clone();
+        let mut slippage_factor = Decimal::ONE;
+        let mut hidden = false;
+
+        for (scenario, stats) in &mut self.scenarios {
+            if !scenario.is_active(trade.timestamp) {
+                continue;
+            }
+
+            let affected = match &scenario.shock {
+                Shock::PriceJump { market, change } => {
+                    if market.as_ref().is_some_and(|m| *m != trade.market) {
+                        false
+                    } else {
+                        shocked.price = clamp_price(shocked.price * (Decimal::ONE + change));
+                        true
+                    }
+                }
+                Shock::LosingStreak {
+                    trader,
+                    adverse_move,
+                } => {
+                    if *trader != trade.trader {
+                        false
+                    } else {
+                        let factor = match trade.side {
+                            OrderSide::Buy => Decimal::ONE + adverse_move,
+                            OrderSide::Sell => Decimal::ONE - adverse_move,
+                        };
+                        shocked.price = clamp_price(shocked.price * factor);
+                        true
+                    }
+                }
+                Shock::SlippageSpike { factor } => {
+                    slippage_factor *= factor;
+                    true
+                }
+                Shock::ApiGap => {
+                    hidden = true;
+                    true
+                }
+            };
+
+            if affected {
+                stats.trades_affected += 1;
+            }
+        }
+
+        if hidden {
+            (None, slippage_factor)
+        } else {
+            (Some(shocked), slippage_factor)
+        }
+    }
+
+    /// Record portfolio value and rejected-copy count for windows open at `time`
+    pub fn observe(&mut self, time: DateTime<Utc>, equity: Decimal, rejected_copies: usize) {
+        for (scenario, stats) in &mut self.scenarios {
+            if !scenario.is_active(time) {
+                continue;
+            }
+
+            if stats.start_equity.is_none() {
+                stats.start_equity = Some(equity);
+                stats.peak_equity = equity;
+                stats.start_rejected = rejected_copies;
+            }
+            stats.end_equity = equity;
+            stats.end_rejected = rejected_copies;
+
+            stats.peak_equity = std::cmp::max(stats.peak_equity, equity);
+            if stats.peak_equity > Decimal::ZERO {
+                let drawdown =
+                    (stats.peak_equity - equity) / stats.peak_equity * Decimal::from(100);
+                stats.max_drawdown = std::cmp::max(stats.max_drawdown, drawdown);
+            }
+        }
+    }
+
+    pub fn reports(&self) -> Vec<ScenarioReport> {
+        self.scenarios
+            .iter()
+            .map(|(scenario, stats)| ScenarioReport {
+                name: scenario.name.clone(),
+                kind: scenario.kind().to_string(),
+                trades_affected: stats.trades_affected,
+                pnl: stats
+                    .start_equity
+                    .map(|start| stats.end_equity - start)
+                    .unwrap_or(Decimal::ZERO),
+                max_drawdown: stats.max_drawdown,
+                rejected_copies: stats.end_rejected - stats.start_rejected,
+            })
+            .collect()
+    }
+}
+
+fn clamp_price(price: Decimal) -> Decimal {
+    price.clamp(MIN_PRICE, MAX_PRICE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn scenario_config(kind: &str, magnitude: Decimal) -> ScenarioConfig {
+        ScenarioConfig {
+            name: kind.to_string(),
+            kind: kind.to_string(),
+            start: "2024-01-01T00:00:00Z".to_string(),
+            end: "2024-01-01T12:00:00Z".to_string(),
+            market: None,
+            trader: None,
+            magnitude,
+        }
+    }
+
+    fn trade_at(hour: u32, side: OrderSide) -> HistoricalTrade {
+        HistoricalTrade {
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap(),
+            trader: Address::zero(),
+            market: "m1".to_string(),
+            side,
+            price: dec!(0.5),
+            size: dec!(100),
+            liquidity: None,
+        }
+    }
+
+    #[test]
+    fn test_price_shock_only_inside_window() {
+        let mut set =
+            ScenarioSet::from_config(&[scenario_config("price_shock", dec!(-0.4))]).unwrap();
+
+        let (inside, factor) = set.apply(&trade_at(6, OrderSide::Buy));
+        assert_eq!(inside.unwrap().price, dec!(0.3));
+        assert_eq!(factor, Decimal::ONE);
+
+        let (outside, _) = set.apply(&trade_at(13, OrderSide::Buy));
+        assert_eq!(outside.unwrap().price, dec!(0.5));
+        assert_eq!(set.reports()[0].trades_affected, 1);
+    }
+
+    #[test]
+    fn test_losing_streak_and_gap() {
+        let mut streak = scenario_config("losing_streak", dec!(0.1));
+        streak.trader = Some(format!("{:?}", Address::zero()));
+        let mut set =
+            ScenarioSet::from_config(&[streak, scenario_config("slippage_spike", dec!(2))])
+                .unwrap();
+
+        let (buy, factor) = set.apply(&trade_at(1, OrderSide::Buy));
+        assert_eq!(buy.unwrap().price, dec!(0.55));
+        assert_eq!(factor, dec!(2));
+        let (sell, _) = set.apply(&trade_at(2, OrderSide::Sell));
+        assert_eq!(sell.unwrap().price, dec!(0.45));
+
+        let mut gap = ScenarioSet::from_config(&[scenario_config("api_gap", dec!(0))]).unwrap();
+        assert!(gap.apply(&trade_at(3, OrderSide::Buy)).0.is_none());
+    }
+
+    #[test]
+    fn test_window_drawdown() {
+        let mut set = ScenarioSet::from_config(&[scenario_config("api_gap", dec!(0))]).unwrap();
+        let at = |hour| Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+
+        set.observe(at(1), dec!(1000), 0);
+        set.observe(at(2), dec!(1200), 1);
+        set.observe(at(3), dec!(900), 3);
+        set.observe(at(20), dec!(100), 9); // outside the window
+
+        let report = &set.reports()[0];
+        assert_eq!(report.pnl, dec!(-100));
+        assert_eq!(report.max_drawdown, dec!(25));
+        assert_eq!(report.rejected_copies, 3);
+    }
+
+    #[test]
+    fn test_invalid_scenarios_rejected() {
+        assert!(Scenario::from_config(&scenario_config("meteor", dec!(1))).is_err());
+        assert!(Scenario::from_config(&scenario_config("losing_streak", dec!(0.1))).is_err());
+        assert!(Scenario::from_config(&scenario_config("slippage_spike", dec!(0))).is_err());
+    }
+}
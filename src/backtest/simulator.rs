@@ -1,13 +1,23 @@
 use crate::backtest::slippage::SlippageModel;
 use crate::errors::{PolymarketError, Result};
 use crate::models::{ClosedPosition, ExecutedTrade, OrderSide, Position};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 
 pub struct TradeSimulator {
     balance: Decimal,
     positions: Vec<Position>,
     fee_rate_bps: u32,
+    /// Flat gas cost of each execution and exit, in USDC
+    gas_per_trade: Decimal,
+    /// Fees and gas paid so far
+    fees_paid: Decimal,
+    gas_paid: Decimal,
+    current_time: DateTime<Utc>,
+    /// Remaining depth per market, valid only for the time it was recorded at
+    liquidity: HashMap<String, (DateTime<Utc>, Decimal)>,
 }
 
 impl TradeSimulator {
@@ -16,6 +26,55 @@ impl TradeSimulator {
             balance: initial_balance,
             positions: Vec::new(),
             fee_rate_bps,
+            gas_per_trade: Decimal::ZERO,
+            fees_paid: Decimal::ZERO,
+            gas_paid: Decimal::ZERO,
+            current_time: Utc::now(),
+            liquidity: HashMap::new(),
+        }
+    }
+
+    /// Charge `gas_per_trade` USDC for every execution and exit
+    pub fn with_gas_cost(mut self, gas_per_trade: Decimal) -> Self {
+        self.gas_per_trade = gas_per_trade;
+        self
+    }
+
+    /// Advance the simulated clock; positions opened or closed afterwards use this time
+    pub fn set_time(&mut self, time: DateTime<Utc>) {
+        self.current_time = time;
+    }
+
+    /// Record available depth for a market at the current time.
+    ///
+    /// Fills at the same timestamp consume the same snapshot, so a second call
+    /// for an already-recorded market/time keeps the remaining depth.
+    pub fn set_liquidity(&mut self, market_id: &str, available: Decimal) {
+        let current_time = self.current_time;
+        match self.liquidity.get(market_id) {
+            Some((time, _)) if *time == current_time => {}
+            _ => {
+                self.liquidity
+                    .insert(market_id.to_string(), (current_time, available));
+            }
+        }
+    }
+
+    /// Depth still available in a market at the current time (None = unlimited)
+    pub fn available_liquidity(&self, market_id: &str) -> Option<Decimal> {
+        match self.liquidity.get(market_id) {
+            Some((time, available)) if *time == self.current_time => Some(*available),
+            _ => None,
+        }
+    }
+
+    /// Take `size` out of the depth recorded at the current time, e.g. for
+    /// other copiers filling before us
+    pub fn consume_liquidity(&mut self, market_id: &str, size: Decimal) {
+        if let Some((time, available)) = self.liquidity.get_mut(market_id) {
+            if *time == self.current_time {
+                *available = (*available - size).max(Decimal::ZERO);
+            }
         }
     }
 
@@ -24,6 +83,11 @@ impl TradeSimulator {
         self.balance
     }
 
+    /// Fees and gas paid so far
+    pub fn costs_paid(&self) -> (Decimal, Decimal) {
+        (self.fees_paid, self.gas_paid)
+    }
+
     /// Get all open positions
     pub fn positions(&self) -> &[Position] {
         &self.positions
@@ -33,11 +97,25 @@ impl TradeSimulator {
     pub fn simulate_execution(
         &mut self,
         market_id: &str,
+        trader: Option<Address>,
         side: OrderSide,
         size: Decimal,
         quote_price: Decimal,
-        slippage_model: &SlippageModel,
+        slippage_model: &dyn SlippageModel,
     ) -> Result<ExecutedTrade> {
+        let requested_size = size;
+
+        // Cap the fill at the depth available at this time
+        let size = match self.available_liquidity(market_id) {
+            Some(available) => std::cmp::min(requested_size, available),
+            None => requested_size,
+        };
+        if size <= Decimal::ZERO {
+            return Err(PolymarketError::InsufficientLiquidity(
+                market_id.to_string(),
+            ));
+        }
+
         // Calculate actual execution price with slippage
         let actual_price = slippage_model.calculate_execution_price(quote_price, size, &side);
         let slippage = slippage_model.calculate_slippage(quote_price, size, &side);
@@ -50,7 +128,7 @@ impl TradeSimulator {
         let total_cost = cost + fee;
 
         // Check balance
-        if side == OrderSide::Buy && total_cost > self.balance {
+        if side == OrderSide::Buy && total_cost + self.gas_per_trade > self.balance {
             return Err(PolymarketError::InsufficientBalance);
         }
 
@@ -59,15 +137,24 @@ impl TradeSimulator {
             OrderSide::Buy => self.balance -= total_cost,
             OrderSide::Sell => self.balance += total_cost,
         }
+        self.balance -= self.gas_per_trade;
+        self.fees_paid += fee;
+        self.gas_paid += self.gas_per_trade;
+
+        // Consume the depth we just took
+        if let Some((_, available)) = self.liquidity.get_mut(market_id) {
+            *available -= size;
+        }
 
-        // Create position
+        // Create position, its P&L starting with what it cost to open
         let position = Position {
             market_id: market_id.to_string(),
             entry_price: actual_price,
             size,
             side,
-            timestamp: Utc::now(),
-            pnl: Decimal::ZERO,
+            timestamp: self.current_time,
+            pnl: -(fee + self.gas_per_trade),
+            trader,
         };
 
         self.positions.push(position.clone());
@@ -77,6 +164,7 @@ impl TradeSimulator {
             actual_price,
             slippage,
             fee,
+            fill_ratio: size / requested_size,
         })
     }
 
@@ -96,25 +184,71 @@ impl TradeSimulator {
 
         let position = self.positions.remove(pos_idx);
 
+        Ok(self.settle(position, exit_price))
+    }
+
+    /// Close `fraction` (0..=1) of every open position in a market opened from
+    /// the given trader's activity, e.g. to mirror that trader's partial exit
+    pub fn reduce_positions(
+        &mut self,
+        market_id: &str,
+        trader: Option<Address>,
+        fraction: Decimal,
+        exit_price: Decimal,
+    ) -> Vec<ClosedPosition> {
+        let fraction = fraction.clamp(Decimal::ZERO, Decimal::ONE);
+        let mut closed = Vec::new();
+        let mut idx = 0;
+
+        while idx < self.positions.len() {
+            let position = &self.positions[idx];
+            if position.market_id != market_id || position.trader != trader {
+                idx += 1;
+                continue;
+            }
+
+            if fraction == Decimal::ONE {
+                let position = self.positions.remove(idx);
+                closed.push(self.settle(position, exit_price));
+            } else {
+                let mut part = position.clone();
+                part.size = position.size * fraction;
+                part.pnl = position.pnl * fraction;
+                self.positions[idx].size -= part.size;
+                self.positions[idx].pnl -= part.pnl;
+                closed.push(self.settle(part, exit_price));
+                idx += 1;
+            }
+        }
+
+        closed
+    }
+
+    /// Realize P&L, exit fees and gas for a position that has been removed
+    /// from the book; the P&L includes what it cost to open
+    fn settle(&mut self, position: Position, exit_price: Decimal) -> ClosedPosition {
         // Calculate P&L
-        let pnl = match position.side {
-            OrderSide::Buy => (exit_price - position.entry_price) * position.size,
-            OrderSide::Sell => (position.entry_price - exit_price) * position.size,
-        };
+        let pnl = position.pnl
+            + match position.side {
+                OrderSide::Buy => (exit_price - position.entry_price) * position.size,
+                OrderSide::Sell => (position.entry_price - exit_price) * position.size,
+            };
 
         // Apply exit fees
         let exit_cost = position.size * exit_price;
         let exit_fee = exit_cost * Decimal::from(self.fee_rate_bps) / Decimal::from(10000);
 
-        // Update balance with position value and fees
-        self.balance += exit_cost - exit_fee;
+        // Update balance with position value and costs
+        self.balance += exit_cost - exit_fee - self.gas_per_trade;
+        self.fees_paid += exit_fee;
+        self.gas_paid += self.gas_per_trade;
 
-        Ok(ClosedPosition {
+        ClosedPosition {
             position,
             exit_price,
-            pnl: pnl - exit_fee,
-            exit_timestamp: Utc::now(),
-        })
+            pnl: pnl - exit_fee - self.gas_per_trade,
+            exit_timestamp: self.current_time,
+        }
     }
 
     /// Close all open positions at market prices
@@ -139,7 +273,10 @@ impl TradeSimulator {
     }
 
     /// Get total portfolio value (balance + position value)
-    pub fn total_value(&self, market_prices: &std::collections::HashMap<String, Decimal>) -> Decimal {
+    pub fn total_value(
+        &self,
+        market_prices: &std::collections::HashMap<String, Decimal>,
+    ) -> Decimal {
         let mut total = self.balance;
 
         for position in &self.positions {
@@ -159,18 +296,20 @@ impl TradeSimulator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backtest::slippage::{LinearSlippage, PercentageSlippage};
     use rust_decimal_macros::dec;
 
     #[test]
     fn test_simulate_execution() {
         let mut simulator = TradeSimulator::new(dec!(10000), 0);
-        let slippage_model = SlippageModel::Linear {
+        let slippage_model = LinearSlippage {
             depth_coefficient: dec!(100000),
         };
 
         let result = simulator
             .simulate_execution(
                 "market1",
+                None,
                 OrderSide::Buy,
                 dec!(1000),
                 dec!(0.5),
@@ -189,13 +328,14 @@ mod tests {
     #[test]
     fn test_close_position() {
         let mut simulator = TradeSimulator::new(dec!(10000), 0);
-        let slippage_model = SlippageModel::Linear {
+        let slippage_model = LinearSlippage {
             depth_coefficient: dec!(100000),
         };
 
         simulator
             .simulate_execution(
                 "market1",
+                None,
                 OrderSide::Buy,
                 dec!(1000),
                 dec!(0.5),
@@ -213,12 +353,13 @@ mod tests {
     #[test]
     fn test_insufficient_balance() {
         let mut simulator = TradeSimulator::new(dec!(100), 0);
-        let slippage_model = SlippageModel::Linear {
+        let slippage_model = LinearSlippage {
             depth_coefficient: dec!(100000),
         };
 
         let result = simulator.simulate_execution(
             "market1",
+            None,
             OrderSide::Buy,
             dec!(1000),
             dec!(0.5),
@@ -226,6 +367,96 @@ mod tests {
         );
 
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), PolymarketError::InsufficientBalance));
+        assert!(matches!(
+            result.unwrap_err(),
+            PolymarketError::InsufficientBalance
+        ));
+    }
+
+    #[test]
+    fn test_partial_fill_from_liquidity() {
+        let mut simulator = TradeSimulator::new(dec!(10000), 0);
+        let slippage_model = LinearSlippage {
+            depth_coefficient: dec!(100000),
+        };
+
+        simulator.set_liquidity("market1", dec!(600));
+
+        let first = simulator
+            .simulate_execution(
+                "market1",
+                None,
+                OrderSide::Buy,
+                dec!(400),
+                dec!(0.5),
+                &slippage_model,
+            )
+            .unwrap();
+        assert_eq!(first.position.size, dec!(400));
+        assert_eq!(first.fill_ratio, dec!(1));
+
+        // Re-recording the same timestamp keeps the remaining depth
+        simulator.set_liquidity("market1", dec!(600));
+        let second = simulator
+            .simulate_execution(
+                "market1",
+                None,
+                OrderSide::Buy,
+                dec!(400),
+                dec!(0.5),
+                &slippage_model,
+            )
+            .unwrap();
+        assert_eq!(second.position.size, dec!(200));
+        assert_eq!(second.fill_ratio, dec!(0.5));
+
+        let exhausted = simulator.simulate_execution(
+            "market1",
+            None,
+            OrderSide::Buy,
+            dec!(100),
+            dec!(0.5),
+            &slippage_model,
+        );
+        assert!(matches!(
+            exhausted.unwrap_err(),
+            PolymarketError::InsufficientLiquidity(_)
+        ));
+    }
+
+    #[test]
+    fn test_reduce_positions() {
+        let mut simulator = TradeSimulator::new(dec!(10000), 0);
+        let slippage_model = PercentageSlippage { rate: dec!(0) };
+        let trader: Address = "0x1111111111111111111111111111111111111111"
+            .parse()
+            .unwrap();
+
+        simulator
+            .simulate_execution(
+                "market1",
+                Some(trader),
+                OrderSide::Buy,
+                dec!(1000),
+                dec!(0.5),
+                &slippage_model,
+            )
+            .unwrap();
+
+        // Trader sells 40% of their position
+        let closed = simulator.reduce_positions("market1", Some(trader), dec!(0.4), dec!(0.6));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].position.size, dec!(400));
+        assert_eq!(closed[0].pnl, dec!(40)); // (0.6 - 0.5) * 400
+        assert_eq!(simulator.positions()[0].size, dec!(600));
+
+        // Another trader's exit leaves our copy untouched
+        let closed = simulator.reduce_positions("market1", None, dec!(1), dec!(0.6));
+        assert!(closed.is_empty());
+
+        let closed = simulator.reduce_positions("market1", Some(trader), dec!(1), dec!(0.4));
+        assert_eq!(closed[0].pnl, dec!(-60)); // (0.4 - 0.5) * 600
+        assert!(simulator.positions().is_empty());
+        assert_eq!(simulator.balance(), dec!(9980));
     }
 }
@@ -1,68 +1,178 @@
 use crate::models::OrderSide;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::sync::Arc;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum SlippageModel {
-    Linear { depth_coefficient: Decimal },
-    Percentage { rate: Decimal },
-    MarketImpact { impact_param: Decimal },
-}
+/// How far a fill moves from the quoted price
+///
+/// The simulator, competition model and calibration all take a
+/// `&dyn SlippageModel`, so models beyond the built-in ones (square-root
+/// impact, per-market curves) can be ranked by `calibrate_with`.
+pub trait SlippageModel: Debug + Send + Sync {
+    /// Name shown in calibration reports
+    fn name(&self) -> &str;
 
-impl SlippageModel {
     /// Calculate the actual execution price including slippage
-    pub fn calculate_execution_price(
+    fn calculate_execution_price(
+        &self,
+        quote_price: Decimal,
+        size: Decimal,
+        side: &OrderSide,
+    ) -> Decimal;
+
+    /// Calculate slippage amount (difference from quote price)
+    fn calculate_slippage(&self, quote_price: Decimal, size: Decimal, side: &OrderSide) -> Decimal {
+        let execution_price = self.calculate_execution_price(quote_price, size, side);
+        (execution_price - quote_price).abs()
+    }
+
+    /// The same model with its price impact multiplied by `factor`
+    fn scaled(&self, factor: Decimal) -> Arc<dyn SlippageModel>;
+
+    /// The `[backtest.calibrated_slippage]` section reproducing this model,
+    /// when the config can express it
+    fn calibrated(&self) -> Option<CalibratedSlippage> {
+        None
+    }
+}
+
+/// impact = size / depth_coefficient
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinearSlippage {
+    pub depth_coefficient: Decimal,
+}
+
+impl Default for LinearSlippage {
+    fn default() -> Self {
+        Self {
+            depth_coefficient: Decimal::from(100000),
+        }
+    }
+}
+
+impl SlippageModel for LinearSlippage {
+    fn name(&self) -> &str {
+        "Linear"
+    }
+
+    fn calculate_execution_price(
         &self,
         quote_price: Decimal,
         size: Decimal,
         side: &OrderSide,
     ) -> Decimal {
-        match self {
-            SlippageModel::Linear { depth_coefficient } => {
-                let impact = size / *depth_coefficient;
-                match side {
-                    OrderSide::Buy => quote_price + impact,
-                    OrderSide::Sell => quote_price - impact,
-                }
-            }
-            SlippageModel::Percentage { rate } => {
-                match side {
-                    OrderSide::Buy => quote_price * (Decimal::ONE + rate),
-                    OrderSide::Sell => quote_price * (Decimal::ONE - rate),
-                }
-            }
-            SlippageModel::MarketImpact { impact_param } => {
-                // Logarithmic impact model
-                let size_f64 = size.to_string().parse::<f64>().unwrap_or(1.0);
-                let impact_value = impact_param.to_string().parse::<f64>().unwrap_or(0.001);
-                let impact = Decimal::from_f64_retain(impact_value * size_f64.ln())
-                    .unwrap_or(Decimal::ZERO);
-
-                match side {
-                    OrderSide::Buy => quote_price + impact,
-                    OrderSide::Sell => quote_price - impact,
-                }
-            }
+        adverse(quote_price, size / self.depth_coefficient, side)
+    }
+
+    fn scaled(&self, factor: Decimal) -> Arc<dyn SlippageModel> {
+        Arc::new(Self {
+            depth_coefficient: self.depth_coefficient / factor,
+        })
+    }
+
+    fn calibrated(&self) -> Option<CalibratedSlippage> {
+        Some(CalibratedSlippage::Linear(self.clone()))
+    }
+}
+
+/// impact = quote_price * rate
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PercentageSlippage {
+    pub rate: Decimal,
+}
+
+impl SlippageModel for PercentageSlippage {
+    fn name(&self) -> &str {
+        "Percentage"
+    }
+
+    fn calculate_execution_price(
+        &self,
+        quote_price: Decimal,
+        _size: Decimal,
+        side: &OrderSide,
+    ) -> Decimal {
+        match side {
+            OrderSide::Buy => quote_price * (Decimal::ONE + self.rate),
+            OrderSide::Sell => quote_price * (Decimal::ONE - self.rate),
         }
     }
 
-    /// Calculate slippage amount (difference from quote price)
-    pub fn calculate_slippage(
+    fn scaled(&self, factor: Decimal) -> Arc<dyn SlippageModel> {
+        Arc::new(Self {
+            rate: self.rate * factor,
+        })
+    }
+
+    fn calibrated(&self) -> Option<CalibratedSlippage> {
+        Some(CalibratedSlippage::Percentage(self.clone()))
+    }
+}
+
+/// impact = impact_param * ln(size)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarketImpactSlippage {
+    pub impact_param: Decimal,
+}
+
+impl SlippageModel for MarketImpactSlippage {
+    fn name(&self) -> &str {
+        "MarketImpact"
+    }
+
+    fn calculate_execution_price(
         &self,
         quote_price: Decimal,
         size: Decimal,
         side: &OrderSide,
     ) -> Decimal {
-        let execution_price = self.calculate_execution_price(quote_price, size, side);
-        (execution_price - quote_price).abs()
+        // Logarithmic impact model
+        let size_f64 = size.to_string().parse::<f64>().unwrap_or(1.0);
+        let impact_value = self
+            .impact_param
+            .to_string()
+            .parse::<f64>()
+            .unwrap_or(0.001);
+        let impact =
+            Decimal::from_f64_retain(impact_value * size_f64.ln()).unwrap_or(Decimal::ZERO);
+        adverse(quote_price, impact, side)
+    }
+
+    fn scaled(&self, factor: Decimal) -> Arc<dyn SlippageModel> {
+        Arc::new(Self {
+            impact_param: self.impact_param * factor,
+        })
+    }
+
+    fn calibrated(&self) -> Option<CalibratedSlippage> {
+        Some(CalibratedSlippage::MarketImpact(self.clone()))
     }
 }
 
-impl Default for SlippageModel {
-    fn default() -> Self {
-        SlippageModel::Linear {
-            depth_coefficient: Decimal::from(100000),
+/// Move `quote_price` by `impact` against the order
+fn adverse(quote_price: Decimal, impact: Decimal, side: &OrderSide) -> Decimal {
+    match side {
+        OrderSide::Buy => quote_price + impact,
+        OrderSide::Sell => quote_price - impact,
+    }
+}
+
+/// A built-in model as written in `[backtest.calibrated_slippage]`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CalibratedSlippage {
+    Linear(LinearSlippage),
+    Percentage(PercentageSlippage),
+    MarketImpact(MarketImpactSlippage),
+}
+
+impl CalibratedSlippage {
+    pub fn model(&self) -> Arc<dyn SlippageModel> {
+        match self {
+            CalibratedSlippage::Linear(model) => Arc::new(model.clone()),
+            CalibratedSlippage::Percentage(model) => Arc::new(model.clone()),
+            CalibratedSlippage::MarketImpact(model) => Arc::new(model.clone()),
         }
     }
 }
@@ -74,7 +184,7 @@ mod tests {
 
     #[test]
     fn test_linear_slippage() {
-        let model = SlippageModel::Linear {
+        let model = LinearSlippage {
             depth_coefficient: dec!(100000),
         };
 
@@ -89,7 +199,7 @@ mod tests {
 
     #[test]
     fn test_percentage_slippage() {
-        let model = SlippageModel::Percentage { rate: dec!(0.01) }; // 1% slippage
+        let model = PercentageSlippage { rate: dec!(0.01) }; // 1% slippage
 
         let price = model.calculate_execution_price(dec!(0.5), dec!(1000), &OrderSide::Buy);
         assert_eq!(price, dec!(0.505)); // 0.5 * 1.01
@@ -100,11 +210,22 @@ mod tests {
 
     #[test]
     fn test_slippage_calculation() {
-        let model = SlippageModel::Linear {
+        let model = LinearSlippage {
             depth_coefficient: dec!(100000),
         };
 
         let slippage = model.calculate_slippage(dec!(0.5), dec!(1000), &OrderSide::Buy);
         assert_eq!(slippage, dec!(0.01));
     }
+
+    #[test]
+    fn test_scaled_slippage() {
+        let model = LinearSlippage {
+            depth_coefficient: dec!(100000),
+        };
+
+        let doubled = model.scaled(dec!(2));
+        let slippage = doubled.calculate_slippage(dec!(0.5), dec!(1000), &OrderSide::Buy);
+        assert_eq!(slippage, dec!(0.02));
+    }
 }
@@ -0,0 +1,421 @@
+use crate::backtest::BacktestEngine;
+use crate::config::{BacktestConfig, PositionSizingConfig, TraderProfile};
+use crate::errors::{PolymarketError, Result};
+use crate::models::{abbreviate, BacktestResults, HistoricalTrade};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use tracing::{info, warn};
+
+/// One combination of parameters to evaluate in a sweep
+#[derive(Clone, Debug)]
+pub struct ParameterSet {
+    pub name: String,
+    pub backtest: BacktestConfig,
+    pub position_sizing: PositionSizingConfig,
+}
+
+impl ParameterSet {
+    /// The sets listed in a sweep file, each a `[[sets]]` entry with a `name`
+    /// and `[sets.backtest]` / `[sets.position_sizing]` keys overriding
+    /// `backtest` and `position_sizing`
+    pub fn load_all<P: AsRef<Path>>(
+        path: P,
+        backtest: &BacktestConfig,
+        position_sizing: &PositionSizingConfig,
+    ) -> Result<Vec<Self>> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            PolymarketError::ConfigError(format!("Failed to read sweep file: {}", e))
+        })?;
+        let file: toml::Table = toml::from_str(&content)?;
+        let Some(sets) = file.get("sets").and_then(|sets| sets.as_array()) else {
+            return Err(PolymarketError::ConfigError(
+                "sweep file lists no [[sets]]".to_string(),
+            ));
+        };
+
+        let mut parameter_sets = Vec::with_capacity(sets.len());
+        for (i, set) in sets.iter().enumerate() {
+            let name = set
+                .get("name")
+                .and_then(|name| name.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("set {}", i + 1));
+            let section = |key: &str| set.get(key).and_then(|section| section.as_table());
+            let overridden = |what: &str, e: String| {
+                PolymarketError::ConfigError(format!("{} of sweep set '{}': {}", what, name, e))
+            };
+            parameter_sets.push(ParameterSet {
+                backtest: with_overrides(backtest, section("backtest"))
+                    .map_err(|e| overridden("backtest", e))?,
+                position_sizing: with_overrides(position_sizing, section("position_sizing"))
+                    .map_err(|e| overridden("position_sizing", e))?,
+                name,
+            });
+        }
+        Ok(parameter_sets)
+    }
+}
+
+/// `base` with the keys in `overrides` replaced
+fn with_overrides<T: Serialize + DeserializeOwned>(
+    base: &T,
+    overrides: Option<&toml::Table>,
+) -> std::result::Result<T, String> {
+    let mut value = toml::Value::try_from(base).map_err(|e| e.to_string())?;
+    if let (Some(table), Some(overrides)) = (value.as_table_mut(), overrides) {
+        for (key, override_value) in overrides {
+            table.insert(key.clone(), override_value.clone());
+        }
+    }
+    value.try_into().map_err(|e: toml::de::Error| e.to_string())
+}
+
+/// Outcome of a single sweep run, in the same order as the input parameter sets
+#[derive(Clone, Debug)]
+pub struct SweepResult {
+    pub name: String,
+    pub results: std::result::Result<BacktestResults, String>,
+}
+
+/// Runs independent backtests concurrently over one shared, preloaded dataset
+pub struct BacktestSweep {
+    dataset: Arc<Vec<HistoricalTrade>>,
+    trader_profiles: Vec<TraderProfile>,
+    threads: usize,
+}
+
+impl BacktestSweep {
+    pub fn new(dataset: Arc<Vec<HistoricalTrade>>) -> Self {
+        let threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+
+        Self {
+            dataset,
+            trader_profiles: Vec::new(),
+            threads,
+        }
+    }
+
+    /// Load the dataset described by `config` (and any profile data files) once
+    pub fn load(config: &BacktestConfig, trader_profiles: &[TraderProfile]) -> Result<Self> {
+        let mut loader = BacktestEngine::new(
+            config.clone(),
+            PositionSizingConfig {
+                max_position_size_absolute: Default::default(),
+                max_position_size_relative: Default::default(),
+                strategy: "absolute".to_string(),
+                priority: "absolute".to_string(),
+                conviction: Default::default(),
+                volatility: Default::default(),
+                min_copy_size_usdc: Default::default(),
+                below_minimum: "skip".to_string(),
+                insufficient_balance: "fail".to_string(),
+            },
+        )
+        .with_trader_profiles(trader_profiles.to_vec());
+        let dataset = loader.load_dataset()?;
+        info!("Loaded {} trades for parameter sweep", dataset.len());
+
+        Ok(Self::new(Arc::new(dataset)).with_trader_profiles(trader_profiles.to_vec()))
+    }
+
+    pub fn with_trader_profiles(mut self, profiles: Vec<TraderProfile>) -> Self {
+        self.trader_profiles = profiles;
+        self
+    }
+
+    /// Number of worker threads (defaults to available parallelism)
+    #[cfg(test)]
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Run every parameter set, returning results in input order
+    ///
+    /// Data source settings in each set are ignored; all runs replay the shared dataset.
+    pub fn run(&self, parameter_sets: &[ParameterSet]) -> Vec<SweepResult> {
+        let next = AtomicUsize::new(0);
+        let workers = self.threads.min(parameter_sets.len()).max(1);
+        info!(
+            "Running {} backtests on {} threads",
+            parameter_sets.len(),
+            workers
+        );
+
+        let mut indexed: Vec<(usize, SweepResult)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(set) = parameter_sets.get(index) else {
+                                break;
+                            };
+                            done.push((index, self.run_one(set)));
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_default())
+                .collect()
+        });
+
+        indexed.sort_by_key(|(index, _)| *index);
+        indexed.into_iter().map(|(_, result)| result).collect()
+    }
+
+    fn run_one(&self, set: &ParameterSet) -> SweepResult {
+        let mut engine = BacktestEngine::new(set.backtest.clone(), set.position_sizing.clone())
+            .with_trader_profiles(self.trader_profiles.clone())
+            .with_shared_data(Arc::clone(&self.dataset));
+
+        // The engine never awaits I/O, so drive it to completion on this worker
+        let results = futures::executor::block_on(engine.run()).map_err(|e| {
+            warn!("Sweep run {} failed: {}", set.name, e);
+            e.to_string()
+        });
+
+        SweepResult {
+            name: set.name.clone(),
+            results,
+        }
+    }
+}
+
+/// Format successful sweep runs as a table, best ROI first
+pub fn format_sweep_table(results: &[SweepResult]) -> String {
+    let mut ranked: Vec<(&str, &BacktestResults)> = results
+        .iter()
+        .filter_map(|r| r.results.as_ref().ok().map(|res| (r.name.as_str(), res)))
+        .collect();
+    ranked.sort_by_key(|(_, res)| std::cmp::Reverse(res.roi));
+
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!("║ {:<60} ║\n", "PARAMETER SWEEP"));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<20} {:>7} {:>9} {:>11} {:>9} ║\n",
+        "Parameter Set", "Trades", "ROI", "Max DD", "Sharpe"
+    ));
+    for (name, res) in ranked {
+        table.push_str(&format!(
+            "║ {:<20} {:>7} {:>8}% {:>10}% {:>9} ║\n",
+            abbreviate(name, 20),
+            res.total_trades,
+            res.roi.round_dp(2),
+            res.max_drawdown.round_dp(2),
+            res.sharpe_ratio.round_dp(2),
+        ));
+    }
+    let failed = results.iter().filter(|r| r.results.is_err()).count();
+    if failed > 0 {
+        table.push_str(&format!(
+            "║ {:<60} ║\n",
+            format!("{} run(s) failed", failed)
+        ));
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+/// Format runs of different configurations side by side, in input order
+pub fn format_comparison_table(results: &[SweepResult]) -> String {
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!("║ {:<60} ║\n", "CONFIG COMPARISON"));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<13} {:>6} {:>7} {:>8} {:>7} {:>7} {:>6} ║\n",
+        "Config", "Trades", "Win", "P&L", "ROI", "Max DD", "Sharpe"
+    ));
+    for result in results {
+        let name = abbreviate(&result.name, 13);
+        match result.results {
+            Ok(ref res) => table.push_str(&format!(
+                "║ {:<13} {:>6} {:>6}% {:>8} {:>6}% {:>6}% {:>6} ║\n",
+                name,
+                res.total_trades,
+                res.win_rate.round_dp(1),
+                res.total_pnl.round_dp(2),
+                res.roi.round_dp(2),
+                res.max_drawdown.round_dp(2),
+                res.sharpe_ratio.round_dp(2),
+            )),
+            Err(ref e) => table.push_str(&format!(
+                "║ {:<13} {:<46} ║\n",
+                name,
+                abbreviate(&format!("failed: {}", e), 46)
+            )),
+        }
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CompetitionConfig, ConvictionConfig, VolatilityConfig};
+    use rust_decimal_macros::dec;
+
+    fn parameter_set(name: &str, max_absolute: rust_decimal::Decimal) -> ParameterSet {
+        ParameterSet {
+            name: name.to_string(),
+            backtest: BacktestConfig {
+                mode: "simulation".to_string(),
+                start_date: "2024-01-01".to_string(),
+                end_date: "2024-12-31".to_string(),
+                initial_balance_usdc: dec!(10000),
+                data_source: "polymarket_api".to_string(),
+                data_file: "".to_string(),
+                slippage_model: "linear".to_string(),
+                depth_coefficient: dec!(100000),
+                slippage_percentage: dec!(0.005),
+                apply_fees: false,
+                fee_rate_bps: 0,
+                apply_gas_costs: false,
+                estimated_gas_per_trade_usd: dec!(0.1),
+                default_liquidity: None,
+                unfilled_policy: "drop".to_string(),
+                mirror_exits: true,
+                chunk_size: 10_000,
+                scenarios: Vec::new(),
+                calibrated_slippage: None,
+                market_slippage_file: None,
+                competition: CompetitionConfig::default(),
+                end_of_backtest: "last_price".to_string(),
+            },
+            position_sizing: PositionSizingConfig {
+                max_position_size_absolute: max_absolute,
+                max_position_size_relative: dec!(0.1),
+                strategy: "hybrid".to_string(),
+                priority: "absolute".to_string(),
+                conviction: ConvictionConfig::default(),
+                volatility: VolatilityConfig::default(),
+                min_copy_size_usdc: dec!(0),
+                below_minimum: "skip".to_string(),
+                insufficient_balance: "fail".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_sweep_matches_individual_runs() {
+        let sets = vec![
+            parameter_set("small", dec!(100)),
+            parameter_set("medium", dec!(500)),
+            parameter_set("large", dec!(1000)),
+        ];
+        let sweep = BacktestSweep::load(&sets[0].backtest, &[])
+            .unwrap()
+            .with_threads(2);
+
+        let results = sweep.run(&sets);
+
+        assert_eq!(results.len(), 3);
+        for (set, result) in sets.iter().zip(&results) {
+            assert_eq!(result.name, set.name);
+            let swept = result.results.as_ref().unwrap();
+
+            let mut engine = BacktestEngine::new(set.backtest.clone(), set.position_sizing.clone());
+            let direct = futures::executor::block_on(engine.run()).unwrap();
+            assert_eq!(swept.total_trades, direct.total_trades);
+            assert_eq!(swept.final_balance, direct.final_balance);
+        }
+
+        let table = format_sweep_table(&results);
+        assert!(table.contains("medium"));
+    }
+
+    #[test]
+    fn test_comparison_table_keeps_input_order_and_failures() {
+        let set = parameter_set("aggressive", dec!(1000));
+        let mut engine = BacktestEngine::new(set.backtest, set.position_sizing);
+        let results = vec![
+            SweepResult {
+                name: "aggressive".to_string(),
+                results: Ok(futures::executor::block_on(engine.run()).unwrap()),
+            },
+            SweepResult {
+                name: "conservative".to_string(),
+                results: Err("Configuration error: no traders".to_string()),
+            },
+        ];
+
+        let table = format_comparison_table(&results);
+        let aggressive = table.find("aggressive").unwrap();
+        let conservative = table.find("conservative").unwrap();
+        assert!(aggressive < conservative);
+        assert!(table.contains("failed: Configuration error: no traders"));
+        assert!(table
+            .lines()
+            .all(|line| line.chars().count() == 64 || line.is_empty()));
+    }
+
+    #[test]
+    fn test_sweep_file_overrides_the_base_config() {
+        let base = parameter_set("base", dec!(100));
+        let path = std::env::temp_dir().join(format!("sweep-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"
+[[sets]]
+name = "baseline"
+
+[[sets]]
+name = "bigger"
+[sets.position_sizing]
+max_position_size_absolute = 250
+[sets.backtest]
+apply_fees = true
+fee_rate_bps = 20
+"#,
+        )
+        .unwrap();
+
+        let sets = ParameterSet::load_all(&path, &base.backtest, &base.position_sizing).unwrap();
+
+        assert_eq!(sets.len(), 2);
+        assert_eq!(sets[0].name, "baseline");
+        assert_eq!(
+            sets[0].position_sizing.max_position_size_absolute,
+            dec!(100)
+        );
+        assert!(!sets[0].backtest.apply_fees);
+        assert_eq!(sets[1].name, "bigger");
+        assert_eq!(
+            sets[1].position_sizing.max_position_size_absolute,
+            dec!(250)
+        );
+        assert_eq!(sets[1].position_sizing.strategy, "hybrid");
+        assert!(sets[1].backtest.apply_fees);
+        assert_eq!(sets[1].backtest.fee_rate_bps, 20);
+        assert_eq!(sets[1].backtest.initial_balance_usdc, dec!(10000));
+
+        std::fs::write(
+            &path,
+            "[[sets]]\nname = \"bad\"\n[sets.backtest]\napply_fees = \"yes\"\n",
+        )
+        .unwrap();
+        let err = ParameterSet::load_all(&path, &base.backtest, &base.position_sizing)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("sweep set 'bad'"), "{}", err);
+
+        std::fs::write(&path, "").unwrap();
+        assert!(ParameterSet::load_all(&path, &base.backtest, &base.position_sizing).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+use crate::config::{self, Config};
+use crate::errors::{PolymarketError, Result};
+use crate::execution::blacklist::{Blacklist, BlacklistEntry};
+use clap::{Args, Subcommand};
+use ethers::types::Address;
+
+#[derive(Subcommand, Debug)]
+pub enum BlacklistCommand {
+    /// Stop copying a market or trader, effective immediately in running bots
+    Add {
+        #[command(flatten)]
+        target: Target,
+
+        /// Note why, shown by `blacklist list`
+        #[arg(long)]
+        reason: Option<String>,
+
+        /// Also have the running bot sell positions in the market, or in every
+        /// market copied from the trader
+        #[arg(long)]
+        close: bool,
+    },
+
+    /// Copy a market or trader again
+    Remove {
+        #[command(flatten)]
+        target: Target,
+    },
+
+    /// Show blacklisted markets and traders
+    List,
+}
+
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct Target {
+    /// Market (outcome token id)
+    #[arg(long)]
+    market: Option<String>,
+
+    /// Trader address
+    #[arg(long)]
+    trader: Option<String>,
+}
+
+/// The configured blacklist file, or the default without a usable config
+fn blacklist(config_path: &str) -> Blacklist {
+    match Config::load_from_file(config_path) {
+        Ok(config) => Blacklist::new(config.general.blacklist_file),
+        Err(_) => Blacklist::new(config::default_blacklist_file()),
+    }
+}
+
+fn parse_trader(trader: &str) -> Result<Address> {
+    trader
+        .parse()
+        .map_err(|_| PolymarketError::ConfigError(format!("Invalid trader address: {}", trader)))
+}
+
+pub fn run(command: BlacklistCommand, config_path: &str) -> Result<()> {
+    let blacklist = blacklist(config_path);
+    match command {
+        BlacklistCommand::Add {
+            target,
+            reason,
+            close,
+        } => {
+            let entry = BlacklistEntry::new(reason, close);
+            let name = match (target.market, target.trader) {
+                (Some(market), _) => {
+                    blacklist.update(|state| state.markets.insert(market.clone(), entry))?;
+                    format!("market {}", market)
+                }
+                (None, Some(trader)) => {
+                    let address = parse_trader(&trader)?;
+                    blacklist.update(|state| state.traders.insert(address, entry))?;
+                    format!("trader {:?}", address)
+                }
+                (None, None) => unreachable!("clap requires a target"),
+            };
+            println!("Blacklisted {}; running bots stop copying it", name);
+            if close {
+                println!("Running bots will sell the related positions");
+            }
+        }
+        BlacklistCommand::Remove { target } => {
+            let removed = match (target.market, target.trader) {
+                (Some(market), _) => blacklist.update(|state| state.markets.remove(&market))?,
+                (None, Some(trader)) => {
+                    let address = parse_trader(&trader)?;
+                    blacklist.update(|state| state.traders.remove(&address))?
+                }
+                (None, None) => unreachable!("clap requires a target"),
+            };
+            if removed.is_some() {
+                println!("Removed from the blacklist");
+            } else {
+                println!("Not blacklisted");
+            }
+        }
+        BlacklistCommand::List => {
+            let state = blacklist.load()?;
+            if state.markets.is_empty() && state.traders.is_empty() {
+                println!("The blacklist is empty");
+            }
+            let entries = state
+                .markets
+                .iter()
+                .map(|(market, entry)| (format!("market {}", market), entry))
+                .chain(
+                    state
+                        .traders
+                        .iter()
+                        .map(|(trader, entry)| (format!("trader {:?}", trader), entry)),
+                );
+            for (name, entry) in entries {
+                println!(
+                    "{} (since {}){}{}",
+                    name,
+                    entry.added_at.format("%Y-%m-%d %H:%M"),
+                    entry
+                        .reason
+                        .as_ref()
+                        .map(|reason| format!(": {}", reason))
+                        .unwrap_or_default(),
+                    if entry.close_positions {
+                        " [closing positions]"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+    }
+    Ok(())
+}
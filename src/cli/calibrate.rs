@@ -0,0 +1,69 @@
+use crate::backtest::calibration::{calibrate, ExecutionSample};
+use crate::backtest::market_slippage::fit_books;
+use crate::errors::{PolymarketError, Result};
+use crate::storage::book_recorder::read_books;
+use crate::storage::TradeStore;
+use std::path::Path;
+use tracing::info;
+
+/// Fit slippage models to the executions in a trade log and print or save the best one
+pub async fn run(store: &dyn TradeStore, market: Option<&str>, output: Option<&str>) -> Result<()> {
+    let entries = store.entries().await?;
+    let samples: Vec<ExecutionSample> = entries
+        .iter()
+        .filter_map(ExecutionSample::from_log_entry)
+        .filter(|sample| market.is_none_or(|m| sample.market_id == m))
+        .collect();
+    info!(
+        "Calibrating from {} of {} log entries",
+        samples.len(),
+        entries.len()
+    );
+
+    let calibration = calibrate(&samples)?;
+    let section = calibration.to_toml().ok_or_else(|| {
+        PolymarketError::SimulationError(
+            "No slippage model fits the recorded executions (fills never worse than quotes)"
+                .to_string(),
+        )
+    })?;
+    write(&section, output)
+}
+
+/// Fit a slippage model per token to recorded order books and print or save
+/// the table
+pub fn run_books(directory: &str, output: Option<&str>) -> Result<()> {
+    let snapshots = read_books(Path::new(directory))?;
+    let table = fit_books(&snapshots);
+    info!(
+        "Fitted {} markets from {} book snapshots",
+        table.markets.len(),
+        snapshots.len()
+    );
+    if table.markets.is_empty() {
+        return Err(PolymarketError::SimulationError(format!(
+            "No recorded books with more than one price level in {}",
+            directory
+        )));
+    }
+    let table = toml::to_string(&table).map_err(|e| {
+        PolymarketError::ConfigError(format!("Failed to write slippage table: {}", e))
+    })?;
+    let content = format!(
+        "# Slippage fitted from {} recorded book snapshots\n{}",
+        snapshots.len(),
+        table
+    );
+    write(&content, output)
+}
+
+fn write(content: &str, output: Option<&str>) -> Result<()> {
+    match output {
+        Some(path) => {
+            std::fs::write(path, content)?;
+            println!("Calibrated slippage written to {}", path);
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::cli::trade_store;
+use crate::config::{Config, ExportConfig};
+use crate::errors::Result;
+use crate::storage::export::Exporter;
+use std::path::PathBuf;
+
+/// Export the trade history once, using [export] settings when a config is available
+pub async fn run(
+    log: Option<String>,
+    output: Option<String>,
+    no_sheets: bool,
+    config_path: &str,
+) -> Result<()> {
+    let config = Config::load_from_file(config_path)
+        .map(|config| config.export)
+        .unwrap_or_else(|_| ExportConfig::default());
+
+    let mut exporter = Exporter::new(trade_store(log, config_path).await?, &config)?;
+    if let Some(directory) = output {
+        exporter = exporter.with_directory(PathBuf::from(directory));
+    }
+    if no_sheets {
+        exporter = exporter.without_sheets();
+    }
+
+    let summary = exporter.run_once().await?;
+    println!(
+        "Exported {} trades to {}",
+        summary.rows,
+        summary.csv_path.display()
+    );
+    if summary.pushed_to_sheet {
+        println!("Google Sheet updated");
+    }
+
+    Ok(())
+}
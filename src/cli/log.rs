@@ -0,0 +1,221 @@
+use crate::cli::{trade_logger, trade_store};
+use crate::errors::{PolymarketError, Result};
+use crate::models::abbreviate;
+use crate::storage::query::{
+    error_type, group_by, group_by_label, skip_counts, EntryStatus, LogSummary, TradeLogQuery,
+};
+use crate::storage::schema::TRADE_LOG_SCHEMA_VERSION;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use ethers::types::Address;
+
+#[derive(Subcommand, Debug)]
+pub enum LogCommand {
+    /// Filter trade log entries and aggregate the matches
+    Query(QueryArgs),
+
+    /// Rewrite the trade log (including rotated files) at the current schema version
+    Migrate {
+        /// Trade log to migrate (defaults to the configured trade log)
+        #[arg(long)]
+        log: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct QueryArgs {
+    /// Trade log to query (defaults to the configured trade store)
+    #[arg(long)]
+    log: Option<String>,
+
+    /// Only entries logged at or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    from: Option<String>,
+
+    /// Only entries logged at or before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Only trades copied from this trader address
+    #[arg(long)]
+    trader: Option<String>,
+
+    /// Only trades in this market
+    #[arg(long)]
+    market: Option<String>,
+
+    /// Only entries with this outcome
+    #[arg(long, value_enum)]
+    status: Option<StatusArg>,
+
+    /// Only trades carrying this label
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Break totals down by this field
+    #[arg(long, value_enum)]
+    group_by: Option<GroupBy>,
+
+    /// Print matching entries as JSON lines instead of totals
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum StatusArg {
+    Success,
+    Failure,
+    Skipped,
+    Passed,
+    Detected,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum GroupBy {
+    Trader,
+    Market,
+    Error,
+    Status,
+    SkipReason,
+    /// Each label; trades with several labels count in each
+    Label,
+}
+
+pub async fn run(command: LogCommand, config_path: &str) -> Result<()> {
+    match command {
+        LogCommand::Query(args) => query(args, config_path).await,
+        LogCommand::Migrate { log } => {
+            let summary = trade_logger(log, config_path).migrate()?;
+            println!(
+                "Upgraded {} entries in {} file(s) to schema version {}",
+                summary.entries_upgraded, summary.files_rewritten, TRADE_LOG_SCHEMA_VERSION
+            );
+            if summary.corrupt_files.is_empty() {
+                return Ok(());
+            }
+            for reason in &summary.corrupt_files {
+                eprintln!("Left untouched, unreadable line at {}", reason);
+            }
+            Err(PolymarketError::StorageError(format!(
+                "{} file(s) could not be migrated",
+                summary.corrupt_files.len()
+            )))
+        }
+    }
+}
+
+async fn query(args: QueryArgs, config_path: &str) -> Result<()> {
+    let query = TradeLogQuery {
+        from: args
+            .from
+            .as_deref()
+            .map(|v| parse_bound(v, false))
+            .transpose()?,
+        to: args
+            .to
+            .as_deref()
+            .map(|v| parse_bound(v, true))
+            .transpose()?,
+        trader: args
+            .trader
+            .as_deref()
+            .map(|trader| {
+                trader.parse::<Address>().map_err(|e| {
+                    PolymarketError::ParseError(format!("Invalid address {}: {}", trader, e))
+                })
+            })
+            .transpose()?,
+        market: args.market,
+        status: args.status.map(|status| match status {
+            StatusArg::Success => EntryStatus::Success,
+            StatusArg::Failure => EntryStatus::Failure,
+            StatusArg::Skipped => EntryStatus::Skipped,
+            StatusArg::Passed => EntryStatus::Passed,
+            StatusArg::Detected => EntryStatus::Detected,
+        }),
+        label: args.label,
+    };
+
+    let entries = trade_store(args.log, config_path).await?.entries().await?;
+    let matched = query.apply(&entries);
+
+    if args.json {
+        for entry in matched {
+            println!("{}", serde_json::to_string(entry)?);
+        }
+        return Ok(());
+    }
+
+    print_summary("All matching entries", &LogSummary::from_entries(&matched));
+
+    let skips = skip_counts(&matched);
+    if !skips.is_empty() {
+        println!();
+        println!("Skip reasons");
+        for (reason, count) in skips {
+            println!("  {:<22} {:>6}", reason, count);
+        }
+    }
+
+    if let Some(field) = args.group_by {
+        let groups = match field {
+            GroupBy::Trader => group_by(&matched, |e| Some(format!("{:?}", e.trade.trader))),
+            GroupBy::Market => group_by(&matched, |e| Some(e.market_name())),
+            GroupBy::Error => group_by(&matched, error_type),
+            GroupBy::Status => {
+                group_by(&matched, |e| Some(EntryStatus::of(e).as_str().to_string()))
+            }
+            GroupBy::SkipReason => group_by(&matched, |e| e.skip_category()),
+            GroupBy::Label => group_by_label(&matched),
+        };
+
+        println!();
+        println!(
+            "{:<44} {:>8} {:>8} {:>8} {:>8} {:>8} {:>14}",
+            "Group", "Entries", "Success", "Failed", "Skipped", "Passed", "P&L (USDC)"
+        );
+        for (key, summary) in groups {
+            println!(
+                "{:<44} {:>8} {:>8} {:>8} {:>8} {:>8} {:>14}",
+                abbreviate(&key, 44),
+                summary.entries,
+                summary.successful,
+                summary.failed,
+                summary.skipped,
+                summary.passed,
+                summary.total_pnl.round_dp(2)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn print_summary(title: &str, summary: &LogSummary) {
+    println!("{}", title);
+    println!("  Entries:      {}", summary.entries);
+    println!("  Successful:   {}", summary.successful);
+    println!("  Failed:       {}", summary.failed);
+    println!("  Skipped:      {}", summary.skipped);
+    if summary.passed > 0 {
+        println!("  Passed:       {}", summary.passed);
+    }
+    println!("  Volume:       {} USDC", summary.total_volume.round_dp(2));
+    println!("  Total P&L:    {} USDC", summary.total_pnl.round_dp(2));
+}
+
+/// Parse a date or RFC 3339 timestamp; bare dates cover the whole day
+fn parse_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|e| PolymarketError::ParseError(format!("Invalid date {}: {}", value, e)))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    Ok(time.unwrap_or_default().and_utc())
+}
@@ -0,0 +1,69 @@
+use crate::config::{self, Config};
+use crate::errors::Result;
+use crate::monitoring::MarketDirectory;
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct MarketArgs {
+    /// Condition id (0x...) or market slug
+    market: String,
+}
+
+/// Print a market's outcomes, trading limits and top of book from the Gamma API
+pub async fn run(args: MarketArgs, config_path: &str) -> Result<()> {
+    let gamma_api_url = match Config::load_from_file(config_path) {
+        Ok(config) => config.general.gamma_api_url,
+        Err(_) => config::default_gamma_api_url(),
+    };
+    let market = MarketDirectory::new(gamma_api_url)
+        .find(&args.market)
+        .await?;
+    let show = |value: Option<rust_decimal::Decimal>| {
+        value
+            .map(|v| v.normalize().to_string())
+            .unwrap_or_else(|| "n/a".to_string())
+    };
+
+    println!("{}", market.question);
+    println!("Slug:          {}", market.slug);
+    println!("Condition id:  {}", market.condition_id);
+    if let Some(ref event) = market.event {
+        println!("Event:         {} ({})", event.title, event.id);
+    }
+    println!(
+        "Status:        {}",
+        market.untradable_reason().unwrap_or("accepting orders")
+    );
+    match market.end_date {
+        Some(end_date) => println!("End date:      {}", end_date.format("%Y-%m-%d %H:%M UTC")),
+        None => println!("End date:      n/a"),
+    }
+    println!("Tick size:     {}", show(market.tick_size));
+    println!("Min size:      {} shares", show(market.min_order_size));
+    println!(
+        "Best bid/ask:  {} / {}",
+        show(market.best_bid),
+        show(market.best_ask)
+    );
+    let spread = market
+        .best_bid
+        .zip(market.best_ask)
+        .map(|(bid, ask)| ask - bid);
+    println!("Spread:        {}", show(spread));
+    println!(
+        "24h volume:    {} USDC",
+        show(market.volume_24h.map(|v| v.round_dp(2)))
+    );
+
+    println!();
+    println!("{:<12} {:>8}  Token id", "Outcome", "Price");
+    for token in &market.tokens {
+        println!(
+            "{:<12} {:>8}  {}",
+            token.outcome,
+            show(token.price),
+            token.token_id
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,187 @@
+pub mod blacklist;
+pub mod calibrate;
+pub mod export;
+pub mod log;
+pub mod market;
+pub mod orders;
+pub mod pause;
+pub mod risk;
+pub mod setup;
+pub mod trader;
+
+use crate::config::Config;
+use crate::errors::Result;
+use crate::storage::{self, TradeLogger, TradeStore};
+use clap::Subcommand;
+use std::sync::Arc;
+
+/// Offline tools that run instead of live trading or backtesting
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check the wallet, API access, token approvals and traders, then write a config
+    Setup(setup::SetupArgs),
+
+    /// Fit backtest slippage parameters to recorded live executions
+    CalibrateSlippage {
+        /// Trade log containing executed trades (defaults to the configured trade store)
+        #[arg(long)]
+        log: Option<String>,
+
+        /// Only use executions in this market
+        #[arg(long)]
+        market: Option<String>,
+
+        /// Fit a model per market to the book recorder's CSVs in this
+        /// directory instead, for backtest.market_slippage_file
+        #[arg(long, conflicts_with_all = ["log", "market"])]
+        books: Option<String>,
+
+        /// Write the calibrated config section to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Export executed trades and P&L to CSV (and the configured Google Sheet)
+    Export {
+        /// Trade log to export (defaults to the configured trade store)
+        #[arg(long)]
+        log: Option<String>,
+
+        /// Directory for the CSV file (defaults to [export] directory)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Only write the CSV, even if a Google Sheet is configured
+        #[arg(long)]
+        no_sheets: bool,
+    },
+
+    /// List copy orders and their lifecycle
+    Orders(orders::OrdersArgs),
+
+    /// Realized P&L from the exchange's fills, checked against the order store
+    Fills {
+        /// How far back to look
+        #[arg(long, default_value_t = 24)]
+        hours: u64,
+    },
+
+    /// Cancel our resting orders on the exchange
+    CancelOrders {
+        /// Only orders on this outcome token
+        #[arg(long)]
+        market: Option<String>,
+    },
+
+    /// Inspect the trade log
+    Log {
+        #[command(subcommand)]
+        command: log::LogCommand,
+    },
+
+    /// Show a trader's positions, recent trades and performance
+    Trader(trader::TraderArgs),
+
+    /// Show a market's outcomes, trading limits and top of book
+    Market(market::MarketArgs),
+
+    /// Estimate value-at-risk of the current positions
+    Risk(risk::RiskArgs),
+
+    /// Stop live trading from opening new copies, including after restarts
+    Pause {
+        /// Note why, shown by `status`
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Let live trading copy trades again
+    Resume,
+
+    /// Reset the drawdown kill switch and resume copying
+    Rearm,
+
+    /// Show whether copying is paused
+    Status,
+
+    /// Serve a mock CLOB API that fills every order, to point a test config at
+    #[cfg(feature = "mock-clob")]
+    MockClob {
+        #[arg(long, default_value = "127.0.0.1:8900")]
+        listen: String,
+    },
+
+    /// Manage markets and traders that are never copied
+    Blacklist {
+        #[command(subcommand)]
+        command: blacklist::BlacklistCommand,
+    },
+}
+
+/// Run a CLI subcommand
+pub async fn run(command: Command, config_path: &str) -> Result<()> {
+    match command {
+        Command::Setup(args) => setup::run(args, config_path).await,
+        Command::CalibrateSlippage {
+            books: Some(books),
+            output,
+            ..
+        } => calibrate::run_books(&books, output.as_deref()),
+        Command::CalibrateSlippage {
+            log,
+            market,
+            books: None,
+            output,
+        } => {
+            let store = trade_store(log, config_path).await?;
+            calibrate::run(store.as_ref(), market.as_deref(), output.as_deref()).await
+        }
+        Command::Export {
+            log,
+            output,
+            no_sheets,
+        } => export::run(log, output, no_sheets, config_path).await,
+        Command::Orders(args) => orders::run(args, config_path).await,
+        Command::Fills { hours } => orders::fills(hours, config_path).await,
+        Command::CancelOrders { market } => orders::cancel(market, config_path).await,
+        Command::Log { command } => log::run(command, config_path).await,
+        Command::Trader(args) => trader::run(args, config_path).await,
+        Command::Market(args) => market::run(args, config_path).await,
+        Command::Risk(args) => risk::run(args, config_path).await,
+        Command::Pause { reason } => pause::pause(reason, config_path),
+        Command::Resume => pause::resume(config_path),
+        Command::Rearm => pause::rearm(config_path),
+        Command::Status => pause::status(config_path),
+        Command::Blacklist { command } => blacklist::run(command, config_path),
+        #[cfg(feature = "mock-clob")]
+        Command::MockClob { listen } => {
+            let mock = crate::execution::mock_clob::MockClob::bind(&listen).await?;
+            println!("Mock CLOB listening on {}", mock.url());
+            mock.run().await
+        }
+    }
+}
+
+/// Open the trade log given on the command line, or the one configured in
+/// `config_path` (falling back to `trades.jsonl` without a usable config)
+pub fn trade_logger(log: Option<String>, config_path: &str) -> TradeLogger {
+    if let Some(path) = log {
+        return TradeLogger::new(path);
+    }
+    match Config::load_from_file(config_path) {
+        Ok(config) => TradeLogger::from_config(&config.logging),
+        Err(_) => TradeLogger::new("trades.jsonl".to_string()),
+    }
+}
+
+/// Open the trade log given on the command line, or the trade store configured
+/// in `config_path` (which may be a database)
+pub async fn trade_store(log: Option<String>, config_path: &str) -> Result<Arc<dyn TradeStore>> {
+    if log.is_some() {
+        return Ok(Arc::new(trade_logger(log, config_path)));
+    }
+    match Config::load_from_file(config_path) {
+        Ok(config) => storage::open_store(&config).await,
+        Err(_) => Ok(Arc::new(trade_logger(None, config_path))),
+    }
+}
@@ -0,0 +1,232 @@
+use crate::config::Config;
+use crate::errors::Result;
+use crate::execution::credentials::CredentialRotation;
+use crate::execution::fills::{discrepancies, our_legs, realized_pnl};
+use crate::execution::lifecycle::{OrderRecord, OrderState};
+use crate::execution::quality::{execution_quality, Cost};
+use crate::execution::{ClobClient, OrderExecutor, OrderSigner, PositionSizer};
+use crate::models::abbreviate;
+use crate::storage::order_journal::OrderJournal;
+use crate::storage::{self, OrderStore};
+use chrono::Utc;
+use clap::{Args, ValueEnum};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+#[derive(Args, Debug)]
+pub struct OrdersArgs {
+    /// Order journal to read (defaults to the configured order store)
+    #[arg(long)]
+    log: Option<String>,
+
+    /// Only orders currently in this state
+    #[arg(long, value_enum)]
+    state: Option<StateArg>,
+
+    /// Only orders copying this detected trade
+    #[arg(long)]
+    trade: Option<String>,
+
+    /// Only orders that haven't reached a final state
+    #[arg(long)]
+    in_flight: bool,
+
+    /// Print every state change under each order
+    #[arg(long)]
+    history: bool,
+
+    /// Print matching orders as JSON lines
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum StateArg {
+    Created,
+    Submitted,
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Failed,
+}
+
+impl From<StateArg> for OrderState {
+    fn from(state: StateArg) -> Self {
+        match state {
+            StateArg::Created => OrderState::Created,
+            StateArg::Submitted => OrderState::Submitted,
+            StateArg::Open => OrderState::Open,
+            StateArg::PartiallyFilled => OrderState::PartiallyFilled,
+            StateArg::Filled => OrderState::Filled,
+            StateArg::Cancelled => OrderState::Cancelled,
+            StateArg::Failed => OrderState::Failed,
+        }
+    }
+}
+
+/// List recorded orders and their lifecycle
+pub async fn run(args: OrdersArgs, config_path: &str) -> Result<()> {
+    let store = order_store(args.log.clone(), config_path).await?;
+    let state = args.state.map(OrderState::from);
+    let orders: Vec<OrderRecord> = store
+        .orders()
+        .await?
+        .into_iter()
+        .filter(|order| state.is_none_or(|s| order.state == s))
+        .filter(|order| args.trade.as_ref().is_none_or(|t| &order.trade_id == t))
+        .filter(|order| !args.in_flight || !order.state.is_terminal())
+        .collect();
+
+    if args.json {
+        for order in &orders {
+            println!("{}", serde_json::to_string(order)?);
+        }
+        return Ok(());
+    }
+
+    println!(
+        "{:<36} {:<20} {:<5} {:>10} {:>10} {:<17} {:<25}",
+        "Order", "Market", "Side", "Size", "Filled", "State", "Updated"
+    );
+    for order in &orders {
+        println!(
+            "{:<36} {:<20} {:<5} {:>10} {:>10} {:<17} {:<25}",
+            order.id,
+            abbreviate(&order.market_id, 20),
+            order.side,
+            order.size.round_dp(2),
+            order.filled_size.round_dp(2),
+            order.state,
+            order.updated_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        if args.history {
+            for transition in &order.transitions {
+                println!(
+                    "    {} {}{}",
+                    transition.at.format("%Y-%m-%d %H:%M:%S%.3f"),
+                    transition.state,
+                    transition
+                        .detail
+                        .as_ref()
+                        .map(|d| format!(" ({})", d))
+                        .unwrap_or_default()
+                );
+            }
+        }
+    }
+    println!("{} order(s)", orders.len());
+
+    let quality = execution_quality(&orders);
+    if !quality.is_empty() {
+        println!();
+        println!("Execution quality (USDC per share beyond each reference; negative = better)");
+        println!(
+            "{:<8} {:>6} {:>10} {:>10} {:>10} {:>10} {:>14}",
+            "Style", "Fills", "Shares", "vs trader", "vs mid", "vs limit", "USDC vs trader"
+        );
+        let per_share = |cost: &Cost| {
+            cost.per_share()
+                .map_or("-".to_string(), |c| c.round_dp(4).to_string())
+        };
+        for (style, q) in &quality {
+            println!(
+                "{:<8} {:>6} {:>10} {:>10} {:>10} {:>10} {:>14}",
+                style,
+                q.fills,
+                q.shares.round_dp(2),
+                per_share(&q.vs_trader),
+                per_share(&q.vs_midpoint),
+                per_share(&q.vs_limit),
+                q.vs_trader.usdc.round_dp(2)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Cancel resting orders in one market or everywhere and record them as
+/// cancelled in the order store
+pub async fn cancel(market: Option<String>, config_path: &str) -> Result<()> {
+    let executor = executor(config_path).await?;
+    let cancelled = executor
+        .cancel_open_orders(market.as_deref(), "cancelled from the command line")
+        .await?;
+    println!("Cancelled {} order(s)", cancelled);
+    Ok(())
+}
+
+/// Realized P&L from the exchange's record of our fills over the last
+/// `hours`, and any orders whose recorded fill disagrees with it
+pub async fn fills(hours: u64, config_path: &str) -> Result<()> {
+    let executor = executor(config_path).await?;
+    let since = Utc::now() - chrono::Duration::hours(hours as i64);
+    let store = order_store(None, config_path).await?;
+    let orders: Vec<OrderRecord> = store
+        .orders()
+        .await?
+        .into_iter()
+        .filter(|order| order.created_at >= since)
+        .collect();
+    let trades = executor.clob_client().get_trades(Some(since)).await?;
+    let legs = our_legs(&trades, &orders);
+
+    println!("{:<20} {:>12}", "Market", "Realized");
+    let pnl = realized_pnl(&legs);
+    for (market_id, realized) in &pnl {
+        println!(
+            "{:<20} {:>12}",
+            abbreviate(market_id, 20),
+            realized.round_dp(2)
+        );
+    }
+    println!(
+        "{} fill(s), realized P&L {} USDC",
+        legs.len(),
+        pnl.values().sum::<Decimal>().round_dp(2)
+    );
+
+    let found = discrepancies(&trades, &orders);
+    if found.is_empty() {
+        println!("Recorded fills match the exchange");
+    }
+    for discrepancy in &found {
+        println!(
+            "MISMATCH order {} ({}): recorded {} shares, exchange matched {}",
+            discrepancy.order_id,
+            discrepancy.exchange_order_id,
+            discrepancy.recorded,
+            discrepancy.exchange
+        );
+    }
+    Ok(())
+}
+
+/// An executor signing with the configured wallet, recording to the
+/// configured order store
+async fn executor(config_path: &str) -> Result<OrderExecutor> {
+    let config = Config::load_from_file(config_path)?;
+    let signer = OrderSigner::from_config(&config.general, 137)?;
+    let rotation = CredentialRotation::new(&config.execution.api_credentials_file);
+    let clob_client = ClobClient::new(config.general.polymarket_api_url.clone(), signer)
+        .with_credential_rotation(Arc::new(rotation));
+    clob_client.load_api_credentials().await?;
+    Ok(OrderExecutor::new(
+        clob_client,
+        PositionSizer::new(config.position_sizing.clone()),
+        config.execution.clone(),
+    )
+    .with_order_store(storage::open_stores(&config).await?.orders))
+}
+
+/// The journal given on the command line, or the configured order store
+async fn order_store(log: Option<String>, config_path: &str) -> Result<Arc<dyn OrderStore>> {
+    if let Some(path) = log {
+        return Ok(Arc::new(OrderJournal::new(path)));
+    }
+    match Config::load_from_file(config_path) {
+        Ok(config) => Ok(storage::open_stores(&config).await?.orders),
+        Err(_) => Ok(Arc::new(OrderJournal::new("orders.jsonl".to_string()))),
+    }
+}
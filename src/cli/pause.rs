@@ -0,0 +1,126 @@
+use crate::config::{self, Config};
+use crate::errors::Result;
+use crate::execution::attribution::PnlStore;
+use crate::execution::drawdown::DrawdownStore;
+use crate::execution::pause::PauseSwitch;
+use rust_decimal::Decimal;
+
+/// The configured pause file and drawdown state, or the defaults without a usable config
+fn switches(config_path: &str) -> (PauseSwitch, DrawdownStore) {
+    match Config::load_from_file(config_path) {
+        Ok(config) => (
+            PauseSwitch::new(config.general.pause_file),
+            DrawdownStore::new(config.risk.drawdown_state_file),
+        ),
+        Err(_) => (
+            PauseSwitch::new(config::default_pause_file()),
+            DrawdownStore::new(config::default_drawdown_state_file()),
+        ),
+    }
+}
+
+/// The configured per-trader P&L snapshot, or the default
+fn pnl_store(config_path: &str) -> PnlStore {
+    match Config::load_from_file(config_path) {
+        Ok(config) => PnlStore::new(config.pnl.snapshot_file),
+        Err(_) => PnlStore::new(config::default_pnl_snapshot_file()),
+    }
+}
+
+pub fn pause(reason: Option<String>, config_path: &str) -> Result<()> {
+    let (switch, _) = switches(config_path);
+    if let Some(state) = switch.state() {
+        println!("Already paused since {}", state.paused_at);
+        return Ok(());
+    }
+    switch.pause(reason)?;
+    println!("Copying paused; running bots stop opening new copies");
+    Ok(())
+}
+
+pub fn resume(config_path: &str) -> Result<()> {
+    let (switch, drawdown) = switches(config_path);
+    if drawdown.load()?.tripped_at.is_some() {
+        println!("The drawdown kill switch has fired; run `rearm` to reset it and resume");
+        return Ok(());
+    }
+    if switch.resume()? {
+        println!("Copying resumed");
+    } else {
+        println!("Copying was not paused");
+    }
+    Ok(())
+}
+
+/// Reset the drawdown kill switch and its equity peak, then resume copying
+pub fn rearm(config_path: &str) -> Result<()> {
+    let (switch, drawdown) = switches(config_path);
+    let previous = drawdown.rearm()?;
+    switch.resume()?;
+    match previous.peak_equity {
+        Some(peak) => println!(
+            "Kill switch re-armed (previous peak {} USDC); copying resumed",
+            peak.round_dp(2)
+        ),
+        None => println!("Kill switch re-armed; copying resumed"),
+    }
+    Ok(())
+}
+
+pub fn status(config_path: &str) -> Result<()> {
+    let (switch, drawdown) = switches(config_path);
+    match switch.state() {
+        Some(state) => println!(
+            "Paused since {}{}",
+            state.paused_at,
+            state
+                .reason
+                .map(|reason| format!(": {}", reason))
+                .unwrap_or_default()
+        ),
+        None => println!("Copying is active"),
+    }
+    let drawdown = drawdown.load()?;
+    if let Some(peak) = drawdown.peak_equity {
+        println!("Equity peak: {} USDC", peak.round_dp(2));
+    }
+    if let Some(tripped_at) = drawdown.tripped_at {
+        println!("Drawdown kill switch fired at {}", tripped_at);
+    }
+
+    if let Some(snapshot) = pnl_store(config_path).load()? {
+        println!();
+        println!(
+            "P&L by trader{}",
+            snapshot
+                .updated_at
+                .map(|at| format!(" (as of {})", at.format("%Y-%m-%d %H:%M UTC")))
+                .unwrap_or_default()
+        );
+        println!(
+            "{:<44} {:>12} {:>12} {:>12} {:>6} {:>9}",
+            "Trader", "Realized", "Unrealized", "Total", "Open", "Win rate"
+        );
+        for row in &snapshot.traders {
+            println!(
+                "{:<44} {:>12} {:>12} {:>12} {:>6} {:>9}",
+                row.label(),
+                row.realized.round_dp(2),
+                row.unrealized.round_dp(2),
+                row.total().round_dp(2),
+                row.open_positions,
+                row.win_rate()
+                    .map(|rate| format!("{}%", (rate * Decimal::ONE_HUNDRED).round_dp(1)))
+                    .unwrap_or_else(|| "n/a".to_string())
+            );
+        }
+        if !snapshot.gas_usd.is_zero() {
+            println!(
+                "Gas paid: {} USD ({} USDC net of gas)",
+                snapshot.gas_usd.round_dp(2),
+                snapshot.net().round_dp(2)
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use crate::config::Config;
+use crate::errors::Result;
+use crate::execution::positions::holdings;
+use crate::execution::var::{estimate_var, Correlations, RiskPosition};
+use crate::models::abbreviate;
+use crate::monitoring::MarketDirectory;
+use crate::storage;
+use clap::Args;
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Args, Debug)]
+pub struct RiskArgs {
+    /// Confidence level (defaults to [risk] var_confidence)
+    #[arg(long)]
+    confidence: Option<Decimal>,
+
+    /// Print the estimate as JSON, e.g. for a metrics collector
+    #[arg(long)]
+    json: bool,
+}
+
+/// Estimate VaR and expected shortfall of the current positions
+pub async fn run(args: RiskArgs, config_path: &str) -> Result<()> {
+    let config = Config::load_from_file(config_path)?;
+    let orders = storage::open_stores(&config).await?.orders.orders().await?;
+    let markets = MarketDirectory::new(config.general.gamma_api_url.clone());
+
+    let mut positions = Vec::new();
+    let mut rows = Vec::new();
+    // Event id to its title, marked value and positions held in it
+    let mut events: BTreeMap<String, (String, Decimal, usize)> = BTreeMap::new();
+    for (token_id, holding) in holdings(&orders) {
+        let market = markets.market(&token_id).await.ok();
+        let quoted = market.as_ref().and_then(|m| {
+            m.tokens
+                .iter()
+                .find(|t| t.token_id == token_id)
+                .and_then(|t| t.price)
+        });
+        // Without a quote, assume the position is still worth what it cost
+        let price = quoted.unwrap_or_else(|| holding.average_price());
+        let name = market
+            .as_ref()
+            .map(|m| m.label(&token_id).to_string())
+            .unwrap_or_else(|| token_id.clone());
+        rows.push((name, holding.shares, price, quoted.is_none()));
+        if let Some(event) = market.as_ref().and_then(|m| m.event.as_ref()) {
+            let exposure = events
+                .entry(event.id.clone())
+                .or_insert_with(|| (event.title.clone(), Decimal::ZERO, 0));
+            exposure.1 += holding.shares * price;
+            exposure.2 += 1;
+        }
+        positions.push(RiskPosition {
+            shares: holding.shares,
+            price,
+            condition_id: market.as_ref().map(|m| m.condition_id.clone()),
+            event_id: market
+                .as_ref()
+                .and_then(|m| m.event.as_ref().map(|e| e.id.clone())),
+        });
+    }
+
+    let correlations = Correlations {
+        same_event: config.risk.var_event_correlation,
+        cross_event: config.risk.var_cross_event_correlation,
+    };
+    let confidence = args.confidence.unwrap_or(config.risk.var_confidence);
+    let estimate = estimate_var(&positions, confidence, correlations);
+
+    // Tags come with the full event, which the market lookups don't include
+    let mut by_event = Vec::new();
+    for (event_id, (title, value, count)) in events {
+        let tags = match markets.event(&event_id).await {
+            Ok(event) => event.tag_labels().join(", "),
+            Err(_) => String::new(),
+        };
+        by_event.push((event_id, title, tags, value, count));
+    }
+    by_event.sort_by_key(|(_, _, _, value, _)| std::cmp::Reverse(*value));
+
+    if args.json {
+        let events: Vec<_> = by_event
+            .iter()
+            .map(|(id, title, tags, value, count)| {
+                json!({ "id": id, "title": title, "tags": tags, "value": value, "positions": count })
+            })
+            .collect();
+        println!(
+            "{}",
+            json!({ "positions": positions.len(), "events": events, "estimate": estimate })
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:<50} {:>10} {:>8} {:>10}",
+        "Position", "Shares", "Price", "Value"
+    );
+    for (name, shares, price, stale) in &rows {
+        println!(
+            "{:<50} {:>10} {:>8} {:>10}{}",
+            abbreviate(name, 50),
+            shares.round_dp(2),
+            price.round_dp(3),
+            (shares * price).round_dp(2),
+            if *stale { "  (entry price)" } else { "" }
+        );
+    }
+    if !by_event.is_empty() {
+        println!();
+        println!("{:<50} {:>10} {:>10}  Tags", "Event", "Positions", "Value");
+        for (_, title, tags, value, count) in &by_event {
+            println!(
+                "{:<50} {:>10} {:>10}  {}",
+                abbreviate(title, 50),
+                count,
+                value.round_dp(2),
+                tags
+            );
+        }
+    }
+    println!();
+    println!("Marked value:        {} USDC", estimate.value);
+    println!(
+        "VaR ({}%):           {} USDC",
+        estimate.confidence * Decimal::ONE_HUNDRED,
+        estimate.value_at_risk
+    );
+    println!("Expected shortfall:  {} USDC", estimate.expected_shortfall);
+    println!(
+        "Correlations:        {} within an event, {} across events",
+        correlations.same_event, correlations.cross_event
+    );
+
+    Ok(())
+}
@@ -0,0 +1,335 @@
+use crate::config::{self, Config};
+use crate::errors::{PolymarketError, Result};
+use crate::execution::gas::GasTracker;
+use crate::execution::{approvals, safe, ClobClient, OrderSigner};
+use crate::monitoring::data_api::DataApiClient;
+use clap::Args;
+use ethers::types::Address;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// The documented example config the written config starts from
+const TEMPLATE: &str = include_str!("../../config.example.toml");
+
+#[derive(Args, Debug)]
+pub struct SetupArgs {
+    /// Environment variable holding the wallet's private key
+    #[arg(long, default_value = "WALLET_PK")]
+    key_env: String,
+
+    /// Environment variable holding a BIP-39 mnemonic, used instead of a key
+    #[arg(long, conflicts_with = "key_env")]
+    mnemonic_env: Option<String>,
+
+    /// Gnosis Safe holding the funds, with the wallet as an owner
+    #[arg(long)]
+    safe: Option<String>,
+
+    /// Trader to copy; repeat for several
+    #[arg(long = "trader")]
+    traders: Vec<String>,
+
+    /// Polygon RPC endpoint for the on-chain checks
+    #[arg(long, default_value = "https://polygon-rpc.com")]
+    rpc_url: String,
+
+    /// Polymarket CLOB API
+    #[arg(long, default_value = "https://clob.polymarket.com")]
+    api_url: String,
+
+    /// Send the missing token approvals without asking
+    #[arg(long)]
+    approve: bool,
+
+    /// Never prompt; fail when something needed isn't given as a flag
+    #[arg(long)]
+    yes: bool,
+
+    /// Where to write the config (defaults to --config)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Replace the output file if it exists
+    #[arg(long)]
+    force: bool,
+}
+
+/// What the written config is filled in with
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answers {
+    /// Config key and environment variable the wallet is read from
+    pub wallet: (&'static str, String),
+    pub safe: Option<Address>,
+    pub traders: Vec<Address>,
+    pub rpc_url: String,
+    pub api_url: String,
+}
+
+/// Gas settings of the config being replaced, if there is one
+fn gas_tracker(config_path: &str) -> GasTracker {
+    match Config::load_from_file(config_path) {
+        Ok(config) => GasTracker::new(&config.gas, &config.logging.gas_log_path),
+        Err(_) => GasTracker::new(&Default::default(), &config::default_gas_log_path()),
+    }
+}
+
+/// Check everything live trading needs and write a config for it
+pub async fn run(args: SetupArgs, config_path: &str) -> Result<()> {
+    let interactive = !args.yes && std::io::stdin().is_terminal();
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| config_path.to_string());
+    if Path::new(&output).exists()
+        && !args.force
+        && !(interactive && confirm(&format!("{} exists. Replace it?", output))?)
+    {
+        return Err(PolymarketError::ConfigError(format!(
+            "{} already exists; pass --force to replace it",
+            output
+        )));
+    }
+
+    // 1. Wallet
+    let (wallet_key, variable) = match args.mnemonic_env {
+        Some(ref variable) => ("wallet_mnemonic", variable.clone()),
+        None => ("wallet_private_key", args.key_env.clone()),
+    };
+    let secret = std::env::var(&variable).map_err(|_| {
+        PolymarketError::ConfigError(format!(
+            "Set {} to the wallet's {} before running setup",
+            variable,
+            if args.mnemonic_env.is_some() {
+                "mnemonic"
+            } else {
+                "private key"
+            }
+        ))
+    })?;
+    let mut signer = match args.mnemonic_env {
+        Some(_) => OrderSigner::from_mnemonic(&secret, &config::default_derivation_path(), 0, 137)?,
+        None => OrderSigner::new(&secret, 137)?,
+    };
+    println!("Wallet: {:?}", signer.address());
+
+    let safe = match args.safe {
+        Some(ref address) => Some(parse_address("Safe", address)?),
+        None if interactive => {
+            let answer = prompt("Gnosis Safe holding the funds (blank for none)")?;
+            (!answer.is_empty())
+                .then(|| parse_address("Safe", &answer))
+                .transpose()?
+        }
+        None => None,
+    };
+    if let Some(safe) = safe {
+        signer = signer.with_safe(safe)?;
+        safe::verify_safe(&args.rpc_url, safe, signer.address()).await?;
+        println!("Safe: {:?} (wallet is an owner)", safe);
+    }
+    let maker = signer.maker();
+    let wallet = signer.wallet();
+
+    // 2. CLOB API
+    let client = ClobClient::new(args.api_url.clone(), signer);
+    let skew = client.check_connection(Duration::from_secs(10)).await?;
+    println!(
+        "CLOB API: reachable, clock {}s off server time",
+        skew.num_seconds()
+    );
+    let credentials = client.derive_api_key().await?;
+    println!("CLOB API credentials: ok (key {})", credentials.api_key);
+
+    // 3. Token approvals
+    let approvals = approvals::check(&args.rpc_url, maker).await?;
+    for approval in &approvals {
+        println!(
+            "Approval of {} for the {}: {}",
+            approval.token,
+            approval.spender_name,
+            if approval.granted { "ok" } else { "missing" }
+        );
+    }
+    let missing = approvals.iter().filter(|a| !a.granted).count();
+    if missing > 0 {
+        if safe.is_some() {
+            println!(
+                "Grant the missing approvals from the Safe (Polymarket does this when you first \
+                 deposit); the wallet can't approve on its behalf"
+            );
+        } else if args.approve
+            || (interactive
+                && confirm(&format!(
+                    "Send {} approval transactions from the wallet (needs POL for gas)?",
+                    missing
+                ))?)
+        {
+            approvals::grant(&args.rpc_url, wallet, &approvals, &gas_tracker(config_path)).await?;
+            println!("Approvals granted");
+        } else {
+            println!("Skipping approvals; orders will fail until they are granted");
+        }
+    }
+
+    // 4. Traders
+    let mut traders = args
+        .traders
+        .iter()
+        .map(|address| parse_address("trader", address))
+        .collect::<Result<Vec<_>>>()?;
+    if traders.is_empty() && interactive {
+        let answer = prompt("Trader addresses to copy (comma-separated)")?;
+        for address in answer.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            traders.push(parse_address("trader", address)?);
+        }
+    }
+    let data_api = DataApiClient::new(config::default_data_api_url());
+    let mut active = 0;
+    for trader in &traders {
+        match data_api.trades(*trader, 1).await {
+            Ok(trades) if !trades.is_empty() => {
+                active += 1;
+                println!("Trader {:?}: ok", trader);
+            }
+            Ok(_) => println!("Trader {:?}: no trades on Polymarket", trader),
+            Err(e) => println!("Trader {:?}: lookup failed: {}", trader, e),
+        }
+    }
+    if active == 0 {
+        return Err(PolymarketError::ConfigError(
+            "None of the traders has traded on Polymarket; give at least one active trader \
+             with --trader"
+                .to_string(),
+        ));
+    }
+
+    // 5. Config
+    let answers = Answers {
+        wallet: (wallet_key, variable),
+        safe,
+        traders,
+        rpc_url: args.rpc_url,
+        api_url: args.api_url,
+    };
+    std::fs::write(&output, render_config(TEMPLATE, &answers))?;
+    Config::load_from_file(&output)?;
+    println!();
+    println!("Wrote {}", output);
+    println!(
+        "Review the position sizing and risk limits in it, then start with --config {}",
+        output
+    );
+    Ok(())
+}
+
+/// `template` with the mode set to live and `[general]` and `[traders]`
+/// filled in from `answers`
+pub fn render_config(template: &str, answers: &Answers) -> String {
+    let mut lines: Vec<String> = template.lines().map(str::to_string).collect();
+    let (wallet_key, variable) = &answers.wallet;
+    set_key(&mut lines, "mode", "\"live\"");
+    set_key(&mut lines, "wallet_private_key", "\"\"");
+    set_key(&mut lines, wallet_key, &format!("\"${{{}}}\"", variable));
+    set_key(
+        &mut lines,
+        "polygon_rpc_url",
+        &format!("\"{}\"", answers.rpc_url),
+    );
+    set_key(
+        &mut lines,
+        "polymarket_api_url",
+        &format!("\"{}\"", answers.api_url),
+    );
+    if let Some(safe) = answers.safe {
+        set_key(&mut lines, "safe_address", &format!("\"{:?}\"", safe));
+    }
+
+    if let Some(start) = lines.iter().position(|l| l.starts_with("tracked_accounts")) {
+        let end = lines[start..]
+            .iter()
+            .position(|l| l.trim_end().ends_with(']'))
+            .map_or(start, |offset| start + offset);
+        let mut accounts = vec!["tracked_accounts = [".to_string()];
+        accounts.extend(answers.traders.iter().map(|t| format!("    \"{:?}\",", t)));
+        accounts.push("]".to_string());
+        lines.splice(start..=end, accounts);
+    }
+
+    lines.retain(|l| l != "wallet_private_key = \"\"");
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+/// Set the first `key = ...` line, uncommenting a `# key = ...` example
+fn set_key(lines: &mut [String], key: &str, value: &str) {
+    let prefix = format!("{} =", key);
+    let commented = format!("# {}", prefix);
+    if let Some(line) = lines
+        .iter_mut()
+        .find(|l| l.starts_with(&prefix) || l.starts_with(&commented))
+    {
+        *line = format!("{} {}", prefix, value);
+    }
+}
+
+fn parse_address(what: &str, address: &str) -> Result<Address> {
+    address
+        .trim()
+        .parse()
+        .map_err(|_| PolymarketError::ConfigError(format!("Invalid {} address: {}", what, address)))
+}
+
+fn prompt(question: &str) -> Result<String> {
+    print!("{}: ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    let answer = prompt(&format!("{} [y/N]", question))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_rendered_from_template() {
+        let safe = Address::from_low_u64_be(0x5afe);
+        let answers = Answers {
+            wallet: ("wallet_mnemonic", "SEED".to_string()),
+            safe: Some(safe),
+            traders: vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)],
+            rpc_url: "https://rpc.example".to_string(),
+            api_url: "https://clob.polymarket.com".to_string(),
+        };
+        let content = render_config(TEMPLATE, &answers);
+
+        let value: toml::Value = toml::from_str(&content).unwrap();
+        let general = &value["general"];
+        assert_eq!(general["mode"].as_str(), Some("live"));
+        assert_eq!(general["wallet_mnemonic"].as_str(), Some("${SEED}"));
+        assert!(general.get("wallet_private_key").is_none());
+        assert_eq!(
+            general["safe_address"].as_str(),
+            Some(format!("{:?}", safe).as_str())
+        );
+        assert_eq!(
+            general["polygon_rpc_url"].as_str(),
+            Some("https://rpc.example")
+        );
+        let traders = value["traders"]["tracked_accounts"].as_array().unwrap();
+        assert_eq!(traders.len(), 2);
+        assert_eq!(
+            traders[1].as_str(),
+            Some("0x0000000000000000000000000000000000000002")
+        );
+        // Later sections keep their own keys of the same name
+        assert_eq!(value["backtest"]["mode"].as_str(), Some("simulation"));
+    }
+}
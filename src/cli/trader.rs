@@ -0,0 +1,161 @@
+use crate::config::{self, Config};
+use crate::endpoints::Endpoints;
+use crate::errors::{PolymarketError, Result};
+use crate::models::abbreviate;
+use crate::monitoring::data_api::DataApiClient;
+use crate::monitoring::stats::trader_performance;
+use chrono::{Duration, Utc};
+use clap::Args;
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Args, Debug)]
+pub struct TraderArgs {
+    /// Trader address, tracked or not
+    address: String,
+
+    /// How many of the trader's most recent trades to fetch
+    #[arg(long, default_value_t = 500)]
+    limit: usize,
+
+    /// Window for win rate and ROI, in days
+    #[arg(long, default_value_t = 30)]
+    days: i64,
+
+    /// Recent trades to list
+    #[arg(long, default_value_t = 10)]
+    show: usize,
+}
+
+/// Print a trader's positions, recent trades and performance from the data API
+pub async fn run(args: TraderArgs, config_path: &str) -> Result<()> {
+    let trader: Address = args.address.parse().map_err(|_| {
+        PolymarketError::ConfigError(format!("Invalid trader address: {}", args.address))
+    })?;
+    let (endpoints, tracked) = match Config::load_from_file(config_path) {
+        Ok(config) => (
+            Endpoints::new(
+                "data",
+                config.general.data_api_url.clone(),
+                &config.failover.data_api_urls,
+                &config.failover,
+            ),
+            config.traders.get_addresses()?.contains(&trader),
+        ),
+        Err(_) => (
+            Endpoints::single("data", config::default_data_api_url()),
+            false,
+        ),
+    };
+    let client = DataApiClient::new(endpoints.url()).with_endpoints(Arc::new(endpoints));
+    let (positions, trades) =
+        tokio::try_join!(client.positions(trader), client.trades(trader, args.limit))?;
+
+    println!(
+        "Trader {:?} ({})",
+        trader,
+        if tracked { "tracked" } else { "not tracked" }
+    );
+
+    println!();
+    println!("Open positions: {}", positions.len());
+    if !positions.is_empty() {
+        println!(
+            "{:<50} {:>10} {:>8} {:>8} {:>10}",
+            "Market", "Shares", "Entry", "Price", "P&L"
+        );
+    }
+    for position in &positions {
+        println!(
+            "{:<50} {:>10} {:>8} {:>8} {:>10}",
+            abbreviate(&position.label().to_string(), 50),
+            position.size.round_dp(2),
+            position.avg_price.round_dp(3),
+            position.cur_price.round_dp(3),
+            position.cash_pnl.round_dp(2)
+        );
+    }
+
+    println!();
+    println!("Recent trades:");
+    for trade in trades.iter().take(args.show) {
+        let copy = trade.to_trade();
+        println!(
+            "{}  {:<4} {:>10} @ {:<6} {}",
+            copy.timestamp.format("%Y-%m-%d %H:%M"),
+            copy.side,
+            copy.size.round_dp(2),
+            copy.price.round_dp(3),
+            abbreviate(&trade.label().to_string(), 50)
+        );
+    }
+
+    // Open shares are marked at the data API's current price, and settled at
+    // it once the market has resolved
+    let prices: HashMap<&str, (Decimal, bool)> = positions
+        .iter()
+        .map(|p| (p.asset.as_str(), (p.cur_price, p.redeemable)))
+        .collect();
+    let history: Vec<_> = trades.iter().map(|t| t.to_trade()).collect();
+    let since = Utc::now() - Duration::days(args.days);
+    let stats = trader_performance(
+        &history,
+        since,
+        |market| prices.get(market).map(|(price, _)| *price),
+        |market| {
+            prices
+                .get(market)
+                .and_then(|(price, redeemable)| redeemable.then_some(*price))
+        },
+    )
+    .remove(&trader)
+    .unwrap_or_default();
+    let mut markets: Vec<String> = Vec::new();
+    for (trade, _) in trades
+        .iter()
+        .zip(&history)
+        .filter(|(_, copy)| copy.timestamp >= since)
+    {
+        let name = if trade.title.is_empty() {
+            trade.asset.clone()
+        } else {
+            trade.title.clone()
+        };
+        if !markets.contains(&name) {
+            markets.push(name);
+        }
+    }
+
+    println!();
+    println!("Last {} days ({} trades fetched):", args.days, trades.len());
+    println!("Trades:     {}", stats.trades);
+    println!("Invested:   {} USDC", stats.invested.round_dp(2));
+    println!("P&L:        {} USDC", stats.pnl.round_dp(2));
+    match stats.roi() {
+        Some(roi) => println!("ROI:        {}%", (roi * Decimal::ONE_HUNDRED).round_dp(2)),
+        None => println!("ROI:        n/a"),
+    }
+    match stats.win_rate() {
+        Some(rate) => println!(
+            "Win rate:   {}% of {} closed trades",
+            (rate * Decimal::ONE_HUNDRED).round_dp(2),
+            stats.closed
+        ),
+        None => println!("Win rate:   n/a (no closed trades)"),
+    }
+    println!("Markets:    {}", markets.len());
+    for market in &markets {
+        println!("  {}", abbreviate(market, 76));
+    }
+    if trades.len() == args.limit {
+        println!();
+        println!(
+            "Only the latest {} trades were fetched; raise --limit for more history",
+            args.limit
+        );
+    }
+
+    Ok(())
+}
@@ -1,7 +1,13 @@
+use crate::backtest::slippage::CalibratedSlippage;
 use crate::errors::{PolymarketError, Result};
+use crate::execution::queue::Priority;
+use crate::monitoring::onboarding::OnboardingPolicy;
+use crate::monitoring::trade_feed::DetectionMode;
+use chrono::{DateTime, Utc};
 use ethers::types::Address;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
 
@@ -16,30 +22,393 @@ pub struct Config {
     pub database: DatabaseConfig,
     #[serde(default)]
     pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub coordination: CoordinationConfig,
+    #[serde(default)]
+    pub prices: PricesConfig,
+    #[serde(default)]
+    pub risk: RiskConfig,
+    #[serde(default)]
+    pub wash_trading: WashTradingConfig,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub recorder: RecorderConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub error_budget: ErrorBudgetConfig,
+    #[serde(default)]
+    pub pnl: PnlConfig,
+    #[serde(default)]
+    pub resolutions: ResolutionsConfig,
+    #[serde(default)]
+    pub publishing: PublishingConfig,
+    #[serde(default)]
+    pub signals: SignalsConfig,
+    #[serde(default)]
+    pub gas: GasConfig,
+    /// Copy strategies run alongside the top-level one
+    #[serde(default)]
+    pub strategies: Vec<StrategyConfig>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GeneralConfig {
     pub mode: String,
+    /// Set from `wallet_private_key_file` when that is given instead
+    #[serde(default)]
     pub wallet_private_key: String,
+    /// Read the key from this file (e.g. a mounted Docker or Kubernetes
+    /// secret), and again whenever the file changes
+    #[serde(default)]
+    pub wallet_private_key_file: Option<String>,
+    /// BIP-39 seed phrase to derive the wallet from, instead of a raw key;
+    /// set from `wallet_mnemonic_file` when that is given instead
+    #[serde(default)]
+    pub wallet_mnemonic: Option<String>,
+    #[serde(default)]
+    pub wallet_mnemonic_file: Option<String>,
+    /// Path the account index is appended to when deriving from the mnemonic
+    #[serde(default = "default_derivation_path")]
+    pub wallet_derivation_path: String,
+    #[serde(default)]
+    pub wallet_account_index: u32,
+    /// Gnosis Safe holding the funds, with the wallet above as one of its
+    /// owners signing orders for it
+    #[serde(default)]
+    pub safe_address: Option<String>,
     pub polygon_rpc_url: String,
     pub polymarket_api_url: String,
+    /// Gamma API for market metadata (questions, outcome tokens, status)
+    #[serde(default = "default_gamma_api_url")]
+    pub gamma_api_url: String,
+    /// Data API for any wallet's positions and trade history
+    #[serde(default = "default_data_api_url")]
+    pub data_api_url: String,
+    /// While this file exists, live trading doesn't open new copies
+    #[serde(default = "default_pause_file")]
+    pub pause_file: String,
+    /// Markets and traders never to copy, managed with the `blacklist` command
+    #[serde(default = "default_blacklist_file")]
+    pub blacklist_file: String,
+    /// Labels put on every trade this configuration copies, such as its
+    /// strategy name, to report P&L by
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+pub(crate) fn default_derivation_path() -> String {
+    "m/44'/60'/0'/0".to_string()
+}
+
+impl GeneralConfig {
+    pub fn safe_address(&self) -> Result<Option<Address>> {
+        self.safe_address
+            .as_deref()
+            .map(|address| {
+                address.parse::<Address>().map_err(|e| {
+                    PolymarketError::ConfigError(format!("Invalid safe_address {}: {}", address, e))
+                })
+            })
+            .transpose()
+    }
+}
+
+pub(crate) fn default_gamma_api_url() -> String {
+    "https://gamma-api.polymarket.com".to_string()
+}
+
+pub(crate) fn default_data_api_url() -> String {
+    "https://data-api.polymarket.com".to_string()
+}
+
+pub(crate) fn default_pause_file() -> String {
+    "copy-trader.paused".to_string()
+}
+
+pub(crate) fn default_blacklist_file() -> String {
+    "blacklist.json".to_string()
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TradersConfig {
     pub tracked_accounts: Vec<String>,
+    /// Optional per-trader copy weights and filters
+    #[serde(default)]
+    pub profiles: Vec<TraderProfile>,
+    /// Scale copy ratios by each trader's recent performance
+    #[serde(default)]
+    pub weighting: WeightingConfig,
+    /// Suspend traders whose recent results fall below thresholds
+    #[serde(default)]
+    pub pruning: PruningConfig,
+    /// Re-read the config file this often and start or stop tracking the
+    /// traders added to or removed from `tracked_accounts`; 0 turns it off
+    #[serde(default)]
+    pub reload_interval_seconds: u64,
+    /// What to do with the positions a newly tracked trader already holds:
+    /// "ignore" them, "copy_all" at current prices, or "copy_recent" to copy
+    /// those opened in the last `onboarding_days`
+    #[serde(default = "default_onboarding")]
+    pub onboarding: String,
+    #[serde(default = "default_onboarding_days")]
+    pub onboarding_days: u64,
+    /// Traders already onboarded, so a restart doesn't copy their book again
+    #[serde(default = "default_onboarded_file")]
+    pub onboarded_file: String,
+}
+
+fn default_onboarding() -> String {
+    "ignore".to_string()
+}
+
+fn default_onboarding_days() -> u64 {
+    7
+}
+
+fn default_onboarded_file() -> String {
+    "onboarded_traders.json".to_string()
+}
+
+/// Adaptive copy ratios from each trader's trailing performance
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WeightingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_weighting_lookback_days")]
+    pub lookback_days: u64,
+    /// Trades needed in the window before a trader's weight moves off 1
+    #[serde(default = "default_weighting_min_trades")]
+    pub min_trades: usize,
+    /// Weight change per unit of ROI (2 turns a -10% ROI into a 0.8 weight)
+    #[serde(default = "default_weighting_sensitivity")]
+    pub sensitivity: Decimal,
+    #[serde(default = "default_min_weight")]
+    pub min_weight: Decimal,
+    #[serde(default = "default_max_weight")]
+    pub max_weight: Decimal,
+    #[serde(default = "default_weighting_refresh_minutes")]
+    pub refresh_minutes: u64,
+}
+
+impl Default for WeightingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lookback_days: default_weighting_lookback_days(),
+            min_trades: default_weighting_min_trades(),
+            sensitivity: default_weighting_sensitivity(),
+            min_weight: default_min_weight(),
+            max_weight: default_max_weight(),
+            refresh_minutes: default_weighting_refresh_minutes(),
+        }
+    }
+}
+
+fn default_weighting_lookback_days() -> u64 {
+    14
+}
+
+fn default_weighting_min_trades() -> usize {
+    5
+}
+
+fn default_weighting_sensitivity() -> Decimal {
+    Decimal::TWO
+}
+
+fn default_min_weight() -> Decimal {
+    Decimal::new(25, 2)
+}
+
+fn default_max_weight() -> Decimal {
+    Decimal::new(15, 1)
+}
+
+fn default_weighting_refresh_minutes() -> u64 {
+    60
+}
+
+/// Automatic suspension of underperforming traders
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PruningConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_pruning_lookback_days")]
+    pub lookback_days: u64,
+    /// Suspend below this ROI (-0.2 = -20%); unset disables the rule
+    #[serde(default)]
+    pub min_roi: Option<Decimal>,
+    /// Trades needed in the window before the ROI rule applies
+    #[serde(default = "default_pruning_min_trades")]
+    pub min_trades: usize,
+    /// Suspend below this win rate (0.4 = 40%); unset disables the rule
+    #[serde(default)]
+    pub min_win_rate: Option<Decimal>,
+    /// Closed trades needed in the window before the win rate rule applies
+    #[serde(default = "default_pruning_min_closed_trades")]
+    pub min_closed_trades: usize,
+    /// How long a suspension lasts before the trader is copied again
+    #[serde(default = "default_probation_days")]
+    pub probation_days: u64,
+    #[serde(default = "default_pruning_check_interval_minutes")]
+    pub check_interval_minutes: u64,
+    #[serde(default = "default_pruning_state_file")]
+    pub state_file: String,
+}
+
+impl Default for PruningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lookback_days: default_pruning_lookback_days(),
+            min_roi: None,
+            min_trades: default_pruning_min_trades(),
+            min_win_rate: None,
+            min_closed_trades: default_pruning_min_closed_trades(),
+            probation_days: default_probation_days(),
+            check_interval_minutes: default_pruning_check_interval_minutes(),
+            state_file: default_pruning_state_file(),
+        }
+    }
+}
+
+fn default_pruning_lookback_days() -> u64 {
+    30
+}
+
+fn default_pruning_min_trades() -> usize {
+    5
+}
+
+fn default_pruning_min_closed_trades() -> usize {
+    10
+}
+
+fn default_probation_days() -> u64 {
+    7
+}
+
+fn default_pruning_check_interval_minutes() -> u64 {
+    60
+}
+
+fn default_pruning_state_file() -> String {
+    "pruned_traders.json".to_string()
+}
+
+/// Per-trader copy settings, keyed by trader address
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraderProfile {
+    pub address: String,
+    /// Multiplier applied to this trader's trade size before position sizing
+    #[serde(default = "default_copy_ratio")]
+    pub copy_ratio: Decimal,
+    #[serde(default)]
+    pub min_trade_size_usdc: Option<Decimal>,
+    #[serde(default)]
+    pub max_trade_size_usdc: Option<Decimal>,
+    /// Only copy trades in these markets (all markets when unset)
+    #[serde(default)]
+    pub allowed_markets: Option<Vec<String>>,
+    /// Backtest dataset containing this trader's historical trades (CSV)
+    #[serde(default)]
+    pub data_file: Option<String>,
+    /// Competing copy bots following this trader in backtests, overriding
+    /// `[backtest.competition] copiers`
+    #[serde(default)]
+    pub competing_copiers: Option<u32>,
+    /// Onboarding policy for this trader, overriding `[traders] onboarding`
+    #[serde(default)]
+    pub onboarding: Option<String>,
+    #[serde(default)]
+    pub onboarding_days: Option<u64>,
+    /// Labels put on this trader's trades
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+fn default_copy_ratio() -> Decimal {
+    Decimal::ONE
+}
+
+impl TraderProfile {
+    pub fn get_address(&self) -> Result<Address> {
+        self.address.parse::<Address>().map_err(|e| {
+            PolymarketError::ParseError(format!("Invalid address {}: {}", self.address, e))
+        })
+    }
 }
 
 impl TradersConfig {
     pub fn get_addresses(&self) -> Result<Vec<Address>> {
-        self.tracked_accounts
-            .iter()
-            .map(|addr| {
-                addr.parse::<Address>()
-                    .map_err(|e| PolymarketError::ParseError(format!("Invalid address {}: {}", addr, e)))
+        parse_addresses(&self.tracked_accounts)
+    }
+}
+
+fn parse_addresses(accounts: &[String]) -> Result<Vec<Address>> {
+    accounts
+        .iter()
+        .map(|addr| {
+            addr.parse::<Address>().map_err(|e| {
+                PolymarketError::ParseError(format!("Invalid address {}: {}", addr, e))
             })
-            .collect()
+        })
+        .collect()
+}
+
+/// A copy strategy sharing the wallet with the top-level configuration, with
+/// traders, filters, sizing and budget of its own
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StrategyConfig {
+    /// Put on the strategy's trades as a label
+    pub name: String,
+    pub tracked_accounts: Vec<String>,
+    /// Most the strategy's open positions may cost; the wallet balance when unset
+    #[serde(default)]
+    pub budget_usdc: Option<Decimal>,
+    /// Overrides of the `[execution]` settings of the same name
+    #[serde(default)]
+    pub min_trade_size_usdc: Option<Decimal>,
+    #[serde(default)]
+    pub max_trade_size_usdc: Option<Decimal>,
+    #[serde(default)]
+    pub event_tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub excluded_event_tags: Option<Vec<String>>,
+    /// Sizing in place of `[position_sizing]`
+    #[serde(default)]
+    pub position_sizing: Option<PositionSizingConfig>,
+}
+
+impl StrategyConfig {
+    pub fn get_addresses(&self) -> Result<Vec<Address>> {
+        parse_addresses(&self.tracked_accounts)
+    }
+
+    /// `execution` with this strategy's overrides applied
+    pub fn execution(&self, execution: &ExecutionConfig) -> ExecutionConfig {
+        let mut execution = execution.clone();
+        if let Some(min) = self.min_trade_size_usdc {
+            execution.min_trade_size_usdc = min;
+        }
+        if let Some(max) = self.max_trade_size_usdc {
+            execution.max_trade_size_usdc = max;
+        }
+        if let Some(ref tags) = self.event_tags {
+            execution.event_tags = tags.clone();
+        }
+        if let Some(ref tags) = self.excluded_event_tags {
+            execution.excluded_event_tags = tags.clone();
+        }
+        execution
     }
 }
 
@@ -49,6 +418,114 @@ pub struct PositionSizingConfig {
     pub max_position_size_relative: Decimal,
     pub strategy: String, // "absolute", "relative", or "hybrid"
     pub priority: String, // "absolute" or "relative"
+    /// Scale copies by the share of their portfolio the trader bet
+    #[serde(default)]
+    pub conviction: ConvictionConfig,
+    /// Shrink copies into markets whose price has been swinging
+    #[serde(default)]
+    pub volatility: VolatilityConfig,
+    /// Copies below this are dust, not worth the fees; 0 disables the check
+    #[serde(default)]
+    pub min_copy_size_usdc: Decimal,
+    /// What to do with a dust copy: "skip" it or "round_up" to the minimum
+    #[serde(default = "default_below_minimum")]
+    pub below_minimum: String,
+    /// What to do with a buy costing more than the balance not held by open
+    /// orders: "fail", placing it as sized, or "scale_down" to what is free
+    #[serde(default = "default_insufficient_balance")]
+    pub insufficient_balance: String,
+}
+
+fn default_below_minimum() -> String {
+    "skip".to_string()
+}
+
+fn default_insufficient_balance() -> String {
+    "fail".to_string()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VolatilityConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Largest one-standard-deviation move, as a fraction of the entry price,
+    /// a copy may carry at full size
+    #[serde(default = "default_target_risk")]
+    pub target_risk: Decimal,
+    /// Trailing window the price standard deviation is taken over
+    #[serde(default = "default_volatility_window_minutes")]
+    pub window_minutes: u64,
+    /// Prices needed in the window before sizing is adjusted
+    #[serde(default = "default_volatility_min_samples")]
+    pub min_samples: usize,
+}
+
+impl Default for VolatilityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_risk: default_target_risk(),
+            window_minutes: default_volatility_window_minutes(),
+            min_samples: default_volatility_min_samples(),
+        }
+    }
+}
+
+fn default_target_risk() -> Decimal {
+    Decimal::new(5, 2)
+}
+
+fn default_volatility_window_minutes() -> u64 {
+    60
+}
+
+fn default_volatility_min_samples() -> usize {
+    10
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConvictionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Share of the trader's portfolio that is copied at the normal size;
+    /// bigger bets are copied bigger, smaller ones smaller
+    #[serde(default = "default_baseline_conviction")]
+    pub baseline: Decimal,
+    #[serde(default = "default_min_conviction_multiplier")]
+    pub min_multiplier: Decimal,
+    #[serde(default = "default_max_conviction_multiplier")]
+    pub max_multiplier: Decimal,
+    /// How long a trader's portfolio value is reused before it is fetched again
+    #[serde(default = "default_portfolio_refresh_seconds")]
+    pub refresh_seconds: u64,
+}
+
+impl Default for ConvictionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            baseline: default_baseline_conviction(),
+            min_multiplier: default_min_conviction_multiplier(),
+            max_multiplier: default_max_conviction_multiplier(),
+            refresh_seconds: default_portfolio_refresh_seconds(),
+        }
+    }
+}
+
+fn default_baseline_conviction() -> Decimal {
+    Decimal::new(5, 2)
+}
+
+fn default_min_conviction_multiplier() -> Decimal {
+    Decimal::new(25, 2)
+}
+
+fn default_max_conviction_multiplier() -> Decimal {
+    Decimal::from(3)
+}
+
+fn default_portfolio_refresh_seconds() -> u64 {
+    300
 }
 
 impl PositionSizingConfig {
@@ -58,6 +535,9 @@ impl PositionSizingConfig {
             && self.max_position_size_absolute > Decimal::ZERO
             && self.max_position_size_relative > Decimal::ZERO
             && self.max_position_size_relative <= Decimal::ONE
+            && self.min_copy_size_usdc >= Decimal::ZERO
+            && matches!(self.below_minimum.as_str(), "skip" | "round_up")
+            && matches!(self.insufficient_balance.as_str(), "fail" | "scale_down")
     }
 }
 
@@ -71,6 +551,182 @@ pub struct ExecutionConfig {
     pub min_trade_size_usdc: Decimal,
     pub max_trade_size_usdc: Decimal,
     pub poll_interval_seconds: u64,
+    /// Where trades are detected from: "positions" (diffs between polls),
+    /// "trades" (the traders' trade lists) or "hybrid" (position diffs, plus
+    /// the round trips between polls they miss)
+    #[serde(default = "default_detection_mode")]
+    pub detection_mode: String,
+    /// Warn when the median time from a trader's fill to our detecting it
+    /// exceeds this
+    #[serde(default = "default_max_detection_latency_seconds")]
+    pub max_detection_latency_seconds: u64,
+    /// Don't copy entries detected more than this many seconds after the
+    /// trader's fill (0 = no limit)
+    #[serde(default)]
+    pub max_trade_age_seconds: u64,
+    /// Check that YES+NO midpoints add up to about 1 before copying:
+    /// "off", "flag" (log anomalies and copy anyway) or "skip"
+    #[serde(default = "default_price_sum_check")]
+    pub price_sum_check: String,
+    /// How far YES+NO may stray from 1 before it counts as an anomaly
+    #[serde(default = "default_price_sum_tolerance")]
+    pub price_sum_tolerance: Decimal,
+    /// Merge a trader's same-market, same-side fills within this many seconds into
+    /// one copy (0 = copy every fill)
+    #[serde(default)]
+    pub aggregation_window_seconds: u64,
+    /// Hold each order this many milliseconds so orders placed around the same
+    /// time go out in one batch request (0 = place every order on its own)
+    #[serde(default)]
+    pub batch_window_ms: u64,
+    /// Refuse to start when the local clock is further than this from the
+    /// CLOB server's
+    #[serde(default = "default_max_clock_skew_seconds")]
+    pub max_clock_skew_seconds: u64,
+    /// At startup, check the fills recorded over this many hours against the
+    /// exchange's trade history (0 = don't check)
+    #[serde(default = "default_fill_check_hours")]
+    pub fill_check_hours: u64,
+    /// Cancel our resting orders when shutting down
+    #[serde(default = "default_true")]
+    pub cancel_on_shutdown: bool,
+    /// Wait this long after detection and re-check before copying (0 = copy at once)
+    #[serde(default)]
+    pub copy_delay_seconds: u64,
+    /// Skip a delayed copy if the price moved more than this from the trader's fill
+    #[serde(default = "default_max_price_gap")]
+    pub max_price_gap: Decimal,
+    /// How new positions are entered: "taker" (an order_type order at the
+    /// trader's price) or "passive" (post-only at the best bid/ask first)
+    #[serde(default = "default_execution_style")]
+    pub execution_style: String,
+    /// How long a passive order rests before it is cancelled
+    #[serde(default = "default_passive_timeout_seconds")]
+    pub passive_timeout_seconds: u64,
+    /// Quote passive orders this many ticks inside the best bid/ask (0 = join it)
+    #[serde(default)]
+    pub passive_improve_ticks: u32,
+    /// Send what a passive order didn't fill as a taker order
+    #[serde(default = "default_true")]
+    pub passive_escalate: bool,
+    /// Times an unfilled GTC/GTD order is cancelled and re-placed at a more
+    /// aggressive price before giving up (0 = just time out)
+    #[serde(default)]
+    pub reprice_steps: u32,
+    /// How much each re-price moves towards the other side of the book
+    #[serde(default = "default_reprice_step")]
+    pub reprice_step: Decimal,
+    /// Furthest a re-priced order may be from the trader's price
+    #[serde(default = "default_max_reprice_deviation")]
+    pub max_reprice_deviation: Decimal,
+    /// Only copy trades in events with one of these Gamma tags, matched by
+    /// label or slug (e.g. "US Politics"); empty copies every event
+    #[serde(default)]
+    pub event_tags: Vec<String>,
+    /// Never copy trades in events with any of these tags
+    #[serde(default)]
+    pub excluded_event_tags: Vec<String>,
+    /// Refuse a second copy of a trader's trade in the same market and side
+    /// within this many seconds, unless its size differs by more than
+    /// `duplicate_size_tolerance` (0 = off)
+    #[serde(default)]
+    pub duplicate_window_seconds: u64,
+    #[serde(default = "default_duplicate_size_tolerance")]
+    pub duplicate_size_tolerance: Decimal,
+    /// Where the CLOB API credentials are kept: derived on the first run,
+    /// replaced when they are rotated
+    #[serde(default = "default_api_credentials_file")]
+    pub api_credentials_file: String,
+    /// Count fills of orders worth at least this many USDC towards our
+    /// positions only once their settlement is seen on-chain (unset = off)
+    #[serde(default)]
+    pub settlement_check_min_usdc: Option<Decimal>,
+    /// How long to wait for the settlement before the fill is marked unverified
+    #[serde(default = "default_settlement_timeout_seconds")]
+    pub settlement_timeout_seconds: u64,
+    /// Copies executed, and so orders in flight, at once; further ones wait
+    /// for a free slot (0 = no limit)
+    #[serde(default)]
+    pub max_concurrent_executions: usize,
+    /// Execute one copy at a time in each market, so our own orders never
+    /// compete or stack fills in the same token
+    #[serde(default = "default_true")]
+    pub one_order_per_market: bool,
+    /// Which waiting copy gets a free slot first: "trade_size",
+    /// "trader_weight" and "time_to_resolution", the first deciding and the
+    /// next breaking ties; arrival order otherwise
+    #[serde(default)]
+    pub execution_priority: Vec<String>,
+    /// Keep retrying a copy that failed for a passing reason (rate limits,
+    /// balance, network) for this long (0 = off)
+    #[serde(default)]
+    pub failed_copy_retry_seconds: u64,
+    #[serde(default = "default_failed_copy_retry_interval_seconds")]
+    pub failed_copy_retry_interval_seconds: u64,
+    /// Give up on the retries once the price moved this far against the copy
+    #[serde(default = "default_max_price_gap")]
+    pub failed_copy_max_price_move: Decimal,
+    /// List our open orders this often and cancel the stale ones (0 = off)
+    #[serde(default)]
+    pub order_sweep_interval_seconds: u64,
+    /// Cancel open orders resting longer than this (0 = no age limit)
+    #[serde(default)]
+    pub max_open_order_age_seconds: u64,
+    /// Cancel resting buys once the trader they copy no longer holds the token
+    #[serde(default = "default_true")]
+    pub cancel_after_trader_exit: bool,
+}
+
+fn default_max_detection_latency_seconds() -> u64 {
+    30
+}
+
+fn default_price_sum_check() -> String {
+    "off".to_string()
+}
+
+fn default_price_sum_tolerance() -> Decimal {
+    Decimal::new(2, 2)
+}
+
+fn default_max_price_gap() -> Decimal {
+    Decimal::new(5, 2)
+}
+
+fn default_detection_mode() -> String {
+    "positions".to_string()
+}
+
+fn default_execution_style() -> String {
+    "taker".to_string()
+}
+
+fn default_passive_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_reprice_step() -> Decimal {
+    Decimal::new(1, 2)
+}
+
+fn default_max_reprice_deviation() -> Decimal {
+    Decimal::new(3, 2)
+}
+
+fn default_duplicate_size_tolerance() -> Decimal {
+    Decimal::new(1, 1)
+}
+
+fn default_api_credentials_file() -> String {
+    "api_credentials.json".to_string()
+}
+
+fn default_settlement_timeout_seconds() -> u64 {
+    120
+}
+
+fn default_failed_copy_retry_interval_seconds() -> u64 {
+    15
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -88,81 +744,1666 @@ pub struct BacktestConfig {
     pub fee_rate_bps: u32,
     pub apply_gas_costs: bool,
     pub estimated_gas_per_trade_usd: Decimal,
+    /// Depth assumed when a historical trade has no recorded liquidity (None = unlimited)
+    #[serde(default)]
+    pub default_liquidity: Option<Decimal>,
+    /// What to do with the unfilled part of a partial fill: "drop" or "roll"
+    #[serde(default = "default_unfilled_policy")]
+    pub unfilled_policy: String,
+    /// Close copied positions when the source trader sells (instead of opening shorts)
+    #[serde(default = "default_true")]
+    pub mirror_exits: bool,
+    /// Number of historical trades read from the data source at a time
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Stress-test shocks injected into the historical stream
+    #[serde(default)]
+    pub scenarios: Vec<ScenarioConfig>,
+    /// Model fitted by `calibrate-slippage`, used when slippage_model = "calibrated"
+    #[serde(default)]
+    pub calibrated_slippage: Option<CalibratedSlippage>,
+    /// TOML file of slippage models per market or liquidity bucket, written by
+    /// `calibrate-slippage --books`; markets it doesn't cover use slippage_model
+    #[serde(default)]
+    pub market_slippage_file: Option<String>,
+    /// Other bots copying the same traders and trading ahead of us
+    #[serde(default)]
+    pub competition: CompetitionConfig,
+    /// What happens to positions still open at the end: "last_price" closes
+    /// them at the last price traded, "resolution" at 1 or 0 where the market
+    /// has resolved, "mark" leaves them open but counts them at the last price,
+    /// "unrealized" reports them apart from the realized results
+    #[serde(default = "default_end_of_backtest")]
+    pub end_of_backtest: String,
 }
 
+/// Competing copy bots simulated in backtests
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct LoggingConfig {
-    pub level: String,
-    pub file_output: String,
-    pub max_log_size_mb: u64,
-    pub log_retention_days: u64,
+pub struct CompetitionConfig {
+    /// Bots copying each tracked trader (0 = no competition); profiles can
+    /// override it per trader with `competing_copiers`
+    #[serde(default)]
+    pub copiers: u32,
+    /// Each competing copier trades this fraction of the tracked trade's size
+    #[serde(default = "default_copier_size_fraction")]
+    pub copier_size_fraction: Decimal,
+    /// Share of the competing copiers that fill before us
+    #[serde(default = "default_ahead_fraction")]
+    pub ahead_fraction: Decimal,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DatabaseConfig {
-    pub db_type: String,
-    pub db_connection: String,
+impl Default for CompetitionConfig {
+    fn default() -> Self {
+        Self {
+            copiers: 0,
+            copier_size_fraction: default_copier_size_fraction(),
+            ahead_fraction: default_ahead_fraction(),
+        }
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
-pub struct NotificationsConfig {
-    pub slack_webhook_url: Option<String>,
-    pub notify_on_trade: bool,
-    pub notify_on_error: bool,
+fn default_copier_size_fraction() -> Decimal {
+    Decimal::new(1, 1)
 }
 
-impl Config {
-    /// Load configuration from a TOML file
-    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = fs::read_to_string(path).map_err(|e| {
-            PolymarketError::ConfigError(format!("Failed to read config file: {}", e))
-        })?;
+fn default_ahead_fraction() -> Decimal {
+    Decimal::new(5, 1)
+}
 
-        let config: Config = toml::from_str(&content)?;
-        config.validate()?;
+/// A shock applied to historical trades inside a time window
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioConfig {
+    pub name: String,
+    /// "price_shock", "losing_streak", "slippage_spike" or "api_gap"
+    pub kind: String,
+    /// Window start (RFC 3339)
+    pub start: String,
+    /// Window end (RFC 3339)
+    pub end: String,
+    /// Market hit by a price shock (all markets when unset)
+    #[serde(default)]
+    pub market: Option<String>,
+    /// Trader whose copied trades go bad during a losing streak
+    #[serde(default)]
+    pub trader: Option<String>,
+    /// Relative price jump (price_shock), adverse price move per trade
+    /// (losing_streak) or slippage multiplier (slippage_spike)
+    #[serde(default)]
+    pub magnitude: Decimal,
+}
 
-        Ok(config)
-    }
+fn default_chunk_size() -> usize {
+    10_000
+}
 
-    /// Validate configuration
-    fn validate(&self) -> Result<()> {
-        // Validate mode
-        if !matches!(self.general.mode.as_str(), "live" | "backtest") {
-            return Err(PolymarketError::ConfigError(
-                "Invalid mode. Must be 'live' or 'backtest'".to_string(),
-            ));
-        }
+fn default_max_clock_skew_seconds() -> u64 {
+    10
+}
 
-        // Validate position sizing
-        if !self.position_sizing.is_valid() {
-            return Err(PolymarketError::ConfigError(
-                "Invalid position sizing configuration".to_string(),
-            ));
-        }
+fn default_fill_check_hours() -> u64 {
+    24
+}
 
-        // Validate trader addresses
-        let _ = self.traders.get_addresses()?;
+fn default_true() -> bool {
+    true
+}
 
-        // Validate execution config
-        if self.execution.min_trade_size_usdc >= self.execution.max_trade_size_usdc {
-            return Err(PolymarketError::ConfigError(
-                "min_trade_size_usdc must be less than max_trade_size_usdc".to_string(),
+fn default_end_of_backtest() -> String {
+    "last_price".to_string()
+}
+
+fn default_unfilled_policy() -> String {
+    "drop".to_string()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    pub level: String,
+    pub file_output: String,
+    pub max_log_size_mb: u64,
+    pub log_retention_days: u64,
+    /// JSONL file that detected and executed trades are appended to
+    #[serde(default = "default_trade_log_path")]
+    pub trade_log_path: String,
+    /// Journal of order lifecycle snapshots (when not using a database)
+    #[serde(default = "default_order_log_path")]
+    pub order_log_path: String,
+    /// Rotate the trade log once it grows past this size (0 = never)
+    #[serde(default)]
+    pub trade_log_max_size_mb: u64,
+    /// Rotate the trade log when the UTC date changes
+    #[serde(default)]
+    pub trade_log_rotate_daily: bool,
+    /// Gzip rotated trade log files
+    #[serde(default)]
+    pub trade_log_compress: bool,
+    /// Fail on corrupt trade log lines instead of skipping them
+    #[serde(default)]
+    pub trade_log_strict: bool,
+    /// Record every exchange request and response in an append-only audit log
+    #[serde(default)]
+    pub audit_log_enabled: bool,
+    #[serde(default = "default_audit_log_path")]
+    pub audit_log_path: String,
+    /// Gas paid for on-chain transactions such as token approvals
+    #[serde(default = "default_gas_log_path")]
+    pub gas_log_path: String,
+}
+
+pub fn default_gas_log_path() -> String {
+    "gas.jsonl".to_string()
+}
+
+fn default_audit_log_path() -> String {
+    "audit.jsonl".to_string()
+}
+
+fn default_trade_log_path() -> String {
+    "trades.jsonl".to_string()
+}
+
+fn default_order_log_path() -> String {
+    "orders.jsonl".to_string()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    /// "jsonl" (the trade log file), "sqlite" or "postgres"
+    pub db_type: String,
+    #[serde(default)]
+    pub db_connection: String,
+    /// Read `db_connection` from this file at startup
+    #[serde(default)]
+    pub db_connection_file: Option<String>,
+    /// Connection pool size for sqlite and postgres
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+}
+
+fn default_max_connections() -> u32 {
+    5
+}
+
+/// Periodic export of executed trades for people who don't read JSONL
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Export on a schedule while live trading
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_export_interval_hours")]
+    pub interval_hours: u64,
+    /// Directory that receives timestamped CSV files
+    #[serde(default = "default_export_directory")]
+    pub directory: String,
+    /// Google Sheet to overwrite with the trade history (optional)
+    #[serde(default)]
+    pub google_sheet_id: Option<String>,
+    /// Service-account key file with edit access to the sheet
+    #[serde(default)]
+    pub google_credentials_path: Option<String>,
+    #[serde(default = "default_sheet_name")]
+    pub sheet_name: String,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_export_interval_hours(),
+            directory: default_export_directory(),
+            google_sheet_id: None,
+            google_credentials_path: None,
+            sheet_name: default_sheet_name(),
+        }
+    }
+}
+
+fn default_export_interval_hours() -> u64 {
+    24
+}
+
+fn default_export_directory() -> String {
+    "exports".to_string()
+}
+
+fn default_sheet_name() -> String {
+    "Trades".to_string()
+}
+
+/// Leader election between bot instances sharing the same wallet
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CoordinationConfig {
+    /// "none", "file" or "redis"
+    #[serde(default = "default_coordination_backend")]
+    pub backend: String,
+    /// Directory shared by all instances (file backend)
+    #[serde(default = "default_lock_dir")]
+    pub lock_dir: String,
+    #[serde(default)]
+    pub redis_url: Option<String>,
+    /// Read `redis_url` from this file at startup
+    #[serde(default)]
+    pub redis_url_file: Option<String>,
+    /// Name of this instance in the lock; defaults to hostname and process id
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    /// A leader that stops renewing is replaced after this long
+    #[serde(default = "default_lease_seconds")]
+    pub lease_seconds: u64,
+    /// How long a claimed trade stays claimed
+    #[serde(default = "default_trade_claim_seconds")]
+    pub trade_claim_seconds: u64,
+}
+
+impl Default for CoordinationConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_coordination_backend(),
+            lock_dir: default_lock_dir(),
+            redis_url: None,
+            redis_url_file: None,
+            instance_id: None,
+            lease_seconds: default_lease_seconds(),
+            trade_claim_seconds: default_trade_claim_seconds(),
+        }
+    }
+}
+
+fn default_coordination_backend() -> String {
+    "none".to_string()
+}
+
+fn default_lock_dir() -> String {
+    "locks".to_string()
+}
+
+fn default_lease_seconds() -> u64 {
+    15
+}
+
+fn default_trade_claim_seconds() -> u64 {
+    3600
+}
+
+/// Shared price cache for markets we hold or watch
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PricesConfig {
+    /// "websocket" (market channel, polling while disconnected) or "poll"
+    #[serde(default = "default_price_source")]
+    pub source: String,
+    #[serde(default = "default_price_websocket_url")]
+    pub websocket_url: String,
+    /// Midpoint polling interval
+    #[serde(default = "default_price_poll_interval_seconds")]
+    pub poll_interval_seconds: u64,
+    /// Cached prices older than this are treated as unknown
+    #[serde(default = "default_price_max_age_seconds")]
+    pub max_age_seconds: u64,
+}
+
+impl Default for PricesConfig {
+    fn default() -> Self {
+        Self {
+            source: default_price_source(),
+            websocket_url: default_price_websocket_url(),
+            poll_interval_seconds: default_price_poll_interval_seconds(),
+            max_age_seconds: default_price_max_age_seconds(),
+        }
+    }
+}
+
+fn default_price_source() -> String {
+    "websocket".to_string()
+}
+
+fn default_price_websocket_url() -> String {
+    "wss://ws-subscriptions-clob.polymarket.com/ws/market".to_string()
+}
+
+fn default_price_poll_interval_seconds() -> u64 {
+    5
+}
+
+fn default_price_max_age_seconds() -> u64 {
+    60
+}
+
+/// Fallback API base URLs, used while the primary ones keep failing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FailoverConfig {
+    /// Tried in order after `general.polymarket_api_url`
+    #[serde(default)]
+    pub clob_api_urls: Vec<String>,
+    /// Tried in order after `general.data_api_url`
+    #[serde(default)]
+    pub data_api_urls: Vec<String>,
+    /// Errors, timeouts or 5xx responses in a row before switching to the next URL
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    #[serde(default = "default_request_timeout_seconds")]
+    pub request_timeout_seconds: u64,
+    /// How often the primary is checked while a fallback is in use
+    #[serde(default = "default_probe_interval_seconds")]
+    pub probe_interval_seconds: u64,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            clob_api_urls: Vec::new(),
+            data_api_urls: Vec::new(),
+            failure_threshold: default_failure_threshold(),
+            request_timeout_seconds: default_request_timeout_seconds(),
+            probe_interval_seconds: default_probe_interval_seconds(),
+        }
+    }
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn default_request_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_probe_interval_seconds() -> u64 {
+    60
+}
+
+/// Prometheus metrics endpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address the `/metrics` endpoint listens on
+    #[serde(default = "default_metrics_listen_addr")]
+    pub listen_addr: String,
+    /// How often the balance, exposure and risk headroom gauges are updated
+    #[serde(default = "default_exposure_interval_seconds")]
+    pub exposure_interval_seconds: u64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_addr: default_metrics_listen_addr(),
+            exposure_interval_seconds: default_exposure_interval_seconds(),
+        }
+    }
+}
+
+fn default_metrics_listen_addr() -> String {
+    "127.0.0.1:9464".to_string()
+}
+
+fn default_exposure_interval_seconds() -> u64 {
+    15
+}
+
+/// Settings for the HTTP clients that talk to the CLOB and poll traders
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Whole-request limit, for requests without a more specific one
+    #[serde(default = "default_network_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Limit for establishing a connection, TLS included
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    /// Idle connections kept open per host
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+    /// Idle connections are closed after this long
+    #[serde(default = "default_pool_idle_timeout_seconds")]
+    pub pool_idle_timeout_seconds: u64,
+    /// Send every request through this proxy, e.g. `http://127.0.0.1:3128`
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    #[serde(default = "default_user_agent")]
+    pub user_agent: String,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: default_network_timeout_seconds(),
+            connect_timeout_seconds: default_connect_timeout_seconds(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+            pool_idle_timeout_seconds: default_pool_idle_timeout_seconds(),
+            proxy_url: None,
+            user_agent: default_user_agent(),
+        }
+    }
+}
+
+fn default_network_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_connect_timeout_seconds() -> u64 {
+    5
+}
+
+fn default_pool_max_idle_per_host() -> usize {
+    8
+}
+
+fn default_pool_idle_timeout_seconds() -> u64 {
+    90
+}
+
+fn default_user_agent() -> String {
+    concat!("polymarket-copy-trader/", env!("CARGO_PKG_VERSION")).to_string()
+}
+
+/// Pausing new copies while API calls or executions keep failing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ErrorBudgetConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Rolling window the failure rates are measured over
+    #[serde(default = "default_error_budget_window_seconds")]
+    pub window_seconds: u64,
+    /// Outcomes needed in the window before a rate counts
+    #[serde(default = "default_error_budget_min_samples")]
+    pub min_samples: usize,
+    /// Pause above this share of failures (0.5 = 50%)
+    #[serde(default = "default_max_failure_rate")]
+    pub max_failure_rate: Decimal,
+    /// Resume once every rate is at or below this
+    #[serde(default = "default_resume_failure_rate")]
+    pub resume_failure_rate: Decimal,
+    #[serde(default = "default_error_budget_check_interval_seconds")]
+    pub check_interval_seconds: u64,
+}
+
+impl Default for ErrorBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_seconds: default_error_budget_window_seconds(),
+            min_samples: default_error_budget_min_samples(),
+            max_failure_rate: default_max_failure_rate(),
+            resume_failure_rate: default_resume_failure_rate(),
+            check_interval_seconds: default_error_budget_check_interval_seconds(),
+        }
+    }
+}
+
+fn default_error_budget_window_seconds() -> u64 {
+    300
+}
+
+fn default_error_budget_min_samples() -> usize {
+    10
+}
+
+fn default_max_failure_rate() -> Decimal {
+    Decimal::new(5, 1)
+}
+
+fn default_resume_failure_rate() -> Decimal {
+    Decimal::new(2, 1)
+}
+
+fn default_error_budget_check_interval_seconds() -> u64 {
+    10
+}
+
+/// Per-trader P&L kept by the live bot
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PnlConfig {
+    /// Where the latest P&L per trader is written for the `status` command
+    #[serde(default = "default_pnl_snapshot_file")]
+    pub snapshot_file: String,
+    #[serde(default = "default_pnl_update_interval_seconds")]
+    pub update_interval_seconds: u64,
+    /// Post the P&L per trader to Slack every day at this UTC time (HH:MM)
+    #[serde(default)]
+    pub daily_summary_time: Option<String>,
+}
+
+impl Default for PnlConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_file: default_pnl_snapshot_file(),
+            update_interval_seconds: default_pnl_update_interval_seconds(),
+            daily_summary_time: None,
+        }
+    }
+}
+
+impl PnlConfig {
+    pub fn daily_summary_at(&self) -> Result<Option<chrono::NaiveTime>> {
+        self.daily_summary_time
+            .as_deref()
+            .map(|time| {
+                chrono::NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| {
+                    PolymarketError::ConfigError(format!(
+                        "Invalid pnl daily_summary_time '{}', expected HH:MM",
+                        time
+                    ))
+                })
+            })
+            .transpose()
+    }
+}
+
+pub(crate) fn default_pnl_snapshot_file() -> String {
+    "trader_pnl.json".to_string()
+}
+
+fn default_pnl_update_interval_seconds() -> u64 {
+    60
+}
+
+/// Recording of market outcomes, so results count resolutions rather than
+/// price marks
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResolutionsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Where resolved outcomes are kept
+    #[serde(default = "default_resolutions_file")]
+    pub file: String,
+    #[serde(default = "default_resolutions_check_interval_minutes")]
+    pub check_interval_minutes: u64,
+    /// Markets of trades detected this many days back are checked, besides
+    /// every market we hold
+    #[serde(default = "default_resolutions_lookback_days")]
+    pub lookback_days: u64,
+}
+
+impl Default for ResolutionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: default_resolutions_file(),
+            check_interval_minutes: default_resolutions_check_interval_minutes(),
+            lookback_days: default_resolutions_lookback_days(),
+        }
+    }
+}
+
+fn default_resolutions_file() -> String {
+    "resolutions.json".to_string()
+}
+
+fn default_resolutions_check_interval_minutes() -> u64 {
+    15
+}
+
+fn default_resolutions_lookback_days() -> u64 {
+    30
+}
+
+/// Publishing of the bot's event stream to a message broker
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PublishingConfig {
+    /// "none", "nats" or "kafka"
+    #[serde(default = "default_publishing_backend")]
+    pub backend: String,
+    /// NATS server URL, or Kafka bootstrap servers
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Events go to `<topic_prefix>.trades`, `.orders` and `.portfolio`
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+    /// Publish a portfolio snapshot this often (0 = never)
+    #[serde(default = "default_snapshot_interval_seconds")]
+    pub snapshot_interval_seconds: u64,
+}
+
+impl Default for PublishingConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_publishing_backend(),
+            url: None,
+            topic_prefix: default_topic_prefix(),
+            snapshot_interval_seconds: default_snapshot_interval_seconds(),
+        }
+    }
+}
+
+fn default_publishing_backend() -> String {
+    "none".to_string()
+}
+
+fn default_topic_prefix() -> String {
+    "polycopy".to_string()
+}
+
+fn default_snapshot_interval_seconds() -> u64 {
+    60
+}
+
+/// HTTP endpoint for trade signals from outside the bot
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignalsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_signals_listen_addr")]
+    pub listen_addr: String,
+    /// Bearer token every request must carry; None accepts anyone who can
+    /// reach `listen_addr`
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// Signals waiting for the monitor beyond this many are refused
+    #[serde(default = "default_signals_queue_size")]
+    pub queue_size: usize,
+}
+
+impl Default for SignalsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_addr: default_signals_listen_addr(),
+            auth_token: None,
+            queue_size: default_signals_queue_size(),
+        }
+    }
+}
+
+fn default_signals_listen_addr() -> String {
+    "127.0.0.1:9470".to_string()
+}
+
+fn default_signals_queue_size() -> usize {
+    100
+}
+
+/// Gas paid for the bot's own on-chain transactions
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GasConfig {
+    /// Hold back transactions that can wait while the network gas price is
+    /// above this (None = never)
+    #[serde(default)]
+    pub max_gas_price_gwei: Option<Decimal>,
+    /// How long a held-back transaction waits for gas to come down before
+    /// giving up
+    #[serde(default = "default_gas_max_deferral_minutes")]
+    pub max_deferral_minutes: u64,
+    /// POL price in USD for costing gas; None reads the Chainlink POL/USD feed
+    #[serde(default)]
+    pub pol_price_usd: Option<Decimal>,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            max_gas_price_gwei: None,
+            max_deferral_minutes: default_gas_max_deferral_minutes(),
+            pol_price_usd: None,
+        }
+    }
+}
+
+fn default_gas_max_deferral_minutes() -> u64 {
+    60
+}
+
+/// Order book snapshots taken in `record` mode
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecorderConfig {
+    /// Token ids, condition ids (`0x...`) or market slugs
+    #[serde(default)]
+    pub markets: Vec<String>,
+    #[serde(default = "default_recorder_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Price levels kept per side
+    #[serde(default = "default_recorder_depth")]
+    pub depth: usize,
+    #[serde(default = "default_recorder_directory")]
+    pub directory: String,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            markets: Vec::new(),
+            interval_seconds: default_recorder_interval_seconds(),
+            depth: default_recorder_depth(),
+            directory: default_recorder_directory(),
+        }
+    }
+}
+
+fn default_recorder_interval_seconds() -> u64 {
+    10
+}
+
+fn default_recorder_depth() -> usize {
+    10
+}
+
+fn default_recorder_directory() -> String {
+    "recordings".to_string()
+}
+
+/// Limits on open positions while live trading
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RiskConfig {
+    /// Largest value (USDC at current prices) a position in one market may reach
+    #[serde(default)]
+    pub max_market_exposure_usdc: Option<Decimal>,
+    /// Largest combined cost (USDC) of positions in markets of the same event
+    #[serde(default)]
+    pub max_event_exposure_usdc: Option<Decimal>,
+    /// Sell down positions that price moves have pushed over the cap
+    #[serde(default)]
+    pub rebalance_enabled: bool,
+    /// Only trim once a position exceeds the cap by this fraction
+    #[serde(default = "default_rebalance_band")]
+    pub rebalance_band: Decimal,
+    #[serde(default = "default_rebalance_interval_seconds")]
+    pub rebalance_interval_seconds: u64,
+    /// Leave a market alone this long after trimming it
+    #[serde(default = "default_rebalance_cooldown_seconds")]
+    pub rebalance_cooldown_seconds: u64,
+    /// Trailing stop distance below the peak price, in cents
+    #[serde(default)]
+    pub trailing_stop_cents: Option<Decimal>,
+    /// Trailing stop distance as a fraction of the peak price (0.1 = 10%)
+    #[serde(default)]
+    pub trailing_stop_percent: Option<Decimal>,
+    /// What a triggered trailing stop does: "sell" or "hedge"
+    #[serde(default = "default_stop_action")]
+    pub stop_action: String,
+    /// Hedge a position once it is down this fraction from entry (0.2 = 20%)
+    #[serde(default)]
+    pub hedge_drawdown_percent: Option<Decimal>,
+    /// Sell a position still held this many days after it was opened
+    #[serde(default)]
+    pub max_holding_days: Option<u64>,
+    /// Sell positions this many hours before their market's end date
+    #[serde(default)]
+    pub exit_hours_before_end: Option<u64>,
+    /// How often positions are checked against the price cache
+    #[serde(default = "default_risk_check_interval_seconds")]
+    pub check_interval_seconds: u64,
+    /// Confidence level of the VaR report (0.95 = the loss exceeded one time in 20)
+    #[serde(default = "default_var_confidence")]
+    pub var_confidence: Decimal,
+    /// Assumed correlation between markets of the same event
+    #[serde(default = "default_var_event_correlation")]
+    pub var_event_correlation: f64,
+    /// Assumed correlation between markets of different events
+    #[serde(default = "default_var_cross_event_correlation")]
+    pub var_cross_event_correlation: f64,
+    /// Stop copying once equity is this fraction below its peak (0.25 = 25%)
+    #[serde(default)]
+    pub max_drawdown_percent: Option<Decimal>,
+    /// Also sell every position when the drawdown kill switch fires
+    #[serde(default)]
+    pub drawdown_flatten: bool,
+    /// Where the equity peak and kill switch state are kept between runs
+    #[serde(default = "default_drawdown_state_file")]
+    pub drawdown_state_file: String,
+    #[serde(default = "default_drawdown_check_interval_seconds")]
+    pub drawdown_check_interval_seconds: u64,
+}
+
+impl RiskConfig {
+    /// Whether any rule needs the risk monitor to watch positions
+    pub fn has_exit_rules(&self) -> bool {
+        self.trailing_stop_cents.is_some()
+            || self.trailing_stop_percent.is_some()
+            || self.hedge_drawdown_percent.is_some()
+            || self.max_holding_days.is_some()
+            || self.exit_hours_before_end.is_some()
+    }
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            max_market_exposure_usdc: None,
+            max_event_exposure_usdc: None,
+            rebalance_enabled: false,
+            rebalance_band: default_rebalance_band(),
+            rebalance_interval_seconds: default_rebalance_interval_seconds(),
+            rebalance_cooldown_seconds: default_rebalance_cooldown_seconds(),
+            trailing_stop_cents: None,
+            trailing_stop_percent: None,
+            stop_action: default_stop_action(),
+            hedge_drawdown_percent: None,
+            max_holding_days: None,
+            exit_hours_before_end: None,
+            check_interval_seconds: default_risk_check_interval_seconds(),
+            var_confidence: default_var_confidence(),
+            var_event_correlation: default_var_event_correlation(),
+            var_cross_event_correlation: default_var_cross_event_correlation(),
+            max_drawdown_percent: None,
+            drawdown_flatten: false,
+            drawdown_state_file: default_drawdown_state_file(),
+            drawdown_check_interval_seconds: default_drawdown_check_interval_seconds(),
+        }
+    }
+}
+
+fn default_rebalance_band() -> Decimal {
+    Decimal::new(1, 1)
+}
+
+fn default_rebalance_interval_seconds() -> u64 {
+    60
+}
+
+fn default_rebalance_cooldown_seconds() -> u64 {
+    900
+}
+
+fn default_stop_action() -> String {
+    "sell".to_string()
+}
+
+fn default_risk_check_interval_seconds() -> u64 {
+    5
+}
+
+fn default_var_confidence() -> Decimal {
+    Decimal::new(95, 2)
+}
+
+fn default_var_event_correlation() -> f64 {
+    0.8
+}
+
+fn default_var_cross_event_correlation() -> f64 {
+    0.1
+}
+
+pub(crate) fn default_drawdown_state_file() -> String {
+    "drawdown.json".to_string()
+}
+
+fn default_drawdown_check_interval_seconds() -> u64 {
+    60
+}
+
+/// Heuristics for tracked traders that farm volume by trading with themselves
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WashTradingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// "flag" (log and keep copying) or "suspend" (stop copying for a while)
+    #[serde(default = "default_wash_action")]
+    pub action: String,
+    /// Exiting a market this soon after entering it counts as a rapid round trip
+    #[serde(default = "default_round_trip_seconds")]
+    pub round_trip_seconds: u64,
+    /// Round trips closer than this to the entry price have no edge
+    #[serde(default = "default_min_price_edge")]
+    pub min_price_edge: Decimal,
+    /// Opposite trades by two tracked wallets this close together look coordinated
+    #[serde(default = "default_counterparty_window_seconds")]
+    pub counterparty_window_seconds: u64,
+    /// Relative size difference still considered "the same size"
+    #[serde(default = "default_size_tolerance")]
+    pub size_tolerance: Decimal,
+    /// Signals within the lookback before a trader is flagged or suspended
+    #[serde(default = "default_signal_threshold")]
+    pub signal_threshold: usize,
+    #[serde(default = "default_wash_lookback_hours")]
+    pub lookback_hours: u64,
+    #[serde(default = "default_suspend_hours")]
+    pub suspend_hours: u64,
+}
+
+impl Default for WashTradingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: default_wash_action(),
+            round_trip_seconds: default_round_trip_seconds(),
+            min_price_edge: default_min_price_edge(),
+            counterparty_window_seconds: default_counterparty_window_seconds(),
+            size_tolerance: default_size_tolerance(),
+            signal_threshold: default_signal_threshold(),
+            lookback_hours: default_wash_lookback_hours(),
+            suspend_hours: default_suspend_hours(),
+        }
+    }
+}
+
+fn default_wash_action() -> String {
+    "flag".to_string()
+}
+
+fn default_round_trip_seconds() -> u64 {
+    600
+}
+
+fn default_min_price_edge() -> Decimal {
+    Decimal::new(1, 2)
+}
+
+fn default_counterparty_window_seconds() -> u64 {
+    60
+}
+
+fn default_size_tolerance() -> Decimal {
+    Decimal::new(1, 1)
+}
+
+fn default_signal_threshold() -> usize {
+    3
+}
+
+fn default_wash_lookback_hours() -> u64 {
+    24
+}
+
+fn default_suspend_hours() -> u64 {
+    24
+}
+
+/// When live trading may open new copies
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// IANA time zone for the trading window, e.g. "America/New_York" (default UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Days trading is allowed, e.g. ["mon", "tue"]; empty means every day
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Daily window as "HH:MM"; the end may be earlier than the start to run past midnight
+    #[serde(default)]
+    pub start_time: Option<String>,
+    #[serde(default)]
+    pub end_time: Option<String>,
+    /// Don't copy into markets that end within this many minutes
+    #[serde(default)]
+    pub end_date_blackout_minutes: u64,
+    /// Fixed periods without new copies
+    #[serde(default)]
+    pub blackouts: Vec<BlackoutPeriod>,
+    /// Hold trades detected outside the window and copy them once it opens
+    #[serde(default)]
+    pub queue_outside_window: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlackoutPeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct NotificationsConfig {
+    pub slack_webhook_url: Option<String>,
+    /// Read the webhook URL from this file, and again whenever it changes
+    #[serde(default)]
+    pub slack_webhook_url_file: Option<String>,
+    pub notify_on_trade: bool,
+    pub notify_on_error: bool,
+}
+
+impl Config {
+    /// Load configuration from a TOML file
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            PolymarketError::ConfigError(format!("Failed to read config file: {}", e))
+        })?;
+
+        let mut value: toml::Value = toml::from_str(&content)?;
+        let mut missing = Vec::new();
+        expand_env_in_value(&mut value, "", &mut missing);
+        let mut problems: Vec<String> = missing
+            .into_iter()
+            .filter(|(key, _)| !ENV_FALLBACK_KEYS.contains(&key.as_str()))
+            .map(|(key, var)| format!("environment variable {} (used by {}) is not set", var, key))
+            .collect();
+
+        resolve_secret_files(&mut value, &mut problems);
+
+        // Collect unknown keys instead of ignoring them, so typos don't fall back to defaults
+        let mut unknown = Vec::new();
+        let mut record_unknown = |path: serde_ignored::Path| unknown.push(path.to_string());
+        let deserializer = serde_ignored::Deserializer::new(value, &mut record_unknown);
+        let parsed: std::result::Result<Config, _> = serde_path_to_error::deserialize(deserializer);
+        let (config, known) = match parsed {
+            Ok(config) => {
+                let known = toml::Value::try_from(&config).ok();
+                (Some(config), known)
+            }
+            Err(e) => {
+                let message = e.inner().message().to_string();
+                problems.push(format!("{}: {}", e.path(), message));
+                // A misspelt required key: offer the missing one for the unknown key
+                let known = message
+                    .strip_prefix("missing field `")
+                    .and_then(|rest| rest.strip_suffix('`'))
+                    .map(|field| {
+                        let mut known = toml::Value::Table(toml::Table::from_iter([(
+                            field.to_string(),
+                            toml::Value::Boolean(true),
+                        )]));
+                        for segment in e.path().to_string().rsplit('.') {
+                            known = toml::Value::Table(toml::Table::from_iter([(
+                                segment.to_string(),
+                                known,
+                            )]));
+                        }
+                        known
+                    });
+                (None, known)
+            }
+        };
+        for key in unknown {
+            let suggestion = known
+                .as_ref()
+                .and_then(|known| closest_known_key(known, &key))
+                .map(|known| format!(" (did you mean `{}`?)", known))
+                .unwrap_or_default();
+            problems.push(format!("unknown key `{}`{}", key, suggestion));
+        }
+        let Some(config) = config else {
+            return Err(config_problems(problems));
+        };
+        problems.extend(config.problems());
+        if !problems.is_empty() {
+            return Err(config_problems(problems));
+        }
+
+        Ok(config)
+    }
+
+    /// Every problem with the configuration's values, so they can be fixed in one go
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        // Validate mode
+        if !matches!(
+            self.general.mode.as_str(),
+            "live" | "observe" | "backtest" | "record"
+        ) {
+            problems.push(
+                "Invalid mode. Must be 'live', 'observe', 'backtest' or 'record'".to_string(),
+            );
+        }
+
+        let has_mnemonic = self.general.wallet_mnemonic.is_some();
+        if self.general.mode == "live"
+            && self.general.wallet_private_key.is_empty()
+            && !has_mnemonic
+        {
+            problems.push(
+                "live mode needs wallet_private_key, wallet_mnemonic or one of their _file variants"
+                    .to_string(),
+            );
+        }
+        if self.general.mode != "backtest"
+            && !self.general.wallet_private_key.is_empty()
+            && has_mnemonic
+        {
+            problems.push("set only one of wallet_private_key and wallet_mnemonic".to_string());
+        }
+
+        if let Err(e) = self.general.safe_address() {
+            problems.push(problem(e));
+        }
+
+        // Validate position sizing
+        if !self.position_sizing.is_valid() {
+            problems.push("Invalid position sizing configuration".to_string());
+        }
+
+        // Validate trader addresses
+        if let Err(e) = self.traders.get_addresses() {
+            problems.push(problem(e));
+        }
+        for profile in &self.traders.profiles {
+            if let Err(e) = profile.get_address() {
+                problems.push(problem(e));
+            }
+            if profile.copy_ratio <= Decimal::ZERO {
+                problems.push(format!(
+                    "copy_ratio for trader {} must be positive",
+                    profile.address
+                ));
+            }
+            if let Some(ref onboarding) = profile.onboarding {
+                if let Err(e) = OnboardingPolicy::parse(onboarding, 0) {
+                    problems.push(format!("trader {}: {}", profile.address, problem(e)));
+                }
+            }
+        }
+        if let Err(e) = OnboardingPolicy::parse(&self.traders.onboarding, 0) {
+            problems.push(problem(e));
+        }
+        let labels = self.traders.profiles.iter().flat_map(|p| &p.labels);
+        if self
+            .general
+            .labels
+            .iter()
+            .chain(labels)
+            .any(|l| l.trim().is_empty())
+        {
+            problems.push("labels must not be empty".to_string());
+        }
+        let mut names = HashSet::new();
+        for strategy in &self.strategies {
+            let name = &strategy.name;
+            if name.trim().is_empty() {
+                problems.push("strategy name must not be empty".to_string());
+            } else if !names.insert(name) {
+                problems.push(format!("strategy {} is defined twice", name));
+            }
+            if strategy.tracked_accounts.is_empty() {
+                problems.push(format!("strategy {} tracks no traders", name));
+            }
+            if let Err(e) = strategy.get_addresses() {
+                problems.push(format!("strategy {}: {}", name, problem(e)));
+            }
+            if strategy.budget_usdc.is_some_and(|b| b <= Decimal::ZERO) {
+                problems.push(format!("budget_usdc of strategy {} must be positive", name));
+            }
+            if strategy
+                .position_sizing
+                .as_ref()
+                .is_some_and(|s| !s.is_valid())
+            {
+                problems.push(format!("Invalid position sizing for strategy {}", name));
+            }
+        }
+
+        // Validate backtest config
+        if !matches!(self.backtest.unfilled_policy.as_str(), "drop" | "roll") {
+            problems.push("unfilled_policy must be 'drop' or 'roll'".to_string());
+        }
+        if !matches!(
+            self.backtest.end_of_backtest.as_str(),
+            "last_price" | "resolution" | "mark" | "unrealized"
+        ) {
+            problems.push(
+                "end_of_backtest must be 'last_price', 'resolution', 'mark' or 'unrealized'"
+                    .to_string(),
+            );
+        }
+        if self.backtest.chunk_size == 0 {
+            problems.push("chunk_size must be greater than zero".to_string());
+        }
+        if self.backtest.slippage_model == "calibrated"
+            && self.backtest.calibrated_slippage.is_none()
+        {
+            problems.push(
+                "slippage_model = \"calibrated\" requires a [backtest.calibrated_slippage] section"
+                    .to_string(),
+            );
+        }
+        let competition = &self.backtest.competition;
+        if competition.copier_size_fraction < Decimal::ZERO
+            || competition.ahead_fraction < Decimal::ZERO
+            || competition.ahead_fraction > Decimal::ONE
+        {
+            problems.push(
+                "competition copier_size_fraction must be non-negative and ahead_fraction between 0 and 1"
+                    .to_string(),
+            );
+        }
+        for scenario in &self.backtest.scenarios {
+            if let Err(e) = crate::backtest::scenario::Scenario::from_config(scenario) {
+                problems.push(problem(e));
+            }
+        }
+
+        // Validate database config; "none" predates the jsonl name
+        match self.database.db_type.as_str() {
+            "jsonl" | "none" => {}
+            "sqlite" | "postgres" if cfg!(feature = "database") => {
+                if self.database.max_connections == 0 {
+                    problems.push("max_connections must be greater than zero".to_string());
+                }
+            }
+            "sqlite" | "postgres" => {
+                problems.push(format!(
+                    "db_type = \"{}\" requires building with --features database",
+                    self.database.db_type
+                ));
+            }
+            other => {
+                problems.push(format!(
+                    "Invalid db_type '{}'. Must be 'jsonl', 'sqlite' or 'postgres'",
+                    other
+                ));
+            }
+        }
+
+        // Validate export config
+        if self.export.enabled && self.export.interval_hours == 0 {
+            problems.push("export interval_hours must be greater than zero".to_string());
+        }
+        if self.export.google_sheet_id.is_some() != self.export.google_credentials_path.is_some() {
+            problems.push(
+                "google_sheet_id and google_credentials_path must be set together".to_string(),
+            );
+        }
+
+        // Validate coordination config
+        match self.coordination.backend.as_str() {
+            "none" | "file" => {}
+            "redis" if cfg!(feature = "redis") => {
+                if self.coordination.redis_url.is_none() {
+                    problems.push("coordination backend \"redis\" requires redis_url".to_string());
+                }
+            }
+            "redis" => {
+                problems.push(
+                    "coordination backend \"redis\" requires building with --features redis"
+                        .to_string(),
+                );
+            }
+            other => {
+                problems.push(format!(
+                    "Invalid coordination backend '{}'. Must be 'none', 'file' or 'redis'",
+                    other
+                ));
+            }
+        }
+
+        if self.signals.enabled && self.signals.queue_size == 0 {
+            problems.push("signals queue_size must be greater than zero".to_string());
+        }
+
+        if self
+            .gas
+            .max_gas_price_gwei
+            .is_some_and(|cap| cap <= Decimal::ZERO)
+        {
+            problems.push("gas max_gas_price_gwei must be greater than zero".to_string());
+        }
+
+        // Validate publishing config
+        let publishing = &self.publishing;
+        match publishing.backend.as_str() {
+            "none" => {}
+            backend @ ("nats" | "kafka") => {
+                let built = match backend {
+                    "nats" => cfg!(feature = "nats"),
+                    _ => cfg!(feature = "kafka"),
+                };
+                if !built {
+                    problems.push(format!(
+                        "publishing backend \"{0}\" requires building with --features {0}",
+                        backend
+                    ));
+                } else if publishing.url.is_none() {
+                    problems.push(format!("publishing backend \"{}\" requires url", backend));
+                }
+                if publishing.topic_prefix.is_empty() {
+                    problems.push("publishing topic_prefix must not be empty".to_string());
+                }
+            }
+            other => {
+                problems.push(format!(
+                    "Invalid publishing backend '{}'. Must be 'none', 'nats' or 'kafka'",
+                    other
+                ));
+            }
+        }
+        if self.coordination.lease_seconds < 3 {
+            problems.push("lease_seconds must be at least 3".to_string());
+        }
+
+        // Validate price cache config
+        if !matches!(self.prices.source.as_str(), "websocket" | "poll") {
+            problems.push(format!(
+                "Invalid price source '{}'. Must be 'websocket' or 'poll'",
+                self.prices.source
             ));
         }
+        if self.prices.poll_interval_seconds == 0 {
+            problems.push("prices poll_interval_seconds must be greater than zero".to_string());
+        }
 
-        Ok(())
+        if self.execution.max_detection_latency_seconds == 0 {
+            problems.push("max_detection_latency_seconds must be greater than zero".to_string());
+        }
+
+        // Validate recorder config
+        if self.general.mode == "record" && self.recorder.markets.is_empty() {
+            problems
+                .push("record mode needs at least one market in [recorder] markets".to_string());
+        }
+        if self.recorder.interval_seconds == 0 || self.recorder.depth == 0 {
+            problems
+                .push("recorder interval_seconds and depth must be greater than zero".to_string());
+        }
+
+        // Validate failover config
+        if self.failover.failure_threshold == 0 {
+            problems.push("failover failure_threshold must be greater than zero".to_string());
+        }
+        if self.failover.request_timeout_seconds == 0 {
+            problems.push("failover request_timeout_seconds must be greater than zero".to_string());
+        }
+        if self.failover.probe_interval_seconds == 0 {
+            problems.push("failover probe_interval_seconds must be greater than zero".to_string());
+        }
+        if self.network.timeout_seconds == 0 || self.network.connect_timeout_seconds == 0 {
+            problems.push(
+                "network timeout_seconds and connect_timeout_seconds must be greater than zero"
+                    .to_string(),
+            );
+        }
+        if let Some(ref proxy_url) = self.network.proxy_url {
+            if let Err(e) = reqwest::Proxy::all(proxy_url) {
+                problems.push(format!(
+                    "network proxy_url '{}' is invalid: {}",
+                    proxy_url, e
+                ));
+            }
+        }
+        if self.metrics.enabled
+            && self
+                .metrics
+                .listen_addr
+                .parse::<std::net::SocketAddr>()
+                .is_err()
+        {
+            problems.push(format!(
+                "metrics listen_addr '{}' is not an address like 127.0.0.1:9464",
+                self.metrics.listen_addr
+            ));
+        }
+        if self.metrics.enabled && self.metrics.exposure_interval_seconds == 0 {
+            problems
+                .push("metrics exposure_interval_seconds must be greater than zero".to_string());
+        }
+
+        if self.resolutions.enabled
+            && (self.resolutions.check_interval_minutes == 0 || self.resolutions.lookback_days == 0)
+        {
+            problems.push(
+                "resolutions check_interval_minutes and lookback_days must be greater than zero"
+                    .to_string(),
+            );
+        }
+        if self.pnl.update_interval_seconds == 0 {
+            problems.push("pnl update_interval_seconds must be greater than zero".to_string());
+        }
+        if self.pnl.daily_summary_at().is_err() {
+            problems.push(format!(
+                "pnl daily_summary_time '{}' is not a time like 08:00",
+                self.pnl.daily_summary_time.as_deref().unwrap_or_default()
+            ));
+        }
+
+        let budget = &self.error_budget;
+        if budget.max_failure_rate <= Decimal::ZERO || budget.max_failure_rate > Decimal::ONE {
+            problems.push("error_budget max_failure_rate must be between 0 and 1".to_string());
+        }
+        if budget.resume_failure_rate < Decimal::ZERO
+            || budget.resume_failure_rate > budget.max_failure_rate
+        {
+            problems.push(
+                "error_budget resume_failure_rate must be between 0 and max_failure_rate"
+                    .to_string(),
+            );
+        }
+        if budget.window_seconds == 0 || budget.check_interval_seconds == 0 {
+            problems.push(
+                "error_budget window_seconds and check_interval_seconds must be greater than zero"
+                    .to_string(),
+            );
+        }
+
+        // Validate risk config
+        if self
+            .risk
+            .max_market_exposure_usdc
+            .is_some_and(|cap| cap <= Decimal::ZERO)
+        {
+            problems.push("max_market_exposure_usdc must be greater than zero".to_string());
+        }
+        if self
+            .risk
+            .max_event_exposure_usdc
+            .is_some_and(|cap| cap <= Decimal::ZERO)
+        {
+            problems.push("max_event_exposure_usdc must be greater than zero".to_string());
+        }
+        if self.risk.var_confidence <= Decimal::new(5, 1)
+            || self.risk.var_confidence >= Decimal::ONE
+        {
+            problems.push("var_confidence must be between 0.5 and 1".to_string());
+        }
+        for correlation in [
+            self.risk.var_event_correlation,
+            self.risk.var_cross_event_correlation,
+        ] {
+            if !(0.0..=1.0).contains(&correlation) {
+                problems.push("VaR correlations must be between 0 and 1".to_string());
+            }
+        }
+        if self
+            .risk
+            .max_drawdown_percent
+            .is_some_and(|limit| limit <= Decimal::ZERO || limit >= Decimal::ONE)
+        {
+            problems.push("max_drawdown_percent must be between 0 and 1".to_string());
+        }
+        if self.risk.drawdown_check_interval_seconds == 0 {
+            problems.push("drawdown_check_interval_seconds must be greater than zero".to_string());
+        }
+        if self.risk.rebalance_enabled {
+            if self.risk.max_market_exposure_usdc.is_none() {
+                problems.push("rebalance_enabled requires max_market_exposure_usdc".to_string());
+            }
+            if self.risk.rebalance_band < Decimal::ZERO || self.risk.rebalance_band >= Decimal::ONE
+            {
+                problems.push("rebalance_band must be between 0 and 1".to_string());
+            }
+            if self.risk.rebalance_interval_seconds == 0 {
+                problems.push("rebalance_interval_seconds must be greater than zero".to_string());
+            }
+        }
+
+        match (
+            self.risk.trailing_stop_cents,
+            self.risk.trailing_stop_percent,
+        ) {
+            (Some(_), Some(_)) => {
+                problems.push(
+                    "Set only one of trailing_stop_cents and trailing_stop_percent".to_string(),
+                );
+            }
+            (Some(cents), None) if cents <= Decimal::ZERO || cents >= Decimal::ONE_HUNDRED => {
+                problems.push("trailing_stop_cents must be between 0 and 100".to_string());
+            }
+            (None, Some(fraction)) if fraction <= Decimal::ZERO || fraction >= Decimal::ONE => {
+                problems.push("trailing_stop_percent must be between 0 and 1".to_string());
+            }
+            _ => {}
+        }
+        if !matches!(self.risk.stop_action.as_str(), "sell" | "hedge") {
+            problems.push(format!(
+                "Invalid stop_action '{}'. Must be 'sell' or 'hedge'",
+                self.risk.stop_action
+            ));
+        }
+        if self
+            .risk
+            .hedge_drawdown_percent
+            .is_some_and(|fraction| fraction <= Decimal::ZERO || fraction >= Decimal::ONE)
+        {
+            problems.push("hedge_drawdown_percent must be between 0 and 1".to_string());
+        }
+        if self.risk.max_holding_days == Some(0) || self.risk.exit_hours_before_end == Some(0) {
+            problems.push(
+                "max_holding_days and exit_hours_before_end must be greater than zero".to_string(),
+            );
+        }
+        if self.risk.check_interval_seconds == 0 {
+            problems.push("risk check_interval_seconds must be greater than zero".to_string());
+        }
+
+        // Validate trader weighting
+        let weighting = &self.traders.weighting;
+        if weighting.min_weight <= Decimal::ZERO || weighting.min_weight > weighting.max_weight {
+            problems.push(
+                "weighting min_weight must be positive and no larger than max_weight".to_string(),
+            );
+        }
+        if weighting.lookback_days == 0 || weighting.refresh_minutes == 0 {
+            problems.push(
+                "weighting lookback_days and refresh_minutes must be greater than zero".to_string(),
+            );
+        }
+
+        // Validate trader pruning
+        let pruning = &self.traders.pruning;
+        if pruning.lookback_days == 0
+            || pruning.probation_days == 0
+            || pruning.check_interval_minutes == 0
+        {
+            problems.push(
+                "pruning lookback_days, probation_days and check_interval_minutes must be greater than zero"
+                    .to_string(),
+            );
+        }
+        if pruning
+            .min_win_rate
+            .is_some_and(|rate| rate < Decimal::ZERO || rate > Decimal::ONE)
+        {
+            problems.push("pruning min_win_rate must be between 0 and 1".to_string());
+        }
+        if pruning.enabled && pruning.min_roi.is_none() && pruning.min_win_rate.is_none() {
+            problems
+                .push("pruning is enabled but neither min_roi nor min_win_rate is set".to_string());
+        }
+
+        // Validate wash trading config
+        if !matches!(self.wash_trading.action.as_str(), "flag" | "suspend") {
+            problems.push(format!(
+                "Invalid wash_trading action '{}'. Must be 'flag' or 'suspend'",
+                self.wash_trading.action
+            ));
+        }
+        if self.wash_trading.signal_threshold == 0 {
+            problems.push("wash_trading signal_threshold must be greater than zero".to_string());
+        }
+
+        // Validate schedule config
+        if self.schedule.enabled {
+            if let Err(e) = crate::execution::schedule::TradingSchedule::from_config(&self.schedule)
+            {
+                problems.push(problem(e));
+            }
+        }
+
+        let conviction = &self.position_sizing.conviction;
+        if conviction.enabled
+            && (conviction.baseline <= Decimal::ZERO
+                || conviction.min_multiplier <= Decimal::ZERO
+                || conviction.min_multiplier > conviction.max_multiplier)
+        {
+            problems.push(
+                "conviction baseline and min_multiplier must be positive and min_multiplier no larger than max_multiplier"
+                    .to_string(),
+            );
+        }
+
+        let volatility = &self.position_sizing.volatility;
+        if volatility.enabled
+            && (volatility.target_risk <= Decimal::ZERO
+                || volatility.window_minutes == 0
+                || volatility.min_samples < 2)
+        {
+            problems.push(
+                "volatility target_risk and window_minutes must be positive and min_samples at least 2"
+                    .to_string(),
+            );
+        }
+
+        // Validate execution config
+        if !matches!(
+            self.execution.price_sum_check.as_str(),
+            "off" | "flag" | "skip"
+        ) {
+            problems.push(format!(
+                "Invalid price_sum_check '{}'. Must be 'off', 'flag' or 'skip'",
+                self.execution.price_sum_check
+            ));
+        }
+        if !matches!(self.execution.execution_style.as_str(), "taker" | "passive") {
+            problems.push(format!(
+                "Invalid execution_style '{}'. Must be 'taker' or 'passive'",
+                self.execution.execution_style
+            ));
+        }
+        if self.execution.passive_timeout_seconds == 0 {
+            problems.push("passive_timeout_seconds must be greater than zero".to_string());
+        }
+        if self.execution.reprice_steps > 0
+            && (self.execution.reprice_step <= Decimal::ZERO
+                || self.execution.max_reprice_deviation <= Decimal::ZERO)
+        {
+            problems.push("reprice_step and max_reprice_deviation must be positive".to_string());
+        }
+        if self.execution.max_price_gap <= Decimal::ZERO {
+            problems.push("max_price_gap must be positive".to_string());
+        }
+        if self.execution.min_trade_size_usdc >= self.execution.max_trade_size_usdc {
+            problems.push("min_trade_size_usdc must be less than max_trade_size_usdc".to_string());
+        }
+        if self.execution.settlement_check_min_usdc.is_some()
+            && self.execution.settlement_timeout_seconds == 0
+        {
+            problems.push("settlement_timeout_seconds must be greater than zero".to_string());
+        }
+        if self.execution.failed_copy_retry_seconds > 0
+            && (self.execution.failed_copy_retry_interval_seconds == 0
+                || self.execution.failed_copy_max_price_move <= Decimal::ZERO)
+        {
+            problems.push(
+                "failed_copy_retry_interval_seconds and failed_copy_max_price_move must be positive"
+                    .to_string(),
+            );
+        }
+        if self.execution.order_sweep_interval_seconds > 0
+            && self.execution.max_open_order_age_seconds == 0
+            && !self.execution.cancel_after_trader_exit
+        {
+            problems.push(
+                "order_sweep_interval_seconds needs max_open_order_age_seconds or \
+                 cancel_after_trader_exit"
+                    .to_string(),
+            );
+        }
+        if let Err(e) = DetectionMode::parse(&self.execution.detection_mode) {
+            problems.push(problem(e));
+        }
+        for priority in &self.execution.execution_priority {
+            if let Err(e) = Priority::parse(priority) {
+                problems.push(problem(e));
+            }
+        }
+
+        problems
     }
 
-    /// Expand environment variables in configuration
+    /// Resolve the variables `load_from_file` leaves unset in keys that have a
+    /// fallback: a placeholder key for backtests, and no Slack webhook
     pub fn expand_env_vars(&mut self) -> Result<()> {
         // Expand wallet private key
-        if self.general.wallet_private_key.starts_with("${") && self.general.wallet_private_key.ends_with("}") {
-            let var_name = &self.general.wallet_private_key[2..self.general.wallet_private_key.len() - 1];
+        if self.general.wallet_private_key.starts_with("${")
+            && self.general.wallet_private_key.ends_with("}")
+        {
+            let var_name =
+                &self.general.wallet_private_key[2..self.general.wallet_private_key.len() - 1];
 
-            // In backtest mode, wallet private key is not needed, so use a placeholder if not set
-            if self.general.mode == "backtest" {
-                self.general.wallet_private_key = std::env::var(var_name)
-                    .unwrap_or_else(|_| "0x0000000000000000000000000000000000000000000000000000000000000000".to_string());
+            // Modes that never sign don't need the key, so use a placeholder if not set
+            if WALLETLESS_MODES.contains(&self.general.mode.as_str()) {
+                self.general.wallet_private_key = std::env::var(var_name).unwrap_or_else(|_| {
+                    "0x0000000000000000000000000000000000000000000000000000000000000000".to_string()
+                });
             } else {
                 self.general.wallet_private_key = std::env::var(var_name).map_err(|_| {
                     PolymarketError::ConfigError(format!(
@@ -185,10 +2426,251 @@ impl Config {
     }
 }
 
+/// One error listing every problem found, or just the problem if there's one
+fn config_problems(problems: Vec<String>) -> PolymarketError {
+    if problems.len() == 1 {
+        return PolymarketError::ConfigError(problems.into_iter().next().unwrap_or_default());
+    }
+    let list: Vec<String> = problems.iter().map(|p| format!("  - {}", p)).collect();
+    PolymarketError::ConfigError(format!(
+        "{} problems in the configuration:\n{}",
+        problems.len(),
+        list.join("\n")
+    ))
+}
+
+/// The message of an error raised while validating part of the config
+fn problem(error: PolymarketError) -> String {
+    match error {
+        PolymarketError::ConfigError(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// The known key next to `unknown` (a dotted path) with the most similar name
+///
+/// Only close matches count: at most a third of the name may differ.
+fn closest_known_key(known: &toml::Value, unknown: &str) -> Option<String> {
+    let (parent, name) = unknown.rsplit_once('.').unwrap_or(("", unknown));
+    let mut table = known;
+    for segment in parent.split('.').filter(|s| !s.is_empty()) {
+        table = match segment.parse::<usize>() {
+            Ok(index) => table.get(index)?,
+            Err(_) => table.get(segment)?,
+        };
+    }
+    table
+        .as_table()?
+        .keys()
+        .map(|key| (edit_distance(key, name), key))
+        .filter(|(distance, _)| *distance * 3 <= name.len().max(3))
+        .min()
+        .map(|(_, key)| key.clone())
+}
+
+/// Levenshtein distance between two keys
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Sensitive keys that can be read from a file named by `<key>_file` instead
+const SECRET_KEYS: [(&str, &str); 5] = [
+    ("general", "wallet_private_key"),
+    ("general", "wallet_mnemonic"),
+    ("notifications", "slack_webhook_url"),
+    ("database", "db_connection"),
+    ("coordination", "redis_url"),
+];
+
+/// Fill each secret key that names a file with the file's contents, before
+/// the config is deserialized
+fn resolve_secret_files(value: &mut toml::Value, problems: &mut Vec<String>) {
+    for (section, key) in SECRET_KEYS {
+        let Some(table) = value.get_mut(section).and_then(|t| t.as_table_mut()) else {
+            continue;
+        };
+        let file_key = format!("{}_file", key);
+        let Some(path) = table
+            .get(&file_key)
+            .and_then(|p| p.as_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        if table.contains_key(key) {
+            problems.push(format!(
+                "set only one of {}.{} and {}.{}",
+                section, key, section, file_key
+            ));
+            continue;
+        }
+        match crate::secrets::read_secret(&path) {
+            Ok(secret) => {
+                table.insert(key.to_string(), toml::Value::String(secret));
+            }
+            Err(e) => problems.push(problem(e)),
+        }
+    }
+}
+
+/// Modes that never sign with the wallet (observe signs with a throwaway key)
+const WALLETLESS_MODES: [&str; 3] = ["backtest", "observe", "record"];
+
+/// Keys whose unset variables are resolved later by `Config::expand_env_vars`
+const ENV_FALLBACK_KEYS: [&str; 2] = [
+    "general.wallet_private_key",
+    "notifications.slack_webhook_url",
+];
+
+/// Expand variables in every string of a parsed config, collecting the key and
+/// name of each variable that is unset and has no default
+fn expand_env_in_value(value: &mut toml::Value, key: &str, missing: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::String(text) => match expand_env(text) {
+            Ok(expanded) => *text = expanded,
+            Err(var) => missing.push((key.to_string(), var)),
+        },
+        toml::Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                expand_env_in_value(item, &format!("{}[{}]", key, i), missing);
+            }
+        }
+        toml::Value::Table(table) => {
+            for (name, item) in table.iter_mut() {
+                let key = if key.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", key, name)
+                };
+                expand_env_in_value(item, &key, missing);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` in `text` with the variable's value
+///
+/// As in the shell, the default also applies when the variable is empty.
+/// Returns the name of the first variable that is unset without a default;
+/// values are inserted as-is and not expanded again.
+fn expand_env(text: &str) -> std::result::Result<String, String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let reference = &rest[start + 2..];
+        let Some(end) = reference.find('}') else {
+            expanded.push_str(&rest[start..]);
+            return Ok(expanded);
+        };
+        let (name, default) = match reference[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&reference[..end], None),
+        };
+        match (std::env::var(name), default) {
+            (Ok(value), Some(default)) if value.is_empty() => expanded.push_str(default),
+            (Ok(value), _) => expanded.push_str(&value),
+            (Err(_), Some(default)) => expanded.push_str(default),
+            (Err(_), None) => return Err(name.to_string()),
+        }
+        rest = &reference[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_example_config_is_strictly_valid() {
+        // Every key documented in the example must be known and valid
+        let config = Config::load_from_file("config.example.toml").unwrap();
+        assert_eq!(config.general.mode, "backtest");
+    }
+
+    #[test]
+    fn test_problems_are_reported_together() {
+        let content = fs::read_to_string("config.example.toml")
+            .unwrap()
+            .replace("price_sum_tolerance", "price_sum_tolerence")
+            .replace("lease_seconds = 15", "lease_seconds = 1")
+            .replace("min_weight = 0.25", "min_weight = 0");
+        let path = std::env::temp_dir().join(format!("config-{}.toml", uuid::Uuid::new_v4()));
+        fs::write(&path, content).unwrap();
+        let error = Config::load_from_file(&path).unwrap_err().to_string();
+        let _ = fs::remove_file(&path);
+
+        assert!(error.contains("3 problems"), "{}", error);
+        assert!(error.contains(
+            "unknown key `execution.price_sum_tolerence` (did you mean `price_sum_tolerance`?)"
+        ));
+        assert!(error.contains("lease_seconds must be at least 3"));
+        assert!(error.contains("min_weight must be positive"));
+    }
+
+    #[test]
+    fn test_expand_env_everywhere() {
+        std::env::set_var("POLYCOPY_TEST_DB_USER", "copier");
+        std::env::set_var("POLYCOPY_TEST_EMPTY", "");
+        let mut value: toml::Value = toml::from_str(
+            r#"
+            [database]
+            db_connection = "postgres://${POLYCOPY_TEST_DB_USER}@${POLYCOPY_TEST_DB_HOST:-localhost}/trades"
+            [traders]
+            tracked_accounts = ["${POLYCOPY_TEST_EMPTY:-0xabc}", "${POLYCOPY_TEST_UNSET}"]
+            "#,
+        )
+        .unwrap();
+        let mut missing = Vec::new();
+        expand_env_in_value(&mut value, "", &mut missing);
+
+        assert_eq!(
+            value["database"]["db_connection"].as_str(),
+            Some("postgres://copier@localhost/trades")
+        );
+        assert_eq!(
+            value["traders"]["tracked_accounts"][0].as_str(),
+            Some("0xabc")
+        );
+        assert_eq!(
+            missing,
+            vec![(
+                "traders.tracked_accounts[1]".to_string(),
+                "POLYCOPY_TEST_UNSET".to_string()
+            )]
+        );
+        // An unterminated reference is left alone
+        assert_eq!(expand_env("cost ${5").unwrap(), "cost ${5");
+    }
+
+    #[test]
+    fn test_walletless_modes_need_no_wallet_key() {
+        let mut config = Config::load_from_file("config.example.toml").unwrap();
+        config.general.wallet_private_key = "${POLYCOPY_TEST_UNSET_WALLET}".to_string();
+        for mode in WALLETLESS_MODES {
+            let mut config = config.clone();
+            config.general.mode = mode.to_string();
+            assert!(config.expand_env_vars().is_ok(), "{}", mode);
+        }
+
+        config.general.mode = "live".to_string();
+        let error = config.expand_env_vars().unwrap_err().to_string();
+        assert!(error.contains("POLYCOPY_TEST_UNSET_WALLET not set"));
+    }
+
     #[test]
     fn test_position_sizing_validation() {
         let valid_config = PositionSizingConfig {
@@ -196,6 +2678,11 @@ mod tests {
             max_position_size_relative: Decimal::new(1, 1), // 0.1
             strategy: "hybrid".to_string(),
             priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
         };
         assert!(valid_config.is_valid());
 
@@ -204,6 +2691,11 @@ mod tests {
             max_position_size_relative: Decimal::new(15, 1), // 1.5 > 1.0
             strategy: "hybrid".to_string(),
             priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
         };
         assert!(!invalid_config.is_valid());
     }
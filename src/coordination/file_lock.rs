@@ -0,0 +1,174 @@
+use crate::coordination::LockBackend;
+use crate::errors::{PolymarketError, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+/// A guard file older than this belongs to a crashed instance
+const GUARD_STALE: Duration = Duration::from_secs(5);
+const GUARD_RETRY: Duration = Duration::from_millis(10);
+const GUARD_ATTEMPTS: u32 = 100;
+
+#[derive(Serialize, Deserialize)]
+struct Lease {
+    instance: String,
+    /// Unix time in milliseconds
+    expires_at: u64,
+}
+
+/// Lock store in a directory shared by all instances (local disk or NFS)
+///
+/// Lease expiry is compared against each host's wall clock, so hosts need
+/// synchronized clocks.
+pub struct FileLock {
+    dir: PathBuf,
+    last_prune: Mutex<SystemTime>,
+}
+
+impl FileLock {
+    pub fn new(dir: &str) -> Result<Self> {
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(dir.join("claims"))?;
+        Ok(Self {
+            dir,
+            last_prune: Mutex::new(SystemTime::now()),
+        })
+    }
+
+    /// Run `f` while holding the directory's guard file, serializing lease updates
+    async fn with_guard<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let guard = self.dir.join("leader.guard");
+        for _ in 0..GUARD_ATTEMPTS {
+            match OpenOptions::new().write(true).create_new(true).open(&guard) {
+                Ok(_) => {
+                    let result = f();
+                    fs::remove_file(&guard)?;
+                    return result;
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    if is_older_than(&guard, GUARD_STALE) {
+                        debug!("Removing stale lock guard {}", guard.display());
+                        let _ = fs::remove_file(&guard);
+                        continue;
+                    }
+                    tokio::time::sleep(GUARD_RETRY).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(PolymarketError::StorageError(format!(
+            "Timed out waiting for lock guard {}",
+            guard.display()
+        )))
+    }
+
+    /// Delete claims that have outlived `ttl`, at most once per `ttl`
+    fn prune_claims(&self, ttl: Duration) -> Result<()> {
+        {
+            let mut last_prune = self.last_prune.lock().unwrap_or_else(|e| e.into_inner());
+            if last_prune.elapsed().unwrap_or_default() < ttl {
+                return Ok(());
+            }
+            *last_prune = SystemTime::now();
+        }
+
+        for entry in fs::read_dir(self.dir.join("claims"))? {
+            let path = entry?.path();
+            if is_older_than(&path, ttl) {
+                let _ = fs::remove_file(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LockBackend for FileLock {
+    async fn acquire_leader(&self, instance: &str, lease: Duration) -> Result<bool> {
+        let path = self.dir.join("leader.json");
+        self.with_guard(|| {
+            let now = now_millis();
+            let current: Option<Lease> = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok());
+
+            let free = match current {
+                Some(ref held) => held.instance == instance || held.expires_at <= now,
+                None => true,
+            };
+            if free {
+                let lease = Lease {
+                    instance: instance.to_string(),
+                    expires_at: now + lease.as_millis() as u64,
+                };
+                write_atomic(&path, &serde_json::to_string(&lease)?)?;
+            }
+            Ok(free)
+        })
+        .await
+    }
+
+    async fn release_leader(&self, instance: &str) -> Result<()> {
+        let path = self.dir.join("leader.json");
+        self.with_guard(|| {
+            let held: Option<Lease> = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok());
+            if held.is_some_and(|lease| lease.instance == instance) {
+                fs::remove_file(&path)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn claim(&self, key: &str, instance: &str, ttl: Duration) -> Result<bool> {
+        self.prune_claims(ttl)?;
+
+        let path = self.dir.join("claims").join(key);
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(instance.as_bytes())?;
+                    return Ok(true);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    if !is_older_than(&path, ttl) {
+                        return Ok(false);
+                    }
+                    // Expired claim; remove it and race for a fresh one
+                    let _ = fs::remove_file(&path);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn is_older_than(path: &Path, age: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map(|modified| modified.elapsed().unwrap_or_default() > age)
+        .unwrap_or(false)
+}
+
+/// Replace `path` with `content` so readers never see a partial write
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
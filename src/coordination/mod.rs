@@ -0,0 +1,260 @@
+pub mod file_lock;
+#[cfg(feature = "redis")]
+pub mod redis_lock;
+
+use crate::config::CoordinationConfig;
+use crate::errors::Result;
+use crate::models::Trade;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sha3::{Digest, Keccak256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Width of the detection-time buckets position diffs are claimed in;
+/// instances see the same change within a poll or two of each other
+const CLAIM_BUCKET_SECONDS: i64 = 60;
+
+/// Shared store that instances use to agree on who trades
+#[async_trait]
+pub trait LockBackend: Send + Sync {
+    /// Take or renew the leader lease; true if `instance` holds it afterwards
+    async fn acquire_leader(&self, instance: &str, lease: Duration) -> Result<bool>;
+
+    /// Give up the lease if `instance` holds it
+    async fn release_leader(&self, instance: &str) -> Result<()>;
+
+    /// Claim `key` for `ttl`; true only for the first instance to ask
+    async fn claim(&self, key: &str, instance: &str, ttl: Duration) -> Result<bool>;
+}
+
+/// Leader election plus per-trade claims, so only one instance copies each trade
+///
+/// Followers keep polling (so their view of trader positions stays current) but
+/// don't execute. When the leader stops renewing its lease another instance takes
+/// over; the per-trade claim covers the moment where both believe they lead.
+pub struct Coordinator {
+    backend: Arc<dyn LockBackend>,
+    instance_id: String,
+    lease: Duration,
+    trade_claim: Duration,
+    leader: AtomicBool,
+}
+
+impl Coordinator {
+    pub fn new(
+        backend: Arc<dyn LockBackend>,
+        instance_id: String,
+        lease: Duration,
+        trade_claim: Duration,
+    ) -> Self {
+        Self {
+            backend,
+            instance_id,
+            lease,
+            trade_claim,
+            leader: AtomicBool::new(false),
+        }
+    }
+
+    /// Coordinator for the configured backend, or None when coordination is off
+    pub async fn from_config(config: &CoordinationConfig) -> Result<Option<Self>> {
+        let backend: Arc<dyn LockBackend> = match config.backend.as_str() {
+            "file" => Arc::new(file_lock::FileLock::new(&config.lock_dir)?),
+            #[cfg(feature = "redis")]
+            "redis" => Arc::new(
+                redis_lock::RedisLock::connect(config.redis_url.as_deref().unwrap_or_default())
+                    .await?,
+            ),
+            _ => return Ok(None),
+        };
+
+        let instance_id = config
+            .instance_id
+            .clone()
+            .unwrap_or_else(default_instance_id);
+        Ok(Some(Self::new(
+            backend,
+            instance_id,
+            Duration::from_secs(config.lease_seconds),
+            Duration::from_secs(config.trade_claim_seconds),
+        )))
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leader.load(Ordering::SeqCst)
+    }
+
+    /// Try to take or renew the lease once, logging leadership changes
+    pub async fn renew(&self) -> bool {
+        let leader = match self
+            .backend
+            .acquire_leader(&self.instance_id, self.lease)
+            .await
+        {
+            Ok(leader) => leader,
+            Err(e) => {
+                // Without the lock store we can't know who leads; stand down
+                warn!("Leader lease renewal failed: {}", e);
+                false
+            }
+        };
+
+        let was_leader = self.leader.swap(leader, Ordering::SeqCst);
+        if leader && !was_leader {
+            info!("Instance {} is now the leader", self.instance_id);
+        } else if !leader && was_leader {
+            warn!("Instance {} lost leadership", self.instance_id);
+        }
+        leader
+    }
+
+    /// Renew the lease well inside its lifetime, forever
+    pub async fn run_lease_loop(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(self.lease / 3);
+        loop {
+            interval.tick().await;
+            self.renew().await;
+        }
+    }
+
+    /// Release the lease so a standby takes over without waiting for expiry
+    pub async fn step_down(&self) -> Result<()> {
+        self.leader.store(false, Ordering::SeqCst);
+        self.backend.release_leader(&self.instance_id).await
+    }
+
+    /// Whether this instance should copy `trade`: it must lead and win every
+    /// claim on it
+    pub async fn should_execute(&self, trade: &Trade) -> Result<bool> {
+        if !self.is_leader() {
+            return Ok(false);
+        }
+        for key in claim_keys(trade, Utc::now()) {
+            if !self
+                .backend
+                .claim(&key, &self.instance_id, self.trade_claim)
+                .await?
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Keys identifying a detected trade the same way on every instance
+///
+/// A trade whose id came from its source, such as a trade-list fill's
+/// transaction hash or a signal's id, is keyed by that id. Position diffs get
+/// local ids, so theirs are built from what each instance observes: trader,
+/// market, side, size and price, in the minute it was detected. The minute
+/// before is claimed too, so instances on either side of a minute don't both
+/// copy the change, while a trader repeating a clip later is copied again.
+///
+/// A strategy's copy has the strategy's name appended to the id, which keys
+/// it apart from the trade's other copies either way.
+pub fn claim_keys(trade: &Trade, detected_at: DateTime<Utc>) -> Vec<String> {
+    let hash = |key: String| hex::encode(Keccak256::digest(key.as_bytes()));
+    let Some(strategy) = local_id_suffix(trade) else {
+        return vec![hash(trade.id.clone())];
+    };
+    let observed = format!(
+        "{}|{:?}|{}|{}|{}|{}",
+        strategy,
+        trade.trader,
+        trade.market_id,
+        trade.side,
+        trade.size.normalize(),
+        trade.price.normalize()
+    );
+    let bucket = detected_at.timestamp().div_euclid(CLAIM_BUCKET_SECONDS);
+    [bucket - 1, bucket]
+        .iter()
+        .map(|bucket| hash(format!("{}|{}", observed, bucket)))
+        .collect()
+}
+
+/// What follows the random id this instance gave `trade`, as position diffs
+/// get, e.g. the name of the strategy copying it; None for an id its source gave
+fn local_id_suffix(trade: &Trade) -> Option<&str> {
+    let (base, suffix) = trade.id.split_once(':').unwrap_or((&trade.id, ""));
+    uuid::Uuid::parse_str(base).is_ok().then_some(suffix)
+}
+
+fn default_instance_id() -> String {
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("{}-{}", host, std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    /// A trade as a position diff detects it, under a fresh local id
+    fn diffed() -> Trade {
+        Trade::sample().with_fill(dec!(0.5), dec!(50))
+    }
+
+    #[tokio::test]
+    async fn test_only_one_instance_executes_a_trade() {
+        let dir = "/tmp/test_coordination_locks";
+        let _ = std::fs::remove_dir_all(dir);
+        let backend: Arc<dyn LockBackend> = Arc::new(file_lock::FileLock::new(dir).unwrap());
+        let lease = Duration::from_secs(30);
+        let claim = Duration::from_secs(60);
+        let a = Coordinator::new(backend.clone(), "a".to_string(), lease, claim);
+        let b = Coordinator::new(backend, "b".to_string(), lease, claim);
+
+        assert!(a.renew().await);
+        assert!(!b.renew().await);
+        assert!(!b.should_execute(&diffed()).await.unwrap());
+
+        // Same observed trade under different local ids is only executed once
+        assert!(a.should_execute(&diffed()).await.unwrap());
+        assert!(!a.should_execute(&diffed()).await.unwrap());
+
+        // Trades from the trade list are claimed by their own ids, so a
+        // trader repeating a clip is copied again
+        let listed = |id: &str| diffed().with_id(id).with_market("111");
+        assert!(a.should_execute(&listed("0xaa:111")).await.unwrap());
+        assert!(!a.should_execute(&listed("0xaa:111")).await.unwrap());
+        assert!(a.should_execute(&listed("0xbb:111")).await.unwrap());
+
+        // Failover: once the leader steps down the standby takes over
+        a.step_down().await.unwrap();
+        assert!(b.renew().await);
+        assert!(!a.renew().await);
+        assert!(!b.should_execute(&diffed()).await.unwrap());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_diffs_are_claimed_by_detection_minute() {
+        let trade = diffed();
+        let minute = DateTime::from_timestamp(1_700_000_070, 0).unwrap();
+        let keys = |seconds: i64| claim_keys(&trade, minute + chrono::Duration::seconds(seconds));
+        let shared = |a: Vec<String>, b: Vec<String>| a.iter().any(|key| b.contains(key));
+
+        // Seen 30s apart across the end of a minute, it is still the same trade
+        assert!(shared(keys(0), keys(30)));
+        assert!(!shared(keys(0), keys(180)));
+        assert_eq!(
+            claim_keys(&trade.clone().with_id("0xaa:m1"), minute).len(),
+            1
+        );
+    }
+}
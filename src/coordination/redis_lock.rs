@@ -0,0 +1,84 @@
+use crate::coordination::LockBackend;
+use crate::errors::{PolymarketError, Result};
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::Script;
+use std::time::Duration;
+
+const LEADER_KEY: &str = "polycopy:leader";
+const CLAIM_PREFIX: &str = "polycopy:trade:";
+
+/// Take the lease if it is free or already ours, extending it either way
+const ACQUIRE_SCRIPT: &str = r#"
+local holder = redis.call('GET', KEYS[1])
+if holder == false or holder == ARGV[1] then
+    redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+    return 1
+end
+return 0
+"#;
+
+/// Delete the lease only if we still hold it
+const RELEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Lock store in Redis; keys expire on their own when an instance dies
+pub struct RedisLock {
+    connection: ConnectionManager,
+}
+
+impl RedisLock {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        let connection = ConnectionManager::new(client).await.map_err(redis_error)?;
+        Ok(Self { connection })
+    }
+}
+
+#[async_trait]
+impl LockBackend for RedisLock {
+    async fn acquire_leader(&self, instance: &str, lease: Duration) -> Result<bool> {
+        let mut connection = self.connection.clone();
+        let acquired: i32 = Script::new(ACQUIRE_SCRIPT)
+            .key(LEADER_KEY)
+            .arg(instance)
+            .arg(lease.as_millis() as u64)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(redis_error)?;
+        Ok(acquired == 1)
+    }
+
+    async fn release_leader(&self, instance: &str) -> Result<()> {
+        let mut connection = self.connection.clone();
+        let _: i32 = Script::new(RELEASE_SCRIPT)
+            .key(LEADER_KEY)
+            .arg(instance)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(redis_error)?;
+        Ok(())
+    }
+
+    async fn claim(&self, key: &str, instance: &str, ttl: Duration) -> Result<bool> {
+        let mut connection = self.connection.clone();
+        let set: Option<String> = redis::cmd("SET")
+            .arg(format!("{}{}", CLAIM_PREFIX, key))
+            .arg(instance)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query_async(&mut connection)
+            .await
+            .map_err(redis_error)?;
+        Ok(set.is_some())
+    }
+}
+
+fn redis_error(e: redis::RedisError) -> PolymarketError {
+    PolymarketError::StorageError(format!("Redis error: {}", e))
+}
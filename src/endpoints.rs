@@ -0,0 +1,217 @@
+use crate::config::FailoverConfig;
+use crate::execution::error_budget::{ErrorBudget, Operation};
+use crate::metrics;
+use reqwest::Client;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Default)]
+struct EndpointState {
+    /// Index into `urls` of the endpoint requests go to
+    active: usize,
+    /// Failures of the active endpoint since its last success
+    consecutive_failures: u32,
+}
+
+/// An API's primary base URL and its fallbacks
+///
+/// Callers build requests against [`url`](Self::url) and report how each one
+/// went with [`record`](Self::record). After `failure_threshold` errors or
+/// timeouts in a row the next URL becomes active; while a fallback is active,
+/// [`run_probe`](Self::run_probe) checks the primary and fails back once it
+/// answers. Requests are never retried here, so an order is never sent twice.
+pub struct Endpoints {
+    api: &'static str,
+    urls: Vec<String>,
+    failure_threshold: u32,
+    request_timeout: Duration,
+    state: Mutex<EndpointState>,
+    error_budget: Option<Arc<ErrorBudget>>,
+}
+
+impl Endpoints {
+    pub fn new(
+        api: &'static str,
+        primary: String,
+        fallbacks: &[String],
+        config: &FailoverConfig,
+    ) -> Self {
+        let mut urls = vec![primary];
+        urls.extend(fallbacks.iter().cloned());
+        let endpoints = Self {
+            api,
+            urls: urls
+                .into_iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect(),
+            failure_threshold: config.failure_threshold.max(1),
+            request_timeout: Duration::from_secs(config.request_timeout_seconds),
+            state: Mutex::new(EndpointState::default()),
+            error_budget: None,
+        };
+        endpoints.publish(0);
+        endpoints
+    }
+
+    /// A single URL with no fallbacks
+    pub fn single(api: &'static str, url: String) -> Self {
+        Self::new(api, url, &[], &FailoverConfig::default())
+    }
+
+    /// Count every request's outcome against `error_budget`
+    pub fn with_error_budget(mut self, error_budget: Arc<ErrorBudget>) -> Self {
+        self.error_budget = Some(error_budget);
+        self
+    }
+
+    /// Base URL to send the next request to
+    pub fn url(&self) -> String {
+        self.urls[self.lock().active].clone()
+    }
+
+    /// How long a request may take before it counts as a failure
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    pub fn is_on_fallback(&self) -> bool {
+        self.lock().active != 0
+    }
+
+    /// Report the outcome of a request to `url`
+    ///
+    /// Transport errors, timeouts and 5xx responses are failures; anything
+    /// the server answered deliberately, including 4xx, is a success.
+    /// Outcomes for an endpoint that is no longer active are ignored.
+    pub fn record(&self, url: &str, healthy: bool) {
+        if let Some(ref error_budget) = self.error_budget {
+            error_budget.record(Operation::ApiCall, healthy);
+        }
+        let mut state = self.lock();
+        let active = &self.urls[state.active];
+        if !url.starts_with(active.as_str()) {
+            return;
+        }
+        if healthy {
+            state.consecutive_failures = 0;
+            return;
+        }
+
+        state.consecutive_failures += 1;
+        if state.consecutive_failures < self.failure_threshold || self.urls.len() < 2 {
+            return;
+        }
+        let next = (state.active + 1) % self.urls.len();
+        warn!(
+            "{} API at {} failed {} times in a row; failing over to {}",
+            self.api, active, state.consecutive_failures, self.urls[next]
+        );
+        state.active = next;
+        state.consecutive_failures = 0;
+        drop(state);
+        self.publish(next);
+        metrics::global().increment(
+            "polycopy_endpoint_failovers_total",
+            "Switches from a failing API endpoint to the next one",
+            &[("api", self.api)],
+        );
+    }
+
+    /// Probe the primary every `interval` through `http_client` while on a
+    /// fallback and switch back to it once it responds, forever
+    pub async fn run_probe(self: Arc<Self>, http_client: Client, interval: Duration) {
+        if self.urls.len() < 2 {
+            return;
+        }
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if !self.is_on_fallback() {
+                continue;
+            }
+            let primary = &self.urls[0];
+            let healthy = match http_client
+                .get(primary)
+                .timeout(self.request_timeout)
+                .send()
+                .await
+            {
+                Ok(response) => !response.status().is_server_error(),
+                Err(e) => {
+                    debug!("{} API primary {} still down: {}", self.api, primary, e);
+                    false
+                }
+            };
+            if healthy {
+                info!(
+                    "{} API primary {} is answering again; failing back",
+                    self.api, primary
+                );
+                *self.lock() = EndpointState::default();
+                self.publish(0);
+                metrics::global().increment(
+                    "polycopy_endpoint_failbacks_total",
+                    "Returns to the primary API endpoint after it recovered",
+                    &[("api", self.api)],
+                );
+            }
+        }
+    }
+
+    /// Mark which endpoint is active in the metrics
+    fn publish(&self, active: usize) {
+        for (i, url) in self.urls.iter().enumerate() {
+            metrics::global().gauge(
+                "polycopy_endpoint_active",
+                "1 for the API endpoint requests are sent to, 0 for standby ones",
+                &[("api", self.api), ("url", url)],
+                if i == active { 1.0 } else { 0.0 },
+            );
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, EndpointState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fails_over_after_repeated_failures() {
+        let config = FailoverConfig {
+            failure_threshold: 2,
+            ..FailoverConfig::default()
+        };
+        let endpoints = Endpoints::new(
+            "test",
+            "https://primary.example/".to_string(),
+            &["https://backup.example".to_string()],
+            &config,
+        );
+        assert_eq!(endpoints.url(), "https://primary.example");
+
+        // A success in between resets the count
+        endpoints.record("https://primary.example/order", false);
+        endpoints.record("https://primary.example/order", true);
+        endpoints.record("https://primary.example/order", false);
+        assert!(!endpoints.is_on_fallback());
+
+        endpoints.record("https://primary.example/midpoint", false);
+        assert_eq!(endpoints.url(), "https://backup.example");
+        // Late failures from requests still in flight to the primary don't count
+        endpoints.record("https://primary.example/order", false);
+        endpoints.record("https://primary.example/order", false);
+        assert_eq!(endpoints.url(), "https://backup.example");
+        assert_eq!(
+            metrics::global().value(
+                "polycopy_endpoint_active",
+                &[("api", "test"), ("url", "https://backup.example")]
+            ),
+            Some(1.0)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::SkipReason;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -20,9 +21,11 @@ pub enum PolymarketError {
     #[error("Execution error: {0}")]
     ExecutionError(String),
 
+    #[allow(dead_code)]
     #[error("Position sizing error: {0}")]
     PositionSizingError(String),
 
+    #[allow(dead_code)]
     #[error("Monitoring error: {0}")]
     MonitoringError(String),
 
@@ -32,21 +35,42 @@ pub enum PolymarketError {
     #[error("Storage error: {0}")]
     StorageError(String),
 
+    #[cfg(feature = "database")]
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
     #[error("Insufficient balance")]
     InsufficientBalance,
 
+    #[error("Insufficient liquidity: {0}")]
+    InsufficientLiquidity(String),
+
     #[error("Order timeout")]
     OrderTimeout,
 
+    #[error("Order outcome unknown: {0}")]
+    OrderOutcomeUnknown(String),
+
+    #[error("Rate limited; retry after {0}s")]
+    RateLimited(u64),
+
+    #[allow(dead_code)]
     #[error("Invalid market: {0}")]
     InvalidMarket(String),
 
+    #[error("Market not tradable: {0}")]
+    MarketNotTradable(String),
+
+    #[allow(dead_code)]
     #[error("Invalid order size")]
     InvalidOrderSize,
 
     #[error("Below minimum size")]
     BelowMinimumSize,
 
+    #[error("Skipped: {1}")]
+    Skipped(SkipReason, String),
+
     #[error("Parse error: {0}")]
     ParseError(String),
 
@@ -63,4 +87,34 @@ pub enum PolymarketError {
     Unknown(String),
 }
 
+impl PolymarketError {
+    /// Why the trade wasn't copied, when this error means it was deliberately
+    /// left alone rather than failed
+    pub fn skip_reason(&self) -> Option<SkipReason> {
+        match self {
+            PolymarketError::Skipped(reason, _) => Some(*reason),
+            PolymarketError::MarketNotTradable(_) => Some(SkipReason::MarketNotTradable),
+            PolymarketError::BelowMinimumSize => Some(SkipReason::BelowMinimumSize),
+            PolymarketError::PositionSizingError(_) => Some(SkipReason::SizingFailed),
+            _ => None,
+        }
+    }
+
+    /// Whether the failure may clear up by itself, so the copy is worth
+    /// another try later; placements whose outcome is unknown never are
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PolymarketError::NetworkError(_)
+            | PolymarketError::RateLimited(_)
+            | PolymarketError::InsufficientBalance => true,
+            PolymarketError::ApiError(message) => message.contains("not enough balance"),
+            PolymarketError::Skipped(reason, _) => matches!(
+                reason,
+                SkipReason::InsufficientBalance | SkipReason::BalanceUnavailable
+            ),
+            _ => false,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, PolymarketError>;
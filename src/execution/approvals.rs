@@ -0,0 +1,239 @@
+use crate::errors::{PolymarketError, Result};
+use crate::execution::gas::GasTracker;
+use ethers::abi::{encode, Token};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, TransactionRequest, U256};
+use ethers::utils::id;
+
+/// USDC.e, the collateral Polymarket settles in
+pub const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+/// Conditional Tokens contract holding the outcome tokens
+pub const CONDITIONAL_TOKENS: &str = "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045";
+
+/// Exchange contracts that settle matched orders
+pub const CTF_EXCHANGE: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+pub const NEG_RISK_CTF_EXCHANGE: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+
+/// Contracts that move our USDC and outcome tokens when orders fill
+pub const SPENDERS: &[(&str, &str)] = &[
+    ("CTF Exchange", CTF_EXCHANGE),
+    ("Neg Risk CTF Exchange", NEG_RISK_CTF_EXCHANGE),
+    (
+        "Neg Risk Adapter",
+        "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296",
+    ),
+];
+
+/// Allowance below which USDC approval is treated as missing; approvals are
+/// granted for the maximum, so anything lower was set by hand
+const MIN_ALLOWANCE: u64 = 1_000_000_000_000;
+
+/// Which token an approval is for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApprovalToken {
+    Usdc,
+    OutcomeTokens,
+}
+
+impl std::fmt::Display for ApprovalToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalToken::Usdc => write!(f, "USDC"),
+            ApprovalToken::OutcomeTokens => write!(f, "outcome tokens"),
+        }
+    }
+}
+
+/// One token approval the exchange needs from the trading address
+#[derive(Clone, Debug, PartialEq)]
+pub struct Approval {
+    pub token: ApprovalToken,
+    pub spender_name: &'static str,
+    pub spender: Address,
+    pub granted: bool,
+}
+
+impl Approval {
+    fn contract(&self) -> Address {
+        match self.token {
+            ApprovalToken::Usdc => parse(USDC),
+            ApprovalToken::OutcomeTokens => parse(CONDITIONAL_TOKENS),
+        }
+    }
+
+    /// Calldata reading the current approval for `owner`
+    fn check_calldata(&self, owner: Address) -> Bytes {
+        match self.token {
+            ApprovalToken::Usdc => calldata(
+                "allowance(address,address)",
+                &[Token::Address(owner), Token::Address(self.spender)],
+            ),
+            ApprovalToken::OutcomeTokens => calldata(
+                "isApprovedForAll(address,address)",
+                &[Token::Address(owner), Token::Address(self.spender)],
+            ),
+        }
+    }
+
+    /// Calldata granting the approval
+    fn grant_calldata(&self) -> Bytes {
+        match self.token {
+            ApprovalToken::Usdc => calldata(
+                "approve(address,uint256)",
+                &[Token::Address(self.spender), Token::Uint(U256::MAX)],
+            ),
+            ApprovalToken::OutcomeTokens => calldata(
+                "setApprovalForAll(address,bool)",
+                &[Token::Address(self.spender), Token::Bool(true)],
+            ),
+        }
+    }
+
+    /// Whether the value a check call returned means the approval is in place
+    fn is_granted(&self, output: &[u8]) -> Result<bool> {
+        if output.len() != 32 {
+            return Err(PolymarketError::BlockchainError(format!(
+                "Unexpected {} approval result of {} bytes",
+                self.token,
+                output.len()
+            )));
+        }
+        let value = U256::from_big_endian(output);
+        Ok(match self.token {
+            ApprovalToken::Usdc => value >= U256::from(MIN_ALLOWANCE),
+            ApprovalToken::OutcomeTokens => !value.is_zero(),
+        })
+    }
+}
+
+fn parse(address: &str) -> Address {
+    address.parse().expect("valid contract address")
+}
+
+fn calldata(signature: &str, args: &[Token]) -> Bytes {
+    let mut data = id(signature).to_vec();
+    data.extend(encode(args));
+    data.into()
+}
+
+/// Every approval trading needs, not yet checked
+pub fn required() -> Vec<Approval> {
+    [ApprovalToken::Usdc, ApprovalToken::OutcomeTokens]
+        .into_iter()
+        .flat_map(|token| {
+            SPENDERS.iter().map(move |(name, spender)| Approval {
+                token,
+                spender_name: name,
+                spender: parse(spender),
+                granted: false,
+            })
+        })
+        .collect()
+}
+
+/// The approvals trading needs, with whether `owner` has granted each
+pub async fn check(rpc_url: &str, owner: Address) -> Result<Vec<Approval>> {
+    let provider = provider(rpc_url)?;
+    let mut approvals = required();
+    for approval in &mut approvals {
+        let call = TransactionRequest::new()
+            .to(approval.contract())
+            .data(approval.check_calldata(owner));
+        let output = provider
+            .call(&call.into(), None)
+            .await
+            .map_err(|e| PolymarketError::BlockchainError(e.to_string()))?;
+        approval.granted = approval.is_granted(&output)?;
+    }
+    Ok(approvals)
+}
+
+/// Send a transaction from `wallet` for each approval not yet granted,
+/// waiting for each to be mined; the wallet pays the gas in POL, which `gas`
+/// records and may hold the transactions back for
+pub async fn grant(
+    rpc_url: &str,
+    wallet: LocalWallet,
+    approvals: &[Approval],
+    gas: &GasTracker,
+) -> Result<()> {
+    let provider = provider(rpc_url)?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| PolymarketError::BlockchainError(e.to_string()))?;
+    let client = SignerMiddleware::new(provider, wallet.with_chain_id(chain_id.as_u64()));
+    for approval in approvals.iter().filter(|a| !a.granted) {
+        gas.wait_for_gas(&client, false).await?;
+        let tx = TransactionRequest::new()
+            .to(approval.contract())
+            .data(approval.grant_calldata());
+        let pending = client.send_transaction(tx, None).await.map_err(|e| {
+            PolymarketError::BlockchainError(format!(
+                "Failed to approve {} for the {}: {}",
+                approval.token, approval.spender_name, e
+            ))
+        })?;
+        let receipt = pending
+            .await
+            .map_err(|e| PolymarketError::BlockchainError(e.to_string()))?;
+        if let Some(ref receipt) = receipt {
+            let operation = format!(
+                "approve {} for the {}",
+                approval.token, approval.spender_name
+            );
+            gas.record(&client, &operation, receipt).await?;
+        }
+        if receipt.and_then(|r| r.status).unwrap_or_default().is_zero() {
+            return Err(PolymarketError::BlockchainError(format!(
+                "Approving {} for the {} reverted",
+                approval.token, approval.spender_name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// JSON-RPC client for `rpc_url`
+pub fn provider(rpc_url: &str) -> Result<Provider<Http>> {
+    Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| PolymarketError::ConfigError(format!("Invalid polygon_rpc_url: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approval_calls() {
+        let approvals = required();
+        assert_eq!(approvals.len(), 6);
+        let owner = Address::from_low_u64_be(1);
+
+        let usdc = &approvals[0];
+        assert_eq!(usdc.contract(), parse(USDC));
+        assert_eq!(
+            format!("{}", usdc.check_calldata(owner)),
+            "0xdd62ed3e\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000004bfb41d5b3570defd03c39a9a4d8de6bd8b8982e"
+        );
+        assert!(format!("{}", usdc.grant_calldata()).starts_with("0x095ea7b3"));
+
+        let outcome = &approvals[3];
+        assert_eq!(outcome.token, ApprovalToken::OutcomeTokens);
+        assert!(format!("{}", outcome.check_calldata(owner)).starts_with("0xe985e9c5"));
+        assert!(format!("{}", outcome.grant_calldata()).starts_with("0xa22cb465"));
+
+        let mut result = [0u8; 32];
+        assert!(!usdc.is_granted(&result).unwrap());
+        assert!(!outcome.is_granted(&result).unwrap());
+        result[31] = 1;
+        assert!(outcome.is_granted(&result).unwrap());
+        assert!(!usdc.is_granted(&result).unwrap());
+        assert!(usdc.is_granted(&[0xff; 32]).unwrap());
+        assert!(usdc.is_granted(&[]).is_err());
+    }
+}
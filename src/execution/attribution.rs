@@ -0,0 +1,585 @@
+use crate::config::PnlConfig;
+use crate::coordination::Coordinator;
+use crate::errors::Result;
+use crate::execution::lifecycle::OrderRecord;
+use crate::execution::positions::Holding;
+use crate::execution::OrderExecutor;
+use crate::metrics;
+use crate::models::OrderSide;
+use crate::monitoring::{PriceCache, Resolutions};
+use crate::notifications::SlackNotifier;
+use crate::storage::gas_log::GasLog;
+use chrono::{DateTime, NaiveTime, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Our P&L on the copies of one trader
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TraderPnl {
+    /// None for positions the bot opened on its own account, such as hedges
+    pub trader: Option<Address>,
+    /// P&L of shares sold or settled at resolution, net of all fees paid
+    pub realized: Decimal,
+    /// Open shares marked at current prices, against what they cost
+    pub unrealized: Decimal,
+    pub fees: Decimal,
+    /// Cost of the shares still held
+    pub open_cost: Decimal,
+    pub open_positions: usize,
+    /// Positions closed at a profit or a loss, by selling or resolution
+    #[serde(default)]
+    pub wins: usize,
+    #[serde(default)]
+    pub losses: usize,
+}
+
+impl TraderPnl {
+    pub fn total(&self) -> Decimal {
+        self.realized + self.unrealized
+    }
+
+    pub fn win_rate(&self) -> Option<Decimal> {
+        let closed = self.wins + self.losses;
+        (closed > 0).then(|| Decimal::from(self.wins) / Decimal::from(closed))
+    }
+
+    /// Add `other`'s P&L and counts to these
+    fn add(&mut self, other: &TraderPnl) {
+        self.realized += other.realized;
+        self.unrealized += other.unrealized;
+        self.fees += other.fees;
+        self.open_cost += other.open_cost;
+        self.open_positions += other.open_positions;
+        self.wins += other.wins;
+        self.losses += other.losses;
+    }
+
+    fn count_closed(&mut self, result: Decimal) {
+        if result > Decimal::ZERO {
+            self.wins += 1;
+        } else {
+            self.losses += 1;
+        }
+    }
+
+    /// The trader's address, or what the bot's own positions are listed as
+    pub fn label(&self) -> String {
+        match self.trader {
+            Some(trader) => format!("{:?}", trader),
+            None => "own orders".to_string(),
+        }
+    }
+}
+
+/// Our P&L per copied trader from the fills recorded on `orders`, best first
+///
+/// Each copy's shares are kept apart under the trader it copied, so a market
+/// several traders are in has one position per trader. A copied exit sells
+/// from that trader's shares; the bot's own sells (stops, rebalancing, the
+/// kill switch) come out of every position in the market in proportion.
+/// Shares held when the market resolved are settled at its `payout` of 1 or
+/// 0; other open shares are marked with `price`, or left at cost without a
+/// quote. A position is a win or a loss by its result, fees included, once it
+/// has been sold off or settled.
+pub fn trader_pnl<F, P>(orders: &[OrderRecord], price: F, payout: P) -> Vec<TraderPnl>
+where
+    F: Fn(&str) -> Option<Decimal>,
+    P: Fn(&str) -> Option<Decimal>,
+{
+    let mut pnl: Vec<TraderPnl> = attribute(orders, |order| order.trader, price, payout)
+        .into_iter()
+        .map(|(trader, row)| TraderPnl { trader, ..row })
+        .collect();
+    pnl.sort_by_key(|row| std::cmp::Reverse(row.total()));
+    pnl
+}
+
+/// Our P&L on the copies carrying one label
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LabelPnl {
+    pub label: String,
+    pub pnl: TraderPnl,
+}
+
+/// Our P&L per trade label from the fills recorded on `orders`, best first
+///
+/// Positions are kept apart by the set of labels their copies carry, as in
+/// [`trader_pnl`], and each label's row adds up every position carrying it.
+/// A copy with several labels counts towards each of them, so rows can
+/// overlap; unlabeled positions are left out.
+pub fn label_pnl<F, P>(orders: &[OrderRecord], price: F, payout: P) -> Vec<LabelPnl>
+where
+    F: Fn(&str) -> Option<Decimal>,
+    P: Fn(&str) -> Option<Decimal>,
+{
+    let key = |order: &OrderRecord| {
+        let mut labels = order.labels.clone();
+        labels.sort();
+        (!labels.is_empty()).then_some(labels)
+    };
+    let mut by_label: BTreeMap<String, TraderPnl> = BTreeMap::new();
+    for (labels, row) in attribute(orders, key, price, payout) {
+        for label in labels.into_iter().flatten() {
+            by_label.entry(label).or_default().add(&row);
+        }
+    }
+    let mut pnl: Vec<LabelPnl> = by_label
+        .into_iter()
+        .map(|(label, pnl)| LabelPnl { label, pnl })
+        .collect();
+    pnl.sort_by_key(|row| std::cmp::Reverse(row.pnl.total()));
+    pnl
+}
+
+/// P&L per position owner, where `owner` says whose copy an order is; None
+/// for the bot's own orders
+fn attribute<K, O, F, P>(
+    orders: &[OrderRecord],
+    owner: O,
+    price: F,
+    payout: P,
+) -> BTreeMap<Option<K>, TraderPnl>
+where
+    K: Clone + Ord + std::hash::Hash,
+    O: Fn(&OrderRecord) -> Option<K>,
+    F: Fn(&str) -> Option<Decimal>,
+    P: Fn(&str) -> Option<Decimal>,
+{
+    let mut filled: Vec<&OrderRecord> = orders
+        .iter()
+        .filter(|o| o.filled_size > Decimal::ZERO)
+        .collect();
+    filled.sort_by_key(|o| o.created_at);
+
+    // Each position's shares, and its result so far
+    let mut books: HashMap<(Option<K>, &str), (Holding, Decimal)> = HashMap::new();
+    let mut pnl: BTreeMap<Option<K>, TraderPnl> = BTreeMap::new();
+    for order in filled {
+        let fill_price = order.fill_price.unwrap_or(order.price);
+        let market = order.market_id.as_str();
+        let key = owner(order);
+        match order.side {
+            OrderSide::Buy => {
+                let (book, result) = books.entry((key.clone(), market)).or_default();
+                book.shares += order.filled_size;
+                book.cost += order.filled_size * fill_price;
+                *result -= order.fee();
+                let stats = pnl.entry(key).or_default();
+                stats.fees += order.fee();
+                stats.realized -= order.fee();
+            }
+            OrderSide::Sell => {
+                let owners: Vec<(Option<K>, Decimal)> = books
+                    .iter()
+                    .filter(|((book_key, book_market), (holding, _))| {
+                        *book_market == market
+                            && holding.shares > Decimal::ZERO
+                            && (key.is_none() || *book_key == key)
+                    })
+                    .map(|((book_key, _), (holding, _))| (book_key.clone(), holding.shares))
+                    .collect();
+                let held: Decimal = owners.iter().map(|(_, shares)| *shares).sum();
+                if held.is_zero() {
+                    continue;
+                }
+                let sold = order.filled_size.min(held);
+                for (book_key, shares) in owners {
+                    let (book, result) = books
+                        .get_mut(&(book_key.clone(), market))
+                        .expect("owner has a book");
+                    let average = book.average_price();
+                    let sold_here = sold * shares / held;
+                    let fee = order.fee() * shares / held;
+                    let realized = sold_here * (fill_price - average) - fee;
+                    book.cost -= sold_here * average;
+                    book.shares -= sold_here;
+                    *result += realized;
+                    let closed = (book.shares <= Decimal::ZERO).then(|| std::mem::take(result));
+                    let stats = pnl.entry(book_key).or_default();
+                    stats.fees += fee;
+                    stats.realized += realized;
+                    if let Some(result) = closed {
+                        stats.count_closed(result);
+                    }
+                }
+            }
+        }
+    }
+
+    for ((key, market), (book, result)) in books {
+        if book.shares <= Decimal::ZERO {
+            continue;
+        }
+        let stats = pnl.entry(key).or_default();
+        if let Some(payout) = payout(market) {
+            let settled = book.value_at(payout) - book.cost;
+            stats.realized += settled;
+            stats.count_closed(result + settled);
+            continue;
+        }
+        let value = price(market).map_or(book.cost, |price| book.value_at(price));
+        stats.open_positions += 1;
+        stats.open_cost += book.cost;
+        stats.unrealized += value - book.cost;
+    }
+    pnl
+}
+
+/// Per-trader P&L as last computed by the live bot, for the `status` command
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PnlSnapshot {
+    pub updated_at: Option<DateTime<Utc>>,
+    pub traders: Vec<TraderPnl>,
+    /// Gas paid for on-chain transactions, in USD
+    #[serde(default)]
+    pub gas_usd: Decimal,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<LabelPnl>,
+}
+
+impl PnlSnapshot {
+    /// One line per trader, for Slack and the terminal
+    pub fn summary(&self) -> String {
+        let mut lines = Vec::new();
+        for row in &self.traders {
+            lines.push(format!(
+                "{}: {} USDC ({} realized, {} unrealized on {} open positions; {} won, {} lost)",
+                row.label(),
+                row.total().round_dp(2),
+                row.realized.round_dp(2),
+                row.unrealized.round_dp(2),
+                row.open_positions,
+                row.wins,
+                row.losses
+            ));
+        }
+        let total: Decimal = self.traders.iter().map(TraderPnl::total).sum();
+        lines.push(format!("Total: {} USDC", total.round_dp(2)));
+        for row in &self.labels {
+            lines.push(format!(
+                "Label {}: {} USDC ({} realized, {} unrealized on {} open positions)",
+                row.label,
+                row.pnl.total().round_dp(2),
+                row.pnl.realized.round_dp(2),
+                row.pnl.unrealized.round_dp(2),
+                row.pnl.open_positions
+            ));
+        }
+        if !self.gas_usd.is_zero() {
+            lines.push(format!(
+                "Gas: {} USD, {} USDC net of gas",
+                self.gas_usd.round_dp(2),
+                self.net().round_dp(2)
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// Total P&L less gas
+    pub fn net(&self) -> Decimal {
+        self.traders.iter().map(TraderPnl::total).sum::<Decimal>() - self.gas_usd
+    }
+}
+
+/// Snapshot file the live bot keeps up to date
+pub struct PnlStore {
+    path: PathBuf,
+}
+
+impl PnlStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The last snapshot, or None if the bot hasn't written one yet
+    pub fn load(&self) -> Result<Option<PnlSnapshot>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, snapshot: &PnlSnapshot) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string_pretty(snapshot)?)?;
+        Ok(())
+    }
+}
+
+/// Keeps per-trader P&L up to date in the snapshot file and the metrics, and
+/// posts it to Slack once a day
+pub struct PnlTracker {
+    executor: Arc<OrderExecutor>,
+    prices: Arc<PriceCache>,
+    store: PnlStore,
+    interval: Duration,
+    daily_summary_at: Option<NaiveTime>,
+    resolutions: Option<Arc<Resolutions>>,
+    notifier: Option<Arc<SlackNotifier>>,
+    coordinator: Option<Arc<Coordinator>>,
+    gas_log: Option<GasLog>,
+}
+
+impl PnlTracker {
+    pub fn from_config(
+        config: &PnlConfig,
+        executor: Arc<OrderExecutor>,
+        prices: Arc<PriceCache>,
+    ) -> Result<Self> {
+        Ok(Self {
+            executor,
+            prices,
+            store: PnlStore::new(&config.snapshot_file),
+            interval: Duration::from_secs(config.update_interval_seconds),
+            daily_summary_at: config.daily_summary_at()?,
+            resolutions: None,
+            notifier: None,
+            coordinator: None,
+            gas_log: None,
+        })
+    }
+
+    /// Settle positions in resolved markets at their payout
+    pub fn with_resolutions(mut self, resolutions: Arc<Resolutions>) -> Self {
+        self.resolutions = Some(resolutions);
+        self
+    }
+
+    /// Post the daily summary here
+    pub fn with_notifier(mut self, notifier: Arc<SlackNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Only post the daily summary while this instance is the leader
+    pub fn with_coordinator(mut self, coordinator: Arc<Coordinator>) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    /// Count the gas recorded here against the P&L
+    pub fn with_gas_log(mut self, gas_log: GasLog) -> Self {
+        self.gas_log = Some(gas_log);
+        self
+    }
+
+    pub async fn run_periodic(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        let mut last_summary = Utc::now();
+        loop {
+            interval.tick().await;
+            let snapshot = match self.update().await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    warn!("Failed to update per-trader P&L: {}", e);
+                    continue;
+                }
+            };
+
+            let now = Utc::now();
+            if let (Some(at), Some(ref notifier)) = (self.daily_summary_at, &self.notifier) {
+                let is_leader = self.coordinator.as_ref().is_none_or(|c| c.is_leader());
+                if summary_due(last_summary, now, at) && is_leader {
+                    notifier
+                        .summary(format!("Daily P&L by trader\n{}", snapshot.summary()))
+                        .await;
+                    last_summary = now;
+                }
+            }
+        }
+    }
+
+    async fn update(&self) -> Result<PnlSnapshot> {
+        let orders = self.executor.orders().await?;
+        let price = |market_id: &str| self.prices.price(market_id);
+        let payout = |market_id: &str| self.resolutions.as_ref()?.payout(market_id);
+        let snapshot = PnlSnapshot {
+            updated_at: Some(Utc::now()),
+            traders: trader_pnl(&orders, price, payout),
+            gas_usd: match self.gas_log {
+                Some(ref gas_log) => gas_log.spent()?.usd,
+                None => Decimal::ZERO,
+            },
+            labels: label_pnl(&orders, price, payout),
+        };
+        for row in &snapshot.traders {
+            let trader = row.label();
+            for (kind, value) in [("realized", row.realized), ("unrealized", row.unrealized)] {
+                metrics::global().gauge(
+                    "polycopy_trader_pnl_usdc",
+                    "Our P&L on the copies of each trader",
+                    &[("trader", &trader), ("kind", kind)],
+                    value.try_into().unwrap_or(0.0),
+                );
+            }
+        }
+        for row in &snapshot.labels {
+            let pnl = &row.pnl;
+            for (kind, value) in [("realized", pnl.realized), ("unrealized", pnl.unrealized)] {
+                metrics::global().gauge(
+                    "polycopy_label_pnl_usdc",
+                    "Our P&L on the copies carrying each label",
+                    &[("label", &row.label), ("kind", kind)],
+                    value.try_into().unwrap_or(0.0),
+                );
+            }
+        }
+        self.store.save(&snapshot)?;
+        Ok(snapshot)
+    }
+}
+
+/// Whether `at` (UTC) has come round since the summary sent at `last`
+fn summary_due(last: DateTime<Utc>, now: DateTime<Utc>, at: NaiveTime) -> bool {
+    let today = now.date_naive().and_time(at).and_utc();
+    let due = if today <= now {
+        today
+    } else {
+        today - chrono::Duration::days(1)
+    };
+    last < due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderType;
+    use rust_decimal_macros::dec;
+
+    fn filled(trader: Option<u64>, side: OrderSide, price: Decimal, size: Decimal) -> OrderRecord {
+        let mut order = OrderRecord::for_market("m1", side, price, size, OrderType::FOK);
+        order.trader = trader.map(Address::from_low_u64_be);
+        order.filled_size = size;
+        order
+    }
+
+    #[test]
+    fn test_pnl_is_attributed_to_the_copied_trader() {
+        let orders = vec![
+            filled(Some(1), OrderSide::Buy, dec!(0.40), dec!(100)),
+            filled(Some(2), OrderSide::Buy, dec!(0.60), dec!(300)),
+            // Alice's exit sells only her copy
+            filled(Some(1), OrderSide::Sell, dec!(0.50), dec!(50)),
+            // A stop sells from both copies, in proportion
+            filled(None, OrderSide::Sell, dec!(0.50), dec!(70)),
+        ];
+        let pnl = trader_pnl(&orders, |_| Some(dec!(0.55)), |_| None);
+        assert_eq!(pnl.len(), 2);
+
+        let alice = &pnl[0];
+        assert_eq!(alice.trader, Some(Address::from_low_u64_be(1)));
+        // 50 @ +0.10, then 10 of the 70 @ +0.10, leaving 40 marked at +0.15
+        assert_eq!(alice.realized, dec!(6));
+        assert_eq!(alice.unrealized, dec!(6));
+        assert_eq!(alice.open_cost, dec!(16));
+
+        let bob = &pnl[1];
+        // 60 of the 70 @ -0.10, leaving 240 marked at -0.05
+        assert_eq!(bob.realized, dec!(-6));
+        assert_eq!(bob.unrealized, dec!(-12));
+        assert_eq!(bob.open_positions, 1);
+    }
+
+    #[test]
+    fn test_pnl_by_label() {
+        let labelled = |side: OrderSide, price: Decimal, size: Decimal, labels: &[&str]| {
+            let mut order = filled(Some(1), side, price, size);
+            order.labels = labels.iter().map(|l| l.to_string()).collect();
+            order
+        };
+        let orders = vec![
+            labelled(
+                OrderSide::Buy,
+                dec!(0.40),
+                dec!(100),
+                &["momentum", "whales"],
+            ),
+            labelled(OrderSide::Buy, dec!(0.50), dec!(100), &["momentum"]),
+            // A stop sells from both positions, in proportion
+            filled(None, OrderSide::Sell, dec!(0.60), dec!(100)),
+        ];
+        let pnl = label_pnl(&orders, |_| Some(dec!(0.70)), |_| None);
+        assert_eq!(pnl.len(), 2);
+
+        // 50 sold @ +0.20 and 50 @ +0.10, then 50 marked @ +0.30 and 50 @ +0.20
+        let momentum = &pnl[0];
+        assert_eq!(momentum.label, "momentum");
+        assert_eq!(momentum.pnl.realized, dec!(15));
+        assert_eq!(momentum.pnl.unrealized, dec!(25));
+        assert_eq!(momentum.pnl.open_positions, 2);
+
+        let whales = &pnl[1];
+        assert_eq!(whales.label, "whales");
+        assert_eq!(
+            (whales.pnl.realized, whales.pnl.unrealized),
+            (dec!(10), dec!(15))
+        );
+
+        let snapshot = PnlSnapshot {
+            labels: pnl,
+            ..PnlSnapshot::default()
+        };
+        assert!(snapshot.summary().contains("\nLabel whales: 25"));
+    }
+
+    #[test]
+    fn test_resolved_positions_are_settled_and_counted() {
+        let mut orders = vec![
+            filled(Some(1), OrderSide::Buy, dec!(0.40), dec!(100)),
+            filled(Some(1), OrderSide::Sell, dec!(0.30), dec!(100)),
+            filled(Some(1), OrderSide::Buy, dec!(0.70), dec!(50)),
+            filled(Some(2), OrderSide::Buy, dec!(0.70), dec!(50)),
+        ];
+        orders[2].market_id = "m2".to_string();
+        orders[3].market_id = "m2".to_string();
+        // m2 last traded at 0.99 but resolved against the position
+        let pnl = trader_pnl(
+            &orders,
+            |_| Some(dec!(0.99)),
+            |m| (m == "m2").then_some(dec!(0)),
+        );
+
+        let alice = pnl
+            .iter()
+            .find(|row| row.trader == Some(Address::from_low_u64_be(1)))
+            .unwrap();
+        assert_eq!(alice.realized, dec!(-45));
+        assert_eq!(alice.unrealized, dec!(0));
+        assert_eq!(alice.open_positions, 0);
+        assert_eq!((alice.wins, alice.losses), (0, 2));
+        assert_eq!(alice.win_rate(), Some(dec!(0)));
+    }
+
+    #[test]
+    fn test_daily_summary_is_due_once_a_day() {
+        let at = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+        let time = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        assert!(!summary_due(
+            time("2024-03-01T07:00:00Z"),
+            time("2024-03-01T07:59:00Z"),
+            at
+        ));
+        assert!(summary_due(
+            time("2024-03-01T07:00:00Z"),
+            time("2024-03-01T08:00:00Z"),
+            at
+        ));
+        assert!(!summary_due(
+            time("2024-03-01T08:00:00Z"),
+            time("2024-03-02T07:00:00Z"),
+            at
+        ));
+        assert!(summary_due(
+            time("2024-03-01T08:00:00Z"),
+            time("2024-03-02T09:00:00Z"),
+            at
+        ));
+    }
+}
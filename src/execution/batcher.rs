@@ -0,0 +1,99 @@
+use crate::errors::{PolymarketError, Result};
+use crate::execution::clob_client::{ClobClient, OrderIntent, MAX_BATCH_ORDERS};
+use crate::models::OrderResponse;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tracing::debug;
+
+type Pending = (OrderIntent, oneshot::Sender<Result<OrderResponse>>);
+
+/// Collects orders placed around the same time into batch requests
+///
+/// Copies run concurrently, so an aggregation window closing or a trader
+/// buying several outcomes at once produces a burst of orders. Each order
+/// waits at most `window` for others to join it; the burst then goes out as
+/// one `/orders` request and every caller gets its own order's result.
+#[derive(Clone)]
+pub struct OrderBatcher {
+    sender: mpsc::UnboundedSender<Pending>,
+}
+
+impl OrderBatcher {
+    /// Start the batching task; must be called inside a Tokio runtime
+    pub fn spawn(client: Arc<ClobClient>, window: Duration) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run(client, window, receiver));
+        Self { sender }
+    }
+
+    /// Place `intent` with whatever other orders arrive within the window
+    pub async fn place_order(&self, intent: OrderIntent) -> Result<OrderResponse> {
+        let (reply, response) = oneshot::channel();
+        self.sender
+            .send((intent, reply))
+            .map_err(|_| PolymarketError::ExecutionError("Order batcher stopped".to_string()))?;
+        response.await.map_err(|_| {
+            PolymarketError::ExecutionError("Order batcher dropped the order".to_string())
+        })?
+    }
+}
+
+async fn run(
+    client: Arc<ClobClient>,
+    window: Duration,
+    mut receiver: mpsc::UnboundedReceiver<Pending>,
+) {
+    while let Some(first) = receiver.recv().await {
+        let deadline = Instant::now() + window;
+        let mut pending = vec![first];
+        while pending.len() < MAX_BATCH_ORDERS {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(next)) => pending.push(next),
+                _ => break,
+            }
+        }
+
+        let (intents, replies): (Vec<_>, Vec<_>) = pending.into_iter().unzip();
+        if let [intent] = intents.as_slice() {
+            let result = client
+                .place_order(
+                    &intent.market_id,
+                    intent.side.clone(),
+                    intent.price,
+                    intent.size,
+                    intent.order_type.clone(),
+                )
+                .await;
+            let _ = replies.into_iter().next().map(|reply| reply.send(result));
+            continue;
+        }
+
+        debug!("Placing {} orders in one batch", intents.len());
+        match client.place_orders(&intents).await {
+            Ok(results) => {
+                for (reply, result) in replies.into_iter().zip(results) {
+                    let _ = reply.send(result);
+                }
+            }
+            Err(e) => {
+                for reply in replies {
+                    let _ = reply.send(Err(shared(&e)));
+                }
+            }
+        }
+    }
+}
+
+/// A batch's error for each of its orders, keeping whether the orders may
+/// have been placed or can be retried later
+fn shared(error: &PolymarketError) -> PolymarketError {
+    match error {
+        PolymarketError::OrderOutcomeUnknown(message) => {
+            PolymarketError::OrderOutcomeUnknown(message.clone())
+        }
+        PolymarketError::RateLimited(seconds) => PolymarketError::RateLimited(*seconds),
+        e => PolymarketError::ApiError(e.to_string()),
+    }
+}
@@ -0,0 +1,278 @@
+use crate::coordination::Coordinator;
+use crate::errors::Result;
+use crate::execution::OrderExecutor;
+use crate::models::{OrderFillStatus, Trade};
+use crate::monitoring::PriceCache;
+use crate::storage::TradeStore;
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How often a running bot looks for blacklisted positions to close
+const CLOSE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlacklistEntry {
+    pub added_at: DateTime<Utc>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Positions still to be sold by the running bot; cleared once they are
+    #[serde(default)]
+    pub close_positions: bool,
+}
+
+impl BlacklistEntry {
+    pub fn new(reason: Option<String>, close_positions: bool) -> Self {
+        Self {
+            added_at: Utc::now(),
+            reason,
+            close_positions,
+        }
+    }
+}
+
+/// Markets and traders that must not be copied
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlacklistState {
+    #[serde(default)]
+    pub markets: BTreeMap<String, BlacklistEntry>,
+    #[serde(default)]
+    pub traders: BTreeMap<Address, BlacklistEntry>,
+}
+
+impl BlacklistState {
+    /// Why `trade` must not be copied, if it's blacklisted
+    pub fn blocks(&self, trade: &Trade) -> Option<String> {
+        let (what, entry) = if let Some(entry) = self.markets.get(&trade.market_id) {
+            ("market", entry)
+        } else {
+            ("trader", self.traders.get(&trade.trader)?)
+        };
+        Some(match entry.reason {
+            Some(ref reason) => format!("{} is blacklisted: {}", what, reason),
+            None => format!("{} is blacklisted", what),
+        })
+    }
+}
+
+/// Blacklist kept in a JSON file
+///
+/// Like the pause file, the file is read on every check, so the `blacklist`
+/// command takes effect on a running bot immediately and survives restarts.
+pub struct Blacklist {
+    path: PathBuf,
+}
+
+impl Blacklist {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn load(&self) -> Result<BlacklistState> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BlacklistState::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, state: &BlacklistState) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(state)?)?;
+        Ok(())
+    }
+
+    /// Apply `change` to the stored blacklist and write it back
+    pub fn update<T>(&self, change: impl FnOnce(&mut BlacklistState) -> T) -> Result<T> {
+        let mut state = self.load()?;
+        let result = change(&mut state);
+        self.save(&state)?;
+        Ok(result)
+    }
+
+    /// Why `trade` must not be copied; an unreadable blacklist blocks nothing
+    pub fn blocks(&self, trade: &Trade) -> Option<String> {
+        match self.load() {
+            Ok(state) => state.blocks(trade),
+            Err(e) => {
+                warn!("Failed to read blacklist {}: {}", self.path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Sell positions of entries added with `close_positions`, every few seconds
+    pub async fn run_close_loop(
+        self: Arc<Self>,
+        executor: Arc<OrderExecutor>,
+        store: Arc<dyn TradeStore>,
+        prices: Arc<PriceCache>,
+        coordinator: Option<Arc<Coordinator>>,
+    ) {
+        let mut interval = tokio::time::interval(CLOSE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if coordinator.as_ref().is_some_and(|c| !c.is_leader()) {
+                continue;
+            }
+            if let Err(e) = self
+                .close_positions(&executor, store.as_ref(), &prices)
+                .await
+            {
+                warn!("Closing blacklisted positions failed: {}", e);
+            }
+        }
+    }
+
+    /// Sell every held position in a blacklisted market, or in a market copied
+    /// from a blacklisted trader, for entries still waiting on a close
+    async fn close_positions(
+        &self,
+        executor: &OrderExecutor,
+        store: &dyn TradeStore,
+        prices: &PriceCache,
+    ) -> Result<()> {
+        let state = self.load()?;
+        let mut pending_markets: Vec<(String, HashSet<String>)> = state
+            .markets
+            .iter()
+            .filter(|(_, entry)| entry.close_positions)
+            .map(|(market, _)| (market.clone(), HashSet::from([market.clone()])))
+            .collect();
+        let pending_traders: Vec<Address> = state
+            .traders
+            .iter()
+            .filter(|(_, entry)| entry.close_positions)
+            .map(|(trader, _)| *trader)
+            .collect();
+        if pending_markets.is_empty() && pending_traders.is_empty() {
+            return Ok(());
+        }
+
+        let mut pending_trader_markets = Vec::new();
+        if !pending_traders.is_empty() {
+            let entries = store.entries().await?;
+            for trader in pending_traders {
+                let copied = entries
+                    .iter()
+                    .filter(|e| e.trade.trader == trader)
+                    .map(|e| e.trade.market_id.clone())
+                    .collect();
+                pending_trader_markets.push((trader, copied));
+            }
+        }
+
+        // Sell each affected market once, even if several entries cover it
+        let holdings = executor.holdings().await?;
+        let affected: HashSet<&String> = pending_markets
+            .iter()
+            .map(|(_, markets)| markets)
+            .chain(pending_trader_markets.iter().map(|(_, markets)| markets))
+            .flatten()
+            .filter(|market| holdings.contains_key(*market))
+            .collect();
+        let mut still_held = HashSet::new();
+        for market_id in affected {
+            let shares = holdings[market_id].shares;
+            prices.track(market_id);
+            let Some(price) = prices.price(market_id) else {
+                debug!("No current price for {}; closing it later", market_id);
+                still_held.insert(market_id.clone());
+                continue;
+            };
+            info!(
+                "Closing blacklisted position: selling {} shares of {} at {}",
+                shares, market_id, price
+            );
+            match executor
+                .reduce_position(market_id, shares, price, "blacklist")
+                .await
+            {
+                Ok(OrderFillStatus::FullyFilled { .. }) => {}
+                Ok(status) => {
+                    warn!("Blacklist sell in {} ended {:?}", market_id, status);
+                    still_held.insert(market_id.clone());
+                }
+                Err(e) => {
+                    warn!("Blacklist sell in {} failed: {}", market_id, e);
+                    still_held.insert(market_id.clone());
+                }
+            }
+        }
+
+        // Entries whose markets are all closed are done; the rest retry next pass
+        pending_markets.retain(|(_, markets)| markets.is_disjoint(&still_held));
+        pending_trader_markets.retain(|(_, markets)| markets.is_disjoint(&still_held));
+        if pending_markets.is_empty() && pending_trader_markets.is_empty() {
+            return Ok(());
+        }
+        self.update(|state| {
+            for (market, _) in &pending_markets {
+                if let Some(entry) = state.markets.get_mut(market) {
+                    entry.close_positions = false;
+                }
+            }
+            for (trader, _) in &pending_trader_markets {
+                if let Some(entry) = state.traders.get_mut(trader) {
+                    entry.close_positions = false;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_blacklist_blocks_markets_and_traders() {
+        let path = std::env::temp_dir().join(format!("blacklist-{}.json", uuid::Uuid::new_v4()));
+        let blacklist = Blacklist::new(&path);
+        let trade = Trade::sample()
+            .with_id("t1")
+            .with_trader(Address::from_low_u64_be(1))
+            .with_fill(dec!(0.5), dec!(10));
+        assert_eq!(blacklist.blocks(&trade), None);
+
+        blacklist
+            .update(|state| {
+                state.traders.insert(
+                    trade.trader,
+                    BlacklistEntry::new(Some("front-running".to_string()), false),
+                )
+            })
+            .unwrap();
+        // A separate instance, as in the running bot, sees the CLI's change
+        let running = Blacklist::new(&path);
+        assert_eq!(
+            running.blocks(&trade).as_deref(),
+            Some("trader is blacklisted: front-running")
+        );
+
+        blacklist
+            .update(|state| {
+                state.traders.clear();
+                state
+                    .markets
+                    .insert("m1".to_string(), BlacklistEntry::new(None, true))
+            })
+            .unwrap();
+        assert_eq!(
+            running.blocks(&trade).as_deref(),
+            Some("market is blacklisted")
+        );
+        let other = trade.with_market("m2");
+        assert_eq!(running.blocks(&other), None);
+        let _ = fs::remove_file(&path);
+    }
+}
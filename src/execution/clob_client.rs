@@ -1,28 +1,479 @@
+use crate::endpoints::Endpoints;
 use crate::errors::{PolymarketError, Result};
+use crate::execution::credentials::CredentialRotation;
 use crate::execution::signer::OrderSigner;
-use crate::models::{MarketData, Order, OrderRequest, OrderResponse, OrderSide, OrderType};
-use ethers::types::Address;
-use reqwest::Client;
+use crate::models::{
+    BookFill, MarketData, Order, OrderRequest, OrderResponse, OrderSide, OrderType,
+};
+use crate::storage::audit_log::{AuditEntry, AuditLogger};
+use base64::engine::general_purpose::URL_SAFE;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, StatusCode};
 use rust_decimal::Decimal;
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+#[derive(Deserialize)]
+struct MidpointResponse {
+    mid: Decimal,
+}
+
+#[derive(Deserialize)]
+struct FeeRateResponse {
+    base_fee: u32,
+}
+
+#[derive(Deserialize)]
+struct BalanceResponse {
+    /// USDC in base units
+    balance: String,
+}
+
+/// Decimals of USDC's base units
+const USDC_DECIMALS: u32 = 6;
+
+#[derive(Deserialize)]
+struct BookLevelResponse {
+    price: Decimal,
+    #[serde(default)]
+    size: Decimal,
+}
+
+#[derive(Deserialize)]
+struct BookResponse {
+    #[serde(default)]
+    bids: Vec<BookLevelResponse>,
+    #[serde(default)]
+    asks: Vec<BookLevelResponse>,
+}
+
+/// The top of a token's order book
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BestQuotes {
+    pub bid: Option<Decimal>,
+    pub ask: Option<Decimal>,
+}
+
+/// A token's resting orders as (price, shares) levels, best first
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OrderBook {
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl OrderBook {
+    /// What a `side` order for `shares` would fill taking liquidity level by
+    /// level; None when the side it crosses is empty
+    pub fn walk(&self, side: &OrderSide, shares: Decimal) -> Option<BookFill> {
+        let levels = match side {
+            OrderSide::Buy => &self.asks,
+            OrderSide::Sell => &self.bids,
+        };
+        let mut filled = Decimal::ZERO;
+        let mut cost = Decimal::ZERO;
+        for (price, size) in levels {
+            let take = (*size).min(shares - filled);
+            if take <= Decimal::ZERO {
+                break;
+            }
+            filled += take;
+            cost += take * price;
+        }
+        (filled > Decimal::ZERO).then(|| BookFill {
+            shares: filled,
+            average_price: cost / filled,
+            fill_ratio: if shares > Decimal::ZERO {
+                filled / shares
+            } else {
+                Decimal::ONE
+            },
+        })
+    }
+}
+
+/// One match involving our orders, as the exchange recorded it
+#[derive(Clone, Debug, Deserialize)]
+pub struct ClobTrade {
+    pub taker_order_id: String,
+    pub asset_id: String,
+    /// Taker's side
+    pub side: OrderSide,
+    /// Shares the taker matched
+    pub size: Decimal,
+    pub price: Decimal,
+    #[serde(default)]
+    pub fee_rate_bps: Decimal,
+    /// Unix seconds
+    pub match_time: String,
+    /// Resting orders the taker matched against
+    #[serde(default)]
+    pub maker_orders: Vec<MakerOrder>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MakerOrder {
+    pub order_id: String,
+    pub side: OrderSide,
+    pub matched_amount: Decimal,
+    pub price: Decimal,
+}
+
+/// One order's part in a match
+#[derive(Clone, Debug, PartialEq)]
+pub struct FillLeg {
+    pub order_id: String,
+    pub asset_id: String,
+    pub side: OrderSide,
+    pub size: Decimal,
+    pub price: Decimal,
+    pub fee_rate_bps: Decimal,
+}
+
+impl ClobTrade {
+    pub fn matched_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.match_time.parse().ok()?, 0)
+    }
+
+    /// The taker's and every maker's part, so an order of ours is found
+    /// whichever side of the match it was on
+    pub fn legs(&self) -> Vec<FillLeg> {
+        let taker = FillLeg {
+            order_id: self.taker_order_id.clone(),
+            asset_id: self.asset_id.clone(),
+            side: self.side.clone(),
+            size: self.size,
+            price: self.price,
+            fee_rate_bps: self.fee_rate_bps,
+        };
+        let makers = self.maker_orders.iter().map(|maker| FillLeg {
+            order_id: maker.order_id.clone(),
+            asset_id: self.asset_id.clone(),
+            side: maker.side.clone(),
+            size: maker.matched_amount,
+            price: maker.price,
+            // Makers don't pay the taker fee
+            fee_rate_bps: Decimal::ZERO,
+        });
+        std::iter::once(taker).chain(makers).collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct TradesPage {
+    #[serde(default)]
+    data: Vec<ClobTrade>,
+    #[serde(default)]
+    next_cursor: String,
+}
+
+/// Cursor the API returns on the last page
+const END_CURSOR: &str = "LTE=";
+
+/// One of our orders resting on the book
+#[derive(Clone, Debug, Deserialize)]
+pub struct OpenOrder {
+    pub id: String,
+    /// Outcome token id
+    pub asset_id: String,
+    /// Unix seconds
+    pub created_at: i64,
+}
+
+impl OpenOrder {
+    pub fn placed_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.created_at, 0)
+    }
+}
+
+#[derive(Deserialize)]
+struct OpenOrdersPage {
+    #[serde(default)]
+    data: Vec<OpenOrder>,
+    #[serde(default)]
+    next_cursor: String,
+}
+
+/// What a bulk cancel did
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CancelledOrders {
+    /// Exchange ids of the orders cancelled
+    #[serde(default)]
+    pub canceled: Vec<String>,
+    /// Orders that stayed open, with the exchange's reason
+    #[serde(default)]
+    pub not_canceled: HashMap<String, String>,
+}
+
+/// API key set the CLOB issues for a wallet
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCredentials {
+    pub api_key: String,
+    pub secret: String,
+    pub passphrase: String,
+}
+
+/// Signature of a request made with the API secret: an HMAC-SHA256 of its
+/// timestamp, method, path and body, URL-safe base64 like the secret itself
+pub fn l2_signature(
+    secret: &str,
+    timestamp: u64,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> Result<String> {
+    let key = URL_SAFE
+        .decode(secret)
+        .map_err(|e| PolymarketError::ApiError(format!("Invalid API secret: {}", e)))?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key)
+        .map_err(|e| PolymarketError::ApiError(format!("Invalid API secret: {}", e)))?;
+    mac.update(format!("{}{}{}", timestamp, method, path).as_bytes());
+    mac.update(body);
+    Ok(URL_SAFE.encode(mac.finalize().into_bytes()))
+}
+
+/// Most orders the exchange accepts in one `/orders` request
+pub const MAX_BATCH_ORDERS: usize = 15;
+
+/// An order to place, before it is rounded to the tick size and signed
+#[derive(Clone, Debug)]
+pub struct OrderIntent {
+    pub market_id: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub order_type: OrderType,
+}
 
 pub struct ClobClient {
     http_client: Client,
-    api_url: String,
-    signer: Arc<OrderSigner>,
-    address: Address,
+    endpoints: Arc<Endpoints>,
+    /// Swapped out when the wallet key file is rotated
+    signer: RwLock<Arc<OrderSigner>>,
+    audit_log: Option<Arc<AuditLogger>>,
+    /// Fee rates per token; they change rarely, so each is fetched once
+    fee_rates: RwLock<HashMap<String, u32>>,
+    /// Sent with authenticated requests once loaded; swapped out on rotation
+    api_credentials: RwLock<Option<ApiCredentials>>,
+    credential_rotation: Option<Arc<CredentialRotation>>,
 }
 
 impl ClobClient {
     pub fn new(api_url: String, signer: OrderSigner) -> Self {
-        let address = signer.address();
         Self {
             http_client: Client::new(),
-            api_url,
-            signer: Arc::new(signer),
-            address,
+            endpoints: Arc::new(Endpoints::single("clob", api_url)),
+            signer: RwLock::new(Arc::new(signer)),
+            audit_log: None,
+            fee_rates: RwLock::new(HashMap::new()),
+            api_credentials: RwLock::new(None),
+            credential_rotation: None,
+        }
+    }
+
+    /// Send requests with this client, e.g. one built from `[network]`
+    pub fn with_http_client(mut self, http_client: Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Record every request and response in an append-only audit log
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLogger>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Send requests to the active one of these endpoints, failing over
+    /// between them
+    pub fn with_endpoints(mut self, endpoints: Arc<Endpoints>) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Re-derive the API credentials and retry once when an authenticated
+    /// request is refused with a 401
+    pub fn with_credential_rotation(mut self, rotation: Arc<CredentialRotation>) -> Self {
+        self.credential_rotation = Some(rotation);
+        self
+    }
+
+    /// Authenticate requests with the credentials the rotation saved last,
+    /// deriving and saving them first when there are none yet
+    pub async fn load_api_credentials(&self) -> Result<()> {
+        let Some(ref rotation) = self.credential_rotation else {
+            return Ok(());
+        };
+        let credentials = match rotation.load()? {
+            Some(credentials) => credentials,
+            None => {
+                let credentials = self.derive_api_key().await?;
+                rotation.save(&credentials)?;
+                credentials
+            }
+        };
+        self.set_api_credentials(credentials);
+        Ok(())
+    }
+
+    /// Authenticate requests with `credentials` from now on
+    pub fn set_api_credentials(&self, credentials: ApiCredentials) {
+        *self
+            .api_credentials
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Some(credentials);
+    }
+
+    /// Sign with `signer` from now on, e.g. after the key file was rotated
+    pub fn replace_signer(&self, signer: OrderSigner) {
+        *self.signer.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(signer);
+    }
+
+    fn signer(&self) -> Arc<OrderSigner> {
+        self.signer
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Send a request, returning its status and body and auditing the exchange
+    async fn send(
+        &self,
+        action: &str,
+        builder: RequestBuilder,
+        payload: Option<serde_json::Value>,
+        auth_timestamp: Option<u64>,
+    ) -> Result<(StatusCode, String)> {
+        let (status, _, body) = self
+            .send_with_headers(action, builder, payload, auth_timestamp)
+            .await?;
+        Ok((status, body))
+    }
+
+    /// [`send`](Self::send), also returning the response headers
+    ///
+    /// Authenticated requests carry the API credentials, once loaded. One
+    /// refused with a 401 is retried once the credentials have been rotated,
+    /// signed afresh with the new ones.
+    async fn send_with_headers(
+        &self,
+        action: &str,
+        builder: RequestBuilder,
+        payload: Option<serde_json::Value>,
+        auth_timestamp: Option<u64>,
+    ) -> Result<(StatusCode, HeaderMap, String)> {
+        let mut request = builder.build()?;
+        *request.timeout_mut() = Some(self.endpoints.request_timeout());
+        let retry = request.try_clone();
+        if let Some(timestamp) = auth_timestamp {
+            self.authenticate(&mut request, timestamp)?;
         }
+        let result = self
+            .exchange(action, request, payload.clone(), auth_timestamp)
+            .await;
+
+        let unauthorized =
+            matches!(&result, Ok((status, _, _)) if *status == StatusCode::UNAUTHORIZED);
+        let (Some(rotation), Some(mut retry)) = (&self.credential_rotation, retry) else {
+            return result;
+        };
+        if !unauthorized || auth_timestamp.is_none() {
+            return result;
+        }
+        let Some(credentials) = rotation.rotate(self.derive_api_key()).await else {
+            return result;
+        };
+        self.set_api_credentials(credentials);
+        let (timestamp, _) = self.get_timestamp_and_nonce();
+        self.authenticate(&mut retry, timestamp)?;
+        self.exchange(action, retry, payload, Some(timestamp)).await
+    }
+
+    /// Add the API key headers to a wallet-signed request, replacing its
+    /// signature with one made with the API secret; left as is before the
+    /// credentials are loaded
+    fn authenticate(&self, request: &mut reqwest::Request, timestamp: u64) -> Result<()> {
+        let Some(credentials) = self
+            .api_credentials
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+        else {
+            return Ok(());
+        };
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .unwrap_or_default();
+        let signature = l2_signature(
+            &credentials.secret,
+            timestamp,
+            request.method().as_str(),
+            request.url().path(),
+            body,
+        )?;
+        let headers = request.headers_mut();
+        for (name, value) in [
+            ("POLY_SIGNATURE", signature),
+            ("POLY_TIMESTAMP", timestamp.to_string()),
+            ("POLY_API_KEY", credentials.api_key),
+            ("POLY_PASSPHRASE", credentials.passphrase),
+        ] {
+            let value = value
+                .parse()
+                .map_err(|_| PolymarketError::ApiError(format!("Invalid {} header", name)))?;
+            headers.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Send one request, auditing the exchange
+    async fn exchange(
+        &self,
+        action: &str,
+        request: reqwest::Request,
+        payload: Option<serde_json::Value>,
+        auth_timestamp: Option<u64>,
+    ) -> Result<(StatusCode, HeaderMap, String)> {
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+        let started = Instant::now();
+
+        let result = async {
+            let response = self.http_client.execute(request).await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.text().await?;
+            Ok::<_, PolymarketError>((status, headers, body))
+        }
+        .await;
+        let healthy = matches!(&result, Ok((status, _, _)) if !status.is_server_error());
+        self.endpoints.record(&url, healthy);
+
+        if let Some(ref audit_log) = self.audit_log {
+            let mut entry = AuditEntry::new(action, &method, &url);
+            if let Some(payload) = payload {
+                entry = entry.with_request(payload);
+            }
+            match &result {
+                Ok((status, _, body)) => entry = entry.with_response(status.as_u16(), body),
+                Err(e) => entry.error = Some(e.to_string()),
+            }
+            entry.auth_timestamp = auth_timestamp;
+            entry.duration_ms = started.elapsed().as_millis() as u64;
+
+            if let Err(e) = audit_log.record(&entry) {
+                warn!("Failed to write audit log entry for {}: {}", action, e);
+            }
+        }
+
+        result
     }
 
     /// Place an order on the CLOB
@@ -34,9 +485,150 @@ impl ClobClient {
         size: Decimal,
         order_type: OrderType,
     ) -> Result<OrderResponse> {
-        // Get market tick size for price adjustment
-        let tick_size = self.get_tick_size(market_id).await?;
-        let adjusted_price = self.adjust_to_tick_size(price, tick_size);
+        self.submit_order(market_id, side, price, size, order_type, false)
+            .await
+    }
+
+    /// Place a GTC order that the exchange rejects instead of matching if it
+    /// would cross the spread, so it only ever rests on the book as a maker
+    pub async fn place_post_only_order(
+        &self,
+        market_id: &str,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<OrderResponse> {
+        self.submit_order(market_id, side, price, size, OrderType::GTC, true)
+            .await
+    }
+
+    async fn submit_order(
+        &self,
+        market_id: &str,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+        order_type: OrderType,
+        post_only: bool,
+    ) -> Result<OrderResponse> {
+        // One signer throughout, even if the key rotates meanwhile
+        let signer = self.signer();
+        let intent = OrderIntent {
+            market_id: market_id.to_string(),
+            side,
+            price,
+            size,
+            order_type,
+        };
+        let request = self.sign_order(&signer, &intent, post_only).await?;
+
+        // Get auth headers
+        let (timestamp, nonce) = self.get_timestamp_and_nonce();
+        let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
+
+        // Send to API
+        let builder = self
+            .http_client
+            .post(format!("{}/order", self.endpoints.url()))
+            .header("POLY_ADDRESS", format!("{:?}", signer.address()))
+            .header("POLY_SIGNATURE", &auth_signature)
+            .header("POLY_TIMESTAMP", timestamp.to_string())
+            .header("POLY_NONCE", nonce.to_string())
+            .json(&request);
+        let response = self
+            .send_with_headers(
+                "place_order",
+                builder,
+                Some(serde_json::to_value(&request)?),
+                Some(timestamp),
+            )
+            .await;
+        placement_response(response)
+    }
+
+    /// Place several orders in as few requests as the exchange allows
+    ///
+    /// Orders go out in batches of up to [`MAX_BATCH_ORDERS`]. The result has
+    /// one entry per order, in the order given, so callers can tell which of
+    /// their orders the exchange accepted. The outer error is for a batch
+    /// request that failed as a whole.
+    pub async fn place_orders(&self, orders: &[OrderIntent]) -> Result<Vec<Result<OrderResponse>>> {
+        let signer = self.signer();
+        let mut results = Vec::with_capacity(orders.len());
+        for chunk in orders.chunks(MAX_BATCH_ORDERS) {
+            // Orders that can't be signed fail alone; the rest still go out
+            let mut signed = Vec::new();
+            let mut chunk_results: Vec<Option<Result<OrderResponse>>> = Vec::new();
+            for intent in chunk {
+                match self.sign_order(&signer, intent, false).await {
+                    Ok(request) => {
+                        signed.push(request);
+                        chunk_results.push(None);
+                    }
+                    Err(e) => chunk_results.push(Some(Err(e))),
+                }
+            }
+
+            let mut responses = if signed.is_empty() {
+                Vec::new()
+            } else {
+                self.post_batch(&signer, &signed).await?
+            }
+            .into_iter();
+            results.extend(chunk_results.into_iter().map(|result| {
+                result.unwrap_or_else(|| {
+                    responses.next().unwrap_or_else(|| {
+                        Err(PolymarketError::ApiError(
+                            "Missing from batch response".to_string(),
+                        ))
+                    })
+                })
+            }));
+        }
+        Ok(results)
+    }
+
+    async fn post_batch(
+        &self,
+        signer: &OrderSigner,
+        requests: &[OrderRequest],
+    ) -> Result<Vec<Result<OrderResponse>>> {
+        let (timestamp, nonce) = self.get_timestamp_and_nonce();
+        let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
+
+        let builder = self
+            .http_client
+            .post(format!("{}/orders", self.endpoints.url()))
+            .header("POLY_ADDRESS", format!("{:?}", signer.address()))
+            .header("POLY_SIGNATURE", &auth_signature)
+            .header("POLY_TIMESTAMP", timestamp.to_string())
+            .header("POLY_NONCE", nonce.to_string())
+            .json(requests);
+        let response = self
+            .send_with_headers(
+                "place_orders",
+                builder,
+                Some(serde_json::to_value(requests)?),
+                Some(timestamp),
+            )
+            .await;
+        batch_results(placement_response(response)?, requests.len())
+    }
+
+    /// Round, sign and wrap an order for the API
+    async fn sign_order(
+        &self,
+        signer: &OrderSigner,
+        intent: &OrderIntent,
+        post_only: bool,
+    ) -> Result<OrderRequest> {
+        // Get market tick size for price adjustment; the exchange rejects
+        // orders signed with a fee rate other than the market's
+        let (tick_size, fee_rate_bps) = tokio::try_join!(
+            self.get_tick_size(&intent.market_id),
+            self.get_fee_rate_bps(&intent.market_id),
+        )?;
+        let adjusted_price = self.adjust_to_tick_size(intent.price, tick_size);
 
         // Calculate expiration (10 minutes from now)
         let expiration_time = SystemTime::now()
@@ -47,123 +639,405 @@ impl ClobClient {
 
         // Create order
         let order = Order {
-            market_id: market_id.to_string(),
+            market_id: intent.market_id.clone(),
             price_decimal: adjusted_price,
-            quantity: size,
-            side: side.clone(),
-            owner: self.address,
+            quantity: intent.size,
+            side: intent.side.clone(),
+            owner: signer.maker(),
+            signer: signer.safe().map(|_| signer.address()),
             expiration_time,
+            fee_rate_bps,
         };
 
         // Sign order
-        let signature = self.signer.sign_order(&order).await?;
+        let signature = signer.sign_order(&order)?;
 
-        // Get auth headers
-        let (timestamp, nonce) = self.get_timestamp_and_nonce();
-        let auth_signature = self.signer.sign_auth_message(timestamp, nonce).await?;
-
-        // Create request
-        let request = OrderRequest {
-            order: order.clone(),
-            owner: format!("{:?}", self.address),
-            order_type: order_type.to_string(),
-            post_only: false,
-            fee_rate_bps: "0".to_string(),
-            side: side.to_string(),
-            signature_type: 0, // EOA
+        Ok(OrderRequest {
+            order,
+            owner: format!("{:?}", signer.maker()),
+            order_type: intent.order_type.to_string(),
+            post_only,
+            fee_rate_bps: fee_rate_bps.to_string(),
+            side: intent.side.to_string(),
+            signature_type: signer.signature_type().as_u8(),
             signature,
-        };
+        })
+    }
 
-        // Send to API
-        let response = self
+    /// Get order status
+    pub async fn get_order(&self, order_id: &str) -> Result<OrderResponse> {
+        let (timestamp, nonce) = self.get_timestamp_and_nonce();
+        let signer = self.signer();
+        let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
+
+        let builder = self
             .http_client
-            .post(&format!("{}/order", self.api_url))
-            .header("POLY_ADDRESS", format!("{:?}", self.address))
+            .get(format!("{}/order/{}", self.endpoints.url(), order_id))
+            .header("POLY_ADDRESS", format!("{:?}", signer.address()))
             .header("POLY_SIGNATURE", &auth_signature)
             .header("POLY_TIMESTAMP", timestamp.to_string())
-            .header("POLY_NONCE", nonce.to_string())
-            .json(&request)
-            .send()
+            .header("POLY_NONCE", nonce.to_string());
+        let (status, body) = self
+            .send("get_order", builder, None, Some(timestamp))
             .await?;
 
-        if !response.status().is_success() {
-            let error = response.text().await?;
+        if !status.is_success() {
             return Err(PolymarketError::ApiError(format!(
-                "Failed to place order: {}",
-                error
+                "Failed to get order: {}",
+                body
             )));
         }
 
-        let order_response: OrderResponse = response.json().await?;
+        let order_response: OrderResponse = serde_json::from_str(&body)?;
         Ok(order_response)
     }
 
-    /// Get order status
-    pub async fn get_order(&self, order_id: &str) -> Result<OrderResponse> {
+    /// Cancel an order
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
         let (timestamp, nonce) = self.get_timestamp_and_nonce();
-        let auth_signature = self.signer.sign_auth_message(timestamp, nonce).await?;
+        let signer = self.signer();
+        let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
 
-        let response = self
+        let builder = self
             .http_client
-            .get(&format!("{}/order/{}", self.api_url, order_id))
-            .header("POLY_ADDRESS", format!("{:?}", self.address))
+            .delete(format!("{}/order/{}", self.endpoints.url(), order_id))
+            .header("POLY_ADDRESS", format!("{:?}", signer.address()))
             .header("POLY_SIGNATURE", &auth_signature)
             .header("POLY_TIMESTAMP", timestamp.to_string())
-            .header("POLY_NONCE", nonce.to_string())
-            .send()
+            .header("POLY_NONCE", nonce.to_string());
+        let (status, body) = self
+            .send("cancel_order", builder, None, Some(timestamp))
             .await?;
 
-        if !response.status().is_success() {
-            let error = response.text().await?;
+        if !status.is_success() {
             return Err(PolymarketError::ApiError(format!(
-                "Failed to get order: {}",
-                error
+                "Failed to cancel order: {}",
+                body
             )));
         }
 
-        let order_response: OrderResponse = response.json().await?;
-        Ok(order_response)
+        Ok(())
     }
 
-    /// Cancel an order
-    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+    /// Cancel every open order of this wallet
+    pub async fn cancel_all(&self) -> Result<CancelledOrders> {
+        self.cancel_bulk("cancel_all", "cancel-all", None).await
+    }
+
+    /// Cancel this wallet's open orders on one outcome token
+    pub async fn cancel_market_orders(&self, token_id: &str) -> Result<CancelledOrders> {
+        let body = serde_json::json!({ "asset_id": token_id });
+        self.cancel_bulk("cancel_market_orders", "cancel-market-orders", Some(body))
+            .await
+    }
+
+    async fn cancel_bulk(
+        &self,
+        action: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<CancelledOrders> {
         let (timestamp, nonce) = self.get_timestamp_and_nonce();
-        let auth_signature = self.signer.sign_auth_message(timestamp, nonce).await?;
+        let signer = self.signer();
+        let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
 
-        let response = self
+        let mut builder = self
             .http_client
-            .delete(&format!("{}/order/{}", self.api_url, order_id))
-            .header("POLY_ADDRESS", format!("{:?}", self.address))
+            .delete(format!("{}/{}", self.endpoints.url(), path))
+            .header("POLY_ADDRESS", format!("{:?}", signer.address()))
             .header("POLY_SIGNATURE", &auth_signature)
             .header("POLY_TIMESTAMP", timestamp.to_string())
-            .header("POLY_NONCE", nonce.to_string())
-            .send()
-            .await?;
+            .header("POLY_NONCE", nonce.to_string());
+        if let Some(ref body) = body {
+            builder = builder.json(body);
+        }
+        let (status, body) = self.send(action, builder, body, Some(timestamp)).await?;
 
-        if !response.status().is_success() {
-            let error = response.text().await?;
+        if !status.is_success() {
             return Err(PolymarketError::ApiError(format!(
-                "Failed to cancel order: {}",
-                error
+                "Failed to cancel orders: {}",
+                body
             )));
         }
 
-        Ok(())
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Our matches since `after`, oldest page first, following the cursor
+    /// through every page
+    pub async fn get_trades(&self, after: Option<DateTime<Utc>>) -> Result<Vec<ClobTrade>> {
+        let mut trades = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let (timestamp, nonce) = self.get_timestamp_and_nonce();
+            let signer = self.signer();
+            let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
+
+            let mut query = vec![("maker_address", format!("{:?}", signer.maker()))];
+            if let Some(after) = after {
+                query.push(("after", after.timestamp().to_string()));
+            }
+            if !cursor.is_empty() {
+                query.push(("next_cursor", cursor.clone()));
+            }
+            let builder = self
+                .http_client
+                .get(format!("{}/data/trades", self.endpoints.url()))
+                .query(&query)
+                .header("POLY_ADDRESS", format!("{:?}", signer.address()))
+                .header("POLY_SIGNATURE", &auth_signature)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_NONCE", nonce.to_string());
+            let (status, body) = self
+                .send("get_trades", builder, None, Some(timestamp))
+                .await?;
+
+            if !status.is_success() {
+                return Err(PolymarketError::ApiError(format!(
+                    "Failed to get trades: {}",
+                    body
+                )));
+            }
+
+            let page: TradesPage = serde_json::from_str(&body)?;
+            let last_page = page.data.is_empty()
+                || page.next_cursor.is_empty()
+                || page.next_cursor == END_CURSOR;
+            trades.extend(page.data);
+            if last_page {
+                return Ok(trades);
+            }
+            cursor = page.next_cursor;
+        }
+    }
+
+    /// Our orders resting on the book, following the cursor through every page
+    pub async fn get_open_orders(&self) -> Result<Vec<OpenOrder>> {
+        let mut orders = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let (timestamp, nonce) = self.get_timestamp_and_nonce();
+            let signer = self.signer();
+            let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
+
+            let mut builder = self
+                .http_client
+                .get(format!("{}/data/orders", self.endpoints.url()))
+                .header("POLY_ADDRESS", format!("{:?}", signer.address()))
+                .header("POLY_SIGNATURE", &auth_signature)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_NONCE", nonce.to_string());
+            if !cursor.is_empty() {
+                builder = builder.query(&[("next_cursor", cursor.clone())]);
+            }
+            let (status, body) = self
+                .send("get_open_orders", builder, None, Some(timestamp))
+                .await?;
+
+            if !status.is_success() {
+                return Err(PolymarketError::ApiError(format!(
+                    "Failed to get open orders: {}",
+                    body
+                )));
+            }
+
+            let page: OpenOrdersPage = serde_json::from_str(&body)?;
+            let last_page = page.data.is_empty()
+                || page.next_cursor.is_empty()
+                || page.next_cursor == END_CURSOR;
+            orders.extend(page.data);
+            if last_page {
+                return Ok(orders);
+            }
+            cursor = page.next_cursor;
+        }
+    }
+
+    /// Make sure the CLOB answers and that our clock agrees with its clock to
+    /// within `max_skew`, since signed requests carry our timestamp
+    ///
+    /// Meant to run once before trading starts, so an unreachable API or a
+    /// drifting clock fails loudly instead of rejecting the first order.
+    pub async fn check_connection(&self, max_skew: Duration) -> Result<chrono::Duration> {
+        let url = self.endpoints.url();
+        let builder = self.http_client.get(format!("{}/", url));
+        let (status, body) = self
+            .send("health_check", builder, None, None)
+            .await
+            .map_err(|e| {
+                PolymarketError::ApiError(format!("CLOB API at {} is unreachable: {}", url, e))
+            })?;
+        if !status.is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "CLOB API at {} is not healthy ({}): {}",
+                url, status, body
+            )));
+        }
+
+        let builder = self.http_client.get(format!("{}/time", url));
+        let (status, body) = self.send("get_server_time", builder, None, None).await?;
+        if !status.is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to get server time: {}",
+                body
+            )));
+        }
+        let server_time = body
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+            .ok_or_else(|| {
+                PolymarketError::ParseError(format!("Unexpected server time: {}", body))
+            })?;
+        check_clock_skew(server_time, Utc::now(), max_skew)
+    }
+
+    /// The wallet's CLOB API credentials, created if it has none yet
+    ///
+    /// Succeeding also shows the exchange accepts our wallet signatures.
+    pub async fn derive_api_key(&self) -> Result<ApiCredentials> {
+        let signer = self.signer();
+        for (action, method, path) in [
+            (
+                "derive_api_key",
+                reqwest::Method::GET,
+                "/auth/derive-api-key",
+            ),
+            ("create_api_key", reqwest::Method::POST, "/auth/api-key"),
+        ] {
+            let (timestamp, nonce) = self.get_timestamp_and_nonce();
+            let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
+            let builder = self
+                .http_client
+                .request(method, format!("{}{}", self.endpoints.url(), path))
+                .header("POLY_ADDRESS", format!("{:?}", signer.address()))
+                .header("POLY_SIGNATURE", &auth_signature)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_NONCE", nonce.to_string());
+            // Never retried through a rotation, which derives the key itself
+            let mut request = builder.build()?;
+            *request.timeout_mut() = Some(self.endpoints.request_timeout());
+            let (status, _, body) = self
+                .exchange(action, request, None, Some(timestamp))
+                .await?;
+            if status.is_success() {
+                return Ok(serde_json::from_str(&body)?);
+            }
+            if status.is_server_error() || status == StatusCode::UNAUTHORIZED {
+                return Err(PolymarketError::ApiError(format!(
+                    "Failed to get API credentials ({}): {}",
+                    status, body
+                )));
+            }
+        }
+        Err(PolymarketError::ApiError(
+            "The CLOB neither derived nor created API credentials for the wallet".to_string(),
+        ))
+    }
+
+    /// Midpoint between the best bid and ask for a token
+    pub async fn get_midpoint(&self, token_id: &str) -> Result<Decimal> {
+        let builder = self
+            .http_client
+            .get(format!("{}/midpoint", self.endpoints.url()))
+            .query(&[("token_id", token_id)]);
+        let (status, body) = self.send("get_midpoint", builder, None, None).await?;
+
+        if !status.is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to get midpoint: {}",
+                body
+            )));
+        }
+
+        let midpoint: MidpointResponse = serde_json::from_str(&body)?;
+        Ok(midpoint.mid)
+    }
+
+    /// The taker fee rate of a token's market, in basis points
+    pub async fn get_fee_rate_bps(&self, token_id: &str) -> Result<u32> {
+        if let Some(rate) = self
+            .fee_rates
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(token_id)
+        {
+            return Ok(*rate);
+        }
+
+        let builder = self
+            .http_client
+            .get(format!("{}/fee-rate", self.endpoints.url()))
+            .query(&[("token_id", token_id)]);
+        let (status, body) = self.send("get_fee_rate", builder, None, None).await?;
+
+        if !status.is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to get fee rate: {}",
+                body
+            )));
+        }
+
+        let fee_rate: FeeRateResponse = serde_json::from_str(&body)?;
+        self.fee_rates
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(token_id.to_string(), fee_rate.base_fee);
+        Ok(fee_rate.base_fee)
+    }
+
+    /// Best bid and ask for a token; either is None when that side is empty
+    pub async fn get_best_quotes(&self, token_id: &str) -> Result<BestQuotes> {
+        let book = self.get_order_book(token_id).await?;
+        Ok(BestQuotes {
+            bid: book.bids.first().map(|(price, _)| *price),
+            ask: book.asks.first().map(|(price, _)| *price),
+        })
+    }
+
+    /// The full order book for a token
+    pub async fn get_order_book(&self, token_id: &str) -> Result<OrderBook> {
+        let builder = self
+            .http_client
+            .get(format!("{}/book", self.endpoints.url()))
+            .query(&[("token_id", token_id)]);
+        let (status, body) = self.send("get_book", builder, None, None).await?;
+
+        if !status.is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to get order book: {}",
+                body
+            )));
+        }
+
+        let book: BookResponse = serde_json::from_str(&body)?;
+        let levels = |levels: Vec<BookLevelResponse>| -> Vec<(Decimal, Decimal)> {
+            levels
+                .into_iter()
+                .map(|level| (level.price, level.size))
+                .collect()
+        };
+        // The API doesn't promise an order, so sort best first
+        let mut bids = levels(book.bids);
+        bids.sort_by_key(|(price, _)| std::cmp::Reverse(*price));
+        let mut asks = levels(book.asks);
+        asks.sort_by_key(|(price, _)| *price);
+        Ok(OrderBook { bids, asks })
     }
 
     /// Get market data including tick size
     pub async fn get_tick_size(&self, market_id: &str) -> Result<Decimal> {
-        let response = self
-            .http_client
-            .get(&format!("{}/markets/{}", self.api_url, market_id))
-            .send()
-            .await?;
+        let builder =
+            self.http_client
+                .get(format!("{}/markets/{}", self.endpoints.url(), market_id));
+        let (status, body) = self.send("get_tick_size", builder, None, None).await?;
 
-        if !response.status().is_success() {
+        if !status.is_success() {
             return Ok(Decimal::new(1, 3)); // Default to 0.001
         }
 
-        let market_data: MarketData = response.json().await.unwrap_or(MarketData {
+        let market_data: MarketData = serde_json::from_str(&body).unwrap_or(MarketData {
             market_id: market_id.to_string(),
             tick_size: Decimal::new(1, 3),
             min_size: Decimal::ONE,
@@ -193,12 +1067,137 @@ impl ClobClient {
         (timestamp, nonce)
     }
 
-    /// Get current USDC balance (mock implementation)
+    /// USDC the exchange holds for our wallet
     pub async fn get_balance(&self) -> Result<Decimal> {
-        // In a real implementation, this would query the blockchain
-        // For now, return a placeholder
-        Ok(Decimal::new(10000, 0))
+        let (timestamp, nonce) = self.get_timestamp_and_nonce();
+        let signer = self.signer();
+        let auth_signature = signer.sign_auth_message(timestamp, nonce)?;
+
+        let builder = self
+            .http_client
+            .get(format!("{}/balance-allowance", self.endpoints.url()))
+            .query(&[
+                ("asset_type", "COLLATERAL".to_string()),
+                (
+                    "signature_type",
+                    signer.signature_type().as_u8().to_string(),
+                ),
+            ])
+            .header("POLY_ADDRESS", format!("{:?}", signer.address()))
+            .header("POLY_SIGNATURE", &auth_signature)
+            .header("POLY_TIMESTAMP", timestamp.to_string())
+            .header("POLY_NONCE", nonce.to_string());
+        let (status, body) = self
+            .send("get_balance", builder, None, Some(timestamp))
+            .await?;
+
+        if !status.is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to get balance: {}",
+                body
+            )));
+        }
+
+        let response: BalanceResponse = serde_json::from_str(&body)?;
+        usdc_from_base_units(&response.balance)
+    }
+}
+
+/// USDC in a balance the API gives in base units
+fn usdc_from_base_units(balance: &str) -> Result<Decimal> {
+    let units: Decimal = balance
+        .trim()
+        .parse()
+        .map_err(|_| PolymarketError::ApiError(format!("Invalid balance '{}'", balance)))?;
+    Ok(units / Decimal::from(10u64.pow(USDC_DECIMALS)))
+}
+
+/// How far `local` is ahead of `server`, or an error when that is more than
+/// `max_skew` either way
+fn check_clock_skew(
+    server: DateTime<Utc>,
+    local: DateTime<Utc>,
+    max_skew: Duration,
+) -> Result<chrono::Duration> {
+    let skew = local - server;
+    if skew.abs().to_std().unwrap_or(Duration::MAX) > max_skew {
+        return Err(PolymarketError::ApiError(format!(
+            "Local clock is {}s {} the CLOB server's; signed requests are rejected beyond {}s. \
+             Sync the system clock (e.g. enable NTP) and restart",
+            skew.num_seconds().abs(),
+            if skew > chrono::Duration::zero() {
+                "ahead of"
+            } else {
+                "behind"
+            },
+            max_skew.as_secs()
+        )));
     }
+    Ok(skew)
+}
+
+/// Parse the response to placing orders, telling apart failures where the
+/// exchange may still have taken them
+///
+/// A rejection (4xx) means nothing was placed. A rate limit also means
+/// nothing was placed, and says when to try again. A request that timed out,
+/// a 5xx or an unreadable success response may each hide placed orders, so
+/// they are reported as [`PolymarketError::OrderOutcomeUnknown`].
+fn placement_response<T: serde::de::DeserializeOwned>(
+    response: Result<(StatusCode, HeaderMap, String)>,
+) -> Result<T> {
+    let (status, headers, body) = response.map_err(|e| match e {
+        PolymarketError::NetworkError(ref error) if error.is_connect() || error.is_builder() => e,
+        e => PolymarketError::OrderOutcomeUnknown(e.to_string()),
+    })?;
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(1);
+        return Err(PolymarketError::RateLimited(retry_after));
+    }
+    if status.is_server_error() {
+        return Err(PolymarketError::OrderOutcomeUnknown(format!(
+            "{}: {}",
+            status, body
+        )));
+    }
+    if !status.is_success() {
+        return Err(PolymarketError::ApiError(format!(
+            "Failed to place order: {}",
+            body
+        )));
+    }
+    serde_json::from_str(&body).map_err(|e| {
+        PolymarketError::OrderOutcomeUnknown(format!("unreadable response ({}): {}", e, body))
+    })
+}
+
+/// Match a batch response to the `expected` orders sent, failing the ones
+/// the exchange rejected
+fn batch_results(
+    responses: Vec<OrderResponse>,
+    expected: usize,
+) -> Result<Vec<Result<OrderResponse>>> {
+    if responses.len() != expected {
+        return Err(PolymarketError::ApiError(format!(
+            "Batch response has {} results for {} orders",
+            responses.len(),
+            expected
+        )));
+    }
+    Ok(responses
+        .into_iter()
+        .map(|response| match response.error {
+            Some(ref error) if !error.is_empty() => Err(PolymarketError::ApiError(format!(
+                "Failed to place order: {}",
+                error
+            ))),
+            _ => Ok(response),
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -223,4 +1222,85 @@ mod tests {
         let adjusted = client.adjust_to_tick_size(dec!(0.5678), dec!(0.001));
         assert_eq!(adjusted, dec!(0.568));
     }
+
+    #[test]
+    fn test_batch_results_map_back_to_orders() {
+        let body = r#"[
+            {"order_id":"0x1","status":"Filled"},
+            {"order_id":"","status":"Cancelled","error":"not enough balance"},
+            {"order_id":"0x3","status":"Open","error":""}
+        ]"#;
+        let responses: Vec<OrderResponse> = serde_json::from_str(body).unwrap();
+        let results = batch_results(responses.clone(), 3).unwrap();
+
+        assert_eq!(results[0].as_ref().unwrap().order_id, "0x1");
+        assert!(results[1]
+            .as_ref()
+            .is_err_and(|e| e.to_string().contains("not enough balance")));
+        assert_eq!(results[2].as_ref().unwrap().order_id, "0x3");
+        // A response that doesn't line up with the request can't be trusted
+        assert!(batch_results(responses, 4).is_err());
+    }
+
+    #[test]
+    fn test_cancelled_orders_response() {
+        let body = r#"{"canceled":["0x1","0x2"],"not_canceled":{"0x3":"order already matched"}}"#;
+        let result: CancelledOrders = serde_json::from_str(body).unwrap();
+        assert_eq!(result.canceled, vec!["0x1", "0x2"]);
+        assert_eq!(result.not_canceled["0x3"], "order already matched");
+
+        let empty: CancelledOrders = serde_json::from_str("{}").unwrap();
+        assert!(empty.canceled.is_empty());
+    }
+
+    #[test]
+    fn test_clock_skew_limit() {
+        let server = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let limit = Duration::from_secs(10);
+
+        let skew = check_clock_skew(server, server + chrono::Duration::seconds(4), limit);
+        assert_eq!(skew.unwrap(), chrono::Duration::seconds(4));
+        let behind = check_clock_skew(server, server - chrono::Duration::seconds(30), limit);
+        assert!(behind.is_err_and(|e| e.to_string().contains("30s behind")));
+    }
+
+    #[test]
+    fn test_balance_is_read_from_base_units() {
+        assert_eq!(usdc_from_base_units("1234560000").unwrap(), dec!(1234.56));
+        assert_eq!(usdc_from_base_units("0").unwrap(), Decimal::ZERO);
+        assert!(usdc_from_base_units("a lot").is_err());
+    }
+
+    #[test]
+    fn test_walking_the_book() {
+        let book = OrderBook {
+            bids: vec![(dec!(0.48), dec!(50))],
+            asks: vec![(dec!(0.50), dec!(100)), (dec!(0.52), dec!(100))],
+        };
+
+        let fill = book.walk(&OrderSide::Buy, dec!(150)).unwrap();
+        assert_eq!(fill.shares, dec!(150));
+        assert_eq!(fill.average_price.round_dp(4), dec!(0.5067));
+        assert_eq!(fill.fill_ratio, Decimal::ONE);
+
+        // Only what rests on the book fills
+        let fill = book.walk(&OrderSide::Sell, dec!(100)).unwrap();
+        assert_eq!((fill.shares, fill.average_price), (dec!(50), dec!(0.48)));
+        assert_eq!(fill.fill_ratio, dec!(0.5));
+        assert_eq!(OrderBook::default().walk(&OrderSide::Buy, dec!(10)), None);
+    }
+
+    #[test]
+    fn test_l2_signature_is_an_hmac_of_the_request() {
+        let signature = l2_signature(
+            "bW9jay1zZWNyZXQ=",
+            1700000000,
+            "POST",
+            "/order",
+            br#"{"a":1}"#,
+        )
+        .unwrap();
+        assert_eq!(signature, "jpRn7uC5GXQGiHyS4QCH8TaI8_TeEnS-AC-22PldWEY=");
+        assert!(l2_signature("not base64!", 1700000000, "GET", "/", b"").is_err());
+    }
 }
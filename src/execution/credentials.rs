@@ -0,0 +1,147 @@
+use crate::errors::Result;
+use crate::execution::clob_client::ApiCredentials;
+use crate::metrics;
+use crate::notifications::SlackNotifier;
+use std::fs;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+/// Shortest time between two attempts to rotate the credentials
+const MIN_ROTATION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Replaces the wallet's CLOB API credentials when the exchange stops
+/// accepting our requests, keeping the current ones in a file
+pub struct CredentialRotation {
+    path: PathBuf,
+    notifier: Option<Arc<SlackNotifier>>,
+    /// When the last rotation was attempted and the credentials it got
+    last_attempt: Mutex<Option<(Instant, Option<ApiCredentials>)>>,
+}
+
+impl CredentialRotation {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            notifier: None,
+            last_attempt: Mutex::new(None),
+        }
+    }
+
+    /// Alert here when rotation fails
+    pub fn with_notifier(mut self, notifier: Arc<SlackNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// The credentials last saved, if any
+    pub fn load(&self) -> Result<Option<ApiCredentials>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get new credentials from `derive` and save them; None when the failed
+    /// request isn't worth retrying
+    ///
+    /// Requests failing together share one rotation: within a minute of the
+    /// last attempt its outcome is returned without deriving again.
+    pub async fn rotate(
+        &self,
+        derive: impl Future<Output = Result<ApiCredentials>>,
+    ) -> Option<ApiCredentials> {
+        let mut last_attempt = self.last_attempt.lock().await;
+        if let Some((at, ref rotated)) = *last_attempt {
+            if at.elapsed() < MIN_ROTATION_INTERVAL {
+                return rotated.clone();
+            }
+        }
+
+        let result = match derive.await {
+            Ok(credentials) => self.save(&credentials).map(|()| credentials),
+            Err(e) => Err(e),
+        };
+        *last_attempt = Some((Instant::now(), result.as_ref().ok().cloned()));
+        match result {
+            Ok(credentials) => {
+                record("rotated");
+                info!(
+                    "CLOB rejected our credentials; rotated to API key {}",
+                    credentials.api_key
+                );
+                Some(credentials)
+            }
+            Err(e) => {
+                record("failed");
+                let message = format!("Failed to rotate the CLOB API credentials: {}", e);
+                error!("{}", message);
+                if let Some(ref notifier) = self.notifier {
+                    notifier.alert(message).await;
+                }
+                None
+            }
+        }
+    }
+
+    /// Write the credentials readable by the owner only
+    pub fn save(&self, credentials: &ApiCredentials) -> Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&self.path)?;
+        file.write_all(serde_json::to_string_pretty(credentials)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn record(result: &str) {
+    metrics::global().increment(
+        "polycopy_credential_rotations_total",
+        "CLOB API credential rotations after an auth failure, by result",
+        &[("result", result)],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::PolymarketError;
+
+    #[tokio::test]
+    async fn test_rotation_saves_credentials_once_per_interval() {
+        let path = std::env::temp_dir().join(format!("credentials-{}.json", uuid::Uuid::new_v4()));
+        let rotation = CredentialRotation::new(&path);
+        assert!(rotation.load().unwrap().is_none());
+
+        let credentials = |key: &str| ApiCredentials {
+            api_key: key.to_string(),
+            secret: "secret".to_string(),
+            passphrase: "passphrase".to_string(),
+        };
+        let rotated = rotation.rotate(async { Ok(credentials("new")) }).await;
+        assert_eq!(rotated.unwrap().api_key, "new");
+        assert_eq!(rotation.load().unwrap().unwrap().api_key, "new");
+
+        // A request failing right after shares the rotation instead of deriving again
+        let derive_again = async { Ok(credentials("newer")) };
+        assert_eq!(rotation.rotate(derive_again).await.unwrap().api_key, "new");
+        assert_eq!(rotation.load().unwrap().unwrap().api_key, "new");
+
+        let failing = CredentialRotation::new(&path);
+        let refused = async { Err(PolymarketError::ApiError("401".to_string())) };
+        assert!(failing.rotate(refused).await.is_none());
+        assert!(failing
+            .rotate(async { Ok(credentials("late")) })
+            .await
+            .is_none());
+        assert_eq!(failing.load().unwrap().unwrap().api_key, "new");
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+use crate::config::RiskConfig;
+use crate::coordination::Coordinator;
+use crate::errors::Result;
+use crate::execution::pause::PauseSwitch;
+use crate::execution::OrderExecutor;
+use crate::monitoring::PriceCache;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Peak equity and whether the kill switch has fired, kept across restarts
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DrawdownState {
+    pub peak_equity: Option<Decimal>,
+    pub peak_at: Option<DateTime<Utc>>,
+    pub tripped_at: Option<DateTime<Utc>>,
+}
+
+impl DrawdownState {
+    /// Record `equity`; returns the drawdown if it just crossed `limit`
+    pub fn observe(
+        &mut self,
+        equity: Decimal,
+        limit: Decimal,
+        now: DateTime<Utc>,
+    ) -> Option<Decimal> {
+        if self.tripped_at.is_some() {
+            return None;
+        }
+        let peak = match self.peak_equity {
+            Some(peak) if peak >= equity => peak,
+            _ => {
+                self.peak_equity = Some(equity);
+                self.peak_at = Some(now);
+                equity
+            }
+        };
+        if peak <= Decimal::ZERO {
+            return None;
+        }
+        let drawdown = (peak - equity) / peak;
+        if drawdown >= limit {
+            self.tripped_at = Some(now);
+            Some(drawdown)
+        } else {
+            None
+        }
+    }
+}
+
+/// Drawdown state file; the kill switch stays tripped until re-armed
+pub struct DrawdownStore {
+    path: PathBuf,
+}
+
+impl DrawdownStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn load(&self) -> Result<DrawdownState> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DrawdownState::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, state: &DrawdownState) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string_pretty(state)?)?;
+        Ok(())
+    }
+
+    /// Forget the peak and clear the trip; the next check starts a new peak
+    pub fn rearm(&self) -> Result<DrawdownState> {
+        let previous = self.load()?;
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(previous)
+    }
+}
+
+/// Stops all copying once equity falls too far below its peak
+///
+/// Equity is the USDC balance plus held positions marked at cached prices
+/// (entry price when there is no quote). The peak persists across restarts,
+/// so losses spread over several days still add up. When it fires, copying
+/// is paused, positions are optionally sold, and nothing resumes until the
+/// `rearm` command resets the peak.
+pub struct DrawdownMonitor {
+    executor: Arc<OrderExecutor>,
+    prices: Arc<PriceCache>,
+    pause: Arc<PauseSwitch>,
+    store: DrawdownStore,
+    limit: Decimal,
+    flatten: bool,
+    interval: Duration,
+    coordinator: Option<Arc<Coordinator>>,
+}
+
+impl DrawdownMonitor {
+    /// None unless `max_drawdown_percent` is set
+    pub fn from_config(
+        config: &RiskConfig,
+        executor: Arc<OrderExecutor>,
+        prices: Arc<PriceCache>,
+        pause: Arc<PauseSwitch>,
+    ) -> Option<Self> {
+        Some(Self {
+            executor,
+            prices,
+            pause,
+            store: DrawdownStore::new(&config.drawdown_state_file),
+            limit: config.max_drawdown_percent?,
+            flatten: config.drawdown_flatten,
+            interval: Duration::from_secs(config.drawdown_check_interval_seconds),
+            coordinator: None,
+        })
+    }
+
+    /// Only act while this instance is the leader
+    pub fn with_coordinator(mut self, coordinator: Arc<Coordinator>) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    pub async fn run_periodic(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            if self.coordinator.as_ref().is_some_and(|c| !c.is_leader()) {
+                continue;
+            }
+            if let Err(e) = self.run_once().await {
+                warn!("Drawdown check failed: {}", e);
+            }
+        }
+    }
+
+    pub async fn run_once(&self) -> Result<()> {
+        let mut state = self.store.load()?;
+        if state.tripped_at.is_some() {
+            // Someone resumed without re-arming
+            if !self.pause.is_paused() {
+                self.pause.pause(Some(
+                    "drawdown kill switch is tripped; run `rearm`".to_string(),
+                ))?;
+            }
+            return Ok(());
+        }
+
+        let equity = self.equity().await?;
+        let Some(drawdown) = state.observe(equity, self.limit, Utc::now()) else {
+            self.store.save(&state)?;
+            return Ok(());
+        };
+        self.store.save(&state)?;
+
+        let reason = format!(
+            "drawdown kill switch: equity {} USDC is {}% below its peak of {} USDC",
+            equity.round_dp(2),
+            (drawdown * Decimal::ONE_HUNDRED).round_dp(1),
+            state.peak_equity.unwrap_or_default().round_dp(2)
+        );
+        error!("{}; copying stopped until `rearm`", reason);
+        self.pause.pause(Some(reason))?;
+
+        // Resting orders would keep adding exposure
+        match self
+            .executor
+            .cancel_open_orders(None, "drawdown kill switch")
+            .await
+        {
+            Ok(cancelled) => info!("Cancelled {} open orders", cancelled),
+            Err(e) => warn!("Failed to cancel open orders: {}", e),
+        }
+
+        if self.flatten {
+            self.flatten_positions().await?;
+        }
+        Ok(())
+    }
+
+    async fn equity(&self) -> Result<Decimal> {
+        let balance = self.executor.get_balance().await?;
+        let positions: Decimal = self
+            .executor
+            .holdings()
+            .await?
+            .iter()
+            .map(|(market_id, holding)| {
+                let price = self
+                    .prices
+                    .price(market_id)
+                    .unwrap_or_else(|| holding.average_price());
+                holding.value_at(price)
+            })
+            .sum();
+        Ok(balance + positions)
+    }
+
+    async fn flatten_positions(&self) -> Result<()> {
+        for (market_id, holding) in self.executor.holdings().await? {
+            let Some(price) = self.prices.price(&market_id) else {
+                warn!("No current price for {}; leaving it open", market_id);
+                continue;
+            };
+            info!("Flattening {} shares of {}", holding.shares, market_id);
+            if let Err(e) = self
+                .executor
+                .reduce_position(&market_id, holding.shares, price, "drawdown kill switch")
+                .await
+            {
+                warn!("Failed to flatten {}: {}", market_id, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_drawdown_trips_once_from_peak() {
+        let now = Utc::now();
+        let mut state = DrawdownState::default();
+        assert_eq!(state.observe(dec!(1000), dec!(0.2), now), None);
+        assert_eq!(state.observe(dec!(1200), dec!(0.2), now), None);
+        assert_eq!(state.observe(dec!(1000), dec!(0.2), now), None);
+        assert_eq!(state.peak_equity, Some(dec!(1200)));
+
+        assert_eq!(state.observe(dec!(960), dec!(0.2), now), Some(dec!(0.2)));
+        assert!(state.tripped_at.is_some());
+        // Stays tripped, even if equity recovers
+        assert_eq!(state.observe(dec!(500), dec!(0.2), now), None);
+        assert!(state.tripped_at.is_some());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::models::{OrderSide, Trade};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+type Key = (Address, String, OrderSide);
+
+/// Refuses a second copy of the same trader's trade in a market and side
+/// within a window
+///
+/// Trade ids can't tell that a fill seen in the position diff and in the
+/// activity feed is one economic trade. Copies whose trader size is within
+/// `size_tolerance` of one already copied in the window are taken for such
+/// duplicates; materially different sizes are copied as new trades.
+pub struct DuplicateGuard {
+    window: Duration,
+    size_tolerance: Decimal,
+    copied: Mutex<HashMap<Key, Vec<(Instant, Decimal)>>>,
+}
+
+impl DuplicateGuard {
+    pub fn new(window: Duration, size_tolerance: Decimal) -> Self {
+        Self {
+            window,
+            size_tolerance,
+            copied: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A guard with the same settings that has seen no copies
+    pub fn fresh(&self) -> Self {
+        Self::new(self.window, self.size_tolerance)
+    }
+
+    /// Why `trade` looks like a copy already made, if it does; otherwise
+    /// records it, so a duplicate arriving meanwhile is refused
+    pub fn check(&self, trade: &Trade) -> Option<String> {
+        self.check_at(trade, Instant::now())
+    }
+
+    fn check_at(&self, trade: &Trade, now: Instant) -> Option<String> {
+        let mut copied = self.copied.lock().unwrap_or_else(|e| e.into_inner());
+        copied.retain(|_, copies| {
+            copies.retain(|(at, _)| now.duration_since(*at) <= self.window);
+            !copies.is_empty()
+        });
+
+        let key = (trade.trader, trade.market_id.clone(), trade.side.clone());
+        let copies = copied.entry(key).or_default();
+        if let Some((at, size)) = copies
+            .iter()
+            .find(|(_, size)| !self.materially_different(*size, trade.size_usdc))
+        {
+            return Some(format!(
+                "a {} of {} USDC by the same trader was copied {}s ago",
+                trade.side,
+                size,
+                now.duration_since(*at).as_secs()
+            ));
+        }
+        copies.push((now, trade.size_usdc));
+        None
+    }
+
+    fn materially_different(&self, copied: Decimal, size: Decimal) -> bool {
+        if copied.is_zero() {
+            return !size.is_zero();
+        }
+        (size - copied).abs() / copied > self.size_tolerance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_refuses_similar_copies_within_the_window() {
+        let guard = DuplicateGuard::new(Duration::from_secs(60), dec!(0.1));
+        let trade = |side: OrderSide, size_usdc: Decimal| {
+            Trade::sample()
+                .with_market("market1")
+                .with_trader(Address::from_low_u64_be(1))
+                .with_side(side)
+                .with_fill(dec!(0.5), size_usdc * dec!(2))
+        };
+        let start = Instant::now();
+
+        assert_eq!(
+            guard.check_at(&trade(OrderSide::Buy, dec!(100)), start),
+            None
+        );
+        let reason = guard
+            .check_at(
+                &trade(OrderSide::Buy, dec!(105)),
+                start + Duration::from_secs(5),
+            )
+            .unwrap();
+        assert_eq!(
+            reason,
+            "a BUY of 100 USDC by the same trader was copied 5s ago"
+        );
+
+        // Another side, a materially different size or another trader is a new trade
+        assert_eq!(
+            guard.check_at(&trade(OrderSide::Sell, dec!(100)), start),
+            None
+        );
+        assert_eq!(
+            guard.check_at(&trade(OrderSide::Buy, dec!(150)), start),
+            None
+        );
+        let other = trade(OrderSide::Buy, dec!(100)).with_trader(Address::from_low_u64_be(2));
+        assert_eq!(guard.check_at(&other, start), None);
+
+        // Once the window has passed
+        let later = start + Duration::from_secs(61);
+        assert_eq!(
+            guard.check_at(&trade(OrderSide::Buy, dec!(100)), later),
+            None
+        );
+    }
+}
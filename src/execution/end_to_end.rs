@@ -0,0 +1,512 @@
+//! The executor run against [`MockClob`], from a detected trade to the
+//! recorded order, including through API outages
+
+use crate::config::{Config, ExecutionConfig, FailoverConfig};
+use crate::endpoints::Endpoints;
+use crate::errors::PolymarketError;
+use crate::execution::credentials::CredentialRotation;
+use crate::execution::exposure::ExposureGauges;
+use crate::execution::lifecycle::OrderState;
+use crate::execution::mock_clob::{Fault, FillBehavior, MockClob};
+use crate::execution::quality::execution_quality;
+use crate::execution::sweeper::OrderSweeper;
+use crate::execution::{ClobClient, OrderExecutor, OrderSigner, PositionSizer};
+use crate::metrics::{self, Registry};
+use crate::models::{OrderSide, OrderStatus, OrderType, Trade};
+use crate::monitoring::data_api::DataApiClient;
+use crate::monitoring::{MarketDirectory, PollingMonitor, PriceCache};
+use crate::network;
+use crate::storage::order_journal::OrderJournal;
+use crate::storage::OrderStore;
+use chrono::{Duration as ChronoDuration, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const TEST_PRIVATE_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+
+/// The executor of the example config, with short timeouts, against `mock`
+fn executor(
+    mock: &MockClob,
+    tweak: impl FnOnce(&mut ExecutionConfig),
+) -> (OrderExecutor, Arc<OrderJournal>) {
+    let config = Config::load_from_file("config.example.toml").unwrap();
+    let mut execution = config.execution.clone();
+    execution.price_sum_check = "off".to_string();
+    execution.order_confirmation_timeout_ms = 300;
+    execution.order_poll_interval_ms = 20;
+    tweak(&mut execution);
+
+    let signer = OrderSigner::new(TEST_PRIVATE_KEY, 137).unwrap();
+    let journal = std::env::temp_dir().join(format!("orders-{}.jsonl", uuid::Uuid::new_v4()));
+    let store = Arc::new(OrderJournal::new(journal.to_string_lossy().to_string()));
+    // Requests time out after a second, so a hanging mock is given up on quickly
+    let endpoints = Endpoints::new(
+        "clob",
+        mock.url(),
+        &[],
+        &FailoverConfig {
+            request_timeout_seconds: 1,
+            ..FailoverConfig::default()
+        },
+    );
+    let client = ClobClient::new(mock.url(), signer).with_endpoints(Arc::new(endpoints));
+    let executor = OrderExecutor::new(
+        client,
+        PositionSizer::new(config.position_sizing.clone()),
+        execution,
+    )
+    .with_order_store(store.clone());
+    (executor, store)
+}
+
+fn trade(market_id: &str) -> Trade {
+    Trade::sample()
+        .with_market(market_id)
+        .with_trader(Address::from_low_u64_be(1))
+}
+
+#[tokio::test]
+async fn test_copy_is_filled_and_recorded() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_fill(FillBehavior::FillAfterPolls(2));
+    mock.set_latency(Duration::from_millis(5));
+    let (executor, store) = executor(&mock, |_| {});
+
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+
+    let placed = mock.orders();
+    assert_eq!(placed.len(), 1);
+    assert_eq!(placed[0].status, OrderStatus::Filled);
+    let orders = store.orders().await.unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].state, OrderState::Filled);
+    assert_eq!(
+        orders[0].exchange_order_id.as_deref(),
+        Some(placed[0].id.as_str())
+    );
+    assert_eq!(orders[0].filled_size, placed[0].size);
+
+    // The exchange's trade history agrees with what was recorded
+    let since = Utc::now() - ChronoDuration::hours(1);
+    assert!(executor.verify_fills(since).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_copied_exit_sells_from_the_shares_bought() {
+    let mock = MockClob::start().await.unwrap();
+    let (executor, store) = executor(&mock, |_| {});
+    let config = Config::load_from_file("config.example.toml").unwrap();
+    let usdc = PositionSizer::new(config.position_sizing)
+        .calculate_position_size(dec!(50), dec!(10000))
+        .unwrap();
+
+    let buy = trade("111");
+    executor.execute_trade(&buy, dec!(10000)).await.unwrap();
+    let bought = executor.holdings().await.unwrap()["111"].shares;
+    assert_eq!(bought, usdc / buy.price);
+    assert_eq!(mock.orders()[0].size, bought);
+
+    // The trader sells 40% of their position; so do we, of our shares
+    let exit = trade("111")
+        .with_side(OrderSide::Sell)
+        .with_exit_fraction(dec!(0.4));
+    executor.execute_trade(&exit, dec!(10000)).await.unwrap();
+    let orders = store.orders().await.unwrap();
+    assert_eq!(orders[1].filled_size, bought * dec!(0.4));
+    let held = &executor.holdings().await.unwrap()["111"];
+    assert_eq!(held.shares, bought * dec!(0.6));
+    assert_eq!(held.cost, usdc * dec!(0.6));
+}
+
+#[tokio::test]
+async fn test_live_fills_feed_the_execution_quality_report() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_fill(FillBehavior::FillAfterPolls(1));
+    let (executor, store) = executor(&mock, |_| {});
+
+    let copied = trade("111").with_midpoint(dec!(0.49));
+    executor.execute_trade(&copied, dec!(10000)).await.unwrap();
+
+    let orders = store.orders().await.unwrap();
+    assert_eq!(orders[0].fill_price, Some(dec!(0.5)));
+    let quality = execution_quality(&orders);
+    let taker = &quality["taker"];
+    assert_eq!(taker.fills, 1);
+    assert_eq!(taker.shares, orders[0].filled_size);
+    assert_eq!(taker.vs_trader.per_share(), Some(Decimal::ZERO));
+    assert_eq!(taker.vs_midpoint.per_share(), Some(dec!(0.01)));
+}
+
+#[tokio::test]
+async fn test_balance_comes_from_the_exchange() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_balance(dec!(1234.56));
+    let (executor, _) = executor(&mock, |_| {});
+
+    assert_eq!(executor.get_balance().await.unwrap(), dec!(1234.56));
+    mock.inject("GET /balance-allowance", Fault::ServerError, 1);
+    assert!(executor.get_balance().await.is_err());
+}
+
+#[tokio::test]
+async fn test_balance_gauge_shows_the_exchange_balance() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_balance(dec!(250.5));
+    let (executor, _) = executor(&mock, |_| {});
+    let config = Config::load_from_file("config.example.toml").unwrap();
+    let gauges = ExposureGauges::from_config(
+        &config,
+        Arc::new(executor),
+        Arc::new(PriceCache::new(Duration::from_secs(60))),
+        Arc::new(MarketDirectory::new(mock.url())),
+    );
+
+    let registry = Registry::default();
+    mock.inject("GET /balance-allowance", Fault::ServerError, 1);
+    assert!(gauges.update(&registry).await.is_err());
+    assert_eq!(registry.value("polycopy_balance_usdc", &[]), None);
+    gauges.update(&registry).await.unwrap();
+    assert_eq!(registry.value("polycopy_balance_usdc", &[]), Some(250.5));
+    assert_eq!(registry.value("polycopy_equity_usdc", &[]), Some(250.5));
+}
+
+#[tokio::test]
+async fn test_partial_fill_records_the_matched_shares() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_fill(FillBehavior::PartialFill);
+    let (executor, store) = executor(&mock, |_| {});
+
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+
+    let placed = mock.orders();
+    let orders = store.orders().await.unwrap();
+    assert_eq!(orders[0].state, OrderState::PartiallyFilled);
+    assert_eq!(orders[0].filled_size, placed[0].size_matched);
+    assert!(orders[0].filled_size < orders[0].size);
+    assert_eq!(orders[0].fill_price, Some(placed[0].price));
+}
+
+#[tokio::test]
+async fn test_resting_copy_is_cancelled_after_the_timeout() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_fill(FillBehavior::Rest);
+    let (executor, store) = executor(&mock, |_| {});
+
+    let result = executor.execute_trade(&trade("111"), dec!(10000)).await;
+    assert!(matches!(result, Err(PolymarketError::OrderTimeout)));
+
+    assert_eq!(mock.orders()[0].status, OrderStatus::Cancelled);
+    assert!(mock
+        .requests()
+        .iter()
+        .any(|r| r.starts_with("DELETE /order/")));
+    assert_eq!(
+        store.orders().await.unwrap()[0].state,
+        OrderState::Cancelled
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_copy_is_retried_then_failed() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_market_fill(
+        "222",
+        FillBehavior::Reject("not enough balance".to_string()),
+    );
+    let (executor, store) = executor(&mock, |execution| execution.max_retries = 2);
+
+    let result = executor.execute_trade(&trade("222"), dec!(10000)).await;
+    assert!(result.is_err_and(|e| e.to_string().contains("not enough balance")));
+
+    let orders = store.orders().await.unwrap();
+    assert_eq!(orders.len(), 2);
+    assert!(orders.iter().all(|o| o.state == OrderState::Failed));
+    let attempts = mock
+        .requests()
+        .iter()
+        .filter(|r| *r == "POST /order")
+        .count();
+    assert_eq!(attempts, 2);
+
+    // Other markets are unaffected
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrent_copies_go_out_in_one_batch() {
+    let mock = MockClob::start().await.unwrap();
+    let (executor, _) = executor(&mock, |_| {});
+    let executor = executor.with_batching(Duration::from_millis(50));
+
+    let (first, second) = (trade("111"), trade("222"));
+    let (first, second) = tokio::join!(
+        executor.execute_trade(&first, dec!(10000)),
+        executor.execute_trade(&second, dec!(10000)),
+    );
+    first.unwrap();
+    second.unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests.iter().filter(|r| *r == "POST /orders").count(), 1);
+    assert!(!requests.iter().any(|r| r == "POST /order"));
+    assert_eq!(mock.orders().len(), 2);
+}
+
+#[tokio::test]
+async fn test_open_orders_are_cancelled_in_bulk() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_fill(FillBehavior::PartialFill);
+    let (executor, store) = executor(&mock, |execution| {
+        execution.order_confirmation_timeout_ms = 50
+    });
+
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+    assert_eq!(
+        store.orders().await.unwrap()[0].state,
+        OrderState::PartiallyFilled
+    );
+
+    assert_eq!(executor.cancel_open_orders(None, "test").await.unwrap(), 1);
+    assert_eq!(mock.orders()[0].status, OrderStatus::Cancelled);
+    assert_eq!(
+        store.orders().await.unwrap()[0].state,
+        OrderState::Cancelled
+    );
+}
+
+#[tokio::test]
+async fn test_sweeper_cancels_orders_past_their_age() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_fill(FillBehavior::PartialFill);
+    let (executor, store) = executor(&mock, |execution| {
+        execution.order_confirmation_timeout_ms = 50
+    });
+    let executor = Arc::new(executor);
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+    assert_eq!(executor.open_orders().await.unwrap().len(), 1);
+
+    let mut config = Config::load_from_file("config.example.toml")
+        .unwrap()
+        .execution;
+    config.order_sweep_interval_seconds = 60;
+    config.max_open_order_age_seconds = 600;
+    let sweeper = OrderSweeper::from_config(&config, executor.clone());
+    assert_eq!(sweeper.sweep(Utc::now()).await.unwrap(), 0);
+    let later = Utc::now() + ChronoDuration::minutes(11);
+    assert_eq!(sweeper.sweep(later).await.unwrap(), 1);
+    assert_eq!(mock.orders()[0].status, OrderStatus::Cancelled);
+    let order = &store.orders().await.unwrap()[0];
+    assert_eq!(order.state, OrderState::Cancelled);
+    assert!(executor.open_orders().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_order_lost_in_a_timeout_is_not_placed_twice() {
+    let mock = MockClob::start().await.unwrap();
+    // The exchange fills the order, but the response never arrives in time
+    mock.inject("POST /order", Fault::Hang(Duration::from_secs(2)), 1);
+    let (executor, store) = executor(&mock, |_| {});
+
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+
+    let placed = mock.orders();
+    assert_eq!(placed.len(), 1);
+    let orders = store.orders().await.unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].state, OrderState::Filled);
+    assert_eq!(
+        orders[0].exchange_order_id.as_deref(),
+        Some(placed[0].id.as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_probe_fails_back_once_the_primary_answers() {
+    let mock = MockClob::start().await.unwrap();
+    let endpoints = Arc::new(Endpoints::new(
+        "test",
+        mock.url(),
+        &["http://127.0.0.1:9".to_string()],
+        &FailoverConfig {
+            failure_threshold: 1,
+            ..FailoverConfig::default()
+        },
+    ));
+    endpoints.record(&mock.url(), false);
+    assert!(endpoints.is_on_fallback());
+
+    let config = Config::load_from_file("config.example.toml").unwrap();
+    let http_client = network::http_client(&config.network).unwrap();
+    let probe = tokio::spawn(
+        endpoints
+            .clone()
+            .run_probe(http_client, Duration::from_millis(20)),
+    );
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while endpoints.is_on_fallback() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    probe.abort();
+    assert!(!endpoints.is_on_fallback());
+    assert!(mock.requests().iter().any(|r| r == "GET /"));
+}
+
+#[tokio::test]
+async fn test_unreadable_response_is_not_placed_twice() {
+    let mock = MockClob::start().await.unwrap();
+    mock.inject("POST /order", Fault::Malformed, 1);
+    let (executor, store) = executor(&mock, |_| {});
+
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+
+    assert_eq!(mock.orders().len(), 1);
+    assert_eq!(store.orders().await.unwrap()[0].state, OrderState::Filled);
+}
+
+#[tokio::test]
+async fn test_resting_order_of_unknown_outcome_is_not_retried() {
+    let mock = MockClob::start().await.unwrap();
+    mock.set_fill(FillBehavior::Rest);
+    mock.inject("POST /order", Fault::Hang(Duration::from_secs(2)), 1);
+    let (executor, store) = executor(&mock, |execution| execution.order_type = "GTC".to_string());
+
+    let result = executor.execute_trade(&trade("111"), dec!(10000)).await;
+    assert!(matches!(
+        result,
+        Err(PolymarketError::OrderOutcomeUnknown(_))
+    ));
+
+    let posts = mock
+        .requests()
+        .iter()
+        .filter(|r| *r == "POST /order")
+        .count();
+    assert_eq!(posts, 1);
+    assert_eq!(store.orders().await.unwrap()[0].state, OrderState::Failed);
+}
+
+#[tokio::test]
+async fn test_server_errors_and_rate_limits_are_retried_after_a_wait() {
+    let mock = MockClob::start().await.unwrap();
+    mock.inject("POST /order", Fault::ServerError, 1);
+    mock.inject("POST /order", Fault::RateLimited(1), 1);
+    let (executor, store) = executor(&mock, |_| {});
+
+    let started = Instant::now();
+    executor
+        .execute_trade(&trade("111"), dec!(10000))
+        .await
+        .unwrap();
+
+    // A second after the 500, then the second the rate limit asked for
+    assert!(started.elapsed() >= Duration::from_secs(2));
+    assert_eq!(mock.orders().len(), 1);
+    let states: Vec<OrderState> = store
+        .orders()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|o| o.state)
+        .collect();
+    assert_eq!(
+        states,
+        vec![OrderState::Failed, OrderState::Failed, OrderState::Filled]
+    );
+    let rate_limited = metrics::global().value(
+        "polycopy_order_placement_errors_total",
+        &[("kind", "rate_limited")],
+    );
+    assert!(rate_limited >= Some(1.0));
+}
+
+#[tokio::test]
+async fn test_monitor_backs_off_while_the_api_is_down() {
+    let mock = MockClob::start().await.unwrap();
+    mock.inject("GET /positions", Fault::ServerError, u32::MAX);
+    let mut monitor = PollingMonitor::new(
+        DataApiClient::new(mock.url()),
+        vec![Address::from_low_u64_be(1)],
+        Duration::from_millis(10),
+    );
+
+    let polling = monitor.monitor_loop(|_| Ok(()));
+    let _ = tokio::time::timeout(Duration::from_millis(700), polling).await;
+
+    // Polls wait 20, 40, 80, 160 and 320ms; without backoff there would be ~70
+    let polls = mock.requests().len();
+    assert!((4..=7).contains(&polls), "{} polls", polls);
+    let failed = metrics::global().value("polycopy_monitor_failed_polls", &[]);
+    assert!(failed >= Some(4.0));
+}
+
+#[tokio::test]
+async fn test_revoked_api_key_is_rotated_and_the_request_retried() {
+    let mock = MockClob::start().await.unwrap();
+    let path = std::env::temp_dir().join(format!("credentials-{}.json", uuid::Uuid::new_v4()));
+    let client = || ClobClient::new(mock.url(), OrderSigner::new(TEST_PRIVATE_KEY, 137).unwrap());
+    let rotation = Arc::new(CredentialRotation::new(&path));
+    let rotating = client().with_credential_rotation(rotation.clone());
+    let derived = || {
+        mock.requests()
+            .iter()
+            .filter(|r| *r == "GET /auth/derive-api-key")
+            .count()
+    };
+
+    // The first run derives the key and authenticates with it
+    rotating.load_api_credentials().await.unwrap();
+    let old = rotation.load().unwrap().unwrap();
+    assert_eq!(old.api_key, "mock-key");
+    rotating.get_balance().await.unwrap();
+
+    // Once revoked, the old key is refused
+    mock.revoke_api_key();
+    let stale = client();
+    stale.set_api_credentials(old);
+    assert!(stale.get_balance().await.is_err());
+
+    // The refused order is retried with the new key, and placed once
+    let response = rotating
+        .place_order("111", OrderSide::Buy, dec!(0.5), dec!(10), OrderType::GTC)
+        .await
+        .unwrap();
+    assert_eq!(response.status, OrderStatus::Filled);
+    assert_eq!(mock.orders().len(), 1);
+    let requests = mock.requests();
+    assert_eq!(requests.iter().filter(|r| *r == "POST /order").count(), 2);
+    assert_eq!(derived(), 2);
+    let new = rotation.load().unwrap().unwrap();
+    assert_eq!(new.api_key, "mock-key-rotated");
+    rotating.get_balance().await.unwrap();
+
+    // A restart authenticates with the saved key without deriving again
+    let restarted = client().with_credential_rotation(Arc::new(CredentialRotation::new(&path)));
+    restarted.load_api_credentials().await.unwrap();
+    restarted.get_balance().await.unwrap();
+    assert_eq!(derived(), 2);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,256 @@
+use crate::config::ErrorBudgetConfig;
+use crate::metrics;
+use crate::notifications::SlackNotifier;
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// What an outcome recorded against the budget was for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// A request to the CLOB API
+    ApiCall,
+    /// A copy trade, from sizing to the order's final state
+    Execution,
+}
+
+impl Operation {
+    fn label(&self) -> &'static str {
+        match self {
+            Operation::ApiCall => "api",
+            Operation::Execution => "execution",
+        }
+    }
+
+    fn plural(&self) -> &'static str {
+        match self {
+            Operation::ApiCall => "API calls",
+            Operation::Execution => "executions",
+        }
+    }
+}
+
+/// A change in whether the budget is holding back new copies
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transition {
+    Paused(String),
+    Resumed,
+}
+
+#[derive(Debug, Default)]
+struct BudgetState {
+    api_calls: VecDeque<(Instant, bool)>,
+    executions: VecDeque<(Instant, bool)>,
+    paused: Option<String>,
+}
+
+impl BudgetState {
+    fn outcomes(&mut self, operation: Operation) -> &mut VecDeque<(Instant, bool)> {
+        match operation {
+            Operation::ApiCall => &mut self.api_calls,
+            Operation::Execution => &mut self.executions,
+        }
+    }
+}
+
+/// Stops new copies while too many API calls or executions fail
+///
+/// Outcomes are kept for a rolling window. Once either failure rate exceeds
+/// `max_failure_rate` over at least `min_samples` outcomes, new copies are
+/// skipped until both rates fall to `resume_failure_rate` or the failures
+/// age out of the window. Unlike the pause file this is per instance and
+/// lifts itself; risk management and open orders are unaffected.
+pub struct ErrorBudget {
+    window: Duration,
+    min_samples: usize,
+    max_failure_rate: Decimal,
+    resume_failure_rate: Decimal,
+    check_interval: Duration,
+    state: Mutex<BudgetState>,
+}
+
+impl ErrorBudget {
+    /// None unless the budget is enabled
+    pub fn from_config(config: &ErrorBudgetConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            window: Duration::from_secs(config.window_seconds),
+            min_samples: config.min_samples,
+            max_failure_rate: config.max_failure_rate,
+            resume_failure_rate: config.resume_failure_rate,
+            check_interval: Duration::from_secs(config.check_interval_seconds),
+            state: Mutex::new(BudgetState::default()),
+        })
+    }
+
+    pub fn record(&self, operation: Operation, success: bool) {
+        self.record_at(operation, success, Instant::now());
+    }
+
+    fn record_at(&self, operation: Operation, success: bool, now: Instant) {
+        self.lock().outcomes(operation).push_back((now, success));
+    }
+
+    /// Why new copies are held back, if they are
+    pub fn pause_reason(&self) -> Option<String> {
+        self.lock().paused.clone()
+    }
+
+    /// Failure rate of `operation` over the window, or None with too few
+    /// outcomes to judge
+    fn failure_rate(
+        &self,
+        state: &mut BudgetState,
+        operation: Operation,
+        now: Instant,
+    ) -> Option<Decimal> {
+        let window = self.window;
+        let outcomes = state.outcomes(operation);
+        while outcomes
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > window)
+        {
+            outcomes.pop_front();
+        }
+        if outcomes.is_empty() || outcomes.len() < self.min_samples {
+            return None;
+        }
+        let failures = outcomes.iter().filter(|(_, success)| !success).count();
+        Some(Decimal::from(failures) / Decimal::from(outcomes.len()))
+    }
+
+    /// Drop outcomes older than the window and pause or resume on the rates
+    fn evaluate(&self, now: Instant) -> Option<Transition> {
+        let mut state = self.lock();
+        let mut over_budget = None;
+        let mut recovered = true;
+        for operation in [Operation::ApiCall, Operation::Execution] {
+            let rate = self.failure_rate(&mut state, operation, now);
+            metrics::global().gauge(
+                "polycopy_failure_rate",
+                "Share of API calls or executions that failed over the error budget window",
+                &[("operation", operation.label())],
+                rate.and_then(|r| r.try_into().ok()).unwrap_or(0.0),
+            );
+            let Some(rate) = rate else {
+                continue;
+            };
+            if rate > self.max_failure_rate && over_budget.is_none() {
+                over_budget = Some(format!(
+                    "{}% of {} failed in the last {}s",
+                    (rate * Decimal::ONE_HUNDRED).round_dp(0),
+                    operation.plural(),
+                    self.window.as_secs()
+                ));
+            }
+            if rate > self.resume_failure_rate {
+                recovered = false;
+            }
+        }
+
+        let transition = match (&state.paused, over_budget) {
+            (None, Some(reason)) => {
+                let reason = format!("error budget exceeded: {}", reason);
+                state.paused = Some(reason.clone());
+                Some(Transition::Paused(reason))
+            }
+            (Some(_), _) if recovered => {
+                state.paused = None;
+                Some(Transition::Resumed)
+            }
+            _ => None,
+        };
+        metrics::global().gauge(
+            "polycopy_error_budget_paused",
+            "1 while new copies are held back by the error budget",
+            &[],
+            if state.paused.is_some() { 1.0 } else { 0.0 },
+        );
+        transition
+    }
+
+    /// Check the rates every `check_interval_seconds`, alerting on each
+    /// pause and resume, forever
+    pub async fn run_periodic(self: Arc<Self>, notifier: Option<Arc<SlackNotifier>>) {
+        let mut interval = tokio::time::interval(self.check_interval);
+        loop {
+            interval.tick().await;
+            let text = match self.evaluate(Instant::now()) {
+                Some(Transition::Paused(reason)) => {
+                    warn!("Copying paused: {}", reason);
+                    format!(
+                        "Copying paused: {}. Risk management carries on; copying resumes once \
+                         the failure rate recovers",
+                        reason
+                    )
+                }
+                Some(Transition::Resumed) => {
+                    info!("Failure rate recovered; copying resumed");
+                    "Failure rate recovered; copying resumed".to_string()
+                }
+                None => continue,
+            };
+            if let Some(ref notifier) = notifier {
+                notifier.alert(text).await;
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BudgetState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_pauses_on_failures_and_resumes_as_they_age_out() {
+        let config = ErrorBudgetConfig {
+            enabled: true,
+            window_seconds: 60,
+            min_samples: 4,
+            max_failure_rate: dec!(0.5),
+            resume_failure_rate: dec!(0.25),
+            ..ErrorBudgetConfig::default()
+        };
+        let budget = ErrorBudget::from_config(&config).unwrap();
+        let start = Instant::now();
+
+        // Too few outcomes to judge
+        for _ in 0..3 {
+            budget.record_at(Operation::Execution, false, start);
+        }
+        assert_eq!(budget.evaluate(start), None);
+
+        budget.record_at(Operation::Execution, true, start);
+        budget.record_at(Operation::Execution, false, start);
+        let Some(Transition::Paused(reason)) = budget.evaluate(start) else {
+            panic!("expected a pause");
+        };
+        assert_eq!(
+            reason,
+            "error budget exceeded: 80% of executions failed in the last 60s"
+        );
+        assert_eq!(budget.pause_reason(), Some(reason));
+
+        // Healthy API calls don't lift it while executions still fail
+        let later = start + Duration::from_secs(30);
+        for _ in 0..10 {
+            budget.record_at(Operation::ApiCall, true, later);
+        }
+        assert_eq!(budget.evaluate(later), None);
+
+        // 1 in 4 failing is at the resume rate
+        let recovered = start + Duration::from_secs(61);
+        budget.record_at(Operation::Execution, false, recovered);
+        for _ in 0..3 {
+            budget.record_at(Operation::Execution, true, recovered);
+        }
+        assert_eq!(budget.evaluate(recovered), Some(Transition::Resumed));
+        assert_eq!(budget.pause_reason(), None);
+    }
+}
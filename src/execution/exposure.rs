@@ -0,0 +1,302 @@
+use crate::config::{Config, RiskConfig};
+use crate::errors::Result;
+use crate::execution::drawdown::DrawdownStore;
+use crate::execution::positions::Holding;
+use crate::execution::OrderExecutor;
+use crate::metrics::{self, Registry};
+use crate::monitoring::{MarketDirectory, PriceCache};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Balance, exposure and how far each risk limit is from firing
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Exposure {
+    pub balance: Decimal,
+    /// Cost of the open positions
+    pub deployed: Decimal,
+    /// Balance plus the open positions at current prices
+    pub equity: Decimal,
+    /// Value of each open position at its current price
+    pub markets: BTreeMap<String, Decimal>,
+    /// USDC left before each limit fires, by limit and market or event
+    pub headroom: Vec<(&'static str, String, Decimal)>,
+}
+
+impl Exposure {
+    /// Exposure of `holdings`, each priced by `price`, against the limits in
+    /// `risk`; `events` maps markets to the event they belong to and
+    /// `peak_equity` is the drawdown kill switch's peak
+    pub fn new(
+        balance: Decimal,
+        holdings: &HashMap<String, Holding>,
+        price: impl Fn(&str, &Holding) -> Decimal,
+        events: &HashMap<String, String>,
+        peak_equity: Option<Decimal>,
+        risk: &RiskConfig,
+    ) -> Self {
+        let open = holdings.iter().filter(|(_, h)| h.shares > Decimal::ZERO);
+        let mut exposure = Self {
+            balance,
+            ..Self::default()
+        };
+        let mut event_costs: BTreeMap<&str, Decimal> = BTreeMap::new();
+        for (market_id, holding) in open {
+            let value = holding.value_at(price(market_id, holding));
+            exposure.deployed += holding.cost;
+            exposure.markets.insert(market_id.clone(), value);
+            if let Some(event) = events.get(market_id) {
+                *event_costs.entry(event).or_default() += holding.cost;
+            }
+        }
+        exposure.equity = balance + exposure.markets.values().sum::<Decimal>();
+
+        if let Some(cap) = risk.max_market_exposure_usdc {
+            for (market_id, value) in &exposure.markets {
+                let headroom = ("market_exposure", market_id.clone(), cap - value);
+                exposure.headroom.push(headroom);
+            }
+        }
+        if let Some(cap) = risk.max_event_exposure_usdc {
+            for (event, cost) in event_costs {
+                let headroom = ("event_exposure", event.to_string(), cap - cost);
+                exposure.headroom.push(headroom);
+            }
+        }
+        if let (Some(limit), Some(peak)) = (risk.max_drawdown_percent, peak_equity) {
+            // Equity above the recorded peak is the new peak
+            let peak = peak.max(exposure.equity);
+            let floor = peak * (Decimal::ONE - limit);
+            let headroom = ("drawdown", String::new(), exposure.equity - floor);
+            exposure.headroom.push(headroom);
+        }
+        exposure
+    }
+
+    /// Set the gauges in `registry`, dropping markets no longer held
+    pub fn publish(&self, registry: &Registry) {
+        let usdc = |value: Decimal| value.to_f64().unwrap_or(0.0);
+        registry.gauge(
+            "polycopy_balance_usdc",
+            "USDC balance available for trading",
+            &[],
+            usdc(self.balance),
+        );
+        registry.gauge(
+            "polycopy_deployed_usdc",
+            "Cost of the open positions",
+            &[],
+            usdc(self.deployed),
+        );
+        registry.gauge(
+            "polycopy_equity_usdc",
+            "Balance plus the open positions at current prices",
+            &[],
+            usdc(self.equity),
+        );
+        registry.gauge(
+            "polycopy_open_positions",
+            "Markets with an open position",
+            &[],
+            self.markets.len() as f64,
+        );
+
+        let markets: Vec<([(&str, &str); 1], f64)> = self
+            .markets
+            .iter()
+            .map(|(market_id, value)| ([("market", market_id.as_str())], usdc(*value)))
+            .collect();
+        registry.replace_gauges(
+            "polycopy_market_exposure_usdc",
+            "Value of the open position in each market at its current price",
+            &markets
+                .iter()
+                .map(|(labels, value)| (&labels[..], *value))
+                .collect::<Vec<_>>(),
+        );
+
+        let headroom: Vec<([(&str, &str); 2], f64)> = self
+            .headroom
+            .iter()
+            .map(|(limit, scope, left)| {
+                ([("limit", *limit), ("scope", scope.as_str())], usdc(*left))
+            })
+            .collect();
+        registry.replace_gauges(
+            "polycopy_risk_headroom_usdc",
+            "USDC left before a risk limit fires, by limit and market or event",
+            &headroom
+                .iter()
+                .map(|(labels, value)| (&labels[..], *value))
+                .collect::<Vec<_>>(),
+        );
+    }
+}
+
+/// Keeps the exposure gauges on the metrics endpoint up to date
+pub struct ExposureGauges {
+    executor: Arc<OrderExecutor>,
+    prices: Arc<PriceCache>,
+    markets: Arc<MarketDirectory>,
+    risk: RiskConfig,
+    drawdown: DrawdownStore,
+    interval: Duration,
+}
+
+impl ExposureGauges {
+    pub fn from_config(
+        config: &Config,
+        executor: Arc<OrderExecutor>,
+        prices: Arc<PriceCache>,
+        markets: Arc<MarketDirectory>,
+    ) -> Self {
+        Self {
+            executor,
+            prices,
+            markets,
+            risk: config.risk.clone(),
+            drawdown: DrawdownStore::new(&config.risk.drawdown_state_file),
+            interval: Duration::from_secs(config.metrics.exposure_interval_seconds),
+        }
+    }
+
+    pub async fn run_periodic(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.update(metrics::global()).await {
+                warn!("Failed to update the exposure gauges: {}", e);
+            }
+        }
+    }
+
+    /// Publish the current exposure to `registry`; nothing is published when
+    /// the balance can't be fetched
+    pub async fn update(&self, registry: &Registry) -> Result<()> {
+        self.exposure().await?.publish(registry);
+        Ok(())
+    }
+
+    async fn exposure(&self) -> Result<Exposure> {
+        let balance = self.executor.get_balance().await?;
+        let holdings = self.executor.holdings().await?;
+
+        let mut events = HashMap::new();
+        if self.risk.max_event_exposure_usdc.is_some() {
+            let held = holdings.iter().filter(|(_, h)| h.shares > Decimal::ZERO);
+            for (market_id, _) in held {
+                match self.markets.event_of(market_id).await {
+                    Ok(Some(event)) => {
+                        events.insert(market_id.clone(), event.title.clone());
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Could not look up event of {}: {}", market_id, e),
+                }
+            }
+        }
+        let peak_equity = match self.risk.max_drawdown_percent {
+            Some(_) => self.drawdown.load()?.peak_equity,
+            None => None,
+        };
+
+        Ok(Exposure::new(
+            balance,
+            &holdings,
+            |market_id, holding| {
+                self.prices
+                    .price(market_id)
+                    .unwrap_or_else(|| holding.average_price())
+            },
+            &events,
+            peak_equity,
+            &self.risk,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_exposure_gauges_and_headroom() {
+        let holding = |shares: Decimal, cost: Decimal| Holding {
+            shares,
+            cost,
+            opened_at: None,
+        };
+        let holdings = HashMap::from([
+            ("yes-a".to_string(), holding(dec!(100), dec!(40))),
+            ("yes-b".to_string(), holding(dec!(50), dec!(30))),
+            ("sold".to_string(), holding(dec!(0), dec!(0))),
+        ]);
+        let events = HashMap::from([
+            ("yes-a".to_string(), "Election".to_string()),
+            ("yes-b".to_string(), "Election".to_string()),
+        ]);
+        let risk = RiskConfig {
+            max_market_exposure_usdc: Some(dec!(60)),
+            max_event_exposure_usdc: Some(dec!(100)),
+            max_drawdown_percent: Some(dec!(0.2)),
+            ..RiskConfig::default()
+        };
+        let exposure = Exposure::new(
+            dec!(900),
+            &holdings,
+            |market_id, _| {
+                if market_id == "yes-a" {
+                    dec!(0.5)
+                } else {
+                    dec!(0.7)
+                }
+            },
+            &events,
+            Some(dec!(1000)),
+            &risk,
+        );
+        assert_eq!(exposure.deployed, dec!(70));
+        assert_eq!(exposure.equity, dec!(985));
+        assert_eq!(
+            exposure.headroom,
+            vec![
+                ("market_exposure", "yes-a".to_string(), dec!(10)),
+                ("market_exposure", "yes-b".to_string(), dec!(25)),
+                ("event_exposure", "Election".to_string(), dec!(30)),
+                ("drawdown", String::new(), dec!(185)),
+            ]
+        );
+
+        let registry = Registry::default();
+        exposure.publish(&registry);
+        assert_eq!(registry.value("polycopy_open_positions", &[]), Some(2.0));
+        assert_eq!(
+            registry.value("polycopy_market_exposure_usdc", &[("market", "yes-b")]),
+            Some(35.0)
+        );
+        let event = [("limit", "event_exposure"), ("scope", "Election")];
+        assert_eq!(
+            registry.value("polycopy_risk_headroom_usdc", &event),
+            Some(30.0)
+        );
+
+        // A position sold out stops being reported
+        let sold = Exposure::new(
+            dec!(935),
+            &HashMap::from([("yes-a".to_string(), holding(dec!(100), dec!(40)))]),
+            |_, _| dec!(0.5),
+            &HashMap::new(),
+            None,
+            &RiskConfig::default(),
+        );
+        sold.publish(&registry);
+        assert_eq!(
+            registry.value("polycopy_market_exposure_usdc", &[("market", "yes-b")]),
+            None
+        );
+        assert_eq!(registry.value("polycopy_risk_headroom_usdc", &event), None);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::execution::clob_client::{ClobTrade, FillLeg};
+use crate::execution::lifecycle::{fee_amount, OrderRecord};
+use crate::models::OrderSide;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Difference in shares below which a recorded fill counts as matching
+const FILL_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+/// An order whose recorded fill disagrees with the exchange's trade history
+#[derive(Clone, Debug, PartialEq)]
+pub struct FillDiscrepancy {
+    /// Local order id
+    pub order_id: String,
+    pub exchange_order_id: String,
+    pub market_id: String,
+    /// Shares filled according to the order store
+    pub recorded: Decimal,
+    /// Shares matched according to the exchange
+    pub exchange: Decimal,
+}
+
+/// Legs of `trades` that belong to one of `orders`, oldest match first
+pub fn our_legs(trades: &[ClobTrade], orders: &[OrderRecord]) -> Vec<FillLeg> {
+    let ours: HashSet<&str> = orders
+        .iter()
+        .filter_map(|order| order.exchange_order_id.as_deref())
+        .collect();
+    let mut trades: Vec<&ClobTrade> = trades.iter().collect();
+    trades.sort_by_key(|trade| trade.matched_at());
+    trades
+        .into_iter()
+        .flat_map(|trade| trade.legs())
+        .filter(|leg| ours.contains(leg.order_id.as_str()))
+        .collect()
+}
+
+/// Orders whose recorded fill doesn't match the shares the exchange matched
+/// for them
+///
+/// Only orders the exchange acknowledged are checked, so `orders` should be
+/// limited to those placed within the window `trades` covers.
+pub fn discrepancies(trades: &[ClobTrade], orders: &[OrderRecord]) -> Vec<FillDiscrepancy> {
+    let mut matched: HashMap<String, Decimal> = HashMap::new();
+    for leg in our_legs(trades, orders) {
+        *matched.entry(leg.order_id).or_default() += leg.size;
+    }
+    orders
+        .iter()
+        .filter_map(|order| {
+            let exchange_order_id = order.exchange_order_id.clone()?;
+            let exchange = matched.get(&exchange_order_id).copied().unwrap_or_default();
+            ((order.filled_size - exchange).abs() >= FILL_TOLERANCE).then(|| FillDiscrepancy {
+                order_id: order.id.clone(),
+                exchange_order_id,
+                market_id: order.market_id.clone(),
+                recorded: order.filled_size,
+                exchange,
+            })
+        })
+        .collect()
+}
+
+/// Realized P&L per market from the exchange's record of our fills, net of
+/// fees
+///
+/// Buys add shares at their fill price; sells realize the difference to the
+/// average cost, as in [`holdings`](crate::execution::positions::holdings).
+pub fn realized_pnl(legs: &[FillLeg]) -> BTreeMap<String, Decimal> {
+    let mut held: HashMap<&str, (Decimal, Decimal)> = HashMap::new();
+    let mut pnl: BTreeMap<String, Decimal> = BTreeMap::new();
+    for leg in legs {
+        let (shares, cost) = held.entry(leg.asset_id.as_str()).or_default();
+        let realized = pnl.entry(leg.asset_id.clone()).or_default();
+        *realized -= fee_amount(leg.fee_rate_bps.to_u32().unwrap_or(0), leg.price, leg.size);
+        match leg.side {
+            OrderSide::Buy => {
+                *shares += leg.size;
+                *cost += leg.size * leg.price;
+            }
+            OrderSide::Sell => {
+                let sold = leg.size.min(*shares);
+                if sold.is_zero() {
+                    continue;
+                }
+                let average = *cost / *shares;
+                *realized += sold * (leg.price - average);
+                *cost -= sold * average;
+                *shares -= sold;
+            }
+        }
+    }
+    pnl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderType;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fills_checked_against_exchange() {
+        let body = r#"[
+            {"id":"t1","taker_order_id":"0xa","asset_id":"m1","side":"BUY","size":"100",
+             "price":"0.40","fee_rate_bps":"0","match_time":"1700000000",
+             "maker_orders":[{"order_id":"0xother","side":"SELL","matched_amount":"100","price":"0.40"}]},
+            {"id":"t2","taker_order_id":"0xother2","asset_id":"m1","side":"BUY","size":"40",
+             "price":"0.60","match_time":"1700000100",
+             "maker_orders":[{"order_id":"0xb","side":"SELL","matched_amount":"40","price":"0.60"}]}
+        ]"#;
+        let trades: Vec<ClobTrade> = serde_json::from_str(body).unwrap();
+        let order = |exchange_id: &str, side: OrderSide, filled: Decimal| {
+            let mut order =
+                OrderRecord::for_market("m1", side, dec!(0.5), dec!(100), OrderType::GTC);
+            order.exchange_order_id = Some(exchange_id.to_string());
+            order.filled_size = filled;
+            order
+        };
+        let orders = vec![
+            order("0xa", OrderSide::Buy, dec!(100)),
+            // Logged as filled in full, but only 40 shares matched as a maker
+            order("0xb", OrderSide::Sell, dec!(100)),
+        ];
+
+        let found = discrepancies(&trades, &orders);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].exchange_order_id, "0xb");
+        assert_eq!(found[0].exchange, dec!(40));
+
+        // Bought 100 at 0.40, sold 40 at 0.60
+        let legs = our_legs(&trades, &orders);
+        assert_eq!(legs.len(), 2);
+        assert_eq!(realized_pnl(&legs)["m1"], dec!(8));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::config::GasConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::storage::gas_log::{GasEntry, GasLog};
+use chrono::Utc;
+use ethers::providers::Middleware;
+use ethers::types::{Address, TransactionReceipt, TransactionRequest, U256};
+use ethers::utils::id;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Chainlink POL/USD price feed on Polygon (8 decimals)
+const POL_USD_FEED: &str = "0xAB594600376Ec9fD91F8e885dADF0CE036862dE0";
+
+/// How often a held-back transaction checks the gas price again
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Holds back transactions while gas is expensive and records what each one
+/// cost in the gas log
+pub struct GasTracker {
+    max_gas_price: Option<U256>,
+    max_deferral: Duration,
+    pol_price_usd: Option<Decimal>,
+    log: GasLog,
+}
+
+impl GasTracker {
+    pub fn new(config: &GasConfig, log_path: &str) -> Self {
+        Self {
+            max_gas_price: config
+                .max_gas_price_gwei
+                .and_then(|gwei| (gwei * Decimal::from(1_000_000_000u64)).trunc().to_u128())
+                .map(U256::from),
+            max_deferral: Duration::from_secs(config.max_deferral_minutes * 60),
+            pol_price_usd: config.pol_price_usd,
+            log: GasLog::new(log_path.to_string()),
+        }
+    }
+
+    /// Wait until the network gas price is at or below the cap; `urgent`
+    /// transactions go at any price
+    pub async fn wait_for_gas<M: Middleware>(&self, client: &M, urgent: bool) -> Result<()> {
+        let Some(cap) = self.max_gas_price.filter(|_| !urgent) else {
+            return Ok(());
+        };
+        let started = Instant::now();
+        loop {
+            let price = client
+                .get_gas_price()
+                .await
+                .map_err(|e| PolymarketError::BlockchainError(e.to_string()))?;
+            if price <= cap {
+                return Ok(());
+            }
+            if started.elapsed() >= self.max_deferral {
+                return Err(PolymarketError::BlockchainError(format!(
+                    "Gas price {} gwei still above the {} gwei cap after {} minutes; try later",
+                    gwei(price),
+                    gwei(cap),
+                    self.max_deferral.as_secs() / 60
+                )));
+            }
+            info!(
+                "Gas price {} gwei is above the {} gwei cap; waiting",
+                gwei(price),
+                gwei(cap)
+            );
+            tokio::time::sleep(RECHECK_INTERVAL).await;
+        }
+    }
+
+    /// Record the gas `receipt` shows was paid for `operation`
+    pub async fn record<M: Middleware>(
+        &self,
+        client: &M,
+        operation: &str,
+        receipt: &TransactionReceipt,
+    ) -> Result<GasEntry> {
+        let pol_price = match self.pol_price_usd {
+            Some(price) => Some(price),
+            None => pol_price(client).await,
+        };
+        let entry = entry(operation, receipt, pol_price);
+        info!(
+            "{} used {} gas: {} POL{}",
+            operation,
+            entry.gas_used,
+            entry.cost_pol.round_dp(6),
+            entry
+                .cost_usd
+                .map(|usd| format!(" ({} USD)", usd.round_dp(4)))
+                .unwrap_or_default()
+        );
+        self.log.append(&entry)?;
+        Ok(entry)
+    }
+}
+
+fn entry(operation: &str, receipt: &TransactionReceipt, pol_price: Option<Decimal>) -> GasEntry {
+    let gas_used = receipt.gas_used.unwrap_or_default();
+    let price = receipt.effective_gas_price.unwrap_or_default();
+    let cost_pol = wei_to(gas_used.saturating_mul(price), 18);
+    GasEntry {
+        timestamp: Utc::now(),
+        operation: operation.to_string(),
+        tx_hash: format!("{:?}", receipt.transaction_hash),
+        gas_used: gas_used.low_u64(),
+        gas_price_gwei: gwei(price),
+        cost_pol,
+        cost_usd: pol_price.map(|usd| (cost_pol * usd).round_dp(6)),
+        reverted: receipt.status.unwrap_or_default().is_zero(),
+    }
+}
+
+fn gwei(wei: U256) -> Decimal {
+    wei_to(wei, 9)
+}
+
+/// `wei` scaled down by 10^`scale`
+fn wei_to(wei: U256, scale: u32) -> Decimal {
+    let wei = wei.min(U256::from(i128::MAX as u128)).as_u128() as i128;
+    Decimal::try_from_i128_with_scale(wei, scale)
+        .unwrap_or_default()
+        .normalize()
+}
+
+/// POL price from the Chainlink feed, or None if it can't be read
+async fn pol_price<M: Middleware>(client: &M) -> Option<Decimal> {
+    let feed: Address = POL_USD_FEED.parse().expect("valid feed address");
+    let call = TransactionRequest::new()
+        .to(feed)
+        .data(id("latestRoundData()").to_vec());
+    match client.call(&call.into(), None).await {
+        Ok(output) => feed_answer(&output),
+        Err(e) => {
+            warn!(
+                "Failed to read the POL price; gas is recorded in POL only: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// The price in a `latestRoundData()` result: (roundId, answer, ...)
+fn feed_answer(output: &[u8]) -> Option<Decimal> {
+    let answer = U256::from_big_endian(output.get(32..64)?);
+    // A negative int256 has the top bit set
+    if answer.bit(255) || answer.is_zero() {
+        return None;
+    }
+    Some(wei_to(answer, 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::gas_log::GasSpent;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_gas_is_costed_in_pol_and_usd() {
+        let receipt = TransactionReceipt {
+            gas_used: Some(U256::from(46_000)),
+            effective_gas_price: Some(U256::from(150_000_000_000u64)),
+            status: Some(1.into()),
+            ..Default::default()
+        };
+        let approval = entry("approve USDC", &receipt, Some(dec!(0.5)));
+        assert_eq!(approval.gas_price_gwei, dec!(150));
+        assert_eq!(approval.cost_pol, dec!(0.0069));
+        assert_eq!(approval.cost_usd, Some(dec!(0.00345)));
+        assert!(!approval.reverted);
+
+        let reverted = entry(
+            "approve outcome tokens",
+            &TransactionReceipt {
+                status: Some(0.into()),
+                ..receipt
+            },
+            None,
+        );
+        assert!(reverted.reverted);
+        assert_eq!(reverted.cost_usd, None);
+        let spent = GasSpent::of(&[approval, reverted]);
+        assert_eq!(
+            (spent.transactions, spent.pol, spent.usd),
+            (2, dec!(0.0138), dec!(0.00345))
+        );
+
+        // roundId, then an answer of $0.41234567
+        let mut output = vec![0u8; 160];
+        output[56..64].copy_from_slice(&41_234_567u64.to_be_bytes());
+        assert_eq!(feed_answer(&output), Some(dec!(0.41234567)));
+        assert_eq!(feed_answer(&output[..40]), None);
+
+        let config = GasConfig {
+            max_gas_price_gwei: Some(dec!(200.5)),
+            ..GasConfig::default()
+        };
+        let tracker = GasTracker::new(&config, "gas.jsonl");
+        assert_eq!(tracker.max_gas_price, Some(U256::from(200_500_000_000u64)));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::errors::{PolymarketError, Result};
+use crate::models::{ExecutedTrade, OrderSide, OrderStatus, OrderType, Position, Trade};
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Where an order is in its life, from our side of the exchange
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderState {
+    /// Built locally, not yet sent
+    Created,
+    /// Sent to the exchange; the response hasn't arrived
+    Submitted,
+    /// Resting on the book with nothing filled
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    /// Rejected or never reached the exchange
+    Failed,
+}
+
+impl OrderState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderState::Created => "created",
+            OrderState::Submitted => "submitted",
+            OrderState::Open => "open",
+            OrderState::PartiallyFilled => "partially_filled",
+            OrderState::Filled => "filled",
+            OrderState::Cancelled => "cancelled",
+            OrderState::Failed => "failed",
+        }
+    }
+
+    /// No further transitions are possible
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Failed
+        )
+    }
+
+    pub fn can_transition_to(&self, next: OrderState) -> bool {
+        use OrderState::*;
+        matches!(
+            (self, next),
+            (Created, Submitted | Failed)
+                | (
+                    Submitted,
+                    Open | PartiallyFilled | Filled | Cancelled | Failed
+                )
+                | (Open, PartiallyFilled | Filled | Cancelled | Failed)
+                | (PartiallyFilled, Filled | Cancelled)
+        )
+    }
+}
+
+impl From<&OrderStatus> for OrderState {
+    fn from(status: &OrderStatus) -> Self {
+        match status {
+            OrderStatus::Open => OrderState::Open,
+            OrderStatus::PartiallyFilled => OrderState::PartiallyFilled,
+            OrderStatus::Filled => OrderState::Filled,
+            OrderStatus::Cancelled => OrderState::Cancelled,
+        }
+    }
+}
+
+impl std::fmt::Display for OrderState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Whether a fill the API reported has been seen settling on-chain
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Settlement {
+    /// Filled according to the API; waiting for the exchange's OrderFilled events
+    Pending,
+    Verified,
+    /// No settlement was found on-chain before the check gave up
+    Unverified,
+}
+
+/// One recorded state change
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderTransition {
+    pub state: OrderState,
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// A copy order and its full state history
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderRecord {
+    /// Local id, assigned before the exchange knows about the order
+    pub id: String,
+    /// Detected trade this order copies; empty for the bot's own orders
+    /// (e.g. rebalancing trims)
+    pub trade_id: String,
+    /// Trader whose trade this order copies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trader: Option<Address>,
+    /// Labels of the copied trade, for P&L by label
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    pub market_id: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Decimal,
+    /// Shares, as placed on the exchange
+    pub size: Decimal,
+    #[serde(default)]
+    pub exchange_order_id: Option<String>,
+    pub state: OrderState,
+    /// Shares matched so far
+    #[serde(default)]
+    pub filled_size: Decimal,
+    #[serde(default)]
+    pub fill_price: Option<Decimal>,
+    /// Market fee rate the order was placed with
+    #[serde(default)]
+    pub fee_rate_bps: u32,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Set on orders whose fills are checked on-chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settlement: Option<Settlement>,
+    /// USDC the copy was scaled down by for lack of free balance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortfall_usdc: Option<Decimal>,
+    /// Price the copied trader filled at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trader_price: Option<Decimal>,
+    /// The market's midpoint when the copied trade was detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection_midpoint: Option<Decimal>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub transitions: Vec<OrderTransition>,
+}
+
+impl OrderRecord {
+    pub fn new(trade: &Trade, size: Decimal, order_type: OrderType) -> Self {
+        Self {
+            trade_id: trade.id.clone(),
+            trader: Some(trade.trader),
+            labels: trade.labels.clone(),
+            trader_price: Some(trade.price),
+            detection_midpoint: trade.detection_midpoint,
+            ..Self::for_market(
+                &trade.market_id,
+                trade.side.clone(),
+                trade.price,
+                size,
+                order_type,
+            )
+        }
+    }
+
+    /// An order that doesn't copy a detected trade
+    pub fn for_market(
+        market_id: &str,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+        order_type: OrderType,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            trade_id: String::new(),
+            trader: None,
+            labels: Vec::new(),
+            market_id: market_id.to_string(),
+            side,
+            order_type,
+            price,
+            size,
+            exchange_order_id: None,
+            state: OrderState::Created,
+            filled_size: Decimal::ZERO,
+            fill_price: None,
+            fee_rate_bps: 0,
+            error: None,
+            settlement: None,
+            shortfall_usdc: None,
+            trader_price: None,
+            detection_midpoint: None,
+            created_at: now,
+            updated_at: now,
+            transitions: vec![OrderTransition {
+                state: OrderState::Created,
+                at: now,
+                detail: None,
+            }],
+        }
+    }
+
+    /// Move to `next`, returning whether the state changed
+    ///
+    /// Re-entering the current state is a no-op; anything the state machine
+    /// doesn't allow is an error and leaves the record untouched.
+    pub fn advance(&mut self, next: OrderState, detail: Option<String>) -> Result<bool> {
+        if next == self.state {
+            return Ok(false);
+        }
+        if !self.state.can_transition_to(next) {
+            return Err(PolymarketError::ExecutionError(format!(
+                "Order {} cannot go from {} to {}",
+                self.id, self.state, next
+            )));
+        }
+
+        let now = Utc::now();
+        self.state = next;
+        self.updated_at = now;
+        self.transitions.push(OrderTransition {
+            state: next,
+            at: now,
+            detail,
+        });
+        Ok(true)
+    }
+
+    /// Fee paid on what has filled so far
+    pub fn fee(&self) -> Decimal {
+        fee_amount(
+            self.fee_rate_bps,
+            self.fill_price.unwrap_or(self.price),
+            self.filled_size,
+        )
+    }
+
+    /// The fill as a copy-trade result for `trade`, if anything filled
+    pub fn executed_trade(&self, trade: &Trade) -> Option<ExecutedTrade> {
+        if self.filled_size <= Decimal::ZERO {
+            return None;
+        }
+        let actual_price = self.fill_price.unwrap_or(self.price);
+        Some(ExecutedTrade {
+            position: Position {
+                market_id: self.market_id.clone(),
+                entry_price: actual_price,
+                size: self.filled_size,
+                side: self.side.clone(),
+                timestamp: self.updated_at,
+                pnl: Decimal::ZERO,
+                trader: Some(trade.trader),
+            },
+            actual_price,
+            slippage: (actual_price - trade.price).abs(),
+            fee: self.fee(),
+            fill_ratio: self.filled_size / self.size,
+        })
+    }
+
+    /// Whether the fill counts towards our positions: fills checked on-chain
+    /// count once their settlement has been seen
+    pub fn settled(&self) -> bool {
+        matches!(self.settlement, None | Some(Settlement::Verified))
+    }
+
+    /// Mark the order failed with `error`
+    pub fn fail(&mut self, error: &str) -> Result<bool> {
+        let changed = self.advance(OrderState::Failed, Some(error.to_string()))?;
+        if changed {
+            self.error = Some(error.to_string());
+        }
+        Ok(changed)
+    }
+}
+
+/// Fee in USDC for trading `shares` at `price`
+///
+/// Polymarket charges the base rate on the cheaper side of the outcome
+/// pair, `rate * min(price, 1 - price) * shares`, so fees shrink towards
+/// the extremes.
+pub fn fee_amount(fee_rate_bps: u32, price: Decimal, shares: Decimal) -> Decimal {
+    let rate = Decimal::from(fee_rate_bps) / Decimal::from(10_000);
+    rate * price.min(Decimal::ONE - price) * shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn record() -> OrderRecord {
+        let trade = Trade::sample().with_id("t1");
+        OrderRecord::new(&trade, dec!(10), OrderType::GTC)
+    }
+
+    #[test]
+    fn test_order_lifecycle_transitions() {
+        let mut order = record();
+        assert!(order.advance(OrderState::Submitted, None).unwrap());
+        assert!(order.advance(OrderState::Open, None).unwrap());
+        assert!(!order.advance(OrderState::Open, None).unwrap());
+        assert!(order.advance(OrderState::PartiallyFilled, None).unwrap());
+        assert!(order.advance(OrderState::Open, None).is_err());
+        assert!(order
+            .advance(OrderState::Cancelled, Some("timed out".to_string()))
+            .unwrap());
+        assert!(order.state.is_terminal());
+        assert!(order.advance(OrderState::Filled, None).is_err());
+
+        let states: Vec<OrderState> = order.transitions.iter().map(|t| t.state).collect();
+        assert_eq!(
+            states,
+            vec![
+                OrderState::Created,
+                OrderState::Submitted,
+                OrderState::Open,
+                OrderState::PartiallyFilled,
+                OrderState::Cancelled
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_order_keeps_error() {
+        let mut order = record();
+        order.advance(OrderState::Submitted, None).unwrap();
+        order.fail("API error: rejected").unwrap();
+        assert_eq!(order.state, OrderState::Failed);
+        assert!(!order.fail("again").unwrap());
+        assert_eq!(order.error.as_deref(), Some("API error: rejected"));
+        assert_eq!(order.transitions.len(), 3);
+    }
+
+    #[test]
+    fn test_executed_trade_from_recovered_fill() {
+        let mut order = record();
+        let trade = Trade::sample().with_id(&order.trade_id);
+        assert!(order.executed_trade(&trade).is_none());
+
+        order.advance(OrderState::Submitted, None).unwrap();
+        order.advance(OrderState::Filled, None).unwrap();
+        order.filled_size = dec!(4);
+        order.fill_price = Some(dec!(0.52));
+        order.fee_rate_bps = 200;
+        let executed = order.executed_trade(&trade).unwrap();
+        assert_eq!(executed.position.size, dec!(4));
+        assert_eq!(executed.slippage, dec!(0.02));
+        assert_eq!(executed.fill_ratio, dec!(0.4));
+        // 2% of the cheaper side (0.48) on 4 shares
+        assert_eq!(executed.fee, dec!(0.0384));
+    }
+}
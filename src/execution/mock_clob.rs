@@ -0,0 +1,635 @@
+// The `mock-clob` command serves the defaults; only tests configure the mock
+#![cfg_attr(not(test), allow(dead_code))]
+
+use crate::errors::Result;
+use crate::execution::clob_client::l2_signature;
+use crate::models::{OrderRequest, OrderResponse, OrderSide, OrderStatus};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Best bid and ask of every book
+const BOOK: (Decimal, Decimal) = (
+    Decimal::from_parts(49, 0, 0, false, 2),
+    Decimal::from_parts(51, 0, 0, false, 2),
+);
+
+/// API secret and passphrase of every key the mock issues
+const API_SECRET: &str = "bW9jay1zZWNyZXQ=";
+const API_PASSPHRASE: &str = "mock-passphrase";
+
+/// What the mock exchange does with an order it accepts
+#[derive(Clone, Debug, PartialEq)]
+pub enum FillBehavior {
+    /// Filled in full as soon as it is placed
+    Fill,
+    /// Open until its status has been polled this many times, then filled
+    FillAfterPolls(u32),
+    /// Half filled when placed, the rest resting until cancelled
+    PartialFill,
+    /// Open until cancelled
+    Rest,
+    /// Rejected with this error
+    Reject(String),
+}
+
+/// A failure injected into the mock's responses
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fault {
+    /// Handle the request, but answer only after this long, so the client
+    /// times out not knowing whether it took effect
+    Hang(Duration),
+    /// Answer 500 without handling the request
+    ServerError,
+    /// Handle the request and answer 200 with a truncated body
+    Malformed,
+    /// Answer 429 with a `Retry-After` of this many seconds, without
+    /// handling the request
+    RateLimited(u64),
+}
+
+/// An order the mock exchange holds
+#[derive(Clone, Debug)]
+pub struct MockOrder {
+    pub id: String,
+    pub market_id: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub status: OrderStatus,
+    /// Shares matched so far
+    pub size_matched: Decimal,
+    /// Unix seconds
+    pub created_at: i64,
+    polls: u32,
+    behavior: FillBehavior,
+}
+
+#[derive(Debug)]
+struct State {
+    fill: FillBehavior,
+    market_fills: HashMap<String, FillBehavior>,
+    latency: Duration,
+    /// Faults with the `METHOD /path` prefix they apply to and how many more
+    /// requests they hit
+    faults: Vec<(String, Fault, u32)>,
+    tick_size: Decimal,
+    /// Our USDC balance
+    balance: Decimal,
+    /// Data API positions of each wallet, by lowercase address
+    positions: HashMap<String, Value>,
+    /// Data API trade lists of each wallet, by lowercase address
+    trader_trades: HashMap<String, Value>,
+    /// The API key issued to the wallet; requests sending another are refused
+    api_key: String,
+    orders: Vec<MockOrder>,
+    trades: Vec<Value>,
+    /// `METHOD /path` of every request, in arrival order
+    requests: Vec<String>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            fill: FillBehavior::Fill,
+            market_fills: HashMap::new(),
+            latency: Duration::ZERO,
+            faults: Vec::new(),
+            tick_size: Decimal::new(1, 2),
+            balance: Decimal::new(10000, 0),
+            positions: HashMap::new(),
+            trader_trades: HashMap::new(),
+            api_key: "mock-key".to_string(),
+            orders: Vec::new(),
+            trades: Vec::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+/// A CLOB API on a local port, for running the executor end to end
+///
+/// Serves the endpoints the client uses: placing, querying and cancelling
+/// orders, listing our open ones, markets, fee rates, books, midpoints,
+/// server time, API keys, our balance, trade history and trader positions. Orders fill
+/// according to a [`FillBehavior`], set for every market or per market, and
+/// every response can be delayed to simulate latency. State is kept in memory
+/// and lost when it is dropped.
+pub struct MockClob {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl MockClob {
+    /// Serve on a free local port
+    pub async fn start() -> Result<Self> {
+        Self::bind("127.0.0.1:0").await
+    }
+
+    /// Serve on `listen_addr`
+    pub async fn bind(listen_addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(listen_addr).await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let task = tokio::spawn(accept(listener, state.clone()));
+        Ok(Self {
+            address,
+            state,
+            task,
+        })
+    }
+
+    /// Base URL to point a client at
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Serve until the process exits
+    #[cfg(feature = "mock-clob")]
+    pub async fn run(mut self) -> Result<()> {
+        let _ = (&mut self.task).await;
+        Ok(())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// How orders in every market without their own behavior fill
+    pub fn set_fill(&self, behavior: FillBehavior) {
+        self.state().fill = behavior;
+    }
+
+    /// How orders in `market_id` fill
+    pub fn set_market_fill(&self, market_id: &str, behavior: FillBehavior) {
+        self.state()
+            .market_fills
+            .insert(market_id.to_string(), behavior);
+    }
+
+    /// Delay every response by `latency`
+    pub fn set_latency(&self, latency: Duration) {
+        self.state().latency = latency;
+    }
+
+    /// Inject `fault` into the next `times` requests whose `METHOD /path`
+    /// starts with `route`, e.g. `"POST /order"` or `"GET /positions"`
+    pub fn inject(&self, route: &str, fault: Fault, times: u32) {
+        self.state().faults.push((route.to_string(), fault, times));
+    }
+
+    /// USDC balance reported for our wallet; 10000 by default
+    pub fn set_balance(&self, balance: Decimal) {
+        self.state().balance = balance;
+    }
+
+    /// Data API positions listed for `user`; none by default
+    pub fn set_positions(&self, user: &str, positions: Value) {
+        self.state()
+            .positions
+            .insert(user.to_lowercase(), positions);
+    }
+
+    /// Data API trades listed for `user`, newest first; none by default
+    pub fn set_trader_trades(&self, user: &str, trades: Value) {
+        self.state()
+            .trader_trades
+            .insert(user.to_lowercase(), trades);
+    }
+
+    /// Revoke the wallet's API key: requests sending it are refused with a
+    /// 401, and deriving the credentials issues a new one
+    pub fn revoke_api_key(&self) {
+        let mut state = self.state();
+        state.api_key = format!("{}-rotated", state.api_key);
+    }
+
+    /// Every order received, in arrival order
+    pub fn orders(&self) -> Vec<MockOrder> {
+        self.state().orders.clone()
+    }
+
+    /// `METHOD /path` of every request received
+    pub fn requests(&self) -> Vec<String> {
+        self.state().requests.clone()
+    }
+}
+
+impl Drop for MockClob {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn accept(listener: TcpListener, state: Arc<Mutex<State>>) {
+    loop {
+        let Ok((stream, peer)) = listener.accept().await else {
+            continue;
+        };
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve(stream, state).await {
+                debug!("Mock CLOB request from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Answer one request; every response closes the connection
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<State>>) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buffer.len() < header_end + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body = String::from_utf8_lossy(&buffer[header_end..]).to_string();
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or("/").to_string();
+    let headers: HashMap<String, String> = head
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let reply = state
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .respond(&method, &target, &headers, &body);
+    if !reply.delay.is_zero() {
+        tokio::time::sleep(reply.delay).await;
+    }
+
+    let reason = match reply.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    };
+    let retry_after = reply
+        .retry_after
+        .map(|seconds| format!("Retry-After: {}\r\n", seconds))
+        .unwrap_or_default();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        reply.status,
+        reason,
+        reply.body.len(),
+        retry_after,
+        reply.body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+struct Reply {
+    status: u16,
+    body: String,
+    delay: Duration,
+    retry_after: Option<u64>,
+}
+
+impl State {
+    /// The reply to a request, with any fault injected for its route
+    fn respond(
+        &mut self,
+        method: &str,
+        target: &str,
+        headers: &HashMap<String, String>,
+        body: &str,
+    ) -> Reply {
+        let path = target.split('?').next().unwrap_or_default();
+        let route = format!("{} {}", method, path);
+        self.requests.push(route.clone());
+        if !self.authorized(method, path, headers, body) {
+            return Reply {
+                status: 401,
+                body: json!({ "error": "Unauthorized/Invalid api key" }).to_string(),
+                delay: self.latency,
+                retry_after: None,
+            };
+        }
+        let fault = self
+            .faults
+            .iter_mut()
+            .find(|(prefix, _, times)| *times > 0 && route.starts_with(prefix.as_str()))
+            .map(|(_, fault, times)| {
+                *times -= 1;
+                fault.clone()
+            });
+
+        let latency = self.latency;
+        let reply = |status, body: String| Reply {
+            status,
+            body,
+            delay: latency,
+            retry_after: None,
+        };
+        match fault {
+            Some(Fault::ServerError) => {
+                reply(500, json!({ "error": "internal error" }).to_string())
+            }
+            Some(Fault::RateLimited(seconds)) => Reply {
+                retry_after: Some(seconds),
+                ..reply(429, json!({ "error": "too many requests" }).to_string())
+            },
+            Some(Fault::Hang(delay)) => {
+                let (status, body) = self.handle(method, target, body);
+                Reply {
+                    delay: latency + delay,
+                    ..reply(status, body)
+                }
+            }
+            Some(Fault::Malformed) => {
+                let (status, mut body) = self.handle(method, target, body);
+                body.truncate(body.len() / 2);
+                reply(status, body)
+            }
+            None => {
+                let (status, body) = self.handle(method, target, body);
+                reply(status, body)
+            }
+        }
+    }
+
+    /// Whether a request sending an API key sends the one issued, signed
+    /// with its secret; requests signed by the wallet alone are let through
+    fn authorized(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &HashMap<String, String>,
+        body: &str,
+    ) -> bool {
+        let Some(api_key) = headers.get("poly_api_key") else {
+            return true;
+        };
+        let header = |name: &str| headers.get(name).map(String::as_str).unwrap_or_default();
+        let expected = header("poly_timestamp").parse().ok().and_then(|timestamp| {
+            l2_signature(API_SECRET, timestamp, method, path, body.as_bytes()).ok()
+        });
+        *api_key == self.api_key
+            && header("poly_passphrase") == API_PASSPHRASE
+            && expected.as_deref() == Some(header("poly_signature"))
+    }
+
+    /// Status code and body for a request
+    fn handle(&mut self, method: &str, target: &str, body: &str) -> (u16, String) {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query: HashMap<&str, &str> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .collect();
+
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", [""]) => ok(json!("OK")),
+            ("GET", ["time"]) => (200, Utc::now().timestamp().to_string()),
+            ("GET", ["markets", market_id]) => ok(json!({
+                "market_id": market_id,
+                "tick_size": self.tick_size.to_string(),
+                "min_size": "1",
+                "max_size": "1000000",
+            })),
+            ("GET", ["fee-rate"]) => ok(json!({ "base_fee": 0 })),
+            ("GET", ["balance-allowance"]) => {
+                let units = (self.balance * Decimal::from(1_000_000)).trunc();
+                ok(json!({ "balance": units.to_string(), "allowance": units.to_string() }))
+            }
+            ("GET", ["book"]) => {
+                let (bid, ask) = BOOK;
+                ok(json!({
+                    "bids": [{ "price": bid.to_string(), "size": "1000" }],
+                    "asks": [{ "price": ask.to_string(), "size": "1000" }],
+                }))
+            }
+            ("GET", ["midpoint"]) => {
+                let (bid, ask) = BOOK;
+                ok(json!({ "mid": ((bid + ask) / Decimal::TWO).to_string() }))
+            }
+            ("GET", ["positions"]) => {
+                let user = query.get("user").copied().unwrap_or_default();
+                let positions = self.positions.get(&user.to_lowercase());
+                ok(positions.cloned().unwrap_or_else(|| json!([])))
+            }
+            ("GET", ["trades"]) => {
+                let user = query.get("user").copied().unwrap_or_default();
+                let trades = self.trader_trades.get(&user.to_lowercase());
+                ok(trades.cloned().unwrap_or_else(|| json!([])))
+            }
+            ("POST", ["order"]) => match serde_json::from_str::<OrderRequest>(body) {
+                Ok(request) => match self.place(request) {
+                    OrderResponse {
+                        error: Some(error), ..
+                    } => (400, json!({ "error": error }).to_string()),
+                    response => ok(json!(response)),
+                },
+                Err(e) => (400, json!({ "error": e.to_string() }).to_string()),
+            },
+            ("POST", ["orders"]) => match serde_json::from_str::<Vec<OrderRequest>>(body) {
+                Ok(requests) => {
+                    let responses: Vec<OrderResponse> =
+                        requests.into_iter().map(|r| self.place(r)).collect();
+                    ok(json!(responses))
+                }
+                Err(e) => (400, json!({ "error": e.to_string() }).to_string()),
+            },
+            ("GET", ["order", order_id]) => match self.poll(order_id) {
+                Some(response) => ok(json!(response)),
+                None => (404, json!({ "error": "order not found" }).to_string()),
+            },
+            ("DELETE", ["order", order_id]) => {
+                if self.cancel(|order| order.id == *order_id).is_empty() {
+                    (400, json!({ "error": "order not open" }).to_string())
+                } else {
+                    ok(json!({ "canceled": [order_id] }))
+                }
+            }
+            ("DELETE", ["cancel-all"]) => {
+                let canceled = self.cancel(|_| true);
+                ok(json!({ "canceled": canceled, "not_canceled": {} }))
+            }
+            ("DELETE", ["cancel-market-orders"]) => {
+                let asset_id = serde_json::from_str::<Value>(body)
+                    .ok()
+                    .and_then(|body| body["asset_id"].as_str().map(str::to_string))
+                    .unwrap_or_default();
+                let canceled = self.cancel(|order| order.market_id == asset_id);
+                ok(json!({ "canceled": canceled, "not_canceled": {} }))
+            }
+            ("GET", ["data", "orders"]) => {
+                let open: Vec<Value> = self
+                    .orders
+                    .iter()
+                    .filter(|order| {
+                        matches!(
+                            order.status,
+                            OrderStatus::Open | OrderStatus::PartiallyFilled
+                        )
+                    })
+                    .map(|order| {
+                        json!({
+                            "id": order.id,
+                            "asset_id": order.market_id,
+                            "side": order.side,
+                            "price": order.price.to_string(),
+                            "original_size": order.size.to_string(),
+                            "size_matched": order.size_matched.to_string(),
+                            "created_at": order.created_at,
+                        })
+                    })
+                    .collect();
+                ok(json!({ "data": open, "next_cursor": "LTE=" }))
+            }
+            ("GET", ["data", "trades"]) => {
+                ok(json!({ "data": self.trades, "next_cursor": "LTE=" }))
+            }
+            ("GET", ["auth", "derive-api-key"]) | ("POST", ["auth", "api-key"]) => ok(json!({
+                "apiKey": self.api_key,
+                "secret": API_SECRET,
+                "passphrase": API_PASSPHRASE,
+            })),
+            _ => (404, json!({ "error": "not found" }).to_string()),
+        }
+    }
+
+    fn place(&mut self, request: OrderRequest) -> OrderResponse {
+        let id = format!("0x{:064x}", self.orders.len() + 1);
+        let behavior = self
+            .market_fills
+            .get(&request.order.market_id)
+            .unwrap_or(&self.fill)
+            .clone();
+        if let FillBehavior::Reject(ref error) = behavior {
+            return OrderResponse {
+                order_id: String::new(),
+                status: OrderStatus::Cancelled,
+                size_matched: None,
+                error: Some(error.clone()),
+            };
+        }
+
+        let mut order = MockOrder {
+            id: id.clone(),
+            market_id: request.order.market_id,
+            side: request.order.side,
+            price: request.order.price_decimal,
+            size: request.order.quantity,
+            status: OrderStatus::Open,
+            size_matched: Decimal::ZERO,
+            created_at: Utc::now().timestamp(),
+            polls: 0,
+            behavior: behavior.clone(),
+        };
+        let size = order.size;
+        match behavior {
+            FillBehavior::Fill => self.fill(&mut order, size),
+            FillBehavior::PartialFill => self.fill(&mut order, size / Decimal::TWO),
+            _ => {}
+        }
+        let response = OrderResponse {
+            order_id: id,
+            status: order.status.clone(),
+            size_matched: Some(order.size_matched),
+            error: None,
+        };
+        self.orders.push(order);
+        response
+    }
+
+    /// The order's status, advancing orders that fill after being polled
+    fn poll(&mut self, order_id: &str) -> Option<OrderResponse> {
+        let index = self.orders.iter().position(|o| o.id == order_id)?;
+        let mut order = self.orders[index].clone();
+        order.polls += 1;
+        if let FillBehavior::FillAfterPolls(polls) = order.behavior {
+            if order.status == OrderStatus::Open && order.polls >= polls {
+                let size = order.size - order.size_matched;
+                self.fill(&mut order, size);
+            }
+        }
+        let response = OrderResponse {
+            order_id: order.id.clone(),
+            status: order.status.clone(),
+            size_matched: Some(order.size_matched),
+            error: None,
+        };
+        self.orders[index] = order;
+        Some(response)
+    }
+
+    /// Match `size` of `order` and record the trade
+    fn fill(&mut self, order: &mut MockOrder, size: Decimal) {
+        order.size_matched += size;
+        order.status = if order.size_matched >= order.size {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        self.trades.push(json!({
+            "id": format!("trade-{}", self.trades.len() + 1),
+            "taker_order_id": order.id,
+            "asset_id": order.market_id,
+            "side": order.side,
+            "size": size.to_string(),
+            "price": order.price.to_string(),
+            "fee_rate_bps": "0",
+            "match_time": Utc::now().timestamp().to_string(),
+            "maker_orders": [],
+        }));
+    }
+
+    /// Cancel the open orders matching `filter`, returning their ids
+    fn cancel(&mut self, filter: impl Fn(&MockOrder) -> bool) -> Vec<String> {
+        self.orders
+            .iter_mut()
+            .filter(|order| {
+                matches!(
+                    order.status,
+                    OrderStatus::Open | OrderStatus::PartiallyFilled
+                ) && filter(order)
+            })
+            .map(|order| {
+                order.status = OrderStatus::Cancelled;
+                order.id.clone()
+            })
+            .collect()
+    }
+}
+
+fn ok(body: Value) -> (u16, String) {
+    (200, body.to_string())
+}
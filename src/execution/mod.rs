@@ -1,9 +1,41 @@
+pub mod approvals;
+pub mod attribution;
+pub mod batcher;
+pub mod blacklist;
 pub mod clob_client;
+pub mod credentials;
+pub mod drawdown;
+pub mod duplicates;
+#[cfg(test)]
+mod end_to_end;
+pub mod error_budget;
+pub mod exposure;
+pub mod fills;
+pub mod gas;
+pub mod lifecycle;
+#[cfg(any(test, feature = "mock-clob"))]
+pub mod mock_clob;
 pub mod order_executor;
+pub mod pause;
 pub mod position_sizer;
+pub mod positions;
+pub mod quality;
+pub mod queue;
+pub mod rebalancer;
+pub mod retries;
+pub mod risk;
+pub mod safe;
+pub mod schedule;
+pub mod settlement;
 pub mod signer;
+pub mod strategies;
+pub mod sweeper;
+pub mod var;
+pub mod weighting;
 
 pub use clob_client::ClobClient;
 pub use order_executor::OrderExecutor;
 pub use position_sizer::PositionSizer;
+pub use rebalancer::Rebalancer;
+pub use risk::RiskMonitor;
 pub use signer::OrderSigner;
@@ -1,17 +1,56 @@
 use crate::config::ExecutionConfig;
 use crate::errors::{PolymarketError, Result};
-use crate::execution::clob_client::ClobClient;
+use crate::execution::batcher::OrderBatcher;
+use crate::execution::clob_client::{BestQuotes, ClobClient, FillLeg, OpenOrder, OrderIntent};
+use crate::execution::duplicates::DuplicateGuard;
+use crate::execution::fills::{discrepancies, FillDiscrepancy};
+use crate::execution::lifecycle::{OrderRecord, OrderState, Settlement};
 use crate::execution::position_sizer::PositionSizer;
-use crate::models::{OrderFillStatus, OrderStatus, OrderType, Trade};
-use rust_decimal::Decimal;
+use crate::execution::positions::{copied_holdings, exit_shares, holdings, Holding};
+use crate::execution::settlement::SettlementVerifier;
+use crate::execution::weighting::TraderWeights;
+use crate::metrics;
+use crate::models::{OrderFillStatus, OrderResponse, OrderSide, OrderType, SkipReason, Trade};
+use crate::monitoring::markets::EventInfo;
+use crate::monitoring::{MarketDirectory, PriceCache, TraderPortfolios};
+use crate::storage::{OrderStore, TradeLogEntry, TradeStore};
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
+/// First wait between attempts to place an order
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait between attempts to place an order
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// What happened to orders recovered at startup
+#[derive(Debug, Default)]
+pub struct ResumeSummary {
+    pub resumed: usize,
+    pub filled: usize,
+    pub cancelled: usize,
+    /// Orders the exchange couldn't be asked about; retried next start
+    pub unresolved: usize,
+}
+
 pub struct OrderExecutor {
     clob_client: Arc<ClobClient>,
     position_sizer: Arc<PositionSizer>,
     config: ExecutionConfig,
+    order_store: Option<Arc<dyn OrderStore>>,
+    markets: Option<Arc<MarketDirectory>>,
+    max_event_exposure: Option<Decimal>,
+    end_date_buffer: Option<chrono::Duration>,
+    trader_weights: Option<Arc<TraderWeights>>,
+    portfolios: Option<Arc<TraderPortfolios>>,
+    prices: Option<Arc<PriceCache>>,
+    batcher: Option<OrderBatcher>,
+    duplicates: Option<DuplicateGuard>,
+    settlement: Option<Arc<SettlementVerifier>>,
 }
 
 impl OrderExecutor {
@@ -24,79 +63,210 @@ impl OrderExecutor {
             clob_client: Arc::new(clob_client),
             position_sizer: Arc::new(position_sizer),
             config,
+            order_store: None,
+            markets: None,
+            max_event_exposure: None,
+            end_date_buffer: None,
+            trader_weights: None,
+            portfolios: None,
+            prices: None,
+            batcher: None,
+            duplicates: None,
+            settlement: None,
+        }
+    }
+
+    pub fn clob_client(&self) -> &ClobClient {
+        &self.clob_client
+    }
+
+    /// Persist every order's lifecycle so history survives restarts
+    pub fn with_order_store(mut self, order_store: Arc<dyn OrderStore>) -> Self {
+        self.order_store = Some(order_store);
+        self
+    }
+
+    /// Market metadata, for skipping untradable markets and the price sum check
+    pub fn with_markets(mut self, markets: Arc<MarketDirectory>) -> Self {
+        self.markets = Some(markets);
+        self
+    }
+
+    /// Scale each trader's trades by their copy ratio before sizing
+    pub fn with_trader_weights(mut self, weights: Arc<TraderWeights>) -> Self {
+        self.trader_weights = Some(weights);
+        self
+    }
+
+    /// Traders' portfolio values, for scaling copies by conviction
+    pub fn with_portfolios(mut self, portfolios: Arc<TraderPortfolios>) -> Self {
+        self.portfolios = Some(portfolios);
+        self
+    }
+
+    /// Recent market prices, for scaling copies down in volatile markets
+    pub fn with_prices(mut self, prices: Arc<PriceCache>) -> Self {
+        self.prices = Some(prices);
+        self
+    }
+
+    /// Send orders placed within `window` of each other as one batch request
+    pub fn with_batching(mut self, window: Duration) -> Self {
+        self.batcher = Some(OrderBatcher::spawn(self.clob_client.clone(), window));
+        self
+    }
+
+    /// Don't buy into markets ending within `hours`, which the risk monitor
+    /// would only sell again; needs markets
+    pub fn with_end_date_buffer(mut self, hours: u64) -> Self {
+        self.end_date_buffer = Some(chrono::Duration::hours(hours as i64));
+        self
+    }
+
+    /// Cap the combined cost of positions in one event; needs markets and an order store
+    pub fn with_event_exposure_cap(mut self, cap: Decimal) -> Self {
+        self.max_event_exposure = Some(cap);
+        self
+    }
+
+    /// Refuse a second copy of a trader's trade in the same market and side
+    /// within `window`, unless its size differs by more than `size_tolerance`
+    pub fn with_duplicate_guard(mut self, window: Duration, size_tolerance: Decimal) -> Self {
+        self.duplicates = Some(DuplicateGuard::new(window, size_tolerance));
+        self
+    }
+
+    /// Count large fills towards our positions only once the exchange's
+    /// settlement shows up on-chain
+    pub fn with_settlement_check(mut self, verifier: SettlementVerifier) -> Self {
+        self.settlement = Some(Arc::new(verifier));
+        self
+    }
+
+    /// An executor for another strategy trading the same wallet, sizing and
+    /// screening copies with `position_sizer` and `config`
+    ///
+    /// It shares this executor's client, order history and market data, and
+    /// gets a duplicate guard of its own. Conviction and volatility scaling
+    /// stay off unless they are on here too.
+    pub fn for_strategy(&self, position_sizer: PositionSizer, config: ExecutionConfig) -> Self {
+        let sizing = position_sizer.config();
+        Self {
+            clob_client: self.clob_client.clone(),
+            portfolios: self
+                .portfolios
+                .clone()
+                .filter(|_| sizing.conviction.enabled),
+            prices: self.prices.clone().filter(|_| sizing.volatility.enabled),
+            position_sizer: Arc::new(position_sizer),
+            config,
+            order_store: self.order_store.clone(),
+            markets: self.markets.clone(),
+            max_event_exposure: self.max_event_exposure,
+            end_date_buffer: self.end_date_buffer,
+            trader_weights: self.trader_weights.clone(),
+            batcher: self.batcher.clone(),
+            duplicates: self.duplicates.as_ref().map(DuplicateGuard::fresh),
+            settlement: self.settlement.clone(),
         }
     }
 
     /// Execute a trade based on detected trader activity
     pub async fn execute_trade(&self, trade: &Trade, current_balance: Decimal) -> Result<()> {
-        // Filter trade by size
-        if !self.should_copy_trade(trade) {
-            info!(
-                "Skipping trade {} - outside configured size limits",
-                trade.id
-            );
-            return Ok(());
+        self.screen(trade).await?;
+        if let Some(fraction) = trade.exit_fraction {
+            return self.copy_exit(trade, fraction).await;
         }
 
         // Calculate position size
-        let position_size = self
-            .position_sizer
-            .calculate_position_size(trade.size_usdc, current_balance)?;
+        let copy_ratio = self
+            .trader_weights
+            .as_ref()
+            .map_or(Decimal::ONE, |weights| weights.copy_ratio(trade.trader));
+        let conviction = self.conviction_multiplier(trade).await;
+        let volatility = self.volatility_multiplier(trade);
+        let mut position_size = self.position_sizer.calculate_position_size(
+            trade.size_usdc * copy_ratio * conviction * volatility,
+            current_balance,
+        )?;
+        if let (OrderSide::Buy, Some(cap)) = (&trade.side, self.max_event_exposure) {
+            position_size = self.cap_event_exposure(trade, position_size, cap).await?;
+        }
+        let mut shortfall = None;
+        if trade.side == OrderSide::Buy {
+            (position_size, shortfall) = self
+                .fit_to_balance(trade, position_size, current_balance)
+                .await?;
+        }
 
+        // Sizing is in USDC; orders, and every record of them, are in shares
+        let mut shares = shares_for(position_size, trade.price)?;
         info!(
-            "Executing trade {} - Market: {}, Side: {}, Size: {} USDC",
-            trade.id, trade.market_id, trade.side, position_size
+            "Executing trade {} - Market: {}, Side: {}, Size: {} USDC ({} shares)",
+            trade.id, trade.market_id, trade.side, position_size, shares
         );
 
+        // Try to fill as a maker first; whatever is left goes through as usual
+        if self.config.execution_style == "passive" {
+            match self.enter_passively(trade, shares, shortfall).await? {
+                Some(remaining) => shares = remaining,
+                None => return Ok(()),
+            }
+        }
+
         // Determine order type
-        let order_type = match self.config.order_type.as_str() {
-            "FOK" => OrderType::FOK,
-            "GTC" => OrderType::GTC,
-            "GTD" => OrderType::GTD,
-            _ => OrderType::FOK,
-        };
+        let order_type = self.order_type();
 
         // Place order with retry logic
         let mut attempts = 0;
         let max_retries = self.config.max_retries;
 
         while attempts < max_retries {
+            // Persist before sending so a crash mid-request leaves a trace
+            let mut order = OrderRecord::new(trade, shares, order_type.clone());
+            order.fee_rate_bps = self.fee_rate(&trade.market_id).await;
+            order.shortfall_usdc = shortfall;
+            order.advance(OrderState::Submitted, None)?;
+            self.save_order(&order).await;
+
             match self
-                .clob_client
-                .place_order(
-                    &trade.market_id,
-                    trade.side.clone(),
-                    trade.price,
-                    position_size,
-                    order_type.clone(),
-                )
+                .place_order(OrderIntent {
+                    market_id: trade.market_id.clone(),
+                    side: trade.side.clone(),
+                    price: trade.price,
+                    size: shares,
+                    order_type: order_type.clone(),
+                })
                 .await
             {
                 Ok(order_response) => {
                     info!("Order placed successfully: {}", order_response.order_id);
+                    order.exchange_order_id = Some(order_response.order_id.clone());
+                    self.apply_status(&mut order, &order_response).await;
 
                     // Monitor order fill status
-                    let fill_status = self
-                        .wait_for_fill(&order_response.order_id, position_size)
-                        .await?;
+                    let fill_status = self.wait_for_fill(&mut order).await?;
 
                     match fill_status {
                         OrderFillStatus::FullyFilled { price, size } => {
-                            info!(
-                                "Order fully filled - Price: {}, Size: {}",
-                                price, size
-                            );
+                            info!("Order fully filled - Price: {}, Size: {}", price, size);
                             return Ok(());
                         }
                         OrderFillStatus::PartiallyFilled { price, size } => {
                             warn!(
                                 "Order partially filled - Price: {}, Size: {} (expected {})",
-                                price, size, position_size
+                                price, size, shares
                             );
                             return Ok(());
                         }
                         OrderFillStatus::TimedOut => {
                             warn!("Order timed out: {}", order_response.order_id);
+                            // Resting orders can chase the price instead of giving up
+                            if self.config.reprice_steps > 0
+                                && !matches!(order_type, OrderType::FOK)
+                            {
+                                return self.reprice(trade, shares, order_type).await;
+                            }
                             return Err(PolymarketError::OrderTimeout);
                         }
                         OrderFillStatus::Cancelled => {
@@ -108,13 +278,63 @@ impl OrderExecutor {
                     }
                 }
                 Err(e) => {
+                    record_placement_error(&e);
+                    // The exchange may have matched an order whose response never
+                    // arrived; placing it again would copy the trade twice
+                    if let PolymarketError::OrderOutcomeUnknown(_) = e {
+                        match self.find_unacknowledged_fill(&order).await {
+                            Ok(Some(leg)) => {
+                                warn!(
+                                    "Order {} went through despite the error ({}); recording the fill",
+                                    order.id, e
+                                );
+                                order.exchange_order_id = Some(leg.order_id);
+                                order.fill_price = Some(leg.price);
+                                order.advance(
+                                    OrderState::Filled,
+                                    Some("found in trade history".to_string()),
+                                )?;
+                                order.filled_size = leg.size;
+                                self.mark_for_settlement(&mut order);
+                                self.save_order(&order).await;
+                                self.confirm_settlement(&mut order).await;
+                                return Ok(());
+                            }
+                            Ok(None) => {}
+                            Err(check) => {
+                                error!(
+                                    "Not retrying order {}: {}, and whether it was placed can't be told: {}",
+                                    order.id, e, check
+                                );
+                                order.fail(&e.to_string())?;
+                                self.save_order(&order).await;
+                                return Err(e);
+                            }
+                        }
+                    }
+                    order.fail(&e.to_string())?;
+                    self.save_order(&order).await;
+
                     attempts += 1;
                     if attempts >= max_retries {
-                        error!("Failed to place order after {} attempts: {}", max_retries, e);
+                        error!(
+                            "Failed to place order after {} attempts: {}",
+                            max_retries, e
+                        );
                         return Err(e);
                     }
-                    warn!("Order placement failed (attempt {}/{}): {}", attempts, max_retries, e);
-                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    let delay = match e {
+                        PolymarketError::RateLimited(seconds) => Duration::from_secs(seconds),
+                        _ => retry_delay(attempts),
+                    };
+                    warn!(
+                        "Order placement failed (attempt {}/{}), retrying in {}s: {}",
+                        attempts,
+                        max_retries,
+                        delay.as_secs(),
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
                 }
             }
         }
@@ -124,50 +344,821 @@ impl OrderExecutor {
         ))
     }
 
-    /// Wait for an order to be filled
-    async fn wait_for_fill(
+    /// Rest a post-only order at the top of the book for up to
+    /// `passive_timeout_seconds`; returns the shares still to be sent as a
+    /// taker order, if any
+    async fn enter_passively(
+        &self,
+        trade: &Trade,
+        size: Decimal,
+        shortfall: Option<Decimal>,
+    ) -> Result<Option<Decimal>> {
+        let (quotes, tick_size) = tokio::try_join!(
+            self.clob_client.get_best_quotes(&trade.market_id),
+            self.clob_client.get_tick_size(&trade.market_id),
+        )?;
+        let Some(price) = passive_price(
+            &trade.side,
+            quotes,
+            tick_size,
+            self.config.passive_improve_ticks,
+        ) else {
+            info!(
+                "No quote to join in {}; copying trade {} as a taker",
+                trade.market_id, trade.id
+            );
+            return Ok(Some(size));
+        };
+
+        info!(
+            "Resting post-only {} for trade {} at {} (trader paid {})",
+            trade.side, trade.id, price, trade.price
+        );
+        let mut order = OrderRecord::new(trade, size, OrderType::GTC);
+        order.price = price;
+        order.fee_rate_bps = self.fee_rate(&trade.market_id).await;
+        order.shortfall_usdc = shortfall;
+        order.advance(OrderState::Submitted, Some("passive entry".to_string()))?;
+        self.save_order(&order).await;
+
+        let response = match self
+            .clob_client
+            .place_post_only_order(&trade.market_id, trade.side.clone(), price, size)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                // Post-only orders are rejected when the book moved through the price
+                order.fail(&e.to_string())?;
+                self.save_order(&order).await;
+                warn!("Passive order for trade {} rejected: {}", trade.id, e);
+                return Ok(self.config.passive_escalate.then_some(size));
+            }
+        };
+        order.exchange_order_id = Some(response.order_id.clone());
+        self.apply_status(&mut order, &response).await;
+
+        let timeout = Duration::from_secs(self.config.passive_timeout_seconds);
+        let filled = match self.wait_for_fill_within(&mut order, timeout).await? {
+            OrderFillStatus::FullyFilled { .. } => {
+                info!("Passive order for trade {} filled", trade.id);
+                return Ok(None);
+            }
+            OrderFillStatus::PartiallyFilled { size: filled, .. } => {
+                // Take the rest off the book before sending it elsewhere
+                self.clob_client.cancel_order(&response.order_id).await?;
+                order.advance(OrderState::Cancelled, Some("passive timeout".to_string()))?;
+                self.save_order(&order).await;
+                filled
+            }
+            OrderFillStatus::TimedOut | OrderFillStatus::Cancelled => Decimal::ZERO,
+        };
+
+        let remaining = size - filled;
+        if !self.config.passive_escalate || remaining <= Decimal::ZERO {
+            info!(
+                "Passive order for trade {} ended with {} of {} filled",
+                trade.id, filled, size
+            );
+            return Ok(None);
+        }
+        info!(
+            "Passive order for trade {} filled {} of {}; taking the remaining {}",
+            trade.id, filled, size, remaining
+        );
+        Ok(Some(remaining))
+    }
+
+    /// Re-place a timed-out order up to `reprice_steps` times, each a
+    /// `reprice_step` more aggressive than the last and never further than
+    /// `max_reprice_deviation` from the trader's price
+    async fn reprice(&self, trade: &Trade, size: Decimal, order_type: OrderType) -> Result<()> {
+        let mut remaining = size;
+        let mut last_price = trade.price;
+        for step in 1..=self.config.reprice_steps {
+            let price = ladder_price(
+                &trade.side,
+                trade.price,
+                self.config.reprice_step * Decimal::from(step),
+                self.config.max_reprice_deviation,
+            );
+            if price == last_price {
+                // Already at the furthest price allowed
+                break;
+            }
+            last_price = price;
+
+            info!(
+                "Re-pricing trade {} at {} (step {}/{}, trader paid {})",
+                trade.id, price, step, self.config.reprice_steps, trade.price
+            );
+            let mut order = OrderRecord::new(trade, remaining, order_type.clone());
+            order.price = price;
+            let reason = format!("re-price {}/{}", step, self.config.reprice_steps);
+            match self.submit_recorded(&mut order, &reason).await? {
+                OrderFillStatus::FullyFilled { .. } => return Ok(()),
+                OrderFillStatus::PartiallyFilled { size: filled, .. } => {
+                    // Partial fills keep resting; pull the rest before the next step
+                    if let Some(ref order_id) = order.exchange_order_id {
+                        self.clob_client.cancel_order(order_id).await?;
+                    }
+                    order.advance(OrderState::Cancelled, Some("re-priced".to_string()))?;
+                    self.save_order(&order).await;
+                    remaining -= filled;
+                }
+                OrderFillStatus::TimedOut | OrderFillStatus::Cancelled => {}
+            }
+        }
+
+        if remaining < size {
+            warn!(
+                "Trade {} filled {} of {} after re-pricing",
+                trade.id,
+                size - remaining,
+                size
+            );
+            return Ok(());
+        }
+        Err(PolymarketError::OrderTimeout)
+    }
+
+    /// Check a trade against the size limits, market status, event tags,
+    /// price sum and duplicate guard, without sizing or placing anything
+    pub async fn screen(&self, trade: &Trade) -> Result<()> {
+        // Exits follow the trader whatever their size, scaled to our copy
+        if trade.exit_fraction.is_some() {
+            return self.check_duplicate(trade);
+        }
+
+        // Filter trade by size
+        if !self.should_copy_trade(trade) {
+            return Err(PolymarketError::Skipped(
+                SkipReason::SizeLimits,
+                format!(
+                    "trade of {} USDC is outside the configured size limits",
+                    trade.size_usdc
+                ),
+            ));
+        }
+
+        // Closed or paused markets would only reject the order on every retry
+        if let Some(ref markets) = self.markets {
+            match markets.market(&trade.market_id).await {
+                Ok(market) => {
+                    if let Some(reason) = market.untradable_reason() {
+                        return Err(PolymarketError::MarketNotTradable(format!(
+                            "{} ({})",
+                            reason, market.question
+                        )));
+                    }
+                    if let (OrderSide::Buy, Some(buffer)) = (&trade.side, self.end_date_buffer) {
+                        if market.ends_within(buffer, Utc::now()) {
+                            return Err(PolymarketError::Skipped(
+                                SkipReason::Schedule,
+                                format!(
+                                    "market ends at {}, within the {}-hour exit buffer",
+                                    market.end_date.unwrap_or_default(),
+                                    buffer.num_hours()
+                                ),
+                            ));
+                        }
+                    }
+                }
+                Err(e) => warn!("Could not check status of {}: {}", trade.market_id, e),
+            }
+        }
+
+        self.check_event_tags(trade).await?;
+
+        // Stale quotes show up as outcome prices that don't add up to 1
+        if self.config.price_sum_check != "off" {
+            match self.price_sum_anomaly(trade).await {
+                Ok(Some(sum)) => {
+                    warn!(
+                        "Price anomaly in {}: YES+NO midpoints sum to {} (trade {})",
+                        trade.market_id, sum, trade.id
+                    );
+                    if self.config.price_sum_check == "skip" {
+                        return Err(PolymarketError::Skipped(
+                            SkipReason::PriceAnomaly,
+                            format!("outcome prices sum to {}", sum),
+                        ));
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Price sum check failed for {}: {}", trade.market_id, e),
+            }
+        }
+
+        self.check_duplicate(trade)
+    }
+
+    /// Skip trades the duplicate guard takes for one already copied
+    fn check_duplicate(&self, trade: &Trade) -> Result<()> {
+        match self
+            .duplicates
+            .as_ref()
+            .and_then(|guard| guard.check(trade))
+        {
+            Some(reason) => Err(PolymarketError::Skipped(SkipReason::Duplicate, reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Scale down copies into markets whose price has been swinging; 1 until
+    /// the price cache holds enough recent prices for the market
+    fn volatility_multiplier(&self, trade: &Trade) -> Decimal {
+        let Some(ref prices) = self.prices else {
+            return Decimal::ONE;
+        };
+        let min_samples = self.position_sizer.config().volatility.min_samples;
+        let Some(volatility) = prices.volatility(&trade.market_id, min_samples) else {
+            return Decimal::ONE;
+        };
+        let multiplier = self
+            .position_sizer
+            .volatility_multiplier(trade.price, volatility);
+        if multiplier < Decimal::ONE {
+            info!(
+                "Price of {} has a standard deviation of {}; copying trade {} at {}x",
+                trade.market_id,
+                volatility.round_dp(4),
+                trade.id,
+                multiplier.round_dp(2)
+            );
+        }
+        multiplier
+    }
+
+    /// Scale for how much of their portfolio the trader bet; 1 when
+    /// conviction sizing is off or the portfolio value is unavailable
+    async fn conviction_multiplier(&self, trade: &Trade) -> Decimal {
+        let Some(ref portfolios) = self.portfolios else {
+            return Decimal::ONE;
+        };
+        match portfolios.conviction(trade).await {
+            Ok(Some(conviction)) => {
+                let multiplier = self.position_sizer.conviction_multiplier(conviction);
+                info!(
+                    "Trader {:?} bet {}% of their portfolio on trade {}; copying at {}x",
+                    trade.trader,
+                    (conviction * Decimal::ONE_HUNDRED).round_dp(2),
+                    trade.id,
+                    multiplier.round_dp(2)
+                );
+                multiplier
+            }
+            Ok(None) => Decimal::ONE,
+            Err(e) => {
+                warn!(
+                    "Could not fetch the portfolio of {:?}; copying trade {} at normal size: {}",
+                    trade.trader, trade.id, e
+                );
+                Decimal::ONE
+            }
+        }
+    }
+
+    /// Skip trades outside the events `event_tags` and `excluded_event_tags`
+    /// allow
+    async fn check_event_tags(&self, trade: &Trade) -> Result<()> {
+        let (allowed, excluded) = (&self.config.event_tags, &self.config.excluded_event_tags);
+        if allowed.is_empty() && excluded.is_empty() {
+            return Ok(());
+        }
+        let Some(ref markets) = self.markets else {
+            return Ok(());
+        };
+        let event = match markets.event_of(&trade.market_id).await {
+            Ok(event) => event,
+            Err(e) if allowed.is_empty() => {
+                warn!("Could not look up event of {}: {}", trade.market_id, e);
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(PolymarketError::Skipped(
+                    SkipReason::EventFilter,
+                    format!("could not check the event's tags: {}", e),
+                ))
+            }
+        };
+
+        let title = event.as_ref().map_or("no event", |e| e.title.as_str());
+        if let Some(tag) = matching_tag(event.as_deref(), excluded) {
+            return Err(PolymarketError::Skipped(
+                SkipReason::EventFilter,
+                format!("event '{}' is tagged {}", title, tag),
+            ));
+        }
+        if !allowed.is_empty() && matching_tag(event.as_deref(), allowed).is_none() {
+            return Err(PolymarketError::Skipped(
+                SkipReason::EventFilter,
+                format!(
+                    "event '{}' has none of the tags {}",
+                    title,
+                    allowed.join(", ")
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Cut a buy down to the balance our open orders leave free, as
+    /// `insufficient_balance` asks; returns the size and what it fell short by
+    async fn fit_to_balance(
+        &self,
+        trade: &Trade,
+        size: Decimal,
+        balance: Decimal,
+    ) -> Result<(Decimal, Option<Decimal>)> {
+        if self.position_sizer.config().insufficient_balance != "scale_down" {
+            return Ok((size, None));
+        }
+        let available = balance - reserved_by_open_buys(&self.orders().await?);
+        if size <= available {
+            return Ok((size, None));
+        }
+        let market_minimum = match self.markets {
+            Some(ref markets) => match markets.market(&trade.market_id).await {
+                Ok(market) => market.min_order_size.unwrap_or_default() * trade.price,
+                Err(e) => {
+                    warn!("Could not look up market {}: {}", trade.market_id, e);
+                    Decimal::ZERO
+                }
+            },
+            None => Decimal::ZERO,
+        };
+        let fitted = self
+            .position_sizer
+            .fit_to_balance(size, available, market_minimum)?;
+        let shortfall = size - fitted;
+        warn!(
+            "Scaling trade {} down to {} USDC, all the balance left free ({} USDC short)",
+            trade.id,
+            fitted.round_dp(2),
+            shortfall.round_dp(2)
+        );
+        metrics::global().increment(
+            "polycopy_scaled_down_copies_total",
+            "Copies scaled down to the free balance",
+            &[],
+        );
+        Ok((fitted, Some(shortfall)))
+    }
+
+    /// Shrink a buy so the cost held across its event stays within `cap`
+    async fn cap_event_exposure(
+        &self,
+        trade: &Trade,
+        size: Decimal,
+        cap: Decimal,
+    ) -> Result<Decimal> {
+        let Some(ref markets) = self.markets else {
+            return Ok(size);
+        };
+        let event = match markets.event_of(&trade.market_id).await {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Could not look up event of {}: {}", trade.market_id, e);
+                None
+            }
+        };
+        let Some(event) = event else {
+            return Ok(size);
+        };
+
+        // The event lists all its markets, so holdings need no lookups of their own
+        let exposure: Decimal = self
+            .holdings()
+            .await?
+            .into_iter()
+            .filter(|(token_id, _)| event.contains_token(token_id))
+            .map(|(_, holding)| holding.cost)
+            .sum();
+
+        match room_under_cap(exposure, size, cap) {
+            Some(allowed) if allowed < size => {
+                info!(
+                    "Reducing trade {} to {} USDC: event '{}' already holds {} of {} USDC",
+                    trade.id, allowed, event.title, exposure, cap
+                );
+                Ok(allowed)
+            }
+            Some(allowed) => Ok(allowed),
+            None => Err(PolymarketError::Skipped(
+                SkipReason::ExposureCap,
+                format!(
+                    "event '{}' is at its exposure cap ({} of {} USDC)",
+                    event.title, exposure, cap
+                ),
+            )),
+        }
+    }
+
+    /// The sum of both outcome midpoints, if it is further from 1 than allowed
+    async fn price_sum_anomaly(&self, trade: &Trade) -> Result<Option<Decimal>> {
+        let Some(ref markets) = self.markets else {
+            return Ok(None);
+        };
+        let market = markets.market(&trade.market_id).await?;
+        let Some(complement) = market.complement(&trade.market_id) else {
+            // Only binary markets have a pair that must sum to 1
+            return Ok(None);
+        };
+
+        let (price, complement_price) = tokio::try_join!(
+            self.clob_client.get_midpoint(&trade.market_id),
+            self.clob_client.get_midpoint(&complement.token_id),
+        )?;
+        let sum = price + complement_price;
+        Ok(((sum - Decimal::ONE).abs() > self.config.price_sum_tolerance).then_some(sum))
+    }
+
+    /// Every order in the order store, or none without one
+    pub async fn orders(&self) -> Result<Vec<OrderRecord>> {
+        match self.order_store {
+            Some(ref store) => store.orders().await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Net holdings per market from the filled orders in the order store
+    pub async fn holdings(&self) -> Result<HashMap<String, Holding>> {
+        Ok(holdings(&self.orders().await?))
+    }
+
+    /// Sell up to `shares` of a held position, never more than we hold
+    ///
+    /// The exchange has no reduce-only flag, so the size is capped by the
+    /// holdings recorded in the order store.
+    pub async fn reduce_position(
         &self,
-        order_id: &str,
-        expected_size: Decimal,
+        market_id: &str,
+        shares: Decimal,
+        price: Decimal,
+        reason: &str,
     ) -> Result<OrderFillStatus> {
-        let start = Instant::now();
+        let held = self
+            .holdings()
+            .await?
+            .get(market_id)
+            .map(|holding| holding.shares)
+            .unwrap_or(Decimal::ZERO);
+        let size = shares.min(held);
+        if size <= Decimal::ZERO {
+            return Err(PolymarketError::ExecutionError(format!(
+                "No position in {} to reduce",
+                market_id
+            )));
+        }
+
+        self.place_own_order(market_id, OrderSide::Sell, price, size, reason)
+            .await
+    }
+
+    /// Buy `shares` of the complementary outcome token to lock in a position
+    ///
+    /// Holding both outcomes pays out 1 per pair whatever happens, so the loss
+    /// on the original position is capped at what has been lost so far.
+    pub async fn hedge_position(
+        &self,
+        complement_token_id: &str,
+        shares: Decimal,
+        price: Decimal,
+    ) -> Result<OrderFillStatus> {
+        self.place_own_order(complement_token_id, OrderSide::Buy, price, shares, "hedge")
+            .await
+    }
+
+    /// Sell the same share of our copy of `trade.trader`'s position as they sold
+    ///
+    /// The copy is what orders copying this trader added up to in the
+    /// market, so a 40% exit trims 40% of it whatever our sizing was.
+    async fn copy_exit(&self, trade: &Trade, fraction: Decimal) -> Result<()> {
+        let orders = match self.order_store {
+            Some(ref store) => store.orders().await?,
+            None => Vec::new(),
+        };
+        let copied = copied_holdings(&orders, trade.trader)
+            .get(&trade.market_id)
+            .map_or(Decimal::ZERO, |holding| holding.shares);
+        let held = holdings(&orders)
+            .get(&trade.market_id)
+            .map_or(Decimal::ZERO, |holding| holding.shares);
+        let shares = exit_shares(copied, fraction).min(held);
+        if trade.side != OrderSide::Sell || shares <= Decimal::ZERO {
+            return Err(PolymarketError::Skipped(
+                SkipReason::NoPosition,
+                format!(
+                    "trader exited {} of a position we hold no copy of",
+                    fraction
+                ),
+            ));
+        }
+
+        info!(
+            "Trader {:?} sold {}% of {}; selling {} of our {} copied shares",
+            trade.trader,
+            (fraction * Decimal::ONE_HUNDRED).round_dp(1),
+            trade.market_id,
+            shares,
+            copied
+        );
+        let order = OrderRecord::new(trade, shares, self.order_type());
+        match self.place_recorded(order, "proportional exit").await? {
+            OrderFillStatus::FullyFilled { .. } | OrderFillStatus::PartiallyFilled { .. } => Ok(()),
+            OrderFillStatus::TimedOut => Err(PolymarketError::OrderTimeout),
+            status => Err(PolymarketError::ExecutionError(format!(
+                "Exit order ended {:?}",
+                status
+            ))),
+        }
+    }
+
+    /// Place and watch an order the bot makes on its own account rather than
+    /// to copy a trade
+    async fn place_own_order(
+        &self,
+        market_id: &str,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+        reason: &str,
+    ) -> Result<OrderFillStatus> {
+        let order = OrderRecord::for_market(market_id, side, price, size, self.order_type());
+        self.place_recorded(order, reason).await
+    }
+
+    /// Submit `order` once, recording it first, and watch it until it settles
+    async fn place_recorded(
+        &self,
+        mut order: OrderRecord,
+        reason: &str,
+    ) -> Result<OrderFillStatus> {
+        self.submit_recorded(&mut order, reason).await
+    }
+
+    /// [`place_recorded`](Self::place_recorded), leaving the final state in `order`
+    async fn submit_recorded(
+        &self,
+        order: &mut OrderRecord,
+        reason: &str,
+    ) -> Result<OrderFillStatus> {
+        order.fee_rate_bps = self.fee_rate(&order.market_id).await;
+        order.advance(OrderState::Submitted, Some(reason.to_string()))?;
+        self.save_order(order).await;
+
+        match self
+            .place_order(OrderIntent {
+                market_id: order.market_id.clone(),
+                side: order.side.clone(),
+                price: order.price,
+                size: order.size,
+                order_type: order.order_type.clone(),
+            })
+            .await
+        {
+            Ok(response) => {
+                order.exchange_order_id = Some(response.order_id.clone());
+                self.apply_status(order, &response).await;
+                self.wait_for_fill(order).await
+            }
+            Err(e) => {
+                order.fail(&e.to_string())?;
+                self.save_order(order).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// The match of `order` in our trade history, for an order whose
+    /// placement failed without saying whether the exchange took it
+    ///
+    /// Fill-or-kill orders are matched at once or not at all, so a match in
+    /// the market on our side that no recorded order accounts for is this
+    /// one. Other orders may be resting unseen, so for those, and when the
+    /// history can't be fetched, this fails rather than risk a duplicate.
+    async fn find_unacknowledged_fill(&self, order: &OrderRecord) -> Result<Option<FillLeg>> {
+        if !matches!(order.order_type, OrderType::FOK) {
+            return Err(PolymarketError::ExecutionError(format!(
+                "a {} order may be resting on the book",
+                order.order_type
+            )));
+        }
+        let known: HashSet<String> = match self.order_store {
+            Some(ref store) => store
+                .orders()
+                .await?
+                .into_iter()
+                .filter_map(|o| o.exchange_order_id)
+                .collect(),
+            None => HashSet::new(),
+        };
+        // Match times are whole seconds
+        let since = order.created_at - chrono::Duration::seconds(1);
+        let trades = self.clob_client.get_trades(Some(since)).await?;
+        Ok(trades
+            .iter()
+            .filter(|trade| trade.matched_at().is_some_and(|at| at >= since))
+            .flat_map(|trade| trade.legs())
+            .find(|leg| {
+                leg.asset_id == order.market_id
+                    && leg.side == order.side
+                    && !known.contains(&leg.order_id)
+            }))
+    }
+
+    /// Place an order, through the batcher when batching is on
+    async fn place_order(&self, intent: OrderIntent) -> Result<OrderResponse> {
+        match self.batcher {
+            Some(ref batcher) => batcher.place_order(intent).await,
+            None => {
+                self.clob_client
+                    .place_order(
+                        &intent.market_id,
+                        intent.side,
+                        intent.price,
+                        intent.size,
+                        intent.order_type,
+                    )
+                    .await
+            }
+        }
+    }
+
+    /// The market's fee rate, recorded on each order so fills know what they
+    /// paid; zero if it can't be fetched, in which case placing fails anyway
+    async fn fee_rate(&self, market_id: &str) -> u32 {
+        match self.clob_client.get_fee_rate_bps(market_id).await {
+            Ok(rate) => rate,
+            Err(e) => {
+                warn!("Could not fetch the fee rate of {}: {}", market_id, e);
+                0
+            }
+        }
+    }
+
+    fn order_type(&self) -> OrderType {
+        match self.config.order_type.as_str() {
+            "FOK" => OrderType::FOK,
+            "GTC" => OrderType::GTC,
+            "GTD" => OrderType::GTD,
+            _ => OrderType::FOK,
+        }
+    }
+
+    /// Wait for an order to be filled, advancing its lifecycle as the exchange reports
+    async fn wait_for_fill(&self, order: &mut OrderRecord) -> Result<OrderFillStatus> {
         let timeout = Duration::from_millis(self.config.order_confirmation_timeout_ms);
+        self.wait_for_fill_within(order, timeout).await
+    }
+
+    /// [`wait_for_fill`](Self::wait_for_fill), cancelling an unfilled order
+    /// after `timeout`
+    async fn wait_for_fill_within(
+        &self,
+        order: &mut OrderRecord,
+        timeout: Duration,
+    ) -> Result<OrderFillStatus> {
+        let order_id = order.exchange_order_id.clone().ok_or_else(|| {
+            PolymarketError::ExecutionError(format!("Order {} was never accepted", order.id))
+        })?;
+        let start = Instant::now();
         let poll_interval = Duration::from_millis(self.config.order_poll_interval_ms);
 
         loop {
-            let order = self.clob_client.get_order(order_id).await?;
+            if !order.state.is_terminal() {
+                let response = self.clob_client.get_order(&order_id).await?;
+                self.apply_status(order, &response).await;
+            }
 
-            match order.status {
-                OrderStatus::Filled => {
+            match order.state {
+                OrderState::Filled => {
+                    self.confirm_settlement(order).await;
                     return Ok(OrderFillStatus::FullyFilled {
-                        price: Decimal::ZERO, // Would be populated from actual response
-                        size: expected_size,
+                        price: order.fill_price.unwrap_or(order.price),
+                        size: order.filled_size,
                     });
                 }
-                OrderStatus::PartiallyFilled => {
+                OrderState::PartiallyFilled => {
                     if start.elapsed() > timeout {
+                        self.confirm_settlement(order).await;
                         return Ok(OrderFillStatus::PartiallyFilled {
-                            price: Decimal::ZERO,
-                            size: expected_size / Decimal::from(2), // Placeholder
+                            price: order.fill_price.unwrap_or(order.price),
+                            size: order.filled_size,
                         });
                     }
                 }
-                OrderStatus::Open => {
+                OrderState::Created | OrderState::Submitted | OrderState::Open => {
                     if start.elapsed() > timeout {
                         // Cancel unfilled orders
-                        self.clob_client.cancel_order(order_id).await?;
+                        self.clob_client.cancel_order(&order_id).await?;
+                        order.advance(OrderState::Cancelled, Some("timed out".to_string()))?;
+                        self.save_order(order).await;
                         return Ok(OrderFillStatus::TimedOut);
                     }
                 }
-                OrderStatus::Cancelled => {
+                OrderState::Cancelled => {
                     return Ok(OrderFillStatus::Cancelled);
                 }
+                OrderState::Failed => {
+                    return Err(PolymarketError::ExecutionError(
+                        order
+                            .error
+                            .clone()
+                            .unwrap_or_else(|| "Order failed".to_string()),
+                    ));
+                }
             }
 
             tokio::time::sleep(poll_interval).await;
         }
     }
 
+    /// Move the order to the state the exchange reports, take its fill from
+    /// the shares the exchange matched, and persist the change
+    async fn apply_status(&self, order: &mut OrderRecord, response: &OrderResponse) {
+        let advanced = match order.advance((&response.status).into(), None) {
+            Ok(advanced) => advanced,
+            // The exchange is the source of truth; keep polling rather than abort
+            Err(e) => {
+                warn!("Ignoring exchange status for order {}: {}", order.id, e);
+                return;
+            }
+        };
+        let filled = match response.size_matched {
+            Some(matched) => matched,
+            // Without a matched size, only a complete fill says how much filled
+            None if order.state == OrderState::Filled => order.size,
+            None => order.filled_size,
+        };
+        let fill_changed = filled != order.filled_size;
+        if !advanced && !fill_changed {
+            return;
+        }
+        if fill_changed {
+            order.filled_size = filled;
+            order.fill_price = self.fill_price(order).await;
+        }
+        self.mark_for_settlement(order);
+        self.save_order(order).await;
+    }
+
+    /// Average price `order` matched at, from our trade history; the price
+    /// recorded so far if the history can't be fetched or doesn't show it yet
+    async fn fill_price(&self, order: &OrderRecord) -> Option<Decimal> {
+        let order_id = order.exchange_order_id.as_deref()?;
+        // Match times are whole seconds
+        let since = order.created_at - chrono::Duration::seconds(1);
+        match self.clob_client.get_trades(Some(since)).await {
+            Ok(trades) => {
+                let legs: Vec<FillLeg> = trades
+                    .iter()
+                    .flat_map(|trade| trade.legs())
+                    .filter(|leg| leg.order_id == order_id)
+                    .collect();
+                average_price(&legs).or(order.fill_price)
+            }
+            Err(e) => {
+                warn!("Could not fetch the fills of order {}: {}", order.id, e);
+                order.fill_price
+            }
+        }
+    }
+
+    /// Hold a large fill back from our positions until its settlement is checked
+    fn mark_for_settlement(&self, order: &mut OrderRecord) {
+        let filled = order.filled_size > Decimal::ZERO;
+        if let Some(ref verifier) = self.settlement {
+            if filled && order.settlement.is_none() && verifier.applies_to(order) {
+                order.settlement = Some(Settlement::Pending);
+            }
+        }
+    }
+
+    /// Check a fill held back by [`mark_for_settlement`](Self::mark_for_settlement)
+    /// on-chain and persist the outcome
+    async fn confirm_settlement(&self, order: &mut OrderRecord) {
+        if let (Some(ref verifier), Some(Settlement::Pending)) =
+            (&self.settlement, order.settlement)
+        {
+            verifier.verify(order).await;
+            self.save_order(order).await;
+        }
+    }
+
+    /// Persist an order snapshot; a storage failure must not abandon a live order
+    async fn save_order(&self, order: &OrderRecord) {
+        if let Some(ref store) = self.order_store {
+            if let Err(e) = store.save_order(order).await {
+                error!(
+                    "Failed to persist order {} ({}): {}",
+                    order.id, order.state, e
+                );
+            }
+        }
+    }
+
     /// Check if a trade should be copied based on filters
     fn should_copy_trade(&self, trade: &Trade) -> bool {
         // Size filters
@@ -181,19 +1172,313 @@ impl OrderExecutor {
         true
     }
 
+    /// Settle orders a previous run left in flight
+    ///
+    /// Each order still marked submitted, open or partially filled is checked
+    /// against the exchange. Fills are recorded against the detected trade the
+    /// order copied; orders still resting after the confirmation timeout are
+    /// cancelled, and younger ones are watched until they fill or time out.
+    pub async fn resume_in_flight(&self, trades: &dyn TradeStore) -> Result<ResumeSummary> {
+        let mut summary = ResumeSummary::default();
+        let Some(ref order_store) = self.order_store else {
+            return Ok(summary);
+        };
+
+        let (in_flight, done): (Vec<OrderRecord>, Vec<OrderRecord>) = order_store
+            .orders()
+            .await?
+            .into_iter()
+            .partition(|order| !order.state.is_terminal());
+        // Fills whose settlement check was cut short
+        for mut order in done {
+            if order.settlement == Some(Settlement::Pending) {
+                self.confirm_settlement(&mut order).await;
+            }
+        }
+        if in_flight.is_empty() {
+            return Ok(summary);
+        }
+        info!("Resuming {} in-flight orders", in_flight.len());
+
+        let detected: HashMap<String, Trade> = trades
+            .entries()
+            .await?
+            .into_iter()
+            .map(|entry| (entry.trade.id.clone(), entry.trade))
+            .collect();
+        let timeout =
+            chrono::Duration::milliseconds(self.config.order_confirmation_timeout_ms as i64);
+
+        for mut order in in_flight {
+            summary.resumed += 1;
+            if let Err(e) = self.settle(&mut order, timeout).await {
+                warn!("Could not settle order {}: {}", order.id, e);
+                summary.unresolved += 1;
+                continue;
+            }
+            if order.trade_id.is_empty() {
+                // The bot's own order; there is no detected trade to annotate
+                continue;
+            }
+
+            let Some(trade) = detected.get(&order.trade_id) else {
+                warn!(
+                    "Order {} copies trade {} which is not in the trade log",
+                    order.id, order.trade_id
+                );
+                continue;
+            };
+            let entry = match order.executed_trade(trade) {
+                Some(executed) => {
+                    summary.filled += 1;
+                    TradeLogEntry::executed(trade, &executed)
+                }
+                None => {
+                    summary.cancelled += 1;
+                    let reason = order
+                        .error
+                        .clone()
+                        .unwrap_or_else(|| format!("Order {} after restart", order.state));
+                    TradeLogEntry::failed(trade, &reason)
+                }
+            };
+            let market = match self.markets {
+                Some(ref markets) => markets.label(&trade.market_id).await,
+                None => None,
+            };
+            trades.append(&entry.with_market(market)).await?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Bring one recovered order to a final (or partially filled) state
+    async fn settle(&self, order: &mut OrderRecord, timeout: chrono::Duration) -> Result<()> {
+        let Some(order_id) = order.exchange_order_id.clone() else {
+            // Never acknowledged; there is nothing to query or cancel
+            order.fail("Process stopped before the exchange acknowledged the order")?;
+            self.save_order(order).await;
+            return Ok(());
+        };
+
+        let response = self.clob_client.get_order(&order_id).await?;
+        self.apply_status(order, &response).await;
+        if order.state.is_terminal() {
+            self.confirm_settlement(order).await;
+            return Ok(());
+        }
+
+        if Utc::now() - order.created_at > timeout {
+            self.clob_client.cancel_order(&order_id).await?;
+            order.advance(
+                OrderState::Cancelled,
+                Some("stale after restart".to_string()),
+            )?;
+            self.save_order(order).await;
+            return Ok(());
+        }
+
+        self.wait_for_fill(order).await?;
+        Ok(())
+    }
+
+    /// Cancel our resting orders in `market_id`, or in every market, with one
+    /// bulk request and record them as cancelled; returns how many were
+    pub async fn cancel_open_orders(&self, market_id: Option<&str>, reason: &str) -> Result<usize> {
+        let result = match market_id {
+            Some(market_id) => self.clob_client.cancel_market_orders(market_id).await?,
+            None => self.clob_client.cancel_all().await?,
+        };
+        for (order_id, why) in &result.not_canceled {
+            warn!("Order {} was not cancelled: {}", order_id, why);
+        }
+
+        if let Some(ref store) = self.order_store {
+            for mut order in store.orders().await? {
+                let cancelled = order
+                    .exchange_order_id
+                    .as_ref()
+                    .is_some_and(|id| result.canceled.contains(id));
+                if cancelled && order.state.can_transition_to(OrderState::Cancelled) {
+                    order.advance(OrderState::Cancelled, Some(reason.to_string()))?;
+                    self.save_order(&order).await;
+                }
+            }
+        }
+        Ok(result.canceled.len())
+    }
+
+    /// Our orders resting on the exchange
+    pub async fn open_orders(&self) -> Result<Vec<OpenOrder>> {
+        self.clob_client.get_open_orders().await
+    }
+
+    /// Cancel one resting order by its exchange id, marking it cancelled in
+    /// the order store
+    pub async fn cancel_order(&self, exchange_order_id: &str, reason: &str) -> Result<()> {
+        self.clob_client.cancel_order(exchange_order_id).await?;
+        if let Some(ref store) = self.order_store {
+            for mut order in store.orders().await? {
+                if order.exchange_order_id.as_deref() == Some(exchange_order_id)
+                    && order.state.can_transition_to(OrderState::Cancelled)
+                {
+                    order.advance(OrderState::Cancelled, Some(reason.to_string()))?;
+                    self.save_order(&order).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare the recorded fills of orders placed since `since` with the
+    /// exchange's trade history, logging and counting every mismatch
+    pub async fn verify_fills(&self, since: DateTime<Utc>) -> Result<Vec<FillDiscrepancy>> {
+        let Some(ref store) = self.order_store else {
+            return Ok(Vec::new());
+        };
+        let orders: Vec<OrderRecord> = store
+            .orders()
+            .await?
+            .into_iter()
+            .filter(|order| order.created_at >= since)
+            .collect();
+        let trades = self.clob_client.get_trades(Some(since)).await?;
+        let found = discrepancies(&trades, &orders);
+        for discrepancy in &found {
+            warn!(
+                "Order {} ({}) in {} is recorded as {} shares filled but the exchange matched {}",
+                discrepancy.order_id,
+                discrepancy.exchange_order_id,
+                discrepancy.market_id,
+                discrepancy.recorded,
+                discrepancy.exchange
+            );
+        }
+        metrics::global().gauge(
+            "polycopy_fill_discrepancies",
+            "Orders whose recorded fill disagrees with the exchange's trade history",
+            &[],
+            found.len() as f64,
+        );
+        Ok(found)
+    }
+
     /// Get current balance from CLOB client
     pub async fn get_balance(&self) -> Result<Decimal> {
         self.clob_client.get_balance().await
     }
 }
 
+/// Wait before retry `attempt` (from 1): a second, doubling each time up to
+/// [`MAX_RETRY_DELAY`]
+fn retry_delay(attempt: u32) -> Duration {
+    (RETRY_BASE_DELAY * 2u32.saturating_pow(attempt.saturating_sub(1))).min(MAX_RETRY_DELAY)
+}
+
+fn record_placement_error(error: &PolymarketError) {
+    let kind = match error {
+        PolymarketError::OrderOutcomeUnknown(_) => "unknown_outcome",
+        PolymarketError::RateLimited(_) => "rate_limited",
+        _ => "rejected",
+    };
+    metrics::global().increment(
+        "polycopy_order_placement_errors_total",
+        "Failed order placements, by whether the order may have gone through",
+        &[("kind", kind)],
+    );
+}
+
+/// Price for a post-only order: `improve_ticks` inside our side's best quote,
+/// but never at or through the other side, or None when our side is empty
+fn passive_price(
+    side: &OrderSide,
+    quotes: BestQuotes,
+    tick_size: Decimal,
+    improve_ticks: u32,
+) -> Option<Decimal> {
+    let improvement = tick_size * Decimal::from(improve_ticks);
+    match side {
+        OrderSide::Buy => {
+            let price = quotes.bid? + improvement;
+            Some(match quotes.ask {
+                Some(ask) if price >= ask => (ask - tick_size).max(quotes.bid?),
+                _ => price,
+            })
+        }
+        OrderSide::Sell => {
+            let price = quotes.ask? - improvement;
+            Some(match quotes.bid {
+                Some(bid) if price <= bid => (bid + tick_size).min(quotes.ask?),
+                _ => price,
+            })
+        }
+    }
+}
+
+/// `base` moved `offset` towards paying more (buys) or receiving less (sells),
+/// capped at `max_deviation` and kept inside the 0.01-0.99 price range
+fn ladder_price(
+    side: &OrderSide,
+    base: Decimal,
+    offset: Decimal,
+    max_deviation: Decimal,
+) -> Decimal {
+    let offset = offset.min(max_deviation);
+    let price = match side {
+        OrderSide::Buy => base + offset,
+        OrderSide::Sell => base - offset,
+    };
+    price.clamp(Decimal::new(1, 2), Decimal::new(99, 2))
+}
+
+/// The first of `tags` the event is tagged with
+fn matching_tag<'a>(event: Option<&EventInfo>, tags: &'a [String]) -> Option<&'a String> {
+    event.and_then(|event| tags.iter().find(|tag| event.has_tag(tag)))
+}
+
+/// Shares `usdc` buys at `price`, rounded down to the exchange's two decimals
+fn shares_for(usdc: Decimal, price: Decimal) -> Result<Decimal> {
+    if price <= Decimal::ZERO {
+        return Err(PolymarketError::ExecutionError(format!(
+            "Cannot size an order at a price of {}",
+            price
+        )));
+    }
+    Ok((usdc / price).round_dp_with_strategy(2, RoundingStrategy::ToZero))
+}
+
+/// Volume-weighted price of `legs`, or None if they matched nothing
+fn average_price(legs: &[FillLeg]) -> Option<Decimal> {
+    let shares: Decimal = legs.iter().map(|leg| leg.size).sum();
+    let cost: Decimal = legs.iter().map(|leg| leg.size * leg.price).sum();
+    (shares > Decimal::ZERO).then(|| cost / shares)
+}
+
+/// USDC our resting buy orders hold back from the balance: the shares each
+/// has left to fill at its limit price
+fn reserved_by_open_buys(orders: &[OrderRecord]) -> Decimal {
+    orders
+        .iter()
+        .filter(|o| o.side == OrderSide::Buy && !o.state.is_terminal())
+        .map(|o| {
+            let unfilled = (o.size - o.filled_size).max(Decimal::ZERO);
+            unfilled * o.price
+        })
+        .sum()
+}
+
+/// How much of `size` fits under `cap` on top of `exposure`, or None if nothing does
+fn room_under_cap(exposure: Decimal, size: Decimal, cap: Decimal) -> Option<Decimal> {
+    let room = cap - exposure;
+    (room > Decimal::ZERO).then(|| size.min(room))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::PositionSizingConfig;
+    use crate::config::{ConvictionConfig, PositionSizingConfig, VolatilityConfig};
     use crate::execution::signer::OrderSigner;
     use crate::models::OrderSide;
-    use chrono::Utc;
     use rust_decimal_macros::dec;
 
     #[test]
@@ -207,6 +1492,41 @@ mod tests {
             min_trade_size_usdc: dec!(5),
             max_trade_size_usdc: dec!(50000),
             poll_interval_seconds: 2,
+            detection_mode: "positions".to_string(),
+            max_detection_latency_seconds: 30,
+            max_trade_age_seconds: 0,
+            price_sum_check: "off".to_string(),
+            price_sum_tolerance: dec!(0.02),
+            aggregation_window_seconds: 0,
+            batch_window_ms: 0,
+            max_clock_skew_seconds: 10,
+            fill_check_hours: 0,
+            cancel_on_shutdown: true,
+            copy_delay_seconds: 0,
+            max_price_gap: dec!(0.05),
+            execution_style: "taker".to_string(),
+            passive_timeout_seconds: 30,
+            passive_improve_ticks: 0,
+            passive_escalate: true,
+            reprice_steps: 0,
+            reprice_step: dec!(0.01),
+            max_reprice_deviation: dec!(0.03),
+            event_tags: Vec::new(),
+            excluded_event_tags: Vec::new(),
+            duplicate_window_seconds: 0,
+            duplicate_size_tolerance: dec!(0.1),
+            api_credentials_file: "api_credentials.json".to_string(),
+            settlement_check_min_usdc: None,
+            settlement_timeout_seconds: 120,
+            max_concurrent_executions: 0,
+            one_order_per_market: true,
+            execution_priority: Vec::new(),
+            failed_copy_retry_seconds: 0,
+            failed_copy_retry_interval_seconds: 15,
+            failed_copy_max_price_move: dec!(0.05),
+            order_sweep_interval_seconds: 0,
+            max_open_order_age_seconds: 0,
+            cancel_after_trader_exit: true,
         };
 
         let signer = OrderSigner::new(
@@ -221,39 +1541,120 @@ mod tests {
             max_position_size_relative: dec!(0.1),
             strategy: "hybrid".to_string(),
             priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
         };
         let position_sizer = PositionSizer::new(position_sizing_config);
 
         let executor = OrderExecutor::new(clob_client, position_sizer, config);
 
         // Trade within limits
-        let trade = Trade {
-            id: "test".to_string(),
-            market_id: "market1".to_string(),
-            trader: "0x0000000000000000000000000000000000000000"
-                .parse()
-                .unwrap(),
-            side: OrderSide::Buy,
-            price: dec!(0.5),
-            size: dec!(100),
-            size_usdc: dec!(50),
-            timestamp: Utc::now(),
-            trader_win_rate: None,
-        };
+        let trade = Trade::sample().with_id("test").with_market("market1");
         assert!(executor.should_copy_trade(&trade));
 
         // Trade too small
-        let small_trade = Trade {
-            size_usdc: dec!(1),
-            ..trade.clone()
-        };
+        let small_trade = trade.clone().with_fill(dec!(0.5), dec!(2));
         assert!(!executor.should_copy_trade(&small_trade));
 
         // Trade too large
-        let large_trade = Trade {
-            size_usdc: dec!(100000),
-            ..trade.clone()
-        };
+        let large_trade = trade.clone().with_fill(dec!(0.5), dec!(200000));
         assert!(!executor.should_copy_trade(&large_trade));
     }
+
+    #[test]
+    fn test_room_under_event_cap() {
+        assert_eq!(
+            room_under_cap(dec!(0), dec!(100), dec!(500)),
+            Some(dec!(100))
+        );
+        assert_eq!(
+            room_under_cap(dec!(450), dec!(100), dec!(500)),
+            Some(dec!(50))
+        );
+        assert_eq!(room_under_cap(dec!(500), dec!(100), dec!(500)), None);
+        assert_eq!(room_under_cap(dec!(650), dec!(100), dec!(500)), None);
+    }
+
+    #[test]
+    fn test_open_buys_reserve_their_unfilled_cost() {
+        let order = |side: OrderSide, state: OrderState, shares, filled| {
+            let mut order = OrderRecord::for_market("m1", side, dec!(0.4), shares, OrderType::GTC);
+            order.state = state;
+            order.filled_size = filled;
+            order
+        };
+        let orders = [
+            // 150 of 250 shares left at 0.40
+            order(
+                OrderSide::Buy,
+                OrderState::PartiallyFilled,
+                dec!(250),
+                dec!(100),
+            ),
+            order(OrderSide::Buy, OrderState::Open, dec!(50), dec!(0)),
+            order(OrderSide::Buy, OrderState::Filled, dec!(500), dec!(500)),
+            order(OrderSide::Sell, OrderState::Open, dec!(500), dec!(0)),
+        ];
+        assert_eq!(reserved_by_open_buys(&orders), dec!(80));
+        assert_eq!(reserved_by_open_buys(&[]), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_passive_price_stays_on_our_side_of_the_spread() {
+        let quotes = BestQuotes {
+            bid: Some(dec!(0.45)),
+            ask: Some(dec!(0.50)),
+        };
+        let tick = dec!(0.01);
+        assert_eq!(
+            passive_price(&OrderSide::Buy, quotes, tick, 0),
+            Some(dec!(0.45))
+        );
+        assert_eq!(
+            passive_price(&OrderSide::Buy, quotes, tick, 2),
+            Some(dec!(0.47))
+        );
+        assert_eq!(
+            passive_price(&OrderSide::Sell, quotes, tick, 2),
+            Some(dec!(0.48))
+        );
+        // Improving into the other side stops a tick short of it
+        assert_eq!(
+            passive_price(&OrderSide::Buy, quotes, tick, 10),
+            Some(dec!(0.49))
+        );
+        assert_eq!(
+            passive_price(&OrderSide::Sell, quotes, tick, 10),
+            Some(dec!(0.46))
+        );
+        // Nothing to join on an empty side
+        let no_bids = BestQuotes {
+            bid: None,
+            ask: Some(dec!(0.50)),
+        };
+        assert_eq!(passive_price(&OrderSide::Buy, no_bids, tick, 0), None);
+    }
+
+    #[test]
+    fn test_ladder_price_is_capped() {
+        assert_eq!(
+            ladder_price(&OrderSide::Buy, dec!(0.50), dec!(0.02), dec!(0.03)),
+            dec!(0.52)
+        );
+        assert_eq!(
+            ladder_price(&OrderSide::Buy, dec!(0.50), dec!(0.05), dec!(0.03)),
+            dec!(0.53)
+        );
+        assert_eq!(
+            ladder_price(&OrderSide::Sell, dec!(0.50), dec!(0.02), dec!(0.03)),
+            dec!(0.48)
+        );
+        assert_eq!(
+            ladder_price(&OrderSide::Buy, dec!(0.98), dec!(0.03), dec!(0.03)),
+            dec!(0.99)
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use crate::config::PositionSizingConfig;
 use crate::errors::{PolymarketError, Result};
+use crate::models::SkipReason;
 use rust_decimal::Decimal;
 use std::cmp::min;
 
@@ -12,6 +13,10 @@ impl PositionSizer {
         Self { config }
     }
 
+    pub fn config(&self) -> &PositionSizingConfig {
+        &self.config
+    }
+
     /// Calculate the actual position size to execute based on the target trade size and current balance
     pub fn calculate_position_size(
         &self,
@@ -55,10 +60,73 @@ impl PositionSizer {
             return Err(PolymarketError::BelowMinimumSize);
         }
 
+        // Copies of a few cents cost more in fees and API calls than they're worth
+        let minimum = self.config.min_copy_size_usdc;
+        if size < minimum {
+            if self.config.below_minimum != "round_up" || minimum > current_balance {
+                return Err(PolymarketError::BelowMinimumSize);
+            }
+            size = minimum;
+        }
+
         Ok(size)
     }
 
+    /// Size to place a buy of `size` USDC at with `available` USDC free;
+    /// `market_minimum` is the smallest order the market takes, in USDC
+    ///
+    /// With `insufficient_balance = "scale_down"` a copy costing more than is
+    /// free is cut down to what is, unless that falls below the market
+    /// minimum or `min_copy_size_usdc`; otherwise it keeps its size.
+    pub fn fit_to_balance(
+        &self,
+        size: Decimal,
+        available: Decimal,
+        market_minimum: Decimal,
+    ) -> Result<Decimal> {
+        if size <= available || self.config.insufficient_balance != "scale_down" {
+            return Ok(size);
+        }
+        let minimum = market_minimum.max(self.config.min_copy_size_usdc);
+        if available <= Decimal::ZERO || available < minimum {
+            return Err(PolymarketError::Skipped(
+                SkipReason::InsufficientBalance,
+                format!(
+                    "only {} USDC is free for a {} USDC copy, below the {} USDC minimum",
+                    available.max(Decimal::ZERO).round_dp(2),
+                    size.round_dp(2),
+                    minimum.round_dp(2)
+                ),
+            ));
+        }
+        Ok(available)
+    }
+
+    /// How much to scale a copy when the trader bet `conviction` of their
+    /// portfolio (1 when conviction sizing is off)
+    pub fn conviction_multiplier(&self, conviction: Decimal) -> Decimal {
+        let config = &self.config.conviction;
+        if !config.enabled {
+            return Decimal::ONE;
+        }
+        (conviction / config.baseline).clamp(config.min_multiplier, config.max_multiplier)
+    }
+
+    /// How much to shrink a copy entered at `price` in a market whose price
+    /// has a standard deviation of `volatility`
+    ///
+    /// A one-sigma move costs `volatility / price` of the position's value;
+    /// copies are scaled down so that stays within `target_risk`, and never up.
+    pub fn volatility_multiplier(&self, price: Decimal, volatility: Decimal) -> Decimal {
+        let config = &self.config.volatility;
+        if !config.enabled || price <= Decimal::ZERO || volatility <= Decimal::ZERO {
+            return Decimal::ONE;
+        }
+        (config.target_risk * price / volatility).min(Decimal::ONE)
+    }
+
     /// Check if a trade size is within configured limits
+    #[allow(dead_code)]
     pub fn is_size_acceptable(&self, size: Decimal, min_size: Decimal, max_size: Decimal) -> bool {
         size >= min_size && size <= max_size
     }
@@ -67,6 +135,7 @@ impl PositionSizer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{ConvictionConfig, VolatilityConfig};
     use rust_decimal_macros::dec;
 
     #[test]
@@ -76,6 +145,11 @@ mod tests {
             max_position_size_relative: dec!(0.1),
             strategy: "absolute".to_string(),
             priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
         };
 
         let sizer = PositionSizer::new(config);
@@ -100,6 +174,11 @@ mod tests {
             max_position_size_relative: dec!(0.1),
             strategy: "relative".to_string(),
             priority: "relative".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
         };
 
         let sizer = PositionSizer::new(config);
@@ -124,6 +203,11 @@ mod tests {
             max_position_size_relative: dec!(0.1),
             strategy: "hybrid".to_string(),
             priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
         };
 
         let sizer = PositionSizer::new(config);
@@ -142,6 +226,11 @@ mod tests {
             max_position_size_relative: dec!(0.1),
             strategy: "absolute".to_string(),
             priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
         };
 
         let sizer = PositionSizer::new(config);
@@ -150,4 +239,150 @@ mod tests {
         assert!(!sizer.is_size_acceptable(dec!(5), dec!(10), dec!(500)));
         assert!(!sizer.is_size_acceptable(dec!(1000), dec!(10), dec!(500)));
     }
+
+    #[test]
+    fn test_conviction_multiplier() {
+        let config = PositionSizingConfig {
+            max_position_size_absolute: dec!(1000),
+            max_position_size_relative: dec!(0.1),
+            strategy: "absolute".to_string(),
+            priority: "absolute".to_string(),
+            conviction: ConvictionConfig {
+                enabled: true,
+                ..ConvictionConfig::default()
+            },
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
+        };
+        let sizer = PositionSizer::new(config.clone());
+
+        // Baseline 5%: a 10% bet is copied at double size, 0.5% at the floor
+        assert_eq!(sizer.conviction_multiplier(dec!(0.10)), dec!(2));
+        assert_eq!(sizer.conviction_multiplier(dec!(0.05)), dec!(1));
+        assert_eq!(sizer.conviction_multiplier(dec!(0.005)), dec!(0.25));
+        assert_eq!(sizer.conviction_multiplier(dec!(0.5)), dec!(3));
+
+        let off = PositionSizer::new(PositionSizingConfig {
+            conviction: ConvictionConfig::default(),
+            ..config
+        });
+        assert_eq!(off.conviction_multiplier(dec!(0.5)), dec!(1));
+    }
+
+    #[test]
+    fn test_volatility_multiplier() {
+        let sizer = PositionSizer::new(PositionSizingConfig {
+            max_position_size_absolute: dec!(1000),
+            max_position_size_relative: dec!(0.1),
+            strategy: "absolute".to_string(),
+            priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig {
+                enabled: true,
+                target_risk: dec!(0.05),
+                ..VolatilityConfig::default()
+            },
+            min_copy_size_usdc: Decimal::ZERO,
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
+        });
+
+        // A stable 0.9 market moving 0.01 is within the 5% target
+        assert_eq!(sizer.volatility_multiplier(dec!(0.9), dec!(0.01)), dec!(1));
+        // A 0.5 market swinging 0.1 risks 20% per sigma: copy a quarter
+        assert_eq!(
+            sizer.volatility_multiplier(dec!(0.5), dec!(0.1)),
+            dec!(0.25)
+        );
+        assert_eq!(sizer.volatility_multiplier(dec!(0.5), dec!(0)), dec!(1));
+    }
+
+    #[test]
+    fn test_dust_copies() {
+        let config = PositionSizingConfig {
+            max_position_size_absolute: dec!(1000),
+            max_position_size_relative: dec!(0.01),
+            strategy: "relative".to_string(),
+            priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: dec!(1),
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
+        };
+
+        // 1% of a 30 USDC balance is 30 cents
+        let skip = PositionSizer::new(config.clone());
+        assert!(matches!(
+            skip.calculate_position_size(dec!(50), dec!(30)),
+            Err(PolymarketError::BelowMinimumSize)
+        ));
+        assert_eq!(
+            skip.calculate_position_size(dec!(50), dec!(500)).unwrap(),
+            dec!(5)
+        );
+
+        let round_up = PositionSizer::new(PositionSizingConfig {
+            below_minimum: "round_up".to_string(),
+            ..config
+        });
+        assert_eq!(
+            round_up
+                .calculate_position_size(dec!(50), dec!(30))
+                .unwrap(),
+            dec!(1)
+        );
+        // Never rounded up past what we can afford
+        assert!(round_up
+            .calculate_position_size(dec!(50), dec!(0.5))
+            .is_err());
+    }
+
+    #[test]
+    fn test_scaling_down_to_the_free_balance() {
+        let config = PositionSizingConfig {
+            max_position_size_absolute: dec!(1000),
+            max_position_size_relative: dec!(0.1),
+            strategy: "absolute".to_string(),
+            priority: "absolute".to_string(),
+            conviction: ConvictionConfig::default(),
+            volatility: VolatilityConfig::default(),
+            min_copy_size_usdc: dec!(1),
+            below_minimum: "skip".to_string(),
+            insufficient_balance: "fail".to_string(),
+        };
+        let fail = PositionSizer::new(config.clone());
+        assert_eq!(
+            fail.fit_to_balance(dec!(500), dec!(120), dec!(2.5))
+                .unwrap(),
+            dec!(500)
+        );
+
+        let scale_down = PositionSizer::new(PositionSizingConfig {
+            insufficient_balance: "scale_down".to_string(),
+            ..config
+        });
+        assert_eq!(
+            scale_down
+                .fit_to_balance(dec!(100), dec!(120), dec!(2.5))
+                .unwrap(),
+            dec!(100)
+        );
+        assert_eq!(
+            scale_down
+                .fit_to_balance(dec!(500), dec!(120), dec!(2.5))
+                .unwrap(),
+            dec!(120)
+        );
+        // Less than the market takes, or nothing free at all
+        for available in [dec!(2), dec!(-30)] {
+            let skipped = scale_down.fit_to_balance(dec!(500), available, dec!(2.5));
+            assert!(matches!(
+                skipped,
+                Err(PolymarketError::Skipped(SkipReason::InsufficientBalance, _))
+            ));
+        }
+    }
 }
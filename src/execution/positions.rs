@@ -0,0 +1,143 @@
+use crate::execution::lifecycle::OrderRecord;
+use crate::models::OrderSide;
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Outcome tokens held in one market
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Holding {
+    pub shares: Decimal,
+    /// What the current shares cost, at average entry price
+    pub cost: Decimal,
+    /// When the first buy of the current position was placed; a position sold
+    /// out and bought again counts from the new buy
+    pub opened_at: Option<DateTime<Utc>>,
+}
+
+impl Holding {
+    pub fn average_price(&self) -> Decimal {
+        if self.shares.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.cost / self.shares
+        }
+    }
+
+    /// Value of the position at `price`
+    pub fn value_at(&self, price: Decimal) -> Decimal {
+        self.shares * price
+    }
+}
+
+/// Net holdings per market, rebuilt from the fills recorded on `orders`
+///
+/// Buys add shares at their fill price; sells remove shares at the average
+/// cost. Tokens can't be shorted, so a market never goes below zero. Fills
+/// still awaiting their on-chain settlement check are left out.
+pub fn holdings(orders: &[OrderRecord]) -> HashMap<String, Holding> {
+    let mut holdings: HashMap<String, Holding> = HashMap::new();
+    for order in orders
+        .iter()
+        .filter(|o| o.filled_size > Decimal::ZERO && o.settled())
+    {
+        let holding = holdings.entry(order.market_id.clone()).or_default();
+        match order.side {
+            OrderSide::Buy => {
+                if holding.shares.is_zero() {
+                    holding.opened_at = Some(order.created_at);
+                }
+                holding.shares += order.filled_size;
+                holding.cost += order.filled_size * order.fill_price.unwrap_or(order.price);
+            }
+            OrderSide::Sell => {
+                let sold = order.filled_size.min(holding.shares);
+                holding.cost -= sold * holding.average_price();
+                holding.shares -= sold;
+                if holding.shares.is_zero() {
+                    holding.opened_at = None;
+                }
+            }
+        }
+    }
+    holdings.retain(|_, holding| holding.shares > Decimal::ZERO);
+    holdings
+}
+
+/// Holdings built only from the orders that copied `trader`
+///
+/// Several traders can share a market, so this is our position that mirrors
+/// theirs, used to scale copies of their exits.
+pub fn copied_holdings(orders: &[OrderRecord], trader: Address) -> HashMap<String, Holding> {
+    let copied: Vec<OrderRecord> = orders
+        .iter()
+        .filter(|o| o.trader == Some(trader))
+        .cloned()
+        .collect();
+    holdings(&copied)
+}
+
+/// Shares to sell so our copy shrinks by the `fraction` the trader sold
+pub fn exit_shares(copied: Decimal, fraction: Decimal) -> Decimal {
+    if fraction >= Decimal::ONE {
+        copied
+    } else {
+        (copied * fraction.max(Decimal::ZERO)).round_dp(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderType;
+    use rust_decimal_macros::dec;
+
+    fn filled(market: &str, side: OrderSide, price: Decimal, size: Decimal) -> OrderRecord {
+        let mut order = OrderRecord::for_market(market, side, price, size, OrderType::FOK);
+        order.filled_size = size;
+        order
+    }
+
+    #[test]
+    fn test_holdings_net_buys_and_sells() {
+        let orders = vec![
+            filled("m1", OrderSide::Buy, dec!(0.30), dec!(100)),
+            filled("m1", OrderSide::Buy, dec!(0.50), dec!(100)),
+            filled("m1", OrderSide::Sell, dec!(0.70), dec!(50)),
+            filled("m2", OrderSide::Buy, dec!(0.40), dec!(10)),
+            filled("m2", OrderSide::Sell, dec!(0.45), dec!(25)),
+            OrderRecord::for_market("m3", OrderSide::Buy, dec!(0.5), dec!(10), OrderType::FOK),
+        ];
+
+        let holdings = holdings(&orders);
+        assert_eq!(holdings.len(), 1);
+        let m1 = &holdings["m1"];
+        assert_eq!(m1.shares, dec!(150));
+        assert_eq!(m1.average_price(), dec!(0.40));
+        assert_eq!(m1.value_at(dec!(0.70)), dec!(105));
+        assert_eq!(m1.opened_at, Some(orders[0].created_at));
+    }
+
+    #[test]
+    fn test_exit_scales_the_copy_of_that_trader() {
+        let alice = Address::from_low_u64_be(1);
+        let copied_from = |trader: Address, side: OrderSide, size: Decimal| {
+            let mut order = filled("m1", side, dec!(0.50), size);
+            order.trader = Some(trader);
+            order
+        };
+        let orders = vec![
+            copied_from(alice, OrderSide::Buy, dec!(200)),
+            copied_from(Address::from_low_u64_be(2), OrderSide::Buy, dec!(300)),
+            filled("m1", OrderSide::Sell, dec!(0.60), dec!(50)),
+        ];
+
+        // Alice selling 40% of her position trims 40% of what we copied from her
+        let copied = copied_holdings(&orders, alice)["m1"].shares;
+        assert_eq!(copied, dec!(200));
+        assert_eq!(exit_shares(copied, dec!(0.4)), dec!(80));
+        assert_eq!(exit_shares(copied, dec!(1)), dec!(200));
+        assert_eq!(holdings(&orders)["m1"].shares, dec!(450));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::execution::lifecycle::OrderRecord;
+use crate::models::OrderSide;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// What our fills cost against one reference price
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cost {
+    /// Shares filled where the reference is known
+    pub shares: Decimal,
+    /// USDC paid beyond the reference; negative when we did better
+    pub usdc: Decimal,
+}
+
+impl Cost {
+    fn add(&mut self, side: &OrderSide, fill: Decimal, reference: Decimal, shares: Decimal) {
+        let worse = match side {
+            OrderSide::Buy => fill - reference,
+            OrderSide::Sell => reference - fill,
+        };
+        self.shares += shares;
+        self.usdc += worse * shares;
+    }
+
+    /// Average USDC per share paid beyond the reference
+    pub fn per_share(&self) -> Option<Decimal> {
+        (self.shares > Decimal::ZERO).then(|| self.usdc / self.shares)
+    }
+}
+
+/// Our fill prices against the trader's price, the midpoint when the trade
+/// was detected and our own limit price
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExecutionQuality {
+    pub fills: usize,
+    pub shares: Decimal,
+    pub vs_trader: Cost,
+    pub vs_midpoint: Cost,
+    pub vs_limit: Cost,
+}
+
+impl ExecutionQuality {
+    fn add(&mut self, order: &OrderRecord, fill: Decimal) {
+        let shares = order.filled_size;
+        self.fills += 1;
+        self.shares += shares;
+        if let Some(price) = order.trader_price {
+            self.vs_trader.add(&order.side, fill, price, shares);
+        }
+        if let Some(midpoint) = order.detection_midpoint {
+            self.vs_midpoint.add(&order.side, fill, midpoint, shares);
+        }
+        self.vs_limit.add(&order.side, fill, order.price, shares);
+    }
+}
+
+/// How a copy order was placed: "passive" (post-only entry), "reprice"
+/// (a step of the re-pricing ladder) or "taker"
+pub fn style(order: &OrderRecord) -> &'static str {
+    let detail = |prefix: &str| {
+        order
+            .transitions
+            .iter()
+            .filter_map(|t| t.detail.as_deref())
+            .any(|d| d.starts_with(prefix))
+    };
+    if detail("passive entry") {
+        "passive"
+    } else if detail("re-price") {
+        "reprice"
+    } else {
+        "taker"
+    }
+}
+
+/// Execution quality of the copy orders that filled, by style and over all
+/// of them ("all")
+pub fn execution_quality(orders: &[OrderRecord]) -> BTreeMap<&'static str, ExecutionQuality> {
+    let mut quality: BTreeMap<&'static str, ExecutionQuality> = BTreeMap::new();
+    let filled = orders
+        .iter()
+        .filter(|order| !order.trade_id.is_empty() && order.filled_size > Decimal::ZERO);
+    for order in filled {
+        let Some(fill) = order.fill_price else {
+            continue;
+        };
+        quality.entry(style(order)).or_default().add(order, fill);
+        quality.entry("all").or_default().add(order, fill);
+    }
+    quality
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::lifecycle::OrderState;
+    use crate::models::{OrderType, Trade};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fill_costs_against_each_reference() {
+        let fill = |side: OrderSide, limit: Decimal, fill: Decimal, detail: Option<&str>| {
+            let trade = Trade::sample()
+                .with_id("t1")
+                .with_side(side)
+                .with_midpoint(dec!(0.52));
+            let mut order = OrderRecord::new(&trade, dec!(100), OrderType::GTC);
+            order.price = limit;
+            order
+                .advance(OrderState::Submitted, detail.map(str::to_string))
+                .unwrap();
+            order.advance(OrderState::Filled, None).unwrap();
+            order.filled_size = dec!(100);
+            order.fill_price = Some(fill);
+            order
+        };
+        let taker = fill(OrderSide::Buy, dec!(0.50), dec!(0.53), None);
+        let passive = fill(
+            OrderSide::Buy,
+            dec!(0.51),
+            dec!(0.51),
+            Some("passive entry"),
+        );
+        // A sell at 0.49 gives up a cent to the trader's 0.50
+        let exit = fill(OrderSide::Sell, dec!(0.49), dec!(0.49), None);
+        let mut unfilled = fill(OrderSide::Buy, dec!(0.50), dec!(0.50), None);
+        unfilled.filled_size = Decimal::ZERO;
+
+        let quality = execution_quality(&[taker, passive, exit, unfilled]);
+        let all = &quality["all"];
+        assert_eq!(all.fills, 3);
+        assert_eq!(all.vs_trader.usdc, dec!(3) + dec!(1) + dec!(1));
+        assert_eq!(all.vs_midpoint.per_share(), Some(dec!(0.01)));
+
+        let passive = &quality["passive"];
+        assert_eq!(passive.vs_trader.per_share(), Some(dec!(0.01)));
+        assert_eq!(passive.vs_midpoint.per_share(), Some(dec!(-0.01)));
+        assert_eq!(passive.vs_limit.per_share(), Some(Decimal::ZERO));
+        assert_eq!(quality["taker"].vs_limit.usdc, dec!(3));
+        assert_eq!(quality["taker"].fills, 2);
+    }
+
+    #[test]
+    fn test_partial_fills_count_the_shares_matched() {
+        let order = |trade: &Trade, filled: Decimal, fill: Option<Decimal>| {
+            let mut order = OrderRecord::new(trade, dec!(100), OrderType::GTC);
+            order.advance(OrderState::Submitted, None).unwrap();
+            order.advance(OrderState::PartiallyFilled, None).unwrap();
+            order.filled_size = filled;
+            order.fill_price = fill;
+            order
+        };
+        // Detected without a midpoint, paying 2 cents over the trader's 0.50
+        let trade = Trade::sample();
+        let quarter = order(&trade, dec!(25), Some(dec!(0.52)));
+        let most = order(&trade, dec!(75), Some(dec!(0.50)));
+        // Filled shares whose price isn't known yet are left out
+        let unpriced = order(&trade, dec!(40), None);
+        // As is an order that copies no trade
+        let mut rebalance =
+            OrderRecord::for_market("m1", OrderSide::Buy, dec!(0.5), dec!(10), OrderType::GTC);
+        rebalance.filled_size = dec!(10);
+        rebalance.fill_price = Some(dec!(0.9));
+
+        let quality = execution_quality(&[quarter, most, unpriced, rebalance]);
+        let all = &quality["all"];
+        assert_eq!(all.fills, 2);
+        assert_eq!(all.shares, dec!(100));
+        assert_eq!(all.vs_trader.usdc, dec!(0.5));
+        assert_eq!(all.vs_trader.per_share(), Some(dec!(0.005)));
+        assert_eq!(all.vs_midpoint, Cost::default());
+        assert_eq!(all.vs_midpoint.per_share(), None);
+    }
+
+    #[test]
+    fn test_no_fills_no_quality() {
+        let trade = Trade::sample();
+        let mut cancelled = OrderRecord::new(&trade, dec!(100), OrderType::GTC);
+        cancelled.advance(OrderState::Submitted, None).unwrap();
+        cancelled.advance(OrderState::Cancelled, None).unwrap();
+
+        assert!(execution_quality(&[]).is_empty());
+        assert!(execution_quality(&[cancelled]).is_empty());
+    }
+}
@@ -0,0 +1,379 @@
+use crate::config::ExecutionConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::execution::weighting::TraderWeights;
+use crate::metrics;
+use crate::models::Trade;
+use crate::monitoring::MarketDirectory;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tracing::info;
+
+/// What decides which waiting copy gets the next free slot
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Priority {
+    /// Larger trader trades first
+    TradeSize,
+    /// Traders with a higher copy ratio first
+    TraderWeight,
+    /// Markets resolving later first; those without an end date count as latest
+    TimeToResolution,
+}
+
+impl Priority {
+    pub fn parse(priority: &str) -> Result<Self> {
+        match priority {
+            "trade_size" => Ok(Priority::TradeSize),
+            "trader_weight" => Ok(Priority::TraderWeight),
+            "time_to_resolution" => Ok(Priority::TimeToResolution),
+            other => Err(PolymarketError::ConfigError(format!(
+                "execution_priority must list 'trade_size', 'trader_weight' or \
+                 'time_to_resolution', not '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Limits how many copies execute at once, overall and in each market; when
+/// every slot is busy, waiting copies get the next free one in priority order
+/// rather than arrival order
+pub struct ExecutionQueue {
+    priorities: Vec<Priority>,
+    markets: Arc<MarketDirectory>,
+    weights: Option<Arc<TraderWeights>>,
+    slots: Arc<Slots>,
+}
+
+impl ExecutionQueue {
+    pub fn from_config(config: &ExecutionConfig, markets: Arc<MarketDirectory>) -> Result<Self> {
+        Ok(Self {
+            priorities: config
+                .execution_priority
+                .iter()
+                .map(|p| Priority::parse(p))
+                .collect::<Result<_>>()?,
+            markets,
+            weights: None,
+            slots: Arc::new(Slots {
+                limit: match config.max_concurrent_executions {
+                    0 => usize::MAX,
+                    limit => limit,
+                },
+                one_per_market: config.one_order_per_market,
+                state: Mutex::default(),
+            }),
+        })
+    }
+
+    /// Copy ratios to rank traders by for `trader_weight`
+    pub fn with_trader_weights(mut self, weights: Arc<TraderWeights>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Wait for a slot to execute a copy of `trade` in; it is freed when the
+    /// returned guard is dropped
+    pub async fn acquire(&self, trade: &Trade) -> Slot {
+        if let Some(slot) = self.slots.try_take(&trade.market_id) {
+            return slot;
+        }
+        // Only rank copies that have to wait
+        let rank = self.rank(trade).await;
+        let receiver = match self.slots.enqueue(&trade.market_id, rank) {
+            Ok(slot) => return slot,
+            Err(receiver) => receiver,
+        };
+        info!(
+            "No execution slot is free for market {}; trade {} waits with {} others",
+            trade.market_id,
+            trade.id,
+            self.slots.waiting() - 1
+        );
+        receiver
+            .await
+            .expect("slots hand over before dropping waiters")
+    }
+
+    /// Copies waiting for a slot
+    #[cfg(test)]
+    pub fn waiting(&self) -> usize {
+        self.slots.waiting()
+    }
+
+    /// `trade`'s standing in each priority, higher first
+    async fn rank(&self, trade: &Trade) -> Vec<Decimal> {
+        let mut rank = Vec::with_capacity(self.priorities.len());
+        for priority in &self.priorities {
+            rank.push(match priority {
+                Priority::TradeSize => trade.size_usdc,
+                Priority::TraderWeight => self
+                    .weights
+                    .as_ref()
+                    .map_or(Decimal::ONE, |w| w.copy_ratio(trade.trader)),
+                Priority::TimeToResolution => match self.markets.market(&trade.market_id).await {
+                    Ok(market) => market.end_date.map_or(Decimal::MAX, |end| {
+                        Decimal::from((end - Utc::now()).num_seconds())
+                    }),
+                    Err(_) => Decimal::MAX,
+                },
+            });
+        }
+        rank
+    }
+}
+
+/// A copy's place in the execution queue; dropping it hands the slot to the
+/// highest ranked waiter
+pub struct Slot {
+    slots: Option<Arc<Slots>>,
+    market_id: String,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if let Some(slots) = self.slots.take() {
+            slots.release(&self.market_id);
+        }
+    }
+}
+
+struct Slots {
+    limit: usize,
+    /// Whether a market's copies wait while one of them executes
+    one_per_market: bool,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    /// Markets a copy is executing in
+    busy: HashSet<String>,
+    waiting: Vec<Waiter>,
+    arrivals: u64,
+}
+
+impl State {
+    fn take(&mut self, market_id: &str) {
+        self.running += 1;
+        self.busy.insert(market_id.to_string());
+    }
+}
+
+impl Slots {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether a copy in `market_id` may start right away
+    fn free(&self, state: &State, market_id: &str) -> bool {
+        state.running < self.limit && !(self.one_per_market && state.busy.contains(market_id))
+    }
+
+    fn try_take(self: &Arc<Self>, market_id: &str) -> Option<Slot> {
+        let mut state = self.lock();
+        // Waiters are handed every slot they can use as it frees up, so none
+        // is ahead of a copy that finds one free
+        self.free(&state, market_id).then(|| {
+            state.take(market_id);
+            self.slot(market_id)
+        })
+    }
+
+    /// A slot if one is free, otherwise a place in the queue
+    ///
+    /// Copies waiting only on a busy market don't hold back those in others.
+    fn enqueue(
+        self: &Arc<Self>,
+        market_id: &str,
+        rank: Vec<Decimal>,
+    ) -> std::result::Result<Slot, oneshot::Receiver<Slot>> {
+        let mut state = self.lock();
+        if self.free(&state, market_id) {
+            state.take(market_id);
+            return Ok(self.slot(market_id));
+        }
+        let (sender, receiver) = oneshot::channel();
+        state.arrivals += 1;
+        let arrival = Reverse(state.arrivals);
+        state.waiting.push(Waiter {
+            market_id: market_id.to_string(),
+            rank,
+            arrival,
+            sender,
+        });
+        record_depth(state.waiting.len());
+        Err(receiver)
+    }
+
+    /// Hand a finished copy's slot, and its market, to the highest ranked
+    /// waiters that can now start
+    fn release(self: &Arc<Self>, market_id: &str) {
+        let mut state = self.lock();
+        state.running -= 1;
+        state.busy.remove(market_id);
+        loop {
+            let next = state
+                .waiting
+                .iter()
+                .enumerate()
+                .filter(|(_, waiter)| self.free(&state, &waiter.market_id))
+                .max_by(|(_, a), (_, b)| a.cmp(b))
+                .map(|(i, _)| i);
+            let Some(next) = next else { break };
+            let waiter = state.waiting.swap_remove(next);
+            match waiter.sender.send(self.slot(&waiter.market_id)) {
+                Ok(()) => state.take(&waiter.market_id),
+                // The waiter gave up; don't let its slot release again
+                Err(mut slot) => slot.slots = None,
+            }
+        }
+        record_depth(state.waiting.len());
+    }
+
+    fn waiting(&self) -> usize {
+        self.lock().waiting.len()
+    }
+
+    fn slot(self: &Arc<Self>, market_id: &str) -> Slot {
+        Slot {
+            slots: Some(self.clone()),
+            market_id: market_id.to_string(),
+        }
+    }
+}
+
+struct Waiter {
+    market_id: String,
+    rank: Vec<Decimal>,
+    /// Earlier arrivals first among equal ranks
+    arrival: Reverse<u64>,
+    sender: oneshot::Sender<Slot>,
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.rank, self.arrival).cmp(&(&other.rank, other.arrival))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+fn record_depth(waiting: usize) {
+    metrics::global().gauge(
+        "polycopy_execution_queue_depth",
+        "Copies waiting for an execution slot",
+        &[],
+        waiting as f64,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_waiting_copies_run_in_priority_order() {
+        let mut config = Config::load_from_file("config.example.toml")
+            .unwrap()
+            .execution;
+        config.max_concurrent_executions = 1;
+        config.execution_priority = vec!["trade_size".to_string()];
+        let markets = Arc::new(MarketDirectory::new("http://localhost".to_string()));
+        let queue = Arc::new(ExecutionQueue::from_config(&config, markets.clone()).unwrap());
+
+        let trade = |id: &str, size_usdc: Decimal| {
+            Trade::sample()
+                .with_id(id)
+                .with_fill(dec!(0.5), size_usdc * dec!(2))
+        };
+        let running = queue.acquire(&trade("first", dec!(1))).await;
+
+        let (sender, mut order) = tokio::sync::mpsc::unbounded_channel();
+        let waiters = [
+            ("small", dec!(10)),
+            ("large", dec!(500)),
+            ("medium", dec!(100)),
+            ("medium-later", dec!(100)),
+        ];
+        for (i, (id, size)) in waiters.into_iter().enumerate() {
+            let (waiter, sender) = (queue.clone(), sender.clone());
+            let trade = trade(id, size);
+            tokio::spawn(async move {
+                let _slot = waiter.acquire(&trade).await;
+                sender.send(trade.id).unwrap();
+            });
+            while queue.waiting() <= i {
+                tokio::task::yield_now().await;
+            }
+        }
+        drop(running);
+
+        let mut ran = Vec::new();
+        for _ in 0..4 {
+            ran.push(order.recv().await.unwrap());
+        }
+        assert_eq!(ran, ["large", "medium", "medium-later", "small"]);
+        assert_eq!(queue.waiting(), 0);
+
+        config.execution_priority = vec!["largest_first".to_string()];
+        assert!(ExecutionQueue::from_config(&config, markets).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_one_copy_per_market_executes_at_once() {
+        let mut config = Config::load_from_file("config.example.toml")
+            .unwrap()
+            .execution;
+        config.max_concurrent_executions = 0;
+        config.one_order_per_market = true;
+        let markets = Arc::new(MarketDirectory::new("http://localhost".to_string()));
+        let queue = Arc::new(ExecutionQueue::from_config(&config, markets.clone()).unwrap());
+
+        let trade = |id: &str, market_id: &str| {
+            Trade::sample()
+                .with_id(id)
+                .with_market(market_id)
+                .with_fill(dec!(0.5), dec!(20))
+        };
+        let running = queue.acquire(&trade("first", "m1")).await;
+
+        let waiter = queue.clone();
+        let stacked = tokio::spawn(async move { waiter.acquire(&trade("second", "m1")).await });
+        while queue.waiting() == 0 {
+            tokio::task::yield_now().await;
+        }
+        // Other markets don't wait behind the busy one
+        let other = queue.acquire(&trade("elsewhere", "m2")).await;
+        assert!(!stacked.is_finished());
+
+        drop(running);
+        let _second = stacked.await.unwrap();
+        assert_eq!(queue.waiting(), 0);
+        drop(other);
+
+        // Without the rule, copies in one market run side by side
+        config.one_order_per_market = false;
+        let unlimited = ExecutionQueue::from_config(&config, markets).unwrap();
+        let _first = unlimited.acquire(&trade("first", "m1")).await;
+        let _second = unlimited.acquire(&trade("second", "m1")).await;
+    }
+}
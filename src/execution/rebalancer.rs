@@ -0,0 +1,144 @@
+use crate::config::RiskConfig;
+use crate::coordination::Coordinator;
+use crate::errors::Result;
+use crate::execution::OrderExecutor;
+use crate::models::OrderFillStatus;
+use crate::monitoring::PriceCache;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Trims positions whose value has grown past the per-market exposure cap
+///
+/// A position is only trimmed once it exceeds the cap by `rebalance_band`,
+/// and then back down to the cap, so small moves around the limit don't
+/// cause a stream of sells. Each market also rests for the cooldown after
+/// a trim.
+pub struct Rebalancer {
+    executor: Arc<OrderExecutor>,
+    prices: Arc<PriceCache>,
+    config: RiskConfig,
+    coordinator: Option<Arc<Coordinator>>,
+    last_trim: HashMap<String, Instant>,
+}
+
+impl Rebalancer {
+    pub fn new(executor: Arc<OrderExecutor>, prices: Arc<PriceCache>, config: RiskConfig) -> Self {
+        Self {
+            executor,
+            prices,
+            config,
+            coordinator: None,
+            last_trim: HashMap::new(),
+        }
+    }
+
+    /// Only trim while this instance is the leader
+    pub fn with_coordinator(mut self, coordinator: Arc<Coordinator>) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    /// Check positions every `rebalance_interval_seconds`, forever
+    pub async fn run_periodic(mut self) {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.config.rebalance_interval_seconds));
+        loop {
+            interval.tick().await;
+            if self.coordinator.as_ref().is_some_and(|c| !c.is_leader()) {
+                continue;
+            }
+            if let Err(e) = self.run_once().await {
+                warn!("Rebalancing failed: {}", e);
+            }
+        }
+    }
+
+    /// Trim every position over the cap; returns the number of sells placed
+    pub async fn run_once(&mut self) -> Result<usize> {
+        let Some(cap) = self.config.max_market_exposure_usdc else {
+            return Ok(0);
+        };
+        let cooldown = Duration::from_secs(self.config.rebalance_cooldown_seconds);
+
+        let mut trims = 0;
+        for (market_id, holding) in self.executor.holdings().await? {
+            if self
+                .last_trim
+                .get(&market_id)
+                .is_some_and(|at| at.elapsed() < cooldown)
+            {
+                continue;
+            }
+            let Some(price) = self.prices.price(&market_id) else {
+                debug!("No current price for {}; not rebalancing it", market_id);
+                continue;
+            };
+            let Some(shares) = trim_size(holding.shares, price, cap, self.config.rebalance_band)
+            else {
+                continue;
+            };
+
+            info!(
+                "Position in {} is worth {} USDC (cap {}); selling {} shares at {}",
+                market_id,
+                holding.value_at(price),
+                cap,
+                shares,
+                price
+            );
+            self.last_trim.insert(market_id.clone(), Instant::now());
+            trims += 1;
+            match self
+                .executor
+                .reduce_position(&market_id, shares, price, "rebalance")
+                .await
+            {
+                Ok(OrderFillStatus::FullyFilled { .. }) => {}
+                Ok(status) => warn!("Rebalancing sell in {} ended {:?}", market_id, status),
+                Err(e) => warn!("Rebalancing sell in {} failed: {}", market_id, e),
+            }
+        }
+        Ok(trims)
+    }
+}
+
+/// Shares to sell so a position worth more than `cap * (1 + band)` is back at `cap`
+fn trim_size(shares: Decimal, price: Decimal, cap: Decimal, band: Decimal) -> Option<Decimal> {
+    if price <= Decimal::ZERO || shares * price <= cap * (Decimal::ONE + band) {
+        return None;
+    }
+    let excess = (shares - cap / price).round_dp(2);
+    (excess > Decimal::ZERO).then(|| excess.min(shares))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_trim_size_hysteresis() {
+        // 1000 shares bought at 0.30 against a 400 USDC cap
+        assert_eq!(
+            trim_size(dec!(1000), dec!(0.30), dec!(400), dec!(0.1)),
+            None
+        );
+        // Just over the cap but inside the band
+        assert_eq!(
+            trim_size(dec!(1000), dec!(0.44), dec!(400), dec!(0.1)),
+            None
+        );
+        // Ran to 0.70: trim back to exactly the cap
+        let trim = trim_size(dec!(1000), dec!(0.70), dec!(400), dec!(0.1)).unwrap();
+        assert_eq!(trim, dec!(428.57));
+        assert!((dec!(1000) - trim) * dec!(0.70) <= dec!(400.01));
+        // After trimming, a small further move stays inside the band
+        assert_eq!(
+            trim_size(dec!(571.43), dec!(0.75), dec!(400), dec!(0.1)),
+            None
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::config::ExecutionConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::metrics;
+use crate::models::{OrderSide, SkipReason, Trade};
+use crate::monitoring::PriceCache;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Gives copies that failed for a passing reason, such as a rate limit or a
+/// short balance, further tries for a while instead of dropping them
+pub struct CopyRetries {
+    window: Duration,
+    interval: Duration,
+    max_price_move: Decimal,
+    prices: Arc<PriceCache>,
+}
+
+impl CopyRetries {
+    pub fn from_config(config: &ExecutionConfig, prices: Arc<PriceCache>) -> Self {
+        Self {
+            window: Duration::from_secs(config.failed_copy_retry_seconds),
+            interval: Duration::from_secs(config.failed_copy_retry_interval_seconds),
+            max_price_move: config.failed_copy_max_price_move,
+            prices,
+        }
+    }
+
+    /// Wait to try `trade`'s copy again after it failed with `error`, its
+    /// first attempt having been at `since`; false when the failure won't
+    /// pass or the retry window is over
+    ///
+    /// Fails with a skip when the price moved too far against the copy while
+    /// it waited.
+    pub async fn wait(
+        &self,
+        trade: &Trade,
+        error: &PolymarketError,
+        since: Instant,
+    ) -> Result<bool> {
+        if !error.is_transient() {
+            return Ok(false);
+        }
+        let delay = match error {
+            PolymarketError::RateLimited(seconds) => {
+                self.interval.max(Duration::from_secs(*seconds))
+            }
+            _ => self.interval,
+        };
+        if since.elapsed() + delay > self.window {
+            info!(
+                "Giving up on trade {} after {}s of failures: {}",
+                trade.id,
+                since.elapsed().as_secs(),
+                error
+            );
+            record("expired");
+            return Ok(false);
+        }
+
+        info!(
+            "Copy of trade {} failed ({}); trying again in {}s",
+            trade.id,
+            error,
+            delay.as_secs()
+        );
+        tokio::time::sleep(delay).await;
+        if let Some(moved) = self.adverse_move(trade) {
+            if moved > self.max_price_move {
+                record("price_moved");
+                return Err(PolymarketError::Skipped(
+                    SkipReason::PriceMoved,
+                    format!(
+                        "price moved {} against the copy while it waited to be retried",
+                        moved
+                    ),
+                ));
+            }
+        }
+        record("retried");
+        Ok(true)
+    }
+
+    /// How far the current price is worse for the copy than the trader's
+    fn adverse_move(&self, trade: &Trade) -> Option<Decimal> {
+        let price = self.prices.price(&trade.market_id)?;
+        Some(match trade.side {
+            OrderSide::Buy => price - trade.price,
+            OrderSide::Sell => trade.price - price,
+        })
+    }
+}
+
+fn record(result: &str) {
+    metrics::global().increment(
+        "polycopy_copy_retries_total",
+        "Failed copies retried, or given up on as `expired` or `price_moved`",
+        &[("result", result)],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried_until_the_price_moves() {
+        let mut config = Config::load_from_file("config.example.toml")
+            .unwrap()
+            .execution;
+        config.failed_copy_retry_seconds = 60;
+        config.failed_copy_retry_interval_seconds = 0;
+        config.failed_copy_max_price_move = dec!(0.03);
+        let prices = Arc::new(PriceCache::new(Duration::from_secs(60)));
+        let retries = CopyRetries::from_config(&config, prices.clone());
+
+        let trade = Trade::sample().with_id("t1");
+        let now = Instant::now();
+        let rejected = PolymarketError::ApiError("Failed to place order: invalid".to_string());
+        assert!(!retries.wait(&trade, &rejected, now).await.unwrap());
+        let unknown = PolymarketError::OrderOutcomeUnknown("timed out".to_string());
+        assert!(!retries.wait(&trade, &unknown, now).await.unwrap());
+
+        let short = PolymarketError::ApiError("not enough balance / allowance".to_string());
+        prices.update("m1", dec!(0.52));
+        assert!(retries.wait(&trade, &short, now).await.unwrap());
+        prices.update("m1", dec!(0.54));
+        assert!(matches!(
+            retries.wait(&trade, &short, now).await,
+            Err(PolymarketError::Skipped(SkipReason::PriceMoved, _))
+        ));
+
+        // A rate limit waits at least as long as asked, which runs past the window
+        let started = Instant::now() - Duration::from_secs(50);
+        let limited = PolymarketError::RateLimited(30);
+        assert!(!retries.wait(&trade, &limited, started).await.unwrap());
+    }
+}
@@ -0,0 +1,337 @@
+use crate::config::RiskConfig;
+use crate::coordination::Coordinator;
+use crate::errors::Result;
+use crate::execution::positions::Holding;
+use crate::execution::OrderExecutor;
+use crate::models::OrderFillStatus;
+use crate::monitoring::{MarketDirectory, PriceCache};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How far below its peak a position may fall before the trailing stop fires
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrailDistance {
+    /// Absolute price distance (0.05 = five cents)
+    Price(Decimal),
+    /// Fraction of the peak price (0.1 = 10%)
+    Fraction(Decimal),
+}
+
+impl TrailDistance {
+    pub fn from_config(config: &RiskConfig) -> Option<Self> {
+        match (config.trailing_stop_cents, config.trailing_stop_percent) {
+            (Some(cents), _) => Some(TrailDistance::Price(cents / Decimal::ONE_HUNDRED)),
+            (None, Some(fraction)) => Some(TrailDistance::Fraction(fraction)),
+            (None, None) => None,
+        }
+    }
+
+    fn stop_below(&self, peak: Decimal) -> Decimal {
+        match self {
+            TrailDistance::Price(distance) => peak - distance,
+            TrailDistance::Fraction(fraction) => peak * (Decimal::ONE - fraction),
+        }
+    }
+}
+
+/// A stop that ratchets up with the best price seen and never moves down
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrailingStop {
+    pub peak: Decimal,
+    pub stop: Decimal,
+}
+
+impl TrailingStop {
+    pub fn new(entry_price: Decimal, distance: TrailDistance) -> Self {
+        Self {
+            peak: entry_price,
+            stop: distance.stop_below(entry_price),
+        }
+    }
+
+    /// Feed a new price; returns true once the price falls to the stop
+    pub fn update(&mut self, price: Decimal, distance: TrailDistance) -> bool {
+        if price > self.peak {
+            self.peak = price;
+            self.stop = self.stop.max(distance.stop_below(price));
+        }
+        price <= self.stop
+    }
+}
+
+/// The time-based exit rule a position has run into, if any
+///
+/// Positions copied from a trader who then goes quiet would otherwise be held
+/// until resolution; these rules close them regardless of price.
+pub fn holding_expiry(
+    config: &RiskConfig,
+    holding: &Holding,
+    end_date: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<&'static str> {
+    let held_too_long = config
+        .max_holding_days
+        .zip(holding.opened_at)
+        .is_some_and(|(days, opened_at)| now - opened_at >= chrono::Duration::days(days as i64));
+    if held_too_long {
+        return Some("max holding period");
+    }
+    let near_end = config
+        .exit_hours_before_end
+        .zip(end_date)
+        .is_some_and(|(hours, end)| end - now <= chrono::Duration::hours(hours as i64));
+    near_end.then_some("market end")
+}
+
+/// What the risk monitor does with a position when a rule fires
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RiskAction {
+    /// Sell the whole position, for the given reason
+    Sell(&'static str),
+    Hedge,
+}
+
+/// Watches held positions against the live price cache and exits or hedges
+/// them when a risk rule fires
+///
+/// Stops start at the average entry price and only track prices seen while
+/// running, so a restart resets each peak to the entry price. A position is
+/// treated as hedged while we also hold its complementary outcome, and hedged
+/// pairs are left alone.
+pub struct RiskMonitor {
+    executor: Arc<OrderExecutor>,
+    prices: Arc<PriceCache>,
+    config: RiskConfig,
+    markets: Option<Arc<MarketDirectory>>,
+    coordinator: Option<Arc<Coordinator>>,
+    stops: HashMap<String, TrailingStop>,
+}
+
+impl RiskMonitor {
+    pub fn new(executor: Arc<OrderExecutor>, prices: Arc<PriceCache>, config: RiskConfig) -> Self {
+        Self {
+            executor,
+            prices,
+            config,
+            markets: None,
+            coordinator: None,
+            stops: HashMap::new(),
+        }
+    }
+
+    /// Market metadata, needed to find the complementary token for hedges
+    pub fn with_markets(mut self, markets: Arc<MarketDirectory>) -> Self {
+        self.markets = Some(markets);
+        self
+    }
+
+    /// Only act while this instance is the leader
+    pub fn with_coordinator(mut self, coordinator: Arc<Coordinator>) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    /// Check positions every `check_interval_seconds`, forever
+    pub async fn run_periodic(mut self) {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.config.check_interval_seconds));
+        loop {
+            interval.tick().await;
+            if self.coordinator.as_ref().is_some_and(|c| !c.is_leader()) {
+                continue;
+            }
+            if let Err(e) = self.run_once().await {
+                warn!("Risk check failed: {}", e);
+            }
+        }
+    }
+
+    /// Evaluate every held position once; returns the number of orders placed
+    pub async fn run_once(&mut self) -> Result<usize> {
+        let distance = TrailDistance::from_config(&self.config);
+        let holdings = self.executor.holdings().await?;
+        self.stops
+            .retain(|market_id, _| holdings.contains_key(market_id));
+
+        let mut actions = 0;
+        for (market_id, holding) in &holdings {
+            let Some(price) = self.prices.price(market_id) else {
+                debug!("No current price for {}; skipping risk checks", market_id);
+                continue;
+            };
+            let complement = self.complement(market_id).await;
+            if complement
+                .as_ref()
+                .is_some_and(|token| holdings.contains_key(token))
+            {
+                // Both outcomes held: the pair pays out the same either way
+                continue;
+            }
+
+            let mut action = None;
+            if let Some(distance) = distance {
+                let stop = self
+                    .stops
+                    .entry(market_id.clone())
+                    .or_insert_with(|| TrailingStop::new(holding.average_price(), distance));
+                if stop.update(price, distance) {
+                    info!(
+                        "Trailing stop hit in {}: price {} <= stop {} (peak {})",
+                        market_id, price, stop.stop, stop.peak
+                    );
+                    action = Some(if self.config.stop_action == "hedge" {
+                        RiskAction::Hedge
+                    } else {
+                        RiskAction::Sell("trailing stop")
+                    });
+                }
+            }
+            if let Some(limit) = self.config.hedge_drawdown_percent {
+                let entry = holding.average_price();
+                if entry > Decimal::ZERO && (entry - price) / entry >= limit {
+                    info!(
+                        "Position in {} is down {} from entry {}",
+                        market_id,
+                        (entry - price) / entry,
+                        entry
+                    );
+                    action = Some(RiskAction::Hedge);
+                }
+            }
+            // Time limits close the position outright, even if a price rule
+            // would only have hedged it
+            let end_date = self.end_date(market_id).await;
+            if let Some(reason) = holding_expiry(&self.config, holding, end_date, Utc::now()) {
+                info!(
+                    "Position in {} hit its {} (opened {:?}, market ends {:?})",
+                    market_id, reason, holding.opened_at, end_date
+                );
+                action = Some(RiskAction::Sell(reason));
+            }
+
+            let result = match action {
+                None => continue,
+                Some(RiskAction::Sell(reason)) => {
+                    info!("Selling {} shares of {}", holding.shares, market_id);
+                    self.executor
+                        .reduce_position(market_id, holding.shares, price, reason)
+                        .await
+                }
+                Some(RiskAction::Hedge) => {
+                    let Some(complement) = complement else {
+                        warn!("Cannot hedge {}: complementary token unknown", market_id);
+                        continue;
+                    };
+                    // Both outcomes sum to about 1 when the complement has no quote yet
+                    self.prices.track(&complement);
+                    let hedge_price = self
+                        .prices
+                        .price(&complement)
+                        .unwrap_or(Decimal::ONE - price);
+                    info!(
+                        "Hedging {} shares of {} by buying {} at {}",
+                        holding.shares, market_id, complement, hedge_price
+                    );
+                    self.executor
+                        .hedge_position(&complement, holding.shares, hedge_price)
+                        .await
+                }
+            };
+            actions += 1;
+            // Stops stay armed, so anything left unfilled is retried next check
+            match result {
+                Ok(OrderFillStatus::FullyFilled { .. }) => {}
+                Ok(status) => warn!("Risk order in {} ended {:?}", market_id, status),
+                Err(e) => warn!("Risk order in {} failed: {}", market_id, e),
+            }
+        }
+        Ok(actions)
+    }
+
+    /// When the market closes, if the time-to-end rule needs it and market
+    /// metadata is available
+    async fn end_date(&self, token_id: &str) -> Option<DateTime<Utc>> {
+        self.config.exit_hours_before_end?;
+        let markets = self.markets.as_ref()?;
+        match markets.market(token_id).await {
+            Ok(market) => market.end_date,
+            Err(e) => {
+                debug!("No market metadata for {}: {}", token_id, e);
+                None
+            }
+        }
+    }
+
+    /// The other outcome's token, if market metadata is available
+    async fn complement(&self, token_id: &str) -> Option<String> {
+        let markets = self.markets.as_ref()?;
+        match markets.market(token_id).await {
+            Ok(market) => market.complement(token_id).map(|t| t.token_id.clone()),
+            Err(e) => {
+                debug!("No market metadata for {}: {}", token_id, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_trailing_stop_ratchets_up_only() {
+        let cents = TrailDistance::Price(dec!(0.05));
+        let mut stop = TrailingStop::new(dec!(0.40), cents);
+        assert_eq!(stop.stop, dec!(0.35));
+        assert!(!stop.update(dec!(0.55), cents));
+        assert_eq!(stop.stop, dec!(0.50));
+        // Pulling back doesn't lower the stop
+        assert!(!stop.update(dec!(0.52), cents));
+        assert_eq!(stop.stop, dec!(0.50));
+        assert!(stop.update(dec!(0.50), cents));
+
+        let percent = TrailDistance::Fraction(dec!(0.1));
+        let mut stop = TrailingStop::new(dec!(0.50), percent);
+        assert!(!stop.update(dec!(0.80), percent));
+        assert_eq!(stop.stop, dec!(0.72));
+        assert!(stop.update(dec!(0.71), percent));
+    }
+
+    #[test]
+    fn test_holding_expiry() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let config = RiskConfig {
+            max_holding_days: Some(7),
+            exit_hours_before_end: Some(24),
+            ..RiskConfig::default()
+        };
+        let opened = |days_ago: i64| Holding {
+            shares: dec!(100),
+            cost: dec!(50),
+            opened_at: Some(now - chrono::Duration::days(days_ago)),
+        };
+        let ends_in = |hours: i64| Some(now + chrono::Duration::hours(hours));
+
+        assert_eq!(holding_expiry(&config, &opened(2), ends_in(72), now), None);
+        assert_eq!(
+            holding_expiry(&config, &opened(7), ends_in(72), now),
+            Some("max holding period")
+        );
+        assert_eq!(
+            holding_expiry(&config, &opened(2), ends_in(12), now),
+            Some("market end")
+        );
+        // Without an end date only the holding period applies
+        assert_eq!(holding_expiry(&config, &opened(2), None, now), None);
+        assert_eq!(
+            holding_expiry(&RiskConfig::default(), &opened(30), ends_in(1), now),
+            None
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::errors::{PolymarketError, Result};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, Bytes, TransactionRequest};
+
+/// Selector of the Safe's `isOwner(address)`
+const IS_OWNER_SELECTOR: [u8; 4] = [0x2f, 0x54, 0xbf, 0x6e];
+
+/// Calldata asking a Safe whether `owner` is one of its owners
+pub fn is_owner_calldata(owner: Address) -> Bytes {
+    let mut data = IS_OWNER_SELECTOR.to_vec();
+    data.extend_from_slice(&[0u8; 12]);
+    data.extend_from_slice(owner.as_bytes());
+    data.into()
+}
+
+/// Decode an ABI-encoded `bool` return value
+pub fn decode_bool(output: &[u8]) -> Result<bool> {
+    match output {
+        [padding @ .., last] if padding.len() == 31 && padding.iter().all(|b| *b == 0) => {
+            match last {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(PolymarketError::BlockchainError(format!(
+                    "Invalid bool return value: {}",
+                    last
+                ))),
+            }
+        }
+        _ => Err(PolymarketError::BlockchainError(format!(
+            "Expected a 32-byte bool, got {} bytes",
+            output.len()
+        ))),
+    }
+}
+
+/// Check that `safe` is a deployed contract and `owner` can sign for it
+///
+/// Orders signed for a Safe the wallet doesn't own are rejected by the
+/// exchange one by one, so this is run before trading starts.
+pub async fn verify_safe(rpc_url: &str, safe: Address, owner: Address) -> Result<()> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| PolymarketError::ConfigError(format!("Invalid polygon_rpc_url: {}", e)))?;
+
+    let code = provider
+        .get_code(safe, None)
+        .await
+        .map_err(|e| PolymarketError::BlockchainError(e.to_string()))?;
+    if code.is_empty() {
+        return Err(PolymarketError::ConfigError(format!(
+            "safe_address {:?} has no contract deployed on Polygon",
+            safe
+        )));
+    }
+
+    let call = TransactionRequest::new()
+        .to(safe)
+        .data(is_owner_calldata(owner));
+    let output = provider.call(&call.into(), None).await.map_err(|e| {
+        PolymarketError::ConfigError(format!(
+            "safe_address {:?} doesn't look like a Gnosis Safe: {}",
+            safe, e
+        ))
+    })?;
+    if !decode_bool(&output)? {
+        return Err(PolymarketError::ConfigError(format!(
+            "Wallet {:?} is not an owner of the Safe {:?}",
+            owner, safe
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_owner_call_encoding() {
+        let owner: Address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+            .parse()
+            .unwrap();
+        let data = is_owner_calldata(owner);
+        assert_eq!(
+            format!("{}", data),
+            "0x2f54bf6e000000000000000000000000f39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+        );
+
+        let mut result = [0u8; 32];
+        assert!(!decode_bool(&result).unwrap());
+        result[31] = 1;
+        assert!(decode_bool(&result).unwrap());
+        result[0] = 1;
+        assert!(decode_bool(&result).is_err());
+        assert!(decode_bool(&[]).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::config::{BlackoutPeriod, ScheduleConfig};
+use crate::errors::{PolymarketError, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// Why the schedule doesn't allow a copy right now
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduleBlock {
+    /// Outside the configured trading days and hours
+    OutsideWindow,
+    /// In a blackout period, or too close to the market's end date
+    Blackout(String),
+}
+
+impl fmt::Display for ScheduleBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleBlock::OutsideWindow => write!(f, "outside the trading window"),
+            ScheduleBlock::Blackout(reason) => write!(f, "blackout: {}", reason),
+        }
+    }
+}
+
+/// Trading days, hours and blackout periods from `[schedule]`
+pub struct TradingSchedule {
+    timezone: Tz,
+    days: Vec<Weekday>,
+    window: Option<(NaiveTime, NaiveTime)>,
+    end_date_blackout: Duration,
+    blackouts: Vec<BlackoutPeriod>,
+    queue_outside_window: bool,
+}
+
+impl TradingSchedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self> {
+        let invalid = |msg: String| PolymarketError::ConfigError(msg);
+
+        let timezone = match config.timezone {
+            Some(ref name) => name
+                .parse::<Tz>()
+                .map_err(|_| invalid(format!("Unknown schedule timezone '{}'", name)))?,
+            None => Tz::UTC,
+        };
+        let days = config
+            .days
+            .iter()
+            .map(|day| {
+                day.parse::<Weekday>()
+                    .map_err(|_| invalid(format!("Invalid schedule day '{}'", day)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| invalid(format!("Invalid schedule time '{}', expected HH:MM", time)))
+        };
+        let window = match (&config.start_time, &config.end_time) {
+            (Some(start), Some(end)) => Some((parse_time(start)?, parse_time(end)?)),
+            (None, None) => None,
+            _ => {
+                return Err(invalid(
+                    "schedule start_time and end_time must be set together".to_string(),
+                ))
+            }
+        };
+        if let Some(period) = config.blackouts.iter().find(|p| p.start >= p.end) {
+            return Err(invalid(format!(
+                "Schedule blackout starting {} must end after it starts",
+                period.start
+            )));
+        }
+
+        Ok(Self {
+            timezone,
+            days,
+            window,
+            end_date_blackout: Duration::minutes(config.end_date_blackout_minutes as i64),
+            blackouts: config.blackouts.clone(),
+            queue_outside_window: config.queue_outside_window,
+        })
+    }
+
+    /// Whether trades detected outside the window wait for it to open
+    pub fn queues_outside_window(&self) -> bool {
+        self.queue_outside_window
+    }
+
+    /// Whether a copy into a market ending at `market_end` may be placed at `now`
+    pub fn check(
+        &self,
+        now: DateTime<Utc>,
+        market_end: Option<DateTime<Utc>>,
+    ) -> Option<ScheduleBlock> {
+        if let Some(period) = self
+            .blackouts
+            .iter()
+            .find(|p| p.start <= now && now < p.end)
+        {
+            let reason = period
+                .reason
+                .clone()
+                .unwrap_or_else(|| format!("until {}", period.end));
+            return Some(ScheduleBlock::Blackout(reason));
+        }
+        if let Some(end) = market_end {
+            if !self.end_date_blackout.is_zero() && end - now <= self.end_date_blackout {
+                return Some(ScheduleBlock::Blackout(format!("market ends at {}", end)));
+            }
+        }
+        (!self.in_window(now)).then_some(ScheduleBlock::OutsideWindow)
+    }
+
+    fn in_window(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.timezone);
+        if !self.days.is_empty() && !self.days.contains(&local.weekday()) {
+            return false;
+        }
+        match self.window {
+            Some((start, end)) if start <= end => {
+                let time = local.time();
+                start <= time && time < end
+            }
+            // Runs past midnight
+            Some((start, end)) => {
+                let time = local.time();
+                time >= start || time < end
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_window_in_timezone_and_blackouts() {
+        let config = ScheduleConfig {
+            enabled: true,
+            timezone: Some("America/New_York".to_string()),
+            days: vec!["mon".to_string(), "tue".to_string()],
+            start_time: Some("22:00".to_string()),
+            end_time: Some("02:00".to_string()),
+            end_date_blackout_minutes: 30,
+            blackouts: vec![BlackoutPeriod {
+                start: at("2024-03-05T03:00:00Z"),
+                end: at("2024-03-05T04:00:00Z"),
+                reason: Some("debate".to_string()),
+            }],
+            queue_outside_window: false,
+        };
+        let schedule = TradingSchedule::from_config(&config).unwrap();
+
+        // Monday 23:30 in New York is Tuesday 04:30 UTC
+        assert_eq!(schedule.check(at("2024-03-05T04:30:00Z"), None), None);
+        // Monday 18:00 in New York
+        assert_eq!(
+            schedule.check(at("2024-03-04T23:00:00Z"), None),
+            Some(ScheduleBlock::OutsideWindow)
+        );
+        // Monday 22:30 in New York, during the blackout
+        assert_eq!(
+            schedule.check(at("2024-03-05T03:30:00Z"), None),
+            Some(ScheduleBlock::Blackout("debate".to_string()))
+        );
+        // In the window, but the market closes in 20 minutes
+        assert!(matches!(
+            schedule.check(at("2024-03-05T04:30:00Z"), Some(at("2024-03-05T04:50:00Z"))),
+            Some(ScheduleBlock::Blackout(_))
+        ));
+
+        let bad = ScheduleConfig {
+            timezone: Some("Mars/Olympus".to_string()),
+            ..config
+        };
+        assert!(TradingSchedule::from_config(&bad).is_err());
+    }
+}
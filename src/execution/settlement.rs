@@ -0,0 +1,231 @@
+use crate::errors::{PolymarketError, Result};
+use crate::execution::approvals::{self, CTF_EXCHANGE, NEG_RISK_CTF_EXCHANGE};
+use crate::execution::lifecycle::{OrderRecord, Settlement};
+use crate::metrics;
+use chrono::Utc;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, Filter, Log, H256, U256};
+use ethers::utils::keccak256;
+use rust_decimal::Decimal;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// `OrderFilled(orderHash, maker, taker, makerAssetId, takerAssetId,
+/// makerAmountFilled, takerAmountFilled, fee)`, emitted by the exchange for
+/// each order a match settles
+const ORDER_FILLED: &str =
+    "OrderFilled(bytes32,address,address,uint256,uint256,uint256,uint256,uint256)";
+
+/// How often a pending settlement is looked for again
+const RECHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Extra blocks searched before the order was placed, for clock differences
+const BLOCK_MARGIN: u64 = 150;
+
+/// Shares the chain may settle short of the API's report and still count
+const SHARE_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+/// Checks the fills of large copies against the exchange's OrderFilled
+/// events before they count towards our positions
+pub struct SettlementVerifier {
+    provider: Provider<Http>,
+    min_notional: Decimal,
+    timeout: Duration,
+}
+
+impl SettlementVerifier {
+    /// Verify orders worth at least `min_notional` USDC, waiting up to
+    /// `timeout` for their settlement
+    pub fn new(rpc_url: &str, min_notional: Decimal, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            provider: approvals::provider(rpc_url)?,
+            min_notional,
+            timeout,
+        })
+    }
+
+    /// Whether `order` is large enough to be verified
+    pub fn applies_to(&self, order: &OrderRecord) -> bool {
+        order.size * order.price >= self.min_notional
+    }
+
+    /// Wait for the settlement of `order`'s fill, recording the outcome on it
+    ///
+    /// A fill the chain settles only in part is cut down to what settled; one
+    /// that doesn't show up before the timeout is marked unverified and left
+    /// out of our positions.
+    pub async fn verify(&self, order: &mut OrderRecord) {
+        let started = Instant::now();
+        let mut settled = Decimal::ZERO;
+        loop {
+            match self.settled_shares(order).await {
+                Ok(shares) => settled = shares,
+                Err(e) => warn!(
+                    "Failed to look up the settlement of order {}: {}",
+                    order.id, e
+                ),
+            }
+            if settled >= order.filled_size - SHARE_TOLERANCE {
+                info!("Order {} settled on-chain: {} shares", order.id, settled);
+                order.settlement = Some(Settlement::Verified);
+                record("verified");
+                return;
+            }
+            if started.elapsed() >= self.timeout {
+                break;
+            }
+            tokio::time::sleep(RECHECK_INTERVAL).await;
+        }
+
+        if settled > Decimal::ZERO {
+            warn!(
+                "Order {} is reported filled for {} shares but only {} settled on-chain; \
+                 recording {}",
+                order.id, order.filled_size, settled, settled
+            );
+            order.filled_size = settled;
+            order.settlement = Some(Settlement::Verified);
+            record("short");
+        } else {
+            error!(
+                "Order {} is reported filled for {} shares but no settlement was found \
+                 on-chain within {}s; not counting it towards positions",
+                order.id,
+                order.filled_size,
+                self.timeout.as_secs()
+            );
+            order.settlement = Some(Settlement::Unverified);
+            record("missing");
+        }
+    }
+
+    /// Shares the exchange has settled for `order` so far
+    async fn settled_shares(&self, order: &OrderRecord) -> Result<Decimal> {
+        let hash: H256 = order
+            .exchange_order_id
+            .as_deref()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| {
+                PolymarketError::ExecutionError(format!(
+                    "Order {} has no exchange order hash",
+                    order.id
+                ))
+            })?;
+        let latest = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| PolymarketError::BlockchainError(e.to_string()))?
+            .as_u64();
+        let elapsed = (Utc::now() - order.created_at).num_seconds().max(0) as u64;
+        let filter = Filter::new()
+            .address(vec![parse(CTF_EXCHANGE), parse(NEG_RISK_CTF_EXCHANGE)])
+            .topic0(H256::from(keccak256(ORDER_FILLED)))
+            .topic1(hash)
+            .from_block(from_block(latest, elapsed));
+        let logs = self
+            .provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| PolymarketError::BlockchainError(e.to_string()))?;
+        Ok(filled_shares(&logs))
+    }
+}
+
+/// First block to search for an order placed `elapsed` seconds ago
+///
+/// Polygon makes a block about every two seconds, so this reaches back at
+/// least as far as the order.
+fn from_block(latest: u64, elapsed: u64) -> u64 {
+    latest.saturating_sub(elapsed / 2 + BLOCK_MARGIN)
+}
+
+/// Outcome token shares our order received or gave up across `logs`
+///
+/// A maker asset id of zero means the order paid USDC for tokens, so the
+/// shares are the taker amount; otherwise it sold tokens, the maker amount.
+/// Both are in 6-decimal units.
+fn filled_shares(logs: &[Log]) -> Decimal {
+    logs.iter()
+        .filter(|log| !log.removed.unwrap_or(false))
+        .filter_map(|log| {
+            let word = |i: usize| {
+                log.data
+                    .get(i * 32..(i + 1) * 32)
+                    .map(U256::from_big_endian)
+            };
+            let maker_asset = word(0)?;
+            let amount = if maker_asset.is_zero() {
+                word(3)?
+            } else {
+                word(2)?
+            };
+            let units = amount.min(U256::from(i128::MAX as u128)).as_u128() as i128;
+            Decimal::try_from_i128_with_scale(units, 6).ok()
+        })
+        .sum::<Decimal>()
+        .normalize()
+}
+
+fn parse(address: &str) -> Address {
+    address.parse().expect("valid address")
+}
+
+fn record(result: &str) {
+    metrics::global().increment(
+        "polycopy_settlement_checks_total",
+        "On-chain settlement checks of large fills, by result",
+        &[("result", result)],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderSide, OrderType};
+    use ethers::types::Bytes;
+    use rust_decimal_macros::dec;
+
+    fn order_filled(maker_asset: u64, maker_amount: u64, taker_amount: u64) -> Log {
+        let mut data = Vec::new();
+        for word in [maker_asset, 7, maker_amount, taker_amount, 0] {
+            let mut bytes = [0u8; 32];
+            U256::from(word).to_big_endian(&mut bytes);
+            data.extend_from_slice(&bytes);
+        }
+        Log {
+            data: Bytes::from(data),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_settled_shares_from_order_filled_events() {
+        // Two matches of a buy paying 0.40: USDC out, tokens in
+        let buy = [
+            order_filled(0, 24_000_000, 60_000_000),
+            order_filled(0, 16_000_000, 40_000_000),
+        ];
+        assert_eq!(filled_shares(&buy), dec!(100));
+
+        // A sell gives up tokens for USDC
+        let mut sell = order_filled(7, 25_500_000, 15_300_000);
+        assert_eq!(filled_shares(&[sell.clone()]), dec!(25.5));
+        sell.removed = Some(true);
+        assert_eq!(filled_shares(&[sell]), Decimal::ZERO);
+
+        assert_eq!(from_block(60_000_000, 600), 60_000_000 - 450);
+        assert_eq!(from_block(100, 600), 0);
+
+        // Fills waiting on the check don't count until they are verified
+        let mut order =
+            OrderRecord::for_market("m1", OrderSide::Buy, dec!(0.4), dec!(100), OrderType::FOK);
+        assert!(order.settled());
+        order.settlement = Some(Settlement::Pending);
+        assert!(!order.settled());
+        order.settlement = Some(Settlement::Unverified);
+        assert!(!order.settled());
+        order.settlement = Some(Settlement::Verified);
+        assert!(order.settled());
+    }
+}
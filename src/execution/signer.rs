@@ -1,12 +1,55 @@
+use crate::config::GeneralConfig;
 use crate::errors::{PolymarketError, Result};
 use crate::models::Order;
-use ethers::signers::{LocalWallet, Signer};
-use ethers::types::{Address, H256};
-use std::sync::Arc;
+use ethers::signers::coins_bip39::English;
+use ethers::signers::{LocalWallet, MnemonicBuilder, Signer};
+use ethers::types::{Address, Signature, H256};
+use ethers::utils::{hash_message, keccak256};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::sync::{Arc, OnceLock};
+
+/// EIP-712 domain orders are signed in
+const DOMAIN_NAME: &str = "Polymarket CTF Exchange";
+const DOMAIN_VERSION: &str = "1";
+/// The CTF Exchange contract on Polygon
+const VERIFYING_CONTRACT: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+
+const DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const ORDER_TYPE: &str = "Order(string tokenId,uint256 price,uint256 quantity,uint8 side,\
+                          address maker,address signer,uint256 expiration,uint256 feeRateBps)";
+
+/// Prices and quantities are signed as integers of this many decimals, like USDC
+const AMOUNT_DECIMALS: u32 = 6;
+
+/// How the exchange verifies an order's signature
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureType {
+    /// Signed by the key that holds the funds
+    Eoa,
+    /// Funds in a Gnosis Safe, signed by one of its owners
+    GnosisSafe,
+}
+
+impl SignatureType {
+    /// Polymarket's code for the type
+    pub fn as_u8(self) -> u8 {
+        match self {
+            SignatureType::Eoa => 0,
+            SignatureType::GnosisSafe => 2,
+        }
+    }
+}
 
 pub struct OrderSigner {
     wallet: Arc<LocalWallet>,
+    #[allow(dead_code)]
     chain_id: u64,
+    /// Safe the orders are funded from, when not the wallet itself
+    safe: Option<Address>,
+    /// Hash of the EIP-712 domain, the same for every order
+    domain_separator: [u8; 32],
 }
 
 impl OrderSigner {
@@ -16,10 +59,88 @@ impl OrderSigner {
             .parse::<LocalWallet>()
             .map_err(|e| PolymarketError::SigningError(format!("Invalid private key: {}", e)))?;
 
-        Ok(Self {
+        Ok(Self::from_wallet(wallet, chain_id))
+    }
+
+    /// Derive the signing key from a BIP-39 mnemonic at `derivation_path/index`
+    pub fn from_mnemonic(
+        phrase: &str,
+        derivation_path: &str,
+        index: u32,
+        chain_id: u64,
+    ) -> Result<Self> {
+        let path = format!("{}/{}", derivation_path.trim_end_matches('/'), index);
+        let wallet = MnemonicBuilder::<English>::default()
+            .phrase(phrase.trim())
+            .derivation_path(&path)
+            .and_then(|builder| builder.build())
+            .map_err(|e| {
+                PolymarketError::SigningError(format!("Invalid mnemonic or path {}: {}", path, e))
+            })?;
+
+        Ok(Self::from_wallet(wallet, chain_id))
+    }
+
+    /// A signer with a fresh random key, for clients that only read market
+    /// data and never place orders
+    pub fn ephemeral(chain_id: u64) -> Self {
+        let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        Self::from_wallet(wallet, chain_id)
+    }
+
+    fn from_wallet(wallet: LocalWallet, chain_id: u64) -> Self {
+        Self {
             wallet: Arc::new(wallet),
             chain_id,
-        })
+            safe: None,
+            domain_separator: domain_separator(chain_id),
+        }
+    }
+
+    /// The signer `[general]` describes: the mnemonic when one is set,
+    /// otherwise the private key, signing for `safe_address` if given
+    pub fn from_config(config: &GeneralConfig, chain_id: u64) -> Result<Self> {
+        let signer = match config.wallet_mnemonic {
+            Some(ref phrase) => Self::from_mnemonic(
+                phrase,
+                &config.wallet_derivation_path,
+                config.wallet_account_index,
+                chain_id,
+            )?,
+            None => Self::new(&config.wallet_private_key, chain_id)?,
+        };
+        match config.safe_address()? {
+            Some(safe) => signer.with_safe(safe),
+            None => Ok(signer),
+        }
+    }
+
+    /// Sign orders funded from `safe`, which this wallet must be an owner of
+    pub fn with_safe(mut self, safe: Address) -> Result<Self> {
+        if safe == self.address() {
+            return Err(PolymarketError::ConfigError(
+                "safe_address is the signing wallet itself; leave it unset for a plain wallet"
+                    .to_string(),
+            ));
+        }
+        self.safe = Some(safe);
+        Ok(self)
+    }
+
+    /// Address orders are funded from and attributed to
+    pub fn maker(&self) -> Address {
+        self.safe.unwrap_or_else(|| self.address())
+    }
+
+    pub fn safe(&self) -> Option<Address> {
+        self.safe
+    }
+
+    pub fn signature_type(&self) -> SignatureType {
+        match self.safe {
+            Some(_) => SignatureType::GnosisSafe,
+            None => SignatureType::Eoa,
+        }
     }
 
     /// Get the wallet address
@@ -27,8 +148,16 @@ impl OrderSigner {
         self.wallet.address()
     }
 
-    /// Sign authentication message for API access (EIP-712)
-    pub async fn sign_auth_message(&self, timestamp: u64, nonce: u64) -> Result<String> {
+    /// The signing key, for sending transactions from the wallet
+    pub fn wallet(&self) -> LocalWallet {
+        self.wallet.as_ref().clone()
+    }
+
+    /// Sign authentication message for API access (EIP-191)
+    ///
+    /// Signing is pure computation, so this and [`OrderSigner::sign_order`]
+    /// don't await and can run on any thread.
+    pub fn sign_auth_message(&self, timestamp: u64, nonce: u64) -> Result<String> {
         let message = format!(
             "This message attests that I control the given wallet\nTimestamp: {}\nNonce: {}",
             timestamp, nonce
@@ -36,16 +165,14 @@ impl OrderSigner {
 
         let signature = self
             .wallet
-            .sign_message(message.as_bytes())
-            .await
+            .sign_hash(hash_message(message))
             .map_err(|e| PolymarketError::SigningError(format!("Failed to sign message: {}", e)))?;
 
-        Ok(format!("0x{}", hex::encode(signature.to_vec())))
+        Ok(signature_hex(&signature))
     }
 
     /// Sign an order using EIP-712 structured data hashing
-    pub async fn sign_order(&self, order: &Order) -> Result<String> {
-        // Note: This is a simplified version - actual implementation would match Polymarket's exact EIP-712 schema
+    pub fn sign_order(&self, order: &Order) -> Result<String> {
         let order_hash = self.hash_order(order)?;
 
         let signature = self
@@ -53,30 +180,101 @@ impl OrderSigner {
             .sign_hash(order_hash)
             .map_err(|e| PolymarketError::SigningError(format!("Failed to sign order: {}", e)))?;
 
-        Ok(format!("0x{}", hex::encode(signature.to_vec())))
+        Ok(signature_hex(&signature))
     }
 
-    /// Hash the order data according to EIP-712
+    /// The EIP-712 digest of an order: the precomputed domain separator and
+    /// the hash of the order's fields, each encoded as one 32-byte word
     fn hash_order(&self, order: &Order) -> Result<H256> {
-        // This is a simplified implementation
-        // In production, this would need to match Polymarket's exact EIP-712 schema
-        use ethers::utils::keccak256;
-
-        let mut data = Vec::new();
-        data.extend_from_slice(order.market_id.as_bytes());
-        data.extend_from_slice(&order.price_decimal.to_string().as_bytes());
-        data.extend_from_slice(&order.quantity.to_string().as_bytes());
-        data.extend_from_slice(&[match order.side {
-            crate::models::OrderSide::Buy => 0u8,
-            crate::models::OrderSide::Sell => 1u8,
-        }]);
-        data.extend_from_slice(order.owner.as_bytes());
-        data.extend_from_slice(&order.expiration_time.to_le_bytes());
-
-        Ok(H256::from_slice(&keccak256(&data)))
+        let mut encoded = [0u8; 9 * 32];
+        encode_words(
+            &mut encoded,
+            &[
+                order_typehash(),
+                keccak256(order.market_id.as_bytes()),
+                amount_word(order.price_decimal)?,
+                amount_word(order.quantity)?,
+                uint_word(match order.side {
+                    crate::models::OrderSide::Buy => 0,
+                    crate::models::OrderSide::Sell => 1,
+                }),
+                address_word(order.owner),
+                address_word(order.signer.unwrap_or(order.owner)),
+                uint_word(order.expiration_time as u128),
+                uint_word(order.fee_rate_bps as u128),
+            ],
+        );
+        let struct_hash = keccak256(encoded);
+
+        let mut digest = [0u8; 2 + 32 + 32];
+        digest[..2].copy_from_slice(b"\x19\x01");
+        digest[2..34].copy_from_slice(&self.domain_separator);
+        digest[34..].copy_from_slice(&struct_hash);
+        Ok(H256(keccak256(digest)))
     }
 }
 
+/// Hashed once per signer rather than for every order
+fn domain_separator(chain_id: u64) -> [u8; 32] {
+    let contract: Address = VERIFYING_CONTRACT
+        .parse()
+        .expect("verifying contract address is valid");
+    let mut encoded = [0u8; 5 * 32];
+    encode_words(
+        &mut encoded,
+        &[
+            keccak256(DOMAIN_TYPE),
+            keccak256(DOMAIN_NAME),
+            keccak256(DOMAIN_VERSION),
+            uint_word(chain_id as u128),
+            address_word(contract),
+        ],
+    );
+    keccak256(encoded)
+}
+
+fn order_typehash() -> [u8; 32] {
+    static TYPEHASH: OnceLock<[u8; 32]> = OnceLock::new();
+    *TYPEHASH.get_or_init(|| keccak256(ORDER_TYPE))
+}
+
+/// ABI-encode 32-byte words back to back into `buffer`, which fits them exactly
+fn encode_words(buffer: &mut [u8], words: &[[u8; 32]]) {
+    for (slot, word) in buffer.chunks_exact_mut(32).zip(words) {
+        slot.copy_from_slice(word);
+    }
+}
+
+fn uint_word(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn address_word(address: Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address.as_bytes());
+    word
+}
+
+/// A non-negative amount as an integer of [`AMOUNT_DECIMALS`] decimals
+fn amount_word(amount: Decimal) -> Result<[u8; 32]> {
+    (amount * Decimal::from(10u64.pow(AMOUNT_DECIMALS)))
+        .trunc()
+        .to_u128()
+        .map(uint_word)
+        .ok_or_else(|| PolymarketError::SigningError(format!("Can't sign an amount of {}", amount)))
+}
+
+/// "0x" and the 65 signature bytes in hex, in one allocation
+fn signature_hex(signature: &Signature) -> String {
+    let bytes: [u8; 65] = signature.into();
+    let mut hex = vec![0u8; 2 + 2 * 65];
+    hex[..2].copy_from_slice(b"0x");
+    hex::encode_to_slice(bytes, &mut hex[2..]).expect("buffer fits the signature");
+    String::from_utf8(hex).expect("hex is ASCII")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,16 +289,65 @@ mod tests {
         assert!(signer.is_ok());
     }
 
+    #[test]
+    fn test_signer_from_mnemonic() {
+        // The well-known development mnemonic and its first two accounts
+        let phrase = "test test test test test test test test test test test junk";
+        let path = "m/44'/60'/0'/0";
+        let first = OrderSigner::from_mnemonic(phrase, path, 0, 137).unwrap();
+        let second = OrderSigner::from_mnemonic(phrase, path, 1, 137).unwrap();
+
+        assert_eq!(
+            first.address(),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                .parse::<Address>()
+                .unwrap()
+        );
+        assert_eq!(
+            second.address(),
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+                .parse::<Address>()
+                .unwrap()
+        );
+        assert!(OrderSigner::from_mnemonic("not a mnemonic", path, 0, 137).is_err());
+    }
+
+    #[test]
+    fn test_signer_for_safe() {
+        let signer = OrderSigner::new(TEST_PRIVATE_KEY, 137).unwrap();
+        assert_eq!(signer.maker(), signer.address());
+        assert_eq!(signer.signature_type().as_u8(), 0);
+
+        let wallet = signer.address();
+        assert!(OrderSigner::new(TEST_PRIVATE_KEY, 137)
+            .unwrap()
+            .with_safe(wallet)
+            .is_err());
+
+        let safe = Address::from_low_u64_be(0x5afe);
+        let signer = signer.with_safe(safe).unwrap();
+        assert_eq!(signer.maker(), safe);
+        assert_eq!(signer.address(), wallet);
+        assert_eq!(signer.signature_type(), SignatureType::GnosisSafe);
+        assert_eq!(signer.signature_type().as_u8(), 2);
+    }
+
     #[tokio::test]
     async fn test_sign_auth_message() {
         let signer = OrderSigner::new(TEST_PRIVATE_KEY, 137).unwrap();
-        let signature = signer.sign_auth_message(1234567890, 0).await;
-        assert!(signature.is_ok());
-        assert!(signature.unwrap().starts_with("0x"));
+        let signature = signer.sign_auth_message(1234567890, 0).unwrap();
+        assert!(signature.starts_with("0x"));
+        assert_eq!(signature.len(), 132);
+
+        // The same signature the wallet's own EIP-191 signing gives
+        let message = "This message attests that I control the given wallet\nTimestamp: \
+                       1234567890\nNonce: 0";
+        let expected = signer.wallet.sign_message(message).await.unwrap();
+        assert_eq!(signature, format!("0x{}", hex::encode(expected.to_vec())));
     }
 
-    #[tokio::test]
-    async fn test_sign_order() {
+    #[test]
+    fn test_sign_order() {
         let signer = OrderSigner::new(TEST_PRIVATE_KEY, 137).unwrap();
         let order = Order {
             market_id: "test_market".to_string(),
@@ -108,11 +355,103 @@ mod tests {
             quantity: Decimal::new(100, 0),
             side: crate::models::OrderSide::Buy,
             owner: signer.address(),
+            signer: None,
+            expiration_time: 1234567890,
+            fee_rate_bps: 0,
+        };
+
+        let signature = signer.sign_order(&order).unwrap();
+        assert!(signature.starts_with("0x"));
+        let signature: Signature = signature.parse().unwrap();
+        let digest = signer.hash_order(&order).unwrap();
+        assert_eq!(signature.recover(digest).unwrap(), signer.address());
+
+        // The domain binds the signature to the chain
+        let other_chain = OrderSigner::new(TEST_PRIVATE_KEY, 80002).unwrap();
+        assert_ne!(other_chain.hash_order(&order).unwrap(), digest);
+    }
+
+    #[test]
+    fn test_order_digest_covers_the_fee_rate() {
+        use ethers::abi::{encode, Token};
+        use ethers::types::U256;
+
+        let signer = OrderSigner::new(TEST_PRIVATE_KEY, 137).unwrap();
+        let order = Order {
+            market_id: "test_market".to_string(),
+            price_decimal: Decimal::new(5, 1),
+            quantity: Decimal::new(100, 0),
+            side: crate::models::OrderSide::Sell,
+            owner: signer.address(),
+            signer: None,
             expiration_time: 1234567890,
+            fee_rate_bps: 200,
         };
 
-        let signature = signer.sign_order(&order).await;
-        assert!(signature.is_ok());
-        assert!(signature.unwrap().starts_with("0x"));
+        // The digest built from the type string with the ABI encoder
+        let contract: Address = VERIFYING_CONTRACT.parse().unwrap();
+        let domain = keccak256(encode(&[
+            Token::FixedBytes(keccak256(DOMAIN_TYPE).to_vec()),
+            Token::FixedBytes(keccak256(DOMAIN_NAME).to_vec()),
+            Token::FixedBytes(keccak256(DOMAIN_VERSION).to_vec()),
+            Token::Uint(U256::from(137)),
+            Token::Address(contract),
+        ]));
+        let type_string = "Order(string tokenId,uint256 price,uint256 quantity,uint8 side,\
+                           address maker,address signer,uint256 expiration,uint256 feeRateBps)";
+        let struct_hash = keccak256(encode(&[
+            Token::FixedBytes(keccak256(type_string).to_vec()),
+            Token::FixedBytes(keccak256("test_market").to_vec()),
+            Token::Uint(U256::from(500_000)),
+            Token::Uint(U256::from(100_000_000)),
+            Token::Uint(U256::from(1)),
+            Token::Address(signer.address()),
+            Token::Address(signer.address()),
+            Token::Uint(U256::from(1234567890)),
+            Token::Uint(U256::from(200)),
+        ]));
+        let expected = keccak256([&b"\x19\x01"[..], &domain, &struct_hash].concat());
+
+        let digest = signer.hash_order(&order).unwrap();
+        assert_eq!(digest, H256(expected));
+        assert_eq!(
+            format!("{:?}", digest),
+            "0x47d5228548b24cbac9558ca851b9af93de9224a685fa516319f19941526a6de9"
+        );
+
+        let unpriced = Order {
+            fee_rate_bps: 0,
+            ..order
+        };
+        assert_ne!(signer.hash_order(&unpriced).unwrap(), digest);
+    }
+
+    /// Benchmark; debug builds are an order of magnitude slower, so run with
+    /// `cargo test --release -- --ignored test_order_signing_throughput --nocapture`
+    #[test]
+    #[ignore]
+    fn test_order_signing_throughput() {
+        let signer = OrderSigner::new(TEST_PRIVATE_KEY, 137).unwrap();
+        let orders: Vec<Order> = (0..1000)
+            .map(|i| Order {
+                market_id: format!("{}", 1_000_000 + i),
+                price_decimal: Decimal::new(500 + i % 400, 3),
+                quantity: Decimal::new(10 + i, 0),
+                side: crate::models::OrderSide::Buy,
+                owner: signer.address(),
+                signer: None,
+                expiration_time: 1234567890 + i as u64,
+                fee_rate_bps: 0,
+            })
+            .collect();
+
+        let started = std::time::Instant::now();
+        for order in &orders {
+            signer.sign_order(order).unwrap();
+            signer.sign_auth_message(order.expiration_time, 0).unwrap();
+        }
+        let per_order = started.elapsed() / orders.len() as u32;
+        println!("{:?} to sign an order and its request", per_order);
+        assert!(per_order < std::time::Duration::from_millis(1));
     }
 }
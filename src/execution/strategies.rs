@@ -0,0 +1,258 @@
+use crate::config::Config;
+use crate::errors::{PolymarketError, Result};
+use crate::execution::attribution::label_pnl;
+use crate::execution::lifecycle::OrderRecord;
+use crate::execution::{OrderExecutor, PositionSizer};
+use crate::models::{OrderSide, SkipReason, Trade};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A `[[strategies]]` entry and the executor copying for it
+struct Strategy {
+    name: String,
+    traders: HashSet<Address>,
+    budget: Option<Decimal>,
+    executor: Arc<OrderExecutor>,
+}
+
+/// The copy strategies one process runs on the shared wallet: the top-level
+/// configuration and each `[[strategies]]` entry
+pub struct Strategies {
+    top_level: Arc<OrderExecutor>,
+    /// Traders in `[traders] tracked_accounts`
+    traders: HashSet<Address>,
+    strategies: Vec<Strategy>,
+}
+
+impl Strategies {
+    /// Strategies copying through executors derived from `top_level`
+    pub fn from_config(config: &Config, top_level: Arc<OrderExecutor>) -> Result<Self> {
+        let mut strategies = Vec::new();
+        for strategy in &config.strategies {
+            let sizing = strategy
+                .position_sizing
+                .clone()
+                .unwrap_or_else(|| config.position_sizing.clone());
+            let executor = top_level.for_strategy(
+                PositionSizer::new(sizing),
+                strategy.execution(&config.execution),
+            );
+            strategies.push(Strategy {
+                name: strategy.name.clone(),
+                traders: strategy.get_addresses()?.into_iter().collect(),
+                budget: strategy.budget_usdc,
+                executor: Arc::new(executor),
+            });
+        }
+        Ok(Self {
+            top_level,
+            traders: config.traders.get_addresses()?.into_iter().collect(),
+            strategies,
+        })
+    }
+
+    /// The copies to make of `trade`, one per strategy tracking its trader
+    ///
+    /// The top-level configuration copies the trades of its own traders and
+    /// of any trader no strategy tracks, such as trade signals. A strategy's
+    /// copy carries its name as a label and an id of its own, so each copy is
+    /// logged, claimed and reported separately.
+    pub fn route(&self, trade: &Trade) -> Vec<Route> {
+        let tracking: Vec<&Strategy> = self
+            .strategies
+            .iter()
+            .filter(|s| s.traders.contains(&trade.trader))
+            .collect();
+        let mut routes = Vec::new();
+        if tracking.is_empty() || self.traders.contains(&trade.trader) {
+            routes.push(Route {
+                trade: trade.clone(),
+                executor: self.top_level.clone(),
+                budget: None,
+            });
+        }
+        for strategy in tracking {
+            let mut copy = trade.clone();
+            copy.id = format!("{}:{}", trade.id, strategy.name);
+            if !copy.labels.contains(&strategy.name) {
+                copy.labels.push(strategy.name.clone());
+            }
+            routes.push(Route {
+                trade: copy,
+                executor: strategy.executor.clone(),
+                budget: strategy.budget.map(|b| (strategy.name.clone(), b)),
+            });
+        }
+        routes
+    }
+}
+
+/// Every trader a strategy tracks, the top-level configuration's first
+pub fn tracked_addresses(config: &Config) -> Result<Vec<Address>> {
+    let mut addresses = config.traders.get_addresses()?;
+    for strategy in &config.strategies {
+        for address in strategy.get_addresses()? {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+    Ok(addresses)
+}
+
+/// A detected trade as one strategy copies it
+pub struct Route {
+    pub trade: Trade,
+    pub executor: Arc<OrderExecutor>,
+    /// Name and budget of a strategy with a budget
+    budget: Option<(String, Decimal)>,
+}
+
+impl Route {
+    /// Balance to size the copy against: the wallet's, and for a buy no more
+    /// than is left of the strategy's budget
+    pub async fn balance(&self) -> Result<Decimal> {
+        let balance = self.executor.get_balance().await?;
+        let Some((ref name, budget)) = self.budget else {
+            return Ok(balance);
+        };
+        if self.trade.side != OrderSide::Buy {
+            return Ok(balance);
+        }
+        let deployed = deployed(&self.executor.orders().await?, name);
+        if deployed >= budget {
+            return Err(PolymarketError::Skipped(
+                SkipReason::StrategyBudget,
+                format!(
+                    "strategy {} has {} of its {} USDC budget in open positions",
+                    name,
+                    deployed.round_dp(2),
+                    budget
+                ),
+            ));
+        }
+        Ok(balance.min(budget - deployed))
+    }
+}
+
+/// Cost of the open positions copied for the strategy `name`
+fn deployed(orders: &[OrderRecord], name: &str) -> Decimal {
+    label_pnl(orders, |_| None, |_| None)
+        .into_iter()
+        .find(|row| row.label == name)
+        .map_or(Decimal::ZERO, |row| row.pnl.open_cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordination::{file_lock::FileLock, Coordinator};
+    use crate::execution::{ClobClient, OrderSigner};
+    use crate::models::OrderType;
+    use rust_decimal_macros::dec;
+    use std::time::Duration;
+
+    /// The example config's top level tracking `shared`, and a `whales`
+    /// strategy with a budget tracking `shared` and `whale`
+    fn whales(shared: Address, whale: Address) -> (Config, Arc<OrderExecutor>, Strategies) {
+        let mut config = Config::load_from_file("config.example.toml").unwrap();
+        config.traders.tracked_accounts = vec![format!("{:?}", shared)];
+        config.strategies = vec![toml::from_str(&format!(
+            "name = \"whales\"\ntracked_accounts = [\"{:?}\", \"{:?}\"]\n\
+                 budget_usdc = 100\nmax_trade_size_usdc = 50000",
+            shared, whale
+        ))
+        .unwrap()];
+
+        let signer = OrderSigner::new(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+            137,
+        )
+        .unwrap();
+        let client = ClobClient::new("http://localhost".to_string(), signer);
+        let sizer = PositionSizer::new(config.position_sizing.clone());
+        let top_level = Arc::new(OrderExecutor::new(client, sizer, config.execution.clone()));
+        let strategies = Strategies::from_config(&config, top_level.clone()).unwrap();
+        (config, top_level, strategies)
+    }
+
+    #[test]
+    fn test_trades_are_routed_to_the_strategies_tracking_the_trader() {
+        let (shared, whale, other) = (
+            Address::from_low_u64_be(1),
+            Address::from_low_u64_be(2),
+            Address::from_low_u64_be(3),
+        );
+        let (config, top_level, strategies) = whales(shared, whale);
+        assert_eq!(tracked_addresses(&config).unwrap(), [shared, whale]);
+
+        let trade = |trader: Address| {
+            Trade::sample()
+                .with_id("t1")
+                .with_trader(trader)
+                .with_fill(dec!(0.5), dec!(10))
+        };
+        let routes = strategies.route(&trade(shared));
+        let ids: Vec<&str> = routes.iter().map(|r| r.trade.id.as_str()).collect();
+        assert_eq!(ids, ["t1", "t1:whales"]);
+        assert!(Arc::ptr_eq(&routes[0].executor, &top_level));
+        assert!(routes[0].trade.labels.is_empty());
+        assert_eq!(routes[1].trade.labels, ["whales"]);
+
+        // Only the strategy copies its own traders; nobody's traders go to the top level
+        let routes = strategies.route(&trade(whale));
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].trade.id, "t1:whales");
+        assert_eq!(routes[0].budget, Some(("whales".to_string(), dec!(100))));
+        assert_eq!(strategies.route(&trade(other))[0].trade.id, "t1");
+
+        let filled = |side: OrderSide, price: Decimal, size: Decimal, labels: &[&str]| {
+            let mut order = OrderRecord::for_market("m1", side, price, size, OrderType::FOK);
+            order.filled_size = size;
+            order.labels = labels.iter().map(|l| l.to_string()).collect();
+            order
+        };
+        let orders = vec![
+            filled(OrderSide::Buy, dec!(0.40), dec!(100), &["whales"]),
+            filled(OrderSide::Buy, dec!(0.50), dec!(100), &[]),
+            filled(OrderSide::Sell, dec!(0.60), dec!(50), &["whales"]),
+        ];
+        assert_eq!(deployed(&orders, "whales"), dec!(20));
+        assert_eq!(deployed(&orders, "momentum"), Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_each_route_of_a_trade_wins_its_own_claim() {
+        let shared = Address::from_low_u64_be(1);
+        let (_, _, strategies) = whales(shared, Address::from_low_u64_be(2));
+        let dir = std::env::temp_dir().join(format!("locks-{}", uuid::Uuid::new_v4()));
+        let backend = Arc::new(FileLock::new(dir.to_str().unwrap()).unwrap());
+        let lease = Duration::from_secs(30);
+        let coordinator = Coordinator::new(backend, "a".to_string(), lease, lease);
+        assert!(coordinator.renew().await);
+
+        // Detected by a position diff, which another instance sees under an
+        // id of its own, or listed by the trade feed
+        let diffed = Trade::sample().with_trader(shared);
+        let listed = Trade::sample().with_id("0xaa:m1").with_trader(shared);
+        for (trade, elsewhere) in [
+            (
+                diffed.clone(),
+                diffed.with_id(&uuid::Uuid::new_v4().to_string()),
+            ),
+            (listed.clone(), listed),
+        ] {
+            let routes = strategies.route(&trade);
+            assert_eq!(routes.len(), 2);
+            for route in routes {
+                assert!(coordinator.should_execute(&route.trade).await.unwrap());
+            }
+            for route in strategies.route(&elsewhere) {
+                assert!(!coordinator.should_execute(&route.trade).await.unwrap());
+            }
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
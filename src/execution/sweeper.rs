@@ -0,0 +1,265 @@
+use crate::config::ExecutionConfig;
+use crate::coordination::Coordinator;
+use crate::errors::Result;
+use crate::execution::clob_client::OpenOrder;
+use crate::execution::lifecycle::OrderRecord;
+use crate::execution::OrderExecutor;
+use crate::metrics;
+use crate::models::OrderSide;
+use crate::monitoring::data_api::DataApiClient;
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Periodically cancels our resting orders that have outlived their purpose:
+/// those older than the age limit, and buys copying a trader who has since
+/// exited the token
+pub struct OrderSweeper {
+    executor: Arc<OrderExecutor>,
+    data_api: Option<DataApiClient>,
+    interval: Duration,
+    max_age: Option<Duration>,
+    coordinator: Option<Arc<Coordinator>>,
+}
+
+impl OrderSweeper {
+    pub fn from_config(config: &ExecutionConfig, executor: Arc<OrderExecutor>) -> Self {
+        Self {
+            executor,
+            data_api: None,
+            interval: Duration::from_secs(config.order_sweep_interval_seconds),
+            max_age: (config.max_open_order_age_seconds > 0)
+                .then(|| Duration::from_secs(config.max_open_order_age_seconds)),
+            coordinator: None,
+        }
+    }
+
+    /// Look up traders' positions here, to cancel the buys copying those
+    /// who exited
+    pub fn with_data_api(mut self, data_api: DataApiClient) -> Self {
+        self.data_api = Some(data_api);
+        self
+    }
+
+    /// Only sweep while this instance is the leader
+    pub fn with_coordinator(mut self, coordinator: Arc<Coordinator>) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    /// Sweep every `order_sweep_interval_seconds`, forever
+    pub async fn run_periodic(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            if self.coordinator.as_ref().is_some_and(|c| !c.is_leader()) {
+                continue;
+            }
+            if let Err(e) = self.sweep(Utc::now()).await {
+                warn!("Sweeping open orders failed: {}", e);
+            }
+        }
+    }
+
+    /// Cancel the stale open orders; returns how many were cancelled
+    pub async fn sweep(&self, now: DateTime<Utc>) -> Result<usize> {
+        let open = self.executor.open_orders().await?;
+        if open.is_empty() {
+            return Ok(0);
+        }
+        let records: HashMap<String, OrderRecord> = self
+            .executor
+            .orders()
+            .await?
+            .into_iter()
+            .filter_map(|order| Some((order.exchange_order_id.clone()?, order)))
+            .collect();
+        let holdings = self.trader_holdings(&open, &records).await;
+
+        let mut cancelled = 0;
+        for order in &open {
+            let record = records.get(&order.id);
+            let Some(reason) = stale(order, record, now, self.max_age, self.interval, &holdings)
+            else {
+                continue;
+            };
+            match self
+                .executor
+                .cancel_order(&order.id, reason.describe())
+                .await
+            {
+                Ok(()) => {
+                    info!(
+                        "Cancelled order {} on {}: {}",
+                        order.id,
+                        order.asset_id,
+                        reason.describe()
+                    );
+                    metrics::global().increment(
+                        "polycopy_swept_orders_total",
+                        "Open orders the sweeper cancelled, by reason",
+                        &[("reason", reason.as_str())],
+                    );
+                    cancelled += 1;
+                }
+                Err(e) => warn!("Failed to cancel stale order {}: {}", order.id, e),
+            }
+        }
+        Ok(cancelled)
+    }
+
+    /// Tokens still held by each trader a resting buy copies; traders whose
+    /// positions couldn't be fetched are left out, keeping their orders
+    async fn trader_holdings(
+        &self,
+        open: &[OpenOrder],
+        records: &HashMap<String, OrderRecord>,
+    ) -> HashMap<Address, HashSet<String>> {
+        let mut holdings = HashMap::new();
+        let Some(ref data_api) = self.data_api else {
+            return holdings;
+        };
+        let traders: HashSet<Address> = open
+            .iter()
+            .filter_map(|order| records.get(&order.id))
+            .filter(|record| record.side == OrderSide::Buy)
+            .filter_map(|record| record.trader)
+            .collect();
+        for trader in traders {
+            match data_api.positions(trader).await {
+                Ok(positions) => {
+                    let held = positions
+                        .into_iter()
+                        .filter(|p| p.size > Decimal::ZERO)
+                        .map(|p| p.asset)
+                        .collect();
+                    holdings.insert(trader, held);
+                }
+                Err(e) => warn!("Could not fetch the positions of {:?}: {}", trader, e),
+            }
+        }
+        holdings
+    }
+}
+
+/// Why a resting order is cancelled
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stale {
+    Expired,
+    TraderExited,
+}
+
+impl Stale {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Stale::Expired => "expired",
+            Stale::TraderExited => "trader_exited",
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            Stale::Expired => "open past max_open_order_age_seconds",
+            Stale::TraderExited => "the copied trader exited the position",
+        }
+    }
+}
+
+/// Whether `order` should be cancelled, given `record`, our record of it if
+/// we placed it, and the tokens traders hold
+///
+/// Orders younger than `grace` are kept whatever their trader holds, as the
+/// data API may not show a trade that was just copied yet.
+fn stale(
+    order: &OpenOrder,
+    record: Option<&OrderRecord>,
+    now: DateTime<Utc>,
+    max_age: Option<Duration>,
+    grace: Duration,
+    holdings: &HashMap<Address, HashSet<String>>,
+) -> Option<Stale> {
+    let age = order
+        .placed_at()
+        .and_then(|at| (now - at).to_std().ok())
+        .unwrap_or_default();
+    if max_age.is_some_and(|max| age > max) {
+        return Some(Stale::Expired);
+    }
+    let record = record?;
+    if record.side != OrderSide::Buy || age < grace {
+        return None;
+    }
+    let held = holdings.get(&record.trader?)?;
+    (!held.contains(&order.asset_id)).then_some(Stale::TraderExited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderType, Trade};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_expired_and_abandoned_orders_are_stale() {
+        let now = Utc::now();
+        let trader = Address::from_low_u64_be(1);
+        let order = |id: &str, minutes: i64| OpenOrder {
+            id: id.to_string(),
+            asset_id: "yes".to_string(),
+            created_at: (now - chrono::Duration::minutes(minutes)).timestamp(),
+        };
+        let copy = |side: OrderSide| {
+            let trade = Trade::sample()
+                .with_id("t1")
+                .with_market("yes")
+                .with_trader(trader)
+                .with_side(side)
+                .with_fill(dec!(0.4), dec!(50))
+                .with_timestamp(now);
+            OrderRecord::new(&trade, dec!(50), OrderType::GTC)
+        };
+        let max_age = Some(Duration::from_secs(3600));
+        let grace = Duration::from_secs(60);
+        let exited = HashMap::from([(trader, HashSet::new())]);
+        let holding = HashMap::from([(trader, HashSet::from(["yes".to_string()]))]);
+
+        // Past the age limit, whoever placed it
+        let old = order("old", 90);
+        assert_eq!(
+            stale(&old, None, now, max_age, grace, &holding),
+            Some(Stale::Expired)
+        );
+        assert_eq!(stale(&old, None, now, None, grace, &holding), None);
+
+        let buy = copy(OrderSide::Buy);
+        let recent = order("recent", 10);
+        assert_eq!(
+            stale(&recent, Some(&buy), now, max_age, grace, &exited),
+            Some(Stale::TraderExited)
+        );
+        assert_eq!(
+            stale(&recent, Some(&buy), now, max_age, grace, &holding),
+            None
+        );
+        // Positions that couldn't be fetched, a sell and a fresh order are kept
+        let unknown = HashMap::new();
+        assert_eq!(
+            stale(&recent, Some(&buy), now, max_age, grace, &unknown),
+            None
+        );
+        let sell = copy(OrderSide::Sell);
+        assert_eq!(
+            stale(&recent, Some(&sell), now, max_age, grace, &exited),
+            None
+        );
+        let fresh = order("fresh", 0);
+        assert_eq!(
+            stale(&fresh, Some(&buy), now, max_age, grace, &exited),
+            None
+        );
+    }
+}
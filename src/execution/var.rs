@@ -0,0 +1,196 @@
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// A held outcome token, as seen by the VaR estimate
+#[derive(Clone, Debug)]
+pub struct RiskPosition {
+    pub shares: Decimal,
+    /// Current price, read as the market's probability that this outcome wins
+    pub price: Decimal,
+    /// Market the token trades in; two tokens of one market move in opposite directions
+    pub condition_id: Option<String>,
+    pub event_id: Option<String>,
+}
+
+/// Assumed correlation between the outcomes of two different markets
+#[derive(Clone, Copy, Debug)]
+pub struct Correlations {
+    /// Markets in the same event
+    pub same_event: f64,
+    /// Markets in different (or unknown) events
+    pub cross_event: f64,
+}
+
+/// Downside of the portfolio if every market resolved now at its implied odds
+#[derive(Clone, Debug, Serialize)]
+pub struct VarEstimate {
+    pub confidence: Decimal,
+    /// Marked value of all positions (the most they can lose)
+    pub value: Decimal,
+    /// Standard deviation of the value at resolution
+    pub std_dev: Decimal,
+    pub value_at_risk: Decimal,
+    pub expected_shortfall: Decimal,
+}
+
+/// Parametric VaR and expected shortfall at `confidence`
+///
+/// Each position pays `shares` with probability `price` and nothing otherwise,
+/// so its expected value is its marked value and its standard deviation is
+/// `shares * sqrt(p * (1 - p))`. Positions are combined with the assumed
+/// correlations and the loss is treated as normal, which is rough for a few
+/// binary bets, so both figures are capped at the marked value.
+pub fn estimate_var(
+    positions: &[RiskPosition],
+    confidence: Decimal,
+    correlations: Correlations,
+) -> VarEstimate {
+    let sigmas: Vec<f64> = positions
+        .iter()
+        .map(|position| {
+            let p = position.price.to_f64().unwrap_or(0.0).clamp(0.0, 1.0);
+            position.shares.to_f64().unwrap_or(0.0) * (p * (1.0 - p)).sqrt()
+        })
+        .collect();
+
+    let mut variance = 0.0;
+    for (i, a) in positions.iter().enumerate() {
+        for (j, b) in positions.iter().enumerate() {
+            let rho = if i == j {
+                1.0
+            } else if a.condition_id.is_some() && a.condition_id == b.condition_id {
+                // Complementary outcomes of one market
+                -1.0
+            } else if a.event_id.is_some() && a.event_id == b.event_id {
+                correlations.same_event
+            } else {
+                correlations.cross_event
+            };
+            variance += rho * sigmas[i] * sigmas[j];
+        }
+    }
+    let std_dev = variance.max(0.0).sqrt();
+
+    let alpha = confidence.to_f64().unwrap_or(0.95);
+    let z = inverse_normal_cdf(alpha);
+    let value: Decimal = positions.iter().map(|p| p.shares * p.price).sum();
+    let to_decimal = |x: f64| {
+        Decimal::from_f64_retain(x)
+            .unwrap_or(Decimal::ZERO)
+            .round_dp(2)
+    };
+
+    VarEstimate {
+        confidence,
+        value: value.round_dp(2),
+        std_dev: to_decimal(std_dev),
+        value_at_risk: to_decimal(z * std_dev).min(value.round_dp(2)),
+        expected_shortfall: to_decimal(std_dev * normal_pdf(z) / (1.0 - alpha))
+            .min(value.round_dp(2)),
+    }
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Acklam's rational approximation of the standard normal quantile
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn position(price: Decimal, condition: &str, event: &str) -> RiskPosition {
+        RiskPosition {
+            shares: dec!(100),
+            price,
+            condition_id: Some(condition.to_string()),
+            event_id: Some(event.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_var_grows_with_correlation_and_hedges_cancel() {
+        assert!((inverse_normal_cdf(0.95) - 1.644854).abs() < 1e-5);
+
+        let independent = Correlations {
+            same_event: 0.0,
+            cross_event: 0.0,
+        };
+        let positions: Vec<_> = (0..4)
+            .map(|i| position(dec!(0.9), &format!("c{}", i), "e1"))
+            .collect();
+        // Four 30-share standard deviations add up to 60 when independent...
+        let spread = estimate_var(&positions, dec!(0.95), independent);
+        assert_eq!(spread.value, dec!(360));
+        assert_eq!(spread.std_dev, dec!(60));
+        assert_eq!(spread.value_at_risk, dec!(98.69));
+        // ...and to 120 when the event moves as one
+        let correlated = Correlations {
+            same_event: 1.0,
+            ..independent
+        };
+        let together = estimate_var(&positions, dec!(0.95), correlated);
+        assert_eq!(together.std_dev, dec!(120));
+        assert!(together.expected_shortfall > together.value_at_risk);
+
+        // Holding both outcomes of one market has no downside at resolution
+        let pair = vec![
+            position(dec!(0.6), "c1", "e1"),
+            position(dec!(0.4), "c1", "e1"),
+        ];
+        assert_eq!(
+            estimate_var(&pair, dec!(0.95), independent).value_at_risk,
+            dec!(0)
+        );
+    }
+}
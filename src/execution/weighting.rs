@@ -0,0 +1,152 @@
+use crate::config::{TradersConfig, WeightingConfig};
+use crate::errors::Result;
+use crate::models::Trade;
+use crate::monitoring::stats::{trader_performance, TraderPerformance};
+use crate::monitoring::{PriceCache, Resolutions};
+use crate::storage::TradeStore;
+use chrono::{Duration, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+/// Copy ratio per trader: the profile's `copy_ratio`, scaled by recent
+/// performance when adaptive weighting is on
+pub struct TraderWeights {
+    base: HashMap<Address, Decimal>,
+    adaptive: RwLock<HashMap<Address, Decimal>>,
+    config: WeightingConfig,
+    resolutions: Option<Arc<Resolutions>>,
+}
+
+impl TraderWeights {
+    pub fn from_config(traders: &TradersConfig) -> Result<Self> {
+        let base = traders
+            .profiles
+            .iter()
+            .map(|profile| Ok((profile.get_address()?, profile.copy_ratio)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            base,
+            adaptive: RwLock::new(HashMap::new()),
+            config: traders.weighting.clone(),
+            resolutions: None,
+        })
+    }
+
+    /// Judge positions held to resolution by their payout
+    pub fn with_resolutions(mut self, resolutions: Arc<Resolutions>) -> Self {
+        self.resolutions = Some(resolutions);
+        self
+    }
+
+    /// Multiplier for `trader`'s trade size before position sizing
+    pub fn copy_ratio(&self, trader: Address) -> Decimal {
+        let base = self.base.get(&trader).copied().unwrap_or(Decimal::ONE);
+        let adaptive = self
+            .adaptive
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&trader)
+            .copied()
+            .unwrap_or(Decimal::ONE);
+        base * adaptive
+    }
+
+    /// Recompute adaptive weights from the trades seen in the lookback window
+    pub fn update(&self, trades: &[Trade], prices: &PriceCache) {
+        let since = Utc::now() - Duration::days(self.config.lookback_days as i64);
+        let performance = trader_performance(
+            trades,
+            since,
+            |market| prices.price(market),
+            |market| self.resolutions.as_ref()?.payout(market),
+        );
+
+        let mut adaptive = self.adaptive.write().unwrap_or_else(|e| e.into_inner());
+        for (trader, stats) in &performance {
+            let weight = performance_weight(stats, &self.config);
+            let previous = adaptive.insert(*trader, weight).unwrap_or(Decimal::ONE);
+            if previous != weight {
+                info!(
+                    "Copy weight for {:?}: {} -> {} (ROI {} over {} trades)",
+                    trader,
+                    previous,
+                    weight,
+                    stats.roi().unwrap_or_default().round_dp(3),
+                    stats.trades
+                );
+            }
+        }
+        // Traders with no trades in the window go back to their base ratio
+        adaptive.retain(|trader, _| performance.contains_key(trader));
+    }
+
+    /// Refresh adaptive weights from the trade store every `refresh_minutes`
+    pub async fn run_periodic(
+        self: Arc<Self>,
+        store: Arc<dyn TradeStore>,
+        prices: Arc<PriceCache>,
+    ) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            self.config.refresh_minutes * 60,
+        ));
+        loop {
+            interval.tick().await;
+            match store.entries().await {
+                Ok(entries) => {
+                    let trades: Vec<Trade> = entries.into_iter().map(|e| e.trade).collect();
+                    self.update(&trades, &prices);
+                }
+                Err(e) => warn!("Failed to read trade history for weighting: {}", e),
+            }
+        }
+    }
+}
+
+/// `1 + roi * sensitivity`, clamped to the configured range; neutral until
+/// a trader has `min_trades` trades in the window
+pub fn performance_weight(stats: &TraderPerformance, config: &WeightingConfig) -> Decimal {
+    match stats.roi() {
+        Some(roi) if stats.trades >= config.min_trades => (Decimal::ONE + roi * config.sensitivity)
+            .clamp(config.min_weight, config.max_weight)
+            .round_dp(2),
+        _ => Decimal::ONE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_weight_follows_roi_within_bounds() {
+        let config = WeightingConfig::default();
+        let stats = |trades: usize, pnl: Decimal| TraderPerformance {
+            trades,
+            invested: dec!(1000),
+            pnl,
+            ..Default::default()
+        };
+
+        assert_eq!(performance_weight(&stats(10, dec!(0)), &config), dec!(1));
+        // Down 20%: 1 - 0.2 * 2
+        assert_eq!(
+            performance_weight(&stats(10, dec!(-200)), &config),
+            dec!(0.6)
+        );
+        // Losing streaks bottom out at min_weight, hot streaks top out at max_weight
+        assert_eq!(
+            performance_weight(&stats(10, dec!(-900)), &config),
+            dec!(0.25)
+        );
+        assert_eq!(
+            performance_weight(&stats(10, dec!(900)), &config),
+            dec!(1.5)
+        );
+        // Too few trades to judge
+        assert_eq!(performance_weight(&stats(2, dec!(-900)), &config), dec!(1));
+    }
+}
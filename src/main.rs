@@ -1,21 +1,68 @@
 mod backtest;
+mod cli;
 mod config;
+mod coordination;
+mod endpoints;
 mod errors;
 mod execution;
+mod metrics;
 mod models;
 mod monitoring;
+mod network;
+mod notifications;
+mod publishing;
+mod secrets;
 mod storage;
 
+use backtest::data::RecordedSession;
+use backtest::sweep::{
+    format_comparison_table, format_sweep_table, BacktestSweep, ParameterSet, SweepResult,
+};
 use backtest::BacktestEngine;
+use chrono::Utc;
 use clap::Parser;
 use config::Config;
-use errors::Result;
-use execution::{ClobClient, OrderExecutor, OrderSigner, PositionSizer};
-use monitoring::PollingMonitor;
-use storage::TradeLogger;
+use coordination::Coordinator;
+use endpoints::Endpoints;
+use errors::{PolymarketError, Result};
+use execution::attribution::PnlTracker;
+use execution::blacklist::Blacklist;
+use execution::credentials::CredentialRotation;
+use execution::drawdown::DrawdownMonitor;
+use execution::error_budget::{ErrorBudget, Operation};
+use execution::exposure::ExposureGauges;
+use execution::lifecycle::fee_amount;
+use execution::pause::PauseSwitch;
+use execution::queue::ExecutionQueue;
+use execution::retries::CopyRetries;
+use execution::schedule::{ScheduleBlock, TradingSchedule};
+use execution::settlement::SettlementVerifier;
+use execution::strategies::{self, Strategies};
+use execution::sweeper::OrderSweeper;
+use execution::weighting::TraderWeights;
+use execution::{ClobClient, OrderExecutor, OrderSigner, PositionSizer, Rebalancer, RiskMonitor};
+use models::{BacktestResults, BookFill, MarketLabel, SkipReason, Trade};
+use monitoring::data_api::DataApiClient;
+use monitoring::labels::TradeLabels;
+use monitoring::pruning::TraderPruner;
+use monitoring::trade_feed::{DetectionMode, TradeFeed};
+use monitoring::{
+    CopyDelay, DetectionLatency, MarketDirectory, Onboarding, PollingMonitor, PriceCache,
+    PriceFeed, Reconfirmation, ResolutionTracker, Resolutions, SignalListener, TrackedSetReloader,
+    TraderPortfolios, WashTradeDetector, WashVerdict,
+};
+use notifications::SlackNotifier;
+use publishing::EventPublisher;
+use rust_decimal::Decimal;
+use secrets::SecretFile;
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::{error, info, Level};
+use std::time::{Duration, Instant};
+use storage::audit_log::AuditLogger;
+use storage::book_recorder::BookRecorder;
+use storage::export::Exporter;
+use storage::gas_log::GasLog;
+use storage::{OrderStore, TradeLogEntry, TradeStore};
+use tracing::{debug, error, info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 #[derive(Parser, Debug)]
@@ -28,9 +75,22 @@ struct Args {
     #[arg(short, long, default_value = "config.toml")]
     config: String,
 
-    /// Operating mode (overrides config): live or backtest
+    /// Operating mode (overrides config): live, observe, backtest or record
     #[arg(short, long)]
     mode: Option<String>,
+
+    /// In backtest mode, also backtest this configuration file and compare the
+    /// results side by side; repeat for more
+    #[arg(long, value_name = "CONFIG")]
+    compare: Vec<String>,
+
+    /// In backtest mode, replay the dataset once per parameter set listed in
+    /// this sweep file and rank the results
+    #[arg(long, value_name = "FILE", conflicts_with = "compare")]
+    sweep: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<cli::Command>,
 }
 
 #[tokio::main]
@@ -41,14 +101,25 @@ async fn main() -> Result<()> {
     // Parse command line arguments
     let args = Args::parse();
 
-    // Load configuration
-    let mut config = Config::load_from_file(&args.config)?;
-    config.expand_env_vars()?;
+    // Offline tools don't need a full configuration; keep their output clean
+    if let Some(command) = args.command {
+        init_logging("warn")?;
+        return cli::run(command, &args.config).await;
+    }
 
-    // Override mode if specified in CLI
+    // Load configuration; problems are listed one per line, so print them plainly
+    let mut config = match Config::load_from_file(&args.config) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    // Override mode if specified in CLI; it decides whether the wallet key is needed
     if let Some(mode) = args.mode {
         config.general.mode = mode;
     }
+    config.expand_env_vars()?;
 
     // Initialize logging
     init_logging(&config.logging.level)?;
@@ -58,12 +129,18 @@ async fn main() -> Result<()> {
 
     // Run appropriate mode
     match config.general.mode.as_str() {
-        "live" => run_live_trading(config).await,
-        "backtest" => run_backtest(config).await,
+        "live" => run_live_trading(config, &args.config).await,
+        "backtest" if args.sweep.is_some() => {
+            run_backtest_sweep(config, args.sweep.as_deref().unwrap_or_default())
+        }
+        "backtest" if args.compare.is_empty() => run_backtest(config).await,
+        "backtest" => run_backtest_comparison(config, &args.config, &args.compare).await,
+        "record" => run_recorder(config).await,
+        "observe" => run_observer(config, &args.config).await,
         _ => {
             error!("Invalid mode: {}", config.general.mode);
             Err(errors::PolymarketError::ConfigError(format!(
-                "Invalid mode: {}. Must be 'live' or 'backtest'",
+                "Invalid mode: {}. Must be 'live', 'observe', 'backtest' or 'record'",
                 config.general.mode
             )))
         }
@@ -95,95 +172,1099 @@ fn init_logging(level: &str) -> Result<()> {
     Ok(())
 }
 
+/// How often mounted secret files are checked for rotation
+const SECRET_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Run live copy trading
-async fn run_live_trading(config: Config) -> Result<()> {
+async fn run_live_trading(config: Config, config_path: &str) -> Result<()> {
     info!("Initializing live trading mode...");
 
     // Initialize components
-    let signer = OrderSigner::new(&config.general.wallet_private_key, 137)?;
+    let signer = OrderSigner::from_config(&config.general, 137)?;
     info!("Wallet address: {:?}", signer.address());
+    if let Some(safe) = signer.safe() {
+        execution::safe::verify_safe(&config.general.polygon_rpc_url, safe, signer.address())
+            .await?;
+        info!("Trading for Safe {:?}", safe);
+    }
 
-    let clob_client = ClobClient::new(config.general.polymarket_api_url.clone(), signer);
-    let position_sizer = PositionSizer::new(config.position_sizing.clone());
-    let executor = Arc::new(OrderExecutor::new(
-        clob_client,
-        position_sizer,
-        config.execution.clone(),
+    serve_metrics(&config);
+
+    // Hold back new copies while API calls or executions keep failing
+    let error_budget = ErrorBudget::from_config(&config.error_budget).map(Arc::new);
+
+    // CLOB base URLs; requests move to a fallback while the primary keeps failing
+    let mut clob_endpoints = Endpoints::new(
+        "clob",
+        config.general.polymarket_api_url.clone(),
+        &config.failover.clob_api_urls,
+        &config.failover,
+    );
+    if let Some(ref error_budget) = error_budget {
+        clob_endpoints = clob_endpoints.with_error_budget(error_budget.clone());
+    }
+    let clob_endpoints = Arc::new(clob_endpoints);
+    let http_client = network::http_client(&config.network)?;
+    tokio::spawn(clob_endpoints.clone().run_probe(
+        http_client.clone(),
+        Duration::from_secs(config.failover.probe_interval_seconds),
     ));
+    let data_api = data_api_client(&config, &http_client);
 
-    // Initialize trade logger
-    let logger = Arc::new(TradeLogger::new("trades.jsonl".to_string()));
+    let notifier = SlackNotifier::from_config(&config.notifications).map(Arc::new);
+    let mut credential_rotation = CredentialRotation::new(&config.execution.api_credentials_file);
+    if let Some(ref notifier) = notifier {
+        credential_rotation = credential_rotation.with_notifier(notifier.clone());
+    }
 
-    // Get tracked trader addresses
-    let tracked_addresses = config.traders.get_addresses()?;
-    info!("Monitoring {} trader accounts", tracked_addresses.len());
+    let mut clob_client = ClobClient::new(config.general.polymarket_api_url.clone(), signer)
+        .with_http_client(http_client.clone())
+        .with_endpoints(clob_endpoints.clone())
+        .with_credential_rotation(Arc::new(credential_rotation));
+    if config.logging.audit_log_enabled {
+        info!(
+            "Auditing exchange requests to {}",
+            config.logging.audit_log_path
+        );
+        let audit_log = AuditLogger::open(&config.logging.audit_log_path)?;
+        clob_client = clob_client.with_audit_log(Arc::new(audit_log));
+    }
 
-    // Initialize polling monitor
-    let poll_interval = Duration::from_secs(config.execution.poll_interval_seconds);
-    let mut monitor = PollingMonitor::new(
-        config.general.polymarket_api_url.clone(),
-        tracked_addresses,
-        poll_interval,
+    // Fail now rather than on the first order if the API is down or our clock is off
+    let skew = clob_client
+        .check_connection(Duration::from_secs(config.execution.max_clock_skew_seconds))
+        .await?;
+    info!(
+        "CLOB API is reachable; local clock is {}s off server time",
+        skew.num_seconds()
     );
+    // Authenticate with the API key saved last, deriving one on the first run
+    clob_client.load_api_credentials().await?;
 
-    info!("Starting monitoring loop...");
+    let position_sizer = PositionSizer::new(config.position_sizing.clone());
+    // Initialize trade and order stores (JSONL logs or database, per [database] db_type)
+    let mut stores = storage::open_stores(&config).await?;
+    // Stream what is stored to a message broker, per [publishing] backend
+    let publisher = EventPublisher::from_config(&config.publishing).await?;
+    if let Some(ref publisher) = publisher {
+        stores = publisher.wrap(stores);
+    }
+    let store = stores.trades;
+
+    // Market metadata (questions, outcome pairs, status), cached per token
+    let markets = Arc::new(MarketDirectory::new(config.general.gamma_api_url.clone()));
 
-    // Run monitoring loop
-    monitor
-        .monitor_loop(|trade| {
-            let executor = executor.clone();
-            let logger = logger.clone();
-            let trade = trade.clone(); // Clone trade to move into async block
+    // Outcomes of resolved markets, so results don't rest on price marks
+    let resolutions = track_resolutions(&config, &markets, &store, &stores.orders)?;
+
+    // Keep current prices for every market we hold or watch in one place
+    let mut price_cache = PriceCache::new(Duration::from_secs(config.prices.max_age_seconds));
+    if config.position_sizing.volatility.enabled {
+        price_cache = price_cache.with_history(Duration::from_secs(
+            config.position_sizing.volatility.window_minutes * 60,
+        ));
+    }
+    let prices = Arc::new(price_cache);
+    for order in stores.orders.orders().await? {
+        if order.filled_size > Decimal::ZERO {
+            prices.track(&order.market_id);
+        }
+    }
+    let price_feed = PriceFeed::new(
+        prices.clone(),
+        config.prices.clone(),
+        config.general.polymarket_api_url.clone(),
+    )
+    .with_endpoints(clob_endpoints);
+    tokio::spawn(price_feed.run());
+
+    let mut trader_weights = TraderWeights::from_config(&config.traders)?;
+    if let Some(ref resolutions) = resolutions {
+        trader_weights = trader_weights.with_resolutions(resolutions.clone());
+    }
+    let trader_weights = Arc::new(trader_weights);
+    let mut executor = OrderExecutor::new(clob_client, position_sizer, config.execution.clone())
+        .with_order_store(stores.orders)
+        .with_markets(markets.clone())
+        .with_trader_weights(trader_weights.clone());
+    if let Some(cap) = config.risk.max_event_exposure_usdc {
+        executor = executor.with_event_exposure_cap(cap);
+    }
+    if let Some(hours) = config.risk.exit_hours_before_end {
+        executor = executor.with_end_date_buffer(hours);
+    }
+    if config.execution.batch_window_ms > 0 {
+        executor = executor.with_batching(Duration::from_millis(config.execution.batch_window_ms));
+    }
+    if config.position_sizing.volatility.enabled {
+        executor = executor.with_prices(prices.clone());
+    }
+    if config.execution.duplicate_window_seconds > 0 {
+        executor = executor.with_duplicate_guard(
+            Duration::from_secs(config.execution.duplicate_window_seconds),
+            config.execution.duplicate_size_tolerance,
+        );
+    }
+    if let Some(min_usdc) = config.execution.settlement_check_min_usdc {
+        executor = executor.with_settlement_check(SettlementVerifier::new(
+            &config.general.polygon_rpc_url,
+            min_usdc,
+            Duration::from_secs(config.execution.settlement_timeout_seconds),
+        )?);
+    }
+    if config.position_sizing.conviction.enabled {
+        executor = executor.with_portfolios(Arc::new(TraderPortfolios::new(
+            data_api.clone(),
+            Duration::from_secs(config.position_sizing.conviction.refresh_seconds),
+        )));
+    }
+    let executor = Arc::new(executor);
 
-            // Log detected trade
-            if let Err(e) = logger.log_detected_trade(&trade) {
-                error!("Failed to log detected trade: {}", e);
+    // Sign with the new key when a mounted key file is rotated
+    if let Some(ref path) = config.general.wallet_private_key_file {
+        let key_file = SecretFile::open(path)?;
+        let safe = config.general.safe_address()?;
+        let executor = executor.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SECRET_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                let rotated = match key_file.refresh() {
+                    Ok(rotated) => rotated,
+                    Err(e) => {
+                        warn!("Failed to re-read the wallet key file: {}", e);
+                        continue;
+                    }
+                };
+                if let Some(key) = rotated {
+                    let signer = OrderSigner::new(&key, 137).and_then(|signer| match safe {
+                        Some(safe) => signer.with_safe(safe),
+                        None => Ok(signer),
+                    });
+                    match signer {
+                        Ok(signer) => {
+                            info!("Wallet key rotated; now signing as {:?}", signer.address());
+                            executor.clob_client().replace_signer(signer);
+                        }
+                        Err(e) => warn!("Ignoring rotated wallet key: {}", e),
+                    }
+                }
             }
+        });
+    }
+
+    if let Some(ref error_budget) = error_budget {
+        tokio::spawn(error_budget.clone().run_periodic(notifier.clone()));
+    }
 
+    // Stop copying traders whose recent results fall below the thresholds
+    let pruner = start_pruner(&config, resolutions.as_ref(), &store, &prices, &notifier)?;
+
+    // Follow traders' recent form when sizing their copies
+    if config.traders.weighting.enabled {
+        tokio::spawn(
+            trader_weights
+                .clone()
+                .run_periodic(store.clone(), prices.clone()),
+        );
+    }
+
+    // Periodically export trade history for sharing
+    if config.export.enabled {
+        let exporter = Exporter::new(store.clone(), &config.export)?;
+        info!(
+            "Exporting trades to {} every {}h",
+            config.export.directory, config.export.interval_hours
+        );
+        tokio::spawn(exporter.run_periodic());
+    }
+
+    // Elect a leader when several instances share this wallet
+    let coordinator = match Coordinator::from_config(&config.coordination).await? {
+        Some(coordinator) => {
+            let coordinator = Arc::new(coordinator);
             info!(
-                "Detected trade: {} - Market: {}, Side: {}, Size: {} USDC",
-                trade.id, trade.market_id, trade.side, trade.size_usdc
+                "Coordinating via {} backend as instance {}",
+                config.coordination.backend,
+                coordinator.instance_id()
             );
+            if !coordinator.renew().await {
+                info!("Another instance is leading; standing by");
+            }
+            tokio::spawn(coordinator.clone().run_lease_loop());
+            Some(coordinator)
+        }
+        None => None,
+    };
+
+    // Settle orders a previous run left in flight, once this instance may trade
+    {
+        let executor = executor.clone();
+        let store = store.clone();
+        let coordinator = coordinator.clone();
+        let fill_check_hours = config.execution.fill_check_hours;
+        tokio::spawn(async move {
+            if let Some(coordinator) = coordinator {
+                while !coordinator.is_leader() {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+            match executor.resume_in_flight(store.as_ref()).await {
+                Ok(summary) if summary.resumed > 0 => info!(
+                    "Resumed {} in-flight orders: {} filled, {} cancelled, {} unresolved",
+                    summary.resumed, summary.filled, summary.cancelled, summary.unresolved
+                ),
+                Ok(_) => {}
+                Err(e) => error!("Failed to resume in-flight orders: {}", e),
+            }
+            if fill_check_hours > 0 {
+                let since = Utc::now() - chrono::Duration::hours(fill_check_hours as i64);
+                match executor.verify_fills(since).await {
+                    Ok(found) if found.is_empty() => {
+                        info!("Recorded fills match the exchange's trade history")
+                    }
+                    Ok(found) => warn!(
+                        "{} orders' recorded fills disagree with the exchange",
+                        found.len()
+                    ),
+                    Err(e) => error!("Failed to check fills against the exchange: {}", e),
+                }
+            }
+        });
+    }
+
+    // Trim positions that price moves have pushed over the exposure cap
+    if config.risk.rebalance_enabled {
+        let mut rebalancer = Rebalancer::new(executor.clone(), prices.clone(), config.risk.clone());
+        if let Some(ref coordinator) = coordinator {
+            rebalancer = rebalancer.with_coordinator(coordinator.clone());
+        }
+        tokio::spawn(rebalancer.run_periodic());
+    }
+
+    // Exit or hedge positions when a risk rule fires
+    if config.risk.has_exit_rules() {
+        let mut risk_monitor =
+            RiskMonitor::new(executor.clone(), prices.clone(), config.risk.clone())
+                .with_markets(markets.clone());
+        if let Some(ref coordinator) = coordinator {
+            risk_monitor = risk_monitor.with_coordinator(coordinator.clone());
+        }
+        tokio::spawn(risk_monitor.run_periodic());
+    }
+
+    // Cancel resting orders that outlived their age limit or their trader's position
+    if config.execution.order_sweep_interval_seconds > 0 {
+        let mut sweeper = OrderSweeper::from_config(&config.execution, executor.clone());
+        if config.execution.cancel_after_trader_exit {
+            sweeper = sweeper.with_data_api(data_api.clone());
+        }
+        if let Some(ref coordinator) = coordinator {
+            sweeper = sweeper.with_coordinator(coordinator.clone());
+        }
+        tokio::spawn(sweeper.run_periodic());
+    }
+
+    let pause = Arc::new(PauseSwitch::new(&config.general.pause_file));
+    if let Some(state) = pause.state() {
+        warn!(
+            "Copying is paused since {} ({}); run `resume` to start copying",
+            state.paused_at, config.general.pause_file
+        );
+    }
+
+    // Markets and traders blacklisted at runtime, and the positions to close for them
+    let blacklist = Arc::new(Blacklist::new(&config.general.blacklist_file));
+    tokio::spawn(blacklist.clone().run_close_loop(
+        executor.clone(),
+        store.clone(),
+        prices.clone(),
+        coordinator.clone(),
+    ));
+
+    // Balance, exposure and risk headroom for alerting ahead of the limits
+    if config.metrics.enabled {
+        let gauges =
+            ExposureGauges::from_config(&config, executor.clone(), prices.clone(), markets.clone());
+        tokio::spawn(gauges.run_periodic());
+    }
+
+    // Stop everything if equity falls too far below its peak
+    if let Some(mut drawdown_monitor) = DrawdownMonitor::from_config(
+        &config.risk,
+        executor.clone(),
+        prices.clone(),
+        pause.clone(),
+    ) {
+        if let Some(ref coordinator) = coordinator {
+            drawdown_monitor = drawdown_monitor.with_coordinator(coordinator.clone());
+        }
+        tokio::spawn(drawdown_monitor.run_periodic());
+    }
+
+    if let Some(publisher) = publisher {
+        if config.publishing.snapshot_interval_seconds > 0 {
+            tokio::spawn(publisher.run_snapshots(executor.clone(), prices.clone()));
+        }
+    }
+
+    // Our P&L per copied trader, for `status`, the metrics and the daily summary
+    let mut pnl_tracker = PnlTracker::from_config(&config.pnl, executor.clone(), prices.clone())?
+        .with_gas_log(GasLog::new(config.logging.gas_log_path.clone()));
+    if let Some(ref notifier) = notifier {
+        pnl_tracker = pnl_tracker.with_notifier(notifier.clone());
+    }
+    if let Some(ref coordinator) = coordinator {
+        pnl_tracker = pnl_tracker.with_coordinator(coordinator.clone());
+    }
+    if let Some(ref resolutions) = resolutions {
+        pnl_tracker = pnl_tracker.with_resolutions(resolutions.clone());
+    }
+    tokio::spawn(pnl_tracker.run_periodic());
+
+    let mut monitor = build_monitor(&config, config_path, data_api, &markets, &notifier)?;
 
-            // Execute trade asynchronously
+    // Stop copying traders who look like they farm volume
+    let mut wash_detector = config
+        .wash_trading
+        .enabled
+        .then(|| WashTradeDetector::new(config.wash_trading.clone()));
+
+    let mut filters = TradeFilters::from_config(
+        &config,
+        markets.clone(),
+        blacklist.clone(),
+        pruner,
+        prices.clone(),
+    )?;
+    filters.pause = Some(pause);
+    filters.error_budget = error_budget.clone();
+    let filters = Arc::new(filters);
+
+    let mut detection_latency = DetectionLatency::new(Duration::from_secs(
+        config.execution.max_detection_latency_seconds,
+    ));
+    let labels = TradeLabels::from_config(&config)?;
+    let strategies = Strategies::from_config(&config, executor.clone())?;
+    // Copies beyond the execution slots, or into a market another copy is
+    // executing in, wait their turn by priority
+    let queue = if config.execution.max_concurrent_executions > 0
+        || config.execution.one_order_per_market
+    {
+        let queue = ExecutionQueue::from_config(&config.execution, markets.clone())?
+            .with_trader_weights(trader_weights.clone());
+        Some(Arc::new(queue))
+    } else {
+        None
+    };
+    let retries = (config.execution.failed_copy_retry_seconds > 0)
+        .then(|| Arc::new(CopyRetries::from_config(&config.execution, prices.clone())));
+
+    info!("Starting monitoring loop...");
+
+    // Run monitoring loop until interrupted
+    let monitoring = monitor.monitor_loop(|trade| {
+        let mut trade = trade.clone();
+        labels.apply(&mut trade);
+
+        prices.track(&trade.market_id);
+        trade.detection_midpoint = prices.price(&trade.market_id);
+        if let Some(ref copy_delay) = filters.copy_delay {
+            copy_delay.record(&trade);
+        }
+
+        let latency = detection_latency.record(&trade, Utc::now());
+        info!(
+            "Detected trade: {} - Market: {}, Side: {}, Size: {} USDC ({:.1}s after the fill)",
+            trade.id,
+            trade.market_id,
+            trade.side,
+            trade.size_usdc,
+            latency.as_secs_f64()
+        );
+
+        // The detector sees every trade in order, so it runs before the spawn
+        let wash_trading = wash_detector
+            .as_mut()
+            .is_some_and(|detector| detector.observe(&trade) == WashVerdict::Suspended);
+
+        // Execute a copy for each strategy tracking the trader, asynchronously
+        for route in strategies.route(&trade) {
+            let store = store.clone();
+            let coordinator = coordinator.clone();
+            let markets = markets.clone();
+            let notifier = notifier.clone();
+            let filters = filters.clone();
+            let error_budget = error_budget.clone();
+            let queue = queue.clone();
+            let retries = retries.clone();
             tokio::spawn(async move {
-                match executor.get_balance().await {
-                    Ok(balance) => {
-                        match executor.execute_trade(&trade, balance).await {
-                            Ok(_) => {
-                                info!("Successfully executed copy trade for {}", trade.id);
+                let (trade, executor) = (route.trade.clone(), route.executor.clone());
+
+                // Followers leave every trade to the leader
+                if coordinator.as_ref().is_some_and(|c| !c.is_leader()) {
+                    debug!("Trade {} is handled by the leader", trade.id);
+                    return;
+                }
+
+                // Log detected trade, with the market question for humans reading the log
+                let market = markets.label(&trade.market_id).await;
+                let entry = TradeLogEntry::detected(&trade).with_market(market.clone());
+                if let Err(e) = store.append(&entry).await {
+                    error!("Failed to log detected trade: {}", e);
+                }
+
+                // Another instance may have detected and claimed it first
+                if let Some(coordinator) = coordinator {
+                    match coordinator.should_execute(&trade).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            let detail = "claimed by another instance";
+                            let reason = SkipReason::ClaimedElsewhere;
+                            log_skipped(store.as_ref(), &trade, market, reason, detail).await;
+                            return;
+                        }
+                        Err(e) => {
+                            error!("Failed to claim trade {}: {}", trade.id, e);
+                            let error = format!("claiming the trade failed: {}", e);
+                            let entry = TradeLogEntry::failed(&trade, &error).with_market(market);
+                            if let Err(log_err) = store.append(&entry).await {
+                                error!("Failed to log error: {}", log_err);
                             }
-                            Err(e) => {
-                                error!("Failed to execute trade {}: {}", trade.id, e);
-                                if let Err(log_err) = logger.log_failed_trade(&trade, &e.to_string())
-                                {
-                                    error!("Failed to log error: {}", log_err);
-                                }
+                            return;
+                        }
+                    }
+                }
+
+                // Copies failing for a passing reason go round again, filters and all
+                let first_attempt = Instant::now();
+                loop {
+                    if let Some((reason, detail)) = filters
+                        .check(&trade, latency, wash_trading, &executor)
+                        .await
+                    {
+                        log_skipped(store.as_ref(), &trade, market, reason, &detail).await;
+                        return;
+                    }
+
+                    // Hold an execution slot until the copy is done
+                    let slot = match queue {
+                        Some(ref queue) => Some(queue.acquire(&trade).await),
+                        None => None,
+                    };
+                    let (result, executed) = match route.balance().await {
+                        Ok(balance) => (executor.execute_trade(&trade, balance).await, true),
+                        Err(PolymarketError::Skipped(reason, detail)) => {
+                            (Err(PolymarketError::Skipped(reason, detail)), false)
+                        }
+                        Err(e) => {
+                            error!("Failed to get balance: {}", e);
+                            let reason = format!("balance unavailable: {}", e);
+                            let skip = SkipReason::BalanceUnavailable;
+                            (Err(PolymarketError::Skipped(skip, reason)), false)
+                        }
+                    };
+                    drop(slot);
+
+                    let e = match result {
+                        Ok(()) => {
+                            info!("Successfully executed copy trade for {}", trade.id);
+                            if let Some(ref error_budget) = error_budget {
+                                error_budget.record(Operation::Execution, true);
+                            }
+                            if let Some(notifier) = notifier {
+                                notifier.trade_copied(&trade, market.as_ref()).await;
                             }
+                            return;
+                        }
+                        Err(e) => e,
+                    };
+                    if let Some(ref retries) = retries {
+                        match retries.wait(&trade, &e, first_attempt).await {
+                            Ok(true) => continue,
+                            Ok(false) => {}
+                            Err(PolymarketError::Skipped(reason, detail)) => {
+                                log_skipped(store.as_ref(), &trade, market, reason, &detail).await;
+                                return;
+                            }
+                            Err(_) => {}
                         }
                     }
-                    Err(e) => {
-                        error!("Failed to get balance: {}", e);
+
+                    if !executed {
+                        if let PolymarketError::Skipped(reason, ref detail) = e {
+                            log_skipped(store.as_ref(), &trade, market, reason, detail).await;
+                        }
+                        return;
+                    }
+                    if let Some(reason) = e.skip_reason() {
+                        let detail = match e {
+                            PolymarketError::Skipped(_, ref detail) => detail.clone(),
+                            _ => e.to_string(),
+                        };
+                        log_skipped(store.as_ref(), &trade, market.clone(), reason, &detail).await;
+                        if let Some(notifier) = notifier {
+                            notifier
+                                .trade_failed(&trade, market.as_ref(), &e.to_string())
+                                .await;
+                        }
+                        return;
+                    }
+                    error!("Failed to execute trade {}: {}", trade.id, e);
+                    if let Some(ref error_budget) = error_budget {
+                        error_budget.record(Operation::Execution, false);
+                    }
+                    let entry =
+                        TradeLogEntry::failed(&trade, &e.to_string()).with_market(market.clone());
+                    if let Err(log_err) = store.append(&entry).await {
+                        error!("Failed to log error: {}", log_err);
                     }
+                    if let Some(notifier) = notifier {
+                        notifier
+                            .trade_failed(&trade, market.as_ref(), &e.to_string())
+                            .await;
+                    }
+                    return;
                 }
             });
+        }
+
+        Ok(())
+    });
+
+    tokio::select! {
+        result = monitoring => result?,
+        _ = tokio::signal::ctrl_c() => info!("Shutting down"),
+    }
+
+    // Don't leave orders resting on the book with nobody watching them;
+    // a standby's wallet orders belong to the leader
+    let is_leader = coordinator.as_ref().is_none_or(|c| c.is_leader());
+    if config.execution.cancel_on_shutdown && is_leader {
+        match executor.cancel_open_orders(None, "shutdown").await {
+            Ok(cancelled) => info!("Cancelled {} open orders", cancelled),
+            Err(e) => error!("Failed to cancel open orders on shutdown: {}", e),
+        }
+    }
+
+    // Hand over immediately instead of making standbys wait out the lease
+    if let Some(coordinator) = coordinator {
+        if coordinator.is_leader() {
+            coordinator.step_down().await?;
+        }
+    }
+
+    Ok(())
+}
 
-            Ok(())
+/// Record a detected trade that won't be copied
+async fn log_skipped(
+    store: &dyn TradeStore,
+    trade: &Trade,
+    market: Option<MarketLabel>,
+    reason: SkipReason,
+    detail: &str,
+) {
+    info!("Not copying trade {}: {}", trade.id, detail);
+    let entry = TradeLogEntry::skipped(trade, reason, detail).with_market(market);
+    if let Err(e) = store.append(&entry).await {
+        error!("Failed to log skipped trade: {}", e);
+    }
+}
+
+/// Hold `trade` while the schedule is closed and queueing is on; returns why
+/// the trade can't be copied, if it can't
+async fn wait_for_schedule(
+    schedule: &TradingSchedule,
+    markets: &MarketDirectory,
+    trade: &Trade,
+) -> Option<ScheduleBlock> {
+    let market_end = match markets.market(&trade.market_id).await {
+        Ok(market) => market.end_date,
+        Err(_) => None,
+    };
+    let mut block = schedule.check(Utc::now(), market_end);
+    if block == Some(ScheduleBlock::OutsideWindow) && schedule.queues_outside_window() {
+        info!("Queueing trade {} until the trading window opens", trade.id);
+        while block == Some(ScheduleBlock::OutsideWindow) {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            block = schedule.check(Utc::now(), market_end);
+        }
+    }
+    block
+}
+
+/// Serve `/metrics` if it is enabled
+fn serve_metrics(config: &Config) {
+    if config.metrics.enabled {
+        let listen_addr = config.metrics.listen_addr.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::global().serve(&listen_addr).await {
+                error!("Metrics endpoint stopped: {}", e);
+            }
+        });
+    }
+}
+
+/// Load recorded market outcomes and keep recording them, if enabled
+fn track_resolutions(
+    config: &Config,
+    markets: &Arc<MarketDirectory>,
+    trades: &Arc<dyn TradeStore>,
+    orders: &Arc<dyn OrderStore>,
+) -> Result<Option<Arc<Resolutions>>> {
+    if !config.resolutions.enabled {
+        return Ok(None);
+    }
+    let resolutions = Arc::new(Resolutions::load(&config.resolutions.file)?);
+    let tracker = ResolutionTracker::new(
+        &config.resolutions,
+        resolutions.clone(),
+        markets.clone(),
+        trades.clone(),
+        orders.clone(),
+    );
+    tokio::spawn(tracker.run_periodic());
+    Ok(Some(resolutions))
+}
+
+/// Start judging traders on their recent results, if pruning is enabled
+fn start_pruner(
+    config: &Config,
+    resolutions: Option<&Arc<Resolutions>>,
+    store: &Arc<dyn TradeStore>,
+    prices: &Arc<PriceCache>,
+    notifier: &Option<Arc<SlackNotifier>>,
+) -> Result<Option<Arc<TraderPruner>>> {
+    if !config.traders.pruning.enabled {
+        return Ok(None);
+    }
+    let mut pruner = TraderPruner::load(config.traders.pruning.clone())?;
+    if let Some(resolutions) = resolutions {
+        pruner = pruner.with_resolutions(resolutions.clone());
+    }
+    let pruner = Arc::new(pruner);
+    tokio::spawn(
+        pruner
+            .clone()
+            .run_periodic(store.clone(), prices.clone(), notifier.clone()),
+    );
+    Ok(Some(pruner))
+}
+
+/// Monitor for the tracked traders, with the aggregation, onboarding, signal
+/// API and reloading `[traders]` and `[signals]` ask for
+fn build_monitor(
+    config: &Config,
+    config_path: &str,
+    data_api: DataApiClient,
+    markets: &Arc<MarketDirectory>,
+    notifier: &Option<Arc<SlackNotifier>>,
+) -> Result<PollingMonitor> {
+    // Get tracked trader addresses, the strategies' included
+    let tracked_addresses = strategies::tracked_addresses(config)?;
+    info!("Monitoring {} trader accounts", tracked_addresses.len());
+
+    // Initialize polling monitor
+    let poll_interval = Duration::from_secs(config.execution.poll_interval_seconds);
+    let mut monitor =
+        PollingMonitor::new(data_api.clone(), tracked_addresses.clone(), poll_interval)
+            .with_markets(markets.clone());
+    if let Some(ref notifier) = notifier {
+        monitor = monitor.with_notifier(notifier.clone());
+    }
+    if config.execution.aggregation_window_seconds > 0 {
+        monitor = monitor.with_aggregation(Duration::from_secs(
+            config.execution.aggregation_window_seconds,
+        ));
+    }
+    monitor = monitor.with_onboarding(Onboarding::from_config(&config.traders)?);
+    let detection_mode = DetectionMode::parse(&config.execution.detection_mode)?;
+    if detection_mode != DetectionMode::Positions {
+        monitor = monitor.with_trade_feed(detection_mode, TradeFeed::new(data_api));
+    }
+    if config.signals.enabled {
+        // Trades from outside the bot join the detected ones
+        let (sender, receiver) = tokio::sync::mpsc::channel(config.signals.queue_size);
+        monitor = monitor.with_signals(receiver);
+        let listener = SignalListener::new(&config.signals, sender);
+        tokio::spawn(async move {
+            if let Err(e) = listener.serve().await {
+                error!("Signal API stopped: {}", e);
+            }
+        });
+    }
+    if config.traders.reload_interval_seconds > 0 {
+        // Follow edits to tracked_accounts without restarting
+        let mut pinned = Vec::new();
+        for strategy in &config.strategies {
+            pinned.extend(strategy.get_addresses()?);
+        }
+        let (tracked_set, receiver) = tokio::sync::watch::channel(tracked_addresses);
+        monitor = monitor.with_tracked_set(receiver);
+        tokio::spawn(
+            TrackedSetReloader::new(config_path, &config.traders, tracked_set)
+                .with_pinned(pinned)
+                .run_periodic(),
+        );
+    }
+
+    Ok(monitor)
+}
+
+/// A data API client failing over between the configured endpoints, whose
+/// primary is probed while on a fallback; clones share the endpoints
+fn data_api_client(config: &Config, http_client: &reqwest::Client) -> DataApiClient {
+    let endpoints = Arc::new(Endpoints::new(
+        "data",
+        config.general.data_api_url.clone(),
+        &config.failover.data_api_urls,
+        &config.failover,
+    ));
+    tokio::spawn(endpoints.clone().run_probe(
+        http_client.clone(),
+        Duration::from_secs(config.failover.probe_interval_seconds),
+    ));
+    DataApiClient::new(endpoints.url())
+        .with_http_client(http_client.clone())
+        .with_endpoints(endpoints)
+}
+
+/// Checks a detected trade must pass before it is handed to the executor
+struct TradeFilters {
+    max_trade_age: Duration,
+    pause: Option<Arc<PauseSwitch>>,
+    error_budget: Option<Arc<ErrorBudget>>,
+    blacklist: Arc<Blacklist>,
+    pruner: Option<Arc<TraderPruner>>,
+    schedule: Option<TradingSchedule>,
+    copy_delay: Option<CopyDelay>,
+    markets: Arc<MarketDirectory>,
+}
+
+impl TradeFilters {
+    fn from_config(
+        config: &Config,
+        markets: Arc<MarketDirectory>,
+        blacklist: Arc<Blacklist>,
+        pruner: Option<Arc<TraderPruner>>,
+        prices: Arc<PriceCache>,
+    ) -> Result<Self> {
+        let schedule = if config.schedule.enabled {
+            Some(TradingSchedule::from_config(&config.schedule)?)
+        } else {
+            None
+        };
+        // Give traders a chance to unwind scalps and fat-finger entries before we copy
+        let copy_delay = (config.execution.copy_delay_seconds > 0).then(|| {
+            CopyDelay::new(
+                Duration::from_secs(config.execution.copy_delay_seconds),
+                config.execution.max_price_gap,
+                prices,
+            )
+        });
+        Ok(Self {
+            max_trade_age: Duration::from_secs(config.execution.max_trade_age_seconds),
+            pause: None,
+            error_budget: None,
+            blacklist,
+            pruner,
+            schedule,
+            copy_delay,
+            markets,
         })
-        .await?;
+    }
+
+    /// Why `trade` isn't copied, if it isn't; holds the trade while the
+    /// schedule queues it and through the copy delay
+    async fn check(
+        &self,
+        trade: &Trade,
+        latency: Duration,
+        wash_trading: bool,
+        executor: &OrderExecutor,
+    ) -> Option<(SkipReason, String)> {
+        // Late exits are still copied, or we'd keep a position the trader left
+        if !self.max_trade_age.is_zero()
+            && latency > self.max_trade_age
+            && trade.exit_fraction.is_none()
+        {
+            let reason = format!(
+                "trade is stale: detected {:.0}s after the fill (limit {}s)",
+                latency.as_secs_f64(),
+                self.max_trade_age.as_secs()
+            );
+            return Some((SkipReason::Stale, reason));
+        }
+
+        if wash_trading {
+            let reason = "trader is suspended for wash trading".to_string();
+            return Some((SkipReason::WashTrading, reason));
+        }
+
+        if self.pause.as_ref().is_some_and(|pause| pause.is_paused()) {
+            return Some((SkipReason::Paused, "copying is paused".to_string()));
+        }
+
+        if let Some(reason) = self.error_budget.as_ref().and_then(|b| b.pause_reason()) {
+            return Some((SkipReason::ErrorBudget, reason));
+        }
+
+        if let Some(reason) = self.blacklist.blocks(trade) {
+            return Some((SkipReason::Blacklisted, reason));
+        }
+
+        // Suspended traders are still logged as detected, so probation has their record
+        if let Some(suspension) = self
+            .pruner
+            .as_ref()
+            .and_then(|p| p.suspension(trade.trader))
+        {
+            let reason = format!("trader is suspended: {}", suspension.reason);
+            return Some((SkipReason::TraderSuspended, reason));
+        }
+
+        if let Some(ref schedule) = self.schedule {
+            if let Some(block) = wait_for_schedule(schedule, &self.markets, trade).await {
+                return Some((SkipReason::Schedule, block.to_string()));
+            }
+        }
+
+        if let Some(ref copy_delay) = self.copy_delay {
+            let fee_per_share = match executor
+                .clob_client()
+                .get_fee_rate_bps(&trade.market_id)
+                .await
+            {
+                Ok(rate) => fee_amount(rate, trade.price, Decimal::ONE),
+                Err(e) => {
+                    warn!("Could not fetch the fee rate of {}: {}", trade.market_id, e);
+                    Decimal::ZERO
+                }
+            };
+            let reason = match copy_delay.reconfirm(trade, fee_per_share).await {
+                Reconfirmation::Confirmed => None,
+                Reconfirmation::Reversed => {
+                    Some("trader reversed the trade during the copy delay".to_string())
+                }
+                Reconfirmation::PriceGapped(gap) => {
+                    Some(format!("price moved {} during the copy delay", gap))
+                }
+            };
+            if let Some(reason) = reason {
+                return Some((SkipReason::CopyDelay, reason));
+            }
+        }
+
+        None
+    }
+}
+
+/// Watch the tracked traders and log which of their trades the filters would
+/// copy, without a wallet and without sizing or placing anything
+async fn run_observer(config: Config, config_path: &str) -> Result<()> {
+    info!("Initializing observe mode; no orders will be placed...");
+
+    serve_metrics(&config);
+
+    let clob_endpoints = Arc::new(Endpoints::new(
+        "clob",
+        config.general.polymarket_api_url.clone(),
+        &config.failover.clob_api_urls,
+        &config.failover,
+    ));
+    let http_client = network::http_client(&config.network)?;
+    tokio::spawn(clob_endpoints.clone().run_probe(
+        http_client.clone(),
+        Duration::from_secs(config.failover.probe_interval_seconds),
+    ));
+    let data_api = data_api_client(&config, &http_client);
+    // Only reads market data, so a throwaway key stands in for the wallet
+    let clob_client = ClobClient::new(
+        config.general.polymarket_api_url.clone(),
+        OrderSigner::ephemeral(137),
+    )
+    .with_http_client(http_client.clone())
+    .with_endpoints(clob_endpoints.clone());
+
+    let mut stores = storage::open_stores(&config).await?;
+    let publisher = EventPublisher::from_config(&config.publishing).await?;
+    if let Some(ref publisher) = publisher {
+        stores = publisher.wrap(stores);
+    }
+    let store = stores.trades;
+
+    let markets = Arc::new(MarketDirectory::new(config.general.gamma_api_url.clone()));
+    let resolutions = track_resolutions(&config, &markets, &store, &stores.orders)?;
 
+    let prices = Arc::new(PriceCache::new(Duration::from_secs(
+        config.prices.max_age_seconds,
+    )));
+    let price_feed = PriceFeed::new(
+        prices.clone(),
+        config.prices.clone(),
+        config.general.polymarket_api_url.clone(),
+    )
+    .with_endpoints(clob_endpoints);
+    tokio::spawn(price_feed.run());
+
+    // Screens trades like the live executor; it never gets to place an order
+    let position_sizer = PositionSizer::new(config.position_sizing.clone());
+    let mut executor = OrderExecutor::new(clob_client, position_sizer, config.execution.clone())
+        .with_markets(markets.clone());
+    if let Some(hours) = config.risk.exit_hours_before_end {
+        executor = executor.with_end_date_buffer(hours);
+    }
+    if config.execution.duplicate_window_seconds > 0 {
+        executor = executor.with_duplicate_guard(
+            Duration::from_secs(config.execution.duplicate_window_seconds),
+            config.execution.duplicate_size_tolerance,
+        );
+    }
+    let executor = Arc::new(executor);
+
+    let notifier = SlackNotifier::from_config(&config.notifications).map(Arc::new);
+    let pruner = start_pruner(&config, resolutions.as_ref(), &store, &prices, &notifier)?;
+    let blacklist = Arc::new(Blacklist::new(&config.general.blacklist_file));
+    let filters = Arc::new(TradeFilters::from_config(
+        &config,
+        markets.clone(),
+        blacklist,
+        pruner,
+        prices.clone(),
+    )?);
+
+    let mut monitor = build_monitor(&config, config_path, data_api, &markets, &notifier)?;
+    let mut wash_detector = config
+        .wash_trading
+        .enabled
+        .then(|| WashTradeDetector::new(config.wash_trading.clone()));
+    let mut detection_latency = DetectionLatency::new(Duration::from_secs(
+        config.execution.max_detection_latency_seconds,
+    ));
+    let labels = TradeLabels::from_config(&config)?;
+    let strategies = Strategies::from_config(&config, executor)?;
+
+    info!("Starting monitoring loop...");
+
+    let monitoring = monitor.monitor_loop(|trade| {
+        let mut trade = trade.clone();
+        labels.apply(&mut trade);
+
+        prices.track(&trade.market_id);
+        trade.detection_midpoint = prices.price(&trade.market_id);
+        if let Some(ref copy_delay) = filters.copy_delay {
+            copy_delay.record(&trade);
+        }
+        let latency = detection_latency.record(&trade, Utc::now());
+        info!(
+            "Detected trade: {} - Market: {}, Side: {}, Size: {} USDC ({:.1}s after the fill)",
+            trade.id,
+            trade.market_id,
+            trade.side,
+            trade.size_usdc,
+            latency.as_secs_f64()
+        );
+        let wash_trading = wash_detector
+            .as_mut()
+            .is_some_and(|detector| detector.observe(&trade) == WashVerdict::Suspended);
+
+        for route in strategies.route(&trade) {
+            let store = store.clone();
+            let markets = markets.clone();
+            let notifier = notifier.clone();
+            let filters = filters.clone();
+            tokio::spawn(async move {
+                let (trade, executor) = (route.trade.clone(), route.executor.clone());
+                let market = markets.label(&trade.market_id).await;
+                let entry = TradeLogEntry::detected(&trade).with_market(market.clone());
+                if let Err(e) = store.append(&entry).await {
+                    error!("Failed to log detected trade: {}", e);
+                }
+
+                if let Some((reason, detail)) = filters
+                    .check(&trade, latency, wash_trading, &executor)
+                    .await
+                {
+                    log_skipped(store.as_ref(), &trade, market, reason, &detail).await;
+                    return;
+                }
+
+                match executor.screen(&trade).await {
+                    Ok(()) => {
+                        let book_fill = book_fill(&executor, &trade).await;
+                        let entry =
+                            TradeLogEntry::passed(&trade, book_fill).with_market(market.clone());
+                        if let Err(e) = store.append(&entry).await {
+                            error!("Failed to log passed trade: {}", e);
+                        }
+                        if let Some(notifier) = notifier {
+                            notifier.trade_observed(&trade, market.as_ref()).await;
+                        }
+                    }
+                    Err(e) => match e.skip_reason() {
+                        Some(reason) => {
+                            let detail = match e {
+                                PolymarketError::Skipped(_, ref detail) => detail.clone(),
+                                _ => e.to_string(),
+                            };
+                            log_skipped(store.as_ref(), &trade, market, reason, &detail).await;
+                        }
+                        None => {
+                            error!("Failed to screen trade {}: {}", trade.id, e);
+                            let entry =
+                                TradeLogEntry::failed(&trade, &e.to_string()).with_market(market);
+                            if let Err(log_err) = store.append(&entry).await {
+                                error!("Failed to log error: {}", log_err);
+                            }
+                        }
+                    },
+                }
+            });
+        }
+
+        Ok(())
+    });
+
+    tokio::select! {
+        result = monitoring => result?,
+        _ = tokio::signal::ctrl_c() => info!("Shutting down"),
+    }
     Ok(())
 }
 
+/// What the trader's size would fill on the live book now; the trader's own
+/// price flatters a copy, which comes after them
+async fn book_fill(executor: &OrderExecutor, trade: &Trade) -> Option<BookFill> {
+    let fill = match executor
+        .clob_client()
+        .get_order_book(&trade.market_id)
+        .await
+    {
+        Ok(book) => book.walk(&trade.side, trade.size),
+        Err(e) => {
+            warn!("Could not fetch the book of {}: {}", trade.market_id, e);
+            None
+        }
+    };
+    match fill {
+        Some(ref fill) => info!(
+            "Trade {} passed every filter; would fill {} of {} shares at {} (trader paid {})",
+            trade.id,
+            fill.shares,
+            trade.size,
+            fill.average_price.round_dp(4),
+            trade.price
+        ),
+        None => info!("Trade {} passed every filter; would copy it", trade.id),
+    }
+    fill
+}
+
 /// Run backtesting simulation
 async fn run_backtest(config: Config) -> Result<()> {
     info!("Initializing backtest mode...");
-
-    // Create backtest engine
-    let mut engine = BacktestEngine::new(config.backtest.clone(), config.position_sizing.clone());
-
-    info!("Running backtest simulation...");
-    let results = engine.run().await?;
+    let results = backtest(&config).await?;
 
     // Print results
     println!("{}", results.format_report());
@@ -195,3 +1276,128 @@ async fn run_backtest(config: Config) -> Result<()> {
 
     Ok(())
 }
+
+/// Backtest `config` and every configuration in `others`, then print their
+/// headline metrics side by side
+async fn run_backtest_comparison(config: Config, path: &str, others: &[String]) -> Result<()> {
+    info!(
+        "Initializing backtest comparison of {} configs...",
+        others.len() + 1
+    );
+    let mut runs = vec![(config_name(path), Ok(config))];
+    for other in others {
+        let loaded = Config::load_from_file(other).and_then(|mut config| {
+            config.expand_env_vars()?;
+            Ok(config)
+        });
+        runs.push((config_name(other), loaded));
+    }
+
+    let mut compared = Vec::new();
+    for (name, config) in runs {
+        info!("Backtesting {}...", name);
+        let results = match config {
+            Ok(config) => backtest(&config).await,
+            Err(e) => Err(e),
+        };
+        if let Err(ref e) = results {
+            warn!("Backtest of {} failed: {}", name, e);
+        }
+        compared.push(SweepResult {
+            name,
+            results: results.map_err(|e| e.to_string()),
+        });
+    }
+
+    println!("{}", format_comparison_table(&compared));
+
+    let json: Vec<serde_json::Value> = compared
+        .iter()
+        .map(|run| match run.results {
+            Ok(ref results) => serde_json::json!({ "config": run.name, "results": results }),
+            Err(ref e) => serde_json::json!({ "config": run.name, "error": e }),
+        })
+        .collect();
+    std::fs::write(
+        "backtest_comparison.json",
+        serde_json::to_string_pretty(&json)?,
+    )?;
+    info!("Results saved to backtest_comparison.json");
+
+    Ok(())
+}
+
+/// Backtest every parameter set in the sweep file at `path` against one load of
+/// the dataset, then print them ranked by ROI
+fn run_backtest_sweep(config: Config, path: &str) -> Result<()> {
+    if config.backtest.data_source == "trade_log" || config.backtest.end_of_backtest == "resolution"
+    {
+        return Err(PolymarketError::ConfigError(
+            "--sweep replays a preloaded dataset; it supports neither data_source = \"trade_log\" \
+             nor end_of_backtest = \"resolution\""
+                .to_string(),
+        ));
+    }
+    let sets = ParameterSet::load_all(path, &config.backtest, &config.position_sizing)?;
+    info!("Initializing parameter sweep of {} sets...", sets.len());
+
+    let results = BacktestSweep::load(&config.backtest, &config.traders.profiles)?.run(&sets);
+
+    println!("{}", format_sweep_table(&results));
+
+    let json: Vec<serde_json::Value> = results
+        .iter()
+        .map(|run| match run.results {
+            Ok(ref results) => serde_json::json!({ "set": run.name, "results": results }),
+            Err(ref e) => serde_json::json!({ "set": run.name, "error": e }),
+        })
+        .collect();
+    std::fs::write("backtest_sweep.json", serde_json::to_string_pretty(&json)?)?;
+    info!("Results saved to backtest_sweep.json");
+
+    Ok(())
+}
+
+/// A configuration file's name without its directory and extension
+fn config_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Run the backtest `config` describes
+async fn backtest(config: &Config) -> Result<BacktestResults> {
+    // Create backtest engine
+    let mut engine = BacktestEngine::new(config.backtest.clone(), config.position_sizing.clone())
+        .with_trader_profiles(config.traders.profiles.clone());
+    if config.backtest.data_source == "trade_log" {
+        // Replay what the live bot saw, from the trade log or database
+        let entries = storage::open_store(config).await?.entries().await?;
+        engine = engine.with_recorded_session(RecordedSession::from_entries(&entries));
+    }
+    if config.backtest.end_of_backtest == "resolution" {
+        // Settle at the outcomes recorded live, or looked up where there are none
+        engine = engine
+            .with_resolutions(Arc::new(Resolutions::load(&config.resolutions.file)?))
+            .with_markets(Arc::new(MarketDirectory::new(
+                config.general.gamma_api_url.clone(),
+            )));
+    }
+
+    info!("Running backtest simulation...");
+    engine.run().await
+}
+
+/// Snapshot order books of the configured markets for later backtests
+async fn run_recorder(config: Config) -> Result<()> {
+    info!("Initializing order book recorder...");
+    let markets = MarketDirectory::new(config.general.gamma_api_url.clone());
+    let recorder = BookRecorder::new(
+        config.recorder.clone(),
+        config.general.polymarket_api_url.clone(),
+        &markets,
+    )
+    .await?;
+    recorder.run().await
+}
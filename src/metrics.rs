@@ -0,0 +1,312 @@
+use crate::errors::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::{debug, info};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl Kind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+            Kind::Histogram => "histogram",
+        }
+    }
+}
+
+/// Observations counted into cumulative `le` buckets
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Histogram {
+    buckets: &'static [f64],
+    /// Observations at or below each bucket's bound
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Series {
+    Value(f64),
+    Histogram(Histogram),
+}
+
+struct Family {
+    kind: Kind,
+    help: &'static str,
+    /// Series per rendered label set, e.g. `{api="clob"}`
+    series: BTreeMap<String, Series>,
+}
+
+/// Process-wide counters and gauges, rendered in the Prometheus text format
+///
+/// Components record into the [`global`] registry wherever they are; the
+/// `/metrics` endpoint is only served when `[metrics] enabled = true`.
+#[derive(Default)]
+pub struct Registry {
+    families: Mutex<BTreeMap<&'static str, Family>>,
+}
+
+impl Registry {
+    /// Set a gauge to `value`
+    pub fn gauge(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        self.update(name, help, Kind::Gauge, labels, |series| {
+            *series = Series::Value(value)
+        });
+    }
+
+    /// Replace every series of a gauge, so label sets no longer given are
+    /// dropped rather than left at their last value
+    pub fn replace_gauges(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        series: &[(&[(&str, &str)], f64)],
+    ) {
+        let mut families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        let series = series
+            .iter()
+            .map(|(labels, value)| (render_labels(labels), Series::Value(*value)))
+            .collect();
+        families.insert(
+            name,
+            Family {
+                kind: Kind::Gauge,
+                help,
+                series,
+            },
+        );
+    }
+
+    /// Add one to a counter
+    pub fn increment(&self, name: &'static str, help: &'static str, labels: &[(&str, &str)]) {
+        self.update(name, help, Kind::Counter, labels, |series| {
+            if let Series::Value(current) = series {
+                *current += 1.0;
+            }
+        });
+    }
+
+    /// Count `value` into a histogram with the given bucket bounds
+    pub fn observe(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        buckets: &'static [f64],
+        value: f64,
+    ) {
+        self.update(name, help, Kind::Histogram, labels, |series| {
+            if !matches!(series, Series::Histogram(_)) {
+                *series = Series::Histogram(Histogram {
+                    buckets,
+                    counts: vec![0; buckets.len()],
+                    ..Histogram::default()
+                });
+            }
+            if let Series::Histogram(histogram) = series {
+                for (bound, count) in histogram.buckets.iter().zip(&mut histogram.counts) {
+                    if value <= *bound {
+                        *count += 1;
+                    }
+                }
+                histogram.sum += value;
+                histogram.count += 1;
+            }
+        });
+    }
+
+    fn update(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        kind: Kind,
+        labels: &[(&str, &str)],
+        apply: impl FnOnce(&mut Series),
+    ) {
+        let mut families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        let family = families.entry(name).or_insert_with(|| Family {
+            kind,
+            help,
+            series: BTreeMap::new(),
+        });
+        apply(
+            family
+                .series
+                .entry(render_labels(labels))
+                .or_insert(Series::Value(0.0)),
+        );
+    }
+
+    /// The current value of a counter or gauge, or the number of observations
+    /// in a histogram
+    #[cfg(test)]
+    pub fn value(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        let families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        families
+            .get(name)
+            .and_then(|family| family.series.get(&render_labels(labels)))
+            .map(|series| match series {
+                Series::Value(value) => *value,
+                Series::Histogram(histogram) => histogram.count as f64,
+            })
+    }
+
+    /// Every series in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let families = self.families.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        for (name, family) in families.iter() {
+            let _ = writeln!(out, "# HELP {} {}", name, family.help);
+            let _ = writeln!(out, "# TYPE {} {}", name, family.kind.as_str());
+            for (labels, series) in &family.series {
+                match series {
+                    Series::Value(value) => {
+                        let _ = writeln!(out, "{}{} {}", name, labels, value);
+                    }
+                    Series::Histogram(histogram) => {
+                        let bounds = histogram
+                            .buckets
+                            .iter()
+                            .map(|bound| bound.to_string())
+                            .chain(["+Inf".to_string()]);
+                        let counts = histogram.counts.iter().chain([&histogram.count]);
+                        for (bound, count) in bounds.zip(counts) {
+                            let _ = writeln!(
+                                out,
+                                "{}_bucket{} {}",
+                                name,
+                                with_label(labels, "le", &bound),
+                                count
+                            );
+                        }
+                        let _ = writeln!(out, "{}_sum{} {}", name, labels, histogram.sum);
+                        let _ = writeln!(out, "{}_count{} {}", name, labels, histogram.count);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Serve the registry on `GET /metrics` at `listen_addr`, forever
+    pub async fn serve(&'static self, listen_addr: &str) -> Result<()> {
+        let listener = TcpListener::bind(listen_addr).await?;
+        info!("Serving metrics on http://{}/metrics", listen_addr);
+        loop {
+            let (mut stream, peer) = listener.accept().await?;
+            tokio::spawn(async move {
+                let mut request = [0u8; 1024];
+                let read = match stream.read(&mut request).await {
+                    Ok(read) => read,
+                    Err(e) => {
+                        debug!("Metrics request from {} failed: {}", peer, e);
+                        return;
+                    }
+                };
+                let request = String::from_utf8_lossy(&request[..read]);
+                let response = if request.starts_with("GET /metrics") {
+                    let body = self.render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                };
+                if let Err(e) = stream.write_all(response.as_bytes()).await {
+                    debug!("Failed to answer metrics request from {}: {}", peer, e);
+                }
+            });
+        }
+    }
+}
+
+/// The registry every component records into
+pub fn global() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::default)
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", key, escaped)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+/// `labels` (already rendered) with one more label appended
+fn with_label(labels: &str, key: &str, value: &str) -> String {
+    match labels.strip_suffix('}') {
+        Some(open) => format!("{},{}=\"{}\"}}", open, key, value),
+        None => format!("{{{}=\"{}\"}}", key, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_text() {
+        let registry = Registry::default();
+        registry.increment("polycopy_failovers_total", "Failovers", &[("api", "clob")]);
+        registry.increment("polycopy_failovers_total", "Failovers", &[("api", "clob")]);
+        registry.gauge("polycopy_up", "Whether the bot is up", &[], 1.0);
+
+        assert_eq!(
+            registry.value("polycopy_failovers_total", &[("api", "clob")]),
+            Some(2.0)
+        );
+        assert_eq!(
+            registry.render(),
+            "# HELP polycopy_failovers_total Failovers\n\
+             # TYPE polycopy_failovers_total counter\n\
+             polycopy_failovers_total{api=\"clob\"} 2\n\
+             # HELP polycopy_up Whether the bot is up\n\
+             # TYPE polycopy_up gauge\n\
+             polycopy_up 1\n"
+        );
+
+        let registry = Registry::default();
+        for seconds in [0.5, 3.0, 20.0] {
+            registry.observe("latency", "Latency", &[], &[1.0, 5.0], seconds);
+        }
+        assert_eq!(
+            registry.render(),
+            "# HELP latency Latency\n\
+             # TYPE latency histogram\n\
+             latency_bucket{le=\"1\"} 1\n\
+             latency_bucket{le=\"5\"} 2\n\
+             latency_bucket{le=\"+Inf\"} 3\n\
+             latency_sum 23.5\n\
+             latency_count 3\n"
+        );
+    }
+}
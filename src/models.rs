@@ -1,10 +1,10 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use ethers::types::Address;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 /// Order side (Buy or Sell)
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum OrderSide {
     Buy,
@@ -21,11 +21,12 @@ impl std::fmt::Display for OrderSide {
 }
 
 /// Order type
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum OrderType {
-    FOK,  // Fill or Kill
-    GTC,  // Good Till Cancelled
-    GTD,  // Good Till Date
+    FOK, // Fill or Kill
+    GTC, // Good Till Cancelled
+    GTD, // Good Till Date
 }
 
 impl std::fmt::Display for OrderType {
@@ -60,6 +61,93 @@ pub struct Trade {
     pub timestamp: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trader_win_rate: Option<Decimal>,
+    /// Share of the trader's position this trade sold (1 = all of it), when it
+    /// reduced an existing position
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_fraction: Option<Decimal>,
+    /// The market's midpoint when the trade was detected, when it was known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection_midpoint: Option<Decimal>,
+    /// Labels to attribute the copy's P&L to, e.g. the strategy or signal
+    /// source; carried onto the orders placed for it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+}
+
+/// Trades for tests: a sample adjusted with the `with_*` methods
+#[cfg(test)]
+impl Trade {
+    /// A buy of 100 shares of market `m1` at 0.50 by the zero address, made
+    /// just now, under a fresh id
+    pub fn sample() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            market_id: "m1".to_string(),
+            trader: Address::zero(),
+            side: OrderSide::Buy,
+            price: Decimal::new(50, 2),
+            size: Decimal::from(100),
+            size_usdc: Decimal::from(50),
+            timestamp: Utc::now(),
+            trader_win_rate: None,
+            exit_fraction: None,
+            detection_midpoint: None,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    pub fn with_market(mut self, market_id: &str) -> Self {
+        self.market_id = market_id.to_string();
+        self
+    }
+
+    pub fn with_trader(mut self, trader: Address) -> Self {
+        self.trader = trader;
+        self
+    }
+
+    pub fn with_side(mut self, side: OrderSide) -> Self {
+        self.side = side;
+        self
+    }
+
+    /// `size` shares at `price`, costing their product
+    pub fn with_fill(mut self, price: Decimal, size: Decimal) -> Self {
+        self.price = price;
+        self.size = size;
+        self.size_usdc = (price * size).normalize();
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_win_rate(mut self, win_rate: Decimal) -> Self {
+        self.trader_win_rate = Some(win_rate);
+        self
+    }
+
+    pub fn with_exit_fraction(mut self, exit_fraction: Decimal) -> Self {
+        self.exit_fraction = Some(exit_fraction);
+        self
+    }
+
+    pub fn with_midpoint(mut self, midpoint: Decimal) -> Self {
+        self.detection_midpoint = Some(midpoint);
+        self
+    }
+
+    pub fn with_labels(mut self, labels: &[&str]) -> Self {
+        self.labels = labels.iter().map(|label| label.to_string()).collect();
+        self
+    }
 }
 
 /// Order data for signing and submission
@@ -69,8 +157,15 @@ pub struct Order {
     pub price_decimal: Decimal,
     pub quantity: Decimal,
     pub side: OrderSide,
+    /// Address the order is funded from and attributed to
     pub owner: Address,
+    /// Key that signed the order, when it isn't the owner's (a Safe owner)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<Address>,
     pub expiration_time: u64,
+    /// The market's fee rate, which the exchange checks the signature covers
+    #[serde(default)]
+    pub fee_rate_bps: u32,
 }
 
 /// Order request to send to CLOB API
@@ -91,6 +186,9 @@ pub struct OrderRequest {
 pub struct OrderResponse {
     pub order_id: String,
     pub status: OrderStatus,
+    /// Shares matched so far, when the exchange reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_matched: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
@@ -111,6 +209,22 @@ pub struct ExecutedTrade {
     pub actual_price: Decimal,
     pub slippage: Decimal,
     pub fee: Decimal,
+    /// Filled size divided by requested size (1 = fully filled)
+    #[serde(default = "default_fill_ratio")]
+    pub fill_ratio: Decimal,
+}
+
+fn default_fill_ratio() -> Decimal {
+    Decimal::ONE
+}
+
+/// What an order would fill walking the live order book
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BookFill {
+    pub shares: Decimal,
+    pub average_price: Decimal,
+    /// Shares filled divided by shares wanted (1 = the book was deep enough)
+    pub fill_ratio: Decimal,
 }
 
 /// Position in a market
@@ -122,6 +236,9 @@ pub struct Position {
     pub side: OrderSide,
     pub timestamp: DateTime<Utc>,
     pub pnl: Decimal,
+    /// Tracked trader whose activity opened this position, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trader: Option<Address>,
 }
 
 /// Closed position with exit information
@@ -133,6 +250,110 @@ pub struct ClosedPosition {
     pub exit_timestamp: DateTime<Utc>,
 }
 
+/// Human-readable description of the market a token trades in
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarketLabel {
+    pub question: String,
+    pub slug: String,
+    /// Outcome the token pays out on, e.g. "Yes"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+}
+
+impl std::fmt::Display for MarketLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.outcome {
+            Some(ref outcome) => write!(f, "{} ({})", self.question, outcome),
+            None => write!(f, "{}", self.question),
+        }
+    }
+}
+
+/// Why a detected trade wasn't copied
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Detected too long after the trader's fill
+    Stale,
+    /// Copying is paused with the `pause` command or by the drawdown kill switch
+    Paused,
+    /// Too many recent API calls or executions failed
+    ErrorBudget,
+    /// Market or trader is blacklisted
+    Blacklisted,
+    /// Trader is suspended by pruning
+    TraderSuspended,
+    /// Trader is suspended for wash trading
+    WashTrading,
+    /// Outside the trading schedule or too close to the market's end
+    Schedule,
+    /// Trader reversed the trade or the price moved during the copy delay
+    CopyDelay,
+    /// Trader's trade is outside `min_trade_size`/`max_trade_size`
+    SizeLimits,
+    /// Market is closed, paused or restricted
+    MarketNotTradable,
+    /// Market's event isn't tagged as `event_tags`/`excluded_event_tags` require
+    EventFilter,
+    /// Outcome prices don't add up to 1
+    PriceAnomaly,
+    /// Sized copy is below the minimum order size
+    BelowMinimumSize,
+    /// Event is at its exposure cap
+    ExposureCap,
+    /// Position size couldn't be calculated
+    SizingFailed,
+    /// Our balance couldn't be fetched
+    BalanceUnavailable,
+    /// Trader exited a position we hold no copy of
+    NoPosition,
+    /// Same trader, market and side as a copy made moments ago
+    Duplicate,
+    /// Strategy has its whole budget in open positions
+    StrategyBudget,
+    /// Too little balance is free to place even a scaled-down copy
+    InsufficientBalance,
+    /// Price moved against a failed copy while it waited to be retried
+    PriceMoved,
+    /// Another instance claimed the trade first
+    ClaimedElsewhere,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::Stale => "stale",
+            SkipReason::Paused => "paused",
+            SkipReason::ErrorBudget => "error_budget",
+            SkipReason::Blacklisted => "blacklisted",
+            SkipReason::TraderSuspended => "trader_suspended",
+            SkipReason::WashTrading => "wash_trading",
+            SkipReason::Schedule => "schedule",
+            SkipReason::CopyDelay => "copy_delay",
+            SkipReason::SizeLimits => "size_limits",
+            SkipReason::MarketNotTradable => "market_not_tradable",
+            SkipReason::EventFilter => "event_filter",
+            SkipReason::PriceAnomaly => "price_anomaly",
+            SkipReason::BelowMinimumSize => "below_minimum_size",
+            SkipReason::ExposureCap => "exposure_cap",
+            SkipReason::SizingFailed => "sizing_failed",
+            SkipReason::BalanceUnavailable => "balance_unavailable",
+            SkipReason::NoPosition => "no_position",
+            SkipReason::Duplicate => "duplicate",
+            SkipReason::StrategyBudget => "strategy_budget",
+            SkipReason::InsufficientBalance => "insufficient_balance",
+            SkipReason::PriceMoved => "price_moved",
+            SkipReason::ClaimedElsewhere => "claimed_elsewhere",
+        }
+    }
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Market data
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MarketData {
@@ -158,9 +379,13 @@ pub struct HistoricalTrade {
     pub market: String,
     pub side: OrderSide,
     pub price: Decimal,
+    /// Shares traded; the USDC amount is `size * price`
     pub size: Decimal,
     pub timestamp: DateTime<Utc>,
     pub trader: Address,
+    /// Order book depth available to us at this time, if recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liquidity: Option<Decimal>,
 }
 
 /// Backtest results
@@ -177,13 +402,170 @@ pub struct BacktestResults {
     pub profit_factor: Decimal,
     pub max_drawdown: Decimal,
     pub sharpe_ratio: Decimal,
+    pub sortino_ratio: Decimal,
+    pub calmar_ratio: Decimal,
+    pub avg_holding_period_hours: Decimal,
+    /// Percentage of the backtest span with at least one open position
+    pub time_in_market: Decimal,
+    pub max_consecutive_wins: usize,
+    pub max_consecutive_losses: usize,
+    pub largest_win: Decimal,
+    pub largest_loss: Decimal,
+    /// Average filled/requested ratio across simulated executions
+    pub avg_fill_ratio: Decimal,
     pub initial_balance: Decimal,
     pub final_balance: Decimal,
+    /// Closed-position statistics grouped by source trader, best P&L first
+    #[serde(default)]
+    pub per_trader: Vec<BreakdownRow>,
+    /// Closed-position statistics grouped by market, best P&L first
+    #[serde(default)]
+    pub per_market: Vec<BreakdownRow>,
+    /// Closed positions grouped by the month they closed in, oldest first
+    #[serde(default)]
+    pub monthly: Vec<PeriodRow>,
+    /// Closed positions grouped by ISO week, oldest first
+    #[serde(default)]
+    pub weekly: Vec<PeriodRow>,
+    /// Sharpe ratio of daily returns over the trailing 30 days, for each day
+    /// from the 30th on
+    #[serde(default)]
+    pub rolling_sharpe: Vec<RollingSharpe>,
+    /// P&L of the positions left open at the end, marked at their last price;
+    /// part of the total P&L only with `end_of_backtest = "mark"`
+    #[serde(default)]
+    pub unrealized_pnl: Decimal,
+    /// Positions left open at the end, with `end_of_backtest = "mark"` or
+    /// `"unrealized"`
+    #[serde(default)]
+    pub open_positions: Vec<OpenPositionRow>,
+    /// P&L before slippage, fees and gas, open positions included
+    #[serde(default)]
+    pub gross_pnl: Decimal,
+    /// Slippage, fees, gas and all three together, in total, per simulated
+    /// execution and as a share of the gross P&L
+    #[serde(default)]
+    pub costs: Vec<CostRow>,
+    /// How the strategy behaved inside each stress-test window
+    #[serde(default)]
+    pub scenarios: Vec<ScenarioReport>,
+    /// Rolled remainders of partial fills still waiting for liquidity when the
+    /// run ended, with `unfilled_policy = "roll"`
+    #[serde(default)]
+    pub unrolled_remainders: usize,
+    /// What actually happened live, when replaying a recorded session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_comparison: Option<LiveComparison>,
+}
+
+/// A replayed live session's real fills next to the simulated ones
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiveComparison {
+    pub detected_trades: usize,
+    pub live_copies: usize,
+    /// Trades the live bot skipped or failed to copy
+    pub live_not_copied: usize,
+    pub live_avg_slippage: Decimal,
+    pub live_avg_fill_ratio: Decimal,
+    pub live_volume_usdc: Decimal,
+    pub simulated_copies: usize,
+    pub simulated_avg_slippage: Decimal,
+    pub simulated_avg_fill_ratio: Decimal,
+    pub simulated_volume_usdc: Decimal,
+}
+
+/// Aggregated results for one group (trader or market) of closed positions
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BreakdownRow {
+    pub key: String,
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub win_rate: Decimal,
+    pub total_pnl: Decimal,
+}
+
+/// A position still open when the backtest ended
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OpenPositionRow {
+    pub market_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trader: Option<Address>,
+    pub size: Decimal,
+    pub entry_price: Decimal,
+    pub mark_price: Decimal,
+    pub unrealized_pnl: Decimal,
+}
+
+impl OpenPositionRow {
+    /// `position` valued at `mark_price`, less what it cost to open
+    pub fn marked(position: &Position, mark_price: Decimal) -> Self {
+        let unrealized_pnl = position.pnl
+            + match position.side {
+                OrderSide::Buy => (mark_price - position.entry_price) * position.size,
+                OrderSide::Sell => (position.entry_price - mark_price) * position.size,
+            };
+        Self {
+            market_id: position.market_id.clone(),
+            trader: position.trader,
+            size: position.size,
+            entry_price: position.entry_price,
+            mark_price,
+            unrealized_pnl,
+        }
+    }
+}
+
+/// One kind of execution cost over the whole backtest
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CostRow {
+    /// "slippage", "fees", "gas" or "total"
+    pub kind: String,
+    pub total: Decimal,
+    pub per_trade: Decimal,
+    /// Percentage of the gross P&L, None unless that was positive
+    pub pct_of_gross: Option<Decimal>,
+}
+
+/// Results of the positions closed in one month or week
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeriodRow {
+    /// "2024-03" for a month, "2024-W09" for an ISO week
+    pub period: String,
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub win_rate: Decimal,
+    pub total_pnl: Decimal,
+    /// P&L as a percentage of the balance the period started with
+    pub return_pct: Decimal,
+    /// The 30-day rolling Sharpe ratio on the period's last day, once there
+    /// are 30 days of history
+    pub rolling_sharpe: Option<Decimal>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RollingSharpe {
+    pub date: NaiveDate,
+    pub sharpe: Decimal,
+}
+
+/// Portfolio behaviour inside one stress-test scenario window
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub kind: String,
+    /// Historical trades altered or hidden by the shock
+    pub trades_affected: usize,
+    /// Change in mark-to-market portfolio value across the window
+    pub pnl: Decimal,
+    /// Largest peak-to-trough decline within the window (percent)
+    pub max_drawdown: Decimal,
+    /// Copies rejected for balance or sizing limits within the window
+    pub rejected_copies: usize,
 }
 
 impl BacktestResults {
     pub fn format_report(&self) -> String {
-        format!(
+        let mut report = format!(
             r#"
 ╔══════════════════════════════════════════════════════════════╗
 ║              BACKTEST RESULTS                                ║
@@ -203,6 +585,16 @@ impl BacktestResults {
 ║ Profit Factor:       {:>40} ║
 ║ Max Drawdown:        {:>39}% ║
 ║ Sharpe Ratio:        {:>40} ║
+║ Sortino Ratio:       {:>40} ║
+║ Calmar Ratio:        {:>40} ║
+╠══════════════════════════════════════════════════════════════╣
+║ Largest Win:         {:>38} USDC ║
+║ Largest Loss:        {:>38} USDC ║
+║ Max Win Streak:      {:>40} ║
+║ Max Loss Streak:     {:>40} ║
+║ Avg Holding Period:  {:>36} hrs ║
+║ Time in Market:      {:>39}% ║
+║ Avg Fill Ratio:      {:>39}% ║
 ╚══════════════════════════════════════════════════════════════╝
 "#,
             self.total_trades,
@@ -218,6 +610,284 @@ impl BacktestResults {
             self.profit_factor.round_dp(2),
             self.max_drawdown.round_dp(2),
             self.sharpe_ratio.round_dp(2),
+            self.sortino_ratio.round_dp(2),
+            self.calmar_ratio.round_dp(2),
+            self.largest_win.round_dp(2),
+            self.largest_loss.round_dp(2),
+            self.max_consecutive_wins,
+            self.max_consecutive_losses,
+            self.avg_holding_period_hours.round_dp(2),
+            self.time_in_market.round_dp(2),
+            (self.avg_fill_ratio * Decimal::from(100)).round_dp(2),
+        );
+
+        if self.unrolled_remainders > 0 {
+            report.push_str(&format!(
+                "\n{} rolled remainder(s) were still unfilled when the backtest ended\n",
+                self.unrolled_remainders
+            ));
+        }
+        if !self.costs.is_empty() {
+            report.push_str(&format_costs(&self.costs, self.gross_pnl));
+        }
+        if !self.per_trader.is_empty() {
+            report.push_str(&format_breakdown(
+                "PER-TRADER BREAKDOWN",
+                "Trader",
+                &self.per_trader,
+            ));
+        }
+        if !self.per_market.is_empty() {
+            report.push_str(&format_breakdown(
+                "PER-MARKET BREAKDOWN",
+                "Market",
+                &self.per_market,
+            ));
+        }
+        if !self.open_positions.is_empty() {
+            report.push_str(&format_open_positions(
+                &self.open_positions,
+                self.unrealized_pnl,
+            ));
+        }
+        if !self.monthly.is_empty() {
+            report.push_str(&format_periods(
+                "MONTHLY PERFORMANCE",
+                "Month",
+                &self.monthly,
+            ));
+        }
+        if !self.weekly.is_empty() {
+            report.push_str(&format_periods("WEEKLY PERFORMANCE", "Week", &self.weekly));
+        }
+        if !self.scenarios.is_empty() {
+            report.push_str(&format_scenarios(&self.scenarios));
+        }
+        if let Some(ref comparison) = self.live_comparison {
+            report.push_str(&format_live_comparison(comparison));
+        }
+
+        report
+    }
+}
+
+/// Render a breakdown table in the same box style as the main report
+fn format_breakdown(title: &str, key_header: &str, rows: &[BreakdownRow]) -> String {
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!("║ {:<60} ║\n", title));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<21} {:>8} {:>10} {:>18} ║\n",
+        key_header, "Trades", "Win Rate", "P&L (USDC)"
+    ));
+    for row in rows {
+        table.push_str(&format!(
+            "║ {:<21} {:>8} {:>9}% {:>18} ║\n",
+            abbreviate(&row.key, 21),
+            row.trades,
+            row.win_rate.round_dp(2),
+            row.total_pnl.round_dp(2),
+        ));
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+/// Render execution costs in the same box style as the main report
+fn format_costs(costs: &[CostRow], gross_pnl: Decimal) -> String {
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!(
+        "║ {:<60} ║\n",
+        format!("EXECUTION COSTS ({} USDC gross P&L)", gross_pnl.round_dp(2))
+    ));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<21} {:>12} {:>12} {:>12} ║\n",
+        "Cost", "Total (USDC)", "Per Trade", "% of Gross"
+    ));
+    for row in costs {
+        let pct_of_gross = match row.pct_of_gross {
+            Some(pct) => format!("{}%", pct.round_dp(2)),
+            None => "n/a".to_string(),
+        };
+        table.push_str(&format!(
+            "║ {:<21} {:>12} {:>12} {:>12} ║\n",
+            row.kind,
+            row.total.round_dp(2),
+            row.per_trade.round_dp(4),
+            pct_of_gross,
+        ));
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+/// Render positions left open at the end in the same box style as the main report
+fn format_open_positions(positions: &[OpenPositionRow], unrealized_pnl: Decimal) -> String {
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!(
+        "║ {:<60} ║\n",
+        format!(
+            "OPEN POSITIONS ({} USDC unrealized)",
+            unrealized_pnl.round_dp(2)
         )
+    ));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<20} {:>10} {:>8} {:>8} {:>10} ║\n",
+        "Market", "Size", "Entry", "Mark", "Unrealized"
+    ));
+    for position in positions {
+        table.push_str(&format!(
+            "║ {:<20} {:>10} {:>8} {:>8} {:>10} ║\n",
+            abbreviate(&position.market_id, 20),
+            position.size.round_dp(2),
+            position.entry_price.round_dp(3),
+            position.mark_price.round_dp(3),
+            position.unrealized_pnl.round_dp(2),
+        ));
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+/// Render per-period results in the same box style as the main report, with
+/// how much of the total P&L the best period made
+fn format_periods(title: &str, key_header: &str, rows: &[PeriodRow]) -> String {
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!("║ {:<60} ║\n", title));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<9} {:>7} {:>9} {:>12} {:>9} {:>9} ║\n",
+        key_header, "Trades", "Win Rate", "P&L (USDC)", "Return", "30d SR"
+    ));
+    for row in rows {
+        table.push_str(&format!(
+            "║ {:<9} {:>7} {:>8}% {:>12} {:>8}% {:>9} ║\n",
+            row.period,
+            row.trades,
+            row.win_rate.round_dp(2),
+            row.total_pnl.round_dp(2),
+            row.return_pct.round_dp(2),
+            row.rolling_sharpe
+                .map(|sharpe| sharpe.round_dp(2).to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        ));
+    }
+    let total: Decimal = rows.iter().map(|row| row.total_pnl).sum();
+    let best = rows.iter().max_by_key(|row| row.total_pnl);
+    if let (Some(best), true) = (best, total > Decimal::ZERO && rows.len() > 1) {
+        table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+        table.push_str(&format!(
+            "║ {:<60} ║\n",
+            format!(
+                "Best: {} with {}% of the total P&L",
+                best.period,
+                (best.total_pnl / total * Decimal::ONE_HUNDRED).round_dp(1)
+            )
+        ));
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+/// Render stress-test scenario outcomes in the same box style as the main report
+fn format_scenarios(scenarios: &[ScenarioReport]) -> String {
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!("║ {:<60} ║\n", "STRESS SCENARIOS"));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<19} {:>8} {:>12} {:>9} {:>8} ║\n",
+        "Scenario", "Affected", "P&L (USDC)", "Max DD", "Rejected"
+    ));
+    for scenario in scenarios {
+        table.push_str(&format!(
+            "║ {:<19} {:>8} {:>12} {:>8}% {:>8} ║\n",
+            abbreviate(&scenario.name, 19),
+            scenario.trades_affected,
+            scenario.pnl.round_dp(2),
+            scenario.max_drawdown.round_dp(2),
+            scenario.rejected_copies,
+        ));
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+/// Render a replayed session's live and simulated results side by side
+fn format_live_comparison(comparison: &LiveComparison) -> String {
+    let mut table = String::new();
+    table.push_str("╔══════════════════════════════════════════════════════════════╗\n");
+    table.push_str(&format!(
+        "║ {:<60} ║\n",
+        format!(
+            "LIVE vs BACKTEST ({} detected trades)",
+            comparison.detected_trades
+        )
+    ));
+    table.push_str("╠══════════════════════════════════════════════════════════════╣\n");
+    table.push_str(&format!(
+        "║ {:<24} {:>17} {:>17} ║\n",
+        "", "Live", "Backtest"
+    ));
+    let rows = [
+        (
+            "Copies",
+            comparison.live_copies.to_string(),
+            comparison.simulated_copies.to_string(),
+        ),
+        (
+            "Not copied",
+            comparison.live_not_copied.to_string(),
+            String::new(),
+        ),
+        (
+            "Avg slippage",
+            comparison.live_avg_slippage.round_dp(4).to_string(),
+            comparison.simulated_avg_slippage.round_dp(4).to_string(),
+        ),
+        (
+            "Avg fill ratio",
+            format!(
+                "{}%",
+                (comparison.live_avg_fill_ratio * Decimal::from(100)).round_dp(2)
+            ),
+            format!(
+                "{}%",
+                (comparison.simulated_avg_fill_ratio * Decimal::from(100)).round_dp(2)
+            ),
+        ),
+        (
+            "Volume (USDC)",
+            comparison.live_volume_usdc.round_dp(2).to_string(),
+            comparison.simulated_volume_usdc.round_dp(2).to_string(),
+        ),
+    ];
+    for (label, live, simulated) in rows {
+        table.push_str(&format!(
+            "║ {:<24} {:>17} {:>17} ║\n",
+            label, live, simulated
+        ));
+    }
+    table.push_str("╚══════════════════════════════════════════════════════════════╝\n");
+    table
+}
+
+/// Shorten long identifiers (addresses, market hashes) to `head…tail`
+pub(crate) fn abbreviate(value: &str, max_len: usize) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= max_len {
+        return value.to_string();
     }
+    let tail = (max_len - 1) / 2;
+    let head = max_len - 1 - tail;
+    let mut short: String = chars[..head].iter().collect();
+    short.push('…');
+    short.extend(&chars[chars.len() - tail..]);
+    short
 }
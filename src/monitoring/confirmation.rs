@@ -0,0 +1,147 @@
+use crate::models::Trade;
+use crate::monitoring::PriceCache;
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Whether a delayed trade is still worth copying
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reconfirmation {
+    Confirmed,
+    /// The trader took the other side of the same market during the delay
+    Reversed,
+    /// The market price moved this far from the trader's fill during the
+    /// delay, counting the fee per share we would pay on top
+    PriceGapped(Decimal),
+}
+
+/// Holds detected trades for the copy delay, then re-checks them
+///
+/// Every detected trade must be passed to [`record`](Self::record) so that
+/// reversals during the delay are seen. The price check uses the shared
+/// price cache and is skipped when the market has no fresh quote.
+pub struct CopyDelay {
+    delay: Duration,
+    max_price_gap: Decimal,
+    prices: Arc<PriceCache>,
+    recent: Mutex<VecDeque<(Instant, Trade)>>,
+}
+
+impl CopyDelay {
+    pub fn new(delay: Duration, max_price_gap: Decimal, prices: Arc<PriceCache>) -> Self {
+        Self {
+            delay,
+            max_price_gap,
+            prices,
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Remember a detected trade so it can reverse earlier ones
+    pub fn record(&self, trade: &Trade) {
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        // Anything older than two delays can no longer affect a pending trade
+        while recent
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > self.delay * 2)
+        {
+            recent.pop_front();
+        }
+        recent.push_back((now, trade.clone()));
+    }
+
+    /// Wait out the delay and check `trade` again; `fee_per_share` is what
+    /// copying it would cost in fees, which the trader's fill price may not
+    /// include
+    pub async fn reconfirm(&self, trade: &Trade, fee_per_share: Decimal) -> Reconfirmation {
+        tokio::time::sleep(self.delay).await;
+        self.check(trade, fee_per_share)
+    }
+
+    fn check(&self, trade: &Trade, fee_per_share: Decimal) -> Reconfirmation {
+        let reversed = {
+            let recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+            let detected_at = recent
+                .iter()
+                .find(|(_, t)| t.id == trade.id)
+                .map(|(at, _)| *at);
+            recent.iter().any(|(at, later)| {
+                detected_at.is_none_or(|detected_at| *at >= detected_at)
+                    && later.trader == trade.trader
+                    && later.market_id == trade.market_id
+                    && later.side != trade.side
+            })
+        };
+        if reversed {
+            return Reconfirmation::Reversed;
+        }
+
+        if let Some(price) = self.prices.price(&trade.market_id) {
+            let gap = (price - trade.price).abs() + fee_per_share;
+            if gap > self.max_price_gap {
+                return Reconfirmation::PriceGapped(gap);
+            }
+        }
+        Reconfirmation::Confirmed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderSide;
+    use ethers::types::Address;
+    use rust_decimal_macros::dec;
+
+    fn trade(id: &str, market: &str, side: OrderSide) -> Trade {
+        Trade::sample()
+            .with_id(id)
+            .with_market(market)
+            .with_trader(Address::from_low_u64_be(1))
+            .with_side(side)
+            .with_fill(dec!(0.40), dec!(100))
+    }
+
+    #[tokio::test]
+    async fn test_reconfirm_catches_reversals_and_gaps() {
+        let prices = Arc::new(PriceCache::new(Duration::from_secs(60)));
+        let delay = CopyDelay::new(Duration::from_millis(10), dec!(0.05), prices.clone());
+
+        let entry = trade("t1", "m1", OrderSide::Buy);
+        delay.record(&entry);
+        assert_eq!(
+            delay.reconfirm(&entry, dec!(0)).await,
+            Reconfirmation::Confirmed
+        );
+
+        prices.track("m1");
+        prices.update("m1", dec!(0.44));
+        assert_eq!(
+            delay.reconfirm(&entry, dec!(0)).await,
+            Reconfirmation::Confirmed
+        );
+        // Fees push the same move over the limit
+        assert_eq!(
+            delay.reconfirm(&entry, dec!(0.02)).await,
+            Reconfirmation::PriceGapped(dec!(0.06))
+        );
+
+        // The trader dumps the position before our copy goes out
+        delay.record(&trade("t2", "m1", OrderSide::Sell));
+        assert_eq!(
+            delay.reconfirm(&entry, dec!(0)).await,
+            Reconfirmation::Reversed
+        );
+
+        // A sell in another market doesn't count
+        let other = trade("t3", "m2", OrderSide::Buy);
+        delay.record(&other);
+        delay.record(&trade("t4", "m3", OrderSide::Sell));
+        assert_eq!(
+            delay.reconfirm(&other, dec!(0)).await,
+            Reconfirmation::Confirmed
+        );
+    }
+}
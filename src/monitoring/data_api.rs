@@ -0,0 +1,246 @@
+use crate::endpoints::Endpoints;
+use crate::errors::{PolymarketError, Result};
+use crate::models::{MarketLabel, OrderSide, Position, Trade};
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use reqwest::{Client, RequestBuilder, Response};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// An open position as reported by the data API
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataApiPosition {
+    /// Outcome token id
+    pub asset: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub outcome: Option<String>,
+    pub size: Decimal,
+    #[serde(default)]
+    pub avg_price: Decimal,
+    #[serde(default)]
+    pub cur_price: Decimal,
+    #[serde(default)]
+    pub cash_pnl: Decimal,
+    /// The market has resolved and the position can be redeemed for `cur_price`
+    #[serde(default)]
+    pub redeemable: bool,
+}
+
+impl DataApiPosition {
+    pub fn label(&self) -> MarketLabel {
+        MarketLabel {
+            question: self.title.clone(),
+            slug: self.slug.clone(),
+            outcome: self.outcome.clone(),
+        }
+    }
+
+    /// The position as `trader`'s holding of the outcome token, marked to
+    /// `cur_price` through its P&L
+    pub fn to_position(&self, trader: Address, now: DateTime<Utc>) -> Position {
+        Position {
+            market_id: self.asset.clone(),
+            entry_price: self.avg_price,
+            size: self.size,
+            side: OrderSide::Buy,
+            timestamp: now,
+            pnl: self.cash_pnl,
+            trader: Some(trader),
+        }
+    }
+}
+
+/// A trade as reported by the data API
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataApiTrade {
+    pub proxy_wallet: Address,
+    pub side: OrderSide,
+    /// Outcome token id
+    pub asset: String,
+    pub size: Decimal,
+    pub price: Decimal,
+    /// Unix seconds
+    pub timestamp: i64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub outcome: Option<String>,
+    #[serde(default)]
+    pub transaction_hash: String,
+}
+
+impl DataApiTrade {
+    pub fn label(&self) -> MarketLabel {
+        MarketLabel {
+            question: self.title.clone(),
+            slug: self.slug.clone(),
+            outcome: self.outcome.clone(),
+        }
+    }
+
+    pub fn to_trade(&self) -> Trade {
+        Trade {
+            // One transaction can fill several outcome tokens
+            id: format!("{}:{}", self.transaction_hash, self.asset),
+            market_id: self.asset.clone(),
+            trader: self.proxy_wallet,
+            side: self.side.clone(),
+            price: self.price,
+            size: self.size,
+            size_usdc: self.size * self.price,
+            timestamp: DateTime::from_timestamp(self.timestamp, 0).unwrap_or_default(),
+            trader_win_rate: None,
+            exit_fraction: None,
+            detection_midpoint: None,
+            labels: Vec::new(),
+        }
+    }
+}
+
+/// A wallet's total position value as reported by the data API
+#[derive(Clone, Debug, Deserialize)]
+struct DataApiValue {
+    value: Decimal,
+}
+
+/// Client for Polymarket's public data API (positions and trade history of
+/// any wallet)
+#[derive(Clone)]
+pub struct DataApiClient {
+    http_client: Client,
+    endpoints: Arc<Endpoints>,
+}
+
+impl DataApiClient {
+    pub fn new(data_api_url: String) -> Self {
+        Self {
+            http_client: Client::new(),
+            endpoints: Arc::new(Endpoints::single("data", data_api_url)),
+        }
+    }
+
+    /// Send requests through `http_client`, e.g. one with the configured proxy
+    pub fn with_http_client(mut self, http_client: Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Query the active one of these endpoints, failing over between them
+    pub fn with_endpoints(mut self, endpoints: Arc<Endpoints>) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Open positions of `user`, largest first
+    pub async fn positions(&self, user: Address) -> Result<Vec<DataApiPosition>> {
+        let response = self.positions_response(user, |request| request).await?;
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch positions: {}",
+                response.status()
+            )));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// The response to a request for `user`'s positions, after `prepare`
+    /// adds to the request, e.g. conditional headers
+    pub async fn positions_response(
+        &self,
+        user: Address,
+        prepare: impl FnOnce(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response> {
+        let request = self
+            .http_client
+            .get(format!("{}/positions", self.endpoints.url()))
+            .query(&[
+                ("user", format!("{:?}", user)),
+                ("sortBy", "CURRENT".to_string()),
+                ("sortDirection", "DESC".to_string()),
+            ]);
+        self.send(prepare(request)).await
+    }
+
+    /// The most recent `limit` trades of `user`, newest first
+    pub async fn trades(&self, user: Address, limit: usize) -> Result<Vec<DataApiTrade>> {
+        let request = self
+            .http_client
+            .get(format!("{}/trades", self.endpoints.url()));
+        let response = self
+            .send(request.query(&[
+                ("user", format!("{:?}", user)),
+                ("limit", limit.to_string()),
+            ]))
+            .await?;
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch trades: {}",
+                response.status()
+            )));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Current value of all of `user`'s open positions, in USDC
+    pub async fn portfolio_value(&self, user: Address) -> Result<Decimal> {
+        let request = self
+            .http_client
+            .get(format!("{}/value", self.endpoints.url()));
+        let response = self
+            .send(request.query(&[("user", format!("{:?}", user))]))
+            .await?;
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch portfolio value: {}",
+                response.status()
+            )));
+        }
+        let values: Vec<DataApiValue> = response.json().await?;
+        Ok(values.iter().map(|v| v.value).sum())
+    }
+
+    /// Send `request`, reporting the outcome to the endpoint set
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let request = request.timeout(self.endpoints.request_timeout()).build()?;
+        let url = request.url().to_string();
+        let response = self.http_client.execute(request).await;
+        self.endpoints.record(
+            &url,
+            response
+                .as_ref()
+                .is_ok_and(|r| !r.status().is_server_error()),
+        );
+        Ok(response?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_data_api_trade() {
+        let body = r#"[{"proxyWallet":"0x56687bf447db6ffa42ffe2204a05edaa20f55839",
+            "side":"BUY","asset":"111","conditionId":"0xabc","size":250,"price":0.42,
+            "timestamp":1730800000,"title":"Will it rain?","slug":"rain","outcome":"Yes",
+            "transactionHash":"0xdef"}]"#;
+        let trades: Vec<DataApiTrade> = serde_json::from_str(body).unwrap();
+        let trade = trades[0].to_trade();
+
+        assert_eq!(trade.id, "0xdef:111");
+        assert_eq!(trade.side, OrderSide::Buy);
+        assert_eq!(trade.size_usdc, dec!(105));
+        assert_eq!(trade.timestamp.timestamp(), 1_730_800_000);
+        assert_eq!(trades[0].label().to_string(), "Will it rain? (Yes)");
+    }
+}
@@ -1,12 +1,14 @@
-use crate::models::Trade;
+use crate::models::{HistoricalTrade, OrderSide, Trade};
+use ethers::types::Address;
 use rust_decimal::Decimal;
-use std::collections::HashSet;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 pub struct TradeFilter {
     pub min_size_usdc: Decimal,
     pub max_size_usdc: Decimal,
     pub allowed_markets: Option<HashSet<String>>,
+    #[allow(dead_code)]
     pub min_market_duration: Duration,
     pub filter_by_win_rate: Option<Decimal>,
 }
@@ -27,36 +29,53 @@ impl TradeFilter {
         self
     }
 
+    #[allow(dead_code)]
     pub fn with_min_duration(mut self, duration: Duration) -> Self {
         self.min_market_duration = duration;
         self
     }
 
+    #[allow(dead_code)]
     pub fn with_min_win_rate(mut self, win_rate: Decimal) -> Self {
         self.filter_by_win_rate = Some(win_rate);
         self
     }
 
     /// Check if a trade should be copied based on filters
+    #[allow(dead_code)]
     pub fn should_copy(&self, trade: &Trade) -> bool {
+        self.passes(&trade.market_id, trade.size_usdc, trade.trader_win_rate)
+    }
+
+    /// Check if a historical (backtest) trade should be copied based on filters
+    pub fn should_copy_historical(&self, trade: &HistoricalTrade) -> bool {
+        self.passes(&trade.market, trade.size * trade.price, None)
+    }
+
+    fn passes(
+        &self,
+        market_id: &str,
+        size_usdc: Decimal,
+        trader_win_rate: Option<Decimal>,
+    ) -> bool {
         // Size filters
-        if trade.size_usdc < self.min_size_usdc {
+        if size_usdc < self.min_size_usdc {
             return false;
         }
-        if trade.size_usdc > self.max_size_usdc {
+        if size_usdc > self.max_size_usdc {
             return false;
         }
 
         // Market filters
         if let Some(ref allowed) = self.allowed_markets {
-            if !allowed.contains(&trade.market_id) {
+            if !allowed.contains(market_id) {
                 return false;
             }
         }
 
         // Trader track record
         if let Some(min_wr) = self.filter_by_win_rate {
-            if let Some(trader_wr) = trade.trader_win_rate {
+            if let Some(trader_wr) = trader_win_rate {
                 if trader_wr < min_wr {
                     return false;
                 }
@@ -70,43 +89,120 @@ impl TradeFilter {
     }
 }
 
+/// Coalesces a trader's clip entries into one trade per market and side
+///
+/// Whales often build a position through dozens of small fills. The first
+/// clip opens a bucket for `window`; later clips on the same market and side
+/// join it, and the bucket is released as a single trade at the volume-weighted
+/// average price once the window has passed. A clip on the other side releases
+/// the bucket immediately so the order of a trader's trades is preserved.
+pub struct TradeAggregator {
+    window: Duration,
+    pending: HashMap<(Address, String, OrderSide), (Instant, Trade)>,
+}
+
+impl TradeAggregator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Add a detected clip; returns any trades that are ready to copy
+    pub fn push(&mut self, trade: Trade, now: Instant) -> Vec<Trade> {
+        let opposite = match trade.side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        };
+        let mut ready: Vec<Trade> = self
+            .pending
+            .remove(&(trade.trader, trade.market_id.clone(), opposite))
+            .map(|(_, t)| t)
+            .into_iter()
+            .collect();
+
+        let key = (trade.trader, trade.market_id.clone(), trade.side.clone());
+        match self.pending.get_mut(&key) {
+            Some((_, bucket)) => merge(bucket, &trade),
+            None => {
+                self.pending.insert(key, (now, trade));
+            }
+        }
+
+        ready.extend(self.flush_due(now));
+        ready
+    }
+
+    /// Release every bucket whose window has passed
+    pub fn flush_due(&mut self, now: Instant) -> Vec<Trade> {
+        let due: Vec<_> = self
+            .pending
+            .iter()
+            .filter(|(_, (opened, _))| now.duration_since(*opened) >= self.window)
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut ready: Vec<Trade> = due
+            .into_iter()
+            .filter_map(|key| self.pending.remove(&key))
+            .map(|(_, trade)| trade)
+            .collect();
+        ready.sort_by_key(|trade| trade.timestamp);
+        ready
+    }
+
+    /// Release every bucket of `trader` now, window or not
+    pub fn drain_trader(&mut self, trader: &Address) -> Vec<Trade> {
+        let keys: Vec<_> = self
+            .pending
+            .keys()
+            .filter(|(owner, _, _)| owner == trader)
+            .cloned()
+            .collect();
+        let mut ready: Vec<Trade> = keys
+            .into_iter()
+            .filter_map(|key| self.pending.remove(&key))
+            .map(|(_, trade)| trade)
+            .collect();
+        ready.sort_by_key(|trade| trade.timestamp);
+        ready
+    }
+}
+
+/// Fold `clip` into `bucket`, keeping the first clip's id
+fn merge(bucket: &mut Trade, clip: &Trade) {
+    bucket.size += clip.size;
+    bucket.size_usdc += clip.size_usdc;
+    if bucket.size > Decimal::ZERO {
+        bucket.price = bucket.size_usdc / bucket.size;
+    }
+    bucket.timestamp = clip.timestamp;
+    bucket.trader_win_rate = clip.trader_win_rate.or(bucket.trader_win_rate);
+    // Two exits of 40% then 50% of what was left leave 30% of the position
+    bucket.exit_fraction = match (bucket.exit_fraction, clip.exit_fraction) {
+        (Some(a), Some(b)) => Some(Decimal::ONE - (Decimal::ONE - a) * (Decimal::ONE - b)),
+        _ => None,
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::OrderSide;
-    use chrono::Utc;
     use rust_decimal_macros::dec;
 
     #[test]
     fn test_trade_filter_size() {
         let filter = TradeFilter::new(dec!(10), dec!(1000));
 
-        let valid_trade = Trade {
-            id: "1".to_string(),
-            market_id: "market1".to_string(),
-            trader: "0x0000000000000000000000000000000000000000"
-                .parse()
-                .unwrap(),
-            side: OrderSide::Buy,
-            price: dec!(0.5),
-            size: dec!(100),
-            size_usdc: dec!(50),
-            timestamp: Utc::now(),
-            trader_win_rate: None,
-        };
+        let valid_trade = Trade::sample();
 
         assert!(filter.should_copy(&valid_trade));
 
-        let too_small = Trade {
-            size_usdc: dec!(5),
-            ..valid_trade.clone()
-        };
+        let too_small = Trade::sample().with_fill(dec!(0.5), dec!(10));
         assert!(!filter.should_copy(&too_small));
 
-        let too_large = Trade {
-            size_usdc: dec!(5000),
-            ..valid_trade.clone()
-        };
+        let too_large = Trade::sample().with_fill(dec!(0.5), dec!(10000));
         assert!(!filter.should_copy(&too_large));
     }
 
@@ -114,26 +210,44 @@ mod tests {
     fn test_trade_filter_win_rate() {
         let filter = TradeFilter::new(dec!(10), dec!(1000)).with_min_win_rate(dec!(0.6));
 
-        let high_wr_trade = Trade {
-            id: "1".to_string(),
-            market_id: "market1".to_string(),
-            trader: "0x0000000000000000000000000000000000000000"
-                .parse()
-                .unwrap(),
-            side: OrderSide::Buy,
-            price: dec!(0.5),
-            size: dec!(100),
-            size_usdc: dec!(50),
-            timestamp: Utc::now(),
-            trader_win_rate: Some(dec!(0.7)),
-        };
+        let high_wr_trade = Trade::sample().with_win_rate(dec!(0.7));
 
         assert!(filter.should_copy(&high_wr_trade));
 
-        let low_wr_trade = Trade {
-            trader_win_rate: Some(dec!(0.4)),
-            ..high_wr_trade.clone()
-        };
+        let low_wr_trade = Trade::sample().with_win_rate(dec!(0.4));
         assert!(!filter.should_copy(&low_wr_trade));
     }
+
+    #[test]
+    fn test_aggregator_merges_clips_at_vwap() {
+        let clip = |side: OrderSide, price: Decimal, size: Decimal| {
+            Trade::sample()
+                .with_market("market1")
+                .with_side(side)
+                .with_fill(price, size)
+        };
+        let start = Instant::now();
+        let mut aggregator = TradeAggregator::new(Duration::from_secs(60));
+
+        let first = clip(OrderSide::Buy, dec!(0.40), dec!(100));
+        assert!(aggregator.push(first.clone(), start).is_empty());
+        let later = start + Duration::from_secs(10);
+        assert!(aggregator
+            .push(clip(OrderSide::Buy, dec!(0.50), dec!(300)), later)
+            .is_empty());
+        assert!(aggregator.flush_due(later).is_empty());
+
+        let ready = aggregator.flush_due(start + Duration::from_secs(60));
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].id, first.id);
+        assert_eq!(ready[0].size, dec!(400));
+        assert_eq!(ready[0].size_usdc, dec!(190));
+        assert_eq!(ready[0].price, dec!(0.475));
+
+        // Selling out releases the open buy bucket straight away
+        aggregator.push(clip(OrderSide::Buy, dec!(0.50), dec!(10)), later);
+        let ready = aggregator.push(clip(OrderSide::Sell, dec!(0.55), dec!(10)), later);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].side, OrderSide::Buy);
+    }
 }
@@ -0,0 +1,73 @@
+use crate::config::Config;
+use crate::errors::Result;
+use crate::models::Trade;
+use ethers::types::Address;
+use std::collections::HashMap;
+
+/// Labels the configuration puts on the trades it copies: its own, for every
+/// trade, and each trader profile's, for that trader's trades
+#[derive(Clone, Debug, Default)]
+pub struct TradeLabels {
+    common: Vec<String>,
+    by_trader: HashMap<Address, Vec<String>>,
+}
+
+impl TradeLabels {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut by_trader = HashMap::new();
+        for profile in &config.traders.profiles {
+            if !profile.labels.is_empty() {
+                by_trader.insert(profile.get_address()?, profile.labels.clone());
+            }
+        }
+        Ok(Self {
+            common: config.general.labels.clone(),
+            by_trader,
+        })
+    }
+
+    /// Add the configured labels `trade` doesn't carry yet, after those it
+    /// arrived with (e.g. from a trade signal)
+    pub fn apply(&self, trade: &mut Trade) {
+        let profile = self.by_trader.get(&trade.trader).into_iter().flatten();
+        for label in self.common.iter().chain(profile) {
+            if !trade.labels.contains(label) {
+                trade.labels.push(label.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_configured_labels_are_added_once() {
+        let mut config = Config::load_from_file("config.example.toml").unwrap();
+        let whale = Address::from_low_u64_be(1);
+        config.general.labels = vec!["momentum".to_string()];
+        config.traders.profiles = vec![toml::from_str(&format!(
+            "address = \"{:?}\"\nlabels = [\"whales\", \"momentum\"]",
+            whale
+        ))
+        .unwrap()];
+        let labels = TradeLabels::from_config(&config).unwrap();
+
+        let trade = |trader: Address, labels: &[&str]| {
+            Trade::sample()
+                .with_id("t1")
+                .with_trader(trader)
+                .with_fill(dec!(0.5), dec!(10))
+                .with_labels(labels)
+        };
+        let mut detected = trade(whale, &[]);
+        labels.apply(&mut detected);
+        assert_eq!(detected.labels, ["momentum", "whales"]);
+
+        let mut signal = trade(Address::from_low_u64_be(2), &["manual"]);
+        labels.apply(&mut signal);
+        assert_eq!(signal.labels, ["manual", "momentum"]);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::metrics;
+use crate::models::Trade;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Recent detections the median is taken over
+const LATENCY_SAMPLES: usize = 50;
+
+/// Histogram bounds for detection latency, in seconds
+const LATENCY_BUCKETS: &[f64] = &[0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+/// Tracks how long after a trader's fill we detect it
+///
+/// Each detection is recorded in the `polycopy_detection_latency_seconds`
+/// histogram, and a warning is logged when the median of the last
+/// [`LATENCY_SAMPLES`] crosses `warn_above`, and again when it recovers.
+pub struct DetectionLatency {
+    warn_above: Duration,
+    recent: VecDeque<Duration>,
+    warned: bool,
+}
+
+impl DetectionLatency {
+    pub fn new(warn_above: Duration) -> Self {
+        Self {
+            warn_above,
+            recent: VecDeque::with_capacity(LATENCY_SAMPLES),
+            warned: false,
+        }
+    }
+
+    /// Record that `trade` was detected at `detected_at`; returns the latency
+    pub fn record(&mut self, trade: &Trade, detected_at: DateTime<Utc>) -> Duration {
+        // A trader timestamp ahead of our clock counts as instant
+        let latency = (detected_at - trade.timestamp)
+            .to_std()
+            .unwrap_or(Duration::ZERO);
+        metrics::global().observe(
+            "polycopy_detection_latency_seconds",
+            "Time from a tracked trader's fill to our detecting it",
+            &[],
+            LATENCY_BUCKETS,
+            latency.as_secs_f64(),
+        );
+
+        if self.recent.len() == LATENCY_SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(latency);
+
+        let median = self.median();
+        if !self.warned && median > self.warn_above {
+            warn!(
+                "Median detection latency is {:.1}s over the last {} trades (limit {}s); \
+                 consider a shorter poll interval",
+                median.as_secs_f64(),
+                self.recent.len(),
+                self.warn_above.as_secs()
+            );
+            self.warned = true;
+        } else if self.warned && median <= self.warn_above {
+            info!(
+                "Median detection latency is back to {:.1}s",
+                median.as_secs_f64()
+            );
+            self.warned = false;
+        }
+        latency
+    }
+
+    /// Median latency of the recent detections
+    pub fn median(&self) -> Duration {
+        let mut sorted: Vec<Duration> = self.recent.iter().copied().collect();
+        sorted.sort();
+        match sorted.len() {
+            0 => Duration::ZERO,
+            n if n % 2 == 1 => sorted[n / 2],
+            n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_median_latency_and_warning() {
+        let detected_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let trade = |seconds_ago: i64| {
+            Trade::sample()
+                .with_id("t")
+                .with_fill(dec!(0.5), dec!(10))
+                .with_timestamp(detected_at - chrono::Duration::seconds(seconds_ago))
+        };
+        let mut latency = DetectionLatency::new(Duration::from_secs(10));
+
+        assert_eq!(
+            latency.record(&trade(4), detected_at),
+            Duration::from_secs(4)
+        );
+        latency.record(&trade(8), detected_at);
+        assert_eq!(latency.median(), Duration::from_secs(6));
+        assert!(!latency.warned);
+
+        latency.record(&trade(30), detected_at);
+        latency.record(&trade(40), detected_at);
+        latency.record(&trade(50), detected_at);
+        assert_eq!(latency.median(), Duration::from_secs(30));
+        assert!(latency.warned);
+
+        // Clock skew doesn't produce negative latencies
+        assert_eq!(latency.record(&trade(-5), detected_at), Duration::ZERO);
+    }
+}
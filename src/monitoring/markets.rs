@@ -0,0 +1,508 @@
+use crate::errors::{PolymarketError, Result};
+use crate::models::MarketLabel;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Market status can change (closing, pausing), so metadata is refetched after this
+const MARKET_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// One outcome of a market and the CLOB token that trades it
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutcomeToken {
+    pub token_id: String,
+    pub outcome: String,
+    /// Last price Gamma reports for the outcome
+    pub price: Option<Decimal>,
+}
+
+/// The event a market belongs to; one event groups related markets, such as
+/// several thresholds of the same outcome
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct MarketEvent {
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub slug: String,
+}
+
+/// A category Gamma files events under, e.g. "Politics"
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct EventTag {
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub slug: String,
+}
+
+/// An event with its tags and every market in it, from the Gamma API
+#[derive(Clone, Debug)]
+pub struct EventInfo {
+    pub title: String,
+    pub tags: Vec<EventTag>,
+    pub markets: Vec<Arc<MarketInfo>>,
+}
+
+impl EventInfo {
+    /// Whether the event is tagged `tag`, matching the label or slug
+    /// regardless of case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|t| t.label.eq_ignore_ascii_case(tag) || t.slug.eq_ignore_ascii_case(tag))
+    }
+
+    /// Whether `token_id` trades in one of the event's markets
+    pub fn contains_token(&self, token_id: &str) -> bool {
+        self.markets
+            .iter()
+            .any(|market| market.tokens.iter().any(|t| t.token_id == token_id))
+    }
+
+    pub fn tag_labels(&self) -> Vec<&str> {
+        self.tags.iter().map(|t| t.label.as_str()).collect()
+    }
+}
+
+/// Market metadata from the Gamma API
+#[derive(Clone, Debug)]
+pub struct MarketInfo {
+    pub condition_id: String,
+    pub question: String,
+    pub slug: String,
+    pub active: bool,
+    pub closed: bool,
+    pub accepting_orders: bool,
+    /// Not available to traders in some jurisdictions
+    pub restricted: bool,
+    /// When trading is scheduled to stop, if Gamma has it
+    pub end_date: Option<DateTime<Utc>>,
+    pub event: Option<MarketEvent>,
+    pub tokens: Vec<OutcomeToken>,
+    pub tick_size: Option<Decimal>,
+    /// Smallest order the CLOB accepts, in shares
+    pub min_order_size: Option<Decimal>,
+    /// Top of the book for the first outcome
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    pub volume_24h: Option<Decimal>,
+}
+
+impl MarketInfo {
+    /// Why orders can't be placed in this market right now, if they can't
+    pub fn untradable_reason(&self) -> Option<&'static str> {
+        if self.closed {
+            Some("market is closed")
+        } else if !self.active {
+            Some("market is inactive")
+        } else if !self.accepting_orders {
+            Some("market is paused and not accepting orders")
+        } else if self.restricted {
+            Some("market is restricted")
+        } else {
+            None
+        }
+    }
+
+    /// What each outcome token paid out, once the market is closed and
+    /// Gamma's prices have settled at 1 for the winner and 0 for the rest
+    pub fn payouts(&self) -> Option<Vec<(&OutcomeToken, Decimal)>> {
+        if !self.closed || self.tokens.is_empty() {
+            return None;
+        }
+        let payouts: Vec<(&OutcomeToken, Decimal)> = self
+            .tokens
+            .iter()
+            .map(|token| Some((token, token.price?)))
+            .collect::<Option<_>>()?;
+        let settled = payouts
+            .iter()
+            .all(|(_, price)| *price == Decimal::ONE || price.is_zero());
+        let winners = payouts
+            .iter()
+            .filter(|(_, price)| *price == Decimal::ONE)
+            .count();
+        (settled && winners == 1).then_some(payouts)
+    }
+
+    /// Whether trading stops within `buffer` of `now`; false without an end date
+    pub fn ends_within(&self, buffer: chrono::Duration, now: DateTime<Utc>) -> bool {
+        self.end_date.is_some_and(|end| end - now <= buffer)
+    }
+
+    /// The other outcome's token in a binary market
+    pub fn complement(&self, token_id: &str) -> Option<&OutcomeToken> {
+        if self.tokens.len() != 2 {
+            return None;
+        }
+        let position = self.tokens.iter().position(|t| t.token_id == token_id)?;
+        self.tokens.get(1 - position)
+    }
+
+    /// Question, slug and outcome for display alongside `token_id`
+    pub fn label(&self, token_id: &str) -> MarketLabel {
+        MarketLabel {
+            question: self.question.clone(),
+            slug: self.slug.clone(),
+            outcome: self.outcome(token_id).map(str::to_string),
+        }
+    }
+
+    /// The outcome `token_id` trades, e.g. "Yes"
+    pub fn outcome(&self, token_id: &str) -> Option<&str> {
+        self.tokens
+            .iter()
+            .find(|t| t.token_id == token_id)
+            .map(|t| t.outcome.as_str())
+    }
+}
+
+/// Gamma market as returned by `/markets`; list fields arrive JSON-encoded
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GammaMarket {
+    condition_id: String,
+    #[serde(default)]
+    question: String,
+    #[serde(default)]
+    slug: String,
+    #[serde(default)]
+    active: bool,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    accepting_orders: bool,
+    #[serde(default)]
+    restricted: bool,
+    #[serde(default)]
+    end_date: Option<String>,
+    #[serde(default)]
+    events: Vec<MarketEvent>,
+    #[serde(default)]
+    outcomes: Option<String>,
+    #[serde(default)]
+    outcome_prices: Option<String>,
+    #[serde(default)]
+    clob_token_ids: Option<String>,
+    #[serde(default)]
+    order_price_min_tick_size: Option<Decimal>,
+    #[serde(default)]
+    order_min_size: Option<Decimal>,
+    #[serde(default)]
+    best_bid: Option<Decimal>,
+    #[serde(default)]
+    best_ask: Option<Decimal>,
+    #[serde(default)]
+    volume_24hr: Option<Decimal>,
+}
+
+impl TryFrom<GammaMarket> for MarketInfo {
+    type Error = PolymarketError;
+
+    fn try_from(market: GammaMarket) -> Result<Self> {
+        let decode = |field: Option<String>| -> Result<Vec<String>> {
+            match field {
+                Some(encoded) => Ok(serde_json::from_str(&encoded)?),
+                None => Ok(Vec::new()),
+            }
+        };
+        let outcomes = decode(market.outcomes)?;
+        let token_ids = decode(market.clob_token_ids)?;
+        let prices = decode(market.outcome_prices)?;
+        let tokens = token_ids
+            .into_iter()
+            .zip(outcomes)
+            .enumerate()
+            .map(|(i, (token_id, outcome))| OutcomeToken {
+                token_id,
+                outcome,
+                price: prices.get(i).and_then(|price| price.parse().ok()),
+            })
+            .collect();
+
+        Ok(Self {
+            condition_id: market.condition_id,
+            question: market.question,
+            slug: market.slug,
+            active: market.active,
+            closed: market.closed,
+            accepting_orders: market.accepting_orders,
+            restricted: market.restricted,
+            end_date: market
+                .end_date
+                .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+                .map(|date| date.to_utc()),
+            event: market.events.into_iter().next(),
+            tokens,
+            tick_size: market.order_price_min_tick_size,
+            min_order_size: market.order_min_size,
+            best_bid: market.best_bid,
+            best_ask: market.best_ask,
+            volume_24h: market.volume_24hr,
+        })
+    }
+}
+
+/// Gamma event as returned by `/events/{id}`
+#[derive(Deserialize)]
+struct GammaEvent {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    slug: String,
+    #[serde(default)]
+    tags: Vec<EventTag>,
+    #[serde(default)]
+    markets: Vec<GammaMarket>,
+}
+
+impl TryFrom<GammaEvent> for EventInfo {
+    type Error = PolymarketError;
+
+    fn try_from(event: GammaEvent) -> Result<Self> {
+        let summary = MarketEvent {
+            id: event.id.clone(),
+            title: event.title.clone(),
+            slug: event.slug.clone(),
+        };
+        // Markets nested in an event don't repeat the event they belong to
+        let markets = event
+            .markets
+            .into_iter()
+            .map(|market| {
+                let mut market = MarketInfo::try_from(market)?;
+                market.event.get_or_insert_with(|| summary.clone());
+                Ok(Arc::new(market))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            title: event.title,
+            tags: event.tags,
+            markets,
+        })
+    }
+}
+
+/// Cached market and event metadata, markets looked up by outcome token id
+pub struct MarketDirectory {
+    http_client: Client,
+    gamma_api_url: String,
+    markets: RwLock<HashMap<String, (Arc<MarketInfo>, Instant)>>,
+    events: RwLock<HashMap<String, (Arc<EventInfo>, Instant)>>,
+}
+
+impl MarketDirectory {
+    pub fn new(gamma_api_url: String) -> Self {
+        Self {
+            http_client: Client::new(),
+            gamma_api_url,
+            markets: RwLock::new(HashMap::new()),
+            events: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The event with its tags and markets
+    pub async fn event(&self, event_id: &str) -> Result<Arc<EventInfo>> {
+        if let Some((event, fetched_at)) = self
+            .events
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(event_id)
+        {
+            if fetched_at.elapsed() < MARKET_CACHE_TTL {
+                return Ok(event.clone());
+            }
+        }
+
+        debug!("Fetching event {}", event_id);
+        let response = self
+            .http_client
+            .get(format!("{}/events/{}", self.gamma_api_url, event_id))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch event {}: {}",
+                event_id,
+                response.status()
+            )));
+        }
+        let event: GammaEvent = response.json().await?;
+        let event = Arc::new(EventInfo::try_from(event)?);
+        for market in &event.markets {
+            self.insert(market.clone());
+        }
+        self.events
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(event_id.to_string(), (event.clone(), Instant::now()));
+        Ok(event)
+    }
+
+    /// The event `token_id`'s market belongs to, or None for a market
+    /// outside any event
+    pub async fn event_of(&self, token_id: &str) -> Result<Option<Arc<EventInfo>>> {
+        match self.market(token_id).await?.event {
+            Some(ref event) => Ok(Some(self.event(&event.id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Metadata for the market that `token_id` trades in
+    pub async fn market(&self, token_id: &str) -> Result<Arc<MarketInfo>> {
+        if let Some((market, fetched_at)) = self
+            .markets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(token_id)
+        {
+            if fetched_at.elapsed() < MARKET_CACHE_TTL {
+                return Ok(market.clone());
+            }
+        }
+
+        debug!("Fetching market metadata for token {}", token_id);
+        self.fetch("clob_token_ids", token_id).await
+    }
+
+    /// Look a market up by condition id (`0x...`) or slug
+    pub async fn find(&self, condition_or_slug: &str) -> Result<Arc<MarketInfo>> {
+        if condition_or_slug.starts_with("0x") {
+            self.fetch("condition_ids", condition_or_slug).await
+        } else {
+            self.fetch("slug", condition_or_slug).await
+        }
+    }
+
+    async fn fetch(&self, filter: &str, value: &str) -> Result<Arc<MarketInfo>> {
+        let response = self
+            .http_client
+            .get(format!("{}/markets", self.gamma_api_url))
+            .query(&[(filter, value)])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch market metadata: {}",
+                response.status()
+            )));
+        }
+        let markets: Vec<GammaMarket> = response.json().await?;
+        let market = markets
+            .into_iter()
+            .next()
+            .ok_or_else(|| PolymarketError::ApiError(format!("No market found for {}", value)))?;
+        let market = Arc::new(MarketInfo::try_from(market)?);
+        self.insert(market.clone());
+        Ok(market)
+    }
+
+    /// Display label for `token_id`, or None if the lookup fails
+    pub async fn label(&self, token_id: &str) -> Option<MarketLabel> {
+        match self.market(token_id).await {
+            Ok(market) => Some(market.label(token_id)),
+            Err(e) => {
+                debug!("No market metadata for {}: {}", token_id, e);
+                None
+            }
+        }
+    }
+
+    /// Cache `market` under each of its tokens
+    fn insert(&self, market: Arc<MarketInfo>) {
+        let now = Instant::now();
+        let mut markets = self.markets.write().unwrap_or_else(|e| e.into_inner());
+        for token in &market.tokens {
+            markets.insert(token.token_id.clone(), (market.clone(), now));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_gamma_market_outcome_pairs() {
+        let body = r#"[{"conditionId":"0xabc","question":"Will it rain?","slug":"rain",
+            "active":true,"closed":false,"acceptingOrders":true,"endDate":"2024-11-05T12:00:00Z",
+            "events":[{"id":"903","title":"NYC weather","slug":"nyc-weather"}],
+            "outcomePrices":"[\"0.62\", \"0.38\"]","orderPriceMinTickSize":0.01,
+            "orderMinSize":5,"bestBid":0.61,"bestAsk":0.63,"volume24hr":15234.5,
+            "outcomes":"[\"Yes\", \"No\"]","clobTokenIds":"[\"111\", \"222\"]"}]"#;
+        let markets: Vec<GammaMarket> = serde_json::from_str(body).unwrap();
+        let market = MarketInfo::try_from(markets.into_iter().next().unwrap()).unwrap();
+
+        assert_eq!(market.complement("111").unwrap().token_id, "222");
+        assert_eq!(market.complement("222").unwrap().outcome, "Yes");
+        assert_eq!(market.outcome("222"), Some("No"));
+        assert_eq!(market.tokens[1].price, Some(dec!(0.38)));
+        assert_eq!(market.label("111").to_string(), "Will it rain? (Yes)");
+        assert!(market.complement("333").is_none());
+        assert_eq!(market.event.as_ref().unwrap().id, "903");
+        assert_eq!(
+            market.end_date.unwrap().to_rfc3339(),
+            "2024-11-05T12:00:00+00:00"
+        );
+        let six_hours = chrono::Duration::hours(6);
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        assert!(!market.ends_within(six_hours, at("2024-11-05T05:59:00Z")));
+        assert!(market.ends_within(six_hours, at("2024-11-05T06:00:00Z")));
+        assert_eq!(market.untradable_reason(), None);
+        assert_eq!(market.tick_size, Some(dec!(0.01)));
+        assert_eq!(market.best_ask, Some(dec!(0.63)));
+        assert_eq!(market.volume_24h, Some(dec!(15234.5)));
+
+        assert!(market.payouts().is_none());
+
+        let mut resolved = market.clone();
+        resolved.closed = true;
+        // Closed but not settled yet
+        assert!(resolved.payouts().is_none());
+        resolved.tokens[0].price = Some(dec!(0));
+        resolved.tokens[1].price = Some(dec!(1));
+        let payouts = resolved.payouts().unwrap();
+        assert_eq!(payouts[1].0.outcome, "No");
+        assert_eq!(payouts[1].1, dec!(1));
+
+        let paused = MarketInfo {
+            accepting_orders: false,
+            ..market
+        };
+        assert_eq!(
+            paused.untradable_reason(),
+            Some("market is paused and not accepting orders")
+        );
+    }
+
+    #[test]
+    fn test_gamma_event_with_tags_and_markets() {
+        let body = r#"{"id":"903","title":"Presidential Election Winner 2024",
+            "slug":"presidential-election-winner-2024",
+            "tags":[{"id":"2","label":"Politics","slug":"politics"},
+                    {"id":"24","label":"US Politics","slug":"us-politics"}],
+            "markets":[{"conditionId":"0xa","question":"Will Trump win?","slug":"trump",
+                "active":true,"outcomes":"[\"Yes\", \"No\"]","clobTokenIds":"[\"111\", \"222\"]"},
+                {"conditionId":"0xb","question":"Will Harris win?","slug":"harris",
+                "active":true,"outcomes":"[\"Yes\", \"No\"]","clobTokenIds":"[\"333\", \"444\"]"}]}"#;
+        let event: GammaEvent = serde_json::from_str(body).unwrap();
+        let event = EventInfo::try_from(event).unwrap();
+
+        assert!(event.has_tag("us politics"));
+        assert!(event.has_tag("politics"));
+        assert!(!event.has_tag("sports"));
+        assert_eq!(event.tag_labels(), vec!["Politics", "US Politics"]);
+        assert!(event.contains_token("444"));
+        assert!(!event.contains_token("555"));
+        assert_eq!(event.markets.len(), 2);
+        assert_eq!(event.markets[1].event.as_ref().unwrap().id, "903");
+    }
+}
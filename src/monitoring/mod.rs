@@ -1,4 +1,30 @@
+pub mod confirmation;
+pub mod data_api;
 pub mod detector;
+pub mod labels;
+pub mod latency;
+pub mod markets;
+pub mod onboarding;
+pub mod portfolios;
+pub mod prices;
+pub mod pruning;
+pub mod reload;
+pub mod resolutions;
+pub mod signals;
+pub mod stats;
 pub mod tracker;
+pub mod trade_feed;
+pub mod volatility;
+pub mod wash;
 
+pub use confirmation::{CopyDelay, Reconfirmation};
+pub use latency::DetectionLatency;
+pub use markets::MarketDirectory;
+pub use onboarding::Onboarding;
+pub use portfolios::TraderPortfolios;
+pub use prices::{PriceCache, PriceFeed};
+pub use reload::TrackedSetReloader;
+pub use resolutions::{ResolutionTracker, Resolutions};
+pub use signals::SignalListener;
 pub use tracker::PollingMonitor;
+pub use wash::{WashTradeDetector, WashVerdict};
@@ -0,0 +1,231 @@
+use crate::config::TradersConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::models::{Trade, TraderState};
+use crate::monitoring::tracker::mark_price;
+use chrono::Duration;
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// What to do with the positions a trader already holds when we start
+/// tracking them
+#[derive(Clone, Debug, PartialEq)]
+pub enum OnboardingPolicy {
+    /// Only copy what they trade from now on
+    Ignore,
+    /// Copy every open position at its current price
+    CopyAll,
+    /// Copy the positions opened within this long, at their current prices
+    CopyRecent(Duration),
+}
+
+impl OnboardingPolicy {
+    pub fn parse(policy: &str, days: u64) -> Result<Self> {
+        match policy {
+            "ignore" => Ok(OnboardingPolicy::Ignore),
+            "copy_all" => Ok(OnboardingPolicy::CopyAll),
+            "copy_recent" => Ok(OnboardingPolicy::CopyRecent(Duration::days(days as i64))),
+            other => Err(PolymarketError::ConfigError(format!(
+                "onboarding must be 'ignore', 'copy_all' or 'copy_recent', not '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Applies each trader's onboarding policy to the first positions seen of
+/// them, once
+///
+/// Onboarded traders are kept in a file, so a restart baselines them silently
+/// rather than copying their book again. Traders not in it yet, including
+/// everyone tracked before the file existed, count as new.
+pub struct Onboarding {
+    default: OnboardingPolicy,
+    per_trader: HashMap<Address, OnboardingPolicy>,
+    path: PathBuf,
+    onboarded: BTreeSet<Address>,
+}
+
+impl Onboarding {
+    pub fn from_config(config: &TradersConfig) -> Result<Self> {
+        let default = OnboardingPolicy::parse(&config.onboarding, config.onboarding_days)?;
+        let mut per_trader = HashMap::new();
+        for profile in &config.profiles {
+            if profile.onboarding.is_none() && profile.onboarding_days.is_none() {
+                continue;
+            }
+            let policy = OnboardingPolicy::parse(
+                profile.onboarding.as_deref().unwrap_or(&config.onboarding),
+                profile.onboarding_days.unwrap_or(config.onboarding_days),
+            )?;
+            per_trader.insert(profile.get_address()?, policy);
+        }
+
+        let path = PathBuf::from(&config.onboarded_file);
+        let onboarded = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            default,
+            per_trader,
+            path,
+            onboarded,
+        })
+    }
+
+    pub fn policy(&self, trader: &Address) -> &OnboardingPolicy {
+        self.per_trader.get(trader).unwrap_or(&self.default)
+    }
+
+    /// The trades that catch up with `state`, the first positions seen of a
+    /// trader: none if they were onboarded before, otherwise whatever their
+    /// policy copies, priced at the current marks
+    pub fn catch_up(&mut self, state: &TraderState) -> Vec<Trade> {
+        let trader = state.address;
+        if self.onboarded.contains(&trader) {
+            return Vec::new();
+        }
+        let policy = self.policy(&trader);
+        let trades: Vec<Trade> = state
+            .positions
+            .iter()
+            .filter(|p| p.size > Decimal::ZERO)
+            .filter(|p| match *policy {
+                OnboardingPolicy::Ignore => false,
+                OnboardingPolicy::CopyAll => true,
+                OnboardingPolicy::CopyRecent(window) => p.timestamp >= state.last_updated - window,
+            })
+            .map(|position| {
+                let price = mark_price(position);
+                Trade {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    market_id: position.market_id.clone(),
+                    trader,
+                    side: position.side.clone(),
+                    price,
+                    size: position.size,
+                    size_usdc: position.size * price,
+                    // Copied now, at the current price
+                    timestamp: state.last_updated,
+                    trader_win_rate: None,
+                    exit_fraction: None,
+                    detection_midpoint: None,
+                    labels: Vec::new(),
+                }
+            })
+            .collect();
+        if !trades.is_empty() {
+            info!(
+                "Onboarding {:?}: copying {} of their {} open positions",
+                trader,
+                trades.len(),
+                state.positions.len()
+            );
+        }
+
+        self.onboarded.insert(trader);
+        if let Err(e) = self.save() {
+            warn!("Failed to save onboarded traders: {}", e);
+        }
+        trades
+    }
+
+    /// Treat `trader` as new again, e.g. once no longer tracked
+    pub fn forget(&mut self, trader: &Address) {
+        if self.onboarded.remove(trader) {
+            if let Err(e) = self.save() {
+                warn!("Failed to save onboarded traders: {}", e);
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string_pretty(&self.onboarded)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::{OrderSide, Position};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_catch_up_follows_each_traders_policy_once() {
+        let config = Config::load_from_file("config.example.toml").unwrap();
+        let (everything, recent, ignored) = (
+            Address::from_low_u64_be(1),
+            Address::from_low_u64_be(2),
+            Address::from_low_u64_be(3),
+        );
+        let mut traders = config.traders.clone();
+        traders.onboarded_file = std::env::temp_dir()
+            .join(format!("onboarded-{}.json", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        traders.onboarding = "copy_recent".to_string();
+        traders.onboarding_days = 7;
+        let profile = |trader: Address, policy: &str| -> crate::config::TraderProfile {
+            toml::from_str(&format!(
+                "address = \"{:?}\"\nonboarding = \"{}\"",
+                trader, policy
+            ))
+            .unwrap()
+        };
+        traders.profiles = vec![profile(everything, "copy_all"), profile(ignored, "ignore")];
+        let mut onboarding = Onboarding::from_config(&traders).unwrap();
+
+        let now = Utc::now();
+        let state = |trader: Address| TraderState {
+            address: trader,
+            positions: vec![
+                Position {
+                    market_id: "old".to_string(),
+                    entry_price: dec!(0.4),
+                    size: dec!(100),
+                    side: OrderSide::Buy,
+                    timestamp: now - Duration::days(30),
+                    // Now worth 0.6 a share
+                    pnl: dec!(20),
+                    trader: None,
+                },
+                Position {
+                    market_id: "new".to_string(),
+                    entry_price: dec!(0.5),
+                    size: dec!(50),
+                    side: OrderSide::Buy,
+                    timestamp: now - Duration::days(1),
+                    pnl: dec!(0),
+                    trader: None,
+                },
+            ],
+            last_updated: now,
+        };
+
+        let copied = onboarding.catch_up(&state(everything));
+        assert_eq!(copied.len(), 2);
+        assert_eq!(copied[0].price, dec!(0.6));
+        assert_eq!(copied[0].size_usdc, dec!(60));
+        assert_eq!(copied[0].timestamp, now);
+        let copied = onboarding.catch_up(&state(recent));
+        assert_eq!(copied.len(), 1);
+        assert_eq!(copied[0].market_id, "new");
+        assert!(onboarding.catch_up(&state(ignored)).is_empty());
+
+        // Already onboarded, here and after a restart
+        assert!(onboarding.catch_up(&state(everything)).is_empty());
+        let mut restarted = Onboarding::from_config(&traders).unwrap();
+        assert!(restarted.catch_up(&state(recent)).is_empty());
+        restarted.forget(&recent);
+        assert_eq!(restarted.catch_up(&state(recent)).len(), 1);
+        fs::remove_file(&traders.onboarded_file).unwrap();
+    }
+}
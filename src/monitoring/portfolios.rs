@@ -0,0 +1,74 @@
+use crate::errors::Result;
+use crate::models::Trade;
+use crate::monitoring::data_api::DataApiClient;
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tracked traders' total portfolio values, fetched from the data API and
+/// reused for `refresh`
+pub struct TraderPortfolios {
+    client: DataApiClient,
+    refresh: Duration,
+    values: Mutex<HashMap<Address, (Instant, Decimal)>>,
+}
+
+impl TraderPortfolios {
+    pub fn new(client: DataApiClient, refresh: Duration) -> Self {
+        Self {
+            client,
+            refresh,
+            values: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The value of `trader`'s open positions, in USDC
+    pub async fn value(&self, trader: Address) -> Result<Decimal> {
+        if let Some((fetched_at, value)) = self.lock().get(&trader) {
+            if fetched_at.elapsed() < self.refresh {
+                return Ok(*value);
+            }
+        }
+        let value = self.client.portfolio_value(trader).await?;
+        self.lock().insert(trader, (Instant::now(), value));
+        Ok(value)
+    }
+
+    /// The share of their portfolio the trader put into `trade`
+    pub async fn conviction(&self, trade: &Trade) -> Result<Option<Decimal>> {
+        let value = self.value(trade.trader).await?;
+        Ok(conviction(trade.size_usdc, value))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Address, (Instant, Decimal)>> {
+        self.values.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// `size` as a share of the portfolio it was bet from
+///
+/// The value is of positions after the trade, so the trade is part of it;
+/// an empty portfolio (a trader who just went all in) counts as 100%.
+fn conviction(size: Decimal, portfolio_value: Decimal) -> Option<Decimal> {
+    if size <= Decimal::ZERO {
+        return None;
+    }
+    Some((size / portfolio_value.max(size)).min(Decimal::ONE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_conviction_share_of_portfolio() {
+        assert_eq!(conviction(dec!(200), dec!(1000)), Some(dec!(0.2)));
+        assert_eq!(conviction(dec!(5), dec!(1000)), Some(dec!(0.005)));
+        // A portfolio not yet showing the new position
+        assert_eq!(conviction(dec!(300), dec!(100)), Some(dec!(1)));
+        assert_eq!(conviction(dec!(0), dec!(100)), None);
+    }
+}
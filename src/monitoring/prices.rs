@@ -0,0 +1,387 @@
+use crate::config::PricesConfig;
+use crate::endpoints::Endpoints;
+use crate::errors::{PolymarketError, Result};
+use crate::monitoring::volatility::PriceHistory;
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, info, warn};
+
+/// The market channel drops connections that stay silent for too long
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceQuote {
+    pub price: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Last known price for every market we hold or watch
+///
+/// Shared by everything that needs a current price so each component doesn't
+/// make its own HTTP calls. A [`PriceFeed`] keeps it up to date.
+pub struct PriceCache {
+    quotes: RwLock<HashMap<String, PriceQuote>>,
+    tracked: RwLock<HashSet<String>>,
+    /// Quotes older than this are treated as unknown
+    max_age: chrono::Duration,
+    tracked_changed: Notify,
+    /// Window of recent prices kept per market, when volatility is needed
+    history_window: Option<Duration>,
+    histories: RwLock<HashMap<String, PriceHistory>>,
+}
+
+impl PriceCache {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            quotes: RwLock::new(HashMap::new()),
+            tracked: RwLock::new(HashSet::new()),
+            max_age: chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::MAX),
+            tracked_changed: Notify::new(),
+            history_window: None,
+            histories: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Also keep each market's prices over the last `window`, for
+    /// [`volatility`](Self::volatility)
+    pub fn with_history(mut self, window: Duration) -> Self {
+        self.history_window = Some(window);
+        self
+    }
+
+    /// Start following `market`; returns false if it was already tracked
+    pub fn track(&self, market_id: &str) -> bool {
+        let added = self
+            .tracked
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(market_id.to_string());
+        if added {
+            self.tracked_changed.notify_one();
+        }
+        added
+    }
+
+    pub fn tracked(&self) -> Vec<String> {
+        let mut markets: Vec<String> = self
+            .tracked
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect();
+        markets.sort();
+        markets
+    }
+
+    pub fn update(&self, market_id: &str, price: Decimal) {
+        let quote = PriceQuote {
+            price,
+            updated_at: Utc::now(),
+        };
+        if let Some(window) = self.history_window {
+            self.histories
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(market_id.to_string())
+                .or_insert_with(|| PriceHistory::new(window))
+                .record(quote.updated_at, price);
+        }
+        self.quotes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(market_id.to_string(), quote);
+    }
+
+    /// Latest quote regardless of age
+    pub fn quote(&self, market_id: &str) -> Option<PriceQuote> {
+        self.quotes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(market_id)
+            .copied()
+    }
+
+    /// Current price, or None if we have nothing recent enough to trust
+    pub fn price(&self, market_id: &str) -> Option<Decimal> {
+        self.quote(market_id)
+            .filter(|quote| Utc::now() - quote.updated_at <= self.max_age)
+            .map(|quote| quote.price)
+    }
+
+    /// Standard deviation of the market's recent prices, if history is kept
+    /// and has at least `min_samples` prices
+    pub fn volatility(&self, market_id: &str, min_samples: usize) -> Option<Decimal> {
+        self.histories
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(market_id)
+            .and_then(|history| history.volatility(min_samples))
+    }
+
+    /// Resolves once a new market is tracked
+    async fn wait_for_new_market(&self) {
+        self.tracked_changed.notified().await;
+    }
+}
+
+/// Keeps a [`PriceCache`] current from the market websocket, or by polling midpoints
+pub struct PriceFeed {
+    cache: Arc<PriceCache>,
+    config: PricesConfig,
+    endpoints: Arc<Endpoints>,
+    http_client: Client,
+}
+
+impl PriceFeed {
+    pub fn new(cache: Arc<PriceCache>, config: PricesConfig, api_url: String) -> Self {
+        Self {
+            cache,
+            config,
+            endpoints: Arc::new(Endpoints::single("clob", api_url)),
+            http_client: Client::new(),
+        }
+    }
+
+    /// Poll the active one of these CLOB endpoints, failing over between them
+    pub fn with_endpoints(mut self, endpoints: Arc<Endpoints>) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Run until the process exits
+    pub async fn run(self) {
+        let poll_interval = Duration::from_secs(self.config.poll_interval_seconds);
+        loop {
+            if self.cache.tracked().is_empty() {
+                self.cache.wait_for_new_market().await;
+                continue;
+            }
+
+            if self.config.source == "websocket" {
+                match self.stream().await {
+                    // A new market was tracked; reconnect with the full set
+                    Ok(()) => continue,
+                    Err(e) => warn!("Price stream failed, polling until it reconnects: {}", e),
+                }
+            }
+
+            self.poll_once().await;
+            tokio::select! {
+                _ = tokio::time::sleep(poll_interval) => {}
+                _ = self.cache.wait_for_new_market() => {}
+            }
+        }
+    }
+
+    /// Subscribe to every tracked market and apply updates until the set changes
+    async fn stream(&self) -> Result<()> {
+        let markets = self.cache.tracked();
+        let (mut socket, _) = connect_async(self.config.websocket_url.as_str())
+            .await
+            .map_err(websocket_error)?;
+        let subscribe = json!({ "assets_ids": markets, "type": "market" });
+        socket
+            .send(Message::Text(subscribe.to_string()))
+            .await
+            .map_err(websocket_error)?;
+        info!("Streaming prices for {} markets", markets.len());
+
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        loop {
+            tokio::select! {
+                message = socket.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        for (market_id, price) in parse_market_message(&text) {
+                            self.cache.update(&market_id, price);
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        return Err(PolymarketError::ApiError(
+                            "Price stream closed by server".to_string(),
+                        ));
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(websocket_error(e)),
+                },
+                _ = ping.tick() => {
+                    socket
+                        .send(Message::Text("PING".to_string()))
+                        .await
+                        .map_err(websocket_error)?;
+                }
+                _ = self.cache.wait_for_new_market() => {
+                    let _ = socket.close(None).await;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Fetch the midpoint of every tracked market once
+    async fn poll_once(&self) {
+        for market_id in self.cache.tracked() {
+            match self.fetch_midpoint(&market_id).await {
+                Ok(price) => self.cache.update(&market_id, price),
+                Err(e) => debug!("Failed to fetch midpoint for {}: {}", market_id, e),
+            }
+        }
+    }
+
+    async fn fetch_midpoint(&self, market_id: &str) -> Result<Decimal> {
+        let url = format!("{}/midpoint", self.endpoints.url());
+        let response = self
+            .http_client
+            .get(&url)
+            .query(&[("token_id", market_id)])
+            .timeout(self.endpoints.request_timeout())
+            .send()
+            .await;
+        self.endpoints.record(
+            &url,
+            response
+                .as_ref()
+                .is_ok_and(|r| !r.status().is_server_error()),
+        );
+        let response = response?;
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch midpoint: {}",
+                response.status()
+            )));
+        }
+        let body: Value = response.json().await?;
+        decimal(&body["mid"])
+            .ok_or_else(|| PolymarketError::ApiError("Midpoint response has no price".to_string()))
+    }
+}
+
+/// Prices carried by a market channel message (a single event or an array of them)
+///
+/// Book snapshots and price changes yield the midpoint when both sides are
+/// known; trades yield the traded price.
+fn parse_market_message(text: &str) -> Vec<(String, Decimal)> {
+    let Ok(value) = serde_json::from_str::<Value>(text) else {
+        // Heartbeat replies ("PONG") and anything else that isn't JSON
+        return Vec::new();
+    };
+    let events = match value {
+        Value::Array(events) => events,
+        event => vec![event],
+    };
+
+    let mut prices = Vec::new();
+    for event in &events {
+        match event["event_type"].as_str() {
+            Some("book") => {
+                let best_bid = levels(&event["bids"]).into_iter().max();
+                let best_ask = levels(&event["asks"]).into_iter().min();
+                if let (Some(asset), Some(price)) =
+                    (event["asset_id"].as_str(), midpoint(best_bid, best_ask))
+                {
+                    prices.push((asset.to_string(), price));
+                }
+            }
+            Some("price_change") => {
+                let changes = event["price_changes"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default();
+                for change in &changes {
+                    let asset = change["asset_id"].as_str().or(event["asset_id"].as_str());
+                    let price =
+                        midpoint(decimal(&change["best_bid"]), decimal(&change["best_ask"]))
+                            .or_else(|| decimal(&change["price"]));
+                    if let (Some(asset), Some(price)) = (asset, price) {
+                        prices.push((asset.to_string(), price));
+                    }
+                }
+            }
+            Some("last_trade_price") => {
+                if let (Some(asset), Some(price)) =
+                    (event["asset_id"].as_str(), decimal(&event["price"]))
+                {
+                    prices.push((asset.to_string(), price));
+                }
+            }
+            _ => {}
+        }
+    }
+    prices
+}
+
+fn levels(side: &Value) -> Vec<Decimal> {
+    side.as_array()
+        .map(|levels| levels.iter().filter_map(|l| decimal(&l["price"])).collect())
+        .unwrap_or_default()
+}
+
+fn midpoint(best_bid: Option<Decimal>, best_ask: Option<Decimal>) -> Option<Decimal> {
+    match (best_bid, best_ask) {
+        (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+        (bid, ask) => bid.or(ask),
+    }
+}
+
+/// The API sends prices as strings, but accept plain numbers too
+fn decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::String(s) => Decimal::from_str(s).ok(),
+        Value::Number(n) => Decimal::from_str(&n.to_string()).ok(),
+        _ => None,
+    }
+}
+
+fn websocket_error(e: tokio_tungstenite::tungstenite::Error) -> PolymarketError {
+    PolymarketError::ApiError(format!("Price stream error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_market_messages_into_cache() {
+        let book = r#"[{"event_type":"book","asset_id":"a1",
+            "bids":[{"price":"0.48","size":"10"},{"price":"0.50","size":"5"}],
+            "asks":[{"price":"0.56","size":"3"},{"price":"0.54","size":"8"}]}]"#;
+        assert_eq!(
+            parse_market_message(book),
+            vec![("a1".to_string(), dec!(0.52))]
+        );
+
+        let change = r#"{"event_type":"price_change","price_changes":[
+            {"asset_id":"a1","price":"0.51","best_bid":"0.51","best_ask":"0.53"},
+            {"asset_id":"a2","price":"0.30"}]}"#;
+        let trade = r#"{"event_type":"last_trade_price","asset_id":"a3","price":"0.9"}"#;
+        let cache = PriceCache::new(Duration::from_secs(60));
+        for (market, price) in parse_market_message(change)
+            .into_iter()
+            .chain(parse_market_message(trade))
+        {
+            cache.update(&market, price);
+        }
+        assert_eq!(cache.price("a1"), Some(dec!(0.52)));
+        assert_eq!(cache.price("a2"), Some(dec!(0.30)));
+        assert_eq!(cache.price("a3"), Some(dec!(0.9)));
+        assert!(parse_market_message("PONG").is_empty());
+
+        let stale = PriceCache::new(Duration::ZERO);
+        stale.update("a1", dec!(0.5));
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(stale.price("a1"), None);
+        assert!(stale.quote("a1").is_some());
+        assert!(stale.track("a1"));
+        assert!(!stale.track("a1"));
+    }
+}
@@ -0,0 +1,285 @@
+use crate::config::PruningConfig;
+use crate::errors::Result;
+use crate::models::Trade;
+use crate::monitoring::stats::{trader_performance, TraderPerformance};
+use crate::monitoring::{PriceCache, Resolutions};
+use crate::notifications::SlackNotifier;
+use crate::storage::TradeStore;
+use chrono::{DateTime, Duration, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+/// A tracked trader taken off copying for poor results
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Suspension {
+    pub reason: String,
+    pub suspended_at: DateTime<Utc>,
+    /// Copying resumes on probation after this
+    pub until: DateTime<Utc>,
+}
+
+/// Suspensions and reinstatements, persisted so they survive restarts
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct PruningState {
+    suspended: HashMap<Address, Suspension>,
+    /// Only trades after a reinstatement count against the trader
+    reinstated: HashMap<Address, DateTime<Utc>>,
+}
+
+/// A change in who gets copied
+#[derive(Clone, Debug, PartialEq)]
+pub enum PruneEvent {
+    Suspended(Address, Suspension),
+    Reinstated(Address),
+}
+
+/// Suspends tracked traders whose recent results fall below the configured
+/// thresholds, and lets them back in after a probation period
+///
+/// A reinstated trader starts with a clean slate: only trades made after the
+/// reinstatement are judged, so they aren't suspended again for the same losses.
+pub struct TraderPruner {
+    config: PruningConfig,
+    path: PathBuf,
+    state: RwLock<PruningState>,
+    resolutions: Option<Arc<Resolutions>>,
+}
+
+impl TraderPruner {
+    pub fn load(config: PruningConfig) -> Result<Self> {
+        let path = PathBuf::from(&config.state_file);
+        let state = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PruningState::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            config,
+            path,
+            state: RwLock::new(state),
+            resolutions: None,
+        })
+    }
+
+    /// Judge positions held to resolution by their payout
+    pub fn with_resolutions(mut self, resolutions: Arc<Resolutions>) -> Self {
+        self.resolutions = Some(resolutions);
+        self
+    }
+
+    pub fn suspension(&self, trader: Address) -> Option<Suspension> {
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .suspended
+            .get(&trader)
+            .cloned()
+    }
+
+    /// Judge every trader on `trades` and apply suspensions and reinstatements
+    pub fn evaluate(
+        &self,
+        trades: &[Trade],
+        prices: &PriceCache,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PruneEvent>> {
+        let since = now - Duration::days(self.config.lookback_days as i64);
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        let mut events = Vec::new();
+
+        let ended: Vec<Address> = state
+            .suspended
+            .iter()
+            .filter(|(_, suspension)| suspension.until <= now)
+            .map(|(trader, _)| *trader)
+            .collect();
+        for trader in ended {
+            state.suspended.remove(&trader);
+            state.reinstated.insert(trader, now);
+            events.push(PruneEvent::Reinstated(trader));
+        }
+        state
+            .reinstated
+            .retain(|_, reinstated_at| *reinstated_at > since);
+
+        // Judge each trader on the window, or on what they did since reinstatement
+        let judged: Vec<Trade> = trades
+            .iter()
+            .filter(|t| {
+                state
+                    .reinstated
+                    .get(&t.trader)
+                    .is_none_or(|reinstated_at| t.timestamp >= *reinstated_at)
+            })
+            .cloned()
+            .collect();
+        let performance = trader_performance(
+            &judged,
+            since,
+            |market| prices.price(market),
+            |market| self.resolutions.as_ref()?.payout(market),
+        );
+        for (trader, stats) in performance {
+            if state.suspended.contains_key(&trader) {
+                continue;
+            }
+            if let Some(reason) = self.violation(&stats) {
+                let suspension = Suspension {
+                    reason,
+                    suspended_at: now,
+                    until: now + Duration::days(self.config.probation_days as i64),
+                };
+                state.suspended.insert(trader, suspension.clone());
+                events.push(PruneEvent::Suspended(trader, suspension));
+            }
+        }
+
+        if !events.is_empty() {
+            fs::write(&self.path, serde_json::to_string_pretty(&*state)?)?;
+        }
+        Ok(events)
+    }
+
+    /// Re-evaluate from the trade store every `check_interval_minutes`
+    pub async fn run_periodic(
+        self: Arc<Self>,
+        store: Arc<dyn TradeStore>,
+        prices: Arc<PriceCache>,
+        notifier: Option<Arc<SlackNotifier>>,
+    ) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            self.config.check_interval_minutes * 60,
+        ));
+        loop {
+            interval.tick().await;
+            let events = match store.entries().await {
+                Ok(entries) => {
+                    let trades: Vec<Trade> = entries.into_iter().map(|e| e.trade).collect();
+                    self.evaluate(&trades, &prices, Utc::now())
+                }
+                Err(e) => Err(e),
+            };
+            let events = match events {
+                Ok(events) => events,
+                Err(e) => {
+                    warn!("Trader pruning check failed: {}", e);
+                    continue;
+                }
+            };
+            for event in events {
+                let message = match event {
+                    PruneEvent::Suspended(trader, suspension) => format!(
+                        "Suspended trader {:?} until {}: {}",
+                        trader,
+                        suspension.until.format("%Y-%m-%d %H:%M UTC"),
+                        suspension.reason
+                    ),
+                    PruneEvent::Reinstated(trader) => {
+                        format!(
+                            "Trader {:?} is back on probation after a suspension",
+                            trader
+                        )
+                    }
+                };
+                info!("{}", message);
+                if let Some(ref notifier) = notifier {
+                    notifier.alert(message).await;
+                }
+            }
+        }
+    }
+
+    fn violation(&self, stats: &TraderPerformance) -> Option<String> {
+        if let (Some(min_roi), Some(roi)) = (self.config.min_roi, stats.roi()) {
+            if stats.trades >= self.config.min_trades && roi < min_roi {
+                return Some(format!(
+                    "ROI {}% over {} trades is below {}%",
+                    (roi * Decimal::ONE_HUNDRED).round_dp(1),
+                    stats.trades,
+                    min_roi * Decimal::ONE_HUNDRED
+                ));
+            }
+        }
+        if let (Some(min_win_rate), Some(win_rate)) = (self.config.min_win_rate, stats.win_rate()) {
+            if stats.closed >= self.config.min_closed_trades && win_rate < min_win_rate {
+                return Some(format!(
+                    "win rate {}% on {} closed trades is below {}%",
+                    (win_rate * Decimal::ONE_HUNDRED).round_dp(1),
+                    stats.closed,
+                    min_win_rate * Decimal::ONE_HUNDRED
+                ));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderSide;
+    use rust_decimal_macros::dec;
+
+    fn round_trip(trader: u64, market: &str, exit: Decimal, at: DateTime<Utc>) -> Vec<Trade> {
+        [(OrderSide::Buy, dec!(0.50)), (OrderSide::Sell, exit)]
+            .into_iter()
+            .map(|(side, price)| {
+                Trade::sample()
+                    .with_market(market)
+                    .with_trader(Address::from_low_u64_be(trader))
+                    .with_side(side)
+                    .with_fill(price, dec!(100))
+                    .with_timestamp(at)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_suspend_then_reinstate_after_probation() {
+        let config = PruningConfig {
+            enabled: true,
+            min_roi: Some(dec!(-0.2)),
+            min_trades: 4,
+            state_file: std::env::temp_dir()
+                .join(format!("pruning-{}.json", uuid::Uuid::new_v4()))
+                .to_string_lossy()
+                .to_string(),
+            ..PruningConfig::default()
+        };
+        let pruner = TraderPruner::load(config.clone()).unwrap();
+        let prices = PriceCache::new(std::time::Duration::from_secs(60));
+        let now = Utc::now();
+        let loser = Address::from_low_u64_be(1);
+
+        // Two round trips losing 40% each, and a winner
+        let mut trades = round_trip(1, "m1", dec!(0.30), now - Duration::days(3));
+        trades.extend(round_trip(1, "m2", dec!(0.30), now - Duration::days(2)));
+        trades.extend(round_trip(2, "m1", dec!(0.70), now - Duration::days(2)));
+
+        let events = pruner.evaluate(&trades, &prices, now).unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], PruneEvent::Suspended(trader, _) if trader == loser));
+        assert!(pruner.suspension(loser).is_some());
+
+        // Suspensions survive a restart
+        let restarted = TraderPruner::load(config).unwrap();
+        assert!(restarted.suspension(loser).is_some());
+
+        // After probation the old losses no longer count
+        let later = now + Duration::days(8);
+        let events = restarted.evaluate(&trades, &prices, later).unwrap();
+        assert_eq!(events, vec![PruneEvent::Reinstated(loser)]);
+        assert!(restarted.suspension(loser).is_none());
+        assert!(restarted
+            .evaluate(&trades, &prices, later)
+            .unwrap()
+            .is_empty());
+        let _ = fs::remove_file(&restarted.path);
+    }
+}
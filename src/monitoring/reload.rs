@@ -0,0 +1,130 @@
+use crate::config::{Config, TradersConfig};
+use crate::errors::Result;
+use ethers::types::Address;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Re-reads the config file and publishes its `tracked_accounts` whenever
+/// they change, so traders are added and removed without a restart
+///
+/// Only the tracked set is applied; other edits take a restart. A file that
+/// doesn't load or validate is reported and the current set kept.
+pub struct TrackedSetReloader {
+    path: PathBuf,
+    interval: Duration,
+    sender: watch::Sender<Vec<Address>>,
+    /// Tracked whatever the file says
+    pinned: Vec<Address>,
+    /// Contents of the file as last read
+    content: Option<String>,
+}
+
+impl TrackedSetReloader {
+    /// Publish to `sender`, which holds the addresses tracked from the start
+    pub fn new(
+        path: impl Into<PathBuf>,
+        config: &TradersConfig,
+        sender: watch::Sender<Vec<Address>>,
+    ) -> Self {
+        let path = path.into();
+        Self {
+            content: fs::read_to_string(&path).ok(),
+            path,
+            interval: Duration::from_secs(config.reload_interval_seconds),
+            sender,
+            pinned: Vec::new(),
+        }
+    }
+
+    /// Keep tracking `addresses` too, such as the traders of strategies,
+    /// which take a restart to change
+    pub fn with_pinned(mut self, addresses: Vec<Address>) -> Self {
+        self.pinned = addresses;
+        self
+    }
+
+    pub async fn run_periodic(mut self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check() {
+                warn!("Keeping the tracked traders; config reload failed: {}", e);
+            }
+        }
+    }
+
+    /// Publish the file's tracked set if the file changed; returns whether
+    /// the set did
+    fn check(&mut self) -> Result<bool> {
+        let content = fs::read_to_string(&self.path)?;
+        if self.content.as_ref() == Some(&content) {
+            return Ok(false);
+        }
+        let mut config = Config::load_from_file(&self.path)?;
+        config.expand_env_vars()?;
+        let mut tracked = config.traders.get_addresses()?;
+        for address in &self.pinned {
+            if !tracked.contains(address) {
+                tracked.push(*address);
+            }
+        }
+        self.content = Some(content);
+
+        let changed = self.sender.send_if_modified(|current| {
+            if *current == tracked {
+                return false;
+            }
+            *current = tracked;
+            true
+        });
+        if changed {
+            info!(
+                "Config reloaded: tracking {} traders",
+                self.sender.borrow().len()
+            );
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracked_set_follows_the_config_file() {
+        let example = fs::read_to_string("config.example.toml").unwrap();
+        let path = std::env::temp_dir().join(format!("config-{}.toml", uuid::Uuid::new_v4()));
+        fs::write(&path, &example).unwrap();
+        let config = Config::load_from_file(&path).unwrap();
+        let (sender, receiver) = watch::channel(config.traders.get_addresses().unwrap());
+        let mut reloader = TrackedSetReloader::new(&path, &config.traders, sender);
+
+        assert!(!reloader.check().unwrap());
+
+        // A comment alone leaves the set as it is
+        fs::write(&path, format!("# edited\n{}", example)).unwrap();
+        assert!(!reloader.check().unwrap());
+
+        let added = "0x1111111111111111111111111111111111111111";
+        let edited = example.replace(
+            "\"0xabcdefabcdefabcdefabcdefabcdefabcdefabcd\"",
+            &format!("\"{}\"", added),
+        );
+        fs::write(&path, &edited).unwrap();
+        assert!(reloader.check().unwrap());
+        let tracked = receiver.borrow().clone();
+        assert_eq!(tracked.len(), 2);
+        assert!(tracked.contains(&added.parse().unwrap()));
+
+        // A broken file keeps the current set
+        fs::write(&path, edited.replace(added, "not an address")).unwrap();
+        assert!(reloader.check().is_err());
+        assert_eq!(*receiver.borrow(), tracked);
+        fs::remove_file(path).unwrap();
+    }
+}
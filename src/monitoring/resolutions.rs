@@ -0,0 +1,243 @@
+use crate::config::ResolutionsConfig;
+use crate::errors::Result;
+use crate::monitoring::markets::MarketInfo;
+use crate::monitoring::MarketDirectory;
+use crate::storage::{OrderStore, TradeStore};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info, warn};
+
+/// How one outcome token's market ended
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub condition_id: String,
+    pub question: String,
+    /// The token's own outcome, e.g. "Yes"
+    pub outcome: String,
+    /// What a share paid out: 1 if the outcome won, 0 if it lost
+    pub payout: Decimal,
+    /// When the resolution was first seen
+    pub resolved_at: DateTime<Utc>,
+}
+
+/// Outcomes of resolved markets by token id, kept in a file so results
+/// don't fall back to price marks after a restart
+pub struct Resolutions {
+    path: PathBuf,
+    resolved: RwLock<BTreeMap<String, Resolution>>,
+}
+
+impl Resolutions {
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let resolved = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            resolved: RwLock::new(resolved),
+        })
+    }
+
+    /// What a share of `token_id` paid out, if its market has resolved
+    pub fn payout(&self, token_id: &str) -> Option<Decimal> {
+        self.resolution(token_id).map(|r| r.payout)
+    }
+
+    pub fn resolution(&self, token_id: &str) -> Option<Resolution> {
+        self.resolved
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(token_id)
+            .cloned()
+    }
+
+    /// Record the outcome of every token in `market` if it has resolved;
+    /// returns the resolution of `token_id` if it is new
+    fn record(
+        &self,
+        market: &MarketInfo,
+        token_id: &str,
+        now: DateTime<Utc>,
+    ) -> Option<Resolution> {
+        let payouts = market.payouts()?;
+        let mut resolved = self.resolved.write().unwrap_or_else(|e| e.into_inner());
+        if resolved.contains_key(token_id) {
+            return None;
+        }
+        for (token, payout) in payouts {
+            resolved.insert(
+                token.token_id.clone(),
+                Resolution {
+                    condition_id: market.condition_id.clone(),
+                    question: market.question.clone(),
+                    outcome: token.outcome.clone(),
+                    payout,
+                    resolved_at: now,
+                },
+            );
+        }
+        resolved.get(token_id).cloned()
+    }
+
+    fn save(&self) -> Result<()> {
+        let resolved = self.resolved.read().unwrap_or_else(|e| e.into_inner());
+        fs::write(&self.path, serde_json::to_string_pretty(&*resolved)?)?;
+        Ok(())
+    }
+}
+
+/// Looks up the markets we and the tracked traders have traded and records
+/// their outcomes once they resolve
+///
+/// Checked are the tokens of every filled order of ours, and of the trades
+/// detected in the last `lookback_days`, until each has resolved.
+pub struct ResolutionTracker {
+    resolutions: Arc<Resolutions>,
+    markets: Arc<MarketDirectory>,
+    trades: Arc<dyn TradeStore>,
+    orders: Arc<dyn OrderStore>,
+    interval: std::time::Duration,
+    lookback: Duration,
+}
+
+impl ResolutionTracker {
+    pub fn new(
+        config: &ResolutionsConfig,
+        resolutions: Arc<Resolutions>,
+        markets: Arc<MarketDirectory>,
+        trades: Arc<dyn TradeStore>,
+        orders: Arc<dyn OrderStore>,
+    ) -> Self {
+        Self {
+            resolutions,
+            markets,
+            trades,
+            orders,
+            interval: std::time::Duration::from_secs(config.check_interval_minutes * 60),
+            lookback: Duration::days(config.lookback_days as i64),
+        }
+    }
+
+    pub async fn run_periodic(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check().await {
+                warn!("Market resolution check failed: {}", e);
+            }
+        }
+    }
+
+    async fn check(&self) -> Result<()> {
+        let now = Utc::now();
+        let since = now - self.lookback;
+        let mut tokens = BTreeSet::new();
+        for entry in self.trades.entries().await? {
+            if entry.trade.timestamp >= since {
+                tokens.insert(entry.trade.market_id);
+            }
+        }
+        let mut held = BTreeSet::new();
+        for order in self.orders.orders().await? {
+            if order.filled_size > Decimal::ZERO {
+                held.insert(order.market_id.clone());
+                tokens.insert(order.market_id);
+            }
+        }
+
+        let mut recorded = 0;
+        for token_id in tokens {
+            if self.resolutions.payout(&token_id).is_some() {
+                continue;
+            }
+            let market = match self.markets.market(&token_id).await {
+                Ok(market) => market,
+                Err(e) => {
+                    debug!("No market metadata for {}: {}", token_id, e);
+                    continue;
+                }
+            };
+            let Some(resolution) = self.resolutions.record(&market, &token_id, now) else {
+                continue;
+            };
+            recorded += 1;
+            if held.contains(&token_id) {
+                info!(
+                    "'{}' resolved: {} paid {} a share",
+                    resolution.question, resolution.outcome, resolution.payout
+                );
+            }
+        }
+        if recorded > 0 {
+            self.resolutions.save()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitoring::markets::OutcomeToken;
+
+    fn market(closed: bool, prices: [&str; 2]) -> MarketInfo {
+        let token = |id: &str, outcome: &str, price: &str| OutcomeToken {
+            token_id: id.to_string(),
+            outcome: outcome.to_string(),
+            price: Some(price.parse().unwrap()),
+        };
+        MarketInfo {
+            condition_id: "0xabc".to_string(),
+            question: "Will it rain?".to_string(),
+            slug: "rain".to_string(),
+            active: true,
+            closed,
+            accepting_orders: !closed,
+            restricted: false,
+            end_date: None,
+            event: None,
+            tokens: vec![
+                token("111", "Yes", prices[0]),
+                token("222", "No", prices[1]),
+            ],
+            tick_size: None,
+            min_order_size: None,
+            best_bid: None,
+            best_ask: None,
+            volume_24h: None,
+        }
+    }
+
+    #[test]
+    fn test_resolutions_are_recorded_once_and_persisted() {
+        let path = std::env::temp_dir().join(format!("resolutions-{}.json", uuid::Uuid::new_v4()));
+        let resolutions = Resolutions::load(&path).unwrap();
+        let now = Utc::now();
+
+        assert!(resolutions
+            .record(&market(false, ["0.97", "0.03"]), "111", now)
+            .is_none());
+        assert_eq!(resolutions.payout("111"), None);
+
+        let resolved = market(true, ["1", "0"]);
+        let resolution = resolutions.record(&resolved, "222", now).unwrap();
+        assert_eq!(resolution.outcome, "No");
+        assert_eq!(resolution.payout, Decimal::ZERO);
+        assert_eq!(resolutions.payout("111"), Some(Decimal::ONE));
+        // Seen already
+        assert!(resolutions.record(&resolved, "111", now).is_none());
+
+        resolutions.save().unwrap();
+        let reloaded = Resolutions::load(&path).unwrap();
+        assert_eq!(reloaded.resolution("222"), Some(resolution));
+        fs::remove_file(path).unwrap();
+    }
+}
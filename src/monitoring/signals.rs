@@ -0,0 +1,262 @@
+use crate::config::SignalsConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::metrics;
+use crate::models::Trade;
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tracing::{debug, info, warn};
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Label of signals sent without labels of their own
+const SIGNAL_LABEL: &str = "signal";
+
+/// Accepts trade signals from outside the bot over HTTP
+///
+/// `POST /signals` takes one trade in the same JSON schema as detected
+/// trades. Accepted signals are handed to the monitor, which passes them on
+/// like any detected trade, so they go through the same filters, sizing,
+/// execution and risk checks.
+pub struct SignalListener {
+    listen_addr: String,
+    auth_token: Option<String>,
+    sender: mpsc::Sender<Trade>,
+}
+
+impl SignalListener {
+    pub fn new(config: &SignalsConfig, sender: mpsc::Sender<Trade>) -> Self {
+        Self {
+            listen_addr: config.listen_addr.clone(),
+            auth_token: config.auth_token.clone(),
+            sender,
+        }
+    }
+
+    pub async fn serve(self) -> Result<()> {
+        let listener = TcpListener::bind(&self.listen_addr).await?;
+        info!(
+            "Accepting trade signals on http://{}/signals",
+            self.listen_addr
+        );
+        let signals = Arc::new(self);
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let signals = signals.clone();
+            tokio::spawn(async move {
+                if let Err(e) = signals.answer(stream).await {
+                    debug!("Signal request from {} failed: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    /// Read one request and answer it; every response closes the connection
+    async fn answer(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let (status, body) = match read_request(&mut stream).await? {
+            Some(request) => self.respond(&request),
+            None => (413, json!({ "error": "request too large" }).to_string()),
+        };
+        let reason = match status {
+            202 => "Accepted",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            413 => "Payload Too Large",
+            _ => "Service Unavailable",
+        };
+        let response = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            reason,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    /// Status code and body for a request
+    fn respond(&self, request: &Request) -> (u16, String) {
+        if request.method != "POST" || request.path != "/signals" {
+            return (404, json!({ "error": "not found" }).to_string());
+        }
+        if let Some(ref token) = self.auth_token {
+            if request.bearer_token.as_deref() != Some(token.as_str()) {
+                return (401, json!({ "error": "unauthorized" }).to_string());
+            }
+        }
+
+        let trade = match parse_signal(&request.body) {
+            Ok(trade) => trade,
+            Err(e) => {
+                record("rejected");
+                return (400, json!({ "error": e.to_string() }).to_string());
+            }
+        };
+        let id = trade.id.clone();
+        match self.sender.try_send(trade) {
+            Ok(()) => {
+                record("accepted");
+                info!("Accepted trade signal {}", id);
+                (202, json!({ "id": id }).to_string())
+            }
+            Err(TrySendError::Full(_)) => {
+                record("dropped");
+                warn!("Signal queue is full; dropping signal {}", id);
+                (503, json!({ "error": "signal queue is full" }).to_string())
+            }
+            Err(TrySendError::Closed(_)) => {
+                record("dropped");
+                (503, json!({ "error": "not accepting signals" }).to_string())
+            }
+        }
+    }
+}
+
+fn record(result: &str) {
+    metrics::global().increment(
+        "polycopy_signals_total",
+        "Trade signals received over the signal API, by result",
+        &[("result", result)],
+    );
+}
+
+/// A trade signal, checked for what a detected trade always satisfies
+fn parse_signal(body: &str) -> Result<Trade> {
+    let mut trade: Trade = serde_json::from_str(body)?;
+    let invalid = |reason: &str| Err(PolymarketError::ParseError(reason.to_string()));
+    if trade.id.is_empty() || trade.market_id.is_empty() {
+        return invalid("id and market_id must not be empty");
+    }
+    if trade.price <= Decimal::ZERO || trade.price >= Decimal::ONE {
+        return invalid("price must be between 0 and 1");
+    }
+    if trade.size <= Decimal::ZERO || trade.size_usdc <= Decimal::ZERO {
+        return invalid("size and size_usdc must be positive");
+    }
+    if trade
+        .exit_fraction
+        .is_some_and(|f| f <= Decimal::ZERO || f > Decimal::ONE)
+    {
+        return invalid("exit_fraction must be above 0 and at most 1");
+    }
+    if trade.labels.is_empty() {
+        trade.labels.push(SIGNAL_LABEL.to_string());
+    }
+    Ok(trade)
+}
+
+struct Request {
+    method: String,
+    path: String,
+    bearer_token: Option<String>,
+    body: String,
+}
+
+/// The request on `stream`, or None if its body is too large
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Request>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if buffer.len() > MAX_BODY_BYTES {
+            return Ok(None);
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let header = |name: &str| {
+        head.lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().to_string())
+    };
+    let content_length = header("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Ok(None);
+    }
+    while buffer.len() < header_end + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or("/");
+    Ok(Some(Request {
+        method,
+        path: target.split('?').next().unwrap_or_default().to_string(),
+        bearer_token: header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer ").map(str::to_string)),
+        body: String::from_utf8_lossy(&buffer[header_end..]).to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signals_are_checked_and_queued() {
+        let config = SignalsConfig {
+            auth_token: Some("secret".to_string()),
+            queue_size: 1,
+            ..SignalsConfig::default()
+        };
+        let (sender, mut receiver) = mpsc::channel(config.queue_size);
+        let listener = SignalListener::new(&config, sender);
+        let signal = json!({
+            "id": "model-1",
+            "market_id": "111",
+            "trader": "0x0000000000000000000000000000000000000001",
+            "side": "BUY",
+            "price": "0.42",
+            "size": "100",
+            "size_usdc": "42",
+            "timestamp": "2024-03-01T12:00:00Z"
+        });
+        let request = |token: Option<&str>, body: String| Request {
+            method: "POST".to_string(),
+            path: "/signals".to_string(),
+            bearer_token: token.map(str::to_string),
+            body,
+        };
+
+        assert_eq!(listener.respond(&request(None, signal.to_string())).0, 401);
+        let (status, body) = listener.respond(&request(Some("secret"), signal.to_string()));
+        assert_eq!((status, body.as_str()), (202, r#"{"id":"model-1"}"#));
+        let trade = receiver.try_recv().unwrap();
+        assert_eq!(trade.market_id, "111");
+        assert_eq!(trade.labels, ["signal"]);
+        assert_eq!(trade.size_usdc, Decimal::new(42, 0));
+
+        let mut bad_price = signal.clone();
+        bad_price["price"] = json!("1.5");
+        let (status, body) = listener.respond(&request(Some("secret"), bad_price.to_string()));
+        assert_eq!(status, 400);
+        assert!(body.contains("price must be between 0 and 1"));
+        assert!(receiver.try_recv().is_err());
+
+        // Nothing is queued beyond queue_size
+        listener.respond(&request(Some("secret"), signal.to_string()));
+        let (status, _) = listener.respond(&request(Some("secret"), signal.to_string()));
+        assert_eq!(status, 503);
+    }
+}
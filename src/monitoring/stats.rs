@@ -0,0 +1,180 @@
+use crate::models::{OrderSide, Trade};
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+
+/// How a trader's observed trades did over a window
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TraderPerformance {
+    pub trades: usize,
+    /// Total cost of the trader's buys
+    pub invested: Decimal,
+    /// Realized P&L on sells and resolutions, plus open shares marked at
+    /// current prices
+    pub pnl: Decimal,
+    /// Sells matched against earlier buys in the window, and positions held
+    /// through resolution
+    pub closed: usize,
+    pub wins: usize,
+    pub markets: usize,
+}
+
+impl TraderPerformance {
+    pub fn roi(&self) -> Option<Decimal> {
+        (self.invested > Decimal::ZERO).then(|| self.pnl / self.invested)
+    }
+
+    pub fn win_rate(&self) -> Option<Decimal> {
+        (self.closed > 0).then(|| Decimal::from(self.wins) / Decimal::from(self.closed))
+    }
+}
+
+/// Per-trader performance of the trades we've seen since `since`
+///
+/// Only trades inside the window count: buys open shares at their price,
+/// sells close them at average cost, and sells of shares bought before the
+/// window are ignored. Shares held when their market resolved are closed at
+/// the `payout` of 1 or 0, and count as a win or loss like a sell. Shares
+/// still open are marked with `price`, or left at cost when there is no quote.
+pub fn trader_performance<F, P>(
+    trades: &[Trade],
+    since: DateTime<Utc>,
+    price: F,
+    payout: P,
+) -> HashMap<Address, TraderPerformance>
+where
+    F: Fn(&str) -> Option<Decimal>,
+    P: Fn(&str) -> Option<Decimal>,
+{
+    let mut window: Vec<&Trade> = trades.iter().filter(|t| t.timestamp >= since).collect();
+    window.sort_by_key(|t| t.timestamp);
+
+    let mut seen = HashSet::new();
+    let mut books: HashMap<(Address, &str), (Decimal, Decimal)> = HashMap::new();
+    let mut performance: HashMap<Address, TraderPerformance> = HashMap::new();
+    for trade in window {
+        // The same trade may be logged more than once (detected, then failed)
+        if !seen.insert(trade.id.as_str()) {
+            continue;
+        }
+        let stats = performance.entry(trade.trader).or_default();
+        stats.trades += 1;
+        let (shares, cost) = books
+            .entry((trade.trader, trade.market_id.as_str()))
+            .or_default();
+        match trade.side {
+            OrderSide::Buy => {
+                *shares += trade.size;
+                *cost += trade.size * trade.price;
+                stats.invested += trade.size * trade.price;
+            }
+            OrderSide::Sell => {
+                let matched = trade.size.min(*shares);
+                if matched > Decimal::ZERO {
+                    let average = *cost / *shares;
+                    let realized = matched * (trade.price - average);
+                    stats.pnl += realized;
+                    stats.closed += 1;
+                    if realized > Decimal::ZERO {
+                        stats.wins += 1;
+                    }
+                    *cost -= matched * average;
+                    *shares -= matched;
+                }
+            }
+        }
+    }
+
+    for ((trader, market_id), (shares, cost)) in books {
+        let stats = performance.entry(trader).or_default();
+        stats.markets += 1;
+        if shares <= Decimal::ZERO {
+            continue;
+        }
+        if let Some(payout) = payout(market_id) {
+            let realized = shares * payout - cost;
+            stats.pnl += realized;
+            stats.closed += 1;
+            if realized > Decimal::ZERO {
+                stats.wins += 1;
+            }
+        } else if let Some(price) = price(market_id) {
+            stats.pnl += shares * price - cost;
+        }
+    }
+    performance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn trade(trader: u64, market: &str, side: OrderSide, price: Decimal, days_ago: i64) -> Trade {
+        Trade::sample()
+            .with_market(market)
+            .with_trader(Address::from_low_u64_be(trader))
+            .with_side(side)
+            .with_fill(price, dec!(100))
+            .with_timestamp(Utc::now() - Duration::days(days_ago))
+    }
+
+    #[test]
+    fn test_performance_marks_open_shares_and_counts_wins() {
+        let trades = vec![
+            trade(1, "m1", OrderSide::Buy, dec!(0.40), 5),
+            trade(1, "m1", OrderSide::Sell, dec!(0.60), 3),
+            trade(1, "m2", OrderSide::Buy, dec!(0.50), 2),
+            // Outside the window
+            trade(1, "m3", OrderSide::Buy, dec!(0.90), 40),
+            trade(2, "m1", OrderSide::Sell, dec!(0.30), 1),
+        ];
+        let since = Utc::now() - Duration::days(30);
+        let stats = trader_performance(
+            &trades,
+            since,
+            |market| (market == "m2").then_some(dec!(0.30)),
+            |_| None,
+        );
+
+        let first = &stats[&Address::from_low_u64_be(1)];
+        assert_eq!(first.trades, 3);
+        assert_eq!(first.invested, dec!(90));
+        // +20 realized in m1, -20 marked in m2
+        assert_eq!(first.pnl, dec!(0));
+        assert_eq!(first.win_rate(), Some(dec!(1)));
+        assert_eq!(first.markets, 2);
+        // A sell with nothing bought in the window doesn't count
+        assert_eq!(stats[&Address::from_low_u64_be(2)].closed, 0);
+    }
+
+    #[test]
+    fn test_positions_held_to_resolution_count_as_closed() {
+        let trades = vec![
+            trade(1, "won", OrderSide::Buy, dec!(0.40), 5),
+            trade(1, "lost", OrderSide::Buy, dec!(0.70), 4),
+            trade(1, "open", OrderSide::Buy, dec!(0.50), 3),
+        ];
+        let since = Utc::now() - Duration::days(30);
+        // The losing market last traded at 0.98 before it resolved against us
+        let stats = trader_performance(
+            &trades,
+            since,
+            |market| (market != "won").then_some(dec!(0.98)),
+            |market| match market {
+                "won" => Some(dec!(1)),
+                "lost" => Some(dec!(0)),
+                _ => None,
+            },
+        );
+
+        let first = &stats[&Address::from_low_u64_be(1)];
+        assert_eq!(first.closed, 2);
+        assert_eq!(first.wins, 1);
+        assert_eq!(first.win_rate(), Some(dec!(0.5)));
+        // +60 won, -70 lost, +48 marked
+        assert_eq!(first.pnl, dec!(38));
+    }
+}
@@ -1,144 +1,614 @@
 use crate::errors::{PolymarketError, Result};
-use crate::models::{Trade, TraderState};
+use crate::metrics;
+use crate::models::{OrderSide, Position, Trade, TraderState};
+use crate::monitoring::data_api::{DataApiClient, DataApiPosition};
+use crate::monitoring::detector::TradeAggregator;
+use crate::monitoring::markets::MarketDirectory;
+use crate::monitoring::onboarding::Onboarding;
+use crate::monitoring::trade_feed::{self, DetectionMode, TradeFeed};
+use crate::notifications::SlackNotifier;
 use ethers::types::Address;
-use reqwest::Client;
-use std::collections::HashMap;
-use std::time::Duration;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{RequestBuilder, StatusCode};
+use rust_decimal::Decimal;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
 use tracing::{debug, info, warn};
 
 pub struct PollingMonitor {
-    api_client: Client,
-    api_url: String,
+    data_api: DataApiClient,
     tracked_traders: Vec<Address>,
     poll_interval: Duration,
     last_state: HashMap<Address, TraderState>,
+    /// Validators of each trader's last positions response
+    responses: HashMap<Address, CachedResponse>,
+    aggregator: Option<TradeAggregator>,
+    notifier: Option<Arc<SlackNotifier>>,
+    /// Traders to track from now on, when the set can change while running
+    tracked_set: Option<watch::Receiver<Vec<Address>>>,
+    /// What to copy of the positions traders hold when first seen
+    onboarding: Option<Onboarding>,
+    /// Trades from outside the bot, handed on as they arrive
+    signals: Option<mpsc::Receiver<Trade>>,
+    detection_mode: DetectionMode,
+    /// Traders' trade lists, in the `trades` and `hybrid` detection modes
+    trade_feed: Option<TradeFeed>,
+    /// Outcome pairs, to tell a flip between a market's outcomes
+    markets: Option<Arc<MarketDirectory>>,
+}
+
+/// Polls in a row in which every trader failed before an alert is sent
+const ALERT_AFTER_FAILED_POLLS: u32 = 5;
+
+/// Longest the poll interval is stretched to while the API keeps failing
+const MAX_POLL_BACKOFF: Duration = Duration::from_secs(60);
+
+/// What identifies a positions response, to tell whether the next one changed
+///
+/// The server's `ETag` and `Last-Modified` are sent back so it can answer
+/// 304 Not Modified; when it doesn't support them, the body's hash catches
+/// identical payloads before they are parsed and diffed.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct CachedResponse {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body_hash: u64,
+}
+
+impl CachedResponse {
+    /// Make `request` conditional on the response having changed
+    fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = match self.etag {
+            Some(ref etag) => request.header(IF_NONE_MATCH, etag),
+            None => request,
+        };
+        match self.last_modified {
+            Some(ref last_modified) => request.header(IF_MODIFIED_SINCE, last_modified),
+            None => request,
+        }
+    }
+
+    fn hash(body: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        hasher.finish()
+    }
 }
 
 impl PollingMonitor {
-    pub fn new(api_url: String, tracked_traders: Vec<Address>, poll_interval: Duration) -> Self {
+    pub fn new(
+        data_api: DataApiClient,
+        tracked_traders: Vec<Address>,
+        poll_interval: Duration,
+    ) -> Self {
         Self {
-            api_client: Client::new(),
-            api_url,
+            data_api,
             tracked_traders,
             poll_interval,
             last_state: HashMap::new(),
+            responses: HashMap::new(),
+            aggregator: None,
+            notifier: None,
+            tracked_set: None,
+            onboarding: None,
+            signals: None,
+            detection_mode: DetectionMode::Positions,
+            trade_feed: None,
+            markets: None,
         }
     }
 
+    /// Merge each trader's same-market, same-side clips within `window` into one trade
+    pub fn with_aggregation(mut self, window: Duration) -> Self {
+        self.aggregator = Some(TradeAggregator::new(window));
+        self
+    }
+
+    /// Alert here when polling keeps failing, and when it recovers
+    pub fn with_notifier(mut self, notifier: Arc<SlackNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Track the traders last sent on `tracked_set` instead of the initial
+    /// ones, from the next poll after each change
+    pub fn with_tracked_set(mut self, tracked_set: watch::Receiver<Vec<Address>>) -> Self {
+        self.tracked_set = Some(tracked_set);
+        self
+    }
+
+    /// Copy some of the positions traders already hold when first seen, as
+    /// their onboarding policies say
+    pub fn with_onboarding(mut self, onboarding: Onboarding) -> Self {
+        self.onboarding = Some(onboarding);
+        self
+    }
+
+    /// Hand on trades received on `signals` like detected ones, as soon as
+    /// they arrive
+    pub fn with_signals(mut self, signals: mpsc::Receiver<Trade>) -> Self {
+        self.signals = Some(signals);
+        self
+    }
+
+    /// Look up markets here, to copy a trader moving from one outcome to the
+    /// other as a close before an open
+    pub fn with_markets(mut self, markets: Arc<MarketDirectory>) -> Self {
+        self.markets = Some(markets);
+        self
+    }
+
+    /// Detect trades from the traders' trade lists on `feed` instead of, or
+    /// on top of, their positions, as `mode` says
+    pub fn with_trade_feed(mut self, mode: DetectionMode, feed: TradeFeed) -> Self {
+        self.detection_mode = mode;
+        self.trade_feed = Some(feed);
+        self
+    }
+
     /// Main monitoring loop - polls trader positions at regular intervals
+    ///
+    /// While every trader's poll fails, as in an API outage or when rate
+    /// limited, the interval doubles after each poll up to
+    /// [`MAX_POLL_BACKOFF`].
     pub async fn monitor_loop<F>(&mut self, mut on_trade_detected: F) -> Result<()>
     where
         F: FnMut(&Trade) -> Result<()>,
     {
-        info!("Starting polling monitor for {} traders", self.tracked_traders.len());
+        info!(
+            "Starting polling monitor for {} traders",
+            self.tracked_traders.len()
+        );
 
+        let mut failed_polls = 0;
         loop {
+            let mut detected = Vec::new();
+            let update = match self.tracked_set {
+                Some(ref mut set) if set.has_changed().unwrap_or(false) => {
+                    Some(set.borrow_and_update().clone())
+                }
+                _ => None,
+            };
+            if let Some(tracked) = update {
+                detected.extend(self.reconcile(&tracked));
+            }
+            let mut errors = 0;
             for trader_addr in &self.tracked_traders.clone() {
                 match self.check_trader_activity(trader_addr).await {
                     Ok(trades) => {
                         for trade in trades {
                             debug!("Detected trade from {:?}: {:?}", trader_addr, trade.id);
-                            if let Err(e) = on_trade_detected(&trade) {
-                                warn!("Error handling trade: {}", e);
+                            match self.aggregator {
+                                Some(ref mut aggregator) => {
+                                    detected.extend(aggregator.push(trade, Instant::now()))
+                                }
+                                None => detected.push(trade),
                             }
                         }
                     }
                     Err(e) => {
+                        errors += 1;
                         warn!("Error checking trader {:?}: {}", trader_addr, e);
                     }
                 }
             }
+            if errors > 0 && errors == self.tracked_traders.len() {
+                failed_polls += 1;
+                if failed_polls == ALERT_AFTER_FAILED_POLLS {
+                    self.alert(format!(
+                        "Position polling has failed {} times in a row; no trades are being detected",
+                        failed_polls
+                    ))
+                    .await;
+                }
+            } else {
+                if failed_polls >= ALERT_AFTER_FAILED_POLLS {
+                    self.alert("Position polling has recovered".to_string())
+                        .await;
+                }
+                failed_polls = 0;
+            }
+            metrics::global().gauge(
+                "polycopy_monitor_failed_polls",
+                "Polls in a row in which every tracked trader failed",
+                &[],
+                failed_polls as f64,
+            );
+            if let Some(ref mut aggregator) = self.aggregator {
+                detected.extend(aggregator.flush_due(Instant::now()));
+            }
+
+            for trade in detected {
+                if let Err(e) = on_trade_detected(&trade) {
+                    warn!("Error handling trade: {}", e);
+                }
+            }
 
-            tokio::time::sleep(self.poll_interval).await;
+            let next_poll =
+                tokio::time::Instant::now() + poll_backoff(self.poll_interval, failed_polls);
+            while let Some(trade) = self.next_signal(next_poll).await {
+                debug!("Received trade signal {}", trade.id);
+                if let Err(e) = on_trade_detected(&trade) {
+                    warn!("Error handling trade signal: {}", e);
+                }
+            }
+        }
+    }
+
+    /// The next trade signal, or None once it is time for the next poll
+    async fn next_signal(&mut self, next_poll: tokio::time::Instant) -> Option<Trade> {
+        let Some(ref mut signals) = self.signals else {
+            tokio::time::sleep_until(next_poll).await;
+            return None;
+        };
+        tokio::select! {
+            _ = tokio::time::sleep_until(next_poll) => None,
+            signal = signals.recv() => {
+                if signal.is_none() {
+                    // The listener is gone; just poll from now on
+                    self.signals = None;
+                    tokio::time::sleep_until(next_poll).await;
+                }
+                signal
+            }
+        }
+    }
+
+    /// Switch to tracking `tracked`, returning the trades still held back
+    /// for traders no longer in it
+    ///
+    /// New traders start without a baseline, so their first poll records the
+    /// positions they already hold, copying only what their onboarding
+    /// policy says.
+    /// Removed traders are polled no more, but what was detected for them is
+    /// handed on rather than dropped.
+    fn reconcile(&mut self, tracked: &[Address]) -> Vec<Trade> {
+        let wanted: HashSet<&Address> = tracked.iter().collect();
+        let removed: Vec<Address> = self
+            .tracked_traders
+            .iter()
+            .filter(|trader| !wanted.contains(trader))
+            .copied()
+            .collect();
+        let added: Vec<Address> = tracked
+            .iter()
+            .filter(|trader| !self.tracked_traders.contains(trader))
+            .copied()
+            .collect();
+        if removed.is_empty() && added.is_empty() {
+            return Vec::new();
+        }
+
+        let mut drained = Vec::new();
+        for trader in &removed {
+            if let Some(ref mut aggregator) = self.aggregator {
+                drained.extend(aggregator.drain_trader(trader));
+            }
+            self.last_state.remove(trader);
+            self.responses.remove(trader);
+            if let Some(ref mut onboarding) = self.onboarding {
+                onboarding.forget(trader);
+            }
+            if let Some(ref mut feed) = self.trade_feed {
+                feed.forget(trader);
+            }
+        }
+        for trader in &added {
+            // Start from a fresh baseline even if the trader was tracked before
+            self.last_state.remove(trader);
+            self.responses.remove(trader);
+            if let Some(ref mut feed) = self.trade_feed {
+                feed.forget(trader);
+            }
+        }
+        self.tracked_traders
+            .retain(|trader| wanted.contains(trader));
+        self.tracked_traders.extend(added.iter().copied());
+        info!(
+            "Tracked traders updated: {} added, {} removed, {} tracked",
+            added.len(),
+            removed.len(),
+            self.tracked_traders.len()
+        );
+        drained
+    }
+
+    async fn alert(&self, text: String) {
+        warn!("{}", text);
+        if let Some(ref notifier) = self.notifier {
+            notifier.alert(text).await;
         }
     }
 
     /// Check a single trader for new activity
     async fn check_trader_activity(&mut self, trader: &Address) -> Result<Vec<Trade>> {
+        if self.detection_mode == DetectionMode::Trades {
+            return self.check_trade_feed(trader, &[], None).await;
+        }
+        let previous = self.last_state.get(trader).cloned();
+
         // Fetch current positions from API
-        let current_state = self.fetch_trader_positions(trader).await?;
+        let trades = match self.fetch_trader_positions(trader).await? {
+            Some(current_state) => {
+                // Compare with previous state to detect changes
+                let trades = if let Some(ref previous_state) = previous {
+                    let flips = self.flips(previous_state, &current_state).await;
+                    self.detect_position_changes(previous_state, &current_state, &flips)?
+                } else {
+                    // First time seeing this trader - only what onboarding catches up on
+                    match self.onboarding {
+                        Some(ref mut onboarding) => onboarding.catch_up(&current_state),
+                        None => Vec::new(),
+                    }
+                };
 
-        // Compare with previous state to detect changes
-        let trades = if let Some(previous_state) = self.last_state.get(trader) {
-            self.detect_position_changes(previous_state, &current_state)?
-        } else {
-            // First time seeing this trader - no changes to report
-            Vec::new()
+                // Update state
+                self.last_state.insert(*trader, current_state);
+                trades
+            }
+            None => {
+                debug!("Positions of {:?} unchanged since the last poll", trader);
+                Vec::new()
+            }
         };
+        if self.detection_mode != DetectionMode::Hybrid {
+            return Ok(trades);
+        }
 
-        // Update state
-        self.last_state.insert(*trader, current_state);
-
-        Ok(trades)
+        // A round trip between polls leaves the positions as they were; the
+        // trade list still shows it
+        match self
+            .check_trade_feed(trader, &trades, previous.as_ref())
+            .await
+        {
+            Ok(missed) => Ok(trades.into_iter().chain(missed).collect()),
+            Err(e) => {
+                warn!("Could not cross-check {:?}'s trade list: {}", trader, e);
+                Ok(trades)
+            }
+        }
     }
 
-    /// Fetch current positions for a trader from the API
-    async fn fetch_trader_positions(&self, trader: &Address) -> Result<TraderState> {
-        // Mock implementation - in production, this would call the Polymarket API
-        // Example endpoint: GET /positions?trader={address}
+    /// `trader`'s new trades on the trade feed: all of them in the `trades`
+    /// detection mode, those the position diff (`diffed`) missed in `hybrid`
+    async fn check_trade_feed(
+        &mut self,
+        trader: &Address,
+        diffed: &[Trade],
+        previous: Option<&TraderState>,
+    ) -> Result<Vec<Trade>> {
+        let Some(ref mut feed) = self.trade_feed else {
+            return Ok(Vec::new());
+        };
+        let Some(fills) = feed.new_trades(*trader).await? else {
+            // First poll of this trader; later ones start from here
+            return Ok(Vec::new());
+        };
+        if self.detection_mode == DetectionMode::Hybrid {
+            return Ok(trade_feed::missed_trades(diffed, &fills, previous));
+        }
+        // Without positions of our own to go by, sells are sized against the
+        // trader's current positions
+        let before = if fills.iter().any(|fill| fill.side == OrderSide::Sell) {
+            match feed.positions_before(*trader, &fills).await {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    warn!(
+                        "Could not fetch {:?}'s positions to size sells: {}",
+                        trader, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        Ok(trade_feed::feed_trades(&fills, before.as_ref()))
+    }
 
+    /// Fetch current positions for a trader from the data API, or None when
+    /// they haven't changed since the last poll
+    async fn fetch_trader_positions(&mut self, trader: &Address) -> Result<Option<TraderState>> {
+        let cached = self.responses.get(trader).cloned().unwrap_or_default();
         let response = self
-            .api_client
-            .get(&format!("{}/positions", self.api_url))
-            .query(&[("trader", format!("{:?}", trader))])
-            .send()
-            .await;
-
-        match response {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    // Parse response into TraderState
-                    // For now, return empty state
-                    Ok(TraderState {
-                        address: *trader,
-                        positions: Vec::new(),
-                        last_updated: chrono::Utc::now(),
-                    })
-                } else {
-                    Err(PolymarketError::ApiError(format!(
-                        "Failed to fetch positions: {}",
-                        resp.status()
-                    )))
+            .data_api
+            .positions_response(*trader, |request| cached.apply(request))
+            .await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            record_poll("not_modified");
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch positions: {}",
+                response.status()
+            )));
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let body = response.bytes().await?;
+        let latest = CachedResponse {
+            etag,
+            last_modified,
+            body_hash: CachedResponse::hash(&body),
+        };
+        if self.last_state.contains_key(trader) && latest.body_hash == cached.body_hash {
+            self.responses.insert(*trader, latest);
+            record_poll("same_body");
+            return Ok(None);
+        }
+        // Only a body that parsed is remembered, so a bad one is parsed again
+        let positions: Vec<DataApiPosition> = serde_json::from_slice(&body)?;
+        self.responses.insert(*trader, latest);
+        record_poll("changed");
+
+        let now = chrono::Utc::now();
+        Ok(Some(TraderState {
+            address: *trader,
+            positions: positions
+                .iter()
+                .filter(|p| p.size > Decimal::ZERO)
+                .map(|p| p.to_position(*trader, now))
+                .collect(),
+            last_updated: now,
+        }))
+    }
+
+    /// The other outcome's token of each token `current` newly holds, where
+    /// the trader sold some of that other outcome since `previous`: a flip
+    /// from one side of a market to the other
+    async fn flips(
+        &self,
+        previous: &TraderState,
+        current: &TraderState,
+    ) -> HashMap<String, String> {
+        let mut flips = HashMap::new();
+        let Some(ref markets) = self.markets else {
+            return flips;
+        };
+        let held: HashMap<&str, Decimal> = current
+            .positions
+            .iter()
+            .map(|p| (p.market_id.as_str(), p.size))
+            .collect();
+        let sold: HashSet<&str> = previous
+            .positions
+            .iter()
+            .filter(|p| {
+                held.get(p.market_id.as_str())
+                    .is_none_or(|size| *size < p.size)
+            })
+            .map(|p| p.market_id.as_str())
+            .collect();
+        if sold.is_empty() {
+            return flips;
+        }
+        let before: HashSet<&str> = previous
+            .positions
+            .iter()
+            .map(|p| p.market_id.as_str())
+            .collect();
+        for position in &current.positions {
+            if before.contains(position.market_id.as_str()) {
+                continue;
+            }
+            match markets.market(&position.market_id).await {
+                Ok(market) => {
+                    if let Some(other) = market.complement(&position.market_id) {
+                        if sold.contains(other.token_id.as_str()) {
+                            flips.insert(position.market_id.clone(), other.token_id.clone());
+                        }
+                    }
                 }
+                Err(e) => debug!("No market metadata for {}: {}", position.market_id, e),
             }
-            Err(e) => Err(PolymarketError::NetworkError(e)),
         }
+        flips
     }
 
     /// Detect position changes between two states
+    ///
+    /// Positions carry no fill times, so each change is stamped with the
+    /// previous poll, the earliest it can have happened: detection latency
+    /// is measured from there, and staleness judged by it. A position opened
+    /// on the other outcome of one the trader sold (`flips`, by token) comes
+    /// after that sale, so the copy closes before it opens.
     fn detect_position_changes(
         &self,
         previous: &TraderState,
         current: &TraderState,
+        flips: &HashMap<String, String>,
     ) -> Result<Vec<Trade>> {
         let mut detected_trades = Vec::new();
+        let since = previous.last_updated;
 
-        // Build map of previous positions by market
+        // Build maps of positions by market
         let prev_positions: HashMap<_, _> = previous
             .positions
             .iter()
             .map(|p| (p.market_id.clone(), p))
             .collect();
+        let current_positions: HashMap<_, _> = current
+            .positions
+            .iter()
+            .map(|p| (p.market_id.clone(), p))
+            .collect();
 
-        // Check for new or increased positions
+        // What the trader sold of a previous position, in part or in full
+        let exit = |prev_pos: &Position| -> Option<Trade> {
+            let current_pos = current_positions.get(&prev_pos.market_id);
+            let left = current_pos.map_or(Decimal::ZERO, |p| p.size);
+            if prev_pos.size <= Decimal::ZERO || left >= prev_pos.size {
+                return None;
+            }
+            let size_diff = prev_pos.size - left;
+            let price = mark_price(current_pos.unwrap_or(&prev_pos));
+            Some(Trade {
+                id: uuid::Uuid::new_v4().to_string(),
+                market_id: prev_pos.market_id.clone(),
+                trader: current.address,
+                side: opposite(&prev_pos.side),
+                price,
+                size: size_diff,
+                size_usdc: size_diff * price,
+                timestamp: since,
+                trader_win_rate: None,
+                exit_fraction: Some(size_diff / prev_pos.size),
+                detection_midpoint: None,
+                labels: Vec::new(),
+            })
+        };
+        let mut exited = HashSet::new();
+
+        // Check for new, increased and partly closed positions
         for current_pos in &current.positions {
             if let Some(prev_pos) = prev_positions.get(&current_pos.market_id) {
-                // Position exists - check if size increased
                 if current_pos.size > prev_pos.size {
+                    // Position exists - size increased
                     let size_diff = current_pos.size - prev_pos.size;
+                    let price = increment_price(prev_pos, current_pos);
                     detected_trades.push(Trade {
                         id: uuid::Uuid::new_v4().to_string(),
                         market_id: current_pos.market_id.clone(),
                         trader: current.address,
                         side: current_pos.side.clone(),
-                        price: current_pos.entry_price,
+                        price,
                         size: size_diff,
-                        size_usdc: size_diff * current_pos.entry_price,
-                        timestamp: current_pos.timestamp,
+                        size_usdc: size_diff * price,
+                        timestamp: since,
                         trader_win_rate: None,
+                        exit_fraction: None,
+                        detection_midpoint: None,
+                        labels: Vec::new(),
                     });
+                } else if exited.insert(prev_pos.market_id.clone()) {
+                    // Partly closed: record the share of the position that was sold
+                    detected_trades.extend(exit(prev_pos));
                 }
             } else {
+                // Flipped from the market's other outcome: that is sold first
+                let flipped = flips
+                    .get(&current_pos.market_id)
+                    .and_then(|other| prev_positions.get(other));
+                if let Some(prev_pos) = flipped {
+                    if exited.insert(prev_pos.market_id.clone()) {
+                        detected_trades.extend(exit(prev_pos));
+                    }
+                }
+
                 // New position
                 detected_trades.push(Trade {
                     id: uuid::Uuid::new_v4().to_string(),
@@ -148,27 +618,102 @@ impl PollingMonitor {
                     price: current_pos.entry_price,
                     size: current_pos.size,
                     size_usdc: current_pos.size * current_pos.entry_price,
-                    timestamp: current_pos.timestamp,
+                    timestamp: since,
                     trader_win_rate: None,
+                    exit_fraction: None,
+                    detection_midpoint: None,
+                    labels: Vec::new(),
                 });
             }
         }
 
+        // Positions that are gone were closed in full
+        for prev_pos in &previous.positions {
+            if !exited.contains(&prev_pos.market_id) {
+                detected_trades.extend(exit(prev_pos));
+            }
+        }
+
         Ok(detected_trades)
     }
 }
 
+/// Time to wait before the next poll after `failed_polls` failed in a row
+fn poll_backoff(interval: Duration, failed_polls: u32) -> Duration {
+    if failed_polls == 0 {
+        return interval;
+    }
+    (interval * 2u32.saturating_pow(failed_polls.min(16))).min(MAX_POLL_BACKOFF.max(interval))
+}
+
+/// Current price implied by a position's unrealized P&L, or its entry price
+/// when there is none
+pub(crate) fn mark_price(position: &Position) -> Decimal {
+    if position.size <= Decimal::ZERO || position.pnl.is_zero() {
+        return position.entry_price;
+    }
+    let per_share = position.pnl / position.size;
+    let mark = match position.side {
+        OrderSide::Buy => position.entry_price + per_share,
+        OrderSide::Sell => position.entry_price - per_share,
+    };
+    mark.clamp(Decimal::ZERO, Decimal::ONE)
+}
+
+/// Price the trader paid for the shares a position grew by, from how its
+/// average entry price moved; its mark when that gives no price, as when the
+/// trader also sold between polls
+fn increment_price(previous: &Position, current: &Position) -> Decimal {
+    let added = current.size - previous.size;
+    let price = (current.size * current.entry_price - previous.size * previous.entry_price) / added;
+    if price > Decimal::ZERO && price <= Decimal::ONE {
+        price
+    } else {
+        mark_price(current)
+    }
+}
+
+/// Count a positions poll by whether it had to be diffed
+fn record_poll(result: &str) {
+    metrics::global().increment(
+        "polycopy_position_polls_total",
+        "Trader positions polls, by whether the response had changed",
+        &[("result", result)],
+    );
+}
+
+fn opposite(side: &OrderSide) -> OrderSide {
+    match side {
+        OrderSide::Buy => OrderSide::Sell,
+        OrderSide::Sell => OrderSide::Buy,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{OrderSide, Position};
+    use crate::execution::mock_clob::MockClob;
     use chrono::Utc;
+    use reqwest::Client;
     use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn test_poll_backoff() {
+        let interval = Duration::from_secs(2);
+        assert_eq!(poll_backoff(interval, 0), interval);
+        assert_eq!(poll_backoff(interval, 1), Duration::from_secs(4));
+        assert_eq!(poll_backoff(interval, 3), Duration::from_secs(16));
+        assert_eq!(poll_backoff(interval, 40), MAX_POLL_BACKOFF);
+        // An interval beyond the cap is never shortened
+        let slow = Duration::from_secs(120);
+        assert_eq!(poll_backoff(slow, 2), slow);
+    }
 
     #[test]
     fn test_detect_position_changes() {
         let monitor = PollingMonitor::new(
-            "http://localhost".to_string(),
+            DataApiClient::new("http://localhost".to_string()),
             vec![],
             Duration::from_secs(1),
         );
@@ -186,6 +731,7 @@ mod tests {
                 side: OrderSide::Buy,
                 timestamp: Utc::now(),
                 pnl: dec!(0),
+                trader: None,
             }],
             last_updated: Utc::now(),
         };
@@ -200,6 +746,7 @@ mod tests {
                     side: OrderSide::Buy,
                     timestamp: Utc::now(),
                     pnl: dec!(0),
+                    trader: None,
                 },
                 Position {
                     market_id: "market2".to_string(),
@@ -208,12 +755,322 @@ mod tests {
                     side: OrderSide::Buy,
                     timestamp: Utc::now(),
                     pnl: dec!(0),
+                    trader: None,
                 },
             ],
             last_updated: Utc::now(),
         };
 
-        let trades = monitor.detect_position_changes(&previous, &current).unwrap();
+        let trades = monitor
+            .detect_position_changes(&previous, &current, &HashMap::new())
+            .unwrap();
         assert_eq!(trades.len(), 2); // One increased, one new
     }
+
+    #[test]
+    fn test_size_increase_is_copied_at_the_price_paid() {
+        let monitor = PollingMonitor::new(
+            DataApiClient::new("http://localhost".to_string()),
+            vec![],
+            Duration::from_secs(1),
+        );
+        let position = |market: &str, size: Decimal, entry_price: Decimal| Position {
+            market_id: market.to_string(),
+            entry_price,
+            size,
+            side: OrderSide::Buy,
+            timestamp: Utc::now(),
+            pnl: dec!(0),
+            trader: None,
+        };
+        let polled_at = Utc::now() - chrono::Duration::seconds(2);
+        let previous = TraderState {
+            address: Address::zero(),
+            positions: vec![
+                position("market1", dec!(100), dec!(0.30)),
+                position("market2", dec!(100), dec!(0.50)),
+            ],
+            last_updated: polled_at,
+        };
+        // 100 more bought at 0.70 lift the average to 0.50; market2's average
+        // dropped below anything paid for the added shares, so its mark is used
+        let current = TraderState {
+            address: Address::zero(),
+            positions: vec![
+                position("market1", dec!(200), dec!(0.50)),
+                Position {
+                    pnl: dec!(6),
+                    ..position("market2", dec!(120), dec!(0.40))
+                },
+            ],
+            last_updated: Utc::now(),
+        };
+        let trades = monitor
+            .detect_position_changes(&previous, &current, &HashMap::new())
+            .unwrap();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].size, dec!(100));
+        assert_eq!(trades[0].price, dec!(0.70));
+        assert_eq!(trades[0].size_usdc, dec!(70));
+        assert_eq!(trades[1].size, dec!(20));
+        assert_eq!(trades[1].price, dec!(0.45));
+        // Stamped with the last poll that didn't see them
+        assert!(trades.iter().all(|t| t.timestamp == polled_at));
+    }
+
+    #[test]
+    fn test_cached_response_validators() {
+        let cached = CachedResponse {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2024 07:28:00 GMT".to_string()),
+            body_hash: CachedResponse::hash(b"[]"),
+        };
+        let request = cached
+            .apply(Client::new().get("http://localhost/positions"))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[IF_NONE_MATCH], "\"v1\"");
+        assert_eq!(
+            request.headers()[IF_MODIFIED_SINCE],
+            "Wed, 21 Oct 2024 07:28:00 GMT"
+        );
+
+        assert_eq!(cached.body_hash, CachedResponse::hash(b"[]"));
+        assert_ne!(cached.body_hash, CachedResponse::hash(b"[{}]"));
+        let fresh = CachedResponse::default()
+            .apply(Client::new().get("http://localhost/positions"))
+            .build()
+            .unwrap();
+        assert!(fresh.headers().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_positions_are_parsed_from_the_data_api() {
+        let mock = MockClob::start().await.unwrap();
+        let trader = Address::from_low_u64_be(7);
+        let user = format!("{:?}", trader);
+        let mut monitor = PollingMonitor::new(
+            DataApiClient::new(mock.url()),
+            vec![trader],
+            Duration::from_secs(1),
+        );
+        let position = |asset: &str, size: Decimal, avg: Decimal, cur: Decimal| {
+            json!({
+                "asset": asset,
+                "title": "Will it rain?",
+                "outcome": "Yes",
+                "size": size,
+                "avgPrice": avg,
+                "curPrice": cur,
+                "cashPnl": size * (cur - avg),
+            })
+        };
+
+        mock.set_positions(
+            &user,
+            json!([position("111", dec!(100), dec!(0.40), dec!(0.40))]),
+        );
+        let first = monitor
+            .fetch_trader_positions(&trader)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.positions.len(), 1);
+        assert_eq!(first.positions[0].market_id, "111");
+        assert_eq!(first.positions[0].size, dec!(100));
+        assert_eq!(first.positions[0].entry_price, dec!(0.40));
+        assert_eq!(first.positions[0].trader, Some(trader));
+        monitor.last_state.insert(trader, first);
+
+        // The same body again isn't diffed
+        assert!(monitor
+            .fetch_trader_positions(&trader)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Sold 40 with the price up to 0.50, and bought into another market
+        mock.set_positions(
+            &user,
+            json!([
+                position("111", dec!(60), dec!(0.40), dec!(0.50)),
+                position("222", dec!(50), dec!(0.30), dec!(0.30)),
+            ]),
+        );
+        let trades = monitor.check_trader_activity(&trader).await.unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].side, OrderSide::Sell);
+        assert_eq!(trades[0].size, dec!(40));
+        assert_eq!(trades[0].price, dec!(0.50));
+        assert_eq!(trades[0].exit_fraction, Some(dec!(0.4)));
+        assert_eq!(trades[1].market_id, "222");
+        assert_eq!(trades[1].side, OrderSide::Buy);
+        assert_eq!(trades[1].size, dec!(50));
+
+        // A body that doesn't parse fails the poll and is parsed again next time
+        mock.set_positions(&user, json!({ "error": "bad gateway" }));
+        assert!(monitor.fetch_trader_positions(&trader).await.is_err());
+        assert!(monitor.fetch_trader_positions(&trader).await.is_err());
+    }
+
+    #[test]
+    fn test_flip_to_the_other_outcome_closes_then_opens() {
+        let monitor = PollingMonitor::new(
+            DataApiClient::new("http://localhost".to_string()),
+            vec![],
+            Duration::from_secs(1),
+        );
+        let position = |token: &str, size: Decimal, price: Decimal| Position {
+            market_id: token.to_string(),
+            entry_price: price,
+            size,
+            side: OrderSide::Buy,
+            timestamp: Utc::now(),
+            pnl: dec!(0),
+            trader: None,
+        };
+        let state = |positions: Vec<Position>| TraderState {
+            address: Address::zero(),
+            positions,
+            last_updated: Utc::now(),
+        };
+        // 111 is the market's YES token and 222 its NO token
+        let flips = HashMap::from([("222".to_string(), "111".to_string())]);
+        let sides = |trades: &[Trade]| -> Vec<(String, OrderSide)> {
+            trades
+                .iter()
+                .map(|t| (t.market_id.clone(), t.side.clone()))
+                .collect()
+        };
+
+        let previous = state(vec![position("111", dec!(100), dec!(0.40))]);
+        let current = state(vec![position("222", dec!(80), dec!(0.55))]);
+        let trades = monitor
+            .detect_position_changes(&previous, &current, &flips)
+            .unwrap();
+        assert_eq!(
+            sides(&trades),
+            [
+                ("111".to_string(), OrderSide::Sell),
+                ("222".to_string(), OrderSide::Buy)
+            ]
+        );
+        // The whole old position is closed, then the new one opened
+        assert_eq!(trades[0].size, dec!(100));
+        assert_eq!(trades[0].exit_fraction, Some(Decimal::ONE));
+        assert_eq!(trades[1].size, dec!(80));
+        assert_eq!(trades[1].exit_fraction, None);
+
+        // Unrelated tokens keep the order they are listed in
+        let unrelated = monitor
+            .detect_position_changes(&previous, &current, &HashMap::new())
+            .unwrap();
+        assert_eq!(unrelated[0].market_id, "222");
+
+        // Half of YES sold into NO, YES listed after NO: still sold first, once
+        let current = state(vec![
+            position("222", dec!(40), dec!(0.55)),
+            position("111", dec!(50), dec!(0.40)),
+        ]);
+        let trades = monitor
+            .detect_position_changes(&previous, &current, &flips)
+            .unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].market_id, "111");
+        assert_eq!(trades[0].exit_fraction, Some(dec!(0.5)));
+        assert_eq!(trades[1].market_id, "222");
+    }
+
+    #[test]
+    fn test_partial_and_full_exits_carry_the_sold_fraction() {
+        let monitor = PollingMonitor::new(
+            DataApiClient::new("http://localhost".to_string()),
+            vec![],
+            Duration::from_secs(1),
+        );
+        let position = |market: &str, size: Decimal, pnl: Decimal| Position {
+            market_id: market.to_string(),
+            entry_price: dec!(0.40),
+            size,
+            side: OrderSide::Buy,
+            timestamp: Utc::now(),
+            pnl,
+            trader: None,
+        };
+        let state = |positions: Vec<Position>| TraderState {
+            address: Address::zero(),
+            positions,
+            last_updated: Utc::now(),
+        };
+
+        let previous = state(vec![
+            position("market1", dec!(500), dec!(0)),
+            position("market2", dec!(100), dec!(0)),
+        ]);
+        // Sold 200 of 500 with the price up to 0.50, and closed market2
+        let current = state(vec![position("market1", dec!(300), dec!(30))]);
+        let trades = monitor
+            .detect_position_changes(&previous, &current, &HashMap::new())
+            .unwrap();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].side, OrderSide::Sell);
+        assert_eq!(trades[0].size, dec!(200));
+        assert_eq!(trades[0].price, dec!(0.50));
+        assert_eq!(trades[0].exit_fraction, Some(dec!(0.4)));
+        assert_eq!(trades[1].market_id, "market2");
+        assert_eq!(trades[1].exit_fraction, Some(Decimal::ONE));
+    }
+
+    #[test]
+    fn test_reconcile_baselines_added_and_drains_removed_traders() {
+        let (kept, removed, added) = (
+            Address::from_low_u64_be(1),
+            Address::from_low_u64_be(2),
+            Address::from_low_u64_be(3),
+        );
+        let mut monitor = PollingMonitor::new(
+            DataApiClient::new("http://localhost".to_string()),
+            vec![kept, removed],
+            Duration::from_secs(1),
+        )
+        .with_aggregation(Duration::from_secs(60));
+        for trader in [kept, removed] {
+            monitor.last_state.insert(
+                trader,
+                TraderState {
+                    address: trader,
+                    positions: Vec::new(),
+                    last_updated: Utc::now(),
+                },
+            );
+            monitor.responses.insert(trader, CachedResponse::default());
+        }
+        // A clip of the removed trader's, held back for aggregation
+        let clip = Trade::sample()
+            .with_id("clip")
+            .with_market("market1")
+            .with_trader(removed)
+            .with_fill(dec!(0.5), dec!(10));
+        let now = Instant::now();
+        assert!(monitor
+            .aggregator
+            .as_mut()
+            .unwrap()
+            .push(clip, now)
+            .is_empty());
+
+        let drained = monitor.reconcile(&[kept, added]);
+
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].id, "clip");
+        assert_eq!(monitor.tracked_traders, vec![kept, added]);
+        assert!(monitor.last_state.contains_key(&kept));
+        assert!(!monitor.last_state.contains_key(&removed));
+        assert!(!monitor.responses.contains_key(&removed));
+        // The first poll of the new trader only records a baseline
+        assert!(!monitor.last_state.contains_key(&added));
+        assert!(monitor.reconcile(&[kept, added]).is_empty());
+    }
 }
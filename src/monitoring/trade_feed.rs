@@ -0,0 +1,360 @@
+use crate::errors::{PolymarketError, Result};
+use crate::metrics;
+use crate::models::{OrderSide, Position, Trade, TraderState};
+use crate::monitoring::data_api::{DataApiClient, DataApiTrade};
+use chrono::Utc;
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Most recent trades fetched per trader and poll
+const TRADES_PER_POLL: usize = 100;
+
+/// Where trades are detected from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DetectionMode {
+    /// Diffs of the traders' positions between polls
+    Positions,
+    /// The traders' trade lists on the data API
+    Trades,
+    /// Position diffs, plus the round trips they missed from the trade lists
+    Hybrid,
+}
+
+impl DetectionMode {
+    pub fn parse(mode: &str) -> Result<Self> {
+        match mode {
+            "positions" => Ok(DetectionMode::Positions),
+            "trades" => Ok(DetectionMode::Trades),
+            "hybrid" => Ok(DetectionMode::Hybrid),
+            other => Err(PolymarketError::ConfigError(format!(
+                "detection_mode must be 'positions', 'trades' or 'hybrid', not '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Newest trade seen of a trader: its time and the ids seen at that time
+#[derive(Clone, Debug, Default)]
+struct Cursor {
+    timestamp: i64,
+    ids: HashSet<String>,
+}
+
+impl Cursor {
+    /// The trades in `trades` after the cursor, oldest first, moving it past them
+    fn advance(&mut self, trades: Vec<DataApiTrade>) -> Vec<DataApiTrade> {
+        let mut fresh: Vec<DataApiTrade> = trades
+            .into_iter()
+            .filter(|t| {
+                t.timestamp > self.timestamp
+                    || (t.timestamp == self.timestamp && !self.ids.contains(&id(t)))
+            })
+            .collect();
+        fresh.sort_by_key(|t| t.timestamp);
+        for trade in &fresh {
+            if trade.timestamp > self.timestamp {
+                self.timestamp = trade.timestamp;
+                self.ids.clear();
+            }
+            self.ids.insert(id(trade));
+        }
+        fresh
+    }
+}
+
+fn id(trade: &DataApiTrade) -> String {
+    format!("{}:{}", trade.transaction_hash, trade.asset)
+}
+
+/// Follows each tracked trader's trade list, handing out the trades made
+/// since the previous poll
+pub struct TradeFeed {
+    client: DataApiClient,
+    cursors: HashMap<Address, Cursor>,
+}
+
+impl TradeFeed {
+    pub fn new(client: DataApiClient) -> Self {
+        Self {
+            client,
+            cursors: HashMap::new(),
+        }
+    }
+
+    /// `trader`'s trades since the last call, oldest first; None on the
+    /// first call, which only records where the list stands
+    pub async fn new_trades(&mut self, trader: Address) -> Result<Option<Vec<DataApiTrade>>> {
+        let trades = self.client.trades(trader, TRADES_PER_POLL).await?;
+        match self.cursors.get_mut(&trader) {
+            Some(cursor) => Ok(Some(cursor.advance(trades))),
+            None => {
+                let mut cursor = Cursor::default();
+                cursor.advance(trades);
+                self.cursors.insert(trader, cursor);
+                Ok(None)
+            }
+        }
+    }
+
+    /// `trader`'s positions before `fills`: the current ones on the data API
+    /// with the fills taken back out, to size the sells among them against
+    pub async fn positions_before(
+        &self,
+        trader: Address,
+        fills: &[DataApiTrade],
+    ) -> Result<TraderState> {
+        let mut sizes: HashMap<String, Decimal> = self
+            .client
+            .positions(trader)
+            .await?
+            .into_iter()
+            .map(|p| (p.asset, p.size))
+            .collect();
+        for fill in fills {
+            let size = sizes.entry(fill.asset.clone()).or_default();
+            match fill.side {
+                OrderSide::Buy => *size -= fill.size,
+                OrderSide::Sell => *size += fill.size,
+            }
+        }
+        let now = Utc::now();
+        Ok(TraderState {
+            address: trader,
+            positions: sizes
+                .into_iter()
+                .filter(|(_, size)| *size > Decimal::ZERO)
+                .map(|(market_id, size)| Position {
+                    market_id,
+                    entry_price: Decimal::ZERO,
+                    size,
+                    side: OrderSide::Buy,
+                    timestamp: now,
+                    pnl: Decimal::ZERO,
+                    trader: Some(trader),
+                })
+                .collect(),
+            last_updated: now,
+        })
+    }
+
+    /// Start `trader` from a fresh baseline the next time
+    pub fn forget(&mut self, trader: &Address) {
+        self.cursors.remove(trader);
+    }
+}
+
+/// `feed` trades as detected trades, sells carrying the share of the
+/// position they sold, counted from `previous` positions
+pub fn feed_trades(feed: &[DataApiTrade], previous: Option<&TraderState>) -> Vec<Trade> {
+    let mut held: HashMap<&str, Decimal> = previous
+        .map(|state| {
+            state
+                .positions
+                .iter()
+                .filter(|p| p.side == OrderSide::Buy)
+                .map(|p| (p.market_id.as_str(), p.size))
+                .collect()
+        })
+        .unwrap_or_default();
+    feed.iter()
+        .map(|fill| {
+            let mut trade = fill.to_trade();
+            let position = held.entry(fill.asset.as_str()).or_default();
+            match fill.side {
+                OrderSide::Buy => *position += fill.size,
+                OrderSide::Sell => {
+                    if *position > Decimal::ZERO {
+                        trade.exit_fraction = Some((fill.size / *position).min(Decimal::ONE));
+                    }
+                    *position = (*position - fill.size).max(Decimal::ZERO);
+                }
+            }
+            trade
+        })
+        .collect()
+}
+
+/// The round trips in `feed` the position diff, which found `diffed`,
+/// couldn't see: trades in markets where the diff found nothing and that
+/// leave the position where it was
+///
+/// Markets whose trades don't net out are left to the diff, as the
+/// positions may just not show them yet.
+pub fn missed_trades(
+    diffed: &[Trade],
+    feed: &[DataApiTrade],
+    previous: Option<&TraderState>,
+) -> Vec<Trade> {
+    let seen: HashSet<&str> = diffed.iter().map(|t| t.market_id.as_str()).collect();
+    let mut net: BTreeMap<&str, Decimal> = BTreeMap::new();
+    for fill in feed {
+        let shares = match fill.side {
+            OrderSide::Buy => fill.size,
+            OrderSide::Sell => -fill.size,
+        };
+        *net.entry(fill.asset.as_str()).or_default() += shares;
+    }
+    let round_trips: HashSet<&str> = net
+        .into_iter()
+        .filter(|(market, shares)| !seen.contains(market) && shares.is_zero())
+        .map(|(market, _)| market)
+        .collect();
+    let missed: Vec<DataApiTrade> = feed
+        .iter()
+        .filter(|fill| round_trips.contains(fill.asset.as_str()))
+        .cloned()
+        .collect();
+    for _ in &missed {
+        metrics::global().increment(
+            "polycopy_missed_trades_total",
+            "Trades the position diff missed and the trade feed caught",
+            &[],
+        );
+    }
+    feed_trades(&missed, previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::mock_clob::{Fault, MockClob};
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    fn fill(tx: &str, asset: &str, side: OrderSide, size: Decimal, timestamp: i64) -> DataApiTrade {
+        DataApiTrade {
+            proxy_wallet: Address::zero(),
+            side,
+            asset: asset.to_string(),
+            size,
+            price: dec!(0.5),
+            timestamp,
+            title: String::new(),
+            slug: String::new(),
+            outcome: None,
+            transaction_hash: tx.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_round_trips_between_polls_are_caught() {
+        // Only trades after the cursor count, including ones sharing its second
+        let mut cursor = Cursor::default();
+        cursor.advance(vec![fill("0x1", "yes", OrderSide::Buy, dec!(10), 100)]);
+        let fresh = cursor.advance(vec![
+            fill("0x3", "yes", OrderSide::Sell, dec!(10), 120),
+            fill("0x2", "yes", OrderSide::Buy, dec!(10), 100),
+            fill("0x1", "yes", OrderSide::Buy, dec!(10), 100),
+        ]);
+        let ids: Vec<&str> = fresh.iter().map(|t| t.transaction_hash.as_str()).collect();
+        assert_eq!(ids, ["0x2", "0x3"]);
+        assert!(cursor.advance(fresh).is_empty());
+
+        // Bought and sold "flip" in one interval, which the diff can't see;
+        // "open" is still on its way into the positions
+        let feed = [
+            fill("0x4", "flip", OrderSide::Buy, dec!(40), 130),
+            fill("0x5", "open", OrderSide::Buy, dec!(15), 131),
+            fill("0x6", "flip", OrderSide::Sell, dec!(40), 140),
+            fill("0x7", "seen", OrderSide::Buy, dec!(5), 141),
+            fill("0x8", "seen", OrderSide::Sell, dec!(5), 142),
+        ];
+        let diffed = feed_trades(&feed[3..4], None);
+        let missed = missed_trades(&diffed, &feed, None);
+        let summary: Vec<(&str, OrderSide, Option<Decimal>)> = missed
+            .iter()
+            .map(|t| (t.market_id.as_str(), t.side.clone(), t.exit_fraction))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("flip", OrderSide::Buy, None),
+                ("flip", OrderSide::Sell, Some(Decimal::ONE)),
+            ]
+        );
+        assert_eq!(
+            DetectionMode::parse("hybrid").unwrap(),
+            DetectionMode::Hybrid
+        );
+        assert!(DetectionMode::parse("activity").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_feed_starts_from_the_list_and_survives_errors() {
+        let mock = MockClob::start().await.unwrap();
+        let trader = Address::from_low_u64_be(9);
+        let user = format!("{:?}", trader);
+        let listed = |tx: &str, asset: &str, side: &str, size: u32, timestamp: i64| {
+            json!({
+                "proxyWallet": user,
+                "side": side,
+                "asset": asset,
+                "size": size,
+                "price": 0.5,
+                "timestamp": timestamp,
+                "transactionHash": tx,
+            })
+        };
+        let mut feed = TradeFeed::new(DataApiClient::new(mock.url()));
+
+        // Trades made before the first call are history, not copied
+        mock.set_trader_trades(&user, json!([listed("0x1", "yes", "BUY", 10, 100)]));
+        assert!(feed.new_trades(trader).await.unwrap().is_none());
+        assert_eq!(feed.new_trades(trader).await.unwrap().unwrap().len(), 0);
+
+        mock.set_trader_trades(
+            &user,
+            json!([
+                listed("0x2", "yes", "SELL", 4, 110),
+                listed("0x1", "yes", "BUY", 10, 100),
+            ]),
+        );
+        mock.inject("GET /trades", Fault::ServerError, 1);
+        assert!(feed.new_trades(trader).await.is_err());
+        // The failed poll loses nothing
+        let fresh = feed.new_trades(trader).await.unwrap().unwrap();
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].transaction_hash, "0x2");
+
+        // Forgotten, the trader starts over from where the list stands
+        feed.forget(&trader);
+        assert!(feed.new_trades(trader).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sells_without_a_known_position_carry_no_fraction() {
+        let previous = TraderState {
+            address: Address::zero(),
+            positions: vec![Position {
+                market_id: "yes".to_string(),
+                entry_price: dec!(0.5),
+                size: dec!(50),
+                side: OrderSide::Buy,
+                timestamp: Utc::now(),
+                pnl: Decimal::ZERO,
+                trader: None,
+            }],
+            last_updated: Utc::now(),
+        };
+        let feed = [
+            fill("0x1", "yes", OrderSide::Sell, dec!(20), 100),
+            // Selling more than is left closes it all
+            fill("0x2", "yes", OrderSide::Sell, dec!(40), 101),
+            fill("0x3", "no", OrderSide::Sell, dec!(10), 102),
+        ];
+
+        let fractions: Vec<Option<Decimal>> = feed_trades(&feed, Some(&previous))
+            .iter()
+            .map(|t| t.exit_fraction)
+            .collect();
+        assert_eq!(fractions, [Some(dec!(0.4)), Some(Decimal::ONE), None]);
+
+        let unknown: Vec<Option<Decimal>> = feed_trades(&feed, None)
+            .iter()
+            .map(|t| t.exit_fraction)
+            .collect();
+        assert_eq!(unknown, [None, None, None]);
+    }
+}
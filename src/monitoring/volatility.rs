@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, MathematicalOps};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// A market's prices over a trailing time window
+#[derive(Clone, Debug)]
+pub struct PriceHistory {
+    window: chrono::Duration,
+    samples: VecDeque<(DateTime<Utc>, Decimal)>,
+}
+
+impl PriceHistory {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window: chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX),
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add a price seen at `at`, dropping samples that fell out of the window
+    pub fn record(&mut self, at: DateTime<Utc>, price: Decimal) {
+        self.samples.push_back((at, price));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(seen, _)| at - *seen > self.window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Standard deviation of the prices in the window, or None with fewer
+    /// than `min_samples` of them
+    pub fn volatility(&self, min_samples: usize) -> Option<Decimal> {
+        let n = self.samples.len();
+        if n < min_samples.max(2) {
+            return None;
+        }
+        let count = Decimal::from(n);
+        let mean = self.samples.iter().map(|(_, p)| *p).sum::<Decimal>() / count;
+        let variance = self
+            .samples
+            .iter()
+            .map(|(_, p)| (*p - mean) * (*p - mean))
+            .sum::<Decimal>()
+            / count;
+        variance.sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_volatility_over_window() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        let mut history = PriceHistory::new(Duration::from_secs(600));
+
+        history.record(at(0), dec!(0.10));
+        assert_eq!(history.volatility(2), None);
+        history.record(at(1), dec!(0.50));
+        history.record(at(2), dec!(0.60));
+        history.record(at(3), dec!(0.40));
+        assert!(history.volatility(3).unwrap() > dec!(0.15));
+
+        // The 0.10 outlier ages out of the 10-minute window
+        history.record(at(11), dec!(0.50));
+        assert_eq!(history.samples.len(), 4);
+        let volatility = history.volatility(3).unwrap();
+        assert!(volatility > dec!(0.07) && volatility < dec!(0.071));
+        assert_eq!(history.volatility(5), None);
+    }
+}
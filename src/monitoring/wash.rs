@@ -0,0 +1,198 @@
+use crate::config::WashTradingConfig;
+use crate::models::Trade;
+use chrono::{DateTime, Duration, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use tracing::warn;
+
+/// A pattern that suggests volume farming rather than real trading
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WashSignal {
+    /// In and out of the same market within `round_trip_seconds`
+    RapidRoundTrip,
+    /// A round trip that exited at about the entry price
+    NoEdgeRoundTrip,
+    /// Another tracked wallet took the other side at the same size and price
+    CoordinatedCounterparty,
+}
+
+/// What to do with a trader's latest trade
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WashVerdict {
+    Clean,
+    /// Suspicious, but the action is only to flag; the count of recent signals
+    Flagged(usize),
+    /// Don't copy this trader until the suspension ends
+    Suspended,
+}
+
+/// Heuristic wash-trading detection across all tracked traders
+///
+/// Every detected trade is fed through [`observe`](Self::observe), including
+/// trades from suspended traders, so their history stays complete. Windows
+/// are measured on trade timestamps.
+pub struct WashTradeDetector {
+    config: WashTradingConfig,
+    recent: VecDeque<Trade>,
+    signals: HashMap<Address, Vec<(DateTime<Utc>, WashSignal)>>,
+    suspended_until: HashMap<Address, DateTime<Utc>>,
+}
+
+impl WashTradeDetector {
+    pub fn new(config: WashTradingConfig) -> Self {
+        Self {
+            config,
+            recent: VecDeque::new(),
+            signals: HashMap::new(),
+            suspended_until: HashMap::new(),
+        }
+    }
+
+    /// Record `trade` and decide whether its trader may be copied
+    pub fn observe(&mut self, trade: &Trade) -> WashVerdict {
+        let now = trade.timestamp;
+        let lookback = Duration::hours(self.config.lookback_hours as i64);
+        while self
+            .recent
+            .front()
+            .is_some_and(|old| now - old.timestamp > lookback)
+        {
+            self.recent.pop_front();
+        }
+
+        for (trader, signal) in self.match_signals(trade) {
+            warn!(
+                "Possible wash trading by {:?} in {}: {:?}",
+                trader, trade.market_id, signal
+            );
+            self.signals.entry(trader).or_default().push((now, signal));
+        }
+        self.recent.push_back(trade.clone());
+
+        for signals in self.signals.values_mut() {
+            signals.retain(|(at, _)| now - *at <= lookback);
+        }
+        self.verdict(trade.trader, now)
+    }
+
+    pub fn is_suspended(&self, trader: Address, now: DateTime<Utc>) -> bool {
+        self.suspended_until
+            .get(&trader)
+            .is_some_and(|until| now < *until)
+    }
+
+    /// Signals raised by `trade` against earlier trades, per trader
+    fn match_signals(&self, trade: &Trade) -> Vec<(Address, WashSignal)> {
+        let round_trip = Duration::seconds(self.config.round_trip_seconds as i64);
+        let counterparty_window = Duration::seconds(self.config.counterparty_window_seconds as i64);
+        let flat =
+            |earlier: &Trade| (earlier.price - trade.price).abs() <= self.config.min_price_edge;
+
+        let mut found = Vec::new();
+        let mut rapid = false;
+        let mut no_edge = false;
+        for earlier in self.recent.iter().rev() {
+            if earlier.market_id != trade.market_id || earlier.side == trade.side {
+                continue;
+            }
+            let gap = trade.timestamp - earlier.timestamp;
+
+            if earlier.trader == trade.trader {
+                if !rapid && gap <= round_trip {
+                    rapid = true;
+                    found.push((trade.trader, WashSignal::RapidRoundTrip));
+                }
+                if !no_edge && flat(earlier) {
+                    no_edge = true;
+                    found.push((trade.trader, WashSignal::NoEdgeRoundTrip));
+                }
+            } else if gap <= counterparty_window
+                && flat(earlier)
+                && similar_size(earlier.size, trade.size, self.config.size_tolerance)
+            {
+                found.push((trade.trader, WashSignal::CoordinatedCounterparty));
+                found.push((earlier.trader, WashSignal::CoordinatedCounterparty));
+            }
+        }
+        found
+    }
+
+    fn verdict(&mut self, trader: Address, now: DateTime<Utc>) -> WashVerdict {
+        if self.is_suspended(trader, now) {
+            return WashVerdict::Suspended;
+        }
+        let count = self.signals.get(&trader).map_or(0, Vec::len);
+        if count < self.config.signal_threshold {
+            return WashVerdict::Clean;
+        }
+
+        if self.config.action == "suspend" {
+            let until = now + Duration::hours(self.config.suspend_hours as i64);
+            warn!(
+                "Suspending trader {:?} until {} after {} wash-trading signals",
+                trader, until, count
+            );
+            self.suspended_until.insert(trader, until);
+            // Start over once the suspension ends
+            self.signals.remove(&trader);
+            WashVerdict::Suspended
+        } else {
+            WashVerdict::Flagged(count)
+        }
+    }
+}
+
+fn similar_size(a: Decimal, b: Decimal, tolerance: Decimal) -> bool {
+    let larger = a.max(b);
+    larger > Decimal::ZERO && (a - b).abs() / larger <= tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderSide;
+    use rust_decimal_macros::dec;
+
+    fn trade(trader: u64, side: OrderSide, price: Decimal, seconds: i64) -> Trade {
+        Trade::sample()
+            .with_trader(Address::from_low_u64_be(trader))
+            .with_side(side)
+            .with_fill(price, dec!(100))
+            .with_timestamp(DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap())
+    }
+
+    #[test]
+    fn test_round_trips_and_counterparties_suspend_trader() {
+        let config = WashTradingConfig {
+            enabled: true,
+            action: "suspend".to_string(),
+            signal_threshold: 3,
+            ..WashTradingConfig::default()
+        };
+        let mut detector = WashTradeDetector::new(config);
+
+        assert_eq!(
+            detector.observe(&trade(1, OrderSide::Buy, dec!(0.50), 0)),
+            WashVerdict::Clean
+        );
+        // Trader 2 sells the same size at the same price moments later
+        assert_eq!(
+            detector.observe(&trade(2, OrderSide::Sell, dec!(0.50), 20)),
+            WashVerdict::Clean
+        );
+        // Trader 1 flips out flat a minute later: rapid + no edge + earlier counterparty
+        assert_eq!(
+            detector.observe(&trade(1, OrderSide::Sell, dec!(0.50), 60)),
+            WashVerdict::Suspended
+        );
+        let soon = trade(1, OrderSide::Buy, dec!(0.40), 3600).timestamp;
+        assert!(detector.is_suspended(Address::from_low_u64_be(1), soon));
+        assert!(!detector.is_suspended(Address::from_low_u64_be(2), soon));
+
+        // An unrelated trader in another market is unaffected
+        let mut other = trade(3, OrderSide::Buy, dec!(0.30), 90);
+        other.market_id = "m2".to_string();
+        assert_eq!(detector.observe(&other), WashVerdict::Clean);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::config::NetworkConfig;
+use crate::errors::{PolymarketError, Result};
+use reqwest::{Client, Proxy};
+use std::time::Duration;
+
+/// An HTTP client with the `[network]` timeouts, pool size, proxy and user agent
+pub fn http_client(config: &NetworkConfig) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_seconds))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_seconds))
+        .user_agent(config.user_agent.clone());
+    if let Some(ref proxy_url) = config.proxy_url {
+        let proxy = Proxy::all(proxy_url).map_err(|e| {
+            PolymarketError::ConfigError(format!("Invalid proxy_url {}: {}", proxy_url, e))
+        })?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_client_from_config() {
+        assert!(http_client(&NetworkConfig::default()).is_ok());
+
+        let proxied = NetworkConfig {
+            proxy_url: Some("http://127.0.0.1:3128".to_string()),
+            ..NetworkConfig::default()
+        };
+        assert!(http_client(&proxied).is_ok());
+
+        let broken = NetworkConfig {
+            proxy_url: Some("not a url".to_string()),
+            ..NetworkConfig::default()
+        };
+        assert!(http_client(&broken).is_err());
+    }
+}
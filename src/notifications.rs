@@ -0,0 +1,138 @@
+use crate::config::NotificationsConfig;
+use crate::models::{MarketLabel, Trade};
+use crate::secrets::SecretFile;
+use reqwest::Client;
+use serde_json::json;
+use tracing::warn;
+
+/// Posts copy-trade results to a Slack incoming webhook
+pub struct SlackNotifier {
+    http_client: Client,
+    webhook_url: String,
+    /// Where the webhook URL came from, when it's a rotating secret file
+    webhook_file: Option<SecretFile>,
+    notify_on_trade: bool,
+    notify_on_error: bool,
+}
+
+impl SlackNotifier {
+    /// None when no webhook is configured or every notification is turned off
+    pub fn from_config(config: &NotificationsConfig) -> Option<Self> {
+        let webhook_url = config.slack_webhook_url.clone()?;
+        if !config.notify_on_trade && !config.notify_on_error {
+            return None;
+        }
+        // The file was already read into the URL; a rotated one is picked up per message
+        let webhook_file =
+            config
+                .slack_webhook_url_file
+                .as_ref()
+                .and_then(|path| match SecretFile::open(path) {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        warn!("Not watching the Slack webhook file for changes: {}", e);
+                        None
+                    }
+                });
+        Some(Self {
+            http_client: Client::new(),
+            webhook_url,
+            webhook_file,
+            notify_on_trade: config.notify_on_trade,
+            notify_on_error: config.notify_on_error,
+        })
+    }
+
+    pub async fn trade_copied(&self, trade: &Trade, market: Option<&MarketLabel>) {
+        if self.notify_on_trade {
+            self.post(format!("Copied {}", describe(trade, market)))
+                .await;
+        }
+    }
+
+    /// A trade observe mode would have copied; sent with trades
+    pub async fn trade_observed(&self, trade: &Trade, market: Option<&MarketLabel>) {
+        if self.notify_on_trade {
+            self.post(format!("Would copy {}", describe(trade, market)))
+                .await;
+        }
+    }
+
+    pub async fn trade_failed(&self, trade: &Trade, market: Option<&MarketLabel>, error: &str) {
+        if self.notify_on_error {
+            self.post(format!(
+                "Failed to copy {}: {}",
+                describe(trade, market),
+                error
+            ))
+            .await;
+        }
+    }
+
+    /// Operational alerts, such as a trader being suspended; sent with errors
+    pub async fn alert(&self, text: String) {
+        if self.notify_on_error {
+            self.post(text).await;
+        }
+    }
+
+    /// Scheduled reports, such as the daily P&L; always sent
+    pub async fn summary(&self, text: String) {
+        self.post(text).await;
+    }
+
+    /// Send a message; a Slack outage must never affect trading
+    async fn post(&self, text: String) {
+        let result = self
+            .http_client
+            .post(match self.webhook_file {
+                Some(ref file) => file.get(),
+                None => self.webhook_url.clone(),
+            })
+            .json(&json!({ "text": text }))
+            .send()
+            .await;
+        match result {
+            Ok(response) if !response.status().is_success() => {
+                warn!("Slack notification rejected: {}", response.status())
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to send Slack notification: {}", e),
+        }
+    }
+}
+
+/// "BUY 100 @ 0.55 (55 USDC) in *Will it rain? (Yes)* from 0x…"
+fn describe(trade: &Trade, market: Option<&MarketLabel>) -> String {
+    let market = match market {
+        Some(label) => format!("*{}*", label),
+        None => format!("market {}", trade.market_id),
+    };
+    format!(
+        "{} {} @ {} ({} USDC) in {} from {:?}",
+        trade.side, trade.size, trade.price, trade.size_usdc, market, trade.trader
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_describe_uses_market_question() {
+        let trade = Trade::sample()
+            .with_id("t1")
+            .with_market("123")
+            .with_fill(dec!(0.55), dec!(100));
+        let label = MarketLabel {
+            question: "Will it rain?".to_string(),
+            slug: "rain".to_string(),
+            outcome: Some("Yes".to_string()),
+        };
+
+        assert!(describe(&trade, Some(&label))
+            .starts_with("BUY 100 @ 0.55 (55 USDC) in *Will it rain? (Yes)*"));
+        assert!(describe(&trade, None).contains("in market 123 from"));
+    }
+}
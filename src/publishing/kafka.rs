@@ -0,0 +1,42 @@
+use crate::errors::{PolymarketError, Result};
+use crate::publishing::EventBroker;
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use std::time::Duration;
+
+/// How long a message may wait for room in the producer queue
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Kafka producer; messages are sent without a key
+pub struct KafkaBroker {
+    producer: FutureProducer,
+}
+
+impl KafkaBroker {
+    /// Connect to the comma-separated `bootstrap_servers`
+    pub fn connect(bootstrap_servers: &str) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", bootstrap_servers)
+            .set("message.timeout.ms", "30000")
+            .create()
+            .map_err(kafka_error)?;
+        Ok(Self { producer })
+    }
+}
+
+#[async_trait]
+impl EventBroker for KafkaBroker {
+    async fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<()> {
+        let record: FutureRecord<'_, (), Vec<u8>> = FutureRecord::to(topic).payload(&payload);
+        self.producer
+            .send(record, QUEUE_TIMEOUT)
+            .await
+            .map_err(|(e, _)| kafka_error(e))?;
+        Ok(())
+    }
+}
+
+fn kafka_error(e: rdkafka::error::KafkaError) -> PolymarketError {
+    PolymarketError::StorageError(format!("Kafka error: {}", e))
+}
@@ -0,0 +1,281 @@
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "nats")]
+pub mod nats;
+
+use crate::config::PublishingConfig;
+use crate::errors::Result;
+use crate::execution::lifecycle::OrderRecord;
+use crate::execution::OrderExecutor;
+use crate::monitoring::PriceCache;
+use crate::storage::{OrderStore, Stores, TradeLogEntry, TradeStore};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Message broker the event stream is published to
+#[async_trait]
+pub trait EventBroker: Send + Sync {
+    /// Publish one message to `topic` (a NATS subject or Kafka topic)
+    async fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<()>;
+}
+
+/// One message of the event stream
+#[derive(Debug, Serialize)]
+pub struct Event<'a, T: Serialize> {
+    /// "trade", "order" or "portfolio"
+    pub kind: &'static str,
+    pub published_at: DateTime<Utc>,
+    pub data: &'a T,
+}
+
+/// A position held, for portfolio snapshots
+#[derive(Debug, Serialize)]
+pub struct SnapshotPosition {
+    pub market_id: String,
+    pub shares: Decimal,
+    pub cost: Decimal,
+    /// Cached price, or the average entry price without one
+    pub price: Decimal,
+    pub value: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioSnapshot {
+    pub balance: Decimal,
+    pub positions: Vec<SnapshotPosition>,
+    /// Balance plus the positions' value
+    pub equity: Decimal,
+}
+
+/// Publishes detected trades, order updates and portfolio snapshots as JSON
+/// to `<topic_prefix>.trades`, `.orders` and `.portfolio`
+///
+/// Publishing is best effort: a broker that is down is logged and the bot
+/// carries on trading.
+pub struct EventPublisher {
+    broker: Arc<dyn EventBroker>,
+    prefix: String,
+    snapshot_interval: Duration,
+}
+
+impl EventPublisher {
+    pub fn new(broker: Arc<dyn EventBroker>, config: &PublishingConfig) -> Self {
+        Self {
+            broker,
+            prefix: config.topic_prefix.clone(),
+            snapshot_interval: Duration::from_secs(config.snapshot_interval_seconds),
+        }
+    }
+
+    /// Publisher for the configured broker, or None when publishing is off
+    pub async fn from_config(config: &PublishingConfig) -> Result<Option<Arc<Self>>> {
+        let Some(broker) = connect(config).await? else {
+            return Ok(None);
+        };
+        info!(
+            "Publishing events to {} under '{}'",
+            config.backend, config.topic_prefix
+        );
+        Ok(Some(Arc::new(Self::new(broker, config))))
+    }
+
+    /// Publish what is stored in `stores` as it is stored
+    pub fn wrap(self: &Arc<Self>, stores: Stores) -> Stores {
+        Stores {
+            trades: Arc::new(PublishingTradeStore {
+                inner: stores.trades,
+                publisher: self.clone(),
+            }),
+            orders: Arc::new(PublishingOrderStore {
+                inner: stores.orders,
+                publisher: self.clone(),
+            }),
+        }
+    }
+
+    pub async fn publish<T: Serialize>(&self, kind: &'static str, topic: &str, data: &T) {
+        let event = Event {
+            kind,
+            published_at: Utc::now(),
+            data,
+        };
+        let topic = format!("{}.{}", self.prefix, topic);
+        let result = match serde_json::to_vec(&event) {
+            Ok(payload) => self.broker.publish(&topic, payload).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            warn!("Failed to publish {} event to {}: {}", kind, topic, e);
+        }
+    }
+
+    /// Publish a portfolio snapshot every `snapshot_interval_seconds`, forever
+    pub async fn run_snapshots(
+        self: Arc<Self>,
+        executor: Arc<OrderExecutor>,
+        prices: Arc<PriceCache>,
+    ) {
+        let mut interval = tokio::time::interval(self.snapshot_interval);
+        loop {
+            interval.tick().await;
+            match snapshot(&executor, &prices).await {
+                Ok(snapshot) => self.publish("portfolio", "portfolio", &snapshot).await,
+                Err(e) => warn!("Failed to take a portfolio snapshot: {}", e),
+            }
+        }
+    }
+}
+
+/// Connect to the configured broker, if it is built in
+async fn connect(config: &PublishingConfig) -> Result<Option<Arc<dyn EventBroker>>> {
+    #[cfg(any(feature = "nats", feature = "kafka"))]
+    let url = config.url.as_deref().unwrap_or_default();
+    Ok(match config.backend.as_str() {
+        #[cfg(feature = "nats")]
+        "nats" => Some(Arc::new(nats::NatsBroker::connect(url).await?)),
+        #[cfg(feature = "kafka")]
+        "kafka" => Some(Arc::new(kafka::KafkaBroker::connect(url)?)),
+        _ => None,
+    })
+}
+
+/// Balance and held positions, marked at cached prices
+async fn snapshot(executor: &OrderExecutor, prices: &PriceCache) -> Result<PortfolioSnapshot> {
+    let balance = executor.get_balance().await?;
+    let mut positions: Vec<SnapshotPosition> = executor
+        .holdings()
+        .await?
+        .into_iter()
+        .filter(|(_, holding)| holding.shares > Decimal::ZERO)
+        .map(|(market_id, holding)| {
+            let price = prices
+                .price(&market_id)
+                .unwrap_or_else(|| holding.average_price());
+            SnapshotPosition {
+                value: holding.value_at(price),
+                market_id,
+                shares: holding.shares,
+                cost: holding.cost,
+                price,
+            }
+        })
+        .collect();
+    positions.sort_by(|a, b| a.market_id.cmp(&b.market_id));
+    let equity = balance + positions.iter().map(|p| p.value).sum::<Decimal>();
+    Ok(PortfolioSnapshot {
+        balance,
+        positions,
+        equity,
+    })
+}
+
+/// Trade store that publishes each entry once it is stored
+struct PublishingTradeStore {
+    inner: Arc<dyn TradeStore>,
+    publisher: Arc<EventPublisher>,
+}
+
+#[async_trait]
+impl TradeStore for PublishingTradeStore {
+    async fn append(&self, entry: &TradeLogEntry) -> Result<()> {
+        self.inner.append(entry).await?;
+        self.publisher.publish("trade", "trades", entry).await;
+        Ok(())
+    }
+
+    async fn entries(&self) -> Result<Vec<TradeLogEntry>> {
+        self.inner.entries().await
+    }
+}
+
+/// Order store that publishes each order snapshot once it is stored
+struct PublishingOrderStore {
+    inner: Arc<dyn OrderStore>,
+    publisher: Arc<EventPublisher>,
+}
+
+#[async_trait]
+impl OrderStore for PublishingOrderStore {
+    async fn save_order(&self, order: &OrderRecord) -> Result<()> {
+        self.inner.save_order(order).await?;
+        self.publisher.publish("order", "orders", order).await;
+        Ok(())
+    }
+
+    async fn orders(&self) -> Result<Vec<OrderRecord>> {
+        self.inner.orders().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::{OrderType, Trade};
+    use crate::storage::open_stores;
+    use ethers::types::Address;
+    use rust_decimal_macros::dec;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingBroker {
+        messages: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    #[async_trait]
+    impl EventBroker for RecordingBroker {
+        async fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<()> {
+            let message = serde_json::from_slice(&payload)?;
+            self.messages
+                .lock()
+                .unwrap()
+                .push((topic.to_string(), message));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stored_trades_and_orders_are_published() {
+        let mut config = Config::load_from_file("config.example.toml").unwrap();
+        let dir = std::env::temp_dir().join(format!("publishing-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        config.logging.trade_log_path = dir.join("trades.jsonl").to_string_lossy().to_string();
+        config.logging.order_log_path = dir.join("orders.jsonl").to_string_lossy().to_string();
+        config.database.db_type = "jsonl".to_string();
+
+        let broker = Arc::new(RecordingBroker::default());
+        let publisher = Arc::new(EventPublisher::new(broker.clone(), &config.publishing));
+        let stores = publisher.wrap(open_stores(&config).await.unwrap());
+
+        let trade = Trade::sample()
+            .with_id("trade-1")
+            .with_market("111")
+            .with_trader(Address::from_low_u64_be(1))
+            .with_fill(dec!(0.5), dec!(20));
+        stores
+            .trades
+            .append(&TradeLogEntry::detected(&trade))
+            .await
+            .unwrap();
+        let order = OrderRecord::new(&trade, dec!(10), OrderType::FOK);
+        stores.orders.save_order(&order).await.unwrap();
+
+        // Stored as before
+        assert_eq!(stores.trades.entries().await.unwrap().len(), 1);
+        assert_eq!(stores.orders.orders().await.unwrap().len(), 1);
+
+        let messages = broker.messages.lock().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].0, "polycopy.trades");
+        assert_eq!(messages[0].1["kind"], "trade");
+        assert_eq!(messages[0].1["data"]["trade"]["id"], "trade-1");
+        assert_eq!(messages[1].0, "polycopy.orders");
+        assert_eq!(messages[1].1["data"]["id"], order.id);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
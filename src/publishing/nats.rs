@@ -0,0 +1,31 @@
+use crate::errors::{PolymarketError, Result};
+use crate::publishing::EventBroker;
+use async_trait::async_trait;
+
+/// Core NATS publishing; subjects are the topics as given
+pub struct NatsBroker {
+    client: async_nats::Client,
+}
+
+impl NatsBroker {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| nats_error(e.to_string()))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl EventBroker for NatsBroker {
+    async fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<()> {
+        self.client
+            .publish(topic.to_string(), payload.into())
+            .await
+            .map_err(|e| nats_error(e.to_string()))
+    }
+}
+
+fn nats_error(e: String) -> PolymarketError {
+    PolymarketError::StorageError(format!("NATS error: {}", e))
+}
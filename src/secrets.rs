@@ -0,0 +1,91 @@
+use crate::errors::{PolymarketError, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Read a secret from a file, without the trailing newline most tools add
+pub fn read_secret(path: &str) -> Result<String> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        PolymarketError::ConfigError(format!("Failed to read secret file {}: {}", path, e))
+    })?;
+    Ok(content.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// A secret mounted as a file (Docker or Kubernetes secrets) that is re-read
+/// when the file changes
+///
+/// Kubernetes rotates a mounted secret by swapping a symlink, so changes are
+/// detected by the modification time of the file the path resolves to.
+pub struct SecretFile {
+    path: PathBuf,
+    state: RwLock<(String, Option<SystemTime>)>,
+}
+
+impl SecretFile {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let value = read_secret(&path.to_string_lossy())?;
+        Ok(Self {
+            path,
+            state: RwLock::new((value, modified)),
+        })
+    }
+
+    /// The current secret, re-read first if the file has changed
+    pub fn get(&self) -> String {
+        if let Err(e) = self.refresh() {
+            warn!("Keeping the previous secret: {}", e);
+        }
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .0
+            .clone()
+    }
+
+    /// Re-read the file if it changed; returns the new secret if it differs
+    pub fn refresh(&self) -> Result<Option<String>> {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified == self.state.read().unwrap_or_else(|e| e.into_inner()).1 {
+            return Ok(None);
+        }
+        let value = read_secret(&self.path.to_string_lossy())?;
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.1 = modified;
+        if value == state.0 {
+            return Ok(None);
+        }
+        state.0 = value.clone();
+        Ok(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_file_follows_rotation() {
+        let path = std::env::temp_dir().join(format!("secret-{}", uuid::Uuid::new_v4()));
+        fs::write(&path, "first\n").unwrap();
+        let secret = SecretFile::open(&path).unwrap();
+        assert_eq!(secret.get(), "first");
+        assert_eq!(secret.refresh().unwrap(), None);
+
+        fs::write(&path, "second\n").unwrap();
+        // Coarse filesystem clocks can hide a rewrite within the same tick
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(secret.refresh().unwrap().as_deref(), Some("second"));
+        assert_eq!(secret.get(), "second");
+        let _ = fs::remove_file(&path);
+    }
+}
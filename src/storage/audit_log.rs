@@ -0,0 +1,158 @@
+use crate::errors::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Field names whose values are credentials and never written to the audit log
+const SENSITIVE_KEYS: &[&str] = &["secret", "passphrase", "private_key", "api_key", "apikey"];
+
+/// One outbound API call and what came back
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the call completed (RFC 3339)
+    pub timestamp: String,
+    /// Client operation, e.g. "place_order" or "cancel_order"
+    pub action: String,
+    pub method: String,
+    pub url: String,
+    /// POLY_TIMESTAMP sent with the authentication headers, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_timestamp: Option<u64>,
+    /// Request body with credentials redacted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Response body (parsed as JSON when possible) with credentials redacted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    /// Transport error when no response was received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl AuditEntry {
+    pub fn new(action: &str, method: &str, url: &str) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            action: action.to_string(),
+            method: method.to_string(),
+            url: url.to_string(),
+            auth_timestamp: None,
+            request: None,
+            status: None,
+            response: None,
+            error: None,
+            duration_ms: 0,
+        }
+    }
+
+    pub fn with_request(mut self, mut request: Value) -> Self {
+        redact(&mut request);
+        self.request = Some(request);
+        self
+    }
+
+    /// Record the raw response body, parsed as JSON when it is JSON
+    pub fn with_response(mut self, status: u16, body: &str) -> Self {
+        let mut response =
+            serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string()));
+        redact(&mut response);
+        self.status = Some(status);
+        self.response = Some(response);
+        self
+    }
+}
+
+/// Append-only JSONL record of every request sent to the exchange
+pub struct AuditLogger {
+    file: Mutex<File>,
+}
+
+impl AuditLogger {
+    /// Open (creating if needed) the audit log for appending
+    pub fn open(path: &str) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Append an entry and flush it to disk before returning
+    pub fn record(&self, entry: &AuditEntry) -> Result<()> {
+        let line = serde_json::to_string(entry)?;
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(file, "{}", line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Replace the values of credential-like fields, recursively
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_KEYS.iter().any(|s| key.contains(s)) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_audit_log_appends_redacted_entries() {
+        let path = "/tmp/test_audit_log.jsonl";
+        let _ = fs::remove_file(path);
+
+        let audit = AuditLogger::open(path).unwrap();
+        let mut entry = AuditEntry::new("place_order", "POST", "http://localhost/order")
+            .with_request(json!({
+                "order": {"market_id": "m1", "quantity": "10"},
+                "signature": "0xabc",
+                "creds": {"api_key": "k", "passphrase": "p"}
+            }))
+            .with_response(200, r#"{"order_id":"o1","status":"OPEN"}"#);
+        entry.auth_timestamp = Some(1_700_000_000);
+        audit.record(&entry).unwrap();
+        audit
+            .record(&AuditEntry::new(
+                "cancel_order",
+                "DELETE",
+                "http://localhost/order/o1",
+            ))
+            .unwrap();
+
+        let contents = fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let first: AuditEntry = serde_json::from_str(lines[0]).unwrap();
+        let request = first.request.unwrap();
+        assert_eq!(request["signature"], "0xabc");
+        assert_eq!(request["creds"]["api_key"], REDACTED);
+        assert_eq!(request["creds"]["passphrase"], REDACTED);
+        assert_eq!(first.response.unwrap()["order_id"], "o1");
+        assert_eq!(first.auth_timestamp, Some(1_700_000_000));
+
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,329 @@
+use crate::config::RecorderConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::monitoring::MarketDirectory;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Column names, in the order `BookSnapshot::rows` produces them
+pub const BOOK_HEADER: &[&str] = &[
+    "timestamp",
+    "token_id",
+    "best_bid",
+    "best_ask",
+    "midpoint",
+    "side",
+    "level",
+    "price",
+    "size",
+];
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct BookLevel {
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// An order book as returned by the CLOB's `/book`
+#[derive(Clone, Debug, Deserialize)]
+struct ClobBook {
+    #[serde(default)]
+    bids: Vec<BookLevel>,
+    #[serde(default)]
+    asks: Vec<BookLevel>,
+}
+
+/// One token's book at one moment, best levels first
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub token_id: String,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+}
+
+impl BookSnapshot {
+    fn from_clob(token_id: &str, book: ClobBook, depth: usize, at: DateTime<Utc>) -> Self {
+        let mut bids = book.bids;
+        let mut asks = book.asks;
+        bids.sort_by_key(|level| Reverse(level.price));
+        asks.sort_by_key(|level| level.price);
+        bids.truncate(depth);
+        asks.truncate(depth);
+        Self {
+            timestamp: at,
+            token_id: token_id.to_string(),
+            bids,
+            asks,
+        }
+    }
+
+    pub fn midpoint(&self) -> Option<Decimal> {
+        match (self.bids.first(), self.asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) / Decimal::TWO),
+            _ => None,
+        }
+    }
+
+    /// CSV rows, one per price level; a book with no levels still gets one
+    /// row so gaps in the data show up
+    pub fn rows(&self) -> Vec<String> {
+        let optional = |value: Option<Decimal>| value.map(|v| v.to_string()).unwrap_or_default();
+        let prefix = format!(
+            "{},{},{},{},{}",
+            self.timestamp.to_rfc3339(),
+            self.token_id,
+            optional(self.bids.first().map(|l| l.price)),
+            optional(self.asks.first().map(|l| l.price)),
+            optional(self.midpoint())
+        );
+        let levels = self
+            .bids
+            .iter()
+            .enumerate()
+            .map(|(i, level)| ("bid", i, level))
+            .chain(
+                self.asks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, level)| ("ask", i, level)),
+            );
+        let rows: Vec<String> = levels
+            .map(|(side, i, level)| {
+                format!("{},{},{},{},{}", prefix, side, i, level.price, level.size)
+            })
+            .collect();
+        if rows.is_empty() {
+            vec![format!("{},,,,", prefix)]
+        } else {
+            rows
+        }
+    }
+}
+
+/// Snapshots the order books of a fixed set of markets into daily CSV files
+///
+/// Polymarket has no historical books, so this builds a dataset for fitting
+/// the slippage model and for latency-aware backtests. Files are named
+/// `books-YYYYMMDD.csv` (UTC) and appended to, so a restart continues the
+/// day's file.
+pub struct BookRecorder {
+    http_client: Client,
+    api_url: String,
+    config: RecorderConfig,
+    /// Outcome tokens to record
+    tokens: Vec<String>,
+}
+
+impl BookRecorder {
+    /// Resolve the configured markets to their outcome tokens
+    ///
+    /// Entries can be token ids, condition ids (`0x...`) or market slugs;
+    /// markets contribute every outcome token.
+    pub async fn new(
+        config: RecorderConfig,
+        api_url: String,
+        markets: &MarketDirectory,
+    ) -> Result<Self> {
+        let mut tokens = Vec::new();
+        for market in &config.markets {
+            if market.chars().all(|c| c.is_ascii_digit()) {
+                tokens.push(market.clone());
+                continue;
+            }
+            let info = markets.find(market).await?;
+            info!(
+                "Recording {} ({} outcomes)",
+                info.question,
+                info.tokens.len()
+            );
+            tokens.extend(info.tokens.iter().map(|t| t.token_id.clone()));
+        }
+        tokens.dedup();
+        Ok(Self {
+            http_client: Client::new(),
+            api_url,
+            config,
+            tokens,
+        })
+    }
+
+    /// Snapshot every `interval_seconds` until the process exits
+    pub async fn run(self) -> Result<()> {
+        info!(
+            "Recording {} order books every {}s into {}",
+            self.tokens.len(),
+            self.config.interval_seconds,
+            self.config.directory
+        );
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_seconds));
+        loop {
+            interval.tick().await;
+            let now = Utc::now();
+            let mut rows = Vec::new();
+            for token_id in &self.tokens {
+                match self.fetch(token_id, now).await {
+                    Ok(snapshot) => rows.extend(snapshot.rows()),
+                    Err(e) => warn!("Failed to snapshot the book of {}: {}", token_id, e),
+                }
+            }
+            let path = append_rows(Path::new(&self.config.directory), now, &rows)?;
+            info!("Recorded {} rows to {}", rows.len(), path.display());
+        }
+    }
+
+    async fn fetch(&self, token_id: &str, at: DateTime<Utc>) -> Result<BookSnapshot> {
+        let response = self
+            .http_client
+            .get(format!("{}/book", self.api_url))
+            .query(&[("token_id", token_id)])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(PolymarketError::ApiError(format!(
+                "Failed to fetch order book: {}",
+                response.status()
+            )));
+        }
+        let book: ClobBook = response.json().await?;
+        Ok(BookSnapshot::from_clob(
+            token_id,
+            book,
+            self.config.depth,
+            at,
+        ))
+    }
+}
+
+/// Append `rows` to the day's file in `directory`, writing the header first
+/// when the file is new
+fn append_rows(directory: &Path, at: DateTime<Utc>, rows: &[String]) -> Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+    let path = directory.join(format!("books-{}.csv", at.format("%Y%m%d")));
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let mut content = String::new();
+    if is_new {
+        content.push_str(&BOOK_HEADER.join(","));
+        content.push('\n');
+    }
+    for row in rows {
+        content.push_str(row);
+        content.push('\n');
+    }
+    file.write_all(content.as_bytes())?;
+    Ok(path)
+}
+
+/// Snapshots from the `books-*.csv` files in `directory`, in file order
+pub fn read_books(directory: &Path) -> Result<Vec<BookSnapshot>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            name.starts_with("books-") && name.ends_with(".csv")
+        })
+        .collect();
+    paths.sort();
+
+    let mut snapshots: Vec<BookSnapshot> = Vec::new();
+    for path in paths {
+        let content = fs::read_to_string(&path)?;
+        for (i, line) in content.lines().enumerate().skip(1) {
+            let invalid = |what: &str| {
+                PolymarketError::ParseError(format!(
+                    "{}:{}: invalid {}",
+                    path.display(),
+                    i + 1,
+                    what
+                ))
+            };
+            let fields: Vec<&str> = line.split(',').collect();
+            if fields.len() != BOOK_HEADER.len() {
+                return Err(invalid("row"));
+            }
+            let timestamp = DateTime::parse_from_rfc3339(fields[0])
+                .map_err(|_| invalid("timestamp"))?
+                .with_timezone(&Utc);
+            let token_id = fields[1];
+            let continues = snapshots
+                .last()
+                .is_some_and(|s| s.timestamp == timestamp && s.token_id == token_id);
+            if !continues {
+                snapshots.push(BookSnapshot {
+                    timestamp,
+                    token_id: token_id.to_string(),
+                    bids: Vec::new(),
+                    asks: Vec::new(),
+                });
+            }
+            if fields[5].is_empty() {
+                continue;
+            }
+            let level = BookLevel {
+                price: fields[7].parse().map_err(|_| invalid("price"))?,
+                size: fields[8].parse().map_err(|_| invalid("size"))?,
+            };
+            let snapshot = snapshots.last_mut().expect("pushed above");
+            match fields[5] {
+                "bid" => snapshot.bids.push(level),
+                "ask" => snapshot.asks.push(level),
+                _ => return Err(invalid("side")),
+            }
+        }
+    }
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_snapshot_rows_best_levels_first() {
+        let body = r#"{"market":"0xabc","asset_id":"111","timestamp":"1700000000000",
+            "bids":[{"price":"0.46","size":"500"},{"price":"0.48","size":"120"}],
+            "asks":[{"price":"0.55","size":"90"},{"price":"0.52","size":"300"}]}"#;
+        let book: ClobBook = serde_json::from_str(body).unwrap();
+        let at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let snapshot = BookSnapshot::from_clob("111", book, 1, at);
+
+        assert_eq!(snapshot.midpoint(), Some(dec!(0.50)));
+        assert_eq!(
+            snapshot.rows(),
+            vec![
+                "2023-11-14T22:13:20+00:00,111,0.48,0.52,0.50,bid,0,0.48,120",
+                "2023-11-14T22:13:20+00:00,111,0.48,0.52,0.50,ask,0,0.52,300",
+            ]
+        );
+
+        let empty = BookSnapshot::from_clob(
+            "222",
+            ClobBook {
+                bids: vec![],
+                asks: vec![],
+            },
+            5,
+            at,
+        );
+        assert_eq!(empty.rows(), vec!["2023-11-14T22:13:20+00:00,222,,,,,,,"]);
+
+        let dir = std::env::temp_dir().join(format!("books-{}", uuid::Uuid::new_v4()));
+        let mut rows = snapshot.rows();
+        rows.extend(empty.rows());
+        append_rows(&dir, at, &rows).unwrap();
+        assert_eq!(read_books(&dir).unwrap(), vec![snapshot, empty]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,391 @@
+use crate::config::DatabaseConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::execution::lifecycle::OrderRecord;
+use crate::storage::schema;
+use crate::storage::trade_log::TradeLogEntry;
+use crate::storage::{OrderStore, TradeStore};
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::any::{install_default_drivers, AnyPoolOptions};
+use sqlx::{AnyPool, Row};
+use tracing::info;
+
+/// Arbitrary key for the advisory lock that serializes migrations across instances
+const MIGRATION_LOCK_KEY: i64 = 0x706f_6c79_636f_7079;
+
+/// Which SQL dialect the pool speaks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+}
+
+/// One schema change, written once per dialect
+struct Migration {
+    version: i64,
+    description: &'static str,
+    sqlite: &'static [&'static str],
+    postgres: &'static [&'static str],
+}
+
+/// Applied in order; never edit a migration once released, add a new one
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create trade_log",
+        sqlite: &[
+            "CREATE TABLE trade_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            logged_at TEXT NOT NULL,
+            trade_id TEXT NOT NULL,
+            trader TEXT NOT NULL,
+            market_id TEXT NOT NULL,
+            success BOOLEAN NOT NULL,
+            error TEXT,
+            schema_version INTEGER NOT NULL,
+            entry TEXT NOT NULL
+        )",
+            "CREATE INDEX trade_log_logged_at_idx ON trade_log (logged_at)",
+            "CREATE INDEX trade_log_trader_idx ON trade_log (trader)",
+        ],
+        postgres: &[
+            "CREATE TABLE trade_log (
+            id BIGSERIAL PRIMARY KEY,
+            logged_at TIMESTAMPTZ NOT NULL,
+            trade_id TEXT NOT NULL,
+            trader TEXT NOT NULL,
+            market_id TEXT NOT NULL,
+            success BOOLEAN NOT NULL,
+            error TEXT,
+            schema_version INTEGER NOT NULL,
+            entry JSONB NOT NULL
+        )",
+            "CREATE INDEX trade_log_logged_at_idx ON trade_log (logged_at)",
+            "CREATE INDEX trade_log_trader_idx ON trade_log (trader)",
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "create orders",
+        sqlite: &[
+            "CREATE TABLE orders (
+            id TEXT PRIMARY KEY,
+            trade_id TEXT NOT NULL,
+            exchange_order_id TEXT,
+            market_id TEXT NOT NULL,
+            state TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            record TEXT NOT NULL
+        )",
+            "CREATE INDEX orders_state_idx ON orders (state)",
+            "CREATE INDEX orders_trade_id_idx ON orders (trade_id)",
+        ],
+        postgres: &[
+            "CREATE TABLE orders (
+            id TEXT PRIMARY KEY,
+            trade_id TEXT NOT NULL,
+            exchange_order_id TEXT,
+            market_id TEXT NOT NULL,
+            state TEXT NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL,
+            record JSONB NOT NULL
+        )",
+            "CREATE INDEX orders_state_idx ON orders (state)",
+            "CREATE INDEX orders_trade_id_idx ON orders (trade_id)",
+        ],
+    },
+];
+
+/// Trade store backed by a pooled SQLite or PostgreSQL connection
+pub struct SqlStore {
+    pool: AnyPool,
+    backend: Backend,
+}
+
+impl SqlStore {
+    /// Connect using `[database]` settings and bring the schema up to date
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self> {
+        let (backend, url) = match config.db_type.as_str() {
+            "sqlite" => (Backend::Sqlite, sqlite_url(&config.db_connection)),
+            "postgres" => (Backend::Postgres, config.db_connection.clone()),
+            other => {
+                return Err(PolymarketError::ConfigError(format!(
+                    "db_type '{}' is not a SQL backend",
+                    other
+                )))
+            }
+        };
+
+        install_default_drivers();
+        let pool = AnyPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&url)
+            .await?;
+
+        let store = Self { pool, backend };
+        let applied = store.migrate().await?;
+        if applied > 0 {
+            info!("Applied {} database migration(s)", applied);
+        }
+        Ok(store)
+    }
+
+    /// Apply pending migrations, returning how many ran
+    pub async fn migrate(&self) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+
+        // Several bot instances may start against the same database at once
+        if self.backend == Backend::Postgres {
+            sqlx::query("SELECT pg_advisory_xact_lock($1)")
+                .bind(MIGRATION_LOCK_KEY)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at TEXT NOT NULL
+            )",
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let current: i64 =
+            sqlx::query("SELECT CAST(COALESCE(MAX(version), 0) AS BIGINT) FROM schema_migrations")
+                .fetch_one(&mut *tx)
+                .await?
+                .try_get(0)?;
+
+        let mut applied = 0;
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            let statements = match self.backend {
+                Backend::Sqlite => migration.sqlite,
+                Backend::Postgres => migration.postgres,
+            };
+            for statement in statements {
+                sqlx::query(statement).execute(&mut *tx).await?;
+            }
+            let record = match self.backend {
+                Backend::Sqlite => {
+                    "INSERT INTO schema_migrations (version, description, applied_at)
+                     VALUES (?, ?, ?)"
+                }
+                Backend::Postgres => {
+                    "INSERT INTO schema_migrations (version, description, applied_at)
+                     VALUES ($1, $2, $3)"
+                }
+            };
+            sqlx::query(record)
+                .bind(migration.version)
+                .bind(migration.description)
+                .bind(Utc::now().to_rfc3339())
+                .execute(&mut *tx)
+                .await?;
+            applied += 1;
+        }
+
+        tx.commit().await?;
+        Ok(applied)
+    }
+}
+
+#[async_trait]
+impl TradeStore for SqlStore {
+    async fn append(&self, entry: &TradeLogEntry) -> Result<()> {
+        let query = match self.backend {
+            Backend::Sqlite => {
+                "INSERT INTO trade_log
+                    (logged_at, trade_id, trader, market_id, success, error, schema_version, entry)
+                 VALUES (?, ?, ?, ?, ?, NULLIF(?, ''), ?, ?)"
+            }
+            Backend::Postgres => {
+                "INSERT INTO trade_log
+                    (logged_at, trade_id, trader, market_id, success, error, schema_version, entry)
+                 VALUES ($1::timestamptz, $2, $3, $4, $5, NULLIF($6, ''), $7, $8::jsonb)"
+            }
+        };
+
+        sqlx::query(query)
+            .bind(entry.timestamp.clone())
+            .bind(entry.trade.id.clone())
+            .bind(format!("{:?}", entry.trade.trader))
+            .bind(entry.trade.market_id.clone())
+            .bind(entry.success)
+            // The Any driver can't switch a parameter between NULL and text on a
+            // cached statement, so send "" and let the database map it to NULL
+            .bind(entry.error.clone().unwrap_or_default())
+            .bind(entry.schema_version as i32)
+            .bind(serde_json::to_string(entry)?)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn entries(&self) -> Result<Vec<TradeLogEntry>> {
+        let query = match self.backend {
+            Backend::Sqlite => "SELECT entry FROM trade_log ORDER BY id",
+            Backend::Postgres => "SELECT entry::text FROM trade_log ORDER BY id",
+        };
+
+        sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| schema::parse_entry(&row.try_get::<String, _>(0)?))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OrderStore for SqlStore {
+    async fn save_order(&self, order: &OrderRecord) -> Result<()> {
+        let query = match self.backend {
+            Backend::Sqlite => {
+                "INSERT INTO orders
+                    (id, trade_id, exchange_order_id, market_id, state, created_at, updated_at,
+                     record)
+                 VALUES (?, ?, NULLIF(?, ''), ?, ?, ?, ?, ?)
+                 ON CONFLICT (id) DO UPDATE SET
+                    exchange_order_id = excluded.exchange_order_id,
+                    state = excluded.state,
+                    updated_at = excluded.updated_at,
+                    record = excluded.record"
+            }
+            Backend::Postgres => {
+                "INSERT INTO orders
+                    (id, trade_id, exchange_order_id, market_id, state, created_at, updated_at,
+                     record)
+                 VALUES ($1, $2, NULLIF($3, ''), $4, $5, $6::timestamptz, $7::timestamptz,
+                         $8::jsonb)
+                 ON CONFLICT (id) DO UPDATE SET
+                    exchange_order_id = excluded.exchange_order_id,
+                    state = excluded.state,
+                    updated_at = excluded.updated_at,
+                    record = excluded.record"
+            }
+        };
+
+        sqlx::query(query)
+            .bind(order.id.clone())
+            .bind(order.trade_id.clone())
+            .bind(order.exchange_order_id.clone().unwrap_or_default())
+            .bind(order.market_id.clone())
+            .bind(order.state.as_str())
+            .bind(order.created_at.to_rfc3339())
+            .bind(order.updated_at.to_rfc3339())
+            .bind(serde_json::to_string(order)?)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn orders(&self) -> Result<Vec<OrderRecord>> {
+        let query = match self.backend {
+            Backend::Sqlite => "SELECT record FROM orders ORDER BY created_at, id",
+            Backend::Postgres => "SELECT record::text FROM orders ORDER BY created_at, id",
+        };
+
+        sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| Ok(serde_json::from_str(&row.try_get::<String, _>(0)?)?))
+            .collect()
+    }
+}
+
+/// Accept a bare file path for sqlite, creating the database if needed
+fn sqlite_url(connection: &str) -> String {
+    if connection.starts_with("sqlite:") {
+        connection.to_string()
+    } else {
+        format!("sqlite://{}?mode=rwc", connection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::lifecycle::OrderState;
+    use crate::models::{ExecutedTrade, OrderSide, OrderType, Position, Trade};
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_sqlite_store_round_trip_and_migrations() {
+        let path = "/tmp/test_sql_store.db";
+        let _ = std::fs::remove_file(path);
+        let config = DatabaseConfig {
+            db_type: "sqlite".to_string(),
+            db_connection: path.to_string(),
+            db_connection_file: None,
+            max_connections: 2,
+        };
+
+        let trade = Trade::sample().with_id("t1").with_fill(dec!(0.5), dec!(10));
+        let executed = ExecutedTrade {
+            position: Position {
+                market_id: "m1".to_string(),
+                entry_price: dec!(0.51),
+                size: dec!(10),
+                side: OrderSide::Buy,
+                timestamp: Utc::now(),
+                pnl: dec!(0),
+                trader: None,
+            },
+            actual_price: dec!(0.51),
+            slippage: dec!(0.01),
+            fee: dec!(0),
+            fill_ratio: dec!(1),
+        };
+
+        let store = SqlStore::connect(&config).await.unwrap();
+        store
+            .append(&TradeLogEntry::detected(&trade))
+            .await
+            .unwrap();
+        store
+            .append(&TradeLogEntry::executed(&trade, &executed))
+            .await
+            .unwrap();
+        store
+            .append(&TradeLogEntry::failed(&trade, "API error: rejected"))
+            .await
+            .unwrap();
+
+        let entries = store.entries().await.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[1].success);
+        assert_eq!(
+            entries[1].executed.as_ref().unwrap().actual_price,
+            dec!(0.51)
+        );
+        assert_eq!(entries[2].error.as_deref(), Some("API error: rejected"));
+
+        // Orders are upserted, so only the latest snapshot is kept
+        let mut order = OrderRecord::new(&trade, dec!(10), OrderType::GTC);
+        store.save_order(&order).await.unwrap();
+        order.advance(OrderState::Submitted, None).unwrap();
+        order.exchange_order_id = Some("0xabc".to_string());
+        order.advance(OrderState::Filled, None).unwrap();
+        store.save_order(&order).await.unwrap();
+        let orders = store.orders().await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].state, OrderState::Filled);
+        assert_eq!(orders[0].exchange_order_id.as_deref(), Some("0xabc"));
+
+        // Reconnecting finds the schema current and keeps existing rows
+        drop(store);
+        let store = SqlStore::connect(&config).await.unwrap();
+        assert_eq!(store.migrate().await.unwrap(), 0);
+        assert_eq!(store.entries().await.unwrap().len(), 3);
+        assert_eq!(store.orders().await.unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
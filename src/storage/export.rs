@@ -0,0 +1,265 @@
+use crate::config::ExportConfig;
+use crate::errors::Result;
+use crate::storage::sheets::SheetsClient;
+use crate::storage::trade_log::TradeLogEntry;
+use crate::storage::TradeStore;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Column names, in the order `ExportRow::fields` produces them
+pub const EXPORT_HEADER: &[&str] = &[
+    "timestamp",
+    "trader",
+    "market_id",
+    "market",
+    "side",
+    "trader_size_usdc",
+    "price",
+    "size",
+    "cost_usdc",
+    "slippage",
+    "fee",
+    "pnl",
+    "cumulative_pnl",
+    "labels",
+];
+
+/// One executed copy trade, flattened for spreadsheets
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportRow {
+    pub timestamp: String,
+    pub trader: String,
+    pub market_id: String,
+    /// Question and outcome, empty when the market couldn't be looked up
+    pub market: String,
+    pub side: String,
+    pub trader_size_usdc: Decimal,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub cost_usdc: Decimal,
+    pub slippage: Decimal,
+    pub fee: Decimal,
+    pub pnl: Decimal,
+    pub cumulative_pnl: Decimal,
+    /// The trade's labels, separated by semicolons
+    pub labels: String,
+}
+
+impl ExportRow {
+    pub fn fields(&self) -> Vec<String> {
+        vec![
+            self.timestamp.clone(),
+            self.trader.clone(),
+            self.market_id.clone(),
+            self.market.clone(),
+            self.side.clone(),
+            self.trader_size_usdc.to_string(),
+            self.price.to_string(),
+            self.size.to_string(),
+            self.cost_usdc.to_string(),
+            self.slippage.to_string(),
+            self.fee.to_string(),
+            self.pnl.to_string(),
+            self.cumulative_pnl.to_string(),
+            self.labels.clone(),
+        ]
+    }
+}
+
+/// Rows for every executed entry, in log order, with a running P&L total
+pub fn export_rows(entries: &[TradeLogEntry]) -> Vec<ExportRow> {
+    let mut cumulative_pnl = Decimal::ZERO;
+    entries
+        .iter()
+        .filter(|entry| entry.success)
+        .filter_map(|entry| {
+            let executed = entry.executed.as_ref()?;
+            cumulative_pnl += executed.position.pnl;
+            Some(ExportRow {
+                timestamp: entry.timestamp.clone(),
+                trader: format!("{:?}", entry.trade.trader),
+                market_id: entry.trade.market_id.clone(),
+                market: entry
+                    .market
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                side: entry.trade.side.to_string(),
+                trader_size_usdc: entry.trade.size_usdc,
+                price: executed.actual_price,
+                size: executed.position.size,
+                cost_usdc: executed.actual_price * executed.position.size,
+                slippage: executed.slippage,
+                fee: executed.fee,
+                pnl: executed.position.pnl,
+                cumulative_pnl,
+                labels: entry.trade.labels.join(";"),
+            })
+        })
+        .collect()
+}
+
+/// Render rows as CSV, header first
+pub fn to_csv(rows: &[ExportRow]) -> String {
+    let mut csv = EXPORT_HEADER.join(",");
+    csv.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.fields().iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a field if it contains a delimiter, quote or newline
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write `trades-YYYYMMDD-HHMMSS.csv` into `directory`, creating it if needed
+pub fn write_csv(rows: &[ExportRow], directory: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+    let path = directory.join(format!("trades-{}.csv", Utc::now().format("%Y%m%d-%H%M%S")));
+    fs::write(&path, to_csv(rows))?;
+    Ok(path)
+}
+
+/// Where one export run went
+#[derive(Debug)]
+pub struct ExportSummary {
+    pub rows: usize,
+    pub csv_path: PathBuf,
+    pub pushed_to_sheet: bool,
+}
+
+/// Dumps executed trades to CSV and, when configured, a Google Sheet
+pub struct Exporter {
+    store: Arc<dyn TradeStore>,
+    directory: PathBuf,
+    interval: Duration,
+    sheets: Option<SheetsClient>,
+}
+
+impl Exporter {
+    pub fn new(store: Arc<dyn TradeStore>, config: &ExportConfig) -> Result<Self> {
+        let sheets = match (&config.google_sheet_id, &config.google_credentials_path) {
+            (Some(sheet_id), Some(credentials)) => Some(SheetsClient::from_key_file(
+                credentials,
+                sheet_id.clone(),
+                config.sheet_name.clone(),
+            )?),
+            _ => None,
+        };
+
+        Ok(Self {
+            store,
+            directory: PathBuf::from(&config.directory),
+            interval: Duration::from_secs(config.interval_hours * 3600),
+            sheets,
+        })
+    }
+
+    /// Write CSVs somewhere other than the configured directory
+    pub fn with_directory(mut self, directory: PathBuf) -> Self {
+        self.directory = directory;
+        self
+    }
+
+    /// Skip the Google Sheet even if one is configured
+    pub fn without_sheets(mut self) -> Self {
+        self.sheets = None;
+        self
+    }
+
+    /// Export the full trade history once
+    pub async fn run_once(&self) -> Result<ExportSummary> {
+        let entries = self.store.entries().await?;
+        let rows = export_rows(&entries);
+        let csv_path = write_csv(&rows, &self.directory)?;
+
+        if let Some(ref sheets) = self.sheets {
+            sheets.replace_rows(EXPORT_HEADER, &rows).await?;
+        }
+
+        Ok(ExportSummary {
+            rows: rows.len(),
+            csv_path,
+            pushed_to_sheet: self.sheets.is_some(),
+        })
+    }
+
+    /// Export on the configured interval until the task is dropped
+    pub async fn run_periodic(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            match self.run_once().await {
+                Ok(summary) => info!(
+                    "Exported {} trades to {}",
+                    summary.rows,
+                    summary.csv_path.display()
+                ),
+                Err(e) => error!("Trade export failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ExecutedTrade, OrderSide, Position, Trade};
+    use rust_decimal_macros::dec;
+
+    fn executed_entry(market: &str, pnl: Decimal) -> TradeLogEntry {
+        let trade = Trade::sample()
+            .with_id(&format!("{}-trade", market))
+            .with_market(market)
+            .with_fill(dec!(0.5), dec!(20));
+        let executed = ExecutedTrade {
+            position: Position {
+                market_id: market.to_string(),
+                entry_price: dec!(0.5),
+                size: dec!(4),
+                side: OrderSide::Buy,
+                timestamp: Utc::now(),
+                pnl,
+                trader: None,
+            },
+            actual_price: dec!(0.5),
+            slippage: dec!(0.002),
+            fee: dec!(0),
+            fill_ratio: dec!(1),
+        };
+        TradeLogEntry::executed(&trade, &executed)
+    }
+
+    #[test]
+    fn test_export_rows_and_csv() {
+        let entries = vec![
+            executed_entry("m1", dec!(1.5)),
+            TradeLogEntry::failed(&executed_entry("m2", dec!(0)).trade, "API error"),
+            executed_entry("market, with comma", dec!(-0.5)),
+        ];
+
+        let rows = export_rows(&entries);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].cost_usdc, dec!(2.0));
+        assert_eq!(rows[1].cumulative_pnl, dec!(1.0));
+
+        let csv = to_csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], EXPORT_HEADER.join(","));
+        assert!(lines[2].contains("\"market, with comma\""));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Gas paid for one on-chain transaction
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GasEntry {
+    pub timestamp: DateTime<Utc>,
+    /// What the transaction did, e.g. "approve USDC for the CTF Exchange"
+    pub operation: String,
+    pub tx_hash: String,
+    pub gas_used: u64,
+    pub gas_price_gwei: Decimal,
+    pub cost_pol: Decimal,
+    /// None when no POL price was available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<Decimal>,
+    /// Reverted transactions still pay for their gas
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reverted: bool,
+}
+
+/// Gas spent over a set of transactions
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GasSpent {
+    pub transactions: usize,
+    pub pol: Decimal,
+    /// Over the transactions with a USD cost
+    pub usd: Decimal,
+}
+
+impl GasSpent {
+    pub fn of(entries: &[GasEntry]) -> Self {
+        Self {
+            transactions: entries.len(),
+            pol: entries.iter().map(|e| e.cost_pol).sum(),
+            usd: entries.iter().filter_map(|e| e.cost_usd).sum(),
+        }
+    }
+}
+
+/// Append-only JSONL record of gas paid
+pub struct GasLog {
+    path: String,
+    write_lock: Mutex<()>,
+}
+
+impl GasLog {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn append(&self, entry: &GasEntry) -> Result<()> {
+        let line = serde_json::to_string(entry)?;
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Every entry, oldest first; unreadable lines are skipped
+    pub fn entries(&self) -> Result<Vec<GasEntry>> {
+        if !Path::new(&self.path).exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for line in BufReader::new(File::open(&self.path)?).lines() {
+            if let Ok(entry) = serde_json::from_str(&line?) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn spent(&self) -> Result<GasSpent> {
+        Ok(GasSpent::of(&self.entries()?))
+    }
+}
@@ -1,3 +1,70 @@
+pub mod audit_log;
+pub mod book_recorder;
+#[cfg(feature = "database")]
+pub mod database;
+pub mod export;
+pub mod gas_log;
+pub mod order_journal;
+pub mod query;
+pub mod schema;
+pub mod sheets;
 pub mod trade_log;
 
-pub use trade_log::TradeLogger;
+pub use trade_log::{TradeLogEntry, TradeLogger};
+
+use crate::config::Config;
+use crate::errors::Result;
+use crate::execution::lifecycle::OrderRecord;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Persistent record of detected and copied trades
+#[async_trait]
+pub trait TradeStore: Send + Sync {
+    /// Persist one entry
+    async fn append(&self, entry: &TradeLogEntry) -> Result<()>;
+
+    /// Every stored entry, oldest first, upgraded to the current schema version
+    async fn entries(&self) -> Result<Vec<TradeLogEntry>>;
+}
+
+/// Persistent order lifecycle records
+#[async_trait]
+pub trait OrderStore: Send + Sync {
+    /// Insert or replace the order's current snapshot
+    async fn save_order(&self, order: &OrderRecord) -> Result<()>;
+
+    /// Latest snapshot of every order, oldest first
+    async fn orders(&self) -> Result<Vec<OrderRecord>>;
+}
+
+/// Trade and order storage backed by the same database or directory
+pub struct Stores {
+    pub trades: Arc<dyn TradeStore>,
+    pub orders: Arc<dyn OrderStore>,
+}
+
+/// Open the stores selected by `[database] db_type`
+pub async fn open_stores(config: &Config) -> Result<Stores> {
+    match config.database.db_type.as_str() {
+        #[cfg(feature = "database")]
+        "sqlite" | "postgres" => {
+            let store = Arc::new(database::SqlStore::connect(&config.database).await?);
+            Ok(Stores {
+                trades: store.clone(),
+                orders: store,
+            })
+        }
+        _ => Ok(Stores {
+            trades: Arc::new(TradeLogger::from_config(&config.logging)),
+            orders: Arc::new(order_journal::OrderJournal::new(
+                config.logging.order_log_path.clone(),
+            )),
+        }),
+    }
+}
+
+/// Open the trade store selected by `[database] db_type`
+pub async fn open_store(config: &Config) -> Result<Arc<dyn TradeStore>> {
+    Ok(open_stores(config).await?.trades)
+}
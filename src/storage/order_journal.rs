@@ -0,0 +1,115 @@
+use crate::errors::Result;
+use crate::execution::lifecycle::OrderRecord;
+use crate::storage::OrderStore;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Append-only JSONL journal of order snapshots; the last line per order wins
+pub struct OrderJournal {
+    path: String,
+    write_lock: Mutex<()>,
+}
+
+impl OrderJournal {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait]
+impl OrderStore for OrderJournal {
+    async fn save_order(&self, order: &OrderRecord) -> Result<()> {
+        let line = serde_json::to_string(order)?;
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        // A crash right after this must still find the order
+        file.sync_data()?;
+        Ok(())
+    }
+
+    async fn orders(&self) -> Result<Vec<OrderRecord>> {
+        if !Path::new(&self.path).exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut latest: Vec<OrderRecord> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut skipped = 0;
+        for line in BufReader::new(File::open(&self.path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A torn final line from a crash shouldn't hide every other order
+            let order: OrderRecord = match serde_json::from_str(&line) {
+                Ok(order) => order,
+                Err(_) => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+            match index.get(&order.id) {
+                Some(&i) => latest[i] = order,
+                None => {
+                    index.insert(order.id.clone(), latest.len());
+                    latest.push(order);
+                }
+            }
+        }
+        if skipped > 0 {
+            warn!("Skipped {} unreadable lines in {}", skipped, self.path);
+        }
+
+        Ok(latest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::lifecycle::OrderState;
+    use crate::models::{OrderType, Trade};
+    use rust_decimal_macros::dec;
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_journal_keeps_latest_snapshot() {
+        let path = "/tmp/test_order_journal.jsonl";
+        let _ = fs::remove_file(path);
+        let journal = OrderJournal::new(path.to_string());
+
+        let trade = Trade::sample().with_id("t1");
+        let mut first = OrderRecord::new(&trade, dec!(10), OrderType::GTC);
+        let second = OrderRecord::new(&trade, dec!(5), OrderType::FOK);
+        journal.save_order(&first).await.unwrap();
+        journal.save_order(&second).await.unwrap();
+        first.advance(OrderState::Submitted, None).unwrap();
+        first.advance(OrderState::Open, None).unwrap();
+        journal.save_order(&first).await.unwrap();
+
+        // Torn write from a crash
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        write!(file, "{{\"id\":\"trunc").unwrap();
+
+        let orders = journal.orders().await.unwrap();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].id, first.id);
+        assert_eq!(orders[0].state, OrderState::Open);
+        assert_eq!(orders[0].transitions.len(), 3);
+        assert_eq!(orders[1].state, OrderState::Created);
+
+        let _ = fs::remove_file(path);
+    }
+}
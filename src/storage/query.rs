@@ -0,0 +1,343 @@
+use crate::storage::trade_log::TradeLogEntry;
+use chrono::{DateTime, Utc};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Outcome recorded for a trade log entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryStatus {
+    /// Trade was copied successfully
+    Success,
+    /// Copy was attempted and failed
+    Failure,
+    /// Trade was deliberately not copied
+    Skipped,
+    /// Trade passed every filter in observe mode
+    Passed,
+    /// Trade was detected but no outcome has been logged for it
+    Detected,
+}
+
+impl EntryStatus {
+    pub fn of(entry: &TradeLogEntry) -> Self {
+        if entry.success {
+            EntryStatus::Success
+        } else if entry.skip_category().is_some() {
+            EntryStatus::Skipped
+        } else if entry.error.is_some() {
+            EntryStatus::Failure
+        } else if entry.passed {
+            EntryStatus::Passed
+        } else {
+            EntryStatus::Detected
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryStatus::Success => "success",
+            EntryStatus::Failure => "failure",
+            EntryStatus::Skipped => "skipped",
+            EntryStatus::Passed => "passed",
+            EntryStatus::Detected => "detected",
+        }
+    }
+}
+
+/// Filters applied to trade log entries; unset fields match everything
+#[derive(Clone, Debug, Default)]
+pub struct TradeLogQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub trader: Option<Address>,
+    pub market: Option<String>,
+    pub status: Option<EntryStatus>,
+    pub label: Option<String>,
+}
+
+impl TradeLogQuery {
+    pub fn matches(&self, entry: &TradeLogEntry) -> bool {
+        let logged_at = entry_time(entry);
+        if self.from.is_some_and(|from| logged_at < from) {
+            return false;
+        }
+        if self.to.is_some_and(|to| logged_at > to) {
+            return false;
+        }
+        if self
+            .trader
+            .is_some_and(|trader| entry.trade.trader != trader)
+        {
+            return false;
+        }
+        if self
+            .market
+            .as_ref()
+            .is_some_and(|market| entry.trade.market_id != *market)
+        {
+            return false;
+        }
+        if self
+            .status
+            .is_some_and(|status| EntryStatus::of(entry) != status)
+        {
+            return false;
+        }
+        if self
+            .label
+            .as_ref()
+            .is_some_and(|label| !entry.trade.labels.contains(label))
+        {
+            return false;
+        }
+        true
+    }
+
+    pub fn apply<'a>(&self, entries: &'a [TradeLogEntry]) -> Vec<&'a TradeLogEntry> {
+        entries.iter().filter(|entry| self.matches(entry)).collect()
+    }
+}
+
+/// When an entry was logged, falling back to the trade's own timestamp
+pub fn entry_time(entry: &TradeLogEntry) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(&entry.timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or(entry.trade.timestamp)
+}
+
+/// Error category of a failed entry: the error message up to the first ':'
+pub fn error_type(entry: &TradeLogEntry) -> Option<String> {
+    entry
+        .error
+        .as_ref()
+        .map(|error| error.split(':').next().unwrap_or(error).trim().to_string())
+}
+
+/// Totals over a set of trade log entries
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogSummary {
+    pub entries: usize,
+    pub successful: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Trades observe mode would have copied
+    pub passed: usize,
+    /// Realized P&L recorded on executed trades
+    pub total_pnl: Decimal,
+    /// Copied volume (size × fill price) of executed trades
+    pub total_volume: Decimal,
+}
+
+impl LogSummary {
+    pub fn from_entries(entries: &[&TradeLogEntry]) -> Self {
+        let mut summary = LogSummary {
+            entries: entries.len(),
+            ..Default::default()
+        };
+        for entry in entries {
+            match EntryStatus::of(entry) {
+                EntryStatus::Success => summary.successful += 1,
+                EntryStatus::Failure => summary.failed += 1,
+                EntryStatus::Skipped => summary.skipped += 1,
+                EntryStatus::Passed => summary.passed += 1,
+                EntryStatus::Detected => {}
+            }
+            if let Some(ref executed) = entry.executed {
+                summary.total_pnl += executed.position.pnl;
+                summary.total_volume += executed.position.size * executed.actual_price;
+            }
+        }
+        summary
+    }
+}
+
+/// How many entries were skipped for each reason, most common first
+pub fn skip_counts(entries: &[&TradeLogEntry]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for reason in entries.iter().filter_map(|entry| entry.skip_category()) {
+        *counts.entry(reason).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+/// Summaries keyed by `key_fn`; entries with no key are left out
+pub fn group_by<F>(entries: &[&TradeLogEntry], key_fn: F) -> BTreeMap<String, LogSummary>
+where
+    F: Fn(&TradeLogEntry) -> Option<String>,
+{
+    let mut groups: BTreeMap<String, Vec<&TradeLogEntry>> = BTreeMap::new();
+    for entry in entries {
+        if let Some(key) = key_fn(entry) {
+            groups.entry(key).or_default().push(entry);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(key, group)| (key, LogSummary::from_entries(&group)))
+        .collect()
+}
+
+/// Summaries per trade label; an entry with several labels counts in each
+pub fn group_by_label(entries: &[&TradeLogEntry]) -> BTreeMap<String, LogSummary> {
+    let mut groups: BTreeMap<String, Vec<&TradeLogEntry>> = BTreeMap::new();
+    for entry in entries {
+        for label in &entry.trade.labels {
+            groups.entry(label.clone()).or_default().push(entry);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(key, group)| (key, LogSummary::from_entries(&group)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SkipReason, Trade};
+    use rust_decimal_macros::dec;
+
+    fn entry(market: &str, success: bool, error: Option<&str>, day: u32) -> TradeLogEntry {
+        let timestamp = format!("2024-03-{:02}T12:00:00+00:00", day);
+        TradeLogEntry {
+            schema_version: crate::storage::schema::TRADE_LOG_SCHEMA_VERSION,
+            timestamp: timestamp.clone(),
+            trade: Trade::sample()
+                .with_id(&format!("{}-{}", market, day))
+                .with_market(market)
+                .with_fill(dec!(0.5), dec!(10))
+                .with_timestamp(timestamp.parse().unwrap()),
+            executed: None,
+            success,
+            error: error.map(str::to_string),
+            market: None,
+            skip_reason: None,
+            passed: false,
+            book_fill: None,
+        }
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let entries = vec![
+            entry("m1", true, None, 1),
+            entry("m1", false, Some("Order timeout"), 2),
+            entry("m2", false, Some("API error: 503"), 3),
+            entry("m2", false, None, 4),
+        ];
+
+        let query = TradeLogQuery {
+            market: Some("m2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query.apply(&entries).len(), 2);
+
+        let query = TradeLogQuery {
+            status: Some(EntryStatus::Failure),
+            from: Some("2024-03-03T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let matched = query.apply(&entries);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(error_type(matched[0]).as_deref(), Some("API error"));
+    }
+
+    #[test]
+    fn test_entries_by_label() {
+        let labelled = |day: u32, labels: &[&str]| {
+            let mut entry = entry("m1", true, None, day);
+            entry.trade.labels = labels.iter().map(|l| l.to_string()).collect();
+            entry
+        };
+        let entries = vec![
+            labelled(1, &["momentum"]),
+            labelled(2, &["momentum", "whales"]),
+            labelled(3, &[]),
+        ];
+        let query = TradeLogQuery {
+            label: Some("whales".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query.apply(&entries).len(), 1);
+
+        let groups = group_by_label(&TradeLogQuery::default().apply(&entries));
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["momentum"].entries, 2);
+        assert_eq!(groups["whales"].entries, 1);
+    }
+
+    #[test]
+    fn test_group_by_error_type() {
+        let entries = vec![
+            entry("m1", false, Some("API error: 503"), 1),
+            entry("m1", false, Some("API error: 429"), 2),
+            entry("m2", false, Some("Insufficient balance"), 3),
+            entry("m2", true, None, 4),
+        ];
+        let all = TradeLogQuery::default().apply(&entries);
+
+        let groups = group_by(&all, error_type);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["API error"].failed, 2);
+        assert_eq!(groups["Insufficient balance"].entries, 1);
+
+        let summary = LogSummary::from_entries(&all);
+        assert_eq!(summary.successful, 1);
+        assert_eq!(summary.failed, 3);
+    }
+
+    #[test]
+    fn test_skips_counted_by_reason() {
+        let trade = entry("m1", false, None, 1).trade;
+        let entries = vec![
+            TradeLogEntry::skipped(&trade, SkipReason::Paused, "copying is paused"),
+            TradeLogEntry::skipped(&trade, SkipReason::SizeLimits, "too small"),
+            TradeLogEntry::skipped(&trade, SkipReason::Paused, "copying is paused"),
+            // Logged before skip reasons were recorded
+            entry("m1", false, Some("Skipped: market is closed"), 2),
+            entry("m1", false, Some("Order timeout"), 3),
+        ];
+        let all = TradeLogQuery::default().apply(&entries);
+
+        let summary = LogSummary::from_entries(&all);
+        assert_eq!(summary.skipped, 4);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(
+            skip_counts(&all),
+            vec![
+                ("paused".to_string(), 2),
+                ("size_limits".to_string(), 1),
+                ("unclassified".to_string(), 1),
+            ]
+        );
+        assert_eq!(entries[1].error.as_deref(), Some("Skipped: too small"));
+    }
+
+    #[test]
+    fn test_observed_trades_count_as_passed() {
+        let trade = entry("m1", false, None, 1).trade;
+        let entries = vec![
+            TradeLogEntry::detected(&trade),
+            TradeLogEntry::passed(&trade, None),
+            TradeLogEntry::skipped(&trade, SkipReason::Stale, "trade is stale"),
+        ];
+        let passed = TradeLogQuery {
+            status: Some(EntryStatus::Passed),
+            ..Default::default()
+        };
+        assert_eq!(passed.apply(&entries).len(), 1);
+
+        let summary = LogSummary::from_entries(&TradeLogQuery::default().apply(&entries));
+        assert_eq!((summary.passed, summary.skipped), (1, 1));
+
+        // Only observe mode's entries carry the flag
+        let json = serde_json::to_string(&entries[0]).unwrap();
+        assert!(!json.contains("passed"));
+        let json = serde_json::to_string(&entries[1]).unwrap();
+        let read: TradeLogEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(EntryStatus::of(&read), EntryStatus::Passed);
+    }
+}
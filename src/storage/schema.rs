@@ -0,0 +1,91 @@
+use crate::errors::{PolymarketError, Result};
+use crate::storage::trade_log::TradeLogEntry;
+use serde_json::Value;
+
+/// Version of the trade log entry layout written by this build
+pub const TRADE_LOG_SCHEMA_VERSION: u32 = 2;
+
+/// Entries written before versioning existed carry no `schema_version`
+const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrade steps; `MIGRATIONS[i]` takes an entry from version `i + 1` to `i + 2`
+const MIGRATIONS: &[fn(&mut serde_json::Map<String, Value>)] = &[migrate_v1_to_v2];
+
+/// v1 -> v2: entries gain `schema_version`; a missing `error` becomes null
+fn migrate_v1_to_v2(entry: &mut serde_json::Map<String, Value>) {
+    entry.entry("error").or_insert(Value::Null);
+    entry.entry("executed").or_insert(Value::Null);
+}
+
+pub fn default_schema_version() -> u32 {
+    LEGACY_SCHEMA_VERSION
+}
+
+/// Schema version recorded in a raw entry
+pub fn version_of(entry: &serde_json::Map<String, Value>) -> Result<u32> {
+    match entry.get("schema_version") {
+        None => Ok(LEGACY_SCHEMA_VERSION),
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= LEGACY_SCHEMA_VERSION)
+            .ok_or_else(|| {
+                PolymarketError::StorageError(format!("Invalid schema_version: {}", value))
+            }),
+    }
+}
+
+/// Bring a raw entry up to the current schema version
+pub fn upgrade(mut value: Value) -> Result<Value> {
+    let entry = value.as_object_mut().ok_or_else(|| {
+        PolymarketError::StorageError("Trade log entry is not a JSON object".to_string())
+    })?;
+
+    let version = version_of(entry)?;
+    if version > TRADE_LOG_SCHEMA_VERSION {
+        return Err(PolymarketError::StorageError(format!(
+            "Entry has schema version {} but this build only understands up to {}",
+            version, TRADE_LOG_SCHEMA_VERSION
+        )));
+    }
+
+    for migration in &MIGRATIONS[(version - LEGACY_SCHEMA_VERSION) as usize..] {
+        migration(entry);
+    }
+    entry.insert(
+        "schema_version".to_string(),
+        Value::from(TRADE_LOG_SCHEMA_VERSION),
+    );
+
+    Ok(value)
+}
+
+/// Parse one JSONL line of any supported schema version
+pub fn parse_entry(line: &str) -> Result<TradeLogEntry> {
+    let value: Value = serde_json::from_str(line)?;
+    Ok(serde_json::from_value(upgrade(value)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY_LINE: &str = r#"{"timestamp":"2024-01-15T10:30:00+00:00","trade":{"id":"abc","market_id":"m1","trader":"0x0000000000000000000000000000000000000000","side":"BUY","price":"0.55","size":"10","size_usdc":"5.5","timestamp":"2024-01-15T10:29:58Z"},"success":false}"#;
+
+    #[test]
+    fn test_legacy_entry_is_upgraded() {
+        let entry = parse_entry(LEGACY_LINE).unwrap();
+        assert_eq!(entry.schema_version, TRADE_LOG_SCHEMA_VERSION);
+        assert_eq!(entry.trade.id, "abc");
+        assert!(entry.executed.is_none());
+        assert!(entry.error.is_none());
+    }
+
+    #[test]
+    fn test_future_and_corrupt_entries_rejected() {
+        let future = LEGACY_LINE.replacen('{', r#"{"schema_version":99,"#, 1);
+        assert!(parse_entry(&future).is_err());
+        assert!(parse_entry("{not json").is_err());
+        assert!(parse_entry("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::errors::{PolymarketError, Result};
+use crate::storage::export::ExportRow;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+const SHEETS_API_URL: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+/// Refresh tokens this long before Google says they expire
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// The fields we need from a Google service-account key file
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// Minimal Google Sheets client authenticated as a service account
+pub struct SheetsClient {
+    http_client: Client,
+    key: ServiceAccountKey,
+    sheet_id: String,
+    sheet_name: String,
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl SheetsClient {
+    pub fn from_key_file(path: &str, sheet_id: String, sheet_name: String) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            PolymarketError::ConfigError(format!(
+                "Failed to read Google credentials {}: {}",
+                path, e
+            ))
+        })?;
+        let key: ServiceAccountKey = serde_json::from_str(&content).map_err(|e| {
+            PolymarketError::ConfigError(format!("Invalid Google credentials {}: {}", path, e))
+        })?;
+
+        Ok(Self {
+            http_client: Client::new(),
+            key,
+            sheet_id,
+            sheet_name,
+            token: Mutex::new(None),
+        })
+    }
+
+    /// Overwrite the sheet with a header row followed by `rows`
+    pub async fn replace_rows(&self, header: &[&str], rows: &[ExportRow]) -> Result<()> {
+        let token = self.access_token().await?;
+
+        let mut values = vec![header.iter().map(|h| h.to_string()).collect::<Vec<_>>()];
+        values.extend(rows.iter().map(ExportRow::fields));
+
+        // Clear first so a shorter history doesn't leave stale rows behind
+        let clear = self.values_url(&format!("{}:clear", self.sheet_name))?;
+        let response = self
+            .http_client
+            .post(clear)
+            .bearer_auth(&token)
+            .json(&json!({}))
+            .send()
+            .await?;
+        check_response(response, "clear sheet").await?;
+
+        // RAW stores cells as given, so text like a market question starting with
+        // `=` is never evaluated as a formula
+        let mut update = self.values_url(&format!("{}!A1", self.sheet_name))?;
+        update
+            .query_pairs_mut()
+            .append_pair("valueInputOption", "RAW");
+        let response = self
+            .http_client
+            .put(update)
+            .bearer_auth(&token)
+            .json(&json!({ "values": values }))
+            .send()
+            .await?;
+        check_response(response, "update sheet").await?;
+        Ok(())
+    }
+
+    /// `.../spreadsheets/{id}/values/{range}` with the range percent-encoded
+    fn values_url(&self, range: &str) -> Result<Url> {
+        let mut url = Url::parse(SHEETS_API_URL)
+            .map_err(|e| PolymarketError::Unknown(format!("Invalid Sheets URL: {}", e)))?;
+        url.path_segments_mut()
+            .map_err(|_| PolymarketError::Unknown("Invalid Sheets URL".to_string()))?
+            .push(&self.sheet_id)
+            .push("values")
+            .push(range);
+        Ok(url)
+    }
+
+    /// Cached OAuth token, exchanging a freshly signed JWT when it is about to expire
+    async fn access_token(&self) -> Result<String> {
+        let mut cached = self.token.lock().await;
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if Instant::now() + TOKEN_REFRESH_MARGIN < *expires_at {
+                return Ok(token.clone());
+            }
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let claims = Claims {
+            iss: &self.key.client_email,
+            scope: SHEETS_SCOPE,
+            aud: &self.key.token_uri,
+            iat: now,
+            exp: now + 3600,
+        };
+        let signing_key =
+            EncodingKey::from_rsa_pem(self.key.private_key.as_bytes()).map_err(|e| {
+                PolymarketError::SigningError(format!("Invalid service account key: {}", e))
+            })?;
+        let assertion = encode(&Header::new(Algorithm::RS256), &claims, &signing_key)
+            .map_err(|e| PolymarketError::SigningError(format!("Failed to sign JWT: {}", e)))?;
+
+        let response = self
+            .http_client
+            .post(&self.key.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await?;
+        let response = check_response(response, "fetch Google access token").await?;
+        let token: TokenResponse = serde_json::from_str(&response)?;
+
+        let expires_at = Instant::now() + Duration::from_secs(token.expires_in);
+        *cached = Some((token.access_token.clone(), expires_at));
+        Ok(token.access_token)
+    }
+}
+
+/// Body of a successful response, or an API error naming the failed step
+async fn check_response(response: reqwest::Response, action: &str) -> Result<String> {
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(PolymarketError::ApiError(format!(
+            "Failed to {}: {} {}",
+            action, status, body
+        )));
+    }
+    Ok(body)
+}
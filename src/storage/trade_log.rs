@@ -1,106 +1,379 @@
-use crate::errors::Result;
-use crate::models::{ExecutedTrade, Trade};
-use chrono::Utc;
+use crate::config::LoggingConfig;
+use crate::errors::{PolymarketError, Result};
+use crate::models::{BookFill, ExecutedTrade, MarketLabel, SkipReason, Trade};
+use crate::storage::schema::{self, default_schema_version, TRADE_LOG_SCHEMA_VERSION};
+use crate::storage::TradeStore;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
-use std::io::{BufReader, BufWriter, Write};
-use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, info, warn};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TradeLogEntry {
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     pub timestamp: String,
     pub trade: Trade,
     pub executed: Option<ExecutedTrade>,
     pub success: bool,
     pub error: Option<String>,
+    /// What the market is about, when it could be looked up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<MarketLabel>,
+    /// Why the trade wasn't copied, for entries of skipped trades
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<SkipReason>,
+    /// Set in observe mode on trades every filter let through; nothing is
+    /// placed for them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub passed: bool,
+    /// For passed trades, what the trader's size would have filled walking
+    /// the live order book
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub book_fill: Option<BookFill>,
+}
+
+impl TradeLogEntry {
+    fn new(trade: &Trade, executed: Option<&ExecutedTrade>, error: Option<&str>) -> Self {
+        Self {
+            schema_version: TRADE_LOG_SCHEMA_VERSION,
+            timestamp: Utc::now().to_rfc3339(),
+            trade: trade.clone(),
+            executed: executed.cloned(),
+            success: executed.is_some(),
+            error: error.map(str::to_string),
+            market: None,
+            skip_reason: None,
+            passed: false,
+            book_fill: None,
+        }
+    }
+
+    /// Attach the market's question and outcome for human readers
+    pub fn with_market(mut self, market: Option<MarketLabel>) -> Self {
+        self.market = market;
+        self
+    }
+
+    /// The market's question when known, otherwise its id
+    pub fn market_name(&self) -> String {
+        match self.market {
+            Some(ref market) => market.to_string(),
+            None => self.trade.market_id.clone(),
+        }
+    }
+
+    /// Entry for a trade that was seen but not yet executed
+    pub fn detected(trade: &Trade) -> Self {
+        Self::new(trade, None, None)
+    }
+
+    /// Entry for a successfully executed copy trade
+    pub fn executed(trade: &Trade, executed: &ExecutedTrade) -> Self {
+        Self::new(trade, Some(executed), None)
+    }
+
+    /// Entry for a copy trade that failed to execute
+    pub fn failed(trade: &Trade, error: &str) -> Self {
+        Self::new(trade, None, Some(error))
+    }
+
+    /// Entry for a detected trade that was deliberately not copied
+    pub fn skipped(trade: &Trade, reason: SkipReason, detail: &str) -> Self {
+        let mut entry = Self::new(trade, None, Some(&format!("Skipped: {}", detail)));
+        entry.skip_reason = Some(reason);
+        entry
+    }
+
+    /// Entry for a trade observe mode would have copied, with what its size
+    /// would have filled on the book when known
+    pub fn passed(trade: &Trade, book_fill: Option<BookFill>) -> Self {
+        let mut entry = Self::new(trade, None, None);
+        entry.passed = true;
+        entry.book_fill = book_fill;
+        entry
+    }
+
+    /// Why the trade wasn't copied; entries logged before reasons were
+    /// recorded only say so in their error
+    pub fn skip_category(&self) -> Option<String> {
+        match self.skip_reason {
+            Some(reason) => Some(reason.to_string()),
+            None => self
+                .error
+                .as_ref()
+                .filter(|error| error.starts_with("Skipped:"))
+                .map(|_| "unclassified".to_string()),
+        }
+    }
+}
+
+/// When the trade log is rotated out to a timestamped file
+#[derive(Clone, Debug, Default)]
+pub struct RotationPolicy {
+    /// Rotate before a write would take the file past this many bytes
+    pub max_size_bytes: Option<u64>,
+    /// Rotate on the first write of a new UTC day
+    pub daily: bool,
+    /// Gzip rotated files
+    pub compress: bool,
+}
+
+impl RotationPolicy {
+    pub fn from_config(config: &LoggingConfig) -> Self {
+        Self {
+            max_size_bytes: (config.trade_log_max_size_mb > 0)
+                .then(|| config.trade_log_max_size_mb * 1024 * 1024),
+            daily: config.trade_log_rotate_daily,
+            compress: config.trade_log_compress,
+        }
+    }
 }
 
 pub struct TradeLogger {
     log_path: String,
+    rotation: RotationPolicy,
+    /// Fail on unreadable lines instead of skipping them
+    strict: bool,
+    /// Serializes appends and rotation across concurrent executions
+    write_lock: Mutex<()>,
+}
+
+/// Outcome of rewriting trade logs at the current schema version
+#[derive(Debug, Default, PartialEq)]
+pub struct MigrationSummary {
+    pub files_rewritten: usize,
+    pub entries_upgraded: usize,
+    /// `file:line: error` for each file left untouched because of an unparseable line
+    pub corrupt_files: Vec<String>,
 }
 
 impl TradeLogger {
     pub fn new(log_path: String) -> Self {
-        Self { log_path }
+        Self {
+            log_path,
+            rotation: RotationPolicy::default(),
+            strict: false,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Trade logger for the path, rotation and strictness configured in [logging]
+    pub fn from_config(config: &LoggingConfig) -> Self {
+        Self::new(config.trade_log_path.clone())
+            .with_rotation(RotationPolicy::from_config(config))
+            .with_strict(config.trade_log_strict)
+    }
+
+    pub fn with_rotation(mut self, rotation: RotationPolicy) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Return an error naming the file and line for corrupt entries
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
     }
 
     /// Log a detected trade
+    #[allow(dead_code)]
     pub fn log_detected_trade(&self, trade: &Trade) -> Result<()> {
-        let entry = TradeLogEntry {
-            timestamp: Utc::now().to_rfc3339(),
-            trade: trade.clone(),
-            executed: None,
-            success: false,
-            error: None,
-        };
-
-        self.write_entry(&entry)
+        self.write_entry(&TradeLogEntry::detected(trade))
     }
 
     /// Log a successfully executed trade
+    #[allow(dead_code)]
     pub fn log_executed_trade(&self, trade: &Trade, executed: &ExecutedTrade) -> Result<()> {
-        let entry = TradeLogEntry {
-            timestamp: Utc::now().to_rfc3339(),
-            trade: trade.clone(),
-            executed: Some(executed.clone()),
-            success: true,
-            error: None,
-        };
-
-        self.write_entry(&entry)
+        self.write_entry(&TradeLogEntry::executed(trade, executed))
     }
 
     /// Log a failed trade execution
+    #[allow(dead_code)]
     pub fn log_failed_trade(&self, trade: &Trade, error: &str) -> Result<()> {
-        let entry = TradeLogEntry {
-            timestamp: Utc::now().to_rfc3339(),
-            trade: trade.clone(),
-            executed: None,
-            success: false,
-            error: Some(error.to_string()),
-        };
-
-        self.write_entry(&entry)
+        self.write_entry(&TradeLogEntry::failed(trade, error))
     }
 
     /// Write an entry to the log file
     fn write_entry(&self, entry: &TradeLogEntry) -> Result<()> {
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let json = serde_json::to_string(entry)?;
+        self.rotate_if_needed(json.len() as u64 + 1)?;
+
         let file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&self.log_path)?;
 
         let mut writer = BufWriter::new(file);
-        let json = serde_json::to_string(entry)?;
         writeln!(writer, "{}", json)?;
         writer.flush()?;
 
         Ok(())
     }
 
-    /// Read all log entries
-    pub fn read_logs(&self) -> Result<Vec<TradeLogEntry>> {
-        if !Path::new(&self.log_path).exists() {
+    /// Move the current log aside if the next write would break the rotation policy
+    fn rotate_if_needed(&self, incoming_bytes: u64) -> Result<()> {
+        let metadata = match fs::metadata(&self.log_path) {
+            Ok(metadata) if metadata.len() > 0 => metadata,
+            _ => return Ok(()),
+        };
+        let last_write: DateTime<Utc> = metadata.modified()?.into();
+
+        let too_big = self
+            .rotation
+            .max_size_bytes
+            .is_some_and(|max| metadata.len() + incoming_bytes > max);
+        let new_day = self.rotation.daily && last_write.date_naive() != Utc::now().date_naive();
+        if !too_big && !new_day {
+            return Ok(());
+        }
+
+        let rotated = self.rotated_path(last_write);
+        fs::rename(&self.log_path, &rotated)?;
+        if self.rotation.compress {
+            compress_file(&rotated)?;
+        }
+        info!("Rotated trade log to {}", rotated.display());
+
+        Ok(())
+    }
+
+    /// `trades.jsonl` -> `trades.20240301-235959.jsonl`, plus a counter on collisions
+    fn rotated_path(&self, last_write: DateTime<Utc>) -> PathBuf {
+        let path = Path::new(&self.log_path);
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("trades");
+        let stamp = last_write.format("%Y%m%d-%H%M%S");
+
+        let mut candidate = path.with_file_name(format!("{}.{}.jsonl", stem, stamp));
+        let mut counter = 1;
+        while candidate.exists() || candidate.with_extension("jsonl.gz").exists() {
+            candidate = path.with_file_name(format!("{}.{}-{}.jsonl", stem, stamp, counter));
+            counter += 1;
+        }
+        candidate
+    }
+
+    /// Rotated files for this log, oldest first
+    fn rotated_files(&self) -> Result<Vec<PathBuf>> {
+        let path = Path::new(&self.log_path);
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("trades");
+        let prefix = format!("{}.", stem);
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        if !dir.exists() {
             return Ok(Vec::new());
         }
 
-        let file = File::open(&self.log_path)?;
-        let reader = BufReader::new(file);
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|candidate| {
+                let name = candidate.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                name.starts_with(&prefix)
+                    && (name.ends_with(".jsonl") || name.ends_with(".jsonl.gz"))
+                    && candidate.file_name() != path.file_name()
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Rotated files oldest first, then the active log
+    fn log_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = self.rotated_files()?;
+        if Path::new(&self.log_path).exists() {
+            files.push(PathBuf::from(&self.log_path));
+        }
+        Ok(files)
+    }
+
+    /// Read all log entries, including rotated files, upgrading old schema versions
+    pub fn read_logs(&self) -> Result<Vec<TradeLogEntry>> {
         let mut entries = Vec::new();
+        let mut skipped = 0;
 
-        use std::io::BufRead;
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if let Ok(entry) = serde_json::from_str::<TradeLogEntry>(&line) {
-                    entries.push(entry);
+        for path in self.log_files()? {
+            for (index, line) in open_log(&path)?.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match schema::parse_entry(&line) {
+                    Ok(entry) => entries.push(entry),
+                    Err(e) if self.strict => {
+                        return Err(PolymarketError::StorageError(format!(
+                            "{}:{}: {}",
+                            path.display(),
+                            index + 1,
+                            e
+                        )));
+                    }
+                    Err(e) => {
+                        debug!("Skipping {}:{}: {}", path.display(), index + 1, e);
+                        skipped += 1;
+                    }
                 }
             }
         }
 
+        if skipped > 0 {
+            warn!(
+                "Skipped {} unreadable trade log lines (set trade_log_strict to fail instead)",
+                skipped
+            );
+        }
+
         Ok(entries)
     }
 
+    /// Rewrite every log file, rotated ones included, at the current schema version
+    ///
+    /// A file containing a line that cannot be parsed is left untouched and reported in
+    /// the summary; the remaining files are still migrated.
+    pub fn migrate(&self) -> Result<MigrationSummary> {
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut summary = MigrationSummary::default();
+
+        for path in self.log_files()? {
+            let (lines, upgraded) = match upgrade_log(&path) {
+                Ok(upgraded) => upgraded,
+                Err(PolymarketError::StorageError(reason)) => {
+                    warn!("Not migrating {}: {}", path.display(), reason);
+                    summary.corrupt_files.push(reason);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            if upgraded == 0 {
+                continue;
+            }
+            rewrite_log(&path, &lines)?;
+            info!("Upgraded {} entries in {}", upgraded, path.display());
+            summary.files_rewritten += 1;
+            summary.entries_upgraded += upgraded;
+        }
+
+        Ok(summary)
+    }
+
     /// Get trade statistics from logs
+    #[allow(dead_code)]
     pub fn get_statistics(&self) -> Result<TradeStatistics> {
         let entries = self.read_logs()?;
 
@@ -116,6 +389,108 @@ impl TradeLogger {
     }
 }
 
+#[async_trait]
+impl TradeStore for TradeLogger {
+    async fn append(&self, entry: &TradeLogEntry) -> Result<()> {
+        self.write_entry(entry)
+    }
+
+    async fn entries(&self) -> Result<Vec<TradeLogEntry>> {
+        self.read_logs()
+    }
+}
+
+fn is_gzip(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+/// Line reader over a plain or gzipped log file
+fn open_log(path: &Path) -> Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    if is_gzip(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Read a log file's lines at the current schema version, with how many were upgraded
+///
+/// An unparseable line fails with a `StorageError` naming the file and line.
+fn upgrade_log(path: &Path) -> Result<(Vec<String>, usize)> {
+    let mut lines = Vec::new();
+    let mut upgraded = 0;
+    for (index, line) in open_log(path)?.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let corrupt = |e: PolymarketError| {
+            PolymarketError::StorageError(format!("{}:{}: {}", path.display(), index + 1, e))
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&line).map_err(|e| corrupt(e.into()))?;
+        let version = value
+            .as_object()
+            .map(schema::version_of)
+            .transpose()
+            .map_err(corrupt)?;
+        if version == Some(TRADE_LOG_SCHEMA_VERSION) {
+            lines.push(line);
+            continue;
+        }
+
+        let entry = schema::parse_entry(&line).map_err(corrupt)?;
+        lines.push(serde_json::to_string(&entry)?);
+        upgraded += 1;
+    }
+    Ok((lines, upgraded))
+}
+
+/// Atomically replace a (possibly gzipped) log file with `lines`
+fn rewrite_log(path: &Path, lines: &[String]) -> Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+
+    let file = File::create(&tmp)?;
+    let mut writer: Box<dyn Write> = if is_gzip(path) {
+        Box::new(GzEncoder::new(file, Compression::default()))
+    } else {
+        Box::new(BufWriter::new(file))
+    };
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Replace `path` with a gzipped `path.gz`
+fn compress_file(path: &Path) -> Result<()> {
+    let mut input = Vec::new();
+    File::open(path)?.read_to_end(&mut input)?;
+
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let mut encoder = GzEncoder::new(File::create(&gz_name)?, Compression::default());
+    encoder.write_all(&input)?;
+    encoder.finish()?;
+
+    if let Err(e) = fs::remove_file(path) {
+        warn!(
+            "Failed to remove {} after compressing: {}",
+            path.display(),
+            e
+        );
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TradeStatistics {
     pub total_trades: usize,
@@ -126,9 +501,6 @@ pub struct TradeStatistics {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::OrderSide;
-    use chrono::Utc;
-    use rust_decimal_macros::dec;
     use std::fs;
 
     #[test]
@@ -138,19 +510,7 @@ mod tests {
 
         let logger = TradeLogger::new(log_path.to_string());
 
-        let trade = Trade {
-            id: "test1".to_string(),
-            market_id: "market1".to_string(),
-            trader: "0x0000000000000000000000000000000000000000"
-                .parse()
-                .unwrap(),
-            side: OrderSide::Buy,
-            price: dec!(0.5),
-            size: dec!(100),
-            size_usdc: dec!(50),
-            timestamp: Utc::now(),
-            trader_win_rate: None,
-        };
+        let trade = Trade::sample().with_id("test1").with_market("market1");
 
         logger.log_detected_trade(&trade).unwrap();
 
@@ -161,4 +521,98 @@ mod tests {
         // Clean up
         let _ = fs::remove_file(log_path);
     }
+
+    #[test]
+    fn test_size_rotation_with_compression() {
+        let dir = "/tmp/test_trade_log_rotation";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        let log_path = format!("{}/trades.jsonl", dir);
+
+        let logger = TradeLogger::new(log_path.clone()).with_rotation(RotationPolicy {
+            max_size_bytes: Some(400),
+            daily: false,
+            compress: true,
+        });
+
+        let trade = Trade::sample().with_id("rotate").with_market("market1");
+        for _ in 0..5 {
+            logger.log_detected_trade(&trade).unwrap();
+        }
+
+        let rotated = logger.rotated_files().unwrap();
+        assert!(!rotated.is_empty());
+        assert!(rotated
+            .iter()
+            .all(|p| p.to_string_lossy().ends_with(".jsonl.gz")));
+        assert!(fs::metadata(&log_path).unwrap().len() <= 400);
+
+        // Rotated history is still readable
+        assert_eq!(logger.read_logs().unwrap().len(), 5);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_strict_mode_and_migration() {
+        let log_path = "/tmp/test_trade_log_schema.jsonl";
+        let _ = fs::remove_file(log_path);
+
+        let legacy = r#"{"timestamp":"2024-01-15T10:30:00+00:00","trade":{"id":"old","market_id":"m1","trader":"0x0000000000000000000000000000000000000000","side":"BUY","price":"0.55","size":"10","size_usdc":"5.5","timestamp":"2024-01-15T10:29:58Z"},"executed":null,"success":false,"error":null}"#;
+        fs::write(log_path, format!("{}\n{{corrupt\n", legacy)).unwrap();
+
+        // Lenient reads skip the corrupt line; strict reads report where it is
+        let logger = TradeLogger::new(log_path.to_string());
+        assert_eq!(logger.read_logs().unwrap().len(), 1);
+        let strict = TradeLogger::new(log_path.to_string()).with_strict(true);
+        let err = strict.read_logs().unwrap_err().to_string();
+        assert!(err.contains(&format!("{}:2", log_path)));
+
+        // Migration leaves a file with corrupt lines untouched and reports it
+        let summary = logger.migrate().unwrap();
+        assert_eq!(summary.files_rewritten, 0);
+        assert_eq!(summary.corrupt_files.len(), 1);
+        assert!(summary.corrupt_files[0].starts_with(&format!("{}:2", log_path)));
+
+        fs::write(log_path, format!("{}\n", legacy)).unwrap();
+        let summary = logger.migrate().unwrap();
+        assert_eq!(summary.entries_upgraded, 1);
+        let rewritten = fs::read_to_string(log_path).unwrap();
+        assert!(rewritten.contains(&format!("\"schema_version\":{}", TRADE_LOG_SCHEMA_VERSION)));
+        assert_eq!(logger.migrate().unwrap(), MigrationSummary::default());
+
+        let _ = fs::remove_file(log_path);
+    }
+
+    #[test]
+    fn test_migration_skips_a_corrupt_rotated_file() {
+        let dir = std::env::temp_dir().join(format!("trade-log-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let log_path = dir.join("trades.jsonl");
+
+        let legacy = r#"{"timestamp":"2024-01-15T10:30:00+00:00","trade":{"id":"old","market_id":"m1","trader":"0x0000000000000000000000000000000000000000","side":"BUY","price":"0.55","size":"10","size_usdc":"5.5","timestamp":"2024-01-15T10:29:58Z"},"executed":null,"success":false,"error":null}"#;
+        let corrupt_path = dir.join("trades.20240102-000000.jsonl");
+        let corrupt = format!("{}\n{{corrupt\n", legacy);
+        fs::write(
+            dir.join("trades.20240101-000000.jsonl"),
+            format!("{}\n", legacy),
+        )
+        .unwrap();
+        fs::write(&corrupt_path, &corrupt).unwrap();
+        fs::write(&log_path, format!("{}\n", legacy)).unwrap();
+
+        // The files on either side of the corrupt one are still migrated
+        let logger = TradeLogger::new(log_path.to_string_lossy().into_owned());
+        let summary = logger.migrate().unwrap();
+        assert_eq!(summary.files_rewritten, 2);
+        assert_eq!(summary.entries_upgraded, 2);
+        assert_eq!(summary.corrupt_files.len(), 1);
+        assert!(summary.corrupt_files[0].starts_with(&format!("{}:2", corrupt_path.display())));
+        assert_eq!(fs::read_to_string(&corrupt_path).unwrap(), corrupt);
+        assert!(fs::read_to_string(&log_path)
+            .unwrap()
+            .contains("\"schema_version\""));
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }